@@ -0,0 +1,194 @@
+// ── Scrollback archive of killed sessions ──
+//
+// With `archive.enabled`, `cove kill`, `all-kill` and the sidebar's kill save
+// the Claude pane's whole scrollback to ~/.cove/captures/ before the window
+// goes, gzipped (plain text if `gzip` isn't installed), under a header naming
+// the session, its directory and Claude session id. Unlike the trash, which
+// keeps only what `cove undo` needs for 15 minutes, captures are for reading
+// later: `zless ~/.cove/captures/2026-10-14-153012-api.txt.gz`. Each save
+// prunes the directory to the newest `archive.keep` files and drops ones
+// older than `archive.max_days`.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::commands::timesheet;
+use crate::config::ArchiveConfig;
+use crate::{names, paths};
+
+// ── Constants ──
+
+const DAY: u64 = 86_400;
+
+// ── Helpers ──
+
+/// "2026-10-14-153012-<name>" for a capture taken at `ts`, `offset` seconds
+/// east of UTC; a tag's `/` would make a subdirectory.
+fn file_stem(name: &str, ts: u64, offset: i64) -> String {
+    let local = ts as i64 + offset;
+    let secs = local.rem_euclid(DAY as i64);
+    format!(
+        "{}-{:02}{:02}{:02}-{}",
+        timesheet::format_day(local.div_euclid(DAY as i64)),
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        names::file_stem(name)
+    )
+}
+
+/// `text` run through `gzip`, or None if it can't be.
+fn gzip(text: &str) -> Option<Vec<u8>> {
+    let mut child = Command::new("gzip")
+        .arg("-c")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdin = child.stdin.take()?;
+    let input = text.to_string();
+    // Written from its own thread so a full stdout pipe can't stall both ends
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output().ok()?;
+    let written = writer.join().ok()?.is_ok();
+    (written && output.status.success()).then_some(output.stdout)
+}
+
+/// Write the capture of session `name` into `dir`, returning its path.
+fn save_in(
+    dir: &Path,
+    name: &str,
+    header: &str,
+    capture: &str,
+    ts: u64,
+    offset: i64,
+) -> Result<PathBuf, String> {
+    let stem = file_stem(name, ts, offset);
+    let text = format!("{header}\n{capture}");
+    let (path, bytes) = match gzip(&text) {
+        Some(bytes) => (dir.join(format!("{stem}.txt.gz")), bytes),
+        None => {
+            log::warn!("gzip failed; archiving {name} uncompressed");
+            (dir.join(format!("{stem}.txt")), text.into_bytes())
+        }
+    };
+    fs::write(&path, bytes).map_err(|e| format!("write {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+/// Drop captures in `dir` past the newest `config.keep` or older than
+/// `config.max_days` (0 turns either limit off).
+fn prune_in(dir: &Path, config: &ArchiveConfig, now: SystemTime) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.0));
+    let max_age = Duration::from_secs(config.max_days * DAY);
+    for (i, (modified, path)) in files.iter().enumerate() {
+        let too_many = config.keep > 0 && i >= config.keep;
+        let too_old =
+            config.max_days > 0 && now.duration_since(*modified).is_ok_and(|age| age > max_age);
+        if too_many || too_old {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+// ── Public API ──
+
+/// Archive the scrollback of session `name`, about to be killed, if the
+/// config asks for it.
+pub fn save(
+    config: &ArchiveConfig,
+    name: &str,
+    dir: &str,
+    claude_session: Option<&str>,
+    capture: &str,
+) -> Result<(), String> {
+    if !config.enabled {
+        return Ok(());
+    }
+    let captures = paths::ensure_cove_dir("captures")?;
+    let ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let header = format!(
+        "# cove capture: {name}\n# dir: {dir}\n# claude session: {}\n",
+        claude_session.unwrap_or("-")
+    );
+    save_in(
+        &captures,
+        name,
+        &header,
+        capture,
+        ts,
+        timesheet::utc_offset(),
+    )?;
+    prune_in(&captures, config, SystemTime::now());
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_names_file_by_local_time_and_compresses() {
+        // 2026-10-14 13:30:12 UTC, two hours east
+        assert_eq!(
+            file_stem("web/api", 1_791_984_612, 7200),
+            "2026-10-14-153012-web%2Fapi"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = save_in(dir.path(), "api", "# cove capture: api\n", "❯ done\n", 0, 0).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        if path.extension().is_some_and(|e| e == "gz") {
+            // gzip's magic number
+            assert_eq!(&bytes[..2], &[0x1f, 0x8b]);
+        } else {
+            assert_eq!(bytes, "# cove capture: api\n\n❯ done\n".as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_within_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        for (name, days_old) in [("a", 0), ("b", 1), ("c", 2), ("old", 40)] {
+            let path = dir.path().join(format!("{name}.txt"));
+            fs::write(&path, name).unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(days_old * DAY))
+                .unwrap();
+        }
+        let config = ArchiveConfig {
+            enabled: true,
+            keep: 10,
+            max_days: 30,
+        };
+        prune_in(dir.path(), &config, now);
+        let mut left: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["a.txt", "b.txt", "c.txt"]);
+
+        let config = ArchiveConfig { keep: 2, ..config };
+        prune_in(dir.path(), &config, now);
+        assert!(!dir.path().join("c.txt").exists());
+    }
+}
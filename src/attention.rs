@@ -0,0 +1,279 @@
+// ── Attention ──
+//
+// Time you spend supervising sessions, kept apart from the time Claude
+// spends working. `c` in the sidebar clocks you in to the selected session
+// and, pressed again, out; clocking in to another session clocks you out of
+// the first, since you watch one at a time. Every change is a line in
+// ~/.cove/attention.jsonl, appended like stats.jsonl so concurrent sidebars
+// can't clobber each other:
+//
+//   {"name":"api","dir":"/code/api","clock":"in","ts":1760450000}
+//
+// Killing a session clocks you out of it and renaming carries the clock-in
+// over. A clock-in nobody ended counts for MAX_INTERVAL at most. `cove
+// timesheet` and `cove stats` report attention next to the agents' time.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+use crate::sidebar::state;
+
+// ── Types ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Clock {
+    In,
+    Out,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub dir: String,
+    pub clock: Clock,
+    pub ts: u64,
+}
+
+/// A stretch of attention on one session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval {
+    pub name: String,
+    pub dir: String,
+    pub start: u64,
+    pub end: u64,
+}
+
+// ── Constants ──
+
+const LOG_FILE: &str = "attention.jsonl";
+
+/// Longest a single clock-in counts for, in case you never clocked out.
+pub const MAX_INTERVAL: u64 = 4 * 3600;
+
+// ── Helpers ──
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn append_in(path: &Path, name: &str, dir: &str, clock: Clock, ts: u64) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create {}: {e}", parent.display()))?;
+    }
+    let entry = Entry {
+        name: name.to_string(),
+        dir: dir.to_string(),
+        clock,
+        ts,
+    };
+    let line = serde_json::to_string(&entry).map_err(|e| format!("encode attention: {e}"))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("open {}: {e}", path.display()))?;
+    writeln!(file, "{line}").map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+/// The stretch clock-in `open` started, ended at `at`.
+fn closed(open: &Entry, at: u64) -> Option<Interval> {
+    let end = at.min(open.ts + MAX_INTERVAL);
+    (end > open.ts).then(|| Interval {
+        name: open.name.clone(),
+        dir: open.dir.clone(),
+        start: open.ts,
+        end,
+    })
+}
+
+/// The session clocked in to per the log at `path`: its latest line, if
+/// that's a clock-in.
+fn clocked_in_at(path: &Path) -> Option<Entry> {
+    let entry: Entry = serde_json::from_str(&state::read_last_line(path)?).ok()?;
+    (entry.clock == Clock::In).then_some(entry)
+}
+
+/// Clock in to `name` (in `dir`), or out if already clocked in to it.
+/// Returns true when now clocked in.
+fn toggle_in(path: &Path, name: &str, dir: &str, ts: u64) -> Result<bool, String> {
+    match clocked_in_at(path) {
+        Some(current) if current.name == name => {
+            append_in(path, name, &current.dir, Clock::Out, ts)?;
+            Ok(false)
+        }
+        _ => {
+            append_in(path, name, dir, Clock::In, ts)?;
+            Ok(true)
+        }
+    }
+}
+
+/// Clock out of `name` if clocked in to it.
+fn clock_out_in(path: &Path, name: &str, ts: u64) -> Result<(), String> {
+    match clocked_in_at(path) {
+        Some(current) if current.name == name => {
+            append_in(path, name, &current.dir, Clock::Out, ts)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Move a clock-in on `old` over to `new`.
+fn rename_in(path: &Path, old: &str, new: &str, ts: u64) -> Result<(), String> {
+    match clocked_in_at(path) {
+        Some(current) if current.name == old => {
+            append_in(path, old, &current.dir, Clock::Out, ts)?;
+            append_in(path, new, &current.dir, Clock::In, ts)
+        }
+        _ => Ok(()),
+    }
+}
+
+// ── Public API ──
+
+/// Where attention is kept.
+pub fn log() -> Option<PathBuf> {
+    paths::cove_path(LOG_FILE)
+}
+
+/// Every line of the log at `path`, oldest first. Unreadable lines are skipped.
+pub fn read_in(path: &Path) -> Vec<Entry> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Stretches of attention in `entries` (oldest first), each ending at the
+/// next clock change, `now` for one still open, and MAX_INTERVAL at most.
+pub fn intervals(entries: &[Entry], now: u64) -> Vec<Interval> {
+    let mut out = Vec::new();
+    let mut open: Option<&Entry> = None;
+    for entry in entries {
+        if let Some(current) = open.take() {
+            // Clocking out of another session leaves this one running
+            if entry.clock == Clock::Out && entry.name != current.name {
+                open = Some(current);
+                continue;
+            }
+            out.extend(closed(current, entry.ts));
+        }
+        if entry.clock == Clock::In {
+            open = Some(entry);
+        }
+    }
+    out.extend(open.and_then(|current| closed(current, now)));
+    out
+}
+
+/// Every stretch of attention so far.
+pub fn all() -> Vec<Interval> {
+    let entries = log().map(|path| read_in(&path)).unwrap_or_default();
+    intervals(&entries, now())
+}
+
+/// Name of the session you're clocked in to.
+pub fn clocked_in() -> Option<String> {
+    clocked_in_at(&log()?).map(|entry| entry.name)
+}
+
+/// Clock in to session `name` (in `dir`), or out if already in. Returns true
+/// when now clocked in.
+pub fn toggle(name: &str, dir: &str) -> Result<bool, String> {
+    let path = log().ok_or("no state dir to record attention in")?;
+    toggle_in(&path, name, dir, now())
+}
+
+/// Clock out of killed session `name`.
+pub fn clock_out(name: &str) {
+    if let Some(path) = log()
+        && let Err(e) = clock_out_in(&path, name, now())
+    {
+        log::warn!("attention: {e}");
+    }
+}
+
+/// Follow session `old` being renamed to `new`.
+pub fn rename(old: &str, new: &str) -> Result<(), String> {
+    match log() {
+        Some(path) => rename_in(&path, old, new, now()),
+        None => Ok(()),
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(name: &str, start: u64, end: u64) -> Interval {
+        Interval {
+            name: name.to_string(),
+            dir: format!("/code/{name}"),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn test_toggle_switches_between_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOG_FILE);
+        assert!(toggle_in(&path, "api", "/code/api", 100).unwrap());
+        // Clocking in elsewhere moves your attention there
+        assert!(toggle_in(&path, "web", "/code/web", 400).unwrap());
+        assert_eq!(clocked_in_at(&path).unwrap().name, "web");
+        assert!(!toggle_in(&path, "web", "/code/web", 500).unwrap());
+        assert_eq!(clocked_in_at(&path), None);
+
+        let entries = read_in(&path);
+        assert_eq!(
+            intervals(&entries, 1000),
+            [interval("api", 100, 400), interval("web", 400, 500)]
+        );
+    }
+
+    #[test]
+    fn test_kill_and_rename_follow_the_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOG_FILE);
+        toggle_in(&path, "api", "/code/api", 100).unwrap();
+        // Another session going away doesn't touch it
+        clock_out_in(&path, "web", 150).unwrap();
+        rename_in(&path, "api", "backend", 200).unwrap();
+        clock_out_in(&path, "backend", 300).unwrap();
+
+        let found = intervals(&read_in(&path), 1000);
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].start, found[0].end), (100, 200));
+        assert_eq!(
+            (found[1].name.as_str(), found[1].dir.as_str(), found[1].end),
+            ("backend", "/code/api", 300)
+        );
+    }
+
+    #[test]
+    fn test_forgotten_clock_in_is_capped() {
+        let entries = [Entry {
+            name: "api".to_string(),
+            dir: "/code/api".to_string(),
+            clock: Clock::In,
+            ts: 100,
+        }];
+        assert_eq!(intervals(&entries, 500), [interval("api", 100, 500)]);
+        assert_eq!(
+            intervals(&entries, 100 + 2 * MAX_INTERVAL),
+            [interval("api", 100, 100 + MAX_INTERVAL)]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+// ── Automatic session tags ──
+//
+// `tags` in the config maps directory globs to tags, e.g. everything under
+// `~/work/**` is `work`. A session started with `cove <name>` in a matching
+// directory gets the first matching rule's tag, as the `work/` in
+// `work/<name>` (see names.rs), unless its name already has one. Since the
+// tag is part of the name, everything that shows names picks it up: the
+// sidebar's `T` filter, `cove timesheet`'s tag column and Toggl tags.
+//
+// Patterns are matched per path component: `*` and `?` within a component,
+// `**` for any number of them (including none, so `~/work/**` also matches
+// `~/work` itself).
+
+use crate::config::TagRule;
+use crate::{names, paths};
+
+// ── Helpers ──
+
+/// `*` and `?` wildcards within one path component.
+fn component_matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|i| component_matches(rest, &text[i..])),
+        Some(('?', rest)) => !text.is_empty() && component_matches(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && component_matches(rest, &text[1..]),
+    }
+}
+
+fn path_matches(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|i| path_matches(rest, &path[i..])),
+        Some((component, rest)) => path.split_first().is_some_and(|(first, tail)| {
+            let pattern: Vec<char> = component.chars().collect();
+            let text: Vec<char> = first.chars().collect();
+            component_matches(&pattern, &text) && path_matches(rest, tail)
+        }),
+    }
+}
+
+fn components(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect()
+}
+
+// ── Public API ──
+
+/// Whether directory `dir` matches glob `pattern` (also used by the
+/// config's `profiles`).
+pub fn matches(pattern: &str, dir: &str) -> bool {
+    let pattern = paths::expand_tilde(pattern).to_string_lossy().into_owned();
+    path_matches(&components(&pattern), &components(dir))
+}
+
+/// Tag of the first rule whose pattern matches directory `dir`.
+pub fn tag_for<'a>(rules: &'a [TagRule], dir: &str) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|rule| matches(&rule.path, dir))
+        .map(|rule| rule.tag.as_str())
+}
+
+/// `name` tagged by the rule matching `dir`, if any, and if it has no tag.
+pub fn apply(rules: &[TagRule], name: &str, dir: &str) -> String {
+    match tag_for(rules, dir) {
+        Some(tag) => names::with_tag(name, tag),
+        None => name.to_string(),
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(path: &str, tag: &str) -> TagRule {
+        TagRule {
+            path: path.to_string(),
+            tag: tag.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_tags_the_name() {
+        let rules = [
+            rule("/code/work/*-infra/**", "infra"),
+            rule("/code/work/**", "work"),
+            rule("/code/oss/proj?", "oss"),
+        ];
+        assert_eq!(tag_for(&rules, "/code/work/api/src"), Some("work"));
+        assert_eq!(tag_for(&rules, "/code/work"), Some("work"));
+        assert_eq!(tag_for(&rules, "/code/work/aws-infra"), Some("infra"));
+        assert_eq!(tag_for(&rules, "/code/oss/proj1"), Some("oss"));
+        assert_eq!(tag_for(&rules, "/code/oss/proj1/src"), None);
+        assert_eq!(tag_for(&rules, "/code/workshop"), None);
+
+        assert_eq!(apply(&rules, "fix-auth", "/code/work/api"), "work/fix-auth");
+        // A tag given by hand wins
+        assert_eq!(
+            apply(&rules, "web/fix-auth", "/code/work/api"),
+            "web/fix-auth"
+        );
+        assert_eq!(apply(&rules, "fix-auth", "/tmp"), "fix-auth");
+    }
+}
@@ -0,0 +1,212 @@
+// ── Files touched by Claude ──
+//
+// The `cove hook file-edit` handler (PostToolUse on Edit/Write/MultiEdit/NotebookEdit)
+// appends one line per edit to ~/.cove/changes/{session_id}.jsonl. `cove changes`
+// and the sidebar's changes view read these back per pane and diff each file
+// against the git index.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{git, paths};
+
+// ── Types ──
+
+#[derive(Serialize, Deserialize)]
+struct ChangeEntry {
+    file: String,
+    tool: String,
+    #[serde(default)]
+    pane_id: String,
+    ts: u64,
+}
+
+/// One line of a rendered diff, classified for coloring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Header,
+    Hunk,
+    Added,
+    Removed,
+    Context,
+}
+
+// ── Helpers ──
+
+fn changes_dir() -> Option<PathBuf> {
+    paths::cove_path("changes")
+}
+
+fn read_entries(dir: &Path) -> Vec<ChangeEntry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        out.extend(
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<ChangeEntry>(line).ok()),
+        );
+    }
+    out
+}
+
+/// Unique files edited from `pane_id`, most recently edited first.
+fn files_for_pane_in(dir: &Path, pane_id: &str) -> Vec<String> {
+    let mut entries: Vec<ChangeEntry> = read_entries(dir)
+        .into_iter()
+        .filter(|e| e.pane_id == pane_id)
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.ts));
+
+    let mut files: Vec<String> = Vec::new();
+    for e in entries {
+        if !files.contains(&e.file) {
+            files.push(e.file);
+        }
+    }
+    files
+}
+
+// ── Public API ──
+
+/// Record that `tool` modified `file` in the given Claude session.
+pub fn record(session_id: &str, pane_id: &str, tool: &str, file: &str) -> Result<(), String> {
+    let dir = paths::ensure_cove_dir("changes")?;
+
+    let path = dir.join(format!("{session_id}.jsonl"));
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("open changes file: {e}"))?;
+
+    let entry = ChangeEntry {
+        file: file.to_string(),
+        tool: tool.to_string(),
+        pane_id: pane_id.to_string(),
+        ts: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let line = serde_json::to_string(&entry).map_err(|e| format!("serialize change: {e}"))?;
+    writeln!(f, "{line}").map_err(|e| format!("write change: {e}"))
+}
+
+/// Files edited by the Claude session running in `pane_id`, most recent first.
+pub fn files_for_pane(pane_id: &str) -> Vec<String> {
+    changes_dir()
+        .map(|dir| files_for_pane_in(&dir, pane_id))
+        .unwrap_or_default()
+}
+
+/// Remove change logs recorded from `pane_id` (called when a pane ID is recycled).
+pub fn purge_for_pane(pane_id: &str) {
+    let Some(dir) = changes_dir() else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let from_pane = content
+            .lines()
+            .filter_map(|line| serde_json::from_str::<ChangeEntry>(line).ok())
+            .any(|e| e.pane_id == pane_id);
+        if from_pane {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// Unified diff of `file` against the git index. Untracked files are shown
+/// as entirely added. Returns an empty vec when the file is unchanged.
+pub fn diff(file: &str) -> Result<Vec<String>, String> {
+    let path = Path::new(file);
+    let dir = path.parent().unwrap_or(Path::new("/"));
+
+    let tracked = git::output(dir, &["ls-files", "--error-unmatch", file], &[0]).is_some();
+    let out = if tracked {
+        git::output(dir, &["diff", "--no-color", "--", file], &[0])
+    } else if path.exists() {
+        // `--no-index` exits 1 when the files differ, which they always do here
+        git::output(
+            dir,
+            &["diff", "--no-color", "--no-index", "--", "/dev/null", file],
+            &[0, 1],
+        )
+    } else {
+        return Ok(vec![format!("{file}: deleted")]);
+    };
+
+    let out = out.ok_or(format!("git diff failed for {file}"))?;
+    Ok(out.lines().map(|l| l.to_string()).collect())
+}
+
+pub fn classify(line: &str) -> DiffLineKind {
+    if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff ") {
+        DiffLineKind::Header
+    } else if line.starts_with("@@") {
+        DiffLineKind::Hunk
+    } else if line.starts_with('+') {
+        DiffLineKind::Added
+    } else if line.starts_with('-') {
+        DiffLineKind::Removed
+    } else {
+        DiffLineKind::Context
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_for_pane_dedup_and_order() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("s1.jsonl"),
+            r#"{"file":"/r/a.rs","tool":"Edit","pane_id":"%1","ts":100}
+{"file":"/r/b.rs","tool":"Write","pane_id":"%1","ts":200}
+{"file":"/r/a.rs","tool":"Edit","pane_id":"%1","ts":300}
+{"file":"/r/c.rs","tool":"Edit","pane_id":"%2","ts":400}
+"#,
+        )
+        .unwrap();
+
+        let files = files_for_pane_in(dir.path(), "%1");
+        assert_eq!(files, vec!["/r/a.rs", "/r/b.rs"]);
+    }
+
+    #[test]
+    fn test_files_for_pane_missing_dir() {
+        assert!(files_for_pane_in(Path::new("/nonexistent/changes"), "%1").is_empty());
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("+++ b/src/a.rs"), DiffLineKind::Header);
+        assert_eq!(classify("@@ -1,2 +1,3 @@"), DiffLineKind::Hunk);
+        assert_eq!(classify("+new"), DiffLineKind::Added);
+        assert_eq!(classify("-old"), DiffLineKind::Removed);
+        assert_eq!(classify(" same"), DiffLineKind::Context);
+    }
+}
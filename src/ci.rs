@@ -0,0 +1,148 @@
+// ── CI status of sessions' pull requests ──
+//
+// With `ci.enabled`, sessions whose branch has a pull request get a badge for
+// its checks: ✓ all passed, ✗ one failed, ● still running. The sidebar's
+// enrichment worker asks `gh pr checks` every `ci.interval_secs` and keeps
+// the answers in ~/.cove/ci.json, which every sidebar and `cove list` read;
+// a sidebar only asks GitHub again once the file is older than the interval,
+// so many open sidebars cost one round of `gh` calls.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{git, paths};
+
+// ── Types ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiStatus {
+    Passed,
+    Failed,
+    Pending,
+}
+
+/// The shared cache: when GitHub was last asked, and the answers by session.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct CiCache {
+    updated: u64,
+    sessions: HashMap<String, CiStatus>,
+}
+
+// ── Constants ──
+
+const CACHE_FILE: &str = "ci.json";
+
+// ── Helpers ──
+
+/// Overall status of `gh pr checks --json bucket` output; None when the PR
+/// has no checks that count.
+fn parse_checks(json: &str) -> Option<CiStatus> {
+    let checks: Vec<Value> = serde_json::from_str(json).ok()?;
+    let buckets: Vec<&str> = checks.iter().filter_map(|c| c["bucket"].as_str()).collect();
+    if buckets.iter().any(|b| matches!(*b, "fail" | "cancel")) {
+        Some(CiStatus::Failed)
+    } else if buckets.contains(&"pending") {
+        Some(CiStatus::Pending)
+    } else if buckets.contains(&"pass") {
+        Some(CiStatus::Passed)
+    } else {
+        None
+    }
+}
+
+/// Status of the checks on the pull request for the branch checked out in
+/// `dir`. gh exits non-zero for failing and pending checks too, so only its
+/// output is read.
+fn pr_checks(dir: &Path) -> Option<CiStatus> {
+    let branch = git::branch(dir)?;
+    let output = Command::new("gh")
+        .args(["pr", "checks", &branch, "--json", "bucket"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    parse_checks(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn load_cache() -> CiCache {
+    paths::cove_path(CACHE_FILE)
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Write through a temp file and a rename, so a sidebar reading the cache
+/// never sees it half written.
+fn save_cache(cache: &CiCache) {
+    let (Ok(dir), Ok(content)) = (paths::ensure_cove_dir(""), serde_json::to_string(cache)) else {
+        return;
+    };
+    let path = dir.join(CACHE_FILE);
+    let tmp = path.with_extension(format!("json.{}", std::process::id()));
+    if fs::write(&tmp, content).is_err() || fs::rename(&tmp, &path).is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+}
+
+// ── Public API ──
+
+/// CI status per session name, as last seen by any sidebar.
+pub fn cached() -> HashMap<String, CiStatus> {
+    load_cache().sessions
+}
+
+/// CI status of the `(name, dir)` sessions, asking GitHub only when the
+/// shared cache is older than `interval_secs`. Shells out to gh per session,
+/// so keep it off the UI thread.
+pub fn statuses(sessions: &[(String, String)], interval_secs: u64) -> HashMap<String, CiStatus> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let cache = load_cache();
+    if now.saturating_sub(cache.updated) < interval_secs {
+        return cache.sessions;
+    }
+    let sessions = sessions
+        .iter()
+        .filter_map(|(name, dir)| Some((name.clone(), pr_checks(Path::new(dir))?)))
+        .collect();
+    let cache = CiCache {
+        updated: now,
+        sessions,
+    };
+    save_cache(&cache);
+    cache.sessions
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checks() {
+        let checks = |buckets: &[&str]| {
+            let list: Vec<String> = buckets
+                .iter()
+                .map(|b| format!(r#"{{"bucket":"{b}"}}"#))
+                .collect();
+            parse_checks(&format!("[{}]", list.join(",")))
+        };
+        assert_eq!(checks(&["pass", "skipping"]), Some(CiStatus::Passed));
+        assert_eq!(checks(&["pass", "pending"]), Some(CiStatus::Pending));
+        assert_eq!(checks(&["pending", "fail"]), Some(CiStatus::Failed));
+        assert_eq!(checks(&["cancel"]), Some(CiStatus::Failed));
+        assert_eq!(checks(&["skipping"]), None);
+        assert_eq!(checks(&[]), None);
+        // "no pull requests found" goes to stderr, leaving stdout empty
+        assert_eq!(parse_checks(""), None);
+    }
+}
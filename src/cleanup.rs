@@ -0,0 +1,81 @@
+// ── Merged-branch cleanup ──
+//
+// With `cleanup.check_merged`, the sidebar checks every `interval_secs`, on
+// its enrichment worker, whether each session's branch has landed on the base
+// branch (locally via `git merge-base`, and with `cleanup.gh` also via
+// `gh pr view`, which sees squash merges). Merged sessions are flagged in the
+// sidebar; tearing one down kills its window and removes its worktree, unless
+// the worktree still has uncommitted changes.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+use crate::commands::kill;
+use crate::config::CleanupConfig;
+use crate::tmux::Tmux;
+use crate::{git, meta};
+
+// ── Helpers ──
+
+/// State of the pull request for `branch`, e.g. "MERGED", per the GitHub CLI.
+fn pr_state(dir: &Path, branch: &str) -> Option<String> {
+    let output = Command::new("gh")
+        .args(["pr", "view", branch, "--json", "state", "--jq", ".state"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn is_merged(dir: &Path, config: &CleanupConfig) -> bool {
+    let Some(branch) = git::branch(dir) else {
+        return false;
+    };
+    let base = config.base.clone().or_else(|| git::default_base(dir));
+    if base.is_some_and(|base| git::is_merged(dir, &branch, &base)) {
+        return true;
+    }
+    config.gh && pr_state(dir, &branch).as_deref() == Some("MERGED")
+}
+
+// ── Public API ──
+
+/// Names of the `(name, dir)` sessions whose branch has been merged. Shells
+/// out to git (and gh) per session, so keep it off the UI thread.
+pub fn merged_sessions(sessions: &[(String, String)], config: &CleanupConfig) -> HashSet<String> {
+    sessions
+        .iter()
+        .filter(|(_, dir)| is_merged(Path::new(dir), config))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Kill a session and remove the linked worktree it runs in. Sessions in a
+/// repository's main checkout only lose their window.
+pub fn teardown(name: &str, dir: &str) -> Result<(), String> {
+    let path = Path::new(dir);
+    let main = git::main_worktree(path);
+    let worktree = git::toplevel(path)
+        .ok()
+        .filter(|top| main.as_ref().is_some_and(|main| main != top));
+    if let Some(top) = &worktree
+        && git::is_dirty(Path::new(top))
+    {
+        return Err(format!("{name}: worktree has uncommitted changes"));
+    }
+
+    // Nothing to undo into once the worktree is gone, so skip the trash
+    kill::remove_window(&Tmux, &kill::Bookkeeping::home(None), name)?;
+    // Killing removed it already if it's one of cove's own (see worktrees.rs)
+    if let (Some(top), Some(main)) = (worktree, main)
+        && Path::new(&top).exists()
+    {
+        git::run(Path::new(&main), &["worktree", "remove", &top])?;
+        meta::update(name, |m| m.worktree = None)?;
+    }
+    Ok(())
+}
@@ -1,4 +1,7 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum, ValueHint};
+use serde::{Deserialize, Serialize};
 
 #[derive(Parser)]
 #[command(name = "cove", about = "Claude Code session manager", version)]
@@ -8,8 +11,50 @@ pub struct Cli {
     pub name: Option<String>,
 
     /// Working directory
+    #[arg(value_hint = ValueHint::DirPath)]
     pub dir: Option<String>,
 
+    /// Open the session as a kitty/wezterm tab instead of a tmux window
+    #[arg(long, value_enum)]
+    pub backend: Option<Backend>,
+
+    /// Start in the git repository root containing the working directory
+    #[arg(long, alias = "dir-from-git-root")]
+    pub root: bool,
+
+    /// Start even if another session already runs in the directory
+    #[arg(long)]
+    pub allow_duplicate_dir: bool,
+
+    /// Run Claude in a container: from an image, or the project's dev container
+    #[arg(long, value_name = "IMAGE|devcontainer", conflicts_with = "backend")]
+    pub container: Option<String>,
+
+    /// Start from a task description: a markdown URL (gists work) or file
+    #[arg(long, value_name = "URL", conflicts_with = "backend")]
+    pub seed: Option<String>,
+
+    /// Send the seed to Claude as its first prompt
+    #[arg(long, requires = "seed")]
+    pub seed_prompt: bool,
+
+    /// Run the session in its own git worktree with this branch checked out
+    /// (created from HEAD if new); `cove kill` removes the worktree again
+    #[arg(long, value_name = "BRANCH", conflicts_with = "backend")]
+    pub worktree: Option<String>,
+
+    /// Safe mode: never type into sessions (send, interrupt, queued prompts)
+    #[arg(long, global = true)]
+    pub no_send: bool,
+
+    /// Print only errors and requested output, and never prompt (for scripts)
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Print long output (changes, cmds, prompts) directly instead of through $PAGER
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
@@ -21,22 +66,431 @@ pub enum Command {
     List,
     /// Kill a single session tab
     Kill {
-        /// Session name to kill
+        /// Session to kill: name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// Stop only Claude and keep the window ("parked"); `cove restart`
+        /// or R in the sidebar starts it again
+        #[arg(long)]
+        keep_pane: bool,
+    },
+    /// Restart Claude in a session, resuming the same conversation
+    Restart {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+    },
+    /// Stop Claude's current turn in a session (Escape), optionally sending a new prompt
+    Interrupt {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// Prompt to send once Claude has stopped
+        #[arg(long, value_name = "PROMPT")]
+        then: Option<String>,
+    },
+    /// Show two sessions' Claude panes side by side in a temporary window
+    Pair {
+        /// Sessions to compare: name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION", required_unless_present = "end")]
+        a: Option<String>,
+        #[arg(value_name = "SESSION", required_unless_present = "end")]
+        b: Option<String>,
+        /// Move the paired panes back into their sessions
+        #[arg(long, conflicts_with_all = ["a", "b"])]
+        end: bool,
+    },
+    /// Arrange a session's panes by preset, or fix up cove's layout
+    #[command(args_conflicts_with_subcommands = true)]
+    Layout {
+        #[command(subcommand)]
+        action: Option<LayoutAction>,
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION", requires = "preset")]
+        name: Option<String>,
+        /// Arrangement to give the session, kept when its window is restored or repaired
+        #[arg(long, value_enum, requires = "name")]
+        preset: Option<Preset>,
+    },
+    /// Remove cove's tmux options and hooks from a window, to keep it as a plain one
+    Clean {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// Unset remain-on-exit, cove's hooks and its @cove-* options
+        #[arg(long)]
+        options: bool,
+    },
+    /// Let config rules answer a session's trivial questions, or show what they answered
+    Delegate {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// Turn delegate mode off again
+        #[arg(long, conflicts_with = "log")]
+        off: bool,
+        /// Show the questions answered for the session
+        #[arg(long)]
+        log: bool,
+    },
+    /// Let a colleague watch a session remotely through tmate or upterm, or list shares
+    Share {
+        /// Session name, window index or pane ID (%7); omit to list shared sessions
+        #[arg(value_name = "SESSION")]
+        name: Option<String>,
+        /// Hand out a read-write link so the guest can type into the session
+        #[arg(long)]
+        write: bool,
+        /// Sharing service to use
+        #[arg(long = "with", value_enum, default_value = "tmate")]
+        backend: ShareBackend,
+    },
+    /// Stop sharing a session started with `cove share`
+    Unshare {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+    },
+    /// Record a milestone in a session's history, or list its milestones
+    Annotate {
+        /// Session name, window index or pane ID (%7)
+        #[arg(
+            value_name = "SESSION",
+            required_unless_present_any = ["git_commit", "install_git_hook"]
+        )]
+        name: Option<String>,
+        /// What was reached, e.g. "migrated db schema" (omit to list milestones)
+        note: Option<String>,
+        /// Record the latest commit in the session this pane belongs to (run by the git hook)
+        #[arg(long, conflicts_with_all = ["name", "install_git_hook"])]
+        git_commit: bool,
+        /// Add a post-commit hook to this repository that records every commit
+        #[arg(long, conflicts_with = "name")]
+        install_git_hook: bool,
+    },
+    /// Show the working-session limit and prompts waiting for a slot
+    Quota,
+    /// Full-screen CPU, memory and token use of every session
+    Top,
+    /// Working and waiting time per session and repository, as CSV or a Toggl import
+    Timesheet {
+        /// Cover this week (since Monday) instead of today
+        #[arg(long)]
+        week: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value = "csv")]
+        format: SheetFormat,
+        /// Email for Toggl entries (default: git config user.email)
+        #[arg(long)]
+        email: Option<String>,
+    },
+    /// Sessions, prompts and Working hours this week and last, or since the start
+    Stats {
+        /// Lifetime totals, weekly averages and milestones
+        #[arg(long)]
+        all_time: bool,
+    },
+    /// Disk used by the worktrees sessions run in, per worktree and in total
+    Du {
+        /// Remove the worktrees of killed sessions (ones with uncommitted changes are kept)
+        #[arg(long)]
+        prune: bool,
+    },
+    /// List event files left by panes that are gone, and archive or delete them
+    OrphanScan {
+        /// Move them to the history `cove timesheet` reads
+        #[arg(long, conflicts_with = "delete")]
+        archive: bool,
+        /// Remove them
+        #[arg(long)]
+        delete: bool,
+    },
+    /// Session state counts for a shell prompt (starship, p10k, PS1)
+    PromptSegment {
+        /// Wrap colors in the shell's zero-width prompt markers
+        #[arg(long, value_enum)]
+        shell: Option<Shell>,
+    },
+    /// Session states, prompts and turn lengths in the Prometheus text format
+    Metrics {
+        /// Keep running and serve them at http://ADDR/metrics
+        #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = "127.0.0.1:9464")]
+        serve: Option<String>,
+    },
+    /// Put a session's working tree back to a snapshot from an earlier turn
+    Rollback {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// Snapshot to roll back to, as numbered by --list (1 is the newest)
+        #[arg(long, value_name = "N")]
+        to: Option<usize>,
+        /// List the session's snapshots
+        #[arg(long, conflicts_with_all = ["to", "diff", "yes", "tell"])]
+        list: bool,
+        /// Show the whole diff before confirming, not just its stat
+        #[arg(long)]
+        diff: bool,
+        /// Roll back without asking
+        #[arg(long, short)]
+        yes: bool,
+        /// Tell Claude which files were reverted
+        #[arg(long)]
+        tell: bool,
+    },
+    /// Bring back a session killed in the last 15 minutes
+    Undo {
+        /// Session to restore (default: the most recently killed)
+        name: Option<String>,
+        /// List sessions that can be restored
+        #[arg(long)]
+        list: bool,
+    },
+    /// Recreate saved sessions that have no window, after tmux or the machine went down
+    Restore {
+        /// List saved sessions and what restoring each would do
+        #[arg(long)]
+        list: bool,
+        /// Drop a saved session instead, e.g. one whose window was closed with tmux
+        #[arg(long, value_name = "SESSION", conflicts_with = "list")]
+        forget: Option<String>,
+    },
+    /// Kill all sessions (optionally filtered), after ticking which go
+    AllKill {
+        /// Sessions to keep, comma-separated
+        #[arg(long, value_name = "SESSION", value_delimiter = ',')]
+        except: Vec<String>,
+        /// Only kill sessions in this state
+        #[arg(long, value_enum)]
+        state: Option<StateFilter>,
+        /// Show which sessions would be killed without killing them
+        #[arg(long)]
+        dry_run: bool,
+        /// Kill every match without asking which to spare
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Save a snippet (e.g. a path) to a session's scratch register, or list it
+    Copy {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// Text to save; omit to list saved entries
+        text: Option<String>,
+    },
+    /// Show files Claude edited in a session, with diffs against the git index
+    Changes {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// List file paths only
+        #[arg(long)]
+        files: bool,
+    },
+    /// Show the shell commands Claude ran in a session, with exit status
+    Cmds {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+    },
+    /// Prompts sent to a session, or search every session's with --all
+    Prompts {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION", conflicts_with = "all")]
+        name: Option<String>,
+        /// Include every session, killed ones too
+        #[arg(long)]
+        all: bool,
+        /// Only prompts containing this text (case-insensitive)
+        #[arg(long, value_name = "TEXT")]
+        search: Option<String>,
+        /// Switch to the session prompt <ID> of the listing was sent in
+        #[arg(long, value_name = "ID", conflicts_with = "send")]
+        jump: Option<String>,
+        /// Send prompt <ID> of the listing again
+        #[arg(long, value_name = "ID")]
+        send: Option<String>,
+        /// Session to send it to (default: the one it was sent in)
+        #[arg(long, value_name = "SESSION", requires = "send")]
+        to: Option<String>,
+    },
+    /// Pick tmux keys for cove actions (switcher, new, kill, focus), scoped to the cove session
+    Bind {
+        /// Remove cove's bindings and restore what the keys did before
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Switch to the session in a quick-switch slot (shown in the sidebar, prefix 1-9)
+    Go {
+        /// Slot number, 1-9
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=9))]
+        slot: u8,
+    },
+    /// Zoom a session's Claude pane and hide the sidebar, or restore the layout
+    Focus {
+        /// Session name, window index or pane ID (default: the current window)
+        #[arg(value_name = "SESSION")]
+        name: Option<String>,
+    },
+    /// Send a message to a session's Claude pane, typed out or from a template
+    Send {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// Message text
+        #[arg(required_unless_present = "template", conflicts_with = "template")]
+        text: Option<String>,
+        /// Template name (~/.cove/templates/<name>.md or `templates:` in the config)
+        #[arg(long)]
+        template: Option<String>,
+        /// Template variable, repeatable
+        #[arg(long = "var", value_name = "KEY=VALUE", requires = "template")]
+        vars: Vec<String>,
+    },
+    /// Start a session for a GitHub issue: branch, worktree, metadata and prompt
+    AdoptBranch {
+        /// Issue number or URL (passed to `gh issue view`)
+        issue: String,
+        /// Directory inside the repository (default: current directory)
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        dir: Option<String>,
+        /// Prompt template (default: the `issue` template, else a built-in one)
+        #[arg(long)]
+        template: Option<String>,
+    },
+    /// Pin a session to the top of the sidebar and `cove list`, or unpin it
+    Pin {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+    },
+    /// Rename a session, keeping its pin, clips and share
+    Rename {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// New session name
+        new_name: String,
+    },
+    /// Move the windows of another tmux session into cove's (every stray `cove-*` one by default)
+    Merge {
+        /// tmux session to empty
+        from: Option<String>,
+        /// tmux session to move them into (default: cove)
+        into: Option<String>,
+    },
+    /// Check whether a name can be used for a new session, suggesting one if not
+    NameCheck {
+        /// Proposed session name
         name: String,
     },
-    /// Kill all sessions
-    AllKill,
     /// Reattach to existing session
     Resume,
+    /// Interactive prompt for list, send, kill and wait over one tmux connection
+    Console,
+    /// Print a shell completion script, or install it where the shell looks
+    Completions {
+        /// Shell to complete for (default: from $SHELL)
+        #[arg(value_enum)]
+        shell: Option<Shell>,
+        /// Write the script to the shell's completions directory
+        #[arg(long)]
+        install: bool,
+    },
     /// Interactive session navigator (launched by start)
-    Sidebar,
+    Sidebar {
+        /// Plain text output for screen readers instead of the TUI
+        #[arg(long)]
+        plain: bool,
+    },
     /// Handle Claude Code hook events (called by hooks, not directly)
     Hook {
+        /// Archive each hook's raw stdin in this directory (for debugging)
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        capture_dir: Option<PathBuf>,
+        /// Write events here instead of the state dir's events/ (set by
+        /// `cove init`, for hooks whose $HOME isn't the sidebar's)
+        #[arg(long, global = true, value_hint = ValueHint::DirPath)]
+        events_dir: Option<PathBuf>,
         #[command(subcommand)]
         event: HookEvent,
     },
     /// Install Claude Code hooks for session status detection
-    Init,
+    Init {
+        /// Install into this repository's .claude/settings.json instead of
+        /// the user settings
+        #[arg(long)]
+        project: bool,
+        /// With --project, use the personal .claude/settings.local.json
+        #[arg(long, requires = "project")]
+        local: bool,
+        /// cove binary the hooks run, instead of asking when several are installed
+        #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+        bin: Option<String>,
+    },
+    /// Check tmux, state dir, config and hook compatibility with the installed Claude
+    Doctor,
+    /// Show which cove hooks are installed; --fire-test checks each one's
+    /// events reach cove
+    VerifyHooks {
+        /// Fire every installed hook and report which events arrive
+        #[arg(long)]
+        fire_test: bool,
+        /// With --fire-test, have `claude -p` fire them instead of running
+        /// the hook commands directly
+        #[arg(long, requires = "fire_test")]
+        claude: bool,
+        /// Seconds to wait for each hook (for Claude's whole run with --claude)
+        #[arg(long, value_name = "SECS", requires = "fire_test")]
+        timeout: Option<u64>,
+    },
+    /// Create sessions declared in a manifest, skipping ones that exist
+    Up {
+        /// Manifest file (YAML)
+        #[arg(short, long, default_value = "sessions.yaml", value_hint = ValueHint::FilePath)]
+        file: String,
+        /// Kill cove windows that aren't listed in the manifest
+        #[arg(long)]
+        prune: bool,
+    },
+    /// Wait for a session to reach a state, then optionally run a command
+    Watch {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// State to wait for
+        #[arg(long, value_enum)]
+        until: StateFilter,
+        /// Shell command to run then (gets COVE_SESSION, COVE_DIR, COVE_PANE,
+        /// COVE_STATE); cove exits with its status
+        #[arg(long)]
+        then: Option<String>,
+        /// Wait for the session to reach the state again if it's in it now
+        #[arg(long)]
+        next: bool,
+        /// Give up after this many seconds
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+    },
+    /// Re-run a command in a session's terminal pane whenever Claude finishes a turn
+    WatchRun {
+        /// Session name, window index or pane ID (%7)
+        #[arg(value_name = "SESSION")]
+        name: String,
+        /// Command to run, after `--` (e.g. `-- cargo check`)
+        #[arg(last = true, required = true)]
+        cmd: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LayoutAction {
+    /// Re-apply hooks, remain-on-exit, pane roles and split sizes without restarting anything
+    Apply {
+        /// Session name, window index or pane ID (%7); default: every session
+        #[arg(value_name = "SESSION")]
+        name: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -49,4 +503,158 @@ pub enum HookEvent {
     Ask,
     /// User answered an AskUserQuestion (PostToolUse hook)
     AskDone,
+    /// Claude edited a file (PostToolUse hook on Edit/Write/MultiEdit/NotebookEdit)
+    FileEdit,
+    /// Claude ran a shell command (PostToolUse hook on Bash)
+    Command,
+    /// Claude is about to run any tool (PreToolUse hook, matcher *)
+    PreTool,
+    /// Claude finished running a tool (PostToolUse hook, matcher *)
+    PostTool,
+    /// Claude exited (SessionEnd hook)
+    SessionEnd,
+    /// Re-run a payload archived with --capture-dir through the handler
+    Replay {
+        /// Capture file
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+    },
+    /// Press a delegated answer once its question shows (run by tmux, see `cove delegate`)
+    #[command(hide = true)]
+    Answer {
+        #[arg(long)]
+        session: String,
+        #[arg(long)]
+        pane: String,
+        #[arg(long)]
+        rule: String,
+        #[arg(long)]
+        question: String,
+        #[arg(long)]
+        label: String,
+        #[arg(long)]
+        key: String,
+    },
+}
+
+/// Session states accepted by `--state` filters.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StateFilter {
+    Fresh,
+    Working,
+    Waiting,
+    Idle,
+    Done,
+    Queued,
+}
+
+/// Output formats of `cove timesheet`.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SheetFormat {
+    /// Hours per day, session and repository
+    Csv,
+    /// Toggl Track CSV import
+    Toggl,
+}
+
+/// Shells `cove completions` writes scripts for (and `prompt-segment` formats for).
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Services `cove share` can mirror a session through.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareBackend {
+    Tmate,
+    Upterm,
+}
+
+/// Pane arrangements of `cove layout --preset`. Claude stays pane .1 and
+/// the sidebar comes before the terminal in each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    /// Claude, sidebar and terminal in three equal columns
+    Even,
+    /// Claude at 70% on the left, the sidebar over the terminal (cove's default)
+    MainVertical,
+    /// Claude at 85%, with a narrow sidebar and terminal beside it
+    Focus,
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Preset::Even => write!(f, "even"),
+            Preset::MainVertical => write!(f, "main-vertical"),
+            Preset::Focus => write!(f, "focus"),
+        }
+    }
+}
+
+/// Terminal tab backends usable instead of tmux.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Kitty,
+    Wezterm,
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::Kitty => write!(f, "kitty"),
+            Backend::Wezterm => write!(f, "wezterm"),
+        }
+    }
+}
+
+/// Parse the command line. `--no-send` is taken out first: in front of a
+/// subcommand clap would otherwise read the subcommand as a session name
+/// (`cove --no-send send api` would start a session called "send").
+pub fn parse() -> Cli {
+    let (no_send, args) = take_no_send(std::env::args());
+    let mut cli = Cli::parse_from(args);
+    cli.no_send |= no_send;
+    cli
+}
+
+/// Remove `--no-send` from `args` (up to a `--`), reporting whether it was there.
+fn take_no_send(args: impl IntoIterator<Item = String>) -> (bool, Vec<String>) {
+    let mut found = false;
+    let mut rest = false;
+    let args = args
+        .into_iter()
+        .filter(|arg| {
+            rest |= arg == "--";
+            let flag = !rest && arg == "--no-send";
+            found |= flag;
+            !flag
+        })
+        .collect();
+    (found, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_no_send_before_subcommand() {
+        let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+        let (found, rest) = take_no_send(args("cove --no-send send api hi"));
+        assert!(found);
+        assert_eq!(rest, args("cove send api hi"));
+        let cli = Cli::parse_from(rest);
+        assert!(matches!(cli.command, Some(Command::Send { .. })));
+
+        // After `--` it's part of the command being run
+        let (found, rest) = take_no_send(args("cove watch-run api -- x --no-send"));
+        assert!(!found);
+        assert_eq!(rest.len(), 6);
+    }
 }
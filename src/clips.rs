@@ -0,0 +1,196 @@
+// ── Per-session scratch register ──
+//
+// Snippets saved with `cove copy <name> <text>` live in ~/.cove/clips/{name}.jsonl
+// (one JSON string per line, oldest first). The sidebar's clip view merges them
+// with files Claude recently edited in that session, and pastes the chosen
+// entry into the Claude pane.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{names, paths};
+
+// ── Types ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipSource {
+    /// Saved via `cove copy`.
+    Saved,
+    /// A file Claude edited in this session (from the file-edit hook).
+    Edited,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clip {
+    pub text: String,
+    pub source: ClipSource,
+}
+
+// ── Constants ──
+
+/// Saved entries kept per session; older ones are dropped.
+const MAX_SAVED: usize = 20;
+/// Recently edited files offered alongside saved entries.
+const MAX_EDITED: usize = 5;
+
+// ── Helpers ──
+
+fn clips_dir() -> Option<PathBuf> {
+    paths::cove_path("clips")
+}
+
+/// Register of session `name` in `dir`.
+fn file_in(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.jsonl", names::file_stem(name)))
+}
+
+fn saved_in_home(name: &str) -> Vec<String> {
+    clips_dir()
+        .map(|dir| read_saved(&file_in(&dir, name)))
+        .unwrap_or_default()
+}
+
+fn read_saved(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<String>(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn add_in(dir: &Path, name: &str, text: &str) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("create clips dir: {e}"))?;
+    let path = file_in(dir, name);
+
+    let mut saved = read_saved(&path);
+    saved.retain(|s| s != text);
+    saved.push(text.to_string());
+    let skip = saved.len().saturating_sub(MAX_SAVED);
+
+    let mut content = String::new();
+    for s in &saved[skip..] {
+        let line = serde_json::to_string(s).map_err(|e| format!("serialize clip: {e}"))?;
+        content.push_str(&line);
+        content.push('\n');
+    }
+    fs::write(&path, content).map_err(|e| format!("write clips: {e}"))
+}
+
+/// Saved entries newest first, then edited files not already saved.
+fn merge(saved: Vec<String>, edited: Vec<String>) -> Vec<Clip> {
+    let mut clips: Vec<Clip> = saved
+        .into_iter()
+        .rev()
+        .map(|text| Clip {
+            text,
+            source: ClipSource::Saved,
+        })
+        .collect();
+    for file in edited.into_iter().take(MAX_EDITED) {
+        if !clips.iter().any(|c| c.text == file) {
+            clips.push(Clip {
+                text: file,
+                source: ClipSource::Edited,
+            });
+        }
+    }
+    clips
+}
+
+// ── Public API ──
+
+/// Save a snippet to a session's register.
+pub fn add(name: &str, text: &str) -> Result<(), String> {
+    add_in(&paths::ensure_cove_dir("clips")?, name, text)
+}
+
+/// Saved snippets for a session, newest first.
+pub fn saved(name: &str) -> Vec<String> {
+    let mut saved = saved_in_home(name);
+    saved.reverse();
+    saved
+}
+
+/// Drop a session's register (called when the session is killed).
+pub fn remove(name: &str) {
+    if let Some(dir) = clips_dir() {
+        let _ = fs::remove_file(file_in(&dir, name));
+    }
+}
+
+/// Move a session's register to its new name.
+pub fn rename(old: &str, new: &str) -> Result<(), String> {
+    let Some(dir) = clips_dir() else {
+        return Ok(());
+    };
+    let from = file_in(&dir, old);
+    if !from.exists() {
+        return Ok(());
+    }
+    let to = file_in(&dir, new);
+    fs::rename(&from, &to).map_err(|e| format!("move clips: {e}"))
+}
+
+/// Everything offered in the sidebar's clip view for a session.
+pub fn entries(name: &str, edited_files: Vec<String>) -> Vec<Clip> {
+    merge(saved_in_home(name), edited_files)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_dedups_and_caps() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..25 {
+            add_in(dir.path(), "api", &format!("path/{i}")).unwrap();
+        }
+        add_in(dir.path(), "api", "path/10").unwrap();
+
+        let saved = read_saved(&dir.path().join("api.jsonl"));
+        assert_eq!(saved.len(), MAX_SAVED);
+        assert_eq!(saved.last().unwrap(), "path/10");
+        assert_eq!(saved.iter().filter(|s| *s == "path/10").count(), 1);
+    }
+
+    #[test]
+    fn test_add_preserves_multiline_text() {
+        let dir = tempfile::tempdir().unwrap();
+        add_in(dir.path(), "api", "line one\nline two").unwrap();
+        assert_eq!(
+            read_saved(&dir.path().join("api.jsonl")),
+            vec!["line one\nline two"]
+        );
+    }
+
+    #[test]
+    fn test_tagged_name_stays_in_the_clips_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        add_in(dir.path(), "work/api", "src/lib.rs").unwrap();
+        assert_eq!(
+            read_saved(&dir.path().join("work%2Fapi.jsonl")),
+            vec!["src/lib.rs"]
+        );
+        assert_eq!(
+            read_saved(&file_in(dir.path(), "work/api")),
+            vec!["src/lib.rs"]
+        );
+    }
+
+    #[test]
+    fn test_merge_orders_saved_before_edited() {
+        let clips = merge(
+            vec!["old".into(), "new".into()],
+            vec!["/r/a.rs".into(), "new".into()],
+        );
+        let texts: Vec<&str> = clips.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(texts, vec!["new", "old", "/r/a.rs"]);
+        assert_eq!(clips[2].source, ClipSource::Edited);
+    }
+}
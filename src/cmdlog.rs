@@ -0,0 +1,228 @@
+// ── Commands run by Claude ──
+//
+// The `cove hook command` handler (PostToolUse on Bash) appends each command
+// Claude ran, with its working directory and whether it was interrupted, to
+// ~/.cove/cmdlog/{session_id}.jsonl. `cove cmds` reads them back per pane so
+// users can audit exactly what the agent executed. Claude's Bash response
+// carries no exit code, so none is recorded.
+//
+// An audit log has to outlive its session: when a session is killed (or its
+// pane ID turns up again in a new window), its logs move to
+// ~/.cove/cmdlog/history/ as `<name>.<session_id>.jsonl`, where `cove cmds
+// <name>` still finds them once the window is gone.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{names, paths};
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandEntry {
+    pub command: String,
+    #[serde(default)]
+    pub cwd: String,
+    /// The user interrupted the command before it finished.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interrupted: bool,
+    #[serde(default)]
+    pub pane_id: String,
+    pub ts: u64,
+}
+
+// ── Constants ──
+
+const HISTORY_DIR: &str = "history";
+
+// ── Helpers ──
+
+fn cmdlog_dir() -> Option<PathBuf> {
+    paths::cove_path("cmdlog")
+}
+
+fn read_file(path: &Path) -> Vec<CommandEntry> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CommandEntry>(line).ok())
+        .collect()
+}
+
+/// Entries of the `.jsonl` files in `dir` whose name passes `keep`.
+fn read_entries_where(dir: &Path, keep: impl Fn(&str) -> bool) -> Vec<CommandEntry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(file) = path.file_name().and_then(|f| f.to_str()) else {
+            continue;
+        };
+        if file.ends_with(".jsonl") && keep(file) {
+            out.extend(read_file(&path));
+        }
+    }
+    out
+}
+
+fn read_entries(dir: &Path) -> Vec<CommandEntry> {
+    read_entries_where(dir, |_| true)
+}
+
+/// Commands of killed session `name` kept in `history`, oldest first.
+fn for_name_in(history: &Path, name: &str) -> Vec<CommandEntry> {
+    let prefix = format!("{}.", names::file_stem(name));
+    let mut entries = read_entries_where(history, |file| file.starts_with(&prefix));
+    entries.sort_by_key(|e| e.ts);
+    entries
+}
+
+/// Move the logs in `dir` recorded from `pane_id` into `history`, under
+/// session `name` when known.
+fn archive_in(dir: &Path, history: &Path, pane_id: &str, name: Option<&str>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(file) = path.file_name().and_then(|f| f.to_str()) else {
+            continue;
+        };
+        if !file.ends_with(".jsonl") || !read_file(&path).iter().any(|e| e.pane_id == pane_id) {
+            continue;
+        }
+        let file = match name {
+            Some(name) => format!("{}.{file}", names::file_stem(name)),
+            None => file.to_string(),
+        };
+        if let Err(e) =
+            fs::create_dir_all(history).and_then(|_| fs::rename(&path, history.join(file)))
+        {
+            log::warn!("cmdlog: keep {}: {e}", path.display());
+        }
+    }
+}
+
+/// Commands run from `pane_id`, oldest first.
+fn for_pane_in(dir: &Path, pane_id: &str) -> Vec<CommandEntry> {
+    let mut entries: Vec<CommandEntry> = read_entries(dir)
+        .into_iter()
+        .filter(|e| e.pane_id == pane_id)
+        .collect();
+    entries.sort_by_key(|e| e.ts);
+    entries
+}
+
+// ── Public API ──
+
+/// Record a Bash command Claude ran, given the hook's tool input and response.
+pub fn record(
+    session_id: &str,
+    pane_id: &str,
+    cwd: &str,
+    input: &Value,
+    response: &Value,
+) -> Result<(), String> {
+    let Some(command) = input["command"].as_str() else {
+        return Ok(());
+    };
+    let dir = paths::ensure_cove_dir("cmdlog")?;
+
+    let path = dir.join(format!("{session_id}.jsonl"));
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("open cmdlog file: {e}"))?;
+
+    let entry = CommandEntry {
+        command: command.to_string(),
+        cwd: cwd.to_string(),
+        interrupted: response["interrupted"].as_bool().unwrap_or(false),
+        pane_id: pane_id.to_string(),
+        ts: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let line = serde_json::to_string(&entry).map_err(|e| format!("serialize command: {e}"))?;
+    writeln!(f, "{line}").map_err(|e| format!("write command: {e}"))
+}
+
+/// Commands run by the Claude session in `pane_id`, oldest first.
+pub fn for_pane(pane_id: &str) -> Vec<CommandEntry> {
+    cmdlog_dir()
+        .map(|dir| for_pane_in(&dir, pane_id))
+        .unwrap_or_default()
+}
+
+/// Commands run by killed session `name`, oldest first.
+pub fn for_killed(name: &str) -> Vec<CommandEntry> {
+    cmdlog_dir()
+        .map(|dir| for_name_in(&dir.join(HISTORY_DIR), name))
+        .unwrap_or_default()
+}
+
+/// Move command logs recorded from `pane_id` to the history, under session
+/// `name` when known (called when a window is killed, and when a new one
+/// gets a recycled pane ID).
+pub fn archive_for_pane(pane_id: &str, name: Option<&str>) {
+    if let Some(dir) = cmdlog_dir() {
+        archive_in(&dir, &dir.join(HISTORY_DIR), pane_id, name);
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_pane_in_filters_and_orders() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("s1.jsonl"),
+            r#"{"command":"cargo test","cwd":"/r","exit_code":101,"pane_id":"%1","ts":200}
+{"command":"ls","cwd":"/r","pane_id":"%2","ts":150}
+{"command":"git status","cwd":"/r","exit_code":0,"pane_id":"%1","ts":100}
+"#,
+        )
+        .unwrap();
+
+        let cmds = for_pane_in(dir.path(), "%1");
+        let names: Vec<&str> = cmds.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(names, ["git status", "cargo test"]);
+        assert!(for_pane_in(dir.path(), "%9").is_empty());
+    }
+
+    #[test]
+    fn test_killed_session_keeps_its_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = dir.path().join(HISTORY_DIR);
+        fs::write(
+            dir.path().join("s1.jsonl"),
+            "{\"command\":\"cargo test\",\"pane_id\":\"%1\",\"ts\":200}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("s2.jsonl"),
+            "{\"command\":\"ls\",\"pane_id\":\"%2\",\"ts\":150}\n",
+        )
+        .unwrap();
+
+        archive_in(dir.path(), &history, "%1", Some("work/api"));
+        assert!(for_pane_in(dir.path(), "%1").is_empty());
+        assert_eq!(for_pane_in(dir.path(), "%2").len(), 1);
+        let kept = for_name_in(&history, "work/api");
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].command, "cargo test");
+        assert!(for_name_in(&history, "api").is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+// ── Issue → branch → worktree → session ──
+//
+// `cove adopt-branch 123` fetches the issue with `gh`, checks out a branch
+// named after it in a sibling worktree (`<repo>-123`), opens a cove session
+// there, records the issue and worktree in the session metadata, and sends
+// Claude a prompt rendered from the `issue` template (see templates.rs) or a
+// built-in default once it's ready. A sibling directory left from before is
+// reused only if it has the issue's branch checked out.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::colors::*;
+use crate::commands::start;
+use crate::tmux::{Tmux, TmuxClient};
+use crate::{git, meta, profiles, say, templates, tmux};
+
+// ── Types ──
+
+#[derive(Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    url: String,
+}
+
+// ── Constants ──
+
+/// Template used when none is given and no `issue` template exists.
+const DEFAULT_PROMPT: &str = "Work on issue #{{number}}: {{title}}\n\n{{body}}";
+
+/// Longest title slug kept in branch and session names.
+const MAX_SLUG: usize = 30;
+
+// ── Helpers ──
+
+fn fetch_issue(repo: &Path, id: &str) -> Result<Issue, String> {
+    let output = Command::new("gh")
+        .args(["issue", "view", id, "--json", "number,title,body,url"])
+        .current_dir(repo)
+        .output()
+        .map_err(|e| format!("gh: {e} (install the GitHub CLI)"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("gh issue view {id}: {}", stderr.trim()));
+    }
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("parse gh output: {e}"))
+}
+
+/// Lowercase, dash-separated form of a title, cut at a word boundary.
+fn slug(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.len() <= MAX_SLUG {
+        return slug.to_string();
+    }
+    let cut = &slug[..MAX_SLUG];
+    cut.rsplit_once('-')
+        .map_or(cut, |(head, _)| head)
+        .to_string()
+}
+
+/// Branch and session name for an issue, e.g. "123-fix-login-redirect".
+fn issue_name(issue: &Issue) -> String {
+    match slug(&issue.title).as_str() {
+        // A bare number would be read as a window index
+        "" => format!("issue-{}", issue.number),
+        s => format!("{}-{s}", issue.number),
+    }
+}
+
+/// Sibling worktree directory: ~/code/api → ~/code/api-123.
+fn worktree_path(toplevel: &Path, number: u64) -> PathBuf {
+    let repo = toplevel
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "repo".to_string());
+    toplevel.with_file_name(format!("{repo}-{number}"))
+}
+
+/// Err unless `worktree`, which already exists, is a worktree of the
+/// repository at `toplevel` with `branch` checked out.
+fn check_reusable(toplevel: &Path, worktree: &Path, branch: &str) -> Result<(), String> {
+    let canonical = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    let checked_out = git::worktree_for_branch(toplevel, branch)
+        .is_some_and(|path| canonical(Path::new(&path)) == canonical(worktree));
+    if checked_out {
+        return Ok(());
+    }
+    Err(format!(
+        "{} already exists without branch {ANSI_WHITE}{branch}{ANSI_RESET} checked out; move it or check the branch out there.",
+        worktree.display()
+    ))
+}
+
+fn render_prompt(template: &str, issue: &Issue, branch: &str) -> Result<String, String> {
+    let vars: HashMap<String, String> = [
+        ("number", issue.number.to_string()),
+        ("title", issue.title.clone()),
+        ("body", issue.body.clone()),
+        ("url", issue.url.clone()),
+        ("branch", branch.to_string()),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v))
+    .collect();
+    templates::render(template, &vars)
+}
+
+// ── Public API ──
+
+pub fn run(id: &str, dir: Option<&str>, template: Option<&str>) -> Result<(), String> {
+    let dir =
+        std::fs::canonicalize(dir.unwrap_or(".")).map_err(|e| format!("invalid directory: {e}"))?;
+    let toplevel = PathBuf::from(git::toplevel(&dir)?);
+
+    let issue = fetch_issue(&toplevel, id)?;
+    let name = issue_name(&issue);
+    if tmux::has_session() && tmux::list_window_names()?.contains(&name) {
+        return Err(format!(
+            "Session '{ANSI_PEACH}{name}{ANSI_RESET}' already exists."
+        ));
+    }
+
+    let template = match template {
+        Some(t) => templates::load(t)?,
+        None => templates::load("issue").unwrap_or_else(|_| DEFAULT_PROMPT.to_string()),
+    };
+    let prompt = render_prompt(&template, &issue, &name)?;
+
+    let worktree = worktree_path(&toplevel, issue.number);
+    if worktree.exists() {
+        check_reusable(&toplevel, &worktree, &name)?;
+        say!("  {ANSI_OVERLAY}reusing{ANSI_RESET} {}", worktree.display());
+    } else {
+        git::worktree_add(&toplevel, &worktree, &name)?;
+    }
+    let worktree = worktree.to_string_lossy().to_string();
+
+    let agent_cmd = profiles::command_for(&worktree, start::claude());
+    start::create_window(&name, &worktree, &agent_cmd)?;
+    meta::update(&name, |m| {
+        m.issue = (!issue.url.is_empty()).then(|| issue.url.clone());
+        m.worktree = Some(worktree.clone());
+    })?;
+
+    say!(
+        "Started {ANSI_PEACH}{name}{ANSI_RESET} on branch {ANSI_WHITE}{name}{ANSI_RESET} in {worktree}"
+    );
+    let sending = start::send_first_prompt(&name, &prompt);
+    if !tmux::is_inside_tmux() {
+        Tmux.attach()?;
+    }
+    if let Some(handle) = sending {
+        let _ = handle.join();
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(number: u64, title: &str) -> Issue {
+        Issue {
+            number,
+            title: title.to_string(),
+            body: "Steps to reproduce".to_string(),
+            url: format!("https://github.com/o/r/issues/{number}"),
+        }
+    }
+
+    #[test]
+    fn test_issue_name() {
+        assert_eq!(
+            issue_name(&issue(123, "Fix login redirect (again!)")),
+            "123-fix-login-redirect-again"
+        );
+        assert_eq!(issue_name(&issue(7, "???")), "issue-7");
+        // Long titles are cut at a word boundary
+        let name = issue_name(&issue(
+            9,
+            "Sidebar flickers when many sessions update at once",
+        ));
+        assert_eq!(name, "9-sidebar-flickers-when-many");
+    }
+
+    #[test]
+    fn test_worktree_path_is_sibling() {
+        assert_eq!(
+            worktree_path(Path::new("/code/api"), 42),
+            PathBuf::from("/code/api-42")
+        );
+    }
+
+    #[test]
+    fn test_reused_worktree_needs_the_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = std::fs::canonicalize(dir.path()).unwrap().join("api");
+        std::fs::create_dir(&repo).unwrap();
+        for args in [
+            &["init", "-q", "-b", "main"][..],
+            &[
+                "-c",
+                "user.email=t@t",
+                "-c",
+                "user.name=t",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        ] {
+            git::output(&repo, args, &[0]).unwrap();
+        }
+        let worktree = worktree_path(&repo, 5);
+        git::worktree_add(&repo, &worktree, "5-crash").unwrap();
+        assert!(check_reusable(&repo, &worktree, "5-crash").is_ok());
+        assert!(check_reusable(&repo, &worktree, "6-other").is_err());
+
+        // A leftover directory that isn't a worktree at all
+        let stray = worktree_path(&repo, 7);
+        std::fs::create_dir(&stray).unwrap();
+        assert!(check_reusable(&repo, &stray, "7-stray").is_err());
+    }
+
+    #[test]
+    fn test_default_prompt() {
+        let prompt = render_prompt(DEFAULT_PROMPT, &issue(5, "Crash on start"), "5-crash").unwrap();
+        assert_eq!(
+            prompt,
+            "Work on issue #5: Crash on start\n\nSteps to reproduce"
+        );
+    }
+}
@@ -0,0 +1,277 @@
+// ── cove annotate ──
+//
+// Milestones give long sessions some structure: `cove annotate api "migrated
+// db schema"` appends an event to the session's event stream with
+// hook "milestone" and the note. It repeats the session's latest state, and
+// the sidebar and everything else reading the latest event pass over it
+// (`state::read_last_event`), so it doesn't count as activity. `cove
+// timesheet` lists each day's milestones and `cove annotate api` shows them
+// all.
+//
+// `--install-git-hook` adds a post-commit hook to the current repository
+// that runs `cove annotate --git-commit`: it finds the session from
+// $TMUX_PANE (Claude's Bash tool and the terminal pane both have it) and
+// records the commit's subject. Outside a cove session it does nothing, and
+// it never fails the commit.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::colors::*;
+use crate::commands::{init, restart, start};
+use crate::sidebar::format::format_age;
+use crate::sidebar::state::{self, EventEntry, MILESTONE_HOOK};
+use crate::tmux::{self, Tmux, TmuxClient};
+use crate::{git, paths, say};
+
+// ── Constants ──
+
+/// Marks the line cove adds to a post-commit hook.
+const GIT_HOOK_MARKER: &str = "# cove: record commits as session milestones";
+
+// ── Helpers ──
+
+/// The events of Claude session `session_id` in `dir`, oldest first.
+fn read_events(dir: &Path, session_id: &str) -> Vec<EventEntry> {
+    fs::read_to_string(dir.join(format!("{session_id}.jsonl")))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Append a milestone to `session_id`'s event file in `dir`.
+fn record_in(
+    dir: &Path,
+    session_id: &str,
+    pane_id: &str,
+    cwd: &str,
+    note: &str,
+    ts: u64,
+) -> Result<(), String> {
+    let note = note.trim();
+    if note.is_empty() {
+        return Err("nothing to record: the note is empty".to_string());
+    }
+    let state = read_events(dir, session_id)
+        .pop()
+        .map(|e| e.state)
+        .ok_or("The session has no activity to annotate yet")?;
+    let line = serde_json::json!({
+        "state": state,
+        "cwd": cwd,
+        "pane_id": pane_id,
+        "ts": ts,
+        "hook": MILESTONE_HOOK,
+        "note": note,
+    });
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(dir.join(format!("{session_id}.jsonl")))
+        .map_err(|e| format!("open event file: {e}"))?;
+    writeln!(file, "{line}").map_err(|e| format!("write event: {e}"))
+}
+
+/// Claude session ID, pane ID and directory of the session named `name`.
+fn session_of(client: &dyn TmuxClient, name: &str) -> Result<(String, String, String), String> {
+    let pane_id = client.get_claude_pane_id(name)?;
+    let session_id = state::session_for_pane(&pane_id)
+        .ok_or(format!("No activity in {name} yet to annotate"))?;
+    let cwd = restart::pane_path(client, &pane_id);
+    Ok((session_id, pane_id, cwd))
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn print_milestones(name: &str, events: &[EventEntry]) {
+    let milestones: Vec<&EventEntry> = events.iter().filter(|e| e.is_milestone()).collect();
+    if milestones.is_empty() {
+        println!("{ANSI_OVERLAY}No milestones in {name}.{ANSI_RESET}");
+        return;
+    }
+    let now = now();
+    for event in milestones {
+        let age = format_age(now.saturating_sub(event.ts));
+        println!(
+            "  {ANSI_OVERLAY}{age:>4}{ANSI_RESET}  {ANSI_WHITE}{}{ANSI_RESET}",
+            event.note
+        );
+    }
+}
+
+/// The post-commit hook script of the repository containing `dir`.
+fn git_hook_path(dir: &Path) -> Option<PathBuf> {
+    // Respects core.hooksPath and worktrees
+    let hooks = git::output(dir, &["rev-parse", "--git-path", "hooks"], &[0])?;
+    Some(dir.join(hooks.trim()).join("post-commit"))
+}
+
+/// The line cove adds to a post-commit hook.
+fn git_hook_line(bin: &str) -> String {
+    format!(
+        "{} annotate --git-commit >/dev/null 2>&1 || true",
+        start::shell_quote(bin)
+    )
+}
+
+/// Add cove's line to the post-commit hook at `path`, creating the script if
+/// needed. An existing script of someone else's is left alone: appending to
+/// it could land after an `exit`.
+fn install_git_hook_at(path: &Path, bin: &str) -> Result<bool, String> {
+    let line = git_hook_line(bin);
+    let content = match fs::read_to_string(path) {
+        Ok(content) if content.contains(GIT_HOOK_MARKER) => return Ok(false),
+        Ok(_) => {
+            return Err(format!(
+                "{} already exists. Add this line to it:\n  {line}",
+                path.display()
+            ));
+        }
+        Err(_) => format!("#!/bin/sh\n{GIT_HOOK_MARKER}\n{line}\n"),
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    }
+    fs::write(path, content).map_err(|e| format!("write {}: {e}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("chmod {}: {e}", path.display()))?;
+    }
+    Ok(true)
+}
+
+/// Record the latest commit in the session whose pane this runs in.
+fn record_commit() -> Result<(), String> {
+    let Ok(pane) = std::env::var("TMUX_PANE") else {
+        return Ok(());
+    };
+    let Ok(name) = Tmux.resolve_target(&pane) else {
+        return Ok(());
+    };
+    let Ok((session_id, pane_id, cwd)) = session_of(&Tmux, &name) else {
+        return Ok(());
+    };
+    let Some(subject) = git::output(Path::new("."), &["log", "-1", "--format=%h %s"], &[0]) else {
+        return Ok(());
+    };
+    let dir = paths::ensure_events_dir()?;
+    record_in(
+        &dir,
+        &session_id,
+        &pane_id,
+        &cwd,
+        &format!("commit {}", subject.trim()),
+        now(),
+    )
+}
+
+// ── Public API ──
+
+/// Record `note` as a milestone of session `name`.
+pub fn record(client: &dyn TmuxClient, name: &str, note: &str) -> Result<(), String> {
+    let (session_id, pane_id, cwd) = session_of(client, name)?;
+    let dir = paths::ensure_events_dir()?;
+    record_in(&dir, &session_id, &pane_id, &cwd, note, now())
+}
+
+/// Record `note` as a milestone of session `name`, or list its milestones.
+pub fn run(
+    name: Option<&str>,
+    note: Option<&str>,
+    git_commit: bool,
+    install: bool,
+) -> Result<(), String> {
+    if git_commit {
+        return record_commit();
+    }
+    if install {
+        let path = git_hook_path(Path::new(".")).ok_or("Not in a git repository")?;
+        if install_git_hook_at(&path, &init::cove_bin_path())? {
+            say!(
+                "{ANSI_GREEN}Installed{ANSI_RESET} {}: commits made in a cove session become its milestones",
+                path.display()
+            );
+        } else {
+            say!(
+                "{ANSI_OVERLAY}Already installed in {}.{ANSI_RESET}",
+                path.display()
+            );
+        }
+        return Ok(());
+    }
+
+    let name = tmux::resolve_target(name.ok_or("Name a session to annotate")?)?;
+    let (session_id, pane_id, cwd) = session_of(&Tmux, &name)?;
+    let dir = paths::events_dir().ok_or("No cove home directory")?;
+    match note {
+        Some(note) => {
+            record_in(&dir, &session_id, &pane_id, &cwd, note, now())?;
+            say!("{ANSI_GREEN}Noted{ANSI_RESET} in {ANSI_PEACH}{name}{ANSI_RESET}: {note}");
+        }
+        None => print_milestones(&name, &read_events(&dir, &session_id)),
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_milestone_keeps_latest_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s1.jsonl");
+        fs::write(
+            &path,
+            r#"{"state":"working","cwd":"/code/api","pane_id":"%1","ts":100,"hook":"user-prompt"}
+"#,
+        )
+        .unwrap();
+
+        record_in(
+            dir.path(),
+            "s1",
+            "%1",
+            "/code/api",
+            "migrated \"db\" schema",
+            160,
+        )
+        .unwrap();
+        let events = read_events(dir.path(), "s1");
+        let last = events.last().unwrap();
+        assert!(last.is_milestone());
+        assert_eq!(last.state, "working");
+        assert_eq!(last.note, "migrated \"db\" schema");
+        // Readers of the latest event see the one before the milestone
+        assert_eq!(state::read_last_event(&path).map(|e| e.ts), Some(100));
+
+        assert!(record_in(dir.path(), "s1", "%1", "/code/api", "  ", 170).is_err());
+        assert!(record_in(dir.path(), "s2", "%2", "/code/web", "first", 170).is_err());
+    }
+
+    #[test]
+    fn test_install_git_hook_leaves_other_scripts_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hooks/post-commit");
+        assert!(install_git_hook_at(&path, "/usr/bin/cove").unwrap());
+        let script = fs::read_to_string(&path).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("annotate --git-commit"));
+        // Installing again is a no-op
+        assert!(!install_git_hook_at(&path, "/usr/bin/cove").unwrap());
+
+        fs::write(&path, "#!/bin/sh\nexit 0\n").unwrap();
+        let err = install_git_hook_at(&path, "/usr/bin/cove").unwrap_err();
+        assert!(err.contains("annotate --git-commit"), "{err}");
+    }
+}
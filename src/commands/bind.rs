@@ -0,0 +1,529 @@
+// ── cove bind ──
+//
+// `cove bind` asks for a tmux key for each of a few common actions (the
+// session switcher, a new session, killing the current one, focus mode) and
+// binds them under the prefix. Every binding is scoped to the cove session:
+// it's an `if-shell` on the session name, and elsewhere the key does what it
+// did before. Bindings live in the tmux server, so they're recorded in
+// ~/.cove/bindings.json with the key's previous binding and applied again
+// (like `prefix F`) whenever cove creates a window. `cove bind --remove`
+// puts the previous bindings back and forgets the record.
+//
+// Keys cove takes on its own are bound on every window created too, the same
+// way: `prefix F`, which leaves focus mode, and with `tmux.slot_keys` on
+// `prefix 1`–`9`, which run `cove go`. Elsewhere they do what they did
+// before. Their previous bindings are recorded the first time, in
+// ~/.cove/focus-key.json and slot-keys.json, and `cove bind --remove` puts
+// those back as well.
+
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+use crate::commands::start;
+use crate::tmux::{SESSION, Tmux, TmuxClient};
+use crate::{paths, say};
+
+// ── Types ──
+
+/// An action keys can be bound to.
+struct KeyAction {
+    id: &'static str,
+    label: &'static str,
+    /// tmux command for the action, given the quoted cove binary.
+    command: fn(&str) -> String,
+}
+
+/// A key cove bound, with what it was bound to before.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Binding {
+    action: String,
+    key: String,
+    /// The key's command outside cove; None if it was unbound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous: Option<String>,
+    /// Whether the previous binding repeated (`bind-key -r`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    repeat: bool,
+}
+
+// ── Constants ──
+
+const BINDINGS_FILE: &str = "bindings.json";
+
+/// Previous bindings of the digit keys `tmux.slot_keys` took over.
+const SLOT_KEYS_FILE: &str = "slot-keys.json";
+
+/// Previous binding of `prefix F`, which leaves focus mode.
+const FOCUS_KEY_FILE: &str = "focus-key.json";
+
+/// Records of keys cove binds without being asked, for `--remove`.
+const TAKEN_FILES: [&str; 2] = [FOCUS_KEY_FILE, SLOT_KEYS_FILE];
+
+const ACTIONS: &[KeyAction] = &[
+    KeyAction {
+        id: "switch",
+        label: "Switch session (popup tree)",
+        command: |_| format!("choose-tree -Zw -f {}", tmux_quote(&scope())),
+    },
+    KeyAction {
+        id: "new",
+        label: "New session here",
+        command: |bin| {
+            let run = format!("{bin} -q '%1' '#{{pane_current_path}}'");
+            format!(
+                "command-prompt -p {} {}",
+                tmux_quote("New session:"),
+                tmux_quote(&format!("run-shell {}", tmux_quote(&run)))
+            )
+        },
+    },
+    KeyAction {
+        id: "kill",
+        label: "Kill current session",
+        command: |bin| {
+            // A global flag before the subcommand would read as a session name
+            let run = format!("{bin} kill -q '#{{window_index}}'");
+            format!(
+                "confirm-before -p {} {}",
+                tmux_quote("Kill #W? (y/n)"),
+                tmux_quote(&format!("run-shell {}", tmux_quote(&run)))
+            )
+        },
+    },
+    KeyAction {
+        id: "focus",
+        label: "Toggle focus mode",
+        command: |bin| format!("run-shell {}", tmux_quote(&format!("{bin} focus"))),
+    },
+];
+
+// ── Helpers ──
+
+/// tmux format that is true inside the cove session.
+fn scope() -> String {
+    format!("#{{==:#{{session_name}},{SESSION}}}")
+}
+
+/// Quote `s` as one tmux command argument. Double quotes, since tmux has no
+/// escapes in single ones; `$` would expand environment variables there.
+fn tmux_quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        if matches!(c, '"' | '\\' | '$') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn load_from(path: &Path) -> Vec<Binding> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_to(path: &Path, bindings: &[Binding]) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(bindings).map_err(|e| format!("encode bindings: {e}"))?;
+    fs::write(path, content).map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+/// The first word of `s` and the rest, both trimmed.
+fn next_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    s.split_once(' ')
+        .map_or((s, ""), |(word, rest)| (word, rest.trim_start()))
+}
+
+/// Command and repeat flag from a `list-keys` line such as
+/// `bind-key -r -T prefix Up select-pane -U`.
+fn parse_list_keys(line: &str) -> Option<(String, bool)> {
+    let (word, mut rest) = next_word(line);
+    if word != "bind-key" {
+        return None;
+    }
+    let mut repeat = false;
+    loop {
+        let (word, tail) = next_word(rest);
+        rest = tail;
+        match word {
+            "-r" => repeat = true,
+            "-n" => {}
+            "-T" => rest = next_word(rest).1,
+            "" => return None,
+            // The key; the rest is its command
+            _ => return (!rest.is_empty()).then(|| (rest.to_string(), repeat)),
+        }
+    }
+}
+
+/// `key`'s command and repeat flag in the prefix table, if bound to
+/// something other than one of cove's bindings.
+fn current_binding(client: &dyn TmuxClient, key: &str) -> Option<(String, bool)> {
+    // tmux calls an unbound key unknown
+    let out = client.output(&["list-keys", "-T", "prefix", key]).ok()?;
+    parse_list_keys(out.lines().next()?).filter(|(command, _)| !command.contains(&scope()))
+}
+
+/// `bind-key` arguments for `binding`, running `command` in the cove session.
+fn bind_args(binding: &Binding, command: &str) -> Vec<String> {
+    let mut args = ["bind-key", "-T", "prefix", &binding.key, "if-shell", "-F"]
+        .map(String::from)
+        .to_vec();
+    args.push(scope());
+    args.push(command.to_string());
+    args.extend(binding.previous.clone());
+    args
+}
+
+fn bind_in(client: &dyn TmuxClient, binding: &Binding, bin: &str) -> Result<(), String> {
+    let action = ACTIONS
+        .iter()
+        .find(|a| a.id == binding.action)
+        .ok_or_else(|| format!("unknown action '{}'", binding.action))?;
+    let args = bind_args(binding, &(action.command)(bin));
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    client.output(&args).map(|_| ())
+}
+
+/// Give `binding`'s key back its previous command, or unbind it.
+fn restore_in(client: &dyn TmuxClient, binding: &Binding) -> Result<(), String> {
+    let key = binding.key.as_str();
+    match &binding.previous {
+        Some(previous) if binding.repeat => {
+            client.output(&["bind-key", "-r", "-T", "prefix", key, previous])?
+        }
+        Some(previous) => client.output(&["bind-key", "-T", "prefix", key, previous])?,
+        None => client.output(&["unbind-key", "-T", "prefix", key])?,
+    };
+    Ok(())
+}
+
+/// Bind `chosen` (action id, key) pairs in place of `old`, restoring keys no
+/// longer used. Returns the new record.
+fn install_in(
+    client: &dyn TmuxClient,
+    old: &[Binding],
+    chosen: &[(String, String)],
+    bin: &str,
+) -> Result<Vec<Binding>, String> {
+    for binding in old
+        .iter()
+        .filter(|b| !chosen.iter().any(|(_, k)| *k == b.key))
+    {
+        restore_in(client, binding)?;
+    }
+    let mut bindings = Vec::new();
+    for (action, key) in chosen {
+        // A key cove already holds keeps the binding it had before cove's
+        let (previous, repeat) = match old.iter().find(|b| b.key == *key) {
+            Some(b) => (b.previous.clone(), b.repeat),
+            None => current_binding(client, key).map_or((None, false), |(c, r)| (Some(c), r)),
+        };
+        let binding = Binding {
+            action: action.clone(),
+            key: key.clone(),
+            previous,
+            repeat,
+        };
+        bind_in(client, &binding, bin)?;
+        bindings.push(binding);
+    }
+    Ok(bindings)
+}
+
+/// Ask for a key per action, showing the current one: Enter keeps it (or
+/// leaves the action unbound), `-` drops it. Returns (action id, key) pairs.
+fn ask_keys(old: &[Binding], input: &mut dyn BufRead) -> Result<Vec<(String, String)>, String> {
+    let mut chosen: Vec<(String, String)> = Vec::new();
+    for action in ACTIONS {
+        let current = old.iter().find(|b| b.action == action.id);
+        let shown = current.map_or("none".to_string(), |b| format!("prefix {}", b.key));
+        loop {
+            print!(
+                "  {ANSI_WHITE}{}{ANSI_RESET} {ANSI_OVERLAY}[{shown}]{ANSI_RESET}: ",
+                action.label
+            );
+            let _ = io::stdout().flush();
+            let mut line = String::new();
+            input
+                .read_line(&mut line)
+                .map_err(|e| format!("read key: {e}"))?;
+            let key = match line.trim() {
+                "" => match current {
+                    Some(b) => b.key.clone(),
+                    None => break,
+                },
+                "-" => break,
+                key if key.contains(char::is_whitespace) => {
+                    println!("  {ANSI_RED}One key, e.g. s, C-s or M-Up.{ANSI_RESET}");
+                    continue;
+                }
+                key => key.to_string(),
+            };
+            if let Some((other, _)) = chosen.iter().find(|(_, k)| *k == key) {
+                println!("  {ANSI_RED}{key} is already bound to {other}.{ANSI_RESET}");
+                continue;
+            }
+            chosen.push((action.id.to_string(), key));
+            break;
+        }
+    }
+    Ok(chosen)
+}
+
+/// tmux command for slot key `key`: `cove go` with `bin`.
+fn slot_command(bin: &str, key: &str) -> String {
+    format!("run-shell {}", tmux_quote(&format!("{bin} go {key}")))
+}
+
+/// `keys`' bindings from before cove took them for `action`, recording them
+/// in `path` the first time (later, the keys already run cove's command).
+fn taken_bindings_in(
+    client: &dyn TmuxClient,
+    path: &Path,
+    action: &str,
+    keys: &[String],
+) -> Result<Vec<Binding>, String> {
+    let saved = load_from(path);
+    if !saved.is_empty() {
+        return Ok(saved);
+    }
+    // tmux always has prefix keys; without any, what the keys did is unknown
+    let table = client.output(&["list-keys", "-T", "prefix"])?;
+    if table.trim().is_empty() {
+        return Err("tmux lists no prefix keys".to_string());
+    }
+    let bindings: Vec<Binding> = keys
+        .iter()
+        .map(|key| {
+            let (previous, repeat) =
+                current_binding(client, key).map_or((None, false), |(c, r)| (Some(c), r));
+            Binding {
+                action: action.to_string(),
+                key: key.clone(),
+                previous,
+                repeat,
+            }
+        })
+        .collect();
+    save_to(path, &bindings)?;
+    Ok(bindings)
+}
+
+/// Bind each of `keys` to `command(key)` in the cove session, keeping its
+/// previous binding (recorded in ~/.cove/`file`) elsewhere. Without a record
+/// the keys are left alone, since `cove bind --remove` couldn't put them back.
+fn take_keys(
+    client: &dyn TmuxClient,
+    file: &str,
+    action: &str,
+    keys: &[String],
+    command: impl Fn(&str) -> String,
+) {
+    let bindings = paths::ensure_cove_dir("")
+        .and_then(|dir| taken_bindings_in(client, &dir.join(file), action, keys));
+    let bindings = match bindings {
+        Ok(bindings) => bindings,
+        Err(e) => {
+            log::warn!("record {action} keys: {e}");
+            return;
+        }
+    };
+    for binding in &bindings {
+        let args = bind_args(binding, &command(&binding.key));
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        if let Err(e) = client.output(&args) {
+            log::warn!("bind {}: {e}", binding.key);
+            return;
+        }
+    }
+}
+
+// ── Public API ──
+
+/// Point `prefix 1`–`9` at the quick-switch slots, in the cove session.
+pub fn bind_slot_keys(client: &dyn TmuxClient) {
+    let bin = start::shell_quote(&start::cove_bin());
+    let keys: Vec<String> = (1..=9).map(|slot: u8| slot.to_string()).collect();
+    take_keys(client, SLOT_KEYS_FILE, "slot", &keys, |key| {
+        slot_command(&bin, key)
+    });
+}
+
+/// Bind `prefix F` to shell `command`, for leaving focus mode (when the
+/// sidebar is hidden), in the cove session.
+pub fn bind_focus_key(client: &dyn TmuxClient, command: &str) {
+    let run = format!("run-shell {}", tmux_quote(command));
+    take_keys(
+        client,
+        FOCUS_KEY_FILE,
+        "focus-exit",
+        &["F".to_string()],
+        |_| run.clone(),
+    );
+}
+
+/// Bind the recorded keys again, e.g. on a tmux server started since.
+pub fn apply_in(client: &dyn TmuxClient) {
+    let Some(path) = paths::cove_path(BINDINGS_FILE) else {
+        return;
+    };
+    let bin = start::shell_quote(&start::cove_bin());
+    for binding in load_from(&path) {
+        if let Err(e) = bind_in(client, &binding, &bin) {
+            log::warn!("bind {}: {e}", binding.key);
+        }
+    }
+}
+
+/// Pick keys for cove's actions interactively, or with `remove` put back
+/// what the keys did before.
+pub fn run(remove: bool) -> Result<(), String> {
+    let path = paths::ensure_cove_dir("")?.join(BINDINGS_FILE);
+    let old = load_from(&path);
+    if remove {
+        for binding in &old {
+            restore_in(&Tmux, binding)?;
+        }
+        let _ = fs::remove_file(&path);
+        let mut count = old.len();
+        for file in TAKEN_FILES {
+            let taken_path = path.with_file_name(file);
+            let taken = load_from(&taken_path);
+            for binding in &taken {
+                restore_in(&Tmux, binding)?;
+            }
+            let _ = fs::remove_file(&taken_path);
+            count += taken.len();
+        }
+        say!("Removed {count} cove key binding(s).");
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        return Err("cove bind asks for keys; run it in a terminal.".to_string());
+    }
+
+    println!(
+        "Keys go under the tmux prefix and only act in the cove session.\n\
+         Enter keeps the key shown, - leaves the action unbound.\n"
+    );
+    let chosen = ask_keys(&old, &mut io::stdin().lock())?;
+    let bin = start::shell_quote(&start::cove_bin());
+    let bindings = install_in(&Tmux, &old, &chosen, &bin)?;
+    save_to(&path, &bindings)?;
+    println!();
+    for binding in &bindings {
+        say!(
+            "  {ANSI_PEACH}prefix {}{ANSI_RESET}  {}",
+            binding.key,
+            binding.action
+        );
+    }
+    say!("{ANSI_OVERLAY}cove bind --remove undoes them.{ANSI_RESET}");
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_parse_list_keys() {
+        assert_eq!(
+            parse_list_keys("bind-key -T prefix s choose-tree -Zs"),
+            Some(("choose-tree -Zs".to_string(), false))
+        );
+        assert_eq!(
+            parse_list_keys("bind-key    -r -T prefix       Up    select-pane -U"),
+            Some(("select-pane -U".to_string(), true))
+        );
+        assert_eq!(
+            parse_list_keys(r#"bind-key -T prefix \" split-window"#),
+            Some(("split-window".to_string(), false))
+        );
+        assert_eq!(parse_list_keys("unknown key: Q"), None);
+        assert_eq!(tmux_quote(r#"say "$HOME""#), r#""say \"\$HOME\"""#);
+    }
+
+    #[test]
+    fn test_taken_keys_fall_back_to_previous_binding() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SLOT_KEYS_FILE);
+        let keys: Vec<String> = (1..=9).map(|k: u8| k.to_string()).collect();
+        let client = FakeTmux::new().on("list-keys", "bind-key -T prefix 3 select-window -t :=3\n");
+        let bindings = taken_bindings_in(&client, &path, "slot", &keys).unwrap();
+        assert_eq!(bindings.len(), 9);
+        assert_eq!(load_from(&path), bindings);
+        // Recorded once: later windows don't take cove's binding for the old one
+        let again = FakeTmux::new().on("list-keys", "bind-key -T prefix 3 run-shell x\n");
+        assert_eq!(
+            taken_bindings_in(&again, &path, "slot", &keys).unwrap(),
+            bindings
+        );
+        // Nor is one of cove's own bindings, from before the record, kept
+        let focus = dir.path().join(FOCUS_KEY_FILE);
+        let ours = FakeTmux::new().on(
+            "list-keys",
+            "bind-key -T prefix F if-shell -F \"#{==:#{session_name},cove}\" \"run-shell x\"\n",
+        );
+        let taken = taken_bindings_in(&ours, &focus, "focus-exit", &["F".to_string()]).unwrap();
+        assert_eq!(taken[0].previous, None);
+
+        let args = bind_args(&bindings[2], &slot_command("'/bin/cove'", "3"));
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        client.output(&args).unwrap();
+        assert_eq!(
+            client.calls_to("bind-key")[0],
+            [
+                "bind-key",
+                "-T",
+                "prefix",
+                "3",
+                "if-shell",
+                "-F",
+                "#{==:#{session_name},cove}",
+                r#"run-shell "'/bin/cove' go 3""#,
+                "select-window -t :=3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_install_keeps_previous_and_restores_it() {
+        let client = FakeTmux::new().on("list-keys", "bind-key -T prefix s choose-tree -Zs\n");
+        let chosen = [("focus".to_string(), "s".to_string())];
+        let bindings = install_in(&client, &[], &chosen, "'/bin/cove'").unwrap();
+        assert_eq!(bindings[0].previous.as_deref(), Some("choose-tree -Zs"));
+        let bind = client.calls_to("bind-key");
+        assert_eq!(
+            bind[0][4..],
+            [
+                "if-shell",
+                "-F",
+                "#{==:#{session_name},cove}",
+                r#"run-shell "'/bin/cove' focus""#,
+                "choose-tree -Zs",
+            ]
+        );
+
+        // Dropping the key puts the old command back
+        install_in(&client, &bindings, &[], "'/bin/cove'").unwrap();
+        let bind = client.calls_to("bind-key");
+        assert_eq!(
+            bind[1],
+            ["bind-key", "-T", "prefix", "s", "choose-tree -Zs"]
+        );
+        assert!(client.calls_to("unbind-key").is_empty());
+    }
+}
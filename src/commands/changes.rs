@@ -0,0 +1,55 @@
+use std::fmt::Write;
+
+use crate::changes::{self, DiffLineKind};
+use crate::colors::*;
+use crate::{pager, paths, tmux};
+
+// ── Public API ──
+
+/// Show files Claude edited in a session, each with its diff against the git index.
+pub fn run(name: &str, files_only: bool) -> Result<(), String> {
+    if !tmux::has_session() {
+        println!("{ANSI_OVERLAY}No active cove session.{ANSI_RESET}");
+        return Err(String::new());
+    }
+    let name = &tmux::resolve_target(name)?;
+
+    let pane_id = tmux::get_claude_pane_id(name)?;
+    let files = changes::files_for_pane(&pane_id);
+    if files.is_empty() {
+        println!("{ANSI_OVERLAY}No files changed in {name}.{ANSI_RESET}");
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    for file in &files {
+        let display = paths::tildify(file);
+        if files_only {
+            let _ = writeln!(out, "  {ANSI_WHITE}{display}{ANSI_RESET}");
+            continue;
+        }
+
+        let _ = writeln!(out, "{ANSI_BOLD}{ANSI_WHITE}{display}{ANSI_RESET}");
+        let lines = changes::diff(file)?;
+        if lines.is_empty() {
+            let _ = writeln!(
+                out,
+                "  {ANSI_OVERLAY}(no changes against index){ANSI_RESET}"
+            );
+        }
+        for line in &lines {
+            let color = match changes::classify(line) {
+                DiffLineKind::Header => ANSI_OVERLAY,
+                DiffLineKind::Hunk => ANSI_BLUE,
+                DiffLineKind::Added => ANSI_GREEN,
+                DiffLineKind::Removed => ANSI_RED,
+                DiffLineKind::Context => ANSI_SUBTEXT,
+            };
+            let _ = writeln!(out, "{color}{line}{ANSI_RESET}");
+        }
+        let _ = writeln!(out);
+    }
+    pager::show(&out);
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+// ── Inline checklist ──
+//
+// A list of items to tick before a command goes ahead, drawn in place under
+// the prompt rather than full screen, so what was picked stays in the
+// scrollback: `cove all-kill` asks which sessions go. Up/Down (or j/k) move,
+// Space toggles, `a` and `n` tick all or none, Enter confirms and Esc (or q,
+// Ctrl-C) cancels.
+
+use std::io::{self, Write};
+
+use crossterm::cursor::{MoveToColumn, MoveUp};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{execute, queue};
+
+use crate::colors::*;
+
+// ── Types ──
+
+struct Checklist {
+    labels: Vec<String>,
+    checked: Vec<bool>,
+    cursor: usize,
+}
+
+/// What a key press asks of the checklist.
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    Redraw,
+    Done,
+    Cancel,
+}
+
+// ── Helpers ──
+
+impl Checklist {
+    fn key(&mut self, key: KeyEvent) -> Step {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => return Step::Cancel,
+            KeyCode::Esc | KeyCode::Char('q') => return Step::Cancel,
+            KeyCode::Enter => return Step::Done,
+            KeyCode::Up | KeyCode::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.cursor = (self.cursor + 1).min(self.labels.len().saturating_sub(1));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(c) = self.checked.get_mut(self.cursor) {
+                    *c = !*c;
+                }
+            }
+            KeyCode::Char('a') => self.checked.fill(true),
+            KeyCode::Char('n') => self.checked.fill(false),
+            _ => {}
+        }
+        Step::Redraw
+    }
+
+    /// The item lines, the cursor's marked.
+    fn lines(&self) -> Vec<String> {
+        self.labels
+            .iter()
+            .zip(&self.checked)
+            .enumerate()
+            .map(|(i, (label, checked))| {
+                let pointer = if i == self.cursor {
+                    format!("{ANSI_PEACH}\u{276f}{ANSI_RESET}")
+                } else {
+                    " ".to_string()
+                };
+                let mark = if *checked {
+                    format!("{ANSI_GREEN}[x]{ANSI_RESET}")
+                } else {
+                    format!("{ANSI_OVERLAY}[ ]{ANSI_RESET}")
+                };
+                format!("{pointer} {mark} {label}")
+            })
+            .collect()
+    }
+}
+
+/// Draw the items, over the previous drawing unless this is the `first`.
+fn draw(list: &Checklist, first: bool) -> io::Result<()> {
+    let mut out = io::stdout();
+    if !first {
+        queue!(out, MoveUp(list.labels.len() as u16))?;
+    }
+    for line in list.lines() {
+        queue!(
+            out,
+            MoveToColumn(0),
+            Clear(ClearType::CurrentLine),
+            Print(line),
+            Print("\r\n")
+        )?;
+    }
+    out.flush()
+}
+
+/// Read a key press, leaving raw mode to the caller.
+fn read_key() -> io::Result<KeyEvent> {
+    loop {
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            return Ok(key);
+        }
+    }
+}
+
+fn interact(list: &mut Checklist) -> io::Result<Step> {
+    draw(list, true)?;
+    loop {
+        match list.key(read_key()?) {
+            Step::Redraw => draw(list, false)?,
+            step => return Ok(step),
+        }
+    }
+}
+
+// ── Public API ──
+
+/// Ask which of `items` (label, ticked to begin with) to go ahead with,
+/// under `title`. None if cancelled. Needs a terminal on stdin.
+pub fn pick(title: &str, items: Vec<(String, bool)>) -> Result<Option<Vec<bool>>, String> {
+    let (labels, checked) = items.into_iter().unzip();
+    let mut list = Checklist {
+        labels,
+        checked,
+        cursor: 0,
+    };
+    println!(
+        "{title} {ANSI_OVERLAY}(space toggles, a all, n none, enter confirms, esc cancels){ANSI_RESET}"
+    );
+    terminal::enable_raw_mode().map_err(|e| format!("raw mode: {e}"))?;
+    let step = interact(&mut list);
+    let _ = terminal::disable_raw_mode();
+    let _ = execute!(io::stdout(), MoveToColumn(0));
+    match step.map_err(|e| e.to_string())? {
+        Step::Done => Ok(Some(list.checked)),
+        _ => Ok(None),
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(list: &mut Checklist, code: KeyCode) -> Step {
+        list.key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_checklist_toggles_and_confirms() {
+        let mut list = Checklist {
+            labels: vec!["api".into(), "web".into(), "docs".into()],
+            checked: vec![true, false, true],
+            cursor: 0,
+        };
+        press(&mut list, KeyCode::Char(' '));
+        press(&mut list, KeyCode::Down);
+        press(&mut list, KeyCode::Down);
+        press(&mut list, KeyCode::Down);
+        press(&mut list, KeyCode::Char(' '));
+        assert_eq!(list.checked, [false, false, false]);
+        assert!(list.lines()[2].starts_with(&format!("{ANSI_PEACH}\u{276f}")));
+
+        press(&mut list, KeyCode::Char('a'));
+        press(&mut list, KeyCode::Up);
+        press(&mut list, KeyCode::Char(' '));
+        assert_eq!(list.checked, [true, false, true]);
+        assert_eq!(press(&mut list, KeyCode::Enter), Step::Done);
+        assert_eq!(press(&mut list, KeyCode::Esc), Step::Cancel);
+    }
+}
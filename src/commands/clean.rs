@@ -0,0 +1,100 @@
+// ── cove clean --options ──
+//
+// Give a window back to plain tmux by unsetting what cove set on it:
+// remain-on-exit, the pane-died and layout hooks, the window-list format and
+// the `@cove-*` window and pane options — say for a window adopted from the
+// sidebar, or moved in with `cove merge`, that should stay your own. cove
+// records each option in the session's metadata as it sets it; windows from
+// before that are checked for all of them. Every option cove sets is scoped
+// to its window or pane; a pane-died hook of cove's found on the cove session
+// itself (tmux before 3.0 had only session hooks), which would respawn panes
+// in every window, is dropped too. Panes and what runs in them are left alone.
+
+use crate::colors::*;
+use crate::tmux::{Tmux, TmuxClient};
+use crate::{meta, say};
+
+// ── Helpers ──
+
+/// Unset cove's options on window `name`, given the ones `recorded` for it.
+fn clean_in(
+    client: &dyn TmuxClient,
+    name: &str,
+    recorded: &[String],
+) -> Result<Vec<String>, String> {
+    if !client.list_window_names()?.iter().any(|n| n == name) {
+        return Err(format!(
+            "No session named '{ANSI_PEACH}{name}{ANSI_RESET}'."
+        ));
+    }
+    client.clean_options(name, recorded)
+}
+
+// ── Public API ──
+
+/// Remove what cove set on session `name`'s window; `options` is the only
+/// thing it cleans so far, and must be asked for.
+pub fn run(name: &str, options: bool) -> Result<(), String> {
+    if !options {
+        return Err("Say what to clean: --options (cove's tmux options and hooks)".to_string());
+    }
+    let name = &Tmux.resolve_target(name)?;
+    let removed = clean_in(&Tmux, name, &meta::tmux_options(name))?;
+    meta::forget_options(name);
+    if removed.is_empty() {
+        say!("{ANSI_OVERLAY}No cove options on {name}.{ANSI_RESET}");
+    } else {
+        say!(
+            "{ANSI_GREEN}Removed{ANSI_RESET} from {ANSI_PEACH}{name}{ANSI_RESET}: {}",
+            removed.join(", ")
+        );
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_clean_unsets_set_options_and_leaked_session_hook() {
+        let fake = FakeTmux::new()
+            .with_windows(&[("api", "claude", "%t1")])
+            .on("show-options", "remain-on-exit on\n@cove-role claude\n")
+            .on(
+                "show-hooks",
+                "pane-died[0] if-shell -F '#{||:#{!=:#{@cove-role},claude},#{==:#{pane_dead_status},0}}' respawn-pane\n",
+            )
+            .on("list-panes", "%t1\n");
+        let removed = clean_in(&fake, "api", &[]).unwrap();
+        assert_eq!(
+            removed,
+            [
+                "remain-on-exit",
+                "pane-died hook",
+                "@cove-role (1 pane)",
+                "pane-died hook of the cove session"
+            ]
+        );
+        let unset = fake.calls_to("set-option");
+        let chain = unset[0].join(" ");
+        assert!(
+            chain.starts_with("set-option -w -u -t cove:api remain-on-exit ;"),
+            "{chain}"
+        );
+        assert!(
+            chain.contains("set-hook -w -u -t cove:api pane-died"),
+            "{chain}"
+        );
+        assert!(
+            chain.contains("set-option -p -u -t %t1 @cove-role"),
+            "{chain}"
+        );
+        assert!(chain.ends_with("set-hook -u -t cove pane-died"), "{chain}");
+
+        assert!(clean_in(&fake, "web", &[]).is_err());
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt::Write;
+
+use crate::cmdlog::{self, CommandEntry};
+use crate::colors::*;
+use crate::sidebar::format::format_age;
+use crate::{pager, paths, tmux};
+
+// ── Helpers ──
+
+/// "interrupted" after a command the user stopped, otherwise nothing.
+fn status_mark(entry: &CommandEntry) -> String {
+    if entry.interrupted {
+        format!("  {ANSI_PEACH}interrupted{ANSI_RESET}")
+    } else {
+        String::new()
+    }
+}
+
+// ── Public API ──
+
+/// List the shell commands Claude ran in a session, oldest first. A killed
+/// session's come from the history.
+pub fn run(name: &str) -> Result<(), String> {
+    let live = Some(name)
+        .filter(|_| tmux::has_session())
+        .and_then(|name| tmux::resolve_target(name).ok())
+        .and_then(|name| Some((tmux::get_claude_pane_id(&name).ok()?, name)));
+    let (entries, name) = match live {
+        Some((pane_id, name)) => (cmdlog::for_pane(&pane_id), name),
+        None => (cmdlog::for_killed(name), name.to_string()),
+    };
+    if entries.is_empty() {
+        println!("{ANSI_OVERLAY}No commands run in {name}.{ANSI_RESET}");
+        return Ok(());
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut out = String::new();
+    let mut last_cwd = "";
+    for entry in &entries {
+        // Only repeat the directory when it changes
+        if entry.cwd != last_cwd {
+            let _ = writeln!(
+                out,
+                "{ANSI_BOLD}{ANSI_WHITE}{}{ANSI_RESET}",
+                paths::tildify(&entry.cwd)
+            );
+            last_cwd = &entry.cwd;
+        }
+        let age = format_age(now.saturating_sub(entry.ts));
+        let mut lines = entry.command.lines();
+        let first = lines.next().unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "  {ANSI_OVERLAY}{age:>4}{ANSI_RESET}  {ANSI_SUBTEXT}{first}{ANSI_RESET}{}",
+            status_mark(entry)
+        );
+        for line in lines {
+            let _ = writeln!(out, "        {ANSI_SUBTEXT}{line}{ANSI_RESET}");
+        }
+    }
+    pager::show(&out);
+
+    Ok(())
+}
@@ -0,0 +1,620 @@
+// ── cove completions ──
+//
+// Shell completion scripts, generated from the clap command tree so they
+// follow the CLI as it grows. Arguments named SESSION (`value_name`) complete
+// the live window names, which the scripts ask tmux for at completion time;
+// value enums complete their values and path hints complete files. With
+// `--install` the script is written where the shell loads completions from:
+//
+//   bash  $XDG_DATA_HOME/bash-completion/completions/cove   (bash-completion 2)
+//   zsh   ~/.zfunc/_cove, a directory that must be on $fpath
+//   fish  $XDG_CONFIG_HOME/fish/completions/cove.fish
+
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use clap::builder::ValueHint;
+use clap::{Arg, CommandFactory};
+
+use crate::cli::{Cli, Shell};
+use crate::colors::*;
+use crate::tmux::SESSION;
+use crate::{paths, quiet};
+
+// ── Types ──
+
+/// What an argument's values complete to.
+enum Values {
+    Session,
+    Choices(Vec<String>),
+    Path,
+    /// Free text, nothing to offer.
+    Any,
+}
+
+struct Flag {
+    long: String,
+    short: Option<char>,
+    help: String,
+    /// `None` for switches.
+    values: Option<Values>,
+    repeatable: bool,
+}
+
+/// One command's completions.
+struct Spec {
+    name: String,
+    aliases: Vec<String>,
+    about: String,
+    flags: Vec<Flag>,
+    positionals: Vec<(String, Values)>,
+}
+
+// ── Helpers ──
+
+/// Lists the session's window names; every script embeds it.
+fn list_sessions() -> String {
+    format!("tmux list-windows -t {SESSION} -F '#W' 2>/dev/null")
+}
+
+fn values(arg: &Arg) -> Values {
+    if arg
+        .get_value_names()
+        .is_some_and(|names| names.iter().any(|n| n.as_str() == "SESSION"))
+    {
+        return Values::Session;
+    }
+    let choices: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|v| !v.is_hide_set())
+        .map(|v| v.get_name().to_string())
+        .collect();
+    if !choices.is_empty() {
+        return Values::Choices(choices);
+    }
+    match arg.get_value_hint() {
+        ValueHint::AnyPath | ValueHint::FilePath | ValueHint::DirPath => Values::Path,
+        _ => Values::Any,
+    }
+}
+
+fn spec(cmd: &clap::Command) -> Spec {
+    let mut flags = Vec::new();
+    let mut positionals = Vec::new();
+    for arg in cmd.get_arguments().filter(|a| !a.is_hide_set()) {
+        let help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+        if arg.is_positional() {
+            let label = arg
+                .get_value_names()
+                .and_then(|names| names.first())
+                .map(|n| n.to_lowercase())
+                .unwrap_or_else(|| arg.get_id().to_string());
+            positionals.push((label, values(arg)));
+        } else if let Some(long) = arg.get_long() {
+            let takes_values = arg.get_action().takes_values();
+            flags.push(Flag {
+                long: long.to_string(),
+                short: arg.get_short(),
+                help,
+                values: takes_values.then(|| values(arg)),
+                repeatable: matches!(arg.get_action(), clap::ArgAction::Append),
+            });
+        }
+    }
+    // Nested subcommands (`cove hook <event>`) complete like a first positional
+    let subs: Vec<String> = cmd
+        .get_subcommands()
+        .map(|s| s.get_name().to_string())
+        .collect();
+    if !subs.is_empty() {
+        positionals.insert(0, ("command".to_string(), Values::Choices(subs)));
+    }
+    Spec {
+        name: cmd.get_name().to_string(),
+        aliases: cmd.get_all_aliases().map(str::to_string).collect(),
+        about: cmd.get_about().map(|a| a.to_string()).unwrap_or_default(),
+        flags,
+        positionals,
+    }
+}
+
+/// The top-level command and each subcommand.
+fn specs() -> (Spec, Vec<Spec>) {
+    let cli = Cli::command();
+    let mut root = spec(&cli);
+    // The root's own positionals (name, dir) only matter for starting sessions
+    root.positionals.retain(|(label, _)| label != "command");
+    let subs = cli
+        .get_subcommands()
+        .filter(|s| !s.is_hide_set())
+        .map(spec)
+        .collect();
+    (root, subs)
+}
+
+fn single_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+fn flag_names(flags: &[Flag]) -> Vec<String> {
+    flags
+        .iter()
+        .flat_map(|f| {
+            let short = f.short.map(|c| format!("-{c}"));
+            short.into_iter().chain([format!("--{}", f.long)])
+        })
+        .collect()
+}
+
+fn bash_reply(values: &Values) -> String {
+    match values {
+        Values::Session => "_cove_sessions".to_string(),
+        Values::Choices(choices) => format!(
+            "COMPREPLY=($(compgen -W {} -- \"$cur\"))",
+            single_quote(&choices.join(" "))
+        ),
+        Values::Path => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+        Values::Any => "COMPREPLY=()".to_string(),
+    }
+}
+
+fn bash_case(flags: &[Flag], positionals: &[(String, Values)], out: &mut String) {
+    let mut valued = Vec::new();
+    let mut cases = String::new();
+    for flag in flags {
+        let Some(values) = &flag.values else {
+            continue;
+        };
+        let names: Vec<String> = flag
+            .short
+            .map(|c| format!("-{c}"))
+            .into_iter()
+            .chain([format!("--{}", flag.long)])
+            .collect();
+        cases.push_str(&format!(
+            "                {}) {}; return ;;\n",
+            names.join("|"),
+            bash_reply(values)
+        ));
+        valued.extend(names);
+    }
+    if !cases.is_empty() {
+        out.push_str(&format!(
+            "            case $prev in\n{cases}            esac\n"
+        ));
+    }
+    if !flags.is_empty() {
+        out.push_str(&format!(
+            "            if [[ $cur == -* ]]; then COMPREPLY=($(compgen -W {} -- \"$cur\")); return; fi\n",
+            single_quote(&flag_names(flags).join(" "))
+        ));
+    }
+    if positionals.is_empty() {
+        out.push_str("            COMPREPLY=()\n");
+        return;
+    }
+    out.push_str(&format!(
+        "            _cove_position {}\n            case $pos in\n",
+        single_quote(&valued.join(" "))
+    ));
+    for (i, (_, values)) in positionals.iter().enumerate() {
+        out.push_str(&format!("                {i}) {} ;;\n", bash_reply(values)));
+    }
+    out.push_str("            esac\n");
+}
+
+fn bash(root: &Spec, subs: &[Spec]) -> String {
+    let mut commands: Vec<String> = subs
+        .iter()
+        .flat_map(|s| std::iter::once(s.name.clone()).chain(s.aliases.clone()))
+        .collect();
+    commands.extend(flag_names(&root.flags));
+    let mut out = format!(
+        r#"# bash completion for cove, from `cove completions bash`
+
+_cove_sessions() {{
+    local IFS=$'\n'
+    COMPREPLY=($(compgen -W "$({list_sessions})" -- "$cur"))
+}}
+
+# Sets pos to the index of the positional argument being completed, skipping
+# flags and the values of the flags in $1.
+_cove_position() {{
+    local i word skip=
+    pos=0
+    for ((i = 2; i < COMP_CWORD; i++)); do
+        word=${{COMP_WORDS[i]}}
+        if [[ -n $skip ]]; then
+            skip=
+        elif [[ " $1 " == *" $word "* ]]; then
+            skip=1
+        elif [[ $word != -* ]]; then
+            ((pos++))
+        fi
+    done
+}}
+
+_cove() {{
+    local cur=${{COMP_WORDS[COMP_CWORD]}} prev=${{COMP_WORDS[COMP_CWORD-1]}} pos
+    if ((COMP_CWORD == 1)); then
+        COMPREPLY=($(compgen -W {} -- "$cur"))
+        return
+    fi
+    case ${{COMP_WORDS[1]}} in
+"#,
+        single_quote(&commands.join(" ")),
+        list_sessions = list_sessions()
+    );
+    for sub in subs {
+        let names: Vec<&str> = std::iter::once(sub.name.as_str())
+            .chain(sub.aliases.iter().map(String::as_str))
+            .collect();
+        out.push_str(&format!("        {})\n", names.join("|")));
+        bash_case(&sub.flags, &sub.positionals, &mut out);
+        out.push_str("            ;;\n");
+    }
+    // `cove <name> <dir>` starts a session; the name was word 1
+    out.push_str("        *)\n");
+    bash_case(
+        &root.flags,
+        root.positionals.get(1..).unwrap_or_default(),
+        &mut out,
+    );
+    out.push_str("            ;;\n    esac\n}\n\ncomplete -F _cove cove\n");
+    out
+}
+
+fn zsh_action(values: &Values) -> String {
+    match values {
+        Values::Session => "_cove_sessions".to_string(),
+        Values::Choices(choices) => format!("({})", choices.join(" ")),
+        Values::Path => "_files".to_string(),
+        Values::Any => " ".to_string(),
+    }
+}
+
+fn zsh_escape_help(help: &str) -> String {
+    help.replace('\\', r"\\")
+        .replace('[', r"\[")
+        .replace(']', r"\]")
+}
+
+fn zsh_arguments(flags: &[Flag], positionals: &[(String, Values)]) -> Vec<String> {
+    let mut specs = Vec::new();
+    for flag in flags {
+        let names = flag
+            .short
+            .map(|c| format!("-{c}"))
+            .into_iter()
+            .chain([format!("--{}", flag.long)]);
+        for name in names {
+            let repeat = if flag.repeatable { "*" } else { "" };
+            let value = match &flag.values {
+                Some(values) => format!(":{}:{}", flag.long, zsh_action(values)),
+                None => String::new(),
+            };
+            specs.push(format!(
+                "{repeat}{name}[{}]{value}",
+                zsh_escape_help(&flag.help)
+            ));
+        }
+    }
+    for (i, (label, values)) in positionals.iter().enumerate() {
+        specs.push(format!("{}:{label}:{}", i + 1, zsh_action(values)));
+    }
+    specs.into_iter().map(|s| single_quote(&s)).collect()
+}
+
+fn zsh(root: &Spec, subs: &[Spec]) -> String {
+    let mut out = format!(
+        r#"#compdef cove
+# zsh completion for cove, from `cove completions zsh`
+
+_cove_sessions() {{
+    local -a sessions
+    sessions=(${{(f)"$({list_sessions})"}})
+    compadd -a sessions
+}}
+
+_cove() {{
+    local curcontext=$curcontext state line
+    _arguments -C \
+"#,
+        list_sessions = list_sessions()
+    );
+    for arg in zsh_arguments(&root.flags, &[]) {
+        out.push_str(&format!("        {arg} \\\n"));
+    }
+    out.push_str(
+        "        '1: :->command' \\\n        '*:: :->args'\n\n    case $state in\n        command)\n            local -a commands\n            commands=(\n",
+    );
+    for sub in subs {
+        for name in std::iter::once(&sub.name).chain(&sub.aliases) {
+            out.push_str(&format!(
+                "                {}\n",
+                single_quote(&format!("{name}:{}", sub.about))
+            ));
+        }
+    }
+    out.push_str(
+        "            )\n            _describe -t commands 'cove command' commands\n            ;;\n        args)\n            case $words[1] in\n",
+    );
+    for sub in subs {
+        let names: Vec<&str> = std::iter::once(sub.name.as_str())
+            .chain(sub.aliases.iter().map(String::as_str))
+            .collect();
+        let args = zsh_arguments(&sub.flags, &sub.positionals);
+        if args.is_empty() {
+            out.push_str(&format!("                {}) ;;\n", names.join("|")));
+        } else {
+            out.push_str(&format!(
+                "                {}) _arguments {} ;;\n",
+                names.join("|"),
+                args.join(" ")
+            ));
+        }
+    }
+    // Past `cove <name>` comes the directory
+    out.push_str("                *) _files -/ ;;\n            esac\n            ;;\n    esac\n}\n\n_cove \"$@\"\n");
+    out
+}
+
+fn fish_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+fn fish_values(values: &Values) -> String {
+    match values {
+        Values::Session => "-xa '(__cove_sessions)'".to_string(),
+        Values::Choices(choices) => format!("-xa {}", fish_quote(&choices.join(" "))),
+        Values::Path => "-rF".to_string(),
+        Values::Any => "-x".to_string(),
+    }
+}
+
+/// Completions for `flags` and, as fish can't tell positionals apart, for
+/// whatever the `first` positional takes.
+fn fish_spec(flags: &[Flag], first: Option<&Values>, condition: &str, out: &mut String) {
+    for flag in flags {
+        let mut line = format!("complete -c cove -n {condition}");
+        if let Some(c) = flag.short {
+            line.push_str(&format!(" -s {c}"));
+        }
+        line.push_str(&format!(" -l {}", flag.long));
+        if let Some(values) = &flag.values {
+            line.push_str(&format!(" {}", fish_values(values)));
+        }
+        if !flag.help.is_empty() {
+            line.push_str(&format!(" -d {}", fish_quote(&flag.help)));
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    match first {
+        Some(Values::Session) => {
+            out.push_str(&format!(
+                "complete -c cove -n {condition} -a '(__cove_sessions)'\n"
+            ));
+        }
+        Some(Values::Choices(choices)) => out.push_str(&format!(
+            "complete -c cove -n {condition} -a {}\n",
+            fish_quote(&choices.join(" "))
+        )),
+        Some(Values::Path) => out.push_str(&format!("complete -c cove -n {condition} -F\n")),
+        Some(Values::Any) | None => {}
+    }
+}
+
+fn fish(root: &Spec, subs: &[Spec]) -> String {
+    let mut out = format!(
+        "# fish completion for cove, from `cove completions fish`\n\n\
+         function __cove_sessions\n    {list_sessions}\nend\n\n\
+         complete -c cove -f\n",
+        list_sessions = list_sessions()
+    );
+    fish_spec(&root.flags, None, "__fish_use_subcommand", &mut out);
+    for sub in subs {
+        for name in std::iter::once(&sub.name).chain(&sub.aliases) {
+            out.push_str(&format!(
+                "complete -c cove -n __fish_use_subcommand -a {name} -d {}\n",
+                fish_quote(&sub.about)
+            ));
+        }
+    }
+    for sub in subs {
+        let names: Vec<&str> = std::iter::once(sub.name.as_str())
+            .chain(sub.aliases.iter().map(String::as_str))
+            .collect();
+        let condition = fish_quote(&format!("__fish_seen_subcommand_from {}", names.join(" ")));
+        let first = sub.positionals.first().map(|(_, v)| v);
+        fish_spec(&sub.flags, first, &condition, &mut out);
+    }
+    out
+}
+
+/// The completion script for `shell`.
+fn script(shell: Shell) -> String {
+    let (root, subs) = specs();
+    match shell {
+        Shell::Bash => bash(&root, &subs),
+        Shell::Zsh => zsh(&root, &subs),
+        Shell::Fish => fish(&root, &subs),
+    }
+}
+
+fn detect_shell() -> Result<Shell, String> {
+    let shell = std::env::var("SHELL").unwrap_or_default();
+    match Path::new(&shell).file_name().and_then(|n| n.to_str()) {
+        Some("bash") => Ok(Shell::Bash),
+        Some("zsh") => Ok(Shell::Zsh),
+        Some("fish") => Ok(Shell::Fish),
+        _ => Err(format!(
+            "Can't tell the shell from $SHELL ({shell:?}). Name it: cove completions bash|zsh|fish"
+        )),
+    }
+}
+
+/// An XDG base directory: the variable if set to an absolute path, else the
+/// default under `home`.
+fn xdg_dir(var: Option<String>, home: &Path, default: &str) -> PathBuf {
+    var.map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home.join(default))
+}
+
+/// Where `shell` loads cove's completions from.
+fn install_path_in(
+    shell: Shell,
+    home: &Path,
+    data_home: Option<String>,
+    config_home: Option<String>,
+) -> PathBuf {
+    match shell {
+        Shell::Bash => xdg_dir(data_home, home, ".local/share")
+            .join("bash-completion")
+            .join("completions")
+            .join("cove"),
+        Shell::Zsh => home.join(".zfunc").join("_cove"),
+        Shell::Fish => xdg_dir(config_home, home, ".config")
+            .join("fish")
+            .join("completions")
+            .join("cove.fish"),
+    }
+}
+
+fn confirm(question: &str) -> bool {
+    if quiet::on() || !io::stdin().is_terminal() {
+        return false;
+    }
+    print!("{question} [Y/n] ");
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    if io::stdin().lock().read_line(&mut input).is_err() {
+        return false;
+    }
+    let answer = input.trim().to_lowercase();
+    answer.is_empty() || answer == "y" || answer == "yes"
+}
+
+fn install(shell: Shell) -> Result<(), String> {
+    let home = paths::home().ok_or("HOME is not set")?;
+    let path = install_path_in(
+        shell,
+        &home,
+        std::env::var("XDG_DATA_HOME").ok(),
+        std::env::var("XDG_CONFIG_HOME").ok(),
+    );
+    let shown = paths::tildify(&path.to_string_lossy());
+    let verb = if path.exists() { "Replace" } else { "Write" };
+    if !confirm(&format!("{verb} {shown}?")) {
+        return Err(format!(
+            "Not installed. To write it yourself: cove completions {} > {shown}",
+            shell_name(shell)
+        ));
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    }
+    fs::write(&path, script(shell)).map_err(|e| format!("write {}: {e}", path.display()))?;
+    println!("{ANSI_GREEN}Installed{ANSI_RESET} {shown}");
+
+    match shell {
+        Shell::Zsh => {
+            let zshrc = fs::read_to_string(home.join(".zshrc")).unwrap_or_default();
+            if !zshrc.contains(".zfunc") {
+                println!(
+                    "Add this to ~/.zshrc, before compinit runs:\n  {ANSI_PEACH}fpath=(~/.zfunc $fpath){ANSI_RESET}"
+                );
+            }
+        }
+        Shell::Bash => println!("New shells load it (needs the bash-completion package)."),
+        Shell::Fish => println!("New shells load it."),
+    }
+    Ok(())
+}
+
+fn shell_name(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => "bash",
+        Shell::Zsh => "zsh",
+        Shell::Fish => "fish",
+    }
+}
+
+// ── Public API ──
+
+pub fn run(shell: Option<Shell>, install_script: bool) -> Result<(), String> {
+    let shell = match shell {
+        Some(shell) => shell,
+        None => detect_shell()?,
+    };
+    if install_script {
+        return install(shell);
+    }
+    print!("{}", script(shell));
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_args_complete_window_names() {
+        let (_, subs) = specs();
+        let kill = subs.iter().find(|s| s.name == "kill").unwrap();
+        assert!(matches!(kill.positionals[0].1, Values::Session));
+        let check = subs.iter().find(|s| s.name == "name-check").unwrap();
+        assert!(matches!(check.positionals[0].1, Values::Any));
+        let sheet = subs.iter().find(|s| s.name == "timesheet").unwrap();
+        let format = sheet.flags.iter().find(|f| f.long == "format").unwrap();
+        assert!(matches!(&format.values, Some(Values::Choices(c)) if c == &["csv", "toggl"]));
+    }
+
+    #[test]
+    fn test_scripts_cover_every_command() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = script(shell);
+            assert!(script.contains(&list_sessions()));
+            for name in ["kill", "timesheet", "ls", "completions"] {
+                assert!(
+                    script.contains(name),
+                    "{name} missing for {}",
+                    shell_name(shell)
+                );
+            }
+        }
+        // The generated bash must at least parse
+        if let Ok(output) = std::process::Command::new("bash")
+            .args(["-n", "-c", &script(Shell::Bash)])
+            .output()
+        {
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+
+    #[test]
+    fn test_install_paths() {
+        let home = Path::new("/home/me");
+        assert_eq!(
+            install_path_in(Shell::Bash, home, None, None),
+            Path::new("/home/me/.local/share/bash-completion/completions/cove")
+        );
+        assert_eq!(
+            install_path_in(Shell::Fish, home, None, Some("/xdg".to_string())),
+            Path::new("/xdg/fish/completions/cove.fish")
+        );
+        assert_eq!(
+            install_path_in(Shell::Zsh, home, None, None),
+            Path::new("/home/me/.zfunc/_cove")
+        );
+    }
+}
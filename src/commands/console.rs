@@ -0,0 +1,581 @@
+// ── cove console ──
+//
+// A prompt for driving many sessions without a process per command: `list`,
+// `send`, `kill` and `wait` run against one tmux control-mode connection
+// (`tmux::control`) and one state detector, whose event cache stays warm
+// between commands. Tab completes commands, session names and states; Up
+// and Down walk the history, which is kept in ~/.cove/console-history.
+// `wait` holds the prompt until a session reaches a state; Esc gives up.
+//
+// Run it in a spare pane (or the window's terminal pane) and leave it there.
+// With stdin not a terminal it reads one command per line, without the
+// editor, e.g. `cove console < steps.txt`.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::Duration;
+
+use clap::ValueEnum;
+use crossterm::cursor::MoveToColumn;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{execute, queue};
+
+use crate::cli::StateFilter;
+use crate::colors::*;
+use crate::commands::{kill, watch};
+use crate::sidebar::state::{StateDetector, WindowState};
+use crate::tmux::TmuxClient;
+use crate::tmux::control::ControlTmux;
+use crate::{paths, say, width};
+
+// ── Types ──
+
+/// The line being typed, and the history it can be swapped for.
+#[derive(Default)]
+struct Editor {
+    text: Vec<char>,
+    cursor: usize,
+    history: Vec<String>,
+    /// History entry shown, counted from the newest; None while typing.
+    recall: Option<usize>,
+    /// What was typed before Up, restored by Down past the newest entry.
+    draft: Vec<char>,
+}
+
+/// What a key press asks of the console.
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    /// The line changed (or didn't); redraw it.
+    Edit,
+    Submit(String),
+    Complete,
+    /// Ctrl-C: drop the line.
+    Cancel,
+    /// Ctrl-D on an empty line.
+    Quit,
+}
+
+struct Console {
+    client: ControlTmux,
+    detector: StateDetector,
+}
+
+// ── Constants ──
+
+const PROMPT: &str = "cove> ";
+
+const HISTORY_FILE: &str = "console-history";
+
+/// Entries loaded from the history file.
+const MAX_HISTORY: usize = 500;
+
+/// Commands, their arguments and what they do, for completion and `help`.
+const COMMANDS: &[(&str, &str, &str)] = &[
+    ("list", "", "sessions, their state and directory"),
+    (
+        "send",
+        "<name> <text>",
+        "type text into a session's Claude and submit it",
+    ),
+    ("kill", "<name>", "kill a session (cove undo restores it)"),
+    (
+        "wait",
+        "<name> <state> [--next]",
+        "wait for a session to reach a state (Esc stops)",
+    ),
+    ("help", "", "list the commands"),
+    ("exit", "", "leave the console (also Ctrl-D)"),
+];
+
+const WAIT_POLL: Duration = Duration::from_millis(500);
+
+// ── Helpers ──
+
+impl Editor {
+    fn line(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    fn set(&mut self, text: &str) {
+        self.text = text.chars().collect();
+        self.cursor = self.text.len();
+    }
+
+    /// Show history entry `recall` (None: the draft).
+    fn show(&mut self, recall: Option<usize>) {
+        if self.recall.is_none() {
+            self.draft = self.text.clone();
+        }
+        self.recall = recall;
+        match recall {
+            Some(n) => {
+                let entry = self.history[self.history.len() - 1 - n].clone();
+                self.set(&entry);
+            }
+            None => {
+                self.text = std::mem::take(&mut self.draft);
+                self.cursor = self.text.len();
+            }
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Step {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => {
+                let line = self.line();
+                self.set("");
+                self.recall = None;
+                if !line.trim().is_empty() && self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                }
+                return Step::Submit(line);
+            }
+            KeyCode::Tab => return Step::Complete,
+            KeyCode::Char('c') if ctrl => {
+                self.set("");
+                self.recall = None;
+                return Step::Cancel;
+            }
+            KeyCode::Char('d') if ctrl && self.text.is_empty() => return Step::Quit,
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.text.len(),
+            KeyCode::Char('u') if ctrl => {
+                self.text.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            KeyCode::Char('w') if ctrl => {
+                let mut start = self.cursor;
+                while start > 0 && self.text[start - 1] == ' ' {
+                    start -= 1;
+                }
+                while start > 0 && self.text[start - 1] != ' ' {
+                    start -= 1;
+                }
+                self.text.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.text.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.text.remove(self.cursor);
+            }
+            KeyCode::Delete if self.cursor < self.text.len() => {
+                self.text.remove(self.cursor);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.text.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.len(),
+            KeyCode::Up if self.recall.map_or(0, |n| n + 1) < self.history.len() => {
+                self.show(Some(self.recall.map_or(0, |n| n + 1)));
+            }
+            KeyCode::Down if self.recall.is_some() => {
+                self.show(self.recall.and_then(|n| n.checked_sub(1)));
+            }
+            _ => {}
+        }
+        Step::Edit
+    }
+}
+
+/// `line` split into words, with '…' and "…" quoting spaces.
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+fn state_names() -> Vec<String> {
+    StateFilter::value_variants()
+        .iter()
+        .filter_map(|s| s.to_possible_value())
+        .map(|v| v.get_name().to_string())
+        .collect()
+}
+
+/// Completions for the last word of `line`: commands, then session names
+/// (`names`), then states for `wait`.
+fn candidates(line: &str, names: &[String]) -> Vec<String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let typing = !line.is_empty() && !line.ends_with(' ');
+    let (done, partial) = match (typing, words.split_last()) {
+        (true, Some((last, rest))) => (rest, *last),
+        _ => (&words[..], ""),
+    };
+    let options: Vec<String> = match done {
+        [] => COMMANDS.iter().map(|(c, _, _)| c.to_string()).collect(),
+        ["send" | "kill" | "wait"] => names.to_vec(),
+        ["wait", _] => state_names(),
+        _ => Vec::new(),
+    };
+    options
+        .into_iter()
+        .filter(|o| o.starts_with(partial))
+        .collect()
+}
+
+/// The longest start all of `words` share.
+fn common_prefix(words: &[String]) -> String {
+    let Some(first) = words.first() else {
+        return String::new();
+    };
+    let mut len = first.len();
+    for word in &words[1..] {
+        len = first
+            .char_indices()
+            .zip(word.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8())
+            .min(len);
+    }
+    first[..len].to_string()
+}
+
+fn state_color(state: WindowState) -> &'static str {
+    match state {
+        WindowState::Working => ANSI_LAVENDER,
+        WindowState::Asking => ANSI_PEACH,
+        WindowState::Idle => ANSI_GREEN,
+        WindowState::Queued => ANSI_BLUE,
+        WindowState::Fresh | WindowState::Done => ANSI_OVERLAY,
+    }
+}
+
+fn load_history() -> Vec<String> {
+    let content = paths::cove_path(HISTORY_FILE)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    let lines: Vec<String> = content.lines().map(str::to_string).collect();
+    lines[lines.len().saturating_sub(MAX_HISTORY)..].to_vec()
+}
+
+fn save_history(line: &str) {
+    let Ok(dir) = paths::ensure_cove_dir("") else {
+        return;
+    };
+    if let Ok(mut file) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(HISTORY_FILE))
+    {
+        let _ = writeln!(file, "{line}");
+    }
+}
+
+/// Draw the prompt line with the cursor in place.
+fn draw(editor: &Editor) -> io::Result<()> {
+    let mut out = io::stdout();
+    let before: String = editor.text[..editor.cursor].iter().collect();
+    queue!(
+        out,
+        MoveToColumn(0),
+        Clear(ClearType::UntilNewLine),
+        crossterm::style::Print(format!("{ANSI_PEACH}{PROMPT}{ANSI_RESET}{}", editor.line())),
+        MoveToColumn((PROMPT.len() + before.chars().count()) as u16),
+    )?;
+    out.flush()
+}
+
+/// Read a key press, leaving raw mode to the caller.
+fn read_key() -> io::Result<KeyEvent> {
+    loop {
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            return Ok(key);
+        }
+    }
+}
+
+impl Console {
+    fn names(&self) -> Vec<String> {
+        self.client.list_window_names().unwrap_or_default()
+    }
+
+    fn list(&mut self) -> Result<(), String> {
+        let windows = self.client.list_windows()?;
+        if windows.is_empty() {
+            println!("{ANSI_OVERLAY}No sessions.{ANSI_RESET}");
+            return Ok(());
+        }
+        let states = self.detector.detect(&self.client, &windows);
+        let width = windows
+            .iter()
+            .map(|w| width::of(&w.name))
+            .max()
+            .unwrap_or(0);
+        for w in &windows {
+            let state = states.get(&w.index).copied().unwrap_or(WindowState::Fresh);
+            println!(
+                "  {}  {}{:<9}{ANSI_RESET}  {ANSI_OVERLAY}{}{ANSI_RESET}",
+                width::pad(&w.name, width),
+                state_color(state),
+                state.label(),
+                paths::tildify(&w.pane_path),
+            );
+        }
+        Ok(())
+    }
+
+    /// Wait for `name` to reach `until` (again, with `next`), until Esc.
+    fn wait(&mut self, name: &str, until: StateFilter, next: bool) -> Result<(), String> {
+        let tty = io::stdin().is_terminal();
+        if tty {
+            terminal::enable_raw_mode().map_err(|e| format!("raw mode: {e}"))?;
+        }
+        let result = self.wait_raw(name, until, next, tty);
+        if tty {
+            let _ = terminal::disable_raw_mode();
+        }
+        let state = result?;
+        say!("{ANSI_PEACH}{name}{ANSI_RESET} is {}", state.label());
+        Ok(())
+    }
+
+    fn wait_raw(
+        &mut self,
+        name: &str,
+        until: StateFilter,
+        next: bool,
+        tty: bool,
+    ) -> Result<WindowState, String> {
+        let mut armed = !next;
+        loop {
+            let Some((_, state)) = watch::current_state(&self.client, &mut self.detector, name)?
+            else {
+                return Err(format!("Session '{name}' is gone."));
+            };
+            let reached = kill::matches_filter(state, until);
+            if reached && armed {
+                return Ok(state);
+            }
+            armed |= !reached;
+            if !tty {
+                std::thread::sleep(WAIT_POLL);
+                continue;
+            }
+            if event::poll(WAIT_POLL).unwrap_or(false)
+                && let Ok(Event::Key(key)) = event::read()
+                && (key.code == KeyCode::Esc
+                    || (key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)))
+            {
+                return Err(format!("Stopped waiting: {name} is {}.", state.label()));
+            }
+        }
+    }
+
+    /// Run one command line. Ok(false) ends the console.
+    fn execute(&mut self, line: &str) -> Result<bool, String> {
+        let words = split_words(line);
+        let args: Vec<&str> = words.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            [] => {}
+            ["exit" | "quit"] => return Ok(false),
+            ["help"] => {
+                for (command, args, about) in COMMANDS {
+                    let usage = format!("{command} {args}");
+                    println!("  {ANSI_PEACH}{usage:<33}{ANSI_RESET}{about}");
+                }
+            }
+            ["list" | "ls"] => self.list()?,
+            ["send", name, text @ ..] if !text.is_empty() => {
+                let name = &self.client.resolve_target(name)?;
+                crate::commands::send::run_in(&self.client, name, &text.join(" "))?;
+                say!("Sent to {ANSI_PEACH}{name}{ANSI_RESET}");
+            }
+            ["kill", name] => {
+                let name = &self.client.resolve_target(name)?;
+                kill::run_in(&self.client, name)?;
+            }
+            ["wait", name, state, rest @ ..] if rest.is_empty() || rest == ["--next"] => {
+                let until = StateFilter::from_str(state, true)
+                    .map_err(|_| format!("unknown state {state}: {}", state_names().join(", ")))?;
+                let name = &self.client.resolve_target(name)?;
+                self.wait(name, until, !rest.is_empty())?;
+            }
+            [command, ..] => {
+                return Err(match COMMANDS.iter().find(|(c, _, _)| c == command) {
+                    Some((c, args, _)) => format!("usage: {c} {args}"),
+                    None => format!("unknown command {command} (help lists them)"),
+                });
+            }
+        }
+        Ok(true)
+    }
+
+    fn report(&mut self, line: &str) -> bool {
+        match self.execute(line) {
+            Ok(more) => more,
+            Err(e) => {
+                if !e.is_empty() {
+                    eprintln!("{e}");
+                }
+                true
+            }
+        }
+    }
+
+    /// Complete the word before the cursor, listing the options when
+    /// there's more than one.
+    fn complete(&self, editor: &mut Editor) -> io::Result<()> {
+        let before: String = editor.text[..editor.cursor].iter().collect();
+        let options = candidates(&before, &self.names());
+        let partial = before.rsplit(' ').next().unwrap_or_default();
+        let (insert, list) = match options.as_slice() {
+            [] => return Ok(()),
+            [only] => (format!("{only} "), false),
+            many => (common_prefix(many), true),
+        };
+        let added: Vec<char> = insert.chars().skip(partial.chars().count()).collect();
+        if list && added.is_empty() {
+            print!("\r\n{}\r\n", options.join("  "));
+        }
+        let n = added.len();
+        editor.text.splice(editor.cursor..editor.cursor, added);
+        editor.cursor += n;
+        Ok(())
+    }
+
+    /// The interactive loop: raw mode while typing, cooked while a command
+    /// runs so its output prints normally.
+    fn interact(&mut self) -> Result<(), String> {
+        let mut editor = Editor {
+            history: load_history(),
+            ..Editor::default()
+        };
+        println!(
+            "{ANSI_OVERLAY}cove console — help lists commands, Tab completes, Ctrl-D exits{ANSI_RESET}"
+        );
+        loop {
+            terminal::enable_raw_mode().map_err(|e| format!("raw mode: {e}"))?;
+            let step = loop {
+                draw(&editor).map_err(|e| e.to_string())?;
+                let key = read_key().map_err(|e| e.to_string())?;
+                match editor.key(key) {
+                    Step::Edit => {}
+                    Step::Complete => self.complete(&mut editor).map_err(|e| e.to_string())?,
+                    step => break step,
+                }
+            };
+            let _ = execute!(io::stdout(), crossterm::style::Print("\r\n"));
+            let _ = terminal::disable_raw_mode();
+            match step {
+                Step::Submit(line) => {
+                    if !line.trim().is_empty() {
+                        save_history(&line);
+                    }
+                    if !self.report(&line) {
+                        return Ok(());
+                    }
+                }
+                Step::Quit => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+// ── Public API ──
+
+/// Read and run cove commands until exit or end of input.
+pub fn run() -> Result<(), String> {
+    let mut console = Console {
+        client: ControlTmux::default(),
+        detector: StateDetector::new(),
+    };
+    if io::stdin().is_terminal() {
+        return console.interact();
+    }
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| format!("read: {e}"))?;
+        if !console.report(&line) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(editor: &mut Editor, code: KeyCode) -> Step {
+        editor.key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_editor_edits_and_recalls_history() {
+        let mut editor = Editor::default();
+        for c in "lxst".chars() {
+            press(&mut editor, KeyCode::Char(c));
+        }
+        press(&mut editor, KeyCode::Left);
+        press(&mut editor, KeyCode::Left);
+        press(&mut editor, KeyCode::Backspace);
+        press(&mut editor, KeyCode::Char('i'));
+        assert_eq!(editor.line(), "list");
+        assert_eq!(
+            press(&mut editor, KeyCode::Enter),
+            Step::Submit("list".to_string())
+        );
+
+        for c in "kill a".chars() {
+            press(&mut editor, KeyCode::Char(c));
+        }
+        press(&mut editor, KeyCode::Up);
+        assert_eq!(editor.line(), "list");
+        press(&mut editor, KeyCode::Down);
+        assert_eq!(editor.line(), "kill a");
+        let ctrl_w = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+        editor.key(ctrl_w);
+        assert_eq!(editor.line(), "kill ");
+    }
+
+    #[test]
+    fn test_candidates_by_position() {
+        let names = ["api".to_string(), "api-docs".to_string(), "web".to_string()];
+        assert_eq!(candidates("k", &names), ["kill"]);
+        assert_eq!(candidates("send a", &names), ["api", "api-docs"]);
+        assert_eq!(common_prefix(&candidates("send a", &names)), "api");
+        assert_eq!(candidates("wait web i", &names), ["idle"]);
+        assert!(candidates("list x", &names).is_empty());
+        assert_eq!(
+            split_words(r#"send api "fix the 'auth' test""#),
+            ["send", "api", "fix the 'auth' test"]
+        );
+    }
+}
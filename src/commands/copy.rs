@@ -0,0 +1,25 @@
+use crate::colors::*;
+use crate::{clips, say, tmux};
+
+// ── Public API ──
+
+/// Save `text` to a session's scratch register, or list the register if no text is given.
+pub fn run(name: &str, text: Option<&str>) -> Result<(), String> {
+    let name = &tmux::resolve_target(name)?;
+    match text {
+        Some(text) => {
+            clips::add(name, text)?;
+            say!("Saved to {ANSI_PEACH}{name}{ANSI_RESET}: {text}");
+        }
+        None => {
+            let saved = clips::saved(name);
+            if saved.is_empty() {
+                println!("{ANSI_OVERLAY}Nothing saved for {name}.{ANSI_RESET}");
+            }
+            for s in &saved {
+                println!("  {ANSI_WHITE}{s}{ANSI_RESET}");
+            }
+        }
+    }
+    Ok(())
+}
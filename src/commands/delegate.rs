@@ -0,0 +1,62 @@
+// ── cove delegate ──
+//
+// Turn delegate mode on for a session (see delegate.rs), `--off` to turn it
+// back off, or `--log` to see which questions were answered for it, by which
+// rule. The flag is kept in the session's metadata, so it survives restarts.
+
+use std::fmt::Write;
+
+use crate::colors::*;
+use crate::sidebar::format::format_age;
+use crate::{config, delegate, meta, pager, say, send, tmux};
+
+// ── Public API ──
+
+/// Turn delegate mode on or off for session `name`, or show its audit log.
+pub fn run(name: &str, off: bool, log: bool) -> Result<(), String> {
+    let name = &tmux::resolve_target(name)?;
+    if log {
+        let entries = delegate::entries(name);
+        if entries.is_empty() {
+            println!("{ANSI_OVERLAY}No questions answered for {name}.{ANSI_RESET}");
+            return Ok(());
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut out = String::new();
+        for entry in &entries {
+            let age = format_age(now.saturating_sub(entry.ts));
+            let outcome = if entry.outcome == "answered" {
+                String::new()
+            } else {
+                format!("  {ANSI_RED}not answered: {}{ANSI_RESET}", entry.outcome)
+            };
+            let _ = writeln!(
+                out,
+                "  {ANSI_OVERLAY}{age:>4}{ANSI_RESET}  {ANSI_SUBTEXT}{}{ANSI_RESET}\n        \
+                 \u{2192} {ANSI_GREEN}{}{ANSI_RESET} {ANSI_OVERLAY}({}){ANSI_RESET}{outcome}",
+                entry.question, entry.answer, entry.rule
+            );
+        }
+        pager::show(&out);
+        return Ok(());
+    }
+
+    meta::set_delegate(name, !off)?;
+    if off {
+        say!("Delegate mode off for {ANSI_PEACH}{name}{ANSI_RESET}");
+        return Ok(());
+    }
+    say!("Delegate mode on for {ANSI_PEACH}{name}{ANSI_RESET}");
+    let config = config::load().unwrap_or_default();
+    if config.delegate.rules.is_empty() {
+        say!(
+            "{ANSI_OVERLAY}No delegate.rules in the config yet, so nothing is answered.{ANSI_RESET}"
+        );
+    } else if send::disabled_by(&config) {
+        say!("{ANSI_OVERLAY}Safe mode is on, so nothing is answered.{ANSI_RESET}");
+    }
+    Ok(())
+}
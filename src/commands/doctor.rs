@@ -0,0 +1,184 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+use crate::colors::*;
+use crate::commands::{init, merge};
+use crate::tmux::{Tmux, TmuxClient};
+use crate::{config, paths, quiet};
+
+// ── Helpers ──
+
+fn ok(label: &str, detail: &str) {
+    println!("  {ANSI_GREEN}\u{2713}{ANSI_RESET} {label} {ANSI_OVERLAY}{detail}{ANSI_RESET}");
+}
+
+fn problem(label: &str, detail: &str) {
+    println!("  {ANSI_RED}\u{2717}{ANSI_RESET} {label} {ANSI_OVERLAY}{detail}{ANSI_RESET}");
+}
+
+fn tmux_version() -> Option<String> {
+    let out = Tmux.output(&["-V"]).ok()?;
+    Some(out.trim().to_string())
+}
+
+fn confirm(question: &str) -> bool {
+    if quiet::on() || !io::stdin().is_terminal() {
+        return false;
+    }
+    print!("{question} [Y/n] ");
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    if io::stdin().lock().read_line(&mut input).is_err() {
+        return false;
+    }
+    let answer = input.trim().to_lowercase();
+    answer.is_empty() || answer == "y" || answer == "yes"
+}
+
+/// Install cove hooks into each of `files`. Returns true if all succeeded.
+fn install(files: &[PathBuf]) -> bool {
+    let mut all = true;
+    for file in files {
+        let display = paths::tildify(&file.to_string_lossy());
+        match init::install_hooks(file) {
+            Ok(()) => ok("hooks", &format!("installed in {display}")),
+            Err(e) => {
+                problem("hooks", &e);
+                all = false;
+            }
+        }
+    }
+    all
+}
+
+/// Check the hooks of every scope (user settings, and the project in the
+/// current directory) against the installed Claude version, offering to
+/// reinstall. Returns true if they're fine (or were fixed).
+fn check_hooks() -> bool {
+    let Some(path) = paths::claude_settings() else {
+        problem(
+            "hooks",
+            "cannot locate Claude settings (set HOME or CLAUDE_CONFIG_DIR)",
+        );
+        return false;
+    };
+
+    let version = init::claude_version();
+    match version {
+        Some((major, minor, patch)) => ok("claude", &format!("{major}.{minor}.{patch}")),
+        None => problem("claude", "not found on PATH"),
+    }
+
+    let project = std::env::current_dir()
+        .ok()
+        .map(|dir| init::project_root(&dir));
+    let installed: Vec<PathBuf> = init::settings_files(project.as_deref())
+        .into_iter()
+        .filter(|p| init::has_any_hooks(p))
+        .collect();
+    if installed.is_empty() {
+        let display = paths::tildify(&path.to_string_lossy());
+        problem("hooks", &format!("not installed in {display}"));
+        if !confirm("Install cove hooks?") {
+            println!(
+                "  Run {ANSI_PEACH}cove init{ANSI_RESET} (or {ANSI_PEACH}cove init --project{ANSI_RESET}) to install them."
+            );
+            return false;
+        }
+        return install(&[path]);
+    }
+
+    let mut broken = Vec::new();
+    for file in &installed {
+        let display = paths::tildify(&file.to_string_lossy());
+        let issues = init::check_hooks(file, version);
+        if issues.is_empty() {
+            ok("hooks", &display);
+            continue;
+        }
+        problem(
+            "hooks",
+            &format!("{} problem(s) in {display}", issues.len()),
+        );
+        for issue in &issues {
+            println!("      {ANSI_SUBTEXT}{issue}{ANSI_RESET}");
+        }
+        broken.push(file.clone());
+    }
+    // Claude runs the hooks of every scope
+    let single = installed.len() == 1;
+    if !single {
+        problem(
+            "hooks",
+            &format!(
+                "installed in {} scopes; each event is recorded more than once (keep one)",
+                installed.len()
+            ),
+        );
+    }
+    if broken.is_empty() {
+        return single;
+    }
+    if !confirm("Reinstall cove hooks?") {
+        println!("  Run {ANSI_PEACH}cove init{ANSI_RESET} to reinstall them.");
+        return false;
+    }
+    install(&broken) && single
+}
+
+// ── Public API ──
+
+/// Check that tmux, the state dir, the config and Claude's hooks are usable.
+pub fn run() -> Result<(), String> {
+    let mut failures = 0;
+
+    match tmux_version() {
+        Some(v) => ok("tmux", &v),
+        None => {
+            problem("tmux", "not found on PATH");
+            failures += 1;
+        }
+    }
+
+    // A server that isn't running has no stray sessions to report
+    if let Ok(sessions) = Tmux.list_sessions() {
+        let strays = merge::strays(&sessions);
+        if strays.is_empty() {
+            ok("sessions", "no stray cove sessions");
+        } else {
+            problem(
+                "sessions",
+                &format!(
+                    "{} also named like cove's, hidden from the sidebar (cove merge)",
+                    strays.join(", ")
+                ),
+            );
+            failures += 1;
+        }
+    }
+
+    match paths::writable_cove_home() {
+        Some(dir) => ok("state", &paths::tildify(&dir.to_string_lossy())),
+        None => {
+            problem("state", "no writable state dir (set COVE_HOME)");
+            failures += 1;
+        }
+    }
+
+    match config::load() {
+        Ok(_) => ok("config", "ok"),
+        Err(e) => {
+            problem("config", &e);
+            failures += 1;
+        }
+    }
+
+    if !check_hooks() {
+        failures += 1;
+    }
+
+    if failures > 0 {
+        return Err(format!("{failures} check(s) failed"));
+    }
+    Ok(())
+}
@@ -0,0 +1,249 @@
+// ── cove du ──
+//
+// Disk used by the worktrees sessions run in. Each parallel agent gets its
+// own checkout, build directory and dependencies included, so they fill a
+// disk without anyone noticing. `cove du` lists every linked worktree of the
+// repositories cove sessions use, with the session it belongs to: a live
+// one (a window or tab runs inside it), a killed one (still in the trash, or
+// recorded in meta.json by `cove adopt-branch`), or none. `--prune` removes
+// the worktrees of killed sessions, keeping any with uncommitted changes.
+// Main checkouts aren't listed: removing one isn't cove's call.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use crate::colors::*;
+use crate::commands::top;
+use crate::{git, meta, paths, say, tabs, tmux, trash};
+
+// ── Types ──
+
+/// Whose a worktree is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Owner {
+    /// A session runs in it.
+    Live(String),
+    /// Its session was killed.
+    Killed(String),
+    /// No cove session has used it.
+    Other,
+}
+
+struct Worktree {
+    path: String,
+    /// The repository's main worktree.
+    repo: String,
+    owner: Owner,
+    bytes: u64,
+}
+
+// ── Helpers ──
+
+/// Bytes allocated under `path`, not following symlinks.
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    let own = meta.blocks() * 512;
+    if !meta.is_dir() {
+        return own;
+    }
+    let children: u64 = fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| disk_usage(&e.path())).sum())
+        .unwrap_or(0);
+    own + children
+}
+
+/// The session worktree `path` belongs to: a live session whose directory is
+/// inside it, else a killed one's. Sessions are (name, directory) pairs.
+fn owner(path: &str, live: &[(String, String)], killed: &[(String, String)]) -> Owner {
+    let inside = |(_, dir): &&(String, String)| Path::new(dir).starts_with(path);
+    if let Some((name, _)) = live.iter().find(inside) {
+        return Owner::Live(name.clone());
+    }
+    match killed.iter().find(inside) {
+        Some((name, _)) => Owner::Killed(name.clone()),
+        None => Owner::Other,
+    }
+}
+
+/// Linked worktrees of every repository a live or killed session used.
+fn gather() -> Vec<Worktree> {
+    let mut live: Vec<(String, String)> = tmux::list_windows()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|w| (w.name, w.pane_path))
+        .collect();
+    live.extend(tabs::sessions().into_iter().map(|t| (t.name, t.dir)));
+    let live_names: HashSet<&str> = live.iter().map(|(name, _)| name.as_str()).collect();
+    let mut killed: Vec<(String, String)> = trash::entries()
+        .into_iter()
+        .map(|e| (e.name, e.dir))
+        .collect();
+    killed.extend(meta::worktrees());
+    // A live session's recorded worktree isn't up for pruning
+    killed.retain(|(name, _)| !live_names.contains(name.as_str()));
+
+    let repos: BTreeSet<String> = live
+        .iter()
+        .chain(&killed)
+        .filter_map(|(_, dir)| git::main_worktree(Path::new(dir)))
+        .collect();
+    let mut worktrees: Vec<Worktree> = repos
+        .iter()
+        .flat_map(|repo| {
+            git::worktrees(Path::new(repo))
+                .into_iter()
+                .skip(1)
+                .map(move |path| (repo, path))
+        })
+        .map(|(repo, path)| Worktree {
+            owner: owner(&path, &live, &killed),
+            bytes: disk_usage(Path::new(&path)),
+            repo: repo.clone(),
+            path,
+        })
+        .collect();
+    worktrees.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+    worktrees
+}
+
+fn size(bytes: u64) -> String {
+    top::format_memory(bytes / 1024)
+}
+
+/// One line per worktree, biggest first, then the total.
+fn render(worktrees: &[Worktree]) -> String {
+    if worktrees.is_empty() {
+        return format!("{ANSI_OVERLAY}No session worktrees.{ANSI_RESET}\n");
+    }
+    let mut out = String::new();
+    for wt in worktrees {
+        let owner = match &wt.owner {
+            Owner::Live(name) => format!("{ANSI_PEACH}{name}{ANSI_RESET}"),
+            Owner::Killed(name) => format!("{ANSI_OVERLAY}killed: {name}{ANSI_RESET}"),
+            Owner::Other => String::new(),
+        };
+        out.push_str(&format!(
+            "{ANSI_WHITE}{:>7}{ANSI_RESET}  {}  {owner}\n",
+            size(wt.bytes),
+            paths::tildify(&wt.path)
+        ));
+    }
+    let total: u64 = worktrees.iter().map(|wt| wt.bytes).sum();
+    let killed: u64 = worktrees
+        .iter()
+        .filter(|wt| matches!(wt.owner, Owner::Killed(_)))
+        .map(|wt| wt.bytes)
+        .sum();
+    out.push_str(&format!(
+        "{ANSI_WHITE}{:>7}{ANSI_RESET}  total",
+        size(total)
+    ));
+    if killed > 0 {
+        out.push_str(&format!(
+            " {ANSI_OVERLAY}({} from killed sessions; cove du --prune removes them){ANSI_RESET}",
+            size(killed)
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+/// Remove the worktrees of killed sessions. Returns the bytes freed.
+fn prune(worktrees: &[Worktree]) -> u64 {
+    let mut freed = 0;
+    for wt in worktrees {
+        let Owner::Killed(name) = &wt.owner else {
+            continue;
+        };
+        let shown = paths::tildify(&wt.path);
+        if git::is_dirty(Path::new(&wt.path)) {
+            say!("{ANSI_OVERLAY}Kept {shown}: uncommitted changes{ANSI_RESET}");
+            continue;
+        }
+        match git::run(Path::new(&wt.repo), &["worktree", "remove", &wt.path]) {
+            Ok(_) => {
+                freed += wt.bytes;
+                let _ = meta::update(name, |m| {
+                    if m.worktree.as_deref() == Some(wt.path.as_str()) {
+                        m.worktree = None;
+                    }
+                });
+                say!("{ANSI_GREEN}Removed{ANSI_RESET} {shown}");
+            }
+            Err(e) => println!("{ANSI_RED}Kept {shown}: {e}{ANSI_RESET}"),
+        }
+    }
+    freed
+}
+
+// ── Public API ──
+
+/// Print session worktrees and their size, then with `prune_killed` remove
+/// the killed sessions' ones.
+pub fn run(prune_killed: bool) -> Result<(), String> {
+    let worktrees = gather();
+    print!("{}", render(&worktrees));
+    if prune_killed {
+        let freed = prune(&worktrees);
+        say!("Freed {ANSI_WHITE}{}{ANSI_RESET}", size(freed));
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, dir: &str) -> (String, String) {
+        (name.to_string(), dir.to_string())
+    }
+
+    #[test]
+    fn test_owner_prefers_live_sessions() {
+        let live = [session("api-7", "/code/api-7/src")];
+        let killed = [
+            session("old", "/code/api-7"),
+            session("fix-9", "/code/api-9"),
+        ];
+        assert_eq!(
+            owner("/code/api-7", &live, &killed),
+            Owner::Live("api-7".into())
+        );
+        assert_eq!(
+            owner("/code/api-9", &live, &killed),
+            Owner::Killed("fix-9".into())
+        );
+        // A path prefix alone isn't a match
+        assert_eq!(owner("/code/api-", &live, &killed), Owner::Other);
+    }
+
+    #[test]
+    fn test_disk_usage_and_render() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("target/big"), vec![1u8; 64 * 1024]).unwrap();
+        let bytes = disk_usage(dir.path());
+        assert!(bytes >= 64 * 1024, "{bytes}");
+
+        let worktree = |path: &str, owner, bytes| Worktree {
+            path: path.to_string(),
+            repo: "/code/api".to_string(),
+            owner,
+            bytes,
+        };
+        let out = render(&[
+            worktree("/code/api-7", Owner::Live("api-7".into()), 3 << 30),
+            worktree("/code/api-9", Owner::Killed("fix-9".into()), 512 << 20),
+        ]);
+        assert!(out.contains("3.0G"), "{out}");
+        assert!(out.contains("killed: fix-9"), "{out}");
+        assert!(out.contains("3.5G"), "{out}");
+        assert!(out.contains("512M from killed sessions"), "{out}");
+    }
+}
@@ -0,0 +1,53 @@
+use crate::tmux::{Tmux, TmuxClient};
+
+pub fn run(name: Option<&str>) -> Result<(), String> {
+    run_in(&Tmux, name)
+}
+
+fn run_in(client: &dyn TmuxClient, name: Option<&str>) -> Result<(), String> {
+    if !client.has_session() {
+        return Err("No active cove session.".to_string());
+    }
+    // Without a name (e.g. from the `prefix F` binding), act on the window the user is in
+    let name = match name {
+        Some(n) => client.resolve_target(n)?,
+        None => client.current_window_name()?,
+    };
+    client.toggle_focus(&name)?;
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_focus_zooms_and_remembers() {
+        let fake = FakeTmux::new().on("display-message", "0");
+        run_in(&fake, Some("api")).unwrap();
+
+        let resize = fake.calls_to("resize-pane");
+        assert_eq!(resize, [["resize-pane", "-Z", "-t", "cove:api.1"]]);
+        let set = fake.calls_to("set-option");
+        assert_eq!(
+            set,
+            [["set-option", "-w", "-t", "cove:api", "@cove-focus", "1"]]
+        );
+    }
+
+    #[test]
+    fn test_focus_again_unzooms_and_forgets() {
+        let fake = FakeTmux::new().on("display-message", "1");
+        run_in(&fake, Some("api")).unwrap();
+
+        assert!(fake.calls_to("select-pane").is_empty());
+        let set = fake.calls_to("set-option");
+        assert_eq!(
+            set,
+            [["set-option", "-w", "-t", "cove:api", "-u", "@cove-focus"]]
+        );
+    }
+}
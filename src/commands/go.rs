@@ -0,0 +1,74 @@
+// ── cove go ──
+//
+// `cove go 3` jumps to the session in quick-switch slot 3, wherever tmux has
+// put its window: the sidebar shows each session's slot before its name, and
+// inside the cove session `prefix 3` runs `cove go 3`. A slot nobody holds
+// falls back to window 3, which is what the key does without cove.
+
+use std::collections::HashMap;
+
+use crate::colors::*;
+use crate::meta;
+use crate::tmux::{self, Tmux, TmuxClient};
+
+// ── Helpers ──
+
+fn run_in(
+    client: &dyn TmuxClient,
+    slot: u8,
+    slots: impl FnOnce(&[String]) -> HashMap<String, u8>,
+    inside_tmux: bool,
+) -> Result<String, String> {
+    if !client.has_session() {
+        return Err(format!(
+            "{ANSI_OVERLAY}No active cove session.{ANSI_RESET} Run {ANSI_PEACH}cove{ANSI_RESET} to create one."
+        ));
+    }
+    let mut windows = client.list_windows()?;
+    windows.sort_by_key(|w| w.index);
+    let managed: Vec<String> = windows
+        .iter()
+        .filter(|w| w.managed)
+        .map(|w| w.name.clone())
+        .collect();
+    let slots = slots(&managed);
+    let win = windows
+        .iter()
+        .find(|w| slots.get(&w.name) == Some(&slot))
+        .or_else(|| windows.iter().find(|w| w.index == u32::from(slot)))
+        .ok_or(format!("No session in slot {slot}"))?;
+    client.select_window(win.index)?;
+    if !inside_tmux {
+        client.attach()?;
+    }
+    Ok(win.name.clone())
+}
+
+// ── Public API ──
+
+/// Switch to the session in quick-switch `slot`.
+pub fn run(slot: u8) -> Result<(), String> {
+    run_in(&Tmux, slot, meta::assign_slots, tmux::is_inside_tmux()).map(|_| ())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_go_follows_slot_then_window_index() {
+        let fake = FakeTmux::new().with_windows(&[
+            ("api", "claude", "%t1"),
+            ("web", "claude", "%t2"),
+            ("docs", "claude", "%t3"),
+        ]);
+        let slots = |_: &[String]| HashMap::from([("docs".to_string(), 1), ("api".to_string(), 4)]);
+        assert_eq!(run_in(&fake, 1, slots, true).unwrap(), "docs");
+        assert_eq!(run_in(&fake, 2, slots, true).unwrap(), "web");
+        assert!(run_in(&fake, 7, slots, true).is_err());
+        assert!(fake.calls_to("attach").is_empty());
+    }
+}
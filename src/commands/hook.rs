@@ -4,18 +4,70 @@
 // Reads JSON from stdin, determines state, appends to ~/.cove/events/{session_id}.jsonl.
 //
 // Hook → state mapping:
-//   UserPromptSubmit           → working
+//   UserPromptSubmit           → working (or queued, see quota.rs); the
+//                                prompt is logged for `cove prompts`
+//                                (see prompts.rs)
 //   PreToolUse(AskUserQuestion)  → asking
 //   PostToolUse(AskUserQuestion) → working
 //   Stop                       → idle
+//
+// Prompts and finished Working stretches are also counted for `cove stats`
+// (see stats.rs). A prompt also records Claude's session id against the pane
+// in the saved sessions, for `cove restore` (see sessions.rs).
+//
+// `--events-dir` (written into the hook commands by `cove init`) pins where
+// events go when Claude runs hooks with a different $HOME than the sidebar.
+//
+// Each line also names the hook that wrote it, so the sidebar can pair
+// ask/ask-done events and count questions still waiting for an answer.
+//
+// With `window_status.enabled` in the config, the state glyph is also set on
+// the tmux window so it shows in the standard window list. With
+// `window_status.pane_title`, the Claude pane's `@cove-title` option says
+// the state and since when ("▶ working since 14:03"), for tmux's pane borders
+// (`pane-border-format`). It's an option rather than the pane title because
+// Claude sets that itself. A title is only written when a hook fires, so it
+// names a start time rather than a running count. SessionEnd clears it when
+// Claude exits, but not for `/clear`, which ends a session and carries on.
+//
+// PostToolUse(Edit|Write|…) doesn't change state; it records the edited file
+// for `cove changes` (see changes.rs); PostToolUse(Bash) logs the command for
+// `cove cmds` (see cmdlog.rs). Pre/PostToolUse(*) record tool activity
+// for the sidebar (see tools.rs).
+//
+// Entering asking or idle may also push an alert, per `notify.rules` (see
+// notify.rs): to Slack, Discord or Pushover, or a desktop notification naming
+// the session and its directory, at most once per `notify.throttle_secs`.
+// SessionEnd journals the finished session in the daily note, with
+// `journal.path` set (see journal.rs), unless `/clear` fired it.
+//
+// Ask may also answer the question by rule, in a session with delegate mode
+// on (see delegate.rs).
+//
+// Stop also snapshots the session's working tree, labeled with the turn's
+// prompt, for `cove rollback` (see snapshots.rs).
+//
+// With $COVE_HOOK_PROBE set (by `cove verify-hooks --fire-test`), a hook only
+// appends its name and tool to `<events>/<probe>.probe` and does nothing else,
+// so the test proves events arrive without touching any session.
 
 use std::fs::{self, OpenOptions};
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::cli::HookEvent;
+use crate::commands::timesheet;
+use crate::config::{self, Config, NotifyConfig, SendConfig, WindowStatusConfig};
+use crate::sidebar::format::format_age;
+use crate::sidebar::state::{self, EventEntry};
+use crate::tmux::{Tmux, TmuxClient};
+use crate::{
+    changes, cmdlog, delegate, journal, notify, paths, prompts, quota, send, sessions, snapshots,
+    stats, tools,
+};
 
 // ── Types ──
 
@@ -23,19 +75,102 @@ use crate::cli::HookEvent;
 struct HookInput {
     session_id: String,
     cwd: String,
+    /// Present on PreToolUse/PostToolUse hooks.
+    #[serde(default)]
+    tool_name: String,
+    #[serde(default)]
+    tool_input: Value,
+    /// Present on PostToolUse hooks.
+    #[serde(default)]
+    tool_response: Value,
+    /// Present on UserPromptSubmit hooks.
+    #[serde(default)]
+    prompt: String,
+    /// Present on SessionEnd hooks: why the session ended ("clear" for
+    /// `/clear`, which keeps Claude running).
+    #[serde(default)]
+    reason: String,
 }
 
+/// One archived hook invocation (see `--capture-dir`).
+#[derive(Serialize, Deserialize)]
+struct Capture {
+    event: String,
+    pane_id: String,
+    ts_ms: u64,
+    /// Raw stdin, kept as text so malformed payloads are preserved too.
+    stdin: String,
+}
+
+// ── Constants ──
+
+/// Set by `cove verify-hooks --fire-test` to the probe hooks report to.
+pub const PROBE_ENV: &str = "COVE_HOOK_PROBE";
+
 // ── Helpers ──
 
-fn events_dir() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_default();
-    PathBuf::from(home).join(".cove").join("events")
+fn event_name(event: &HookEvent) -> &'static str {
+    match event {
+        HookEvent::UserPrompt => "user-prompt",
+        HookEvent::Stop => "stop",
+        HookEvent::Ask => "ask",
+        HookEvent::AskDone => "ask-done",
+        HookEvent::FileEdit => "file-edit",
+        HookEvent::Command => "command",
+        HookEvent::PreTool => "pre-tool",
+        HookEvent::PostTool => "post-tool",
+        HookEvent::SessionEnd => "session-end",
+        HookEvent::Replay { .. } => "replay",
+        HookEvent::Answer { .. } => "answer",
+    }
 }
 
-/// Append a state event to the session's event file.
-fn write_event(session_id: &str, cwd: &str, pane_id: &str, state: &str) -> Result<(), String> {
-    let dir = events_dir();
-    fs::create_dir_all(&dir).map_err(|e| format!("create events dir: {e}"))?;
+fn event_from_name(name: &str) -> Option<HookEvent> {
+    Some(match name {
+        "user-prompt" => HookEvent::UserPrompt,
+        "stop" => HookEvent::Stop,
+        "ask" => HookEvent::Ask,
+        "ask-done" => HookEvent::AskDone,
+        "file-edit" => HookEvent::FileEdit,
+        "command" => HookEvent::Command,
+        "pre-tool" => HookEvent::PreTool,
+        "post-tool" => HookEvent::PostTool,
+        "session-end" => HookEvent::SessionEnd,
+        _ => return None,
+    })
+}
+
+/// Archive a hook's raw stdin as `<dir>/<ms>-<event>.json`.
+fn capture_in(dir: &Path, event: &str, pane_id: &str, stdin: &str) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    let ts_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let capture = Capture {
+        event: event.to_string(),
+        pane_id: pane_id.to_string(),
+        ts_ms,
+        stdin: stdin.to_string(),
+    };
+    let content =
+        serde_json::to_string_pretty(&capture).map_err(|e| format!("serialize capture: {e}"))?;
+    // Same-millisecond hooks (pre-tool + ask) get distinct names via the pid
+    let path = dir.join(format!("{ts_ms}-{}-{event}.json", std::process::id()));
+    fs::write(&path, content).map_err(|e| format!("write {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+/// Append a state event to the session's event file. `hook` names the hook
+/// that fired, so ask/ask-done pairs can be matched up.
+pub fn write_event(
+    session_id: &str,
+    cwd: &str,
+    pane_id: &str,
+    state: &str,
+    hook: &str,
+) -> Result<(), String> {
+    let dir = paths::ensure_events_dir()?;
 
     let path = dir.join(format!("{session_id}.jsonl"));
     let mut file = OpenOptions::new()
@@ -49,16 +184,44 @@ fn write_event(session_id: &str, cwd: &str, pane_id: &str, state: &str) -> Resul
         .unwrap_or_default()
         .as_secs();
 
-    let line = format!(r#"{{"state":"{state}","cwd":"{cwd}","pane_id":"{pane_id}","ts":{ts}}}"#);
+    let line = format!(
+        r#"{{"state":"{state}","cwd":"{cwd}","pane_id":"{pane_id}","ts":{ts},"hook":"{hook}"}}"#
+    );
     writeln!(file, "{line}").map_err(|e| format!("write event: {e}"))?;
+    log::debug!("{hook}: {state} for pane {pane_id} (session {session_id})");
 
     Ok(())
 }
 
+/// Note that hook `event` fired for `tool` in `dir`'s probe file.
+fn probe_in(dir: &Path, probe: &str, event: &str, tool: &str) -> Result<(), String> {
+    // The probe names a file, so nothing that could leave the directory
+    if probe.is_empty() || !probe.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("invalid {PROBE_ENV} '{probe}'"));
+    }
+    let path = probe_file(dir, probe);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("open {}: {e}", path.display()))?;
+    let line = serde_json::json!({"hook": event, "tool": tool});
+    writeln!(file, "{line}").map_err(|e| format!("write {}: {e}", path.display()))
+}
+
 /// Check if the session's event file contains at least one "working" entry,
 /// proving the user has submitted a prompt in this session.
 fn has_working_event(session_id: &str) -> bool {
-    has_working_event_in(session_id, &events_dir())
+    paths::events_dir().is_some_and(|dir| has_working_event_in(session_id, &dir))
+}
+
+/// Path of the file an edit tool touched, made absolute against the hook's cwd.
+fn edited_file(hook: &HookInput) -> Option<String> {
+    let file = hook.tool_input["file_path"]
+        .as_str()
+        .or_else(|| hook.tool_input["notebook_path"].as_str())?;
+    let path = Path::new(&hook.cwd).join(file);
+    Some(path.to_string_lossy().to_string())
 }
 
 fn has_working_event_in(session_id: &str, dir: &Path) -> bool {
@@ -72,21 +235,358 @@ fn has_working_event_in(session_id: &str, dir: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Unix time the session's current turn began: its latest submitted prompt.
+fn turn_started_in(session_id: &str, dir: &Path) -> Option<u64> {
+    let content = fs::read_to_string(dir.join(format!("{session_id}.jsonl"))).ok()?;
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<EventEntry>(line).ok())
+        .find(|event| event.hook == "user-prompt")
+        .map(|event| event.ts)
+}
+
+/// Unix time the session's current Working stretch began, if its latest
+/// event is Working. Milestones repeat the state, so they carry it on.
+fn working_since_in(session_id: &str, dir: &Path) -> Option<u64> {
+    let content = fs::read_to_string(dir.join(format!("{session_id}.jsonl"))).ok()?;
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<EventEntry>(line).ok())
+        .take_while(|event| event.state == "working")
+        .last()
+        .map(|event| event.ts)
+}
+
+/// Push the alerts `notify.rules` ask for. Delivery failures only go to
+/// stderr so Claude never sees them.
+fn send_alerts(
+    client: &dyn TmuxClient,
+    config: &NotifyConfig,
+    session_id: &str,
+    pane_id: &str,
+    cwd: &str,
+    state: &str,
+) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let turn_secs = paths::events_dir()
+        .and_then(|dir| turn_started_in(session_id, &dir))
+        .map(|ts| now.saturating_sub(ts));
+    let backends = notify::backends(config, state, turn_secs);
+    if backends.is_empty() {
+        return;
+    }
+    let name = session_name(client, pane_id, cwd);
+    if !notify::due(&name, state, config.throttle_secs) {
+        return;
+    }
+    let text = notify::message(&name, state, turn_secs);
+    for backend in backends {
+        if let Err(e) = notify::send(config, backend, &text, Some(cwd)) {
+            log::warn!("{e}");
+        }
+    }
+}
+
+/// Name of the tmux window holding `pane_id`, if tmux knows it.
+fn window_name(client: &dyn TmuxClient, pane_id: &str) -> Option<String> {
+    client
+        .output(&["display-message", "-t", pane_id, "-p", "#{window_name}"])
+        .map(|out| out.trim().to_string())
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
+/// The session's window name, or its directory's outside tmux.
+fn session_name(client: &dyn TmuxClient, pane_id: &str, cwd: &str) -> String {
+    window_name(client, pane_id).unwrap_or_else(|| {
+        let dir = Path::new(cwd).file_name().unwrap_or_default();
+        dir.to_string_lossy().to_string()
+    })
+}
+
+/// Identify the pane Claude runs in: `$TMUX_PANE`, falling back to `$COVE_PANE`.
+fn pane_id_from_env() -> String {
+    ["TMUX_PANE", "COVE_PANE"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty())
+        .unwrap_or_default()
+}
+
+/// Mirror the state into the tmux window list, if enabled. Only tmux panes
+/// (`%N`) have a window to mark; failures are ignored so Claude never sees them.
+fn show_window_state(
+    client: &dyn TmuxClient,
+    config: &WindowStatusConfig,
+    pane_id: &str,
+    state: &str,
+) {
+    if !config.enabled || !pane_id.starts_with('%') {
+        return;
+    }
+    if let Some(glyph) = config.glyph(state) {
+        let _ = client.set_window_state(pane_id, glyph, &config.format);
+    }
+}
+
+/// Pane title for `state` entered at local time of day `clock` (seconds
+/// since midnight), after a turn of `turn_secs` when that's known.
+fn pane_title(
+    config: &WindowStatusConfig,
+    state: &str,
+    clock: u64,
+    turn_secs: Option<u64>,
+) -> String {
+    let label = state::state_from_str(state).label();
+    let glyph = config.glyph(state).unwrap_or_default();
+    let mut title = format!(
+        "{glyph} {label} since {:02}:{:02}",
+        clock / 3600,
+        clock / 60 % 60
+    );
+    if state == "idle"
+        && let Some(secs) = turn_secs.filter(|s| *s > 0)
+    {
+        title.push_str(&format!(" ({} turn)", format_age(secs)));
+    }
+    title.trim_start().to_string()
+}
+
+/// Title the Claude pane with its state, if enabled. Failures are ignored so
+/// Claude never sees them.
+fn show_pane_title(
+    client: &dyn TmuxClient,
+    config: &WindowStatusConfig,
+    session_id: &str,
+    pane_id: &str,
+    state: &str,
+) {
+    if !config.pane_title || !pane_id.starts_with('%') {
+        return;
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let clock = (now as i64 + timesheet::utc_offset()).rem_euclid(86_400) as u64;
+    let turn_secs = paths::events_dir()
+        .and_then(|dir| turn_started_in(session_id, &dir))
+        .map(|ts| now.saturating_sub(ts));
+    let title = pane_title(config, state, clock, turn_secs);
+    let _ = client.set_pane_title(pane_id, &title);
+}
+
+/// Hold the prompt back when `limit` other sessions are already working.
+/// Returns the reason to give Claude for blocking it.
+fn queue_prompt(
+    client: &dyn TmuxClient,
+    limit: u32,
+    hook: &HookInput,
+    pane_id: &str,
+) -> Result<Option<String>, String> {
+    if limit == 0 || hook.prompt.trim().is_empty() || !pane_id.starts_with('%') {
+        return Ok(None);
+    }
+    let live = quota::live_panes(client);
+    let working = quota::working_elsewhere(&live, pane_id);
+    if working < limit as usize {
+        return Ok(None);
+    }
+    quota::enqueue(pane_id, &hook.session_id, &hook.prompt)?;
+    Ok(Some(format!(
+        "Queued by cove: {working} sessions are already working (limit {limit}). \
+         The prompt will be sent when one of them finishes."
+    )))
+}
+
+/// A session finished its turn: send the oldest queued prompt, if a slot is free.
+fn release_queued(client: &dyn TmuxClient, limit: u32, send: &SendConfig) {
+    if limit == 0 {
+        return;
+    }
+    let live = quota::live_panes(client);
+    if quota::working_elsewhere(&live, "") >= limit as usize {
+        return;
+    }
+    if let Some(next) = quota::pop(&live) {
+        let _ = send::deliver_with(client, send, &next.pane_id, &next.prompt);
+    }
+}
+
 // ── Public API ──
 
-pub fn run(event: HookEvent) -> Result<(), String> {
+/// Where hooks fired with probe `probe` report, in events dir `dir`.
+pub fn probe_file(dir: &Path, probe: &str) -> PathBuf {
+    dir.join(format!("{probe}.probe"))
+}
+
+pub fn run(
+    event: HookEvent,
+    capture_dir: Option<&Path>,
+    events_dir: Option<PathBuf>,
+) -> Result<(), String> {
+    if let Some(dir) = events_dir {
+        paths::set_events_root(dir);
+    }
+    let config = config::load().unwrap_or_default();
+    if let HookEvent::Replay { file } = &event {
+        return replay(file, &config);
+    }
+    if let HookEvent::Answer {
+        session,
+        pane,
+        rule,
+        question,
+        label,
+        key,
+    } = event
+    {
+        let answer = delegate::Answer {
+            rule,
+            question,
+            label,
+            key,
+        };
+        delegate::press(&Tmux, &session, &pane, &answer);
+        return Ok(());
+    }
+
     let mut input = String::new();
     io::stdin()
         .read_to_string(&mut input)
         .map_err(|e| format!("read stdin: {e}"))?;
+    if let Some(probe) = std::env::var(PROBE_ENV).ok().filter(|p| !p.is_empty()) {
+        // Parsed all the same, so a payload cove can't read fails the test
+        let hook: HookInput =
+            serde_json::from_str(&input).map_err(|e| format!("parse hook input: {e}"))?;
+        let dir = paths::ensure_events_dir()?;
+        return probe_in(&dir, &probe, event_name(&event), &hook.tool_name);
+    }
+    let pane_id = pane_id_from_env();
+
+    // Debug captures must never get in Claude's way, so failures are dropped
+    let capture_dir = capture_dir.map(Path::to_path_buf).or_else(|| {
+        let dir = config.hooks.capture_dir.as_deref()?;
+        Some(paths::expand_tilde(dir))
+    });
+    if let Some(dir) = capture_dir {
+        let _ = capture_in(&dir, event_name(&event), &pane_id, &input);
+    }
+
+    handle(event, &input, &pane_id, &config)
+}
 
+/// Re-run a payload saved by `--capture-dir` as if its hook had just fired.
+fn replay(file: &Path, config: &Config) -> Result<(), String> {
+    let content = fs::read_to_string(file).map_err(|e| format!("read {}: {e}", file.display()))?;
+    let capture: Capture =
+        serde_json::from_str(&content).map_err(|e| format!("parse {}: {e}", file.display()))?;
+    let event =
+        event_from_name(&capture.event).ok_or(format!("unknown hook event '{}'", capture.event))?;
+    eprintln!(
+        "Replaying {} from pane {} (captured at {} ms)",
+        capture.event, capture.pane_id, capture.ts_ms
+    );
+    handle(event, &capture.stdin, &capture.pane_id, config)
+}
+
+/// Run one hook invocation: `input` is the JSON Claude wrote to stdin.
+fn handle(event: HookEvent, input: &str, pane_id: &str, config: &Config) -> Result<(), String> {
     let hook: HookInput =
-        serde_json::from_str(&input).map_err(|e| format!("parse hook input: {e}"))?;
+        serde_json::from_str(input).map_err(|e| format!("parse hook input: {e}"))?;
 
-    let state = match event {
-        HookEvent::UserPrompt | HookEvent::AskDone => "working",
-        HookEvent::Stop => "idle",
-        HookEvent::Ask => "asking",
+    // Without a usable events directory (HOME unset, read-only filesystem) there's
+    // nowhere to record events — succeed quietly so Claude isn't bothered by hook errors.
+    if !paths::ensure_events_dir().is_ok_and(|dir| paths::is_writable(&dir)) {
+        return Ok(());
+    }
+
+    let pane_id = pane_id.to_string();
+    // In safe mode nothing would release a queued prompt, so none are queued
+    let limit = if send::disabled_by(config) {
+        0
+    } else {
+        config.quota.max_working
+    };
+
+    let (state, hook_name) = match event {
+        // A blocked prompt is dropped by Claude; stdout carries the decision
+        HookEvent::UserPrompt => match queue_prompt(&Tmux, limit, &hook, &pane_id)? {
+            Some(reason) => {
+                println!(
+                    "{}",
+                    serde_json::json!({"decision": "block", "reason": reason})
+                );
+                ("queued", "queued")
+            }
+            None => {
+                // History is best-effort: losing a line beats blocking the prompt
+                let window = window_name(&Tmux, &pane_id).unwrap_or_default();
+                let _ =
+                    prompts::record(&hook.session_id, &pane_id, &window, &hook.cwd, &hook.prompt);
+                // For `cove restore` to resume the conversation
+                if let Err(e) = sessions::note_claude_session(&pane_id, &hook.session_id) {
+                    log::warn!("sessions: {e}");
+                }
+                stats::record(stats::Kind::Prompt, 0);
+                ("working", "user-prompt")
+            }
+        },
+        HookEvent::AskDone => ("working", "ask-done"),
+        HookEvent::Stop => ("idle", "stop"),
+        HookEvent::Ask => ("asking", "ask"),
+        HookEvent::FileEdit => {
+            return match edited_file(&hook) {
+                Some(file) => changes::record(&hook.session_id, &pane_id, &hook.tool_name, &file),
+                None => Ok(()),
+            };
+        }
+        HookEvent::Command => {
+            return cmdlog::record(
+                &hook.session_id,
+                &pane_id,
+                &hook.cwd,
+                &hook.tool_input,
+                &hook.tool_response,
+            );
+        }
+        HookEvent::SessionEnd => {
+            if config.window_status.pane_title && pane_id.starts_with('%') && hook.reason != "clear"
+            {
+                let _ = Tmux.set_pane_title(&pane_id, "");
+            }
+            // `/clear` ends the conversation but not the session
+            if hook.reason == "clear" {
+                return Ok(());
+            }
+            let name = session_name(&Tmux, &pane_id, &hook.cwd);
+            if let Err(e) = journal::record(&config.journal, &name, &hook.session_id) {
+                log::warn!("journal: {e}");
+            }
+            return Ok(());
+        }
+        HookEvent::Replay { .. } | HookEvent::Answer { .. } => {
+            return Err(format!("{} can't be captured", event_name(&event)));
+        }
+        HookEvent::PreTool | HookEvent::PostTool => {
+            let phase = if matches!(event, HookEvent::PreTool) {
+                "start"
+            } else {
+                "end"
+            };
+            return tools::record(
+                &hook.session_id,
+                &pane_id,
+                phase,
+                &hook.tool_name,
+                &hook.tool_input,
+            );
+        }
     };
 
     // Suppress the initial "idle" on session startup — only write it after
@@ -95,11 +595,57 @@ pub fn run(event: HookEvent) -> Result<(), String> {
         return Ok(());
     }
 
+    // A Working stretch ending here counts toward `cove stats`
+    if state != "working"
+        && let Some(since) =
+            paths::events_dir().and_then(|dir| working_since_in(&hook.session_id, &dir))
+    {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        stats::record(stats::Kind::Working, now.saturating_sub(since));
+    }
+
     // $TMUX_PANE uniquely identifies which tmux pane Claude is running in.
     // This lets the sidebar distinguish sessions even when they share a cwd.
-    let pane_id = std::env::var("TMUX_PANE").unwrap_or_default();
+    // Tabs opened by the kitty/wezterm backends carry a cove token in $COVE_PANE.
+    write_event(&hook.session_id, &hook.cwd, &pane_id, state, hook_name)?;
 
-    write_event(&hook.session_id, &hook.cwd, &pane_id, state)
+    show_window_state(&Tmux, &config.window_status, &pane_id, state);
+    show_pane_title(
+        &Tmux,
+        &config.window_status,
+        &hook.session_id,
+        &pane_id,
+        state,
+    );
+    send_alerts(
+        &Tmux,
+        &config.notify,
+        &hook.session_id,
+        &pane_id,
+        &hook.cwd,
+        state,
+    );
+    if matches!(event, HookEvent::Ask) && pane_id.starts_with('%') {
+        let name = session_name(&Tmux, &pane_id, &hook.cwd);
+        if let Err(e) = delegate::on_ask(&Tmux, config, &name, &pane_id, &hook.tool_input) {
+            log::warn!("delegate {name}: {e}");
+        }
+    }
+    if matches!(event, HookEvent::Stop) {
+        release_queued(&Tmux, limit, &config.send);
+        let name = session_name(&Tmux, &pane_id, &hook.cwd);
+        let prompt = prompts::latest(&hook.session_id).map(|e| e.prompt);
+        snapshots::after_turn(
+            &config.snapshots,
+            Path::new(&hook.cwd),
+            &name,
+            prompt.as_deref(),
+        );
+    }
+    Ok(())
 }
 
 // ── Tests ──
@@ -129,6 +675,24 @@ mod tests {
         assert!(content.contains(r#""cwd":"/tmp""#));
     }
 
+    #[test]
+    fn test_edited_file() {
+        let hook: HookInput = serde_json::from_str(
+            r#"{"session_id":"s","cwd":"/repo","tool_name":"Edit","tool_input":{"file_path":"/repo/src/a.rs"}}"#,
+        )
+        .unwrap();
+        assert_eq!(edited_file(&hook).as_deref(), Some("/repo/src/a.rs"));
+
+        let hook: HookInput = serde_json::from_str(
+            r#"{"session_id":"s","cwd":"/repo","tool_name":"NotebookEdit","tool_input":{"notebook_path":"nb.ipynb"}}"#,
+        )
+        .unwrap();
+        assert_eq!(edited_file(&hook).as_deref(), Some("/repo/nb.ipynb"));
+
+        let hook: HookInput = serde_json::from_str(r#"{"session_id":"s","cwd":"/repo"}"#).unwrap();
+        assert!(edited_file(&hook).is_none());
+    }
+
     #[test]
     fn test_has_working_event_empty() {
         let dir = tempfile::tempdir().unwrap();
@@ -150,4 +714,138 @@ mod tests {
 
         assert!(has_working_event_in("test-session", dir.path()));
     }
+
+    #[test]
+    fn test_turn_started_is_latest_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("s.jsonl"),
+            [
+                r#"{"state":"working","cwd":"/p","pane_id":"%1","ts":100,"hook":"user-prompt"}"#,
+                r#"{"state":"idle","cwd":"/p","pane_id":"%1","ts":150,"hook":"stop"}"#,
+                r#"{"state":"working","cwd":"/p","pane_id":"%1","ts":200,"hook":"user-prompt"}"#,
+                r#"{"state":"working","cwd":"/p","pane_id":"%1","ts":260,"hook":"ask-done"}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        assert_eq!(turn_started_in("s", dir.path()), Some(200));
+        assert_eq!(turn_started_in("missing", dir.path()), None);
+        // Both working events belong to the same stretch
+        assert_eq!(working_since_in("s", dir.path()), Some(200));
+    }
+
+    #[test]
+    fn test_show_window_state() {
+        use crate::tmux::fake::FakeTmux;
+
+        let mut config = WindowStatusConfig::default();
+        let fake = FakeTmux::new();
+        show_window_state(&fake, &config, "%t1", "working");
+        assert!(fake.calls_to("set-option").is_empty());
+
+        config.enabled = true;
+        show_window_state(&fake, &config, "%t1", "asking");
+        let calls = fake.calls_to("set-option");
+        assert_eq!(calls.len(), 3);
+        assert_eq!(
+            calls[0],
+            ["set-option", "-w", "-t", "%t1", "@cove-state", "?"]
+        );
+
+        // Terminal tabs have no tmux window; an empty format leaves it alone
+        let fake = FakeTmux::new();
+        show_window_state(&fake, &config, "cove-1f-2a", "idle");
+        assert!(fake.calls_to("set-option").is_empty());
+        config.format.clear();
+        show_window_state(&fake, &config, "%t1", "idle");
+        assert_eq!(fake.calls_to("set-option").len(), 1);
+    }
+
+    #[test]
+    fn test_pane_title_names_state_and_start() {
+        use crate::tmux::fake::FakeTmux;
+
+        let config = WindowStatusConfig::default();
+        let clock = 14 * 3600 + 3 * 60 + 59;
+        assert_eq!(
+            pane_title(&config, "working", clock, Some(30)),
+            "\u{25b6} working since 14:03"
+        );
+        assert_eq!(
+            pane_title(&config, "idle", clock, Some(360)),
+            "\u{2713} your turn since 14:03 (6m turn)"
+        );
+
+        let fake = FakeTmux::new();
+        show_pane_title(&fake, &config, "s", "%t1", "working");
+        assert!(fake.calls().is_empty());
+        fake.set_pane_title("%t1", "").unwrap();
+        assert_eq!(
+            fake.calls()[0],
+            ["set-option", "-p", "-u", "-t", "%t1", "@cove-title"]
+        );
+    }
+
+    #[test]
+    fn test_queue_prompt_without_limit_asks_nothing() {
+        use crate::tmux::fake::FakeTmux;
+
+        let hook: HookInput =
+            serde_json::from_str(r#"{"session_id":"s","cwd":"/repo","prompt":"fix it"}"#).unwrap();
+        let fake = FakeTmux::new();
+        assert_eq!(queue_prompt(&fake, 0, &hook, "%t1").unwrap(), None);
+        // Terminal tabs aren't counted against tmux panes
+        assert_eq!(queue_prompt(&fake, 1, &hook, "cove-1f-2a").unwrap(), None);
+        assert!(fake.calls().is_empty());
+    }
+
+    #[test]
+    fn test_event_names_round_trip() {
+        let names = [
+            "user-prompt",
+            "stop",
+            "ask",
+            "ask-done",
+            "file-edit",
+            "command",
+            "pre-tool",
+            "post-tool",
+            "session-end",
+        ];
+        for name in names {
+            let event = event_from_name(name).unwrap();
+            assert_eq!(event_name(&event), name);
+        }
+        assert!(event_from_name("replay").is_none());
+    }
+
+    #[test]
+    fn test_capture_keeps_raw_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let stdin = r#"{"session_id":"s","cwd":"/repo"} trailing"#;
+        let path = capture_in(dir.path(), "stop", "%t1", stdin).unwrap();
+
+        let capture: Capture = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(capture.event, "stop");
+        assert_eq!(capture.pane_id, "%t1");
+        assert_eq!(capture.stdin, stdin);
+    }
+
+    #[test]
+    fn test_probe_appends_hook_and_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        probe_in(dir.path(), "verify-1", "pre-tool", "Bash").unwrap();
+        probe_in(dir.path(), "verify-1", "stop", "").unwrap();
+        let content = fs::read_to_string(probe_file(dir.path(), "verify-1")).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                r#"{"hook":"pre-tool","tool":"Bash"}"#,
+                r#"{"hook":"stop","tool":""}"#
+            ]
+        );
+        assert!(probe_in(dir.path(), "../x", "stop", "").is_err());
+    }
 }
@@ -1,28 +1,304 @@
 // ── Hook installation for Claude Code ──
 //
 // Adds Cove hook entries to ~/.claude/settings.json so Claude Code
-// calls `cove hook user-prompt` and `cove hook stop` on session events.
+// calls `cove hook user-prompt`, `cove hook stop`, etc. on session events.
+//
+// `cove init --project` installs them for one repository instead, in
+// <repo>/.claude/settings.json (or settings.local.json with --local, or when
+// that's where they already are). Claude runs the hooks of every scope, so
+// checks look at all of them and flag an install in more than one. The shared
+// settings.json is committed, so its hooks run a bare `cove` from $PATH
+// rather than this machine's binary.
+//
+// Commands in user and personal settings carry `--events-dir` with the
+// absolute events root, so a hook Claude runs in a sandbox with another $HOME
+// still writes its events where the sidebar reads them. A root that moved
+// counts as not installed. The shared project file gets neither.
+//
+// Hooks run one cove binary, normally this one. With two installed (say
+// Homebrew's and cargo's), hooks written by the other aren't stale: `init`
+// lists the binaries and asks which the hooks should run, and `--bin <path>`
+// picks one without asking. The choice goes in ~/.cove/hook-bin, so every
+// cove binary checks and repairs hooks against the same one.
 
 use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
 use std::path::{Path, PathBuf};
 
 use serde_json::Value;
 
+use crate::colors::*;
+use crate::commands::start;
+use crate::{git, paths, quiet, say};
+
+// ── Constants ──
+
+/// File under ~/.cove naming the binary hooks run, when it isn't this one.
+const HOOK_BIN_FILE: &str = "hook-bin";
+
+/// Tools whose PostToolUse hook records an edited file.
+const FILE_EDIT_MATCHER: &str = "Edit|Write|MultiEdit|NotebookEdit";
+
+/// Every hook cove installs: (hook_type, matcher, cove_command).
+pub const HOOK_ENTRIES: &[(&str, &str, &str)] = &[
+    ("UserPromptSubmit", "*", "hook user-prompt"),
+    ("Stop", "*", "hook stop"),
+    ("PreToolUse", "AskUserQuestion", "hook ask"),
+    ("PostToolUse", "AskUserQuestion", "hook ask-done"),
+    ("PreToolUse", "ExitPlanMode", "hook ask"),
+    ("PostToolUse", "ExitPlanMode", "hook ask-done"),
+    ("PreToolUse", "EnterPlanMode", "hook ask"),
+    ("PostToolUse", "EnterPlanMode", "hook ask-done"),
+    ("PostToolUse", FILE_EDIT_MATCHER, "hook file-edit"),
+    ("PostToolUse", "Bash", "hook command"),
+    ("PreToolUse", "*", "hook pre-tool"),
+    ("PostToolUse", "*", "hook post-tool"),
+    ("SessionEnd", "*", "hook session-end"),
+];
+
+/// Hooks that must run synchronously: `user-prompt` may block the prompt when
+/// `quota.max_working` is reached (see quota.rs).
+const SYNC_COMMANDS: &[&str] = &["hook user-prompt"];
+
+/// Hook settings layouts cove has been verified against, oldest first. When a
+/// Claude release renames or drops hook events, add a schema for it here so
+/// `cove doctor` and the sidebar flag installs that would silently stop firing.
+const SCHEMAS: &[HookSchema] = &[HookSchema {
+    since: (1, 0, 0),
+    events: &[
+        "PreToolUse",
+        "PostToolUse",
+        "Notification",
+        "UserPromptSubmit",
+        "Stop",
+        "SubagentStop",
+        "PreCompact",
+        "SessionStart",
+        "SessionEnd",
+    ],
+}];
+
+// ── Types ──
+
+/// Hook events a range of Claude versions accepts in settings.json.
+pub struct HookSchema {
+    /// First Claude version using this layout.
+    pub since: (u32, u32, u32),
+    pub events: &'static [&'static str],
+}
+
+/// A way the installed hooks don't match what cove expects.
+#[derive(Debug, PartialEq, Eq)]
+pub enum HookIssue {
+    /// settings.json can't be read or isn't valid JSON.
+    Unreadable(String),
+    /// A cove hook isn't installed (hook_type, matcher, command).
+    Missing(String, String, String),
+    /// Cove hooks call a binary other than the running one.
+    StaleBinary(String),
+    /// A cove hook sits under an event this Claude version doesn't fire.
+    UnknownEvent(String),
+    /// A cove hook entry doesn't have the expected shape.
+    Malformed(String),
+}
+
+impl std::fmt::Display for HookIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookIssue::Unreadable(e) => write!(f, "settings unreadable: {e}"),
+            HookIssue::Missing(t, m, c) => write!(f, "missing {t}({m}) \u{2192} cove {c}"),
+            HookIssue::StaleBinary(b) => write!(f, "hooks call an old binary: {b}"),
+            HookIssue::UnknownEvent(t) => write!(f, "{t} is not a hook event this Claude fires"),
+            HookIssue::Malformed(t) => write!(f, "malformed cove entry under {t}"),
+        }
+    }
+}
+
 // ── Helpers ──
 
-fn settings_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_default();
-    PathBuf::from(home).join(".claude").join("settings.json")
+fn settings_path() -> Result<PathBuf, String> {
+    paths::claude_settings()
+        .ok_or("cannot locate Claude settings: HOME is not set (set CLAUDE_CONFIG_DIR)".to_string())
 }
 
-fn cove_bin_path() -> String {
+/// The project root Claude would read settings from for `dir`: its
+/// repository's top level, or `dir` outside git.
+pub fn project_root(dir: &Path) -> PathBuf {
+    git::toplevel(dir)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| dir.to_path_buf())
+}
+
+/// Settings file `cove init --project` writes under `root`: the personal one
+/// with `local` or if it already holds cove hooks, else the shared one.
+fn project_target(root: &Path, local: bool) -> PathBuf {
+    let [shared, personal] = paths::project_settings(root);
+    if local || has_any_hooks(&personal) {
+        personal
+    } else {
+        shared
+    }
+}
+
+/// Absolute path of this cove binary, for commands other programs run.
+pub fn cove_bin_path() -> String {
     if let Ok(exe) = std::env::current_exe() {
         if let Ok(canonical) = fs::canonicalize(exe) {
             return canonical.to_string_lossy().to_string();
         }
     }
-    let home = std::env::var("HOME").unwrap_or_default();
-    format!("{home}/.local/bin/cove")
+    paths::home()
+        .map(|h| h.join(".local/bin/cove").to_string_lossy().to_string())
+        .unwrap_or_else(|| "cove".to_string())
+}
+
+fn canonical(bin: &str) -> String {
+    fs::canonicalize(bin)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| bin.to_string())
+}
+
+/// Binaries the cove hooks in settings.json run, in order of appearance.
+fn hook_bins(path: &Path) -> Vec<String> {
+    let settings: Value = fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    let mut bins: Vec<String> = Vec::new();
+    let hooks = settings["hooks"].as_object().into_iter().flatten();
+    let commands = hooks
+        .filter_map(|(_, arr)| arr.as_array())
+        .flatten()
+        .filter_map(|entry| entry["hooks"].as_array())
+        .flatten()
+        .filter(|h| is_cove_command(h))
+        .filter_map(|h| h["command"].as_str());
+    for cmd in commands {
+        let bin = cmd.split(" hook ").next().unwrap_or_default().to_string();
+        if !bins.contains(&bin) {
+            bins.push(bin);
+        }
+    }
+    bins
+}
+
+/// Binaries in hooks that still exist but aren't `bin`: another cove
+/// install, rather than a path left behind by a move.
+fn foreign_bins_with(path: &Path, bin: &str) -> Vec<String> {
+    let ours = canonical(bin);
+    hook_bins(path)
+        .into_iter()
+        .filter(|b| Path::new(b).is_file() && canonical(b) != ours)
+        .collect()
+}
+
+/// Every `cove` on $PATH, resolved, for the list to pick from.
+fn path_bins() -> Vec<String> {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path)
+        .map(|dir| dir.join("cove"))
+        .filter(|bin| bin.is_file())
+        .map(|bin| canonical(&bin.to_string_lossy()))
+        .collect()
+}
+
+/// `bin` as a path hooks can run: absolute, and an existing file.
+fn resolve_bin(bin: &str) -> Result<String, String> {
+    let path = fs::canonicalize(bin).map_err(|e| format!("--bin {bin}: {e}"))?;
+    if !path.is_file() {
+        return Err(format!("--bin {bin}: not a file"));
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Remember `bin` as the binary hooks run (forgotten when it's this one).
+fn set_hook_bin(bin: &str) -> Result<(), String> {
+    let path = paths::ensure_cove_dir("")?.join(HOOK_BIN_FILE);
+    if canonical(bin) == cove_bin_path() {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(format!("remove hook-bin: {e}")),
+            _ => Ok(()),
+        };
+    }
+    fs::write(&path, format!("{bin}\n")).map_err(|e| format!("write hook-bin: {e}"))
+}
+
+/// Ask which of `candidates` hooks should run; an empty answer keeps the
+/// first (what the hooks run now).
+fn choose_bin(
+    candidates: &[String],
+    ours: &str,
+    input: &mut dyn BufRead,
+) -> Result<String, String> {
+    println!(
+        "{ANSI_PEACH}More than one cove is installed.{ANSI_RESET} Which should Claude's hooks run?"
+    );
+    for (i, bin) in candidates.iter().enumerate() {
+        let mut notes = Vec::new();
+        if i == 0 {
+            notes.push("in hooks now");
+        }
+        if bin == ours {
+            notes.push("this one");
+        }
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!(" {ANSI_OVERLAY}({}){ANSI_RESET}", notes.join(", "))
+        };
+        println!("  {}) {}{notes}", i + 1, paths::tildify(bin));
+    }
+    loop {
+        print!("Hooks run [1]: ");
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        if input
+            .read_line(&mut line)
+            .map_err(|e| format!("read: {e}"))?
+            == 0
+        {
+            return Err("no binary picked".to_string());
+        }
+        match line.trim() {
+            "" => return Ok(candidates[0].clone()),
+            n => match n
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| candidates.get(n.wrapping_sub(1)))
+            {
+                Some(bin) => return Ok(bin.clone()),
+                None => println!("  {ANSI_RED}Pick 1 to {}.{ANSI_RESET}", candidates.len()),
+            },
+        }
+    }
+}
+
+/// The binary hooks should run for `init` on `path`: `bin_override`, or a
+/// pick from the cove binaries when settings already run another one.
+fn pick_bin(path: &Path, bin_override: Option<&str>) -> Result<String, String> {
+    if let Some(bin) = bin_override {
+        return resolve_bin(bin);
+    }
+    let current = hook_bin();
+    let foreign = foreign_bins_with(path, &current);
+    if foreign.is_empty() {
+        return Ok(current);
+    }
+    if quiet::on() || !io::stdin().is_terminal() {
+        return Err(format!(
+            "cove hooks in {} run another cove ({}); pick one with cove init --bin <path>",
+            paths::tildify(&path.to_string_lossy()),
+            foreign.join(", ")
+        ));
+    }
+    let ours = cove_bin_path();
+    let mut candidates = foreign;
+    for bin in std::iter::once(current).chain(path_bins()) {
+        if !candidates.contains(&bin) {
+            candidates.push(bin);
+        }
+    }
+    choose_bin(&candidates, &ours, &mut io::stdin().lock())
 }
 
 /// Check if Cove hooks are already installed in settings.json with the correct binary path.
@@ -32,17 +308,76 @@ pub fn hooks_installed(path: &Path) -> bool {
         Ok(c) => c,
         Err(_) => return false,
     };
-    // Must have the ask hook AND ExitPlanMode (detects old installs missing newer hooks)
-    // AND point to the current binary (detects stale paths after rename/move)
-    let bin = cove_bin_path();
+    // Must have the ask hook AND ExitPlanMode AND file-edit AND post-tool (detects
+    // old installs missing newer hooks) AND point to the current binary (detects
+    // stale paths after rename/move)
+    let bin = bin_for(path);
     let ask_cmd = format!("{bin} hook ask");
-    content.contains(&ask_cmd) && content.contains("ExitPlanMode")
+    content.contains(&ask_cmd)
+        && content.contains(&events_arg(events_for(path).as_deref()))
+        && content.contains("ExitPlanMode")
+        && content.contains(" hook file-edit")
+        && content.contains(" hook post-tool")
+        && content.contains(" hook command")
 }
 
 /// Install Cove hooks into settings.json.
 /// Appends to existing hook arrays — does not overwrite.
 pub fn install_hooks(path: &Path) -> Result<(), String> {
-    install_hooks_with_bin(path, &cove_bin_path())
+    install_hooks_with(path, &bin_for(path), events_for(path).as_deref())
+}
+
+/// True for a project's shared `.claude/settings.json`, which is committed
+/// and so mustn't name a path on this machine.
+fn is_shared(path: &Path) -> bool {
+    path.ends_with(".claude/settings.json") && paths::claude_settings().as_deref() != Some(path)
+}
+
+/// The binary hooks in `path` run: a bare `cove` from $PATH in a shared
+/// project file, else [`hook_bin`].
+pub fn bin_for(path: &Path) -> String {
+    if is_shared(path) {
+        "cove".to_string()
+    } else {
+        hook_bin()
+    }
+}
+
+/// The binary hooks run: the one picked with `cove init`, if it's still
+/// there, else this one.
+pub fn hook_bin() -> String {
+    paths::cove_path(HOOK_BIN_FILE)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|bin| bin.trim().to_string())
+        .filter(|bin| !bin.is_empty() && Path::new(bin).is_file())
+        .unwrap_or_else(cove_bin_path)
+}
+
+/// Cove binaries other than the one hooks should run that settings.json's
+/// cove hooks call.
+pub fn foreign_bins(path: &Path) -> Vec<String> {
+    foreign_bins_with(path, &hook_bin())
+}
+
+/// The events root hooks in `path` pin: none in a shared project file, where
+/// it would be this machine's path.
+fn events_for(path: &Path) -> Option<PathBuf> {
+    if is_shared(path) {
+        None
+    } else {
+        paths::events_root()
+    }
+}
+
+/// What every hook command ends with to pin its events to `events`.
+fn events_arg(events: Option<&Path>) -> String {
+    match events {
+        Some(dir) => format!(
+            " --events-dir {}",
+            start::shell_quote(&dir.to_string_lossy())
+        ),
+        None => String::new(),
+    }
 }
 
 /// Check if a hook array already contains an entry with the given matcher whose command includes `needle`.
@@ -98,7 +433,7 @@ pub fn has_stale_hooks(path: &Path, current_bin: &str) -> bool {
     content.contains(" hook user-prompt") && !content.contains(current_bin)
 }
 
-fn install_hooks_with_bin(path: &Path, bin: &str) -> Result<(), String> {
+fn install_hooks_with(path: &Path, bin: &str, events: Option<&Path>) -> Result<(), String> {
     let mut settings: Value = if path.exists() {
         let content = fs::read_to_string(path).map_err(|e| format!("read settings: {e}"))?;
         serde_json::from_str(&content).map_err(|e| format!("parse settings: {e}"))?
@@ -117,23 +452,15 @@ fn install_hooks_with_bin(path: &Path, bin: &str) -> Result<(), String> {
 
     let hooks_obj = hooks.as_object_mut().ok_or("hooks is not an object")?;
 
-    // Each entry: (hook_type, matcher, cove_command)
-    let entries: &[(&str, &str, &str)] = &[
-        ("UserPromptSubmit", "*", "hook user-prompt"),
-        ("Stop", "*", "hook stop"),
-        ("PreToolUse", "AskUserQuestion", "hook ask"),
-        ("PostToolUse", "AskUserQuestion", "hook ask-done"),
-        ("PreToolUse", "ExitPlanMode", "hook ask"),
-        ("PostToolUse", "ExitPlanMode", "hook ask-done"),
-        ("PreToolUse", "EnterPlanMode", "hook ask"),
-        ("PostToolUse", "EnterPlanMode", "hook ask-done"),
-    ];
-
-    // Remove stale cove hooks once per hook_type before adding new ones.
+    // Remove every existing cove hook before adding new ones — including ones
+    // under events a newer Claude no longer fires, dropping arrays they emptied.
     // (Doing it per-entry would remove hooks added by earlier entries of the same type.)
-    let mut cleaned_types: Vec<&str> = Vec::new();
+    hooks_obj.retain(|_, arr| match arr.as_array_mut() {
+        Some(arr) => remove_hook_commands(arr, "cove hook") == 0 || !arr.is_empty(),
+        None => true,
+    });
 
-    for &(hook_type, matcher, cmd) in entries {
+    for &(hook_type, matcher, cmd) in HOOK_ENTRIES {
         let arr = hooks_obj
             .entry(hook_type)
             .or_insert_with(|| serde_json::json!([]));
@@ -141,19 +468,14 @@ fn install_hooks_with_bin(path: &Path, bin: &str) -> Result<(), String> {
             .as_array_mut()
             .ok_or(format!("{hook_type} is not an array"))?;
 
-        if !cleaned_types.contains(&hook_type) {
-            cleaned_types.push(hook_type);
-            remove_hook_commands(arr, "cove hook");
-        }
-
-        let full_cmd = format!("{bin} {cmd}");
+        let full_cmd = format!("{bin} {cmd}{}", events_arg(events));
         if !has_hook_entry(arr, matcher, &full_cmd) {
             arr.push(serde_json::json!({
                 "matcher": matcher,
                 "hooks": [{
                     "type": "command",
                     "command": full_cmd,
-                    "async": true,
+                    "async": !SYNC_COMMANDS.contains(&cmd),
                     "timeout": 5
                 }]
             }));
@@ -167,35 +489,215 @@ fn install_hooks_with_bin(path: &Path, bin: &str) -> Result<(), String> {
     Ok(())
 }
 
+fn is_cove_command(hook: &Value) -> bool {
+    hook["command"]
+        .as_str()
+        .is_some_and(|c| c.contains("cove hook"))
+}
+
+/// Validate cove's entries in parsed settings against `schema`.
+fn check_settings(settings: &Value, bin: &str, schema: &HookSchema) -> Vec<HookIssue> {
+    let mut issues = Vec::new();
+    let hooks = settings["hooks"].as_object();
+
+    for (hook_type, arr) in hooks.into_iter().flatten() {
+        let Some(arr) = arr.as_array() else {
+            continue;
+        };
+        for entry in arr {
+            let Some(commands) = entry["hooks"].as_array() else {
+                continue;
+            };
+            let cove: Vec<&Value> = commands.iter().filter(|h| is_cove_command(h)).collect();
+            if cove.is_empty() {
+                continue;
+            }
+            if !schema.events.contains(&hook_type.as_str()) {
+                issues.push(HookIssue::UnknownEvent(hook_type.clone()));
+            } else if !entry["matcher"].is_string() || cove.iter().any(|h| h["type"] != "command") {
+                issues.push(HookIssue::Malformed(hook_type.clone()));
+            }
+            for h in cove {
+                let cmd = h["command"].as_str().unwrap_or_default();
+                let old_bin = cmd.split(" hook ").next().unwrap_or_default();
+                if old_bin != bin && !issues.contains(&HookIssue::StaleBinary(old_bin.to_string()))
+                {
+                    issues.push(HookIssue::StaleBinary(old_bin.to_string()));
+                }
+            }
+        }
+    }
+    issues.dedup();
+
+    for &(hook_type, matcher, cmd) in HOOK_ENTRIES {
+        let installed = settings["hooks"][hook_type]
+            .as_array()
+            .is_some_and(|arr| has_hook_entry(arr, matcher, &format!(" {cmd}")));
+        if !installed {
+            issues.push(HookIssue::Missing(
+                hook_type.to_string(),
+                matcher.to_string(),
+                cmd.to_string(),
+            ));
+        }
+    }
+    issues
+}
+
+/// The newest schema that applies to `version`; unknown versions get the newest.
+fn schema_for(version: Option<(u32, u32, u32)>) -> &'static HookSchema {
+    let newest = &SCHEMAS[SCHEMAS.len() - 1];
+    match version {
+        Some(v) => SCHEMAS
+            .iter()
+            .rev()
+            .find(|s| s.since <= v)
+            .unwrap_or(&SCHEMAS[0]),
+        None => newest,
+    }
+}
+
+/// Parse `claude --version` output, e.g. "2.0.14 (Claude Code)".
+fn parse_version(output: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = output.split_whitespace().next()?.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    let patch = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    Some((major, minor, patch))
+}
+
 // ── Public API ──
 
-pub fn run() -> Result<(), String> {
-    let path = settings_path();
+/// Installed Claude Code version, if `claude` is on PATH.
+pub fn claude_version() -> Option<(u32, u32, u32)> {
+    let output = std::process::Command::new("claude")
+        .arg("--version")
+        .output()
+        .ok()?;
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Check cove's installed hooks against the schema for `version`.
+/// A settings file without any cove hooks reports every hook as missing.
+pub fn check_hooks(path: &Path, version: Option<(u32, u32, u32)>) -> Vec<HookIssue> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::from("{}"),
+        Err(e) => return vec![HookIssue::Unreadable(e.to_string())],
+    };
+    match serde_json::from_str::<Value>(&content) {
+        Ok(settings) => check_settings(&settings, &bin_for(path), schema_for(version)),
+        Err(e) => vec![HookIssue::Unreadable(e.to_string())],
+    }
+}
+
+/// The command settings file `path` runs for cove hook `cmd` (e.g. "hook
+/// ask") under `hook_type` with `matcher`, if it's installed there.
+pub fn installed_command(path: &Path, hook_type: &str, matcher: &str, cmd: &str) -> Option<String> {
+    let settings: Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    let entries = settings["hooks"][hook_type].as_array()?;
+    let words: Vec<&str> = cmd.split(' ').collect();
+    entries
+        .iter()
+        .filter(|entry| entry["matcher"].as_str() == Some(matcher))
+        .flat_map(|entry| entry["hooks"].as_array().into_iter().flatten())
+        .filter(|h| is_cove_command(h))
+        .filter_map(|h| h["command"].as_str())
+        // Whole words, so "hook ask" doesn't find "hook ask-done"
+        .find(|c| {
+            let have: Vec<&str> = c.split_whitespace().collect();
+            have.windows(words.len()).any(|w| w == words.as_slice())
+        })
+        .map(String::from)
+}
+
+/// True if settings.json contains any cove hook (current or stale).
+pub fn has_any_hooks(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|c| c.contains("cove hook"))
+}
+
+/// Every settings file Claude reads for a session in `project` (user
+/// settings first), whether or not it exists.
+pub fn settings_files(project: Option<&Path>) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = paths::claude_settings().into_iter().collect();
+    if let Some(root) = project {
+        files.extend(paths::project_settings(root));
+    }
+    files
+}
+
+/// True if some scope of `project` has the current cove hooks installed.
+pub fn hooks_installed_for(project: Option<&Path>) -> bool {
+    settings_files(project).iter().any(|p| hooks_installed(p))
+}
+
+/// Settings files of `project` holding cove hooks that don't match what cove
+/// expects for `version`.
+pub fn broken_settings(project: Option<&Path>, version: Option<(u32, u32, u32)>) -> Vec<PathBuf> {
+    settings_files(project)
+        .into_iter()
+        .filter(|p| has_any_hooks(p) && !check_hooks(p, version).is_empty())
+        .collect()
+}
+
+/// Install hooks, running `bin_override` if given.
+pub fn run(project: bool, local: bool, bin_override: Option<&str>) -> Result<(), String> {
+    let root = if project {
+        let cwd = std::env::current_dir().map_err(|e| format!("current dir: {e}"))?;
+        Some(project_root(&cwd))
+    } else {
+        None
+    };
+    let path = match &root {
+        Some(root) => project_target(root, local),
+        None => settings_path()?,
+    };
+    let display = paths::tildify(&path.to_string_lossy());
 
+    let bin = pick_bin(&path, bin_override)?;
+    set_hook_bin(&bin)?;
     if hooks_installed(&path) {
-        println!("Cove hooks are already installed in ~/.claude/settings.json");
+        say!("Cove hooks are already installed in {display}");
         return Ok(());
     }
 
-    let bin = cove_bin_path();
-    let stale = has_stale_hooks(&path, &bin);
+    let cmd_bin = bin_for(&path);
+    let stale = has_stale_hooks(&path, &cmd_bin);
 
     install_hooks(&path)?;
 
     if stale {
-        println!("Updated Cove hooks in ~/.claude/settings.json");
-        println!("  (old binary path was replaced with {bin})");
+        say!("Updated Cove hooks in {display}");
+        say!("  (old binary path was replaced with {cmd_bin})");
     } else {
-        println!("Installed Cove hooks in ~/.claude/settings.json");
+        say!("Installed Cove hooks in {display}");
+    }
+    say!("  UserPromptSubmit              → cove hook user-prompt (sync)");
+    say!("  Stop                          → cove hook stop");
+    say!("  PreToolUse(AskUserQuestion)   → cove hook ask");
+    say!("  PostToolUse(AskUserQuestion)  → cove hook ask-done");
+    say!("  PreToolUse(ExitPlanMode)      → cove hook ask");
+    say!("  PostToolUse(ExitPlanMode)     → cove hook ask-done");
+    say!("  PreToolUse(EnterPlanMode)     → cove hook ask");
+    say!("  PostToolUse(EnterPlanMode)    → cove hook ask-done");
+    say!("  PostToolUse(Edit|Write|…)     → cove hook file-edit");
+    say!("  PostToolUse(Bash)             → cove hook command");
+    say!("  PreToolUse(*)                 → cove hook pre-tool");
+    say!("  PostToolUse(*)                → cove hook post-tool");
+    say!("  SessionEnd                    → cove hook session-end");
+
+    // Both scopes' hooks fire, which would record every event twice
+    let others: Vec<String> = settings_files(root.as_deref())
+        .into_iter()
+        .filter(|p| *p != path && has_any_hooks(p))
+        .map(|p| paths::tildify(&p.to_string_lossy()))
+        .collect();
+    if !others.is_empty() {
+        println!(
+            "{ANSI_PEACH}Note:{ANSI_RESET} cove hooks are also in {}; remove them there so events aren't recorded twice.",
+            others.join(", ")
+        );
     }
-    println!("  UserPromptSubmit              → cove hook user-prompt");
-    println!("  Stop                          → cove hook stop");
-    println!("  PreToolUse(AskUserQuestion)   → cove hook ask");
-    println!("  PostToolUse(AskUserQuestion)  → cove hook ask-done");
-    println!("  PreToolUse(ExitPlanMode)      → cove hook ask");
-    println!("  PostToolUse(ExitPlanMode)     → cove hook ask-done");
-    println!("  PreToolUse(EnterPlanMode)     → cove hook ask");
-    println!("  PostToolUse(EnterPlanMode)    → cove hook ask-done");
 
     Ok(())
 }
@@ -251,7 +753,7 @@ mod tests {
         let path = dir.path().join("settings.json");
         fs::write(&path, "{}").unwrap();
 
-        install_hooks_with_bin(&path, "cove").unwrap();
+        install_hooks_with(&path, "cove", None).unwrap();
 
         let content = fs::read_to_string(&path).unwrap();
         assert!(content.contains("cove hook user-prompt"));
@@ -263,14 +765,18 @@ mod tests {
         let hooks = parsed["hooks"].as_object().unwrap();
         assert_eq!(hooks["UserPromptSubmit"].as_array().unwrap().len(), 1);
         assert_eq!(hooks["Stop"].as_array().unwrap().len(), 1);
-        assert_eq!(hooks["PreToolUse"].as_array().unwrap().len(), 3);
-        assert_eq!(hooks["PostToolUse"].as_array().unwrap().len(), 3);
+        // The prompt hook runs synchronously so it can queue prompts
+        assert_eq!(hooks["UserPromptSubmit"][0]["hooks"][0]["async"], false);
+        assert_eq!(hooks["Stop"][0]["hooks"][0]["async"], true);
+        assert_eq!(hooks["PreToolUse"].as_array().unwrap().len(), 4);
+        assert_eq!(hooks["PostToolUse"].as_array().unwrap().len(), 6);
 
-        // PreToolUse should have AskUserQuestion, ExitPlanMode, EnterPlanMode matchers
+        // PreToolUse should have AskUserQuestion, ExitPlanMode, EnterPlanMode, * matchers
         let pre = hooks["PreToolUse"].as_array().unwrap();
         assert_eq!(pre[0]["matcher"].as_str().unwrap(), "AskUserQuestion");
         assert_eq!(pre[1]["matcher"].as_str().unwrap(), "ExitPlanMode");
         assert_eq!(pre[2]["matcher"].as_str().unwrap(), "EnterPlanMode");
+        assert_eq!(pre[3]["matcher"].as_str().unwrap(), "*");
     }
 
     #[test]
@@ -283,7 +789,7 @@ mod tests {
         )
         .unwrap();
 
-        install_hooks_with_bin(&path, "cove").unwrap();
+        install_hooks_with(&path, "cove", None).unwrap();
 
         let content = fs::read_to_string(&path).unwrap();
         let parsed: Value = serde_json::from_str(&content).unwrap();
@@ -311,8 +817,8 @@ mod tests {
         let path = dir.path().join("settings.json");
         fs::write(&path, "{}").unwrap();
 
-        install_hooks_with_bin(&path, "cove").unwrap();
-        install_hooks_with_bin(&path, "cove").unwrap();
+        install_hooks_with(&path, "cove", None).unwrap();
+        install_hooks_with(&path, "cove", None).unwrap();
 
         let content = fs::read_to_string(&path).unwrap();
         let parsed: Value = serde_json::from_str(&content).unwrap();
@@ -321,8 +827,8 @@ mod tests {
         // Each hook type should still have the correct number of Cove entries
         assert_eq!(hooks["UserPromptSubmit"].as_array().unwrap().len(), 1);
         assert_eq!(hooks["Stop"].as_array().unwrap().len(), 1);
-        assert_eq!(hooks["PreToolUse"].as_array().unwrap().len(), 3);
-        assert_eq!(hooks["PostToolUse"].as_array().unwrap().len(), 3);
+        assert_eq!(hooks["PreToolUse"].as_array().unwrap().len(), 4);
+        assert_eq!(hooks["PostToolUse"].as_array().unwrap().len(), 6);
     }
 
     #[test]
@@ -330,7 +836,7 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         let path = dir.path().join("subdir").join("settings.json");
 
-        install_hooks_with_bin(&path, "cove").unwrap();
+        install_hooks_with(&path, "cove", None).unwrap();
 
         assert!(path.exists());
         let content = fs::read_to_string(&path).unwrap();
@@ -348,7 +854,7 @@ mod tests {
         )
         .unwrap();
 
-        install_hooks_with_bin(&path, "cove").unwrap();
+        install_hooks_with(&path, "cove", None).unwrap();
 
         let content = fs::read_to_string(&path).unwrap();
         let parsed: Value = serde_json::from_str(&content).unwrap();
@@ -357,9 +863,11 @@ mod tests {
         // Old hooks should not be duplicated
         assert_eq!(hooks["UserPromptSubmit"].as_array().unwrap().len(), 1);
         assert_eq!(hooks["Stop"].as_array().unwrap().len(), 1);
-        // New hooks should be added (3 each: AskUserQuestion + ExitPlanMode + EnterPlanMode)
-        assert_eq!(hooks["PreToolUse"].as_array().unwrap().len(), 3);
-        assert_eq!(hooks["PostToolUse"].as_array().unwrap().len(), 3);
+        // New hooks should be added (AskUserQuestion + ExitPlanMode + EnterPlanMode,
+        // the catch-all tool tracker, plus the file-edit and command recorders on
+        // PostToolUse)
+        assert_eq!(hooks["PreToolUse"].as_array().unwrap().len(), 4);
+        assert_eq!(hooks["PostToolUse"].as_array().unwrap().len(), 6);
     }
 
     #[test]
@@ -375,7 +883,7 @@ mod tests {
 
         assert!(has_stale_hooks(&path, "/new/path/cove"));
 
-        install_hooks_with_bin(&path, "/new/path/cove").unwrap();
+        install_hooks_with(&path, "/new/path/cove", None).unwrap();
 
         let content = fs::read_to_string(&path).unwrap();
         let parsed: Value = serde_json::from_str(&content).unwrap();
@@ -404,6 +912,177 @@ mod tests {
         assert_eq!(hooks["UserPromptSubmit"].as_array().unwrap().len(), 1);
     }
 
+    #[test]
+    fn test_foreign_bins_are_other_installs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        let (brew, cargo) = (dir.path().join("brew-cove"), dir.path().join("cargo-cove"));
+        fs::write(&brew, "").unwrap();
+        fs::write(&cargo, "").unwrap();
+        let (brew, cargo) = (
+            canonical(&brew.to_string_lossy()),
+            canonical(&cargo.to_string_lossy()),
+        );
+
+        install_hooks_with(&path, &brew, None).unwrap();
+        assert_eq!(foreign_bins_with(&path, &cargo), vec![brew.clone()]);
+        assert!(foreign_bins_with(&path, &brew).is_empty());
+
+        // A binary that's gone is a stale path, not another install
+        install_hooks_with(&path, "/old/path/cove", None).unwrap();
+        assert!(foreign_bins_with(&path, &cargo).is_empty());
+    }
+
+    #[test]
+    fn test_choose_bin_defaults_to_hooks_binary() {
+        let candidates = [
+            "/opt/homebrew/bin/cove".to_string(),
+            "/home/u/.cargo/bin/cove".to_string(),
+        ];
+        let ours = &candidates[1];
+        let pick =
+            |input: &str| choose_bin(&candidates, ours, &mut io::Cursor::new(input.to_string()));
+        assert_eq!(pick("\n").unwrap(), candidates[0]);
+        assert_eq!(pick("3\n2\n").unwrap(), candidates[1]);
+        assert!(pick("").is_err());
+    }
+
+    fn settings_with_bin(bin: &str) -> Value {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        install_hooks_with(&path, bin, None).unwrap();
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_install_hooks_pins_events_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        install_hooks_with(&path, "/bin/cove", Some(Path::new("/home/u/.cove/events"))).unwrap();
+        let settings: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            settings["hooks"]["Stop"][0]["hooks"][0]["command"],
+            "/bin/cove hook stop --events-dir '/home/u/.cove/events'"
+        );
+        assert!(check_settings(&settings, "/bin/cove", schema_for(None)).is_empty());
+    }
+
+    #[test]
+    fn test_installed_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        install_hooks_with(&path, "/bin/cove", None).unwrap();
+        let ask = installed_command(&path, "PreToolUse", "AskUserQuestion", "hook ask");
+        assert_eq!(ask.as_deref(), Some("/bin/cove hook ask"));
+        let done = installed_command(&path, "PostToolUse", "AskUserQuestion", "hook ask-done");
+        assert_eq!(done.as_deref(), Some("/bin/cove hook ask-done"));
+        // Under another hook type or matcher it isn't there
+        assert_eq!(
+            installed_command(&path, "PostToolUse", "AskUserQuestion", "hook ask"),
+            None
+        );
+        assert_eq!(installed_command(&path, "Stop", "Bash", "hook stop"), None);
+    }
+
+    #[test]
+    fn test_check_settings_clean_install() {
+        let settings = settings_with_bin("/bin/cove");
+        assert!(check_settings(&settings, "/bin/cove", schema_for(None)).is_empty());
+    }
+
+    #[test]
+    fn test_check_settings_flags_renamed_event_and_stale_binary() {
+        let mut settings = settings_with_bin("/old/cove");
+        // Simulate a Claude release that renamed Stop
+        let stop = settings["hooks"]["Stop"].take();
+        settings["hooks"].as_object_mut().unwrap().remove("Stop");
+        settings["hooks"]["TurnEnd"] = stop;
+
+        let issues = check_settings(&settings, "/bin/cove", schema_for(Some((2, 0, 14))));
+        assert!(issues.contains(&HookIssue::UnknownEvent("TurnEnd".to_string())));
+        assert!(issues.contains(&HookIssue::StaleBinary("/old/cove".to_string())));
+        assert!(issues.contains(&HookIssue::Missing(
+            "Stop".to_string(),
+            "*".to_string(),
+            "hook stop".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_reinstall_removes_hooks_under_unknown_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.json");
+        fs::write(
+            &path,
+            r#"{"hooks":{"TurnEnd":[{"matcher":"*","hooks":[{"type":"command","command":"cove hook stop"}]}]}}"#,
+        )
+        .unwrap();
+
+        install_hooks_with(&path, "cove", None).unwrap();
+
+        let parsed: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(parsed["hooks"].get("TurnEnd").is_none());
+        assert!(check_settings(&parsed, "cove", schema_for(None)).is_empty());
+    }
+
+    #[test]
+    fn test_project_target_respects_local_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let [shared, personal] = paths::project_settings(dir.path());
+        assert_eq!(project_target(dir.path(), false), shared);
+        assert_eq!(project_target(dir.path(), true), personal);
+
+        // An existing personal install is updated rather than duplicated
+        install_hooks_with(&personal, "/old/cove", None).unwrap();
+        assert_eq!(project_target(dir.path(), false), personal);
+        assert!(!shared.exists());
+
+        let files = settings_files(Some(dir.path()));
+        assert!(files.ends_with(&[shared, personal.clone()]));
+        assert!(broken_settings(Some(dir.path()), None).contains(&personal));
+    }
+
+    #[test]
+    fn test_shared_project_hooks_run_cove_from_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let [shared, personal] = paths::project_settings(dir.path());
+        install_hooks(&shared).unwrap();
+        install_hooks(&personal).unwrap();
+
+        let cmd = installed_command(&shared, "Stop", "*", "hook stop").unwrap();
+        assert!(cmd.starts_with("cove hook stop"), "{cmd}");
+        assert!(hooks_installed(&shared));
+        assert!(check_hooks(&shared, None).is_empty());
+        let content = fs::read_to_string(&personal).unwrap();
+        assert!(content.contains(&format!("\"{} hook stop", hook_bin())));
+    }
+
+    #[test]
+    fn test_shared_project_hooks_name_no_home_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let [shared, personal] = paths::project_settings(dir.path());
+        install_hooks(&shared).unwrap();
+        install_hooks(&personal).unwrap();
+
+        let content = fs::read_to_string(&shared).unwrap();
+        assert!(!content.contains("--events-dir"));
+        if let Some(home) = paths::home() {
+            assert!(!content.contains(&*home.to_string_lossy()));
+        }
+        let personal = fs::read_to_string(&personal).unwrap();
+        assert_eq!(
+            personal.contains("--events-dir"),
+            paths::events_root().is_some()
+        );
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("2.0.14 (Claude Code)\n"), Some((2, 0, 14)));
+        assert_eq!(parse_version("1.0"), Some((1, 0, 0)));
+        assert_eq!(parse_version("claude"), None);
+    }
+
     #[test]
     fn test_hooks_installed_stale_path() {
         let dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,130 @@
+// ── cove interrupt ──
+//
+// Stop Claude's current turn in a session, the way pressing Escape in its pane
+// does. Ctrl-C isn't sent: Claude reads it as "clear the input" and a second
+// one quits. An `interrupt` event (state idle) is recorded so the sidebar
+// shows the session as waiting for you, and history shows who stopped it.
+// `--then` follows up with a new prompt once Claude is listening again.
+
+use std::thread;
+use std::time::Duration;
+
+use crate::colors::*;
+use crate::commands::{hook, restart};
+use crate::say;
+use crate::send;
+use crate::sidebar::state;
+use crate::tmux::{self, Tmux, TmuxClient};
+
+// ── Constants ──
+
+/// Time Claude needs after Escape to stop the turn; until then its screen
+/// still looks ready (see send.rs) but typed input joins the old turn.
+const SETTLE: Duration = Duration::from_millis(400);
+
+// ── Helpers ──
+
+/// Press Escape in `name`'s Claude pane, call `record` with the Claude
+/// session, pane id and directory, then submit `then` if given. Returns the
+/// pane id.
+fn interrupt_in(
+    client: &dyn TmuxClient,
+    name: &str,
+    then: Option<&str>,
+    claude_session: impl Fn(&str) -> Option<String>,
+    record: impl Fn(&str, &str, &str) -> Result<(), String>,
+) -> Result<String, String> {
+    let pane_id = client.get_claude_pane_id(name)?;
+    if pane_id.is_empty() {
+        return Err(format!("No Claude pane found for '{name}'"));
+    }
+    client.send_key(&pane_id, "Escape")?;
+    if let Some(session) = claude_session(&pane_id) {
+        record(&session, &pane_id, &restart::pane_path(client, &pane_id))?;
+    }
+    if let Some(prompt) = then.filter(|p| !p.trim().is_empty()) {
+        thread::sleep(SETTLE);
+        send::deliver(client, &pane_id, prompt)?;
+    }
+    Ok(pane_id)
+}
+
+// ── Public API ──
+
+/// Interrupt Claude in session `name` (already resolved), optionally
+/// following up with `then`.
+pub fn interrupt(name: &str, then: Option<&str>) -> Result<(), String> {
+    if send::disabled() {
+        return Err(send::SAFE_MODE.to_string());
+    }
+    let record = |session: &str, pane_id: &str, cwd: &str| {
+        hook::write_event(session, cwd, pane_id, "idle", "interrupt")
+    };
+    interrupt_in(&Tmux, name, then, state::session_for_pane, record)?;
+    Ok(())
+}
+
+pub fn run(name: &str, then: Option<&str>) -> Result<(), String> {
+    let name = tmux::resolve_target(name)?;
+    if send::skip(&format!("interrupting {name}")) {
+        return Ok(());
+    }
+    interrupt(&name, then)?;
+    let followed = if then.is_some() {
+        " and sent the follow-up"
+    } else {
+        ""
+    };
+    say!("{ANSI_GREEN}Interrupted{ANSI_RESET} {ANSI_PEACH}{name}{ANSI_RESET}{followed}");
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_interrupt_sends_escape_records_and_follows_up() {
+        let fake = FakeTmux::new()
+            .with_windows(&[("api", "claude", "%t1")])
+            .on("capture-pane", "? for shortcuts");
+        let recorded = RefCell::new(Vec::new());
+        let pane = interrupt_in(
+            &fake,
+            "api",
+            Some("try the other approach"),
+            |_| Some("abc".to_string()),
+            |session, pane, _| {
+                recorded
+                    .borrow_mut()
+                    .push((session.to_string(), pane.to_string()));
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(pane, "%t1");
+        assert_eq!(
+            fake.calls_to("send-keys")[0],
+            ["send-keys", "-t", "%t1", "Escape"]
+        );
+        assert_eq!(
+            recorded.into_inner(),
+            [("abc".to_string(), "%t1".to_string())]
+        );
+        let set = &fake.calls_to("set-buffer")[0];
+        assert_eq!(set.last().unwrap(), "try the other approach");
+    }
+
+    #[test]
+    fn test_interrupt_without_follow_up() {
+        let fake = FakeTmux::new().with_windows(&[("api", "claude", "%t1")]);
+        interrupt_in(&fake, "api", None, |_| None, |_, _, _| Ok(())).unwrap();
+        assert_eq!(fake.calls_to("send-keys").len(), 1);
+        assert!(fake.calls_to("paste-buffer").is_empty());
+    }
+}
@@ -1,24 +1,665 @@
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use crate::cli::StateFilter;
 use crate::colors::*;
-use crate::tmux;
+use crate::commands::{checklist, hook, restart, start};
+use crate::sidebar::format::format_age;
+use crate::sidebar::state::{self, StateDetector, WindowState};
+use crate::tmux::{Tmux, TmuxClient, WindowInfo};
+use crate::trash::{self, TrashEntry};
+use crate::{
+    archive, attention, clips, config, container, crash, feed, journal, meta, quiet, say, sessions,
+    stats, tabs, tmux, width, worktrees,
+};
+
+// ── Types ──
+
+/// How `cove all-kill` settles on what goes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Confirm {
+    /// Only show what would go.
+    DryRun,
+    /// Let the user untick sessions to spare first.
+    Ask,
+    /// Kill every match (`--yes`).
+    Yes,
+}
+
+/// What a kill keeps and forgets besides the window. `Bookkeeping::home`
+/// works in the state root; tests use `Bookkeeping::default()` with their
+/// own paths, so nothing under ~/.cove is read or written.
+#[derive(Default)]
+pub struct Bookkeeping {
+    /// Where killed windows are kept for `cove undo`.
+    pub trash: Option<PathBuf>,
+    /// Where kills are counted for `cove stats`.
+    pub stats_log: Option<PathBuf>,
+    /// Also use the rest of the state root: archive the scrollback, keep the
+    /// Claude session and clips with the trash entry, and forget what was
+    /// recorded about the window (`clean_up`).
+    pub home: bool,
+}
+
+impl Bookkeeping {
+    /// Everything under the state root, keeping kills in `trash`.
+    pub fn home(trash: Option<PathBuf>) -> Self {
+        Self {
+            trash,
+            stats_log: stats::log(),
+            home: true,
+        }
+    }
+
+    /// Where to keep `name` for `cove undo`: nowhere if its worktree goes
+    /// with it, since there'd be no directory to bring it back in.
+    fn trash_for(&self, name: &str) -> Option<&Path> {
+        self.trash
+            .as_deref()
+            .filter(|_| !(self.home && worktrees::will_remove(name)))
+    }
+
+    /// Forget killed window `name` and count the kill.
+    fn forget(&self, name: &str, pane_id: Option<&str>) {
+        if self.home {
+            clean_up(name, pane_id);
+        }
+        if let Some(path) = &self.stats_log {
+            let _ = stats::record_in(path, stats::Kind::Killed, 0);
+        }
+    }
+}
+
+// ── Helpers ──
+
+/// Whether `state` is what `--state` (or `--until`) `filter` asks for.
+pub fn matches_filter(state: WindowState, filter: StateFilter) -> bool {
+    matches!(
+        (filter, state),
+        (StateFilter::Fresh, WindowState::Fresh)
+            | (StateFilter::Working, WindowState::Working)
+            | (StateFilter::Waiting, WindowState::Asking)
+            | (StateFilter::Idle, WindowState::Idle)
+            | (StateFilter::Done, WindowState::Done)
+            | (StateFilter::Queued, WindowState::Queued)
+    )
+}
+
+/// Windows `all-kill` should remove, with the active window last so the
+/// command isn't cut short when run from inside the session.
+fn select_targets<'a>(
+    windows: &'a [WindowInfo],
+    states: &std::collections::HashMap<u32, WindowState>,
+    except: &[String],
+    filter: Option<StateFilter>,
+) -> Vec<&'a WindowInfo> {
+    let mut targets: Vec<&WindowInfo> = windows
+        .iter()
+        .filter(|w| !except.contains(&w.name))
+        .filter(|w| {
+            let state = states.get(&w.index).copied().unwrap_or(WindowState::Fresh);
+            filter.is_none_or(|f| matches_filter(state, f))
+        })
+        .collect();
+    targets.sort_by_key(|w| w.is_active);
+    targets
+}
+
+/// Ask which of `targets` to kill, Working sessions unticked to begin with.
+/// None if the user cancelled.
+fn choose_targets<'a>(
+    targets: &[&'a WindowInfo],
+    states: &std::collections::HashMap<u32, WindowState>,
+    detector: &StateDetector,
+) -> Result<Option<Vec<&'a WindowInfo>>, String> {
+    if quiet::on() || !io::stdin().is_terminal() {
+        return Err(
+            "Not killing without confirmation: pass --yes (--dry-run shows what would go)"
+                .to_string(),
+        );
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let name_width = targets
+        .iter()
+        .map(|w| width::of(&w.name))
+        .max()
+        .unwrap_or(0);
+    let items = targets
+        .iter()
+        .map(|w| {
+            let state = states.get(&w.index).copied().unwrap_or(WindowState::Fresh);
+            let age = detector
+                .last_event_ts(w.index)
+                .map_or("-".to_string(), |ts| format_age(now.saturating_sub(ts)));
+            let color = if state == WindowState::Working {
+                ANSI_LAVENDER
+            } else {
+                ANSI_SUBTEXT
+            };
+            let label = format!(
+                "{ANSI_PEACH}{}{ANSI_RESET}  {color}{:<9}{ANSI_RESET}  {ANSI_OVERLAY}{age:>4}{ANSI_RESET}",
+                width::pad(&w.name, name_width),
+                state.label()
+            );
+            (label, state != WindowState::Working)
+        })
+        .collect();
+    let Some(picked) = checklist::pick("Kill which sessions?", items)? else {
+        return Ok(None);
+    };
+    Ok(Some(
+        targets
+            .iter()
+            .zip(picked)
+            .filter(|(_, kill)| *kill)
+            .map(|(w, _)| *w)
+            .collect(),
+    ))
+}
+
+/// Where killed windows are kept for `cove undo`. Killing goes ahead without
+/// one, with a warning.
+fn trash_dir() -> Option<PathBuf> {
+    trash::dir()
+        .inspect_err(|e| eprintln!("{ANSI_PEACH}Warning:{ANSI_RESET} kills can't be undone: {e}"))
+        .ok()
+}
+
+/// Keep what `cove undo` needs to recreate a window that's about to be
+/// killed: its directory, Claude session, clips and last screen. With
+/// `archive.enabled`, the scrollback is also archived.
+fn trash_window(
+    client: &dyn TmuxClient,
+    books: &Bookkeeping,
+    name: &str,
+    dir: &str,
+    pane_id: Option<&str>,
+) {
+    let trash = books.trash_for(name);
+    let archive = if books.home {
+        config::load().unwrap_or_default().archive
+    } else {
+        Default::default()
+    };
+    if trash.is_none() && !archive.enabled {
+        return;
+    }
+    let capture = pane_id
+        .and_then(|p| client.capture_pane(p).ok())
+        .unwrap_or_default();
+    let claude_session = pane_id
+        .filter(|_| books.home)
+        .and_then(state::session_for_pane);
+    if let Err(e) = archive::save(&archive, name, dir, claude_session.as_deref(), &capture) {
+        eprintln!("{ANSI_PEACH}Warning:{ANSI_RESET} {name}'s scrollback wasn't archived: {e}");
+    }
+    let Some(trash) = trash else {
+        return;
+    };
+    let mut saved = if books.home {
+        clips::saved(name)
+    } else {
+        Vec::new()
+    };
+    saved.reverse();
+    let entry = TrashEntry::new(name, dir, claude_session, saved);
+    if let Err(e) = trash::save_in(trash, &entry, &capture) {
+        eprintln!("{ANSI_PEACH}Warning:{ANSI_RESET} {name} can't be undone: {e}");
+    }
+}
+
+/// Forget a killed window's recorded state so nothing leaks into the next
+/// window that reuses its name or pane ID (and `cove restore`), and remove
+/// its container and worktree.
+fn clean_up(name: &str, pane_id: Option<&str>) {
+    if let Some(pane_id) = pane_id {
+        // Journaled before its events go
+        if let Some(session) = state::session_for_pane(pane_id) {
+            let journal = config::load().unwrap_or_default().journal;
+            if let Err(e) = journal::record(&journal, name, &session) {
+                log::warn!("journal: {e}");
+            }
+        }
+        start::purge_pane_state(pane_id, Some(name));
+    }
+    clips::remove(name);
+    sessions::forget(name);
+    attention::clock_out(name);
+    // Options die with the window
+    meta::forget_options(name);
+    if let Err(e) = container::tear_down(name) {
+        eprintln!("{ANSI_PEACH}Warning:{ANSI_RESET} {name}'s container is still running: {e}");
+    }
+    if let Err(e) = worktrees::tear_down(name) {
+        eprintln!("{ANSI_PEACH}Warning:{ANSI_RESET} {name}'s worktree: {e}");
+    }
+}
+
+/// What a parked session's Claude pane shows instead of Claude.
+fn park_command(name: &str) -> String {
+    let text = format!(
+        "Claude is parked. Press R in the sidebar or run `cove restart {name}` to start it again."
+    );
+    format!(
+        "printf '\\n  %s\\n' {}; exec tail -f /dev/null",
+        start::shell_quote(&text)
+    )
+}
 
-pub fn run(name: &str) -> Result<(), String> {
-    if !tmux::has_session() {
+/// Stop Claude in `name`'s pane .1 but keep the window: record a "parked"
+/// event (through `record`, with the Claude session, pane and directory) so
+/// the sidebar stops showing the last turn, mark the pane, and swap Claude
+/// for a placeholder. `cove restart` resumes the conversation from there.
+fn park_in(
+    client: &dyn TmuxClient,
+    name: &str,
+    claude_session: impl Fn(&str) -> Option<String>,
+    record: impl Fn(&str, &str, &str) -> Result<(), String>,
+) -> Result<(), String> {
+    let pane_id = client.get_claude_pane_id(name)?;
+    if pane_id.is_empty() {
+        return Err(format!("No Claude pane found for '{name}'"));
+    }
+    if let Some(session) = claude_session(&pane_id) {
+        record(&session, &pane_id, &restart::pane_path(client, &pane_id))?;
+    }
+    client.set_parked(&pane_id, true)?;
+    client.respawn_pane(&pane_id, &park_command(name))
+}
+
+// ── Public API ──
+
+/// Kill session `name`, or with `keep_pane` only stop its Claude.
+pub fn run(name: &str, keep_pane: bool) -> Result<(), String> {
+    let name = &tmux::resolve_target(name)?;
+    if keep_pane {
+        return park(name);
+    }
+    if tabs::close(name)? {
+        clips::remove(name);
+        stats::record(stats::Kind::Killed, 0);
+        say!("Killed: {ANSI_PEACH}{name}{ANSI_RESET}");
+        return Ok(());
+    }
+
+    run_in(&Tmux, name)
+}
+
+/// Kill tmux session `name` through `client`, keeping it for `cove undo`.
+pub fn run_in(client: &dyn TmuxClient, name: &str) -> Result<(), String> {
+    kill_in(client, &Bookkeeping::home(trash_dir()), name)
+}
+
+/// Stop Claude in session `name` and leave the window parked.
+pub fn park(name: &str) -> Result<(), String> {
+    if tabs::sessions().iter().any(|t| t.name == name) {
+        return Err(format!(
+            "{name} is a terminal tab; only tmux sessions can keep their panes"
+        ));
+    }
+    let record = |session: &str, pane_id: &str, cwd: &str| {
+        hook::write_event(session, cwd, pane_id, state::PARKED, "park")
+    };
+    park_in(&Tmux, name, state::session_for_pane, record)?;
+    say!(
+        "{ANSI_GREEN}Parked{ANSI_RESET} {ANSI_PEACH}{name}{ANSI_RESET}: Claude stopped, window kept {ANSI_OVERLAY}(R in the sidebar or cove restart {name} brings it back){ANSI_RESET}"
+    );
+    Ok(())
+}
+
+/// Kill one cove window without printing anything (for full-screen views),
+/// keeping it in the trash for `cove undo` when possible.
+pub fn kill_quietly(name: &str) -> Result<(), String> {
+    remove_window(&Tmux, &Bookkeeping::home(trash::dir().ok()), name)
+}
+
+/// Kill a window, keeping it and forgetting its recorded state as `books`
+/// says.
+pub fn remove_window(
+    client: &dyn TmuxClient,
+    books: &Bookkeeping,
+    name: &str,
+) -> Result<(), String> {
+    let pane_id = client.get_claude_pane_id(name).ok();
+    if let Some(window) = client.list_windows()?.iter().find(|w| w.name == name) {
+        trash_window(client, books, name, &window.pane_path, pane_id.as_deref());
+    }
+    client.kill_window(name)?;
+    books.forget(name, pane_id.as_deref());
+    Ok(())
+}
+
+fn kill_in(client: &dyn TmuxClient, books: &Bookkeeping, name: &str) -> Result<(), String> {
+    if !client.has_session() {
         println!("{ANSI_OVERLAY}No active cove session.{ANSI_RESET}");
         return Err(String::new());
     }
 
-    tmux::kill_window(name)?;
-    println!("Killed: {ANSI_PEACH}{name}{ANSI_RESET}");
+    let kept = books.trash_for(name).is_some();
+    remove_window(client, books, name)?;
+    let hint = if kept {
+        format!(" {ANSI_OVERLAY}(cove undo restores it){ANSI_RESET}")
+    } else {
+        String::new()
+    };
+    say!("Killed: {ANSI_PEACH}{name}{ANSI_RESET}{hint}");
     Ok(())
 }
 
-pub fn run_all() -> Result<(), String> {
-    if !tmux::has_session() {
+pub fn run_all(
+    except: &[String],
+    filter: Option<StateFilter>,
+    dry_run: bool,
+    yes: bool,
+) -> Result<(), String> {
+    let how = match (dry_run, yes) {
+        (true, _) => Confirm::DryRun,
+        (false, true) => Confirm::Yes,
+        (false, false) => Confirm::Ask,
+    };
+    let books = Bookkeeping::home(if dry_run { None } else { trash_dir() });
+    // Every session is going, and kill-session may end this process too
+    if how == Confirm::Yes && except.is_empty() && filter.is_none() {
+        feed::clear();
+    }
+    run_all_in(&Tmux, StateDetector::new(), &books, except, filter, how)
+}
+
+/// Split the window at index `own` (the caller's) off `targets`, to be
+/// killed after the rest.
+fn own_last(targets: Vec<&WindowInfo>, own: Option<u32>) -> (Vec<&WindowInfo>, Vec<&WindowInfo>) {
+    targets.into_iter().partition(|w| Some(w.index) == own)
+}
+
+fn run_all_in(
+    client: &dyn TmuxClient,
+    mut detector: StateDetector,
+    books: &Bookkeeping,
+    except: &[String],
+    filter: Option<StateFilter>,
+    how: Confirm,
+) -> Result<(), String> {
+    if !client.has_session() {
         println!("{ANSI_OVERLAY}No active cove session.{ANSI_RESET}");
         return Err(String::new());
     }
 
-    tmux::kill_session()?;
-    println!("Killed all sessions.");
-    Ok(())
+    let windows = client.list_windows()?;
+    let states = detector.detect(client, &windows);
+    let targets = select_targets(&windows, &states, except, filter);
+
+    if targets.is_empty() {
+        println!("{ANSI_OVERLAY}No sessions match.{ANSI_RESET}");
+        return Ok(());
+    }
+
+    if how == Confirm::DryRun {
+        println!("Would kill:");
+        for w in &targets {
+            let state = states.get(&w.index).copied().unwrap_or(WindowState::Fresh);
+            println!(
+                "  {ANSI_PEACH}{}{ANSI_RESET}  {ANSI_SUBTEXT}{}{ANSI_RESET}",
+                w.name,
+                state.label()
+            );
+        }
+        return Ok(());
+    }
+    let targets = match how {
+        Confirm::Ask => match choose_targets(&targets, &states, &detector)? {
+            Some(picked) if !picked.is_empty() => picked,
+            _ => {
+                say!("{ANSI_OVERLAY}Nothing killed.{ANSI_RESET}");
+                return Ok(());
+            }
+        },
+        _ => targets,
+    };
+
+    for w in &targets {
+        trash_window(
+            client,
+            books,
+            &w.name,
+            &w.pane_path,
+            detector.pane_id(w.index),
+        );
+    }
+
+    // Everything goes: clean up first, since kill-session also ends this
+    // process when it's run from inside cove
+    if targets.len() == windows.len() {
+        for w in &targets {
+            books.forget(&w.name, detector.pane_id(w.index));
+        }
+        // `run_all` clears it up front when there's nothing to pick
+        if how == Confirm::Ask {
+            feed::clear();
+        }
+        client.kill_session()?;
+        let plural = if targets.len() == 1 { "" } else { "s" };
+        say!("Killed {} session{plural}.", targets.len());
+        return Ok(());
+    }
+
+    // Kill by window ID: indices shift as windows close, and a window may
+    // disappear on its own (Claude exited, another cove kill) mid-loop.
+    // The window this runs in (the sidebar, a key binding) goes last, once
+    // everything else is done, since killing it ends this process.
+    let (own, targets) = own_last(targets, crash::own_window(client));
+    let mut killed = 0;
+    let mut gone = 0;
+    let mut failed = Vec::new();
+    for w in &targets {
+        let pane_id = detector.pane_id(w.index).map(str::to_string);
+        match client.kill_window_id(&w.id) {
+            Ok(true) => killed += 1,
+            Ok(false) => gone += 1,
+            Err(e) => {
+                failed.push(format!("{}: {e}", w.name));
+                continue;
+            }
+        }
+        books.forget(&w.name, pane_id.as_deref());
+    }
+
+    let kept = windows.len() - targets.len() - own.len();
+    killed += own.len();
+    let plural = if killed == 1 { "" } else { "s" };
+    let mut summary = format!("Killed {killed} session{plural}");
+    if gone > 0 {
+        summary.push_str(&format!(", {gone} already gone"));
+    }
+    if kept > 0 {
+        summary.push_str(&format!(", kept {kept}"));
+    }
+    say!("{summary}.");
+
+    for w in &own {
+        books.forget(&w.name, detector.pane_id(w.index));
+        if let Err(e) = client.kill_window_id(&w.id) {
+            failed.push(format!("{}: {e}", w.name));
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed to kill:\n  {}", failed.join("\n  ")))
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+    use std::collections::HashMap;
+
+    fn window(index: u32, name: &str, is_active: bool) -> WindowInfo {
+        WindowInfo {
+            id: format!("@{index}"),
+            index,
+            name: name.to_string(),
+            is_active,
+            pane_path: "/tmp".to_string(),
+            managed: true,
+        }
+    }
+
+    #[test]
+    fn test_select_targets_except_and_state() {
+        let windows = vec![
+            window(1, "api", true),
+            window(2, "web", false),
+            window(3, "docs", false),
+        ];
+        let states = HashMap::from([
+            (1, WindowState::Done),
+            (2, WindowState::Working),
+            (3, WindowState::Done),
+        ]);
+
+        let names = |t: Vec<&WindowInfo>| t.iter().map(|w| w.name.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            names(select_targets(&windows, &states, &["web".into()], None)),
+            vec!["docs", "api"]
+        );
+        assert_eq!(
+            names(select_targets(
+                &windows,
+                &states,
+                &[],
+                Some(StateFilter::Done)
+            )),
+            vec!["docs", "api"]
+        );
+        assert!(select_targets(&windows, &states, &[], Some(StateFilter::Idle)).is_empty());
+    }
+
+    fn fake_session() -> FakeTmux {
+        FakeTmux::new().with_windows(&[
+            ("cove-test-api", "claude", "%t1"),
+            ("cove-test-web", "zsh", "%t2"),
+            ("cove-test-docs", "zsh", "%t3"),
+        ])
+    }
+
+    fn no_events() -> StateDetector {
+        StateDetector::with_events_dir(Path::new("/nonexistent/events"))
+    }
+
+    #[test]
+    fn test_kill_one_targets_window_by_name() {
+        let fake = fake_session();
+        kill_in(&fake, &Bookkeeping::default(), "cove-test-web").unwrap();
+        let kills = fake.calls_to("kill-window");
+        assert_eq!(kills, vec![vec!["kill-window", "-t", "cove:cove-test-web"]]);
+    }
+
+    #[test]
+    fn test_kill_keeps_window_for_undo() {
+        let trash = tempfile::tempdir().unwrap();
+        let fake = fake_session().on("capture-pane", "last screen");
+        let books = Bookkeeping {
+            trash: Some(trash.path().to_path_buf()),
+            ..Default::default()
+        };
+        kill_in(&fake, &books, "cove-test-web").unwrap();
+
+        let kept = std::fs::read_to_string(trash.path().join("cove-test-web.json")).unwrap();
+        let entry: TrashEntry = serde_json::from_str(&kept).unwrap();
+        assert_eq!(entry.dir, "/code/cove-test-web");
+        let capture = std::fs::read_to_string(trash.path().join("cove-test-web.txt")).unwrap();
+        assert_eq!(capture, "last screen");
+    }
+
+    #[test]
+    fn test_park_keeps_window_and_marks_pane() {
+        let fake = fake_session();
+        let recorded = std::cell::RefCell::new(Vec::new());
+        park_in(
+            &fake,
+            "cove-test-api",
+            |_| Some("abc".to_string()),
+            |session, pane, _| {
+                recorded.borrow_mut().push(format!("{session} {pane}"));
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(recorded.into_inner(), ["abc %t1"]);
+        assert!(fake.calls_to("kill-window").is_empty());
+        let marks = fake.calls_to("set-option");
+        assert_eq!(
+            marks,
+            vec![vec!["set-option", "-p", "-t", "%t1", "@cove-parked", "1"]]
+        );
+        let respawn = fake.calls_to("respawn-pane");
+        assert!(
+            respawn[0]
+                .last()
+                .unwrap()
+                .contains("cove restart cove-test-api")
+        );
+    }
+
+    #[test]
+    fn test_all_kill_dry_run_kills_nothing() {
+        let fake = fake_session();
+        let books = Bookkeeping::default();
+        run_all_in(&fake, no_events(), &books, &[], None, Confirm::DryRun).unwrap();
+        assert!(fake.calls_to("kill-window").is_empty());
+        assert!(fake.calls_to("kill-session").is_empty());
+    }
+
+    #[test]
+    fn test_all_kill_everything_kills_session() {
+        let fake = fake_session();
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("stats.jsonl");
+        let books = Bookkeeping {
+            stats_log: Some(log.clone()),
+            ..Default::default()
+        };
+        run_all_in(&fake, no_events(), &books, &[], None, Confirm::Yes).unwrap();
+        assert_eq!(fake.calls_to("kill-session").len(), 1);
+        assert_eq!(stats::read_in(&log).len(), 3);
+        assert!(fake.calls_to("kill-window").is_empty());
+    }
+
+    #[test]
+    fn test_own_window_is_killed_last() {
+        let windows = [window(1, "api", false), window(2, "web", true)];
+        let names = |ws: &[&WindowInfo]| ws.iter().map(|w| w.name.clone()).collect::<Vec<_>>();
+        let (own, rest) = own_last(windows.iter().collect(), Some(2));
+        assert_eq!(names(&own), ["web"]);
+        assert_eq!(names(&rest), ["api"]);
+        let (own, rest) = own_last(windows.iter().collect(), None);
+        assert!(own.is_empty());
+        assert_eq!(names(&rest), ["api", "web"]);
+    }
+
+    #[test]
+    fn test_all_kill_filtered_kills_by_id() {
+        let fake = fake_session();
+        let except = vec!["cove-test-docs".to_string()];
+        run_all_in(
+            &fake,
+            no_events(),
+            &Bookkeeping::default(),
+            &except,
+            Some(StateFilter::Done),
+            Confirm::Yes,
+        )
+        .unwrap();
+        assert_eq!(
+            fake.calls_to("kill-window"),
+            vec![vec!["kill-window", "-t", "@2"]]
+        );
+    }
 }
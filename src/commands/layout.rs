@@ -0,0 +1,144 @@
+// ── cove layout ──
+//
+// Layouts drift: a window from an older cove lacks the crash-aware pane-died
+// hook or pane roles, a hook got unset, a pane was dragged to an odd size.
+// `cove layout apply [name]` puts back what `setup_layout` sets on one
+// session (or every session) without restarting anything in its panes, and
+// says what it fixed. Running it again on a tidy window changes nothing.
+//
+// `cove layout <name> --preset even|main-vertical|focus` rearranges one
+// session with `select-layout` and sets the Claude pane's share of the width,
+// which the layout hook (and `apply`) keep from then on. The preset is kept
+// in the session's metadata, so a window `cove up` or `cove undo` re-creates,
+// or one whose Claude pane `R` repairs, gets it back.
+
+use crate::cli::Preset;
+use crate::colors::*;
+use crate::tmux::{self, Tmux, TmuxClient};
+use crate::{meta, say};
+
+// ── Helpers ──
+
+/// tmux layout of `preset`, and the Claude pane's percentage of the width.
+fn arrangement(preset: Preset) -> (&'static str, u32) {
+    match preset {
+        Preset::Even => ("even-horizontal", 33),
+        Preset::MainVertical => ("main-vertical", 70),
+        Preset::Focus => ("main-vertical", 85),
+    }
+}
+
+fn select_in(client: &dyn TmuxClient, name: &str, preset: Preset) -> Result<(), String> {
+    let (layout, claude_width) = arrangement(preset);
+    client.select_preset(name, layout, claude_width)
+}
+
+/// Re-apply the layout to each of `names`, printing what changed. Returns
+/// how many windows couldn't be fixed.
+fn apply_in(client: &dyn TmuxClient, names: &[String]) -> usize {
+    let mut failed = 0;
+    for name in names {
+        match client.apply_layout(name) {
+            Ok(fixes) if fixes.is_empty() => {
+                say!("{ANSI_PEACH}{name}{ANSI_RESET}  {ANSI_OVERLAY}already tidy{ANSI_RESET}");
+            }
+            Ok(fixes) => say!(
+                "{ANSI_PEACH}{name}{ANSI_RESET}  {ANSI_GREEN}fixed{ANSI_RESET} {}",
+                fixes.join(", ")
+            ),
+            Err(e) => {
+                eprintln!("{ANSI_PEACH}{name}{ANSI_RESET}  {ANSI_RED}{e}{ANSI_RESET}");
+                failed += 1;
+            }
+        }
+    }
+    failed
+}
+
+// ── Public API ──
+
+/// Arrange session `name` by `preset` and remember it for the window.
+pub fn preset(name: &str, preset: Preset) -> Result<(), String> {
+    let name = tmux::resolve_target(name)?;
+    select_in(&Tmux, &name, preset)?;
+    meta::update(&name, |m| {
+        m.layout = (preset != Preset::MainVertical).then_some(preset)
+    })?;
+    say!("{ANSI_PEACH}{name}{ANSI_RESET}  {ANSI_GREEN}{preset}{ANSI_RESET}");
+    Ok(())
+}
+
+/// Give a re-created or repaired window for session `name` the preset last
+/// picked for it, if any.
+pub fn reapply_in(client: &dyn TmuxClient, name: &str) {
+    if let Some(preset) = meta::layout(name)
+        && let Err(e) = select_in(client, name, preset)
+    {
+        log::warn!("{preset} layout for {name}: {e}");
+    }
+}
+
+/// Re-apply cove's layout to session `name`, or to every session.
+pub fn apply(name: Option<&str>) -> Result<(), String> {
+    let names = match name {
+        Some(name) => vec![tmux::resolve_target(name)?],
+        None => tmux::list_windows()?
+            .into_iter()
+            .filter(|w| w.managed)
+            .map(|w| w.name)
+            .collect(),
+    };
+    if names.is_empty() {
+        println!("{ANSI_OVERLAY}No sessions.{ANSI_RESET}");
+        return Ok(());
+    }
+    match apply_in(&Tmux, &names) {
+        0 => Ok(()),
+        n => Err(format!("{n} of {} sessions couldn't be fixed", names.len())),
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_apply_runs_one_command_chain_per_window() {
+        let fake = FakeTmux::new()
+            .on(
+                "list-panes",
+                "%1||100|50|200|0|off|\n%2||99|10|200|0|off|\n%3||99|39|200|0|off|\n",
+            )
+            .on("show-hooks", "");
+        let failed = apply_in(&fake, &["api".to_string(), "web".to_string()]);
+        assert_eq!(failed, 0);
+        let chains = fake.calls_to("set-option");
+        assert_eq!(chains.len(), 2);
+        assert_eq!(chains[0][..4], ["set-option", "-w", "-t", "cove:api"]);
+        assert!(chains[0].iter().any(|a| a == "pane-died"));
+    }
+
+    #[test]
+    fn test_preset_sets_layout_and_claude_width() {
+        let fake = FakeTmux::new();
+        select_in(&fake, "api", Preset::Even).unwrap();
+        assert_eq!(
+            fake.calls_to("set-option"),
+            [[
+                "set-option",
+                "-w",
+                "-t",
+                "cove:api",
+                "@cove-claude-width",
+                "33"
+            ]]
+        );
+        assert_eq!(
+            fake.calls_to("select-layout").remove(0).join(" "),
+            "select-layout -t cove:api even-horizontal ; resize-pane -t cove:api.1 -x 33%"
+        );
+    }
+}
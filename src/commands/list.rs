@@ -1,29 +1,231 @@
+use std::io::IsTerminal;
+
+use crate::ci::{self, CiStatus};
 use crate::colors::*;
-use crate::tmux;
+use crate::sidebar::state::{self, WindowState};
+use crate::{config, meta, paths, tabs, tmux, width};
 
-pub fn run() -> Result<(), String> {
-    if !tmux::has_session() {
-        println!("{ANSI_OVERLAY}No active cove session.{ANSI_RESET}");
-        return Err(String::new());
+// ── Types ──
+
+struct Row {
+    name: String,
+    dir: String,
+    active: bool,
+    /// Backend/state suffix for tab sessions.
+    suffix: Option<String>,
+    /// Checks of the session's pull request, as last seen by a sidebar.
+    ci: Option<CiStatus>,
+}
+
+// ── Constants ──
+
+/// Marker column ("  ❯ " / "    ") plus the gap after the name.
+const FIXED_WIDTH: usize = 6;
+/// Never squeeze the directory column below this, even on tiny terminals.
+const MIN_DIR_WIDTH: usize = 12;
+
+// ── Helpers ──
+
+/// Shorten a path to at most `max` columns by eliding middle components:
+/// `~/code/org/repo/deep/dir` → `~/code/…/deep/dir`.
+fn truncate_middle(path: &str, max: usize) -> String {
+    if width::of(path) <= max {
+        return path.to_string();
     }
 
-    let windows = tmux::list_windows()?;
-    let home = std::env::var("HOME").unwrap_or_default();
+    // Keep the root ("~/code", "/var", "code") and as many trailing components as fit
+    let parts: Vec<&str> = path.split('/').collect();
+    let head_len = if parts[0].is_empty() || parts[0] == "~" {
+        2
+    } else {
+        1
+    };
+    if parts.len() > head_len + 1 {
+        let head = parts[..head_len].join("/");
+        let mut keep = 0;
+        for n in 1..parts.len() - head_len {
+            let tail = parts[parts.len() - n..].join("/");
+            if width::of(&head) + 3 + width::of(&tail) > max {
+                break;
+            }
+            keep = n;
+        }
+        if keep > 0 {
+            let tail = parts[parts.len() - keep..].join("/");
+            return format!("{head}/\u{2026}/{tail}");
+        }
+    }
 
-    for w in &windows {
-        let dir = w.pane_path.replace(&home, "~");
-        if w.is_active {
+    // Not enough components to elide: keep the end of the path
+    width::truncate_start(path, max)
+}
+
+/// " ✓" / " ✗" / " ●" after a session name, as in the sidebar.
+fn ci_mark(status: CiStatus) -> (&'static str, &'static str) {
+    match status {
+        CiStatus::Passed => (" \u{2713}", ANSI_GREEN),
+        CiStatus::Failed => (" \u{2717}", ANSI_RED),
+        CiStatus::Pending => (" \u{25cf}", ANSI_PEACH),
+    }
+}
+
+/// Width of a row's name column: the name plus its CI mark.
+fn name_len(row: &Row) -> usize {
+    width::of(&row.name) + if row.ci.is_some() { 2 } else { 0 }
+}
+
+fn terminal_width() -> Option<usize> {
+    crossterm::terminal::size().ok().map(|(w, _)| w as usize)
+}
+
+/// Print rows with names aligned; `width` limits line length, `color` enables ANSI.
+fn print_rows(rows: &[Row], width: Option<usize>, color: bool) {
+    let c = |code: &'static str| if color { code } else { "" };
+    let name_width = rows.iter().map(name_len).max().unwrap_or(0);
+
+    for row in rows {
+        let suffix_width = row.suffix.as_ref().map_or(0, |s| width::of(s) + 2);
+        let dir = match width {
+            Some(w) => {
+                let budget = w
+                    .saturating_sub(FIXED_WIDTH + name_width + suffix_width)
+                    .max(MIN_DIR_WIDTH);
+                truncate_middle(&row.dir, budget)
+            }
+            None => row.dir.clone(),
+        };
+        let pad = " ".repeat(name_width - name_len(row));
+        let mark = row
+            .ci
+            .map(|s| {
+                let (glyph, code) = ci_mark(s);
+                format!("{}{glyph}{}", c(code), c(ANSI_RESET))
+            })
+            .unwrap_or_default();
+        let suffix = row
+            .suffix
+            .as_ref()
+            .map(|s| format!("  {}{s}{}", c(ANSI_SUBTEXT), c(ANSI_RESET)))
+            .unwrap_or_default();
+
+        if row.active {
             println!(
-                "  {ANSI_WHITE}{ANSI_BOLD}❯{ANSI_RESET} {ANSI_WHITE}{ANSI_BOLD}{}{ANSI_RESET}  {ANSI_SUBTEXT}{dir}{ANSI_RESET}",
-                w.name
+                "  {}{}❯{} {}{}{}{}{mark}{pad}  {}{dir}{}{suffix}",
+                c(ANSI_WHITE),
+                c(ANSI_BOLD),
+                c(ANSI_RESET),
+                c(ANSI_WHITE),
+                c(ANSI_BOLD),
+                row.name,
+                c(ANSI_RESET),
+                c(ANSI_SUBTEXT),
+                c(ANSI_RESET),
             );
         } else {
             println!(
-                "    {ANSI_OVERLAY}{}{ANSI_RESET}  {ANSI_SURFACE}{dir}{ANSI_RESET}",
-                w.name
+                "    {}{}{}{mark}{pad}  {}{dir}{}{suffix}",
+                c(ANSI_OVERLAY),
+                row.name,
+                c(ANSI_RESET),
+                c(ANSI_SURFACE),
+                c(ANSI_RESET),
             );
         }
     }
+}
+
+// ── Public API ──
+
+pub fn run() -> Result<(), String> {
+    let has_session = tmux::has_session();
+    let tab_sessions = tabs::sessions();
+    if !has_session && tab_sessions.is_empty() {
+        println!("{ANSI_OVERLAY}No active cove session.{ANSI_RESET}");
+        return Err(String::new());
+    }
+
+    // Piped output gets plain text and full paths, so scripts can split on
+    // columns; CI marks would shift them
+    let tty = std::io::stdout().is_terminal();
+    let ci = if tty && config::load().is_ok_and(|c| c.ci.enabled) {
+        ci::cached()
+    } else {
+        Default::default()
+    };
+
+    let mut rows = Vec::new();
+    if has_session {
+        for w in tmux::list_windows()? {
+            rows.push(Row {
+                ci: ci.get(&w.name).copied(),
+                dir: paths::tildify(&w.pane_path),
+                name: w.name,
+                active: w.is_active,
+                suffix: None,
+            });
+        }
+    }
+    if !tab_sessions.is_empty() {
+        let states = state::load_pane_states();
+        for t in tab_sessions {
+            let state = states.get(&t.token).copied().unwrap_or(WindowState::Fresh);
+            rows.push(Row {
+                dir: paths::tildify(&t.dir),
+                suffix: Some(format!("[{}] {}", t.backend, state.label())),
+                name: t.name,
+                active: false,
+                ci: None,
+            });
+        }
+    }
+
+    meta::pinned_first(&mut rows, &meta::pinned(), |r| &r.name);
+
+    let width = if tty { terminal_width() } else { None };
+    print_rows(&rows, width, tty);
 
     Ok(())
 }
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_middle_fits() {
+        assert_eq!(truncate_middle("~/code/api", 20), "~/code/api");
+    }
+
+    #[test]
+    fn test_truncate_middle_elides_components() {
+        assert_eq!(
+            truncate_middle("~/code/org/repo/deep/dir", 17),
+            "~/code/\u{2026}/deep/dir"
+        );
+        assert_eq!(
+            truncate_middle("/var/lib/things/deep/dir", 14),
+            "/var/\u{2026}/dir"
+        );
+    }
+
+    #[test]
+    fn test_truncate_middle_long_component() {
+        let out = truncate_middle("~/an-extremely-long-directory-name", 10);
+        assert_eq!(out.chars().count(), 10);
+        assert!(out.starts_with('\u{2026}'));
+        assert!(out.ends_with("name"));
+    }
+
+    #[test]
+    fn test_truncate_middle_counts_columns() {
+        // Each of these characters takes two columns
+        let out = truncate_middle("~/code/プロジェクト/修正", 14);
+        assert_eq!(out, "~/code/\u{2026}/修正");
+        assert_eq!(
+            width::of(&truncate_middle("~/修正されたディレクトリ", 9)),
+            9
+        );
+    }
+}
@@ -0,0 +1,187 @@
+// ── cove merge ──
+//
+// Every cove session is a window of one tmux session, `cove`; the sidebar
+// lists that session's windows and nothing else. A second tmux session named
+// like it (a `tmux new -s cove2` by hand, or the wrong one restored after a
+// crash) splits the workspace, and its windows run unseen. `cove merge
+// <from> [into]` moves every window of `from` into `into` (default `cove`)
+// and puts back the cove layout of the ones it moves into `cove`; tmux
+// removes `from` once it's empty. Without arguments it merges every tmux
+// session named `cove-<something>`, the ones `cove doctor` warns about.
+// Metadata, clips and shares are keyed by window name or id, which the move
+// keeps, so they carry over. A window whose name is taken in `into` is
+// renamed `<name>-2` (or -3, ...) and gets a copy of the name's metadata
+// (`meta::copy`); its worktree, container, slot, clips and share stay with
+// the window that kept the name.
+
+use std::collections::HashSet;
+
+use crate::colors::*;
+use crate::tmux::{SESSION, Tmux, TmuxClient, WindowInfo};
+use crate::{meta, say};
+
+// ── Types ──
+
+/// One window to move: its id, its name, and its name once moved.
+#[derive(Debug, PartialEq, Eq)]
+struct Move {
+    id: String,
+    name: String,
+    new_name: String,
+    managed: bool,
+}
+
+// ── Helpers ──
+
+/// `name`, or the first `<name>-N` not in `taken`.
+fn free_name(name: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{name}-{n}"))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_default()
+}
+
+/// Moves for `windows` into a session whose windows are named `into`.
+fn plan(windows: &[WindowInfo], into: &[String]) -> Vec<Move> {
+    let mut taken: HashSet<String> = into.iter().cloned().collect();
+    windows
+        .iter()
+        .map(|w| {
+            let new_name = free_name(&w.name, &taken);
+            taken.insert(new_name.clone());
+            Move {
+                id: w.id.clone(),
+                name: w.name.clone(),
+                new_name,
+                managed: w.managed,
+            }
+        })
+        .collect()
+}
+
+/// Move every window of tmux session `from` into `into`. Returns one line
+/// per window moved.
+fn merge_in(client: &dyn TmuxClient, from: &str, into: &str) -> Result<Vec<String>, String> {
+    if from == into {
+        return Err(format!("Can't merge '{from}' into itself."));
+    }
+    let sessions = client.list_sessions()?;
+    for session in [from, into] {
+        if !sessions.iter().any(|s| s == session) {
+            return Err(format!("No tmux session '{session}'."));
+        }
+    }
+    let into_names: Vec<String> = client
+        .session_windows(into)?
+        .into_iter()
+        .map(|w| w.name)
+        .collect();
+
+    let mut lines = Vec::new();
+    for m in plan(&client.session_windows(from)?, &into_names) {
+        client.move_window(&m.id, into)?;
+        let mut line = format!("Moved {ANSI_PEACH}{}{ANSI_RESET}", m.name);
+        if m.new_name != m.name {
+            client.output(&["rename-window", "-t", &m.id, &m.new_name])?;
+            if let Err(e) = meta::copy(&m.name, &m.new_name) {
+                log::warn!("copying metadata of {} to {}: {e}", m.name, m.new_name);
+            }
+            line.push_str(&format!(" as {ANSI_PEACH}{}{ANSI_RESET}", m.new_name));
+        }
+        if m.managed && into == SESSION {
+            match client.apply_layout(&m.new_name) {
+                Ok(fixes) if !fixes.is_empty() => {
+                    line.push_str(&format!(
+                        " {ANSI_OVERLAY}(fixed {}){ANSI_RESET}",
+                        fixes.join(", ")
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => line.push_str(&format!(" {ANSI_RED}({e}){ANSI_RESET}")),
+            }
+        }
+        lines.push(line);
+    }
+    Ok(lines)
+}
+
+// ── Public API ──
+
+/// Other tmux sessions named like cove's, e.g. "cove-old" or "cove-2";
+/// "coverage" is someone else's.
+pub fn strays(sessions: &[String]) -> Vec<String> {
+    let prefix = format!("{SESSION}-");
+    sessions
+        .iter()
+        .filter(|s| s.starts_with(&prefix))
+        .cloned()
+        .collect()
+}
+
+/// Merge tmux session `from` into `into` (default cove's), or without
+/// `from`, every stray cove session into cove's.
+pub fn run(from: Option<&str>, into: Option<&str>) -> Result<(), String> {
+    let into = into.unwrap_or(SESSION);
+    let sources = match from {
+        Some(from) => vec![from.to_string()],
+        None => strays(&Tmux.list_sessions()?),
+    };
+    if sources.is_empty() {
+        say!("{ANSI_OVERLAY}No other cove sessions to merge.{ANSI_RESET}");
+        return Ok(());
+    }
+    for from in &sources {
+        for line in merge_in(&Tmux, from, into)? {
+            say!("{line}");
+        }
+        say!("Merged {ANSI_WHITE}{from}{ANSI_RESET} into {ANSI_WHITE}{into}{ANSI_RESET}");
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_plan_renames_taken_names() {
+        let window = |id: &str, name: &str| WindowInfo {
+            id: id.to_string(),
+            index: 1,
+            name: name.to_string(),
+            is_active: false,
+            pane_path: String::new(),
+            managed: true,
+        };
+        let into = ["api".to_string(), "api-2".to_string()];
+        let moves = plan(&[window("@7", "api"), window("@8", "web")], &into);
+        assert_eq!(
+            moves
+                .iter()
+                .map(|m| (m.id.as_str(), m.new_name.as_str()))
+                .collect::<Vec<_>>(),
+            [("@7", "api-3"), ("@8", "web")]
+        );
+
+        let sessions = ["cove", "cove-2", "coverage", "share-cove-@3", "work"].map(String::from);
+        assert_eq!(strays(&sessions), ["cove-2"]);
+    }
+
+    #[test]
+    fn test_merge_needs_both_sessions() {
+        let client = FakeTmux::new().on("list-sessions", "cove\ncove2\n");
+        assert!(
+            merge_in(&client, "cove3", "cove")
+                .unwrap_err()
+                .contains("No tmux session 'cove3'")
+        );
+        assert!(merge_in(&client, "cove", "cove").is_err());
+        assert!(client.calls_to("move-window").is_empty());
+    }
+}
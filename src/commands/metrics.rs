@@ -0,0 +1,283 @@
+// ── cove metrics ──
+//
+// Agent activity in the Prometheus text format, for graphing in Grafana.
+// `cove metrics` prints one scrape (handy for node_exporter's textfile
+// collector); `cove metrics --serve [addr]` stays up and answers
+// `GET /metrics` itself, reading everything fresh on each request.
+//
+//   cove_sessions{state}                   gauge: live sessions per state
+//   cove_prompts_total                     counter: prompts logged (prompts.rs)
+//   cove_state_transitions_total{state}    counter: changes into each state
+//   cove_working_duration_seconds          histogram: finished Working stretches
+//
+// Events come from the live event files plus the history of killed sessions,
+// like `cove timesheet`. An event repeating the session's state isn't a
+// transition; a Working stretch runs from the change into Working to the next
+// change out of it, and the one still running isn't observed until it ends.
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::colors::*;
+use crate::commands::timesheet::{self, Session};
+use crate::sidebar::state::{self, WindowState};
+use crate::{paths, prompts, quota, tabs, tmux};
+
+// ── Types ──
+
+#[derive(Debug, Default, PartialEq)]
+struct Snapshot {
+    /// Live sessions per state name.
+    sessions: BTreeMap<&'static str, usize>,
+    prompts: usize,
+    /// Changes into each state, by name.
+    transitions: BTreeMap<String, u64>,
+    /// Seconds of each finished Working stretch.
+    working: Vec<u64>,
+}
+
+// ── Constants ──
+
+/// Upper bounds of the working-duration buckets, in seconds.
+const BUCKETS: &[u64] = &[30, 60, 300, 600, 1800, 3600, 7200];
+
+/// How long a scraper gets to send its request line; requests are handled
+/// one at a time, so an idle connection mustn't hold up the next.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+const STATES: &[WindowState] = &[
+    WindowState::Fresh,
+    WindowState::Working,
+    WindowState::Asking,
+    WindowState::Idle,
+    WindowState::Done,
+    WindowState::Queued,
+];
+
+// ── Helpers ──
+
+/// State as a label value; `label()` is for people ("your turn").
+fn state_name(state: WindowState) -> &'static str {
+    match state {
+        WindowState::Fresh => "fresh",
+        WindowState::Working => "working",
+        WindowState::Asking => "asking",
+        WindowState::Idle => "idle",
+        WindowState::Done => "done",
+        WindowState::Queued => "queued",
+    }
+}
+
+/// Transition counts and Working stretches from every session's events.
+fn from_events(sessions: &[(String, Session)], snapshot: &mut Snapshot) {
+    for (_, session) in sessions {
+        // Milestones aren't transitions, and a turn runs on past them
+        let events: Vec<_> = session
+            .events
+            .iter()
+            .filter(|e| !e.is_milestone())
+            .collect();
+        let mut previous = None;
+        let mut working_since = None;
+        for event in events {
+            let state = state::state_from_str(&event.state);
+            if previous == Some(state) {
+                continue;
+            }
+            previous = Some(state);
+            *snapshot
+                .transitions
+                .entry(state_name(state).to_string())
+                .or_default() += 1;
+            if let Some(start) = working_since.take() {
+                snapshot.working.push(event.ts.saturating_sub(start));
+            }
+            if state == WindowState::Working {
+                working_since = Some(event.ts);
+            }
+        }
+    }
+}
+
+/// Live sessions per state: tmux Claude panes and terminal tabs, by their
+/// latest event (Fresh before the first one).
+fn live_sessions() -> BTreeMap<&'static str, usize> {
+    let mut live: HashSet<String> = if tmux::has_session() {
+        quota::live_panes(&tmux::Tmux)
+    } else {
+        HashSet::new()
+    };
+    live.extend(tabs::sessions().into_iter().map(|t| t.token));
+    let states = state::load_pane_states();
+    let mut out: BTreeMap<&'static str, usize> =
+        STATES.iter().map(|s| (state_name(*s), 0)).collect();
+    for pane in &live {
+        let state = states.get(pane).copied().unwrap_or(WindowState::Fresh);
+        *out.entry(state_name(state)).or_default() += 1;
+    }
+    out
+}
+
+fn collect() -> Snapshot {
+    let mut snapshot = Snapshot {
+        sessions: live_sessions(),
+        prompts: prompts::count(),
+        ..Snapshot::default()
+    };
+    for dir in [state::history_dir(), paths::events_dir()]
+        .into_iter()
+        .flatten()
+    {
+        from_events(&timesheet::read_sessions(&dir), &mut snapshot);
+    }
+    snapshot
+}
+
+fn render(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+    out.push_str("# HELP cove_sessions Live sessions per state.\n");
+    out.push_str("# TYPE cove_sessions gauge\n");
+    for (state, count) in &snapshot.sessions {
+        out.push_str(&format!("cove_sessions{{state=\"{state}\"}} {count}\n"));
+    }
+
+    out.push_str("# HELP cove_prompts_total Prompts submitted to Claude.\n");
+    out.push_str("# TYPE cove_prompts_total counter\n");
+    out.push_str(&format!("cove_prompts_total {}\n", snapshot.prompts));
+
+    out.push_str(
+        "# HELP cove_state_transitions_total Changes of session state, by the state moved to.\n",
+    );
+    out.push_str("# TYPE cove_state_transitions_total counter\n");
+    for (state, count) in &snapshot.transitions {
+        out.push_str(&format!(
+            "cove_state_transitions_total{{state=\"{state}\"}} {count}\n"
+        ));
+    }
+
+    out.push_str("# HELP cove_working_duration_seconds How long Claude worked per turn.\n");
+    out.push_str("# TYPE cove_working_duration_seconds histogram\n");
+    for bound in BUCKETS {
+        let count = snapshot.working.iter().filter(|s| *s <= bound).count();
+        out.push_str(&format!(
+            "cove_working_duration_seconds_bucket{{le=\"{bound}\"}} {count}\n"
+        ));
+    }
+    let total = snapshot.working.len();
+    out.push_str(&format!(
+        "cove_working_duration_seconds_bucket{{le=\"+Inf\"}} {total}\n"
+    ));
+    let sum: u64 = snapshot.working.iter().sum();
+    out.push_str(&format!("cove_working_duration_seconds_sum {sum}\n"));
+    out.push_str(&format!("cove_working_duration_seconds_count {total}\n"));
+    out
+}
+
+/// The response to one HTTP request line ("GET /metrics HTTP/1.1").
+fn respond(request_line: &str, body: impl FnOnce() -> String) -> String {
+    let path = request_line.split_whitespace().nth(1).unwrap_or("");
+    let (status, content_type, body) = match path.split('?').next() {
+        Some("/metrics") => ("200 OK", "text/plain; version=0.0.4", body()),
+        _ => ("404 Not Found", "text/plain", "see /metrics\n".to_string()),
+    };
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+fn handle(stream: TcpStream) {
+    if stream.set_read_timeout(Some(READ_TIMEOUT)).is_err() {
+        return;
+    }
+    let mut line = String::new();
+    if BufReader::new(&stream).read_line(&mut line).is_err() {
+        return;
+    }
+    let response = respond(&line, || render(&collect()));
+    let _ = (&stream).write_all(response.as_bytes());
+}
+
+// ── Public API ──
+
+/// Print the metrics once, or with `serve` answer scrapes on that address.
+pub fn run(serve: Option<&str>) -> Result<(), String> {
+    let Some(addr) = serve else {
+        print!("{}", render(&collect()));
+        return Ok(());
+    };
+    let listener = TcpListener::bind(addr).map_err(|e| format!("listen on {addr}: {e}"))?;
+    println!("Serving {ANSI_PEACH}http://{addr}/metrics{ANSI_RESET} (Ctrl-C to stop)");
+    for stream in listener.incoming().flatten() {
+        handle(stream);
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sidebar::state::EventEntry;
+
+    fn event(state: &str, ts: u64) -> EventEntry {
+        EventEntry {
+            state: state.to_string(),
+            cwd: "/code/api".to_string(),
+            pane_id: "%1".to_string(),
+            ts,
+            hook: String::new(),
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_events_give_transitions_and_working_stretches() {
+        let session = Session {
+            name: None,
+            events: vec![
+                event("working", 100),
+                event("asking", 140),
+                event("working", 200),
+                // Another tool call in the same turn: not a transition
+                event("working", 500),
+                EventEntry {
+                    hook: state::MILESTONE_HOOK.to_string(),
+                    note: "migrated db schema".to_string(),
+                    ..event("working", 900)
+                },
+                event("idle", 2_000),
+                event("working", 3_000),
+            ],
+        };
+        let mut snapshot = Snapshot::default();
+        from_events(&[("s1".to_string(), session)], &mut snapshot);
+        assert_eq!(snapshot.transitions["working"], 3);
+        assert_eq!(snapshot.transitions["asking"], 1);
+        // The turn still running isn't a finished stretch yet
+        assert_eq!(snapshot.working, [40, 1_800]);
+
+        let text = render(&snapshot);
+        assert!(text.contains("cove_working_duration_seconds_bucket{le=\"60\"} 1\n"));
+        assert!(text.contains("cove_working_duration_seconds_bucket{le=\"1800\"} 2\n"));
+        assert!(text.contains("cove_working_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("cove_working_duration_seconds_sum 1840\n"));
+        assert!(text.contains("cove_state_transitions_total{state=\"idle\"} 1\n"));
+    }
+
+    #[test]
+    fn test_respond_serves_only_metrics() {
+        let ok = respond("GET /metrics HTTP/1.1\r\n", || {
+            "cove_prompts_total 3\n".to_string()
+        });
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(ok.contains("Content-Length: 21\r\n"));
+        assert!(ok.ends_with("\r\n\r\ncove_prompts_total 3\n"));
+
+        let missing = respond("GET / HTTP/1.1\r\n", || unreachable!());
+        assert!(missing.starts_with("HTTP/1.1 404"));
+    }
+}
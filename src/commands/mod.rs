@@ -1,6 +1,46 @@
+pub mod adopt;
+pub mod annotate;
+pub mod bind;
+pub mod changes;
+pub mod checklist;
+pub mod clean;
+pub mod cmds;
+pub mod completions;
+pub mod console;
+pub mod copy;
+pub mod delegate;
+pub mod doctor;
+pub mod du;
+pub mod focus;
+pub mod go;
 pub mod hook;
 pub mod init;
+pub mod interrupt;
 pub mod kill;
+pub mod layout;
 pub mod list;
+pub mod merge;
+pub mod metrics;
+pub mod name_check;
+pub mod orphan_scan;
+pub mod pair;
+pub mod pin;
+pub mod prompt_segment;
+pub mod prompts;
+pub mod quota;
+pub mod rename;
+pub mod restart;
+pub mod restore;
 pub mod resume;
+pub mod rollback;
+pub mod send;
+pub mod share;
 pub mod start;
+pub mod stats;
+pub mod timesheet;
+pub mod top;
+pub mod undo;
+pub mod up;
+pub mod verify_hooks;
+pub mod watch;
+pub mod watch_run;
@@ -0,0 +1,18 @@
+use crate::colors::*;
+use crate::names;
+
+// ── Public API ──
+
+/// Say whether `name` can be used for a new session, suggesting one if not.
+pub fn run(name: &str) -> Result<(), String> {
+    match names::problem(name) {
+        None => {
+            println!("{ANSI_PEACH}{name}{ANSI_RESET} is a valid session name");
+            Ok(())
+        }
+        Some(problem) => Err(format!(
+            "'{name}' {problem}.\n  Suggested: {ANSI_PEACH}{}{ANSI_RESET}",
+            names::sanitize(name)
+        )),
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::colors::*;
+use crate::sidebar::format::format_age;
+use crate::sidebar::state;
+use crate::tmux::Tmux;
+use crate::{orphans, say};
+
+// ── Public API ──
+
+/// Report event files left by panes that are gone, and archive or delete them.
+pub fn run(archive: bool, delete: bool) -> Result<(), String> {
+    let found = orphans::find(&Tmux)?;
+    if found.is_empty() {
+        say!("{ANSI_OVERLAY}No orphaned event files.{ANSI_RESET}");
+        return Ok(());
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    for orphan in &found {
+        let pane = if orphan.pane_id.is_empty() {
+            "no pane"
+        } else {
+            &orphan.pane_id
+        };
+        println!(
+            "  {ANSI_OVERLAY}{:>4}{ANSI_RESET}  {ANSI_SUBTEXT}{:<9}{ANSI_RESET}  {ANSI_PEACH}{:<10}{ANSI_RESET}  {}",
+            format_age(now.saturating_sub(orphan.ts)),
+            state::state_from_str(&orphan.state).label(),
+            pane,
+            orphan.session_id
+        );
+    }
+
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    if delete {
+        let n = orphans::delete(&found);
+        say!(
+            "{ANSI_GREEN}Deleted{ANSI_RESET} {n} orphaned event file{}.",
+            plural(n)
+        );
+    } else if archive {
+        let n = orphans::archive(&found);
+        say!(
+            "{ANSI_GREEN}Archived{ANSI_RESET} {n} orphaned event file{} to the history.",
+            plural(n)
+        );
+    } else {
+        say!(
+            "{ANSI_OVERLAY}{} orphaned event file{}: --archive moves them to the history, --delete removes them.{ANSI_RESET}",
+            found.len(),
+            plural(found.len())
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,181 @@
+// ── cove pair ──
+//
+// Show two sessions' Claude panes side by side in a temporary "pair" window,
+// e.g. to compare two agents attempting the same task. The panes are moved
+// there with break-pane/join-pane, so both Claudes keep running, and the
+// window records in @cove-pair where each pane came from. `cove pair --end`
+// moves them back as the full-height left pane of their own windows, where
+// the layout hook sizes them again. While paired, those windows show only
+// their sidebar and terminal.
+
+use crate::colors::*;
+use crate::say;
+use crate::tmux::{self, PAIR_OPTION, Tmux, TmuxClient};
+
+// ── Constants ──
+
+const PAIR_WINDOW: &str = "pair";
+
+// ── Helpers ──
+
+fn window_id(client: &dyn TmuxClient, name: &str) -> Result<String, String> {
+    client
+        .list_windows()?
+        .into_iter()
+        .find(|w| w.name == name && w.managed)
+        .map(|w| w.id)
+        .ok_or_else(|| format!("No session named '{name}'"))
+}
+
+fn pair_window(client: &dyn TmuxClient) -> Result<Option<String>, String> {
+    Ok(client
+        .list_windows()?
+        .into_iter()
+        .find(|w| w.name == PAIR_WINDOW)
+        .map(|w| w.id))
+}
+
+/// `(pane id, window id)` pairs stored as "%1:@2 %5:@3".
+fn parse_origins(value: &str) -> Vec<(String, String)> {
+    value
+        .split_whitespace()
+        .filter_map(|entry| {
+            let (pane, window) = entry.split_once(':')?;
+            Some((pane.to_string(), window.to_string()))
+        })
+        .collect()
+}
+
+fn pair_in(client: &dyn TmuxClient, a: &str, b: &str) -> Result<String, String> {
+    if a == b {
+        return Err("Pick two different sessions to pair".to_string());
+    }
+    if pair_window(client)?.is_some() {
+        return Err(format!(
+            "A pair view is already open. End it with {ANSI_PEACH}cove pair --end{ANSI_RESET}"
+        ));
+    }
+    let mut origins = Vec::new();
+    for name in [a, b] {
+        origins.push((client.get_claude_pane_id(name)?, window_id(client, name)?));
+    }
+    let (left, right) = (&origins[0].0, &origins[1].0);
+
+    let pair = client.output(&[
+        "break-pane",
+        "-d",
+        "-s",
+        left,
+        "-n",
+        PAIR_WINDOW,
+        "-P",
+        "-F",
+        "#{window_id}",
+    ])?;
+    let pair = pair.trim().to_string();
+    client.output(&["join-pane", "-d", "-h", "-s", right, "-t", left])?;
+    let value: Vec<String> = origins
+        .iter()
+        .map(|(pane, window)| format!("{pane}:{window}"))
+        .collect();
+    client.output(&[
+        "set-option",
+        "-w",
+        "-t",
+        &pair,
+        PAIR_OPTION,
+        &value.join(" "),
+    ])?;
+    client.output(&["select-window", "-t", &pair])?;
+    Ok(pair)
+}
+
+fn end_in(client: &dyn TmuxClient) -> Result<(), String> {
+    let pair = pair_window(client)?.ok_or("No pair view is open")?;
+    let value = client.output(&["show-options", "-wqv", "-t", &pair, PAIR_OPTION])?;
+    let origins = parse_origins(&value);
+    if origins.is_empty() {
+        return Err(format!(
+            "Window '{PAIR_WINDOW}' wasn't opened by cove pair; leaving it alone"
+        ));
+    }
+    // The pair window closes by itself once its last pane has moved out
+    for (pane, window) in &origins {
+        client.output(&[
+            "join-pane",
+            "-d",
+            "-f",
+            "-h",
+            "-b",
+            "-s",
+            pane,
+            "-t",
+            window,
+        ])?;
+    }
+    client.output(&["select-window", "-t", &origins[0].1])?;
+    Ok(())
+}
+
+// ── Public API ──
+
+pub fn run(a: Option<&str>, b: Option<&str>, end: bool) -> Result<(), String> {
+    if end {
+        end_in(&Tmux)?;
+        say!("{ANSI_GREEN}Pair view closed{ANSI_RESET}; both panes are back in their sessions");
+        return Ok(());
+    }
+    let (Some(a), Some(b)) = (a, b) else {
+        return Err("Usage: cove pair <name-a> <name-b>, or cove pair --end".to_string());
+    };
+    let a = tmux::resolve_target(a)?;
+    let b = tmux::resolve_target(b)?;
+    pair_in(&Tmux, &a, &b)?;
+    say!(
+        "Pairing {ANSI_PEACH}{a}{ANSI_RESET} and {ANSI_PEACH}{b}{ANSI_RESET} in window '{PAIR_WINDOW}'. \
+         Run {ANSI_PEACH}cove pair --end{ANSI_RESET} to put them back."
+    );
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_pair_moves_both_panes_and_records_origins() {
+        let fake = FakeTmux::new()
+            .with_windows(&[("api", "claude", "%t1"), ("web", "claude", "%t2")])
+            .on("break-pane", "@9\n");
+        assert_eq!(pair_in(&fake, "api", "web").unwrap(), "@9");
+
+        let breaks = fake.calls_to("break-pane");
+        assert!(breaks[0].contains(&"%t1".to_string()));
+        let joins = fake.calls_to("join-pane");
+        assert_eq!(joins[0][4..], ["%t2", "-t", "%t1"]);
+        let set = &fake.calls_to("set-option")[0];
+        assert_eq!(set.last().unwrap(), "%t1:@1 %t2:@2");
+    }
+
+    #[test]
+    fn test_pair_rejects_same_session() {
+        let fake = FakeTmux::new().with_windows(&[("api", "claude", "%t1")]);
+        assert!(pair_in(&fake, "api", "api").is_err());
+        assert!(fake.calls_to("break-pane").is_empty());
+    }
+
+    #[test]
+    fn test_parse_origins() {
+        assert_eq!(
+            parse_origins("%1:@2 %5:@3\n"),
+            [
+                ("%1".to_string(), "@2".to_string()),
+                ("%5".to_string(), "@3".to_string())
+            ]
+        );
+        assert!(parse_origins("").is_empty());
+    }
+}
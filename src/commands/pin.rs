@@ -0,0 +1,15 @@
+use crate::colors::*;
+use crate::{meta, say, tmux};
+
+// ── Public API ──
+
+/// Toggle whether a session stays at the top of the sidebar and `cove list`.
+pub fn run(name: &str) -> Result<(), String> {
+    let name = &tmux::resolve_target(name)?;
+    if meta::toggle_pin(name)? {
+        say!("Pinned {ANSI_PEACH}{name}{ANSI_RESET}");
+    } else {
+        say!("Unpinned {ANSI_PEACH}{name}{ANSI_RESET}");
+    }
+    Ok(())
+}
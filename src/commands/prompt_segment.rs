@@ -0,0 +1,172 @@
+// ── cove prompt-segment ──
+//
+// A one-line summary of session states for shell prompts (starship, p10k,
+// a plain PS1): "⠋2 ?1 ●3" — working, waiting on a question, your turn,
+// queued — each in its color, and nothing at all without sessions.
+// It runs on every prompt, so it has to be quick: states come from the
+// events files (one last-line read each), and the set of live panes — the
+// part that needs tmux — is kept in ~/.cove/segment.json for a few seconds.
+// A new or removed events file means sessions came or went, so the cache is
+// refreshed then too.
+//
+// `--shell zsh|bash` wraps the color codes in the prompt's zero-width markers
+// so line editing keeps the right width (fish, starship and p10k take them
+// as they are); `NO_COLOR` drops them.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::Shell;
+use crate::colors::*;
+use crate::sidebar::state::{self, WindowState};
+use crate::{paths, quota, tabs, tmux};
+
+// ── Types ──
+
+/// Live panes as of `updated`, shared by every prompt.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PaneCache {
+    updated: u64,
+    panes: Vec<String>,
+}
+
+// ── Constants ──
+
+const CACHE_FILE: &str = "segment.json";
+
+/// Seconds the live-pane list is trusted before asking tmux again.
+const TTL_SECS: u64 = 10;
+
+/// States in the segment, in order, with their glyph and color.
+const PARTS: &[(WindowState, &str, &str)] = &[
+    (WindowState::Working, "\u{280b}", ANSI_LAVENDER),
+    (WindowState::Asking, "?", ANSI_PEACH),
+    (WindowState::Idle, "\u{25cf}", ANSI_GREEN),
+    (WindowState::Queued, "\u{2026}", ANSI_BLUE),
+];
+
+// ── Helpers ──
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Unix time the events dir last gained or lost a file.
+fn dir_changed(dir: &Path) -> u64 {
+    fs::metadata(dir)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Whether a cache written at `updated` still holds at `now`.
+fn is_fresh(updated: u64, dir_changed: u64, now: u64) -> bool {
+    now.saturating_sub(updated) < TTL_SECS && dir_changed < updated
+}
+
+/// Claude panes (and terminal tabs) that are live, from the cache if it's
+/// fresh, else from tmux.
+fn live_panes(events: Option<&Path>) -> HashSet<String> {
+    let path = paths::cove_path(CACHE_FILE);
+    let cache: PaneCache = path
+        .as_ref()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    let now = now();
+    if is_fresh(cache.updated, events.map_or(0, dir_changed), now) {
+        return cache.panes.into_iter().collect();
+    }
+
+    let mut live: HashSet<String> = if tmux::has_session() {
+        quota::live_panes(&tmux::Tmux)
+    } else {
+        HashSet::new()
+    };
+    live.extend(tabs::sessions().into_iter().map(|t| t.token));
+    let cache = PaneCache {
+        updated: now,
+        panes: live.iter().cloned().collect(),
+    };
+    if let (Some(path), Ok(content)) = (path, serde_json::to_string(&cache)) {
+        let _ = fs::write(path, content);
+    }
+    live
+}
+
+/// The segment for `states` of live sessions, `wrap` placing each color code.
+fn render(states: &[WindowState], wrap: &dyn Fn(&str) -> String) -> String {
+    PARTS
+        .iter()
+        .filter_map(|(state, glyph, color)| {
+            let count = states.iter().filter(|s| *s == state).count();
+            (count > 0).then(|| format!("{}{glyph}{count}{}", wrap(color), wrap(ANSI_RESET)))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// ── Public API ──
+
+/// Print the state counts of live sessions for a shell prompt.
+pub fn run(shell: Option<Shell>) -> Result<(), String> {
+    let events = paths::events_dir();
+    let live = live_panes(events.as_deref());
+    let states: Vec<WindowState> = state::load_pane_states()
+        .into_iter()
+        .filter(|(pane, _)| live.contains(pane))
+        .map(|(_, state)| state)
+        .collect();
+
+    let color = std::env::var_os("NO_COLOR").is_none();
+    let wrap = |code: &str| match (color, shell) {
+        (false, _) => String::new(),
+        (true, Some(Shell::Zsh)) => format!("%{{{code}%}}"),
+        (true, Some(Shell::Bash)) => format!("\\[{code}\\]"),
+        (true, Some(Shell::Fish) | None) => code.to_string(),
+    };
+    let segment = render(&states, &wrap);
+    if !segment.is_empty() {
+        println!("{segment}");
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_shown_states() {
+        let states = [
+            WindowState::Idle,
+            WindowState::Working,
+            WindowState::Done,
+            WindowState::Idle,
+            WindowState::Fresh,
+        ];
+        assert_eq!(render(&states, &|_| String::new()), "\u{280b}1 \u{25cf}2");
+        assert_eq!(
+            render(&[WindowState::Asking], &|c| format!("%{{{c}%}}")),
+            format!("%{{{ANSI_PEACH}%}}?1%{{{ANSI_RESET}%}}")
+        );
+        assert_eq!(render(&[WindowState::Done], &|c| c.to_string()), "");
+    }
+
+    #[test]
+    fn test_cache_fresh_until_ttl_or_new_events_file() {
+        assert!(is_fresh(1_000, 990, 1_005));
+        assert!(!is_fresh(1_000, 990, 1_000 + TTL_SECS));
+        assert!(!is_fresh(1_000, 1_000, 1_001));
+    }
+}
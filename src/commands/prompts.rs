@@ -0,0 +1,219 @@
+// ── cove prompts ──
+//
+// List the prompts sent to one session, or with --all to every session cove
+// has seen (killed ones included), newest first and numbered. `--search`
+// keeps those containing a phrase. Each prompt is listed with its id (see
+// prompts.rs), which picks it: `--jump <id>` switches to the session it was
+// sent in, `--send <id>` sends it again (to `--to <session>`, or its own
+// session). A unique start of the id is enough.
+
+use std::fmt::Write;
+
+use crate::colors::*;
+use crate::prompts::{self, PromptEntry};
+use crate::sidebar::format::format_age;
+use crate::tmux::{self, Tmux, TmuxClient};
+use crate::{pager, send, width};
+
+// ── Types ──
+
+/// What to do with the prompt an id picks.
+pub enum Pick<'a> {
+    Jump(&'a str),
+    Send { id: &'a str, to: Option<&'a str> },
+}
+
+// ── Helpers ──
+
+/// The session a prompt belongs to, for display.
+fn session_label(entry: &PromptEntry) -> String {
+    if !entry.window.is_empty() {
+        return entry.window.clone();
+    }
+    std::path::Path::new(&entry.cwd)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "?".to_string())
+}
+
+/// The listing of `entries` with their ids, and their sessions if `all`.
+fn format_list(entries: &[PromptEntry], all: bool) -> String {
+    let mut out = String::new();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let width = entries
+        .iter()
+        .map(|e| width::of(&session_label(e)))
+        .max()
+        .unwrap_or(0);
+    for entry in entries {
+        let age = format_age(now.saturating_sub(entry.ts));
+        let session = if all {
+            format!(
+                "{ANSI_PEACH}{}{ANSI_RESET}  ",
+                width::pad(&session_label(entry), width)
+            )
+        } else {
+            String::new()
+        };
+        let indent = if all { width + 2 } else { 0 };
+        let mut lines = entry.prompt.lines();
+        let first = lines.next().unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "{ANSI_BOLD}{}{ANSI_RESET}  {session}{ANSI_OVERLAY}{age:>4}{ANSI_RESET}  {ANSI_WHITE}{first}{ANSI_RESET}",
+            entry.id()
+        );
+        for line in lines {
+            let _ = writeln!(
+                out,
+                "        {:indent$}      {ANSI_SUBTEXT}{line}{ANSI_RESET}",
+                ""
+            );
+        }
+    }
+    let _ = writeln!(
+        out,
+        "\n{ANSI_OVERLAY}Add --jump <id> to switch to a prompt's session, --send <id> [--to <session>] to send it again.{ANSI_RESET}"
+    );
+    out
+}
+
+/// The one of `entries` whose id starts with `id`.
+fn find<'a>(entries: &'a [PromptEntry], id: &str) -> Result<&'a PromptEntry, String> {
+    let id = id.to_lowercase();
+    let mut found = entries
+        .iter()
+        .filter(|e| !id.is_empty() && e.id().starts_with(&id));
+    match (found.next(), found.next()) {
+        (Some(entry), None) => Ok(entry),
+        (Some(_), Some(_)) => Err(format!("More than one prompt's id starts with {id}")),
+        (None, _) => Err(format!("No prompt {id} in this listing")),
+    }
+}
+
+/// Act on the prompt of `entries` that `pick` names.
+fn pick_in(
+    client: &dyn TmuxClient,
+    entries: &[PromptEntry],
+    pick: &Pick,
+    inside_tmux: bool,
+) -> Result<String, String> {
+    let id = match pick {
+        Pick::Jump(id) | Pick::Send { id, .. } => id,
+    };
+    let entry = find(entries, id)?;
+    let own = session_label(entry);
+    match pick {
+        Pick::Jump(_) => {
+            let win = client
+                .list_windows()?
+                .into_iter()
+                .find(|w| w.name == own)
+                .ok_or(format!("Session '{own}' is no longer open"))?;
+            client.select_window(win.index)?;
+            if !inside_tmux {
+                client.attach()?;
+            }
+            Ok(own)
+        }
+        Pick::Send { to, .. } => {
+            let name = client.resolve_target(to.unwrap_or(&own))?;
+            let pane_id = client.get_claude_pane_id(&name)?;
+            send::deliver(client, &pane_id, &entry.prompt)?;
+            Ok(name)
+        }
+    }
+}
+
+// ── Public API ──
+
+/// List prompts of session `name` (every session with `all`) containing
+/// `search`, or act on one of them with `pick`.
+pub fn run(
+    name: Option<&str>,
+    all: bool,
+    search: Option<&str>,
+    pick: Option<Pick>,
+) -> Result<(), String> {
+    let window = match (name, all) {
+        (_, true) => None,
+        // A killed session's prompts are still listed under its name
+        (Some(name), false) => {
+            Some(tmux::resolve_target(name).unwrap_or_else(|_| name.to_string()))
+        }
+        (None, false) => return Err("Name a session, or pass --all".to_string()),
+    };
+    let entries = prompts::search(window.as_deref(), search);
+    if entries.is_empty() {
+        let scope = window.as_deref().unwrap_or("any session");
+        let matching = search
+            .map(|s| format!(" matching '{s}'"))
+            .unwrap_or_default();
+        println!("{ANSI_OVERLAY}No prompts{matching} in {scope}.{ANSI_RESET}");
+        return Ok(());
+    }
+    match pick {
+        None => pager::show(&format_list(&entries, window.is_none())),
+        Some(Pick::Send { .. }) if send::skip("sending the prompt") => {}
+        Some(pick) => {
+            let session = pick_in(&Tmux, &entries, &pick, tmux::is_inside_tmux())?;
+            if matches!(pick, Pick::Send { .. }) {
+                println!("{ANSI_GREEN}Sent{ANSI_RESET} to {ANSI_PEACH}{session}{ANSI_RESET}");
+            }
+        }
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    fn entry(prompt: &str, window: &str) -> PromptEntry {
+        PromptEntry {
+            prompt: prompt.to_string(),
+            window: window.to_string(),
+            cwd: format!("/code/{window}"),
+            pane_id: "%1".to_string(),
+            ts: 100,
+        }
+    }
+
+    #[test]
+    fn test_pick_sends_again_or_jumps() {
+        let fake = FakeTmux::new()
+            .with_windows(&[("api", "claude", "%t1"), ("web", "claude", "%t2")])
+            .on("capture-pane", "? for shortcuts");
+        let entries = [entry("add the migration", "api"), entry("fix css", "gone")];
+        let ids: Vec<String> = entries.iter().map(PromptEntry::id).collect();
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(ids[0].len(), 6);
+
+        let send = Pick::Send {
+            id: &ids[0],
+            to: Some("web"),
+        };
+        assert_eq!(pick_in(&fake, &entries, &send, true).unwrap(), "web");
+        let set = &fake.calls_to("set-buffer")[0];
+        assert_eq!(set.last().unwrap(), "add the migration");
+
+        // The start of an id will do
+        assert_eq!(
+            pick_in(&fake, &entries, &Pick::Jump(&ids[0][..4]), true).unwrap(),
+            "api"
+        );
+        assert_eq!(fake.calls_to("select-window").len(), 1);
+        assert!(fake.calls_to("attach").is_empty());
+
+        // A killed session can't be jumped to, and ids must match one prompt
+        assert!(pick_in(&fake, &entries, &Pick::Jump(&ids[1]), true).is_err());
+        assert!(pick_in(&fake, &entries, &Pick::Jump("zz"), true).is_err());
+        assert!(pick_in(&fake, &entries, &Pick::Jump(""), true).is_err());
+    }
+}
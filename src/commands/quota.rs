@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use crate::colors::*;
+use crate::config;
+use crate::quota;
+use crate::tmux::{Tmux, TmuxClient};
+
+// ── Constants ──
+
+/// Characters of each queued prompt shown.
+const PREVIEW_MAX: usize = 60;
+
+// ── Helpers ──
+
+fn preview(prompt: &str) -> String {
+    let first = prompt.lines().next().unwrap_or_default().trim();
+    if first.chars().count() <= PREVIEW_MAX {
+        first.to_string()
+    } else {
+        let cut: String = first.chars().take(PREVIEW_MAX - 1).collect();
+        format!("{cut}\u{2026}")
+    }
+}
+
+// ── Public API ──
+
+/// Show the working-session limit, how much of it is in use, and queued prompts.
+pub fn run() -> Result<(), String> {
+    let limit = config::load()?.quota.max_working;
+    if !Tmux.has_session() {
+        println!("{ANSI_OVERLAY}No active cove session.{ANSI_RESET}");
+        return Err(String::new());
+    }
+
+    let live = quota::live_panes(&Tmux);
+    let working = quota::working_elsewhere(&live, "");
+    if limit == 0 {
+        println!(
+            "Working: {ANSI_PEACH}{working}{ANSI_RESET} {ANSI_OVERLAY}(no limit; set quota.max_working){ANSI_RESET}"
+        );
+    } else {
+        println!("Working: {ANSI_PEACH}{working}{ANSI_RESET} of {limit}");
+    }
+
+    // Name queued prompts by window rather than pane ID
+    let names: HashMap<u32, String> = Tmux
+        .list_windows()?
+        .into_iter()
+        .map(|w| (w.index, w.name))
+        .collect();
+    let windows: HashMap<String, &str> = Tmux
+        .list_pane_commands()?
+        .into_iter()
+        .filter_map(|p| {
+            let name = names.get(&p.window_index)?;
+            Some((p.pane_id, name.as_str()))
+        })
+        .collect();
+
+    let queued: Vec<_> = quota::queued()
+        .into_iter()
+        .filter(|q| live.contains(&q.pane_id))
+        .collect();
+    if queued.is_empty() {
+        return Ok(());
+    }
+    println!("Queued:");
+    for q in &queued {
+        let name = windows.get(&q.pane_id).copied().unwrap_or(&q.pane_id);
+        println!(
+            "  {ANSI_PEACH}{name}{ANSI_RESET}  {ANSI_SUBTEXT}{}{ANSI_RESET}",
+            preview(&q.prompt)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// ── cove rename ──
+//
+// Renaming the window with tmux alone would strand what cove keeps under a
+// session's name: its metadata in meta.json (the pin, issue and worktree),
+// its clips, its share, its entry in the saved sessions, a clock-in on it and
+// its snapshot refs in the repository it runs in. `cove rename` and `r` in
+// the sidebar rename the window and move those with it. Event files are keyed
+// by pane, so the session's state carries over by itself. Trash entries and
+// scrollback captures stay under the old name: they belong to sessions
+// killed before, not to the one being renamed.
+
+use std::path::Path;
+
+use crate::colors::*;
+use crate::commands::share;
+use crate::tmux::{self, Tmux, TmuxClient};
+use crate::{attention, clips, meta, names, say, sessions, snapshots};
+
+// ── Helpers ──
+
+/// Rename window `old` to `new` if `new` is a valid name no window has.
+/// Returns the window's directory.
+fn rename_in(client: &dyn TmuxClient, old: &str, new: &str) -> Result<String, String> {
+    names::validate(new)?;
+    let windows = client.list_windows()?;
+    if windows.iter().any(|w| w.name == new) {
+        return Err(format!("Session '{new}' already exists."));
+    }
+    let dir = windows
+        .into_iter()
+        .find(|w| w.name == old)
+        .map(|w| w.pane_path)
+        .ok_or(format!("No session named '{old}'."))?;
+    client.rename_window(old, new)?;
+    Ok(dir)
+}
+
+// ── Public API ──
+
+/// Rename session `old` to `new`, moving its metadata, clips, share and
+/// snapshots.
+pub fn rename(old: &str, new: &str) -> Result<(), String> {
+    if old == new {
+        return Ok(());
+    }
+    let dir = rename_in(&Tmux, old, new)?;
+    meta::rename(old, new)?;
+    // The window is renamed already: carry on with the rest past a failure
+    if let Err(e) = sessions::rename(old, new) {
+        log::warn!("saved session {old}: {e}");
+    }
+    if let Err(e) = attention::rename(old, new) {
+        log::warn!("clock-in on {old}: {e}");
+    }
+    if let Err(e) = snapshots::rename(Path::new(&dir), old, new) {
+        log::warn!("snapshots of {old}: {e}");
+    }
+    clips::rename(old, new)?;
+    share::rename(old, new)
+}
+
+pub fn run(name: &str, new_name: &str) -> Result<(), String> {
+    let old = tmux::resolve_target(name)?;
+    rename(&old, new_name)?;
+    say!("Renamed {ANSI_PEACH}{old}{ANSI_RESET} to {ANSI_PEACH}{new_name}{ANSI_RESET}");
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_rename_checks_new_name() {
+        let client =
+            FakeTmux::new().with_windows(&[("api", "claude", "%1"), ("web", "claude", "%2")]);
+
+        assert!(
+            rename_in(&client, "api", "web")
+                .unwrap_err()
+                .contains("already exists")
+        );
+        assert!(rename_in(&client, "api", "has space").is_err());
+        assert!(rename_in(&client, "gone", "api-v2").is_err());
+        assert!(client.calls_to("rename-window").is_empty());
+
+        assert_eq!(rename_in(&client, "api", "api-v2").unwrap(), "/code/api");
+        assert_eq!(
+            client.calls_to("rename-window"),
+            [["rename-window", "-t", "cove:api", "api-v2"]]
+        );
+    }
+}
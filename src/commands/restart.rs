@@ -0,0 +1,182 @@
+// ── cove restart ──
+//
+// Restart Claude in a session's pane .1 without losing the conversation: the
+// running Claude is killed with `respawn-pane -k` and `claude --resume <id>`
+// (or `claude --continue` when the session id is unknown) takes its place in
+// the same pane. Sending `/exit` instead would let the window's pane-died hook
+// respawn a plain `claude` first. A "restarting" event is recorded beforehand
+// (under a placeholder file when the session id is unknown) so the sidebar
+// shows the session as fresh, not Done, while the new process starts and a
+// turn cut short doesn't keep showing as working. A session
+// started with `--container` gets its Claude back inside the same container,
+// and one in a directory with a `profiles` rule its flags (see profiles.rs).
+
+use crate::colors::*;
+use crate::commands::{hook, start};
+use crate::config::{self, ProfileRule};
+use crate::container::{self, Container};
+use crate::sidebar::state;
+use crate::tmux::{self, Tmux, TmuxClient};
+use crate::{profiles, say};
+
+// ── Helpers ──
+
+/// Command that starts Claude again on the same conversation.
+pub fn restart_command(claude_session: Option<&str>) -> String {
+    match claude_session {
+        Some(id) => format!("{} --resume {}", start::claude(), start::shell_quote(id)),
+        None => format!("{} --continue", start::claude()),
+    }
+}
+
+/// The current directory of a pane, or "" if tmux can't say.
+pub fn pane_path(client: &dyn TmuxClient, pane_id: &str) -> String {
+    client
+        .output(&[
+            "display-message",
+            "-t",
+            pane_id,
+            "-p",
+            "#{pane_current_path}",
+        ])
+        .map(|out| out.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Swap Claude in `name`'s pane .1 for `restart_command` with the flags of
+/// the `profiles` matching its directory, run in `container` if given,
+/// calling `record` with the Claude session (a placeholder when unknown),
+/// pane id and directory first. Returns the pane id.
+fn run_in(
+    client: &dyn TmuxClient,
+    name: &str,
+    container: Option<&Container>,
+    profiles: &[ProfileRule],
+    claude_session: impl Fn(&str) -> Option<String>,
+    record: impl Fn(&str, &str, &str) -> Result<(), String>,
+) -> Result<String, String> {
+    let pane_id = client.get_claude_pane_id(name)?;
+    if pane_id.is_empty() {
+        return Err(format!("No Claude pane found for '{name}'"));
+    }
+    let session = claude_session(&pane_id);
+    let dir = pane_path(client, &pane_id);
+    // Recorded either way, so the sidebar stops showing the old state now
+    let recorded = session
+        .clone()
+        .unwrap_or_else(|| state::placeholder_session(&pane_id));
+    record(&recorded, &pane_id, &dir)?;
+    let command = profiles::apply(profiles, &dir, &restart_command(session.as_deref()));
+    let command = match container {
+        Some(container) => container::exec_command(container, &dir, &command),
+        None => command,
+    };
+    client.respawn_pane(&pane_id, &command)?;
+    // A parked or failed session is running again
+    client.set_parked(&pane_id, false)?;
+    client.set_failed(&pane_id, false)?;
+    Ok(pane_id)
+}
+
+// ── Public API ──
+
+/// Start Claude again in session `name`'s pane .1 on the same conversation.
+pub fn restart(name: &str) -> Result<(), String> {
+    let record = |session: &str, pane_id: &str, cwd: &str| {
+        hook::write_event(session, cwd, pane_id, state::RESTARTING, "restart")
+    };
+    let container = container::for_session(name);
+    let profiles = config::load().unwrap_or_default().profiles;
+    run_in(
+        &Tmux,
+        name,
+        container.as_ref(),
+        &profiles,
+        state::session_for_pane,
+        record,
+    )
+    .map(|_| ())
+}
+
+pub fn run(name: &str) -> Result<(), String> {
+    let name = tmux::resolve_target(name)?;
+    restart(&name)?;
+    say!("{ANSI_GREEN}Restarted{ANSI_RESET} Claude in {ANSI_PEACH}{name}{ANSI_RESET}");
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_restart_command() {
+        assert_eq!(restart_command(None), "claude --continue");
+        assert_eq!(
+            restart_command(Some("abc-123")),
+            "claude --resume 'abc-123'"
+        );
+    }
+
+    #[test]
+    fn test_restart_records_then_respawns() {
+        let fake = FakeTmux::new().with_windows(&[("api", "claude", "%t1")]);
+        let recorded = RefCell::new(Vec::new());
+        let pane = run_in(
+            &fake,
+            "api",
+            None,
+            &[],
+            |pane| (pane == "%t1").then(|| "abc".to_string()),
+            |session, pane, _| {
+                assert!(fake.calls_to("respawn-pane").is_empty());
+                recorded
+                    .borrow_mut()
+                    .push((session.to_string(), pane.to_string()));
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(pane, "%t1");
+        assert_eq!(
+            recorded.into_inner(),
+            [("abc".to_string(), "%t1".to_string())]
+        );
+        let calls = fake.calls_to("respawn-pane");
+        assert_eq!(calls.len(), 1);
+        assert!(calls[0].contains(&"%t1".to_string()));
+        assert!(calls[0].contains(&"claude --resume 'abc'".to_string()));
+    }
+
+    #[test]
+    fn test_restart_without_a_session_still_records() {
+        let fake = FakeTmux::new().with_windows(&[("api", "claude", "%t1")]);
+        let recorded = RefCell::new(Vec::new());
+        run_in(
+            &fake,
+            "api",
+            None,
+            &[],
+            |_| None,
+            |session, pane, _| {
+                recorded
+                    .borrow_mut()
+                    .push((session.to_string(), pane.to_string()));
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            recorded.into_inner(),
+            [(state::placeholder_session("%t1"), "%t1".to_string())]
+        );
+        let calls = fake.calls_to("respawn-pane");
+        assert!(calls[0].contains(&"claude --continue".to_string()));
+    }
+}
@@ -0,0 +1,272 @@
+// ── cove restore ──
+//
+// Brings back the sessions saved in ~/.cove/sessions.json (see sessions.rs)
+// after tmux or the machine went down. Every saved session without a window
+// gets one in its directory, in the order they were first created, with the
+// cove layout and any preset picked for it, and Claude resumes its
+// conversation with `claude --resume <id>`. A session that never got a
+// prompt starts with the command it was first started with. Sessions whose
+// directory is gone, or whose Claude ran in a container, are skipped.
+// `--forget <name>` drops a saved session for good, e.g. one whose window
+// was closed with tmux rather than `cove kill`.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::colors::*;
+use crate::commands::restart;
+use crate::commands::start::{self, Records};
+use crate::config::{self, ProfileRule};
+use crate::sessions::{self, SavedSession};
+use crate::tmux::{self, Tmux, TmuxClient};
+use crate::{container, paths, profiles, say};
+
+// ── Types ──
+
+/// What restoring a saved session comes to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Plan {
+    /// A window with the name is already open.
+    Open,
+    /// Its directory no longer exists.
+    NoDir,
+    /// Its Claude ran in a container, which didn't survive.
+    Container,
+    /// Create the window with this command in the Claude pane.
+    Create(String),
+}
+
+// ── Helpers ──
+
+/// Command that brings back `session`'s Claude: its conversation if known,
+/// else what it was started with.
+fn restore_command(session: &SavedSession, profiles: &[ProfileRule]) -> String {
+    match &session.claude_session {
+        Some(id) => profiles::apply(profiles, &session.dir, &restart::restart_command(Some(id))),
+        None => session.command.clone(),
+    }
+}
+
+fn plan(
+    session: &SavedSession,
+    open: &HashSet<String>,
+    in_container: impl Fn(&str) -> bool,
+    profiles: &[ProfileRule],
+) -> Plan {
+    if open.contains(&session.name) {
+        Plan::Open
+    } else if !Path::new(&session.dir).is_dir() {
+        Plan::NoDir
+    } else if in_container(&session.name) {
+        Plan::Container
+    } else {
+        Plan::Create(restore_command(session, profiles))
+    }
+}
+
+fn list(saved: &[SavedSession], plans: &[Plan]) {
+    if saved.is_empty() {
+        println!("{ANSI_OVERLAY}No saved sessions.{ANSI_RESET}");
+        return;
+    }
+    for (session, plan) in saved.iter().zip(plans) {
+        let what = match plan {
+            Plan::Open => "open".to_string(),
+            Plan::NoDir => format!("directory is gone (cove restore --forget {})", session.name),
+            Plan::Container => "ran in a container".to_string(),
+            Plan::Create(_) if session.claude_session.is_some() => {
+                "resumes its conversation".to_string()
+            }
+            Plan::Create(_) => "starts fresh".to_string(),
+        };
+        println!(
+            "  {ANSI_PEACH}{}{ANSI_RESET}  {ANSI_SUBTEXT}{}{ANSI_RESET}  {ANSI_OVERLAY}{what}{ANSI_RESET}",
+            session.name,
+            paths::tildify(&session.dir)
+        );
+    }
+}
+
+/// Create a window for each saved session `plans` says to, going on past
+/// failures. Returns how many were created and the errors.
+fn restore_in(
+    client: &dyn TmuxClient,
+    saved: &[SavedSession],
+    plans: &[Plan],
+    inside_tmux: bool,
+    records: &Records,
+) -> (usize, Vec<String>) {
+    let mut created = 0;
+    let mut failed = Vec::new();
+    for (session, plan) in saved.iter().zip(plans) {
+        match plan {
+            Plan::Open => {}
+            Plan::NoDir => say!(
+                "  {ANSI_OVERLAY}skipped{ANSI_RESET}  {ANSI_WHITE}{}{ANSI_RESET}  {ANSI_OVERLAY}{} is gone{ANSI_RESET}",
+                session.name,
+                paths::tildify(&session.dir)
+            ),
+            Plan::Container => say!(
+                "  {ANSI_OVERLAY}skipped{ANSI_RESET}  {ANSI_WHITE}{}{ANSI_RESET}  {ANSI_OVERLAY}ran in a container; start it again with --container{ANSI_RESET}",
+                session.name
+            ),
+            Plan::Create(command) => {
+                let result = start::create_window_in(
+                    client,
+                    &session.name,
+                    &session.dir,
+                    command,
+                    inside_tmux,
+                    records,
+                );
+                match result {
+                    Ok(()) => {
+                        created += 1;
+                        say!(
+                            "  {ANSI_PEACH}restored{ANSI_RESET} {ANSI_WHITE}{}{ANSI_RESET}  {ANSI_SUBTEXT}{}{ANSI_RESET}",
+                            session.name,
+                            paths::tildify(&session.dir)
+                        );
+                    }
+                    Err(e) => failed.push(format!("{}: {e}", session.name)),
+                }
+            }
+        }
+    }
+    (created, failed)
+}
+
+// ── Public API ──
+
+/// Recreate every saved session that has no window. With `list_only`, show
+/// the saved sessions and what restoring each would do; with `forget`, drop
+/// that one instead.
+pub fn run(list_only: bool, forget: Option<&str>) -> Result<(), String> {
+    if let Some(name) = forget {
+        sessions::forget_saved(name)?;
+        say!("Forgot saved session {ANSI_PEACH}{name}{ANSI_RESET}");
+        return Ok(());
+    }
+    let saved = sessions::saved();
+    let open: HashSet<String> = if Tmux.has_session() {
+        Tmux.list_window_names()?.into_iter().collect()
+    } else {
+        HashSet::new()
+    };
+    let profiles = config::load().unwrap_or_default().profiles;
+    let in_container = |name: &str| container::for_session(name).is_some();
+    let plans: Vec<Plan> = saved
+        .iter()
+        .map(|s| plan(s, &open, in_container, &profiles))
+        .collect();
+    if list_only {
+        list(&saved, &plans);
+        return Ok(());
+    }
+    if !plans.iter().any(|p| matches!(p, Plan::Create(_))) {
+        say!("{ANSI_OVERLAY}Nothing to restore.{ANSI_RESET}");
+        return Ok(());
+    }
+
+    let inside_tmux = tmux::is_inside_tmux();
+    let (created, failed) = restore_in(&Tmux, &saved, &plans, inside_tmux, &Records::home());
+    let plural = if created == 1 { "" } else { "s" };
+    say!("Restored {created} session{plural}.");
+    if created > 0 && !inside_tmux {
+        Tmux.attach()?;
+    }
+    if !failed.is_empty() {
+        return Err(format!("Couldn't restore:\n  {}", failed.join("\n  ")));
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    fn saved(name: &str, dir: &str, claude_session: Option<&str>) -> SavedSession {
+        SavedSession {
+            name: name.to_string(),
+            dir: dir.to_string(),
+            command: "claude 'fix tests'".to_string(),
+            pane_id: "%t1".to_string(),
+            claude_session: claude_session.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_restore_command_resumes_conversation() {
+        let session = saved("api", "/code/api", Some("abc-123"));
+        assert_eq!(restore_command(&session, &[]), "claude --resume 'abc-123'");
+        // Never prompted: started as it was first time
+        let session = saved("api", "/code/api", None);
+        assert_eq!(restore_command(&session, &[]), "claude 'fix tests'");
+    }
+
+    #[test]
+    fn test_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let open: HashSet<String> = ["api".to_string()].into();
+        let no = |_: &str| false;
+
+        assert_eq!(plan(&saved("api", path, None), &open, no, &[]), Plan::Open);
+        assert_eq!(
+            plan(&saved("web", "/nonexistent/web", None), &open, no, &[]),
+            Plan::NoDir
+        );
+        assert_eq!(
+            plan(&saved("web", path, None), &open, |_| true, &[]),
+            Plan::Container
+        );
+        assert!(matches!(
+            plan(&saved("web", path, Some("x")), &open, no, &[]),
+            Plan::Create(c) if c.contains("--resume 'x'")
+        ));
+    }
+
+    #[test]
+    fn test_restore_creates_missing_windows_in_order() {
+        let fake = FakeTmux::new().with_windows(&[("cove-test-api", "claude", "%t1")]);
+        let sessions = [
+            saved("cove-test-api", "/tmp", None),
+            saved("cove-test-web", "/tmp", Some("abc")),
+            saved("cove-test-docs", "/gone", None),
+            saved("cove-test-cli", "/tmp", None),
+        ];
+        let plans = [
+            Plan::Open,
+            Plan::Create("claude --resume 'abc'".to_string()),
+            Plan::NoDir,
+            Plan::Create("claude".to_string()),
+        ];
+        let (created, failed) = restore_in(&fake, &sessions, &plans, true, &Records::default());
+        assert_eq!(created, 2);
+        assert!(failed.is_empty(), "{failed:?}");
+
+        let new_window = fake.calls_to("new-window");
+        assert_eq!(new_window.len(), 2);
+        assert_eq!(new_window[0][4], "cove-test-web");
+        assert_eq!(new_window[0].last().unwrap(), "claude --resume 'abc'");
+        assert_eq!(new_window[1][4], "cove-test-cli");
+    }
+
+    #[test]
+    fn test_restore_after_tmux_died_needs_a_terminal() {
+        let fake = FakeTmux::new().fail("has-session", "no server running");
+        let sessions = [saved("cove-test-api", "/tmp", None)];
+        let plans = [Plan::Create("claude".to_string())];
+        // Inside another tmux there's nothing to size a new session against
+        let (created, failed) = restore_in(&fake, &sessions, &plans, true, &Records::default());
+        assert_eq!(created, 0);
+        assert_eq!(failed.len(), 1);
+
+        let (created, _) = restore_in(&fake, &sessions, &plans, false, &Records::default());
+        assert_eq!(created, 1);
+        assert_eq!(fake.calls_to("new-session").len(), 1);
+    }
+}
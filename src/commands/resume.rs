@@ -1,16 +1,47 @@
 use crate::colors::*;
-use crate::tmux;
+use crate::tmux::{self, Tmux, TmuxClient};
 
 pub fn run() -> Result<(), String> {
-    if !tmux::has_session() {
+    run_in(&Tmux, tmux::is_inside_tmux())
+}
+
+fn run_in(client: &dyn TmuxClient, inside_tmux: bool) -> Result<(), String> {
+    if !client.has_session() {
         return Err(format!(
             "{ANSI_OVERLAY}No active cove session.{ANSI_RESET} Run {ANSI_PEACH}cove{ANSI_RESET} to create one."
         ));
     }
 
-    if tmux::is_inside_tmux() {
-        tmux::switch_client()
+    if inside_tmux {
+        client.switch_client()
     } else {
-        tmux::attach()
+        client.attach()
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_resume_attaches_or_switches() {
+        let fake = FakeTmux::new();
+        run_in(&fake, false).unwrap();
+        assert_eq!(fake.calls_to("attach").len(), 1);
+
+        let fake = FakeTmux::new();
+        run_in(&fake, true).unwrap();
+        assert_eq!(fake.calls_to("switch-client").len(), 1);
+        assert!(fake.calls_to("attach").is_empty());
+    }
+
+    #[test]
+    fn test_resume_without_session() {
+        let fake = FakeTmux::new().fail("has-session", "no server running");
+        assert!(run_in(&fake, false).is_err());
+        assert!(fake.calls_to("attach").is_empty());
     }
 }
@@ -0,0 +1,263 @@
+// ── cove rollback ──
+//
+// Put a session's working tree back the way it was at one of its snapshots
+// (see snapshots.rs): after an earlier turn, or when the session started.
+// `--list` shows them, newest first; without `--to N` the snapshot is picked
+// from that list. Before anything changes, the diff stat of what the rollback
+// would do is shown (`--diff` adds the patch, through the pager) and has to be
+// confirmed, unless `--yes`. The tree as it was just before is snapshotted
+// first, so a rollback can itself be rolled back.
+//
+// A rollback is recorded as a milestone in the session's history (see
+// annotate.rs). With `--tell`, Claude is sent a prompt listing the files that
+// were reverted, so it doesn't carry on from edits that are gone.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use crate::colors::*;
+use crate::commands::{annotate, watch};
+use crate::sidebar::format::format_age;
+use crate::sidebar::state::{StateDetector, WindowState};
+use crate::snapshots::{self, Snapshot};
+use crate::tmux::{Tmux, TmuxClient};
+use crate::{config, pager, quiet, say, send};
+
+// ── Helpers ──
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// "12m ago: <label>" for `snapshot`, at `now` (seconds).
+fn describe(snapshot: &Snapshot, now: u64) -> String {
+    format!(
+        "{} ago: {}",
+        format_age(now.saturating_sub(snapshot.ms / 1000)),
+        snapshot.label
+    )
+}
+
+fn print_list(snapshots: &[Snapshot], current: &str, now: u64) {
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        let mark = if snapshot.tree == current {
+            format!(" {ANSI_GREEN}(current){ANSI_RESET}")
+        } else {
+            String::new()
+        };
+        println!(
+            "  {ANSI_OVERLAY}{:>2}){ANSI_RESET} {ANSI_SUBTEXT}{}{ANSI_RESET}{mark}",
+            i + 1,
+            describe(snapshot, now)
+        );
+    }
+}
+
+/// Ask which of `snapshots` to roll back to; the default is the newest that
+/// differs from the `current` tree.
+fn choose(
+    snapshots: &[Snapshot],
+    current: &str,
+    now: u64,
+    input: &mut dyn BufRead,
+) -> Result<usize, String> {
+    let default = snapshots
+        .iter()
+        .position(|s| s.tree != current)
+        .ok_or("Nothing to roll back: every snapshot matches the working tree")?;
+    print_list(snapshots, current, now);
+    loop {
+        print!("Roll back to [{}]: ", default + 1);
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        if input
+            .read_line(&mut line)
+            .map_err(|e| format!("read: {e}"))?
+            == 0
+        {
+            return Err("no snapshot picked".to_string());
+        }
+        match line.trim() {
+            "" => return Ok(default),
+            n => match n
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..=snapshots.len()).contains(n))
+            {
+                Some(n) => return Ok(n - 1),
+                None => println!("  {ANSI_RED}Pick 1 to {}.{ANSI_RESET}", snapshots.len()),
+            },
+        }
+    }
+}
+
+fn confirm(question: &str) -> bool {
+    print!("{question} [y/N] ");
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    if io::stdin().lock().read_line(&mut input).is_err() {
+        return false;
+    }
+    let answer = input.trim().to_lowercase();
+    answer == "y" || answer == "yes"
+}
+
+/// The prompt telling Claude what a rollback changed, from the
+/// `--name-status` diff of the rollback.
+fn tell_message(snapshot: &str, name_status: &str) -> String {
+    let files: Vec<String> = name_status
+        .lines()
+        .filter_map(|line| {
+            let (status, path) = line.split_once('\t')?;
+            let what = match status.chars().next()? {
+                'A' => "restored",
+                'D' => "removed",
+                _ => "reverted",
+            };
+            Some(format!("- {path} ({what})"))
+        })
+        .collect();
+    format!(
+        "I rolled the working tree back to the snapshot from {snapshot}. \
+         Your edits to these files since then are gone:\n{}\n\
+         Re-read them before changing them again.",
+        files.join("\n")
+    )
+}
+
+// ── Public API ──
+
+/// Roll session `name`'s working tree back to snapshot `to` (1 is the
+/// newest), or list its snapshots.
+pub fn run(
+    name: &str,
+    to: Option<usize>,
+    list: bool,
+    show_diff: bool,
+    yes: bool,
+    tell: bool,
+) -> Result<(), String> {
+    let name = &Tmux.resolve_target(name)?;
+    let mut detector = StateDetector::new();
+    let (window, state) = watch::current_state(&Tmux, &mut detector, name)?.ok_or(format!(
+        "No session named '{ANSI_PEACH}{name}{ANSI_RESET}'."
+    ))?;
+    let dir = Path::new(&window.pane_path);
+    let snapshots = snapshots::list(dir, name);
+    if snapshots.is_empty() {
+        return Err(format!(
+            "No snapshots of {name} yet: they're taken when a session starts and after each turn"
+        ));
+    }
+    let current = snapshots::worktree_tree(dir)?;
+    let now = now();
+    if list {
+        print_list(&snapshots, &current, now);
+        return Ok(());
+    }
+
+    let index = match to {
+        Some(n) if (1..=snapshots.len()).contains(&n) => n - 1,
+        Some(_) => {
+            return Err(format!(
+                "No snapshot {}: {name} has {} (see --list)",
+                to.unwrap_or_default(),
+                snapshots.len()
+            ));
+        }
+        None if quiet::on() || !io::stdin().is_terminal() => {
+            return Err("Pick a snapshot with --to N (see --list)".to_string());
+        }
+        None => choose(&snapshots, &current, now, &mut io::stdin().lock())?,
+    };
+    let target = &snapshots[index];
+    if target.tree == current {
+        println!("{ANSI_OVERLAY}The working tree already matches that snapshot.{ANSI_RESET}");
+        return Ok(());
+    }
+    if state == WindowState::Working {
+        return Err(format!(
+            "{name} is working; wait for the turn to end (or cove interrupt {name}) first"
+        ));
+    }
+
+    let args: &[&str] = if show_diff {
+        &["--stat", "--patch", "--color=always"]
+    } else {
+        &["--stat", "--color=always"]
+    };
+    pager::show(&snapshots::diff(dir, &current, &target.tree, args)?);
+    if !yes {
+        if quiet::on() || !io::stdin().is_terminal() {
+            return Err("Not rolling back without confirmation: pass --yes".to_string());
+        }
+        if !confirm(&format!("Roll {name} back to {}?", describe(target, now))) {
+            return Ok(());
+        }
+    }
+
+    let config = config::load().unwrap_or_default();
+    if let Err(e) = snapshots::take(&config.snapshots, dir, name, "before rollback") {
+        return Err(format!(
+            "Not rolling back: couldn't snapshot the tree first: {e}"
+        ));
+    }
+    let changed = snapshots::diff(dir, &current, &target.tree, &["--name-status"])?;
+    snapshots::restore(dir, &current, &target.tree)?;
+
+    let age = format!("{} ago", format_age(now.saturating_sub(target.ms / 1000)));
+    let note = format!("rolled back to snapshot from {age} ({})", target.label);
+    if let Err(e) = annotate::record(&Tmux, name, &note) {
+        log::warn!("milestone for {name}: {e}");
+    }
+    say!("{ANSI_GREEN}Rolled back{ANSI_RESET} {ANSI_PEACH}{name}{ANSI_RESET} to {age}");
+
+    if tell && !send::skip(&format!("telling {name}")) {
+        let pane_id = Tmux.get_claude_pane_id(name)?;
+        send::deliver(&Tmux, &pane_id, &tell_message(&age, &changed))?;
+        say!("Told {ANSI_PEACH}{name}{ANSI_RESET} what was reverted");
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(tree: &str, secs: u64) -> Snapshot {
+        Snapshot {
+            commit: format!("c-{tree}"),
+            tree: tree.to_string(),
+            ms: secs * 1000,
+            label: format!("after: {tree}"),
+        }
+    }
+
+    #[test]
+    fn test_choose_defaults_to_newest_different_snapshot() {
+        let snapshots = [
+            snapshot("t3", 300),
+            snapshot("t2", 200),
+            snapshot("t1", 100),
+        ];
+        let pick = |input: &str| choose(&snapshots, "t3", 400, &mut input.as_bytes());
+        assert_eq!(pick("\n"), Ok(1));
+        assert_eq!(pick("9\n3\n"), Ok(2));
+        assert!(pick("").is_err());
+        assert!(choose(&snapshots[..1], "t3", 400, &mut "\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_tell_message_names_reverted_files() {
+        let message = tell_message("5m ago", "M\tsrc/lib.rs\nA\told.rs\nD\tnew.rs\n");
+        assert!(message.contains("snapshot from 5m ago"));
+        assert!(
+            message.contains("- src/lib.rs (reverted)\n- old.rs (restored)\n- new.rs (removed)")
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use crate::colors::*;
+use crate::tmux::{Tmux, TmuxClient};
+use crate::{say, send, templates};
+
+// ── Public API ──
+
+/// Send a message to a session's Claude pane, either `text` or a rendered template.
+pub fn run(
+    name: &str,
+    text: Option<&str>,
+    template: Option<&str>,
+    vars: &[String],
+) -> Result<(), String> {
+    let message = match (text, template) {
+        (_, Some(template)) => {
+            let vars = vars
+                .iter()
+                .map(|v| templates::parse_var(v))
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            templates::render(&templates::load(template)?, &vars)?
+        }
+        (Some(text), None) => text.to_string(),
+        (None, None) => return Err("nothing to send: pass text or --template".to_string()),
+    };
+
+    let name = &Tmux.resolve_target(name)?;
+    if send::skip(&format!("sending to {name}")) {
+        return Ok(());
+    }
+    run_in(&Tmux, name, &message)?;
+    say!("Sent to {ANSI_PEACH}{name}{ANSI_RESET}");
+    Ok(())
+}
+
+/// Send `message` to session `name` through `client`.
+pub fn run_in(client: &dyn TmuxClient, name: &str, message: &str) -> Result<(), String> {
+    if message.trim().is_empty() {
+        return Err("nothing to send: message is empty".to_string());
+    }
+    if !client.has_session() {
+        return Err(format!(
+            "{ANSI_OVERLAY}No active cove session.{ANSI_RESET} Run {ANSI_PEACH}cove{ANSI_RESET} to create one."
+        ));
+    }
+    let pane_id = client
+        .get_claude_pane_id(name)
+        .map_err(|_| format!("No session named '{ANSI_PEACH}{name}{ANSI_RESET}'."))?;
+    send::deliver(client, &pane_id, message)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_send_pastes_and_submits() {
+        let fake = FakeTmux::new()
+            .with_windows(&[("api", "claude", "%t1")])
+            .on("capture-pane", "? for shortcuts");
+        run_in(&fake, "api", "-n line one\nline two").unwrap();
+
+        // A leading dash is text, not a flag
+        assert_eq!(
+            fake.calls_to("set-buffer"),
+            [[
+                "set-buffer",
+                "-b",
+                "cove-send",
+                "--",
+                "-n line one\nline two"
+            ]]
+        );
+        let paste = fake.calls_to("paste-buffer");
+        assert!(paste[0].contains(&"%t1".to_string()));
+        assert_eq!(
+            fake.calls_to("send-keys"),
+            [["send-keys", "-t", "%t1", "Enter"]]
+        );
+    }
+
+    #[test]
+    fn test_send_unknown_session() {
+        let fake = FakeTmux::new().with_windows(&[("api", "claude", "%t1")]);
+        assert!(run_in(&fake, "web", "hello").is_err());
+        assert!(fake.calls_to("paste-buffer").is_empty());
+    }
+}
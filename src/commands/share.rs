@@ -0,0 +1,341 @@
+// ── cove share / cove unshare ──
+//
+// `cove share api` lets a colleague watch a session from their own machine.
+// The session's window is linked into a small tmux session of its own
+// (`share-cove-<n>`), so the guest sees only that window and switching
+// windows on either side doesn't move the other. A tmate (or, with
+// `--with upterm`, upterm) server then runs `tmux attach -r` on it and cove
+// prints the join command. Shares are read-only twice over: the guest gets
+// the read-only link and the attach itself is read-only. `--write` hands out
+// the read-write link and attaches normally, so the guest can take over.
+//
+// Shares are recorded in ~/.cove/shares.json; `cove share` lists them and
+// `cove unshare api` stops the server and removes the extra session (the
+// window itself stays in cove).
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::ShareBackend;
+use crate::colors::*;
+use crate::commands::start;
+use crate::paths;
+use crate::say;
+use crate::tmux::{self, Tmux, TmuxClient};
+
+// ── Types ──
+
+/// One live share.
+#[derive(Serialize, Deserialize)]
+struct Share {
+    name: String,
+    backend: ShareBackend,
+    /// The tmux session holding the linked window.
+    session: String,
+    /// tmate's socket, or the tmux session running `upterm host`.
+    server: String,
+    join: String,
+    write: bool,
+}
+
+// ── Constants ──
+
+const SHARES_FILE: &str = "shares.json";
+
+/// How long upterm gets to connect and report its session.
+const UPTERM_WAIT: Duration = Duration::from_secs(15);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+// ── Helpers ──
+
+fn load_shares() -> Vec<Share> {
+    paths::cove_path(SHARES_FILE)
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_shares(shares: &[Share]) -> Result<(), String> {
+    let path = paths::ensure_cove_dir("")?.join(SHARES_FILE);
+    let content =
+        serde_json::to_string_pretty(shares).map_err(|e| format!("encode shares: {e}"))?;
+    fs::write(&path, content).map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+/// Name of the tmux session a window with ID `window_id` ("@3") is shared
+/// from. It mustn't start with "cove": `-t cove` would match it by prefix
+/// once the cove session is gone.
+fn share_session(window_id: &str) -> String {
+    format!("share-cove-{}", window_id.trim_start_matches('@'))
+}
+
+/// The shell command a guest's terminal runs: attach to `session` on the
+/// tmux server at `socket`, read-only unless `write`.
+fn attach_command(socket: &str, session: &str, write: bool) -> String {
+    let read_only = if write { "" } else { " -r" };
+    // The share server's own $TMUX would make tmux refuse to nest
+    format!(
+        "env -u TMUX tmux -S {} attach{read_only} -t {}",
+        start::shell_quote(socket),
+        start::shell_quote(&format!("={session}"))
+    )
+}
+
+/// The `ssh ...` join command in `upterm session current` output.
+fn parse_upterm_join(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| Some(line[line.find("ssh ")?..].trim().to_string()))
+}
+
+fn run_tool(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program).args(args).output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            format!("{program} is not installed")
+        } else {
+            format!("run {program}: {e}")
+        }
+    })?;
+    if !output.status.success() {
+        return Err(format!(
+            "{program} {}: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Start tmate on `attach` and return its socket and join command.
+fn start_tmate(session: &str, attach: &str, write: bool) -> Result<(String, String), String> {
+    let socket = paths::ensure_cove_dir("share")?.join(format!("{session}.sock"));
+    let socket = socket.to_string_lossy().to_string();
+    run_tool("tmate", &["-S", &socket, "new-session", "-d", attach])?;
+    run_tool("tmate", &["-S", &socket, "wait", "tmate-ready"])?;
+    let format = if write {
+        "#{tmate_ssh}"
+    } else {
+        "#{tmate_ssh_ro}"
+    };
+    let join = run_tool("tmate", &["-S", &socket, "display", "-p", format])?;
+    Ok((socket, join))
+}
+
+/// Start `upterm host` on `attach` in a detached tmux session and return
+/// that session and the join command. upterm only reports its session to
+/// commands running inside it, so the hosted command writes it to a file
+/// before attaching.
+fn start_upterm(
+    client: &dyn TmuxClient,
+    session: &str,
+    attach: &str,
+    write: bool,
+) -> Result<(String, String), String> {
+    let info = paths::ensure_cove_dir("share")?.join(format!("{session}.upterm"));
+    let _ = fs::remove_file(&info);
+    let hosted = format!(
+        "upterm session current > {} 2>&1; exec {attach}",
+        start::shell_quote(&info.to_string_lossy())
+    );
+    let mut host = vec!["upterm", "host", "--accept"];
+    if !write {
+        host.push("--read-only");
+    }
+    let host = format!(
+        "{} -- sh -c {}",
+        host.join(" "),
+        start::shell_quote(&hosted)
+    );
+    let server = format!("{session}-host");
+    client.output(&["new-session", "-d", "-s", &server, &host])?;
+    match wait_for_join(&info) {
+        Some(join) => Ok((server, join)),
+        None => {
+            let _ = client.output(&["kill-session", "-t", &format!("={server}")]);
+            Err(
+                "upterm didn't report a session (is it installed and can it reach its server?)"
+                    .into(),
+            )
+        }
+    }
+}
+
+fn wait_for_join(info: &Path) -> Option<String> {
+    let deadline = Instant::now() + UPTERM_WAIT;
+    while Instant::now() < deadline {
+        if let Some(join) = fs::read_to_string(info)
+            .ok()
+            .and_then(|out| parse_upterm_join(&out))
+        {
+            return Some(join);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    None
+}
+
+/// Shut down `share`'s server and its tmux session.
+fn stop(client: &dyn TmuxClient, share: &Share) {
+    if share.backend == ShareBackend::Tmate {
+        let _ = run_tool("tmate", &["-S", &share.server, "kill-server"]);
+        let _ = fs::remove_file(&share.server);
+    } else {
+        let _ = client.output(&["kill-session", "-t", &format!("={}", share.server)]);
+    }
+    let _ = client.output(&["kill-session", "-t", &format!("={}", share.session)]);
+}
+
+fn print_shares(shares: &[Share]) {
+    if shares.is_empty() {
+        println!("{ANSI_OVERLAY}No shared sessions.{ANSI_RESET}");
+        return;
+    }
+    for share in shares {
+        let backend = match share.backend {
+            ShareBackend::Tmate => "tmate",
+            ShareBackend::Upterm => "upterm",
+        };
+        let mode = if share.write {
+            "read-write"
+        } else {
+            "read-only"
+        };
+        println!(
+            "{ANSI_PEACH}{}{ANSI_RESET}  {ANSI_OVERLAY}{} {mode}{ANSI_RESET}  {}",
+            share.name, backend, share.join
+        );
+    }
+}
+
+/// Link `name`'s window into a share session and start `backend` on it.
+fn share_in(
+    client: &dyn TmuxClient,
+    name: &str,
+    backend: ShareBackend,
+    write: bool,
+) -> Result<Share, String> {
+    let target = client.window_target(name)?;
+    let window_id = client.output(&["display-message", "-t", &target, "-p", "#{window_id}"])?;
+    let socket = client.output(&["display-message", "-p", "#{socket_path}"])?;
+    let session = share_session(window_id.trim());
+    // The window the new session starts with is replaced by the linked one
+    client.output(&[
+        "new-session",
+        "-d",
+        "-s",
+        &session,
+        ";",
+        "link-window",
+        "-s",
+        window_id.trim(),
+        "-t",
+        &format!("={session}:"),
+        ";",
+        "kill-window",
+        "-t",
+        &format!("={session}:^"),
+    ])?;
+    let attach = attach_command(socket.trim(), &session, write);
+    let started = match backend {
+        ShareBackend::Tmate => start_tmate(&session, &attach, write),
+        ShareBackend::Upterm => start_upterm(client, &session, &attach, write),
+    };
+    let (server, join) = started.inspect_err(|_| {
+        let _ = client.output(&["kill-session", "-t", &format!("={session}")]);
+    })?;
+    Ok(Share {
+        name: name.to_string(),
+        backend,
+        session,
+        server,
+        join,
+        write,
+    })
+}
+
+// ── Public API ──
+
+/// Share session `name` through `backend`, or list shares when `name` is None.
+pub fn run(name: Option<&str>, backend: ShareBackend, write: bool) -> Result<(), String> {
+    let mut shares = load_shares();
+    let Some(name) = name else {
+        print_shares(&shares);
+        return Ok(());
+    };
+    let name = tmux::resolve_target(name)?;
+    if let Some(share) = shares.iter().find(|s| s.name == name) {
+        return Err(format!(
+            "{name} is already shared: {}\n  Stop it with: cove unshare {name}",
+            share.join
+        ));
+    }
+    let share = share_in(&Tmux, &name, backend, write)?;
+    let access = if write { "can type into" } else { "can watch" };
+    say!(
+        "{ANSI_GREEN}Shared{ANSI_RESET} {ANSI_PEACH}{name}{ANSI_RESET}: anyone with this command {access} it"
+    );
+    println!("  {ANSI_WHITE}{}{ANSI_RESET}", share.join);
+    say!("{ANSI_OVERLAY}Stop sharing with: cove unshare {name}{ANSI_RESET}");
+    shares.push(share);
+    save_shares(&shares)
+}
+
+/// Keep a share pointing at its session after a rename.
+pub fn rename(old: &str, new: &str) -> Result<(), String> {
+    let mut shares = load_shares();
+    let Some(share) = shares.iter_mut().find(|s| s.name == old) else {
+        return Ok(());
+    };
+    share.name = new.to_string();
+    save_shares(&shares)
+}
+
+/// Stop sharing session `name`.
+pub fn unshare(name: &str) -> Result<(), String> {
+    let mut shares = load_shares();
+    // A killed session's share is still stopped by its old name
+    let name = tmux::resolve_target(name).unwrap_or_else(|_| name.to_string());
+    let index = shares
+        .iter()
+        .position(|s| s.name == name)
+        .ok_or(format!("{name} isn't shared"))?;
+    let share = shares.remove(index);
+    stop(&Tmux, &share);
+    save_shares(&shares)?;
+    say!("{ANSI_GREEN}Stopped sharing{ANSI_RESET} {ANSI_PEACH}{name}{ANSI_RESET}");
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attach_command_is_read_only_by_default() {
+        assert_eq!(
+            attach_command("/tmp/tmux-0/default", "share-cove-3", false),
+            "env -u TMUX tmux -S '/tmp/tmux-0/default' attach -r -t '=share-cove-3'"
+        );
+        assert!(!attach_command("/s", "share-cove-3", true).contains(" -r "));
+        assert!(!share_session("@3").starts_with("cove"));
+    }
+
+    #[test]
+    fn test_parse_upterm_join() {
+        let out = "=== ABC123\nCommand:        sh\nHost:           ssh://uptermd.upterm.dev:22\n\
+                   SSH Session:    ssh ABC123@uptermd.upterm.dev\n";
+        assert_eq!(
+            parse_upterm_join(out).as_deref(),
+            Some("ssh ABC123@uptermd.upterm.dev")
+        );
+        assert_eq!(parse_upterm_join("upterm: not found\n"), None);
+    }
+}
@@ -1,40 +1,129 @@
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::thread;
 
+use crate::cli::Backend;
 use crate::colors::*;
-use crate::commands::init;
+use crate::commands::{bind, init, layout};
+use crate::config::{self, HookInstall};
 use crate::sidebar::state;
-use crate::tmux;
+use crate::tmux::{Tmux, TmuxClient, WindowInfo};
+use crate::{
+    autotag, changes, cmdlog, container, git, meta, names, paths, profiles, quiet, say, seed, send,
+    sessions, snapshots, stats, tabs, tmux, watch, worktrees,
+};
+
+// ── Types ──
+
+/// How to start a session beyond its name and directory.
+#[derive(Default)]
+pub struct StartOptions<'a> {
+    /// Start even if another session runs in the directory.
+    pub allow_duplicate_dir: bool,
+    /// Run Claude in a container: an image, or `devcontainer`.
+    pub container: Option<&'a str>,
+    /// Task description the session starts from: a URL or file.
+    pub seed: Option<&'a str>,
+    /// Send the seed to Claude as its first prompt.
+    pub seed_prompt: bool,
+    /// Run the session in a worktree with this branch checked out.
+    pub worktree: Option<&'a str>,
+}
+
+/// Where a new window is recorded. `Records::home` is the state root; tests
+/// use `Records::default()` with their own paths, so nothing under ~/.cove
+/// is read or written.
+#[derive(Default)]
+pub struct Records {
+    /// Where the session is counted for `cove stats`.
+    pub stats_log: Option<PathBuf>,
+    /// Where it's saved for `cove restore`.
+    pub manifest: Option<PathBuf>,
+    /// Also use the rest of the state root: cove's key bindings, the layout
+    /// picked for the name, and what's left recorded against the new pane's
+    /// recycled ID.
+    pub home: bool,
+}
+
+impl Records {
+    /// Everything under the state root.
+    pub fn home() -> Self {
+        Self {
+            stats_log: stats::log(),
+            manifest: sessions::manifest(),
+            home: true,
+        }
+    }
+}
 
 // ── Helpers ──
 
-fn resolve_sidebar_bin() -> String {
+/// Path of the cove binary, for the commands tmux and hooks run.
+pub fn cove_bin() -> String {
     // Try to find the binary we're running from (works after `cargo install` or symlink)
     if let Ok(exe) = std::env::current_exe()
         && let Ok(canonical) = std::fs::canonicalize(exe)
     {
         return canonical.to_string_lossy().to_string();
     }
-    // Fallback to the expected install location
-    let home = std::env::var("HOME").unwrap_or_default();
-    format!("{home}/.local/bin/cove")
+    // Fallback to the expected install location, then to $PATH
+    paths::home()
+        .map(|h| h.join(".local/bin/cove").to_string_lossy().to_string())
+        .unwrap_or_else(|| "cove".to_string())
 }
 
-fn settings_path() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_default();
-    PathBuf::from(home).join(".claude").join("settings.json")
+/// Command line that runs the sidebar in a window's pane .2.
+pub fn sidebar_command() -> String {
+    format!("{} sidebar", cove_bin())
 }
 
-/// Prompt user to install or update hooks if needed.
-fn check_hooks() {
-    let path = settings_path();
-    if init::hooks_installed(&path) {
+/// Install or update hooks if needed, asking first unless `hooks.install`
+/// (or quiet mode) settles it. Hooks installed for the project in `dir`
+/// count too.
+fn check_hooks(dir: &str) {
+    // No settings location, or no state dir for hooks to write to — status
+    // indicators can't work either way, so don't nag.
+    let Some(path) = paths::claude_settings() else {
+        return;
+    };
+    if paths::writable_cove_home().is_none() {
+        return;
+    }
+    let project = init::project_root(Path::new(dir));
+    if init::hooks_installed_for(Some(&project)) {
+        return;
+    }
+
+    // Hooks run by another installed cove are a choice for `cove init`,
+    // not a stale path to overwrite
+    let foreign = init::foreign_bins(&path);
+    if !foreign.is_empty() {
+        say!(
+            "{ANSI_PEACH}Note:{ANSI_RESET} cove hooks run another cove ({}); run cove init to pick one.\n",
+            foreign.join(", ")
+        );
         return;
     }
 
-    let bin = resolve_sidebar_bin();
-    let stale = init::has_stale_hooks(&path, &bin);
+    let stale = init::has_stale_hooks(&path, &init::hook_bin());
 
+    let install = match config::load().unwrap_or_default().hooks.install {
+        HookInstall::Always => true,
+        HookInstall::Ask if !quiet::on() => ask_to_install(stale),
+        HookInstall::Ask | HookInstall::Never => false,
+    };
+    if install {
+        match init::install_hooks(&path) {
+            Ok(()) if stale => say!("Hooks updated.\n"),
+            Ok(()) => say!("Hooks installed.\n"),
+            Err(e) => eprintln!("Failed to install hooks: {e}\n"),
+        }
+    }
+}
+
+/// Ask whether to install (or, when `stale`, update) cove's hooks.
+fn ask_to_install(stale: bool) -> bool {
     if stale {
         println!(
             "{ANSI_PEACH}Warning:{ANSI_RESET} Cove hooks point to an old binary path.\n\
@@ -54,76 +143,574 @@ fn check_hooks() {
 
     let mut input = String::new();
     if io::stdin().read_line(&mut input).is_err() {
-        return;
+        return false;
     }
 
     let answer = input.trim().to_lowercase();
-    if answer.is_empty() || answer == "y" || answer == "yes" {
-        match init::install_hooks(&path) {
-            Ok(()) if stale => println!("Hooks updated.\n"),
-            Ok(()) => println!("Hooks installed.\n"),
-            Err(e) => eprintln!("Failed to install hooks: {e}\n"),
-        }
-    } else {
+    let yes = answer.is_empty() || answer == "y" || answer == "yes";
+    if !yes {
         println!("Skipped. Run `cove init` later to enable status indicators.\n");
     }
+    yes
 }
 
-// ── Public API ──
+/// Command line that starts Claude (`agent.command` in the config), read once.
+pub fn claude() -> &'static str {
+    static COMMAND: OnceLock<String> = OnceLock::new();
+    COMMAND.get_or_init(|| {
+        let command = config::load().unwrap_or_default().agent.command;
+        if command.trim().is_empty() {
+            "claude".to_string()
+        } else {
+            command
+        }
+    })
+}
+
+/// Quote a string for safe interpolation into a `sh -c` command line.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
 
-pub fn run(name: &str, dir: Option<&str>) -> Result<(), String> {
+/// Managed windows whose directory is `dir` (both compared canonicalized).
+fn sessions_in_dir<'a>(windows: &'a [WindowInfo], dir: &str) -> Vec<&'a WindowInfo> {
+    let canonical = |p: &str| {
+        std::fs::canonicalize(p)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| p.to_string())
+    };
+    let dir = canonical(dir);
+    windows
+        .iter()
+        .filter(|w| w.managed && canonical(&w.pane_path) == dir)
+        .collect()
+}
+
+/// Ask before starting another session in a directory; no answer (stdin
+/// isn't a terminal) means no.
+fn confirm_duplicate() -> bool {
+    if quiet::on() || !io::stdin().is_terminal() {
+        return false;
+    }
+    print!("Start another session there? [y/N] ");
+    let _ = io::stdout().flush();
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        return false;
+    }
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Two sessions in one directory are usually an accident: list the ones
+/// already there and go ahead only if `confirm` says so.
+fn check_duplicate_dir(
+    client: &dyn TmuxClient,
+    dir: &str,
+    confirm: impl Fn() -> bool,
+) -> Result<(), String> {
+    if !client.has_session() {
+        return Ok(());
+    }
+    let windows = client.list_windows()?;
+    let existing = sessions_in_dir(&windows, dir);
+    if existing.is_empty() {
+        return Ok(());
+    }
+    say!(
+        "{ANSI_PEACH}Warning:{ANSI_RESET} {} already in use by:",
+        paths::tildify(dir)
+    );
+    for w in &existing {
+        say!("  {ANSI_WHITE}{}{ANSI_RESET}", w.name);
+    }
+    if confirm() {
+        return Ok(());
+    }
+    Err(format!(
+        "Not starting a second session there. Pass {ANSI_PEACH}--allow-duplicate-dir{ANSI_RESET} to do it anyway."
+    ))
+}
+
+/// Canonicalize the session directory; with `git_root`, climb to the top of
+/// the repository containing it.
+fn resolve_dir(dir: Option<&str>, git_root: bool) -> Result<String, String> {
     let dir = dir.unwrap_or(".");
-    let dir = std::fs::canonicalize(dir)
-        .map_err(|e| format!("invalid directory '{dir}': {e}"))?
-        .to_string_lossy()
-        .to_string();
+    let path = std::fs::canonicalize(dir).map_err(|e| format!("invalid directory '{dir}': {e}"))?;
+    if git_root {
+        return git::toplevel(&path);
+    }
+    Ok(path.to_string_lossy().to_string())
+}
 
-    let sidebar_bin = resolve_sidebar_bin();
-    let sidebar_cmd = format!("{sidebar_bin} sidebar");
+// ── Public API ──
 
-    // First-run: prompt to install hooks if needed
-    check_hooks();
+/// Start a session as a kitty/wezterm tab instead of a tmux window.
+pub fn run_tab(
+    name: &str,
+    dir: Option<&str>,
+    git_root: bool,
+    backend: Backend,
+) -> Result<(), String> {
+    let dir = resolve_dir(dir, git_root)?;
+    let name = &tagged(name, &dir);
+    names::validate(name)?;
 
-    if tmux::has_session() {
-        // Reject duplicate window names
-        let names = tmux::list_window_names()?;
-        if names.iter().any(|n| n == name) {
-            return Err(format!(
-                "Session '{ANSI_PEACH}{name}{ANSI_RESET}' already exists. Pick a different name."
-            ));
+    check_hooks(&dir);
+    snapshot_start(name, &dir);
+
+    tabs::spawn(backend, name, &dir, &profiles::command_for(&dir, claude()))?;
+    stats::record(stats::Kind::Created, 0);
+    say!("Opened {backend} tab: {ANSI_PEACH}{name}{ANSI_RESET}");
+    Ok(())
+}
+
+/// Start session `name` in `dir` (or the repository root containing it, with
+/// `git_root`).
+pub fn run(
+    name: &str,
+    dir: Option<&str>,
+    git_root: bool,
+    options: &StartOptions,
+) -> Result<(), String> {
+    let mut dir = resolve_dir(dir, git_root)?;
+    let name = &tagged(name, &dir);
+    // Check the name before fetching, asking or setting up anything for it
+    check_name(&Tmux, name, &dir)?;
+    let seed = options.seed.map(seed::fetch).transpose()?;
+
+    let checkout = match options.worktree {
+        Some(branch) => {
+            let checkout = worktrees::checkout(Path::new(&dir), branch)?;
+            let verb = if checkout.created {
+                "created"
+            } else {
+                "reusing"
+            };
+            say!(
+                "  {ANSI_OVERLAY}{verb}{ANSI_RESET} {}",
+                paths::tildify(&checkout.path)
+            );
+            dir = checkout.path.clone();
+            Some(checkout)
         }
+        None => None,
+    };
+
+    // First-run: prompt to install hooks if needed
+    check_hooks(&dir);
 
-        tmux::new_window(name, &dir)?;
-        tmux::setup_layout(name, &dir, &sidebar_cmd)?;
+    if !options.allow_duplicate_dir
+        && let Err(e) = check_duplicate_dir(&Tmux, &dir, confirm_duplicate)
+    {
+        if let Some(checkout) = &checkout {
+            worktrees::discard(checkout);
+        }
+        return Err(e);
+    }
+    snapshot_start(name, &dir);
 
-        // Purge stale event files that match this pane's recycled ID
-        if let Ok(pane_id) = tmux::get_claude_pane_id(name) {
-            state::purge_events_for_pane(&pane_id);
+    // Typed once Claude is ready: the pane command is what respawns re-run
+    let prompt = seed
+        .as_ref()
+        .filter(|_| options.seed_prompt)
+        .map(|s| s.text.clone());
+    let mut agent_cmd = profiles::command_for(&dir, claude());
+    if options.container.is_none() && seed.is_none() && checkout.is_none() {
+        return run_in(
+            &Tmux,
+            name,
+            &dir,
+            &agent_cmd,
+            None,
+            tmux::is_inside_tmux(),
+            &Records::home(),
+        );
+    }
+    if let Some(spec) = options.container {
+        say!("Starting container {ANSI_OVERLAY}({spec}){ANSI_RESET}...");
+        let started = container::start(spec, name, &dir)?;
+        agent_cmd = container::exec_command(&started, &dir, &agent_cmd);
+        meta::update(name, |m| m.container = Some(started))?;
+    }
+    if let Some(seed) = seed {
+        meta::update(name, |m| m.seed = Some(seed))?;
+    }
+    // A worktree cove keeps under its root is the session's, to remove on kill
+    if let Some(checkout) = checkout.as_ref().filter(|c| worktrees::is_managed(&c.path)) {
+        meta::update(name, |m| m.worktree = Some(checkout.path.clone()))?;
+    }
+    // Recorded first: outside tmux, run_in returns only once the user detaches
+    let result = run_in(
+        &Tmux,
+        name,
+        &dir,
+        &agent_cmd,
+        prompt.as_deref(),
+        tmux::is_inside_tmux(),
+        &Records::home(),
+    );
+    if result.is_err() {
+        let _ = container::tear_down(name);
+        let _ = meta::update(name, |m| {
+            m.seed = None;
+            m.worktree = None;
+        });
+        if let Some(checkout) = &checkout {
+            worktrees::discard(checkout);
         }
+    }
+    result
+}
+
+/// Snapshot `dir` as session `name` starts, for `cove rollback`.
+fn snapshot_start(name: &str, dir: &str) {
+    let config = config::load().unwrap_or_default();
+    if let Err(e) = snapshots::take(&config.snapshots, Path::new(dir), name, "session start") {
+        log::warn!("snapshot of {name}: {e}");
+    }
+}
+
+/// `name` with the tag the config's `tags` rules give `dir`.
+fn tagged(name: &str, dir: &str) -> String {
+    autotag::apply(&config::load().unwrap_or_default().tags, name, dir)
+}
+
+/// `name` tagged with the directory's name (`web/fix-auth`), if that is a
+/// valid name no window has yet.
+fn tagged_alternative(name: &str, dir: &str, windows: &[String]) -> Option<String> {
+    let base = Path::new(dir).file_name()?.to_string_lossy();
+    let tagged = names::sanitize(&format!("{base}/{}", names::short_name(name)));
+    (tagged != name && !windows.contains(&tagged)).then_some(tagged)
+}
+
+/// Err if `name` isn't valid or a window already has it.
+fn check_name(client: &dyn TmuxClient, name: &str, dir: &str) -> Result<(), String> {
+    names::validate(name)?;
+    if !client.has_session() {
+        return Ok(());
+    }
+    // The full name must be free; a short name may repeat under another tag
+    let windows = client.list_window_names()?;
+    if windows.iter().any(|n| n == name) {
+        let hint = match tagged_alternative(name, dir, &windows) {
+            Some(tagged) => {
+                format!(" Pick a different name, or tag it: {ANSI_PEACH}{tagged}{ANSI_RESET}.")
+            }
+            None => " Pick a different name.".to_string(),
+        };
+        return Err(format!(
+            "Session '{ANSI_PEACH}{name}{ANSI_RESET}' already exists.{hint}"
+        ));
+    }
+    Ok(())
+}
 
-        // If outside tmux, attach so the user sees it
-        if !tmux::is_inside_tmux() {
-            tmux::attach()?;
+/// `prompt` is typed into Claude once it's ready (see `send_first_prompt`),
+/// and the new session is recorded in `records`.
+fn run_in(
+    client: &dyn TmuxClient,
+    name: &str,
+    dir: &str,
+    agent_cmd: &str,
+    prompt: Option<&str>,
+    inside_tmux: bool,
+    records: &Records,
+) -> Result<(), String> {
+    check_name(client, name, dir)?;
+    let had_session = client.has_session();
+    create_window_in(client, name, dir, agent_cmd, inside_tmux, records)?;
+    let sending = prompt.and_then(|p| send_first_prompt(name, p));
+    // If outside tmux, attach so the user sees it
+    if !inside_tmux || !had_session {
+        client.attach()?;
+    }
+    if let Some(handle) = sending {
+        let _ = handle.join();
+    }
+    Ok(())
+}
+
+/// Type `prompt` into session `name`'s Claude once it's ready, on a thread:
+/// outside tmux the caller attaches next, which only returns when the user
+/// detaches. Join the handle before exiting so the prompt isn't lost.
+///
+/// The prompt is typed rather than passed on Claude's command line, which
+/// the pane's respawns, restarts and `cove restore` run again.
+pub fn send_first_prompt(name: &str, prompt: &str) -> Option<thread::JoinHandle<()>> {
+    if prompt.trim().is_empty() {
+        return None;
+    }
+    let pane_id = match Tmux.get_claude_pane_id(name) {
+        Ok(pane_id) => pane_id,
+        Err(e) => {
+            eprintln!("{ANSI_PEACH}Warning:{ANSI_RESET} {name}: prompt not sent: {e}");
+            return None;
         }
+    };
+    let (name, prompt) = (name.to_string(), prompt.to_string());
+    Some(thread::spawn(move || {
+        if let Err(e) = send::deliver(&Tmux, &pane_id, &prompt) {
+            eprintln!("{ANSI_PEACH}Warning:{ANSI_RESET} {name}: prompt not sent: {e}");
+        }
+    }))
+}
+
+/// Create a window with the full cove layout — or the cove session itself if
+/// none exists yet — without attaching to it.
+pub fn create_window(name: &str, dir: &str, agent_cmd: &str) -> Result<(), String> {
+    create_window_in(
+        &Tmux,
+        name,
+        dir,
+        agent_cmd,
+        tmux::is_inside_tmux(),
+        &Records::home(),
+    )
+}
+
+/// `create_window` through `client`, recording the session in `records`.
+pub fn create_window_in(
+    client: &dyn TmuxClient,
+    name: &str,
+    dir: &str,
+    agent_cmd: &str,
+    inside_tmux: bool,
+    records: &Records,
+) -> Result<(), String> {
+    let sidebar_bin = cove_bin();
+    let sidebar_cmd = sidebar_command();
+
+    if client.has_session() {
+        client.new_window(name, dir, agent_cmd)?;
+        client.setup_layout(name, dir, &sidebar_cmd)?;
     } else {
         // No session — create from scratch. Must run outside tmux for proper dimensions.
-        if tmux::is_inside_tmux() {
+        if inside_tmux {
             return Err(format!(
                 "No cove session exists. Run from outside tmux first:\n  \
                  {ANSI_PEACH}cove{ANSI_RESET} {name} {dir}"
             ));
         }
 
-        tmux::new_session(name, &dir, &sidebar_cmd)?;
-
-        // Purge stale event files that match this pane's recycled ID.
         // new_session creates detached (-d), so this runs before the user sees anything.
-        if let Ok(pane_id) = tmux::get_claude_pane_id(name) {
-            state::purge_events_for_pane(&pane_id);
+        client.new_session(name, dir, &sidebar_cmd, agent_cmd)?;
+    }
+
+    if records.home {
+        // `prefix F` leaves focus mode, where the sidebar isn't visible
+        bind::bind_focus_key(client, &format!("{} focus", shell_quote(&sidebar_bin)));
+        // Before `cove bind`'s keys, so a digit picked there wins
+        if config::load().unwrap_or_default().tmux.slot_keys {
+            bind::bind_slot_keys(client);
         }
+        // Keys picked with `cove bind` don't outlive the tmux server
+        bind::apply_in(client);
+        // A re-created window gets back the preset picked for it (`cove layout`)
+        layout::reapply_in(client, name);
+    }
 
-        tmux::attach()?;
+    // Purge stale event and change files that match this pane's recycled ID
+    let pane_id = client.get_claude_pane_id(name).unwrap_or_default();
+    if records.home && !pane_id.is_empty() {
+        purge_pane_state(&pane_id, None);
+    }
+    if let Some(path) = &records.manifest
+        && let Err(e) = sessions::record_in(path, name, dir, agent_cmd, &pane_id)
+    {
+        log::warn!("sessions: {e}");
     }
 
+    if let Some(path) = &records.stats_log {
+        let _ = stats::record_in(path, stats::Kind::Created, 0);
+    }
     Ok(())
 }
+
+/// Remove everything recorded against a Claude pane ID: hook events and
+/// commands run (both kept in their history under `name`), edited files, and
+/// watch results.
+/// Used both when a window is created (recycled IDs) and when one is killed.
+pub fn purge_pane_state(pane_id: &str, name: Option<&str>) {
+    state::purge_events_for_pane(pane_id, name);
+    changes::purge_for_pane(pane_id);
+    cmdlog::archive_for_pane(pane_id, name);
+    watch::purge_for_pane(pane_id);
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("plain"), "'plain'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_resolve_dir_git_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        git::output(&root, &["init", "-q"], &[0]).unwrap();
+        let sub = root.join("crates/core");
+        std::fs::create_dir_all(&sub).unwrap();
+        let sub = sub.to_str().unwrap();
+
+        assert_eq!(resolve_dir(Some(sub), false).unwrap(), sub);
+        assert_eq!(
+            resolve_dir(Some(sub), true).unwrap(),
+            root.to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn test_run_rejects_duplicate_name() {
+        let fake = FakeTmux::new().with_windows(&[("cove-test-api", "claude", "%t1")]);
+        let err = run_in(
+            &fake,
+            "cove-test-api",
+            "/code/web",
+            "claude",
+            None,
+            false,
+            &Records::default(),
+        )
+        .unwrap_err();
+        assert!(err.contains("web/cove-test-api"), "{err}");
+        assert!(fake.calls_to("new-window").is_empty());
+    }
+
+    #[test]
+    fn test_run_allows_short_name_under_another_tag() {
+        let fake = FakeTmux::new().with_windows(&[("backend/fix-auth", "claude", "%t1")]);
+        run_in(
+            &fake,
+            "frontend/fix-auth",
+            "/tmp",
+            "claude",
+            None,
+            true,
+            &Records::default(),
+        )
+        .unwrap();
+        assert_eq!(fake.calls_to("new-window").len(), 1);
+    }
+
+    #[test]
+    fn test_run_rejects_untargetable_name() {
+        let fake = FakeTmux::new().with_windows(&[("cove-test-api", "claude", "%t1")]);
+        assert!(
+            run_in(
+                &fake,
+                "cove-test.web",
+                "/tmp",
+                "claude",
+                None,
+                false,
+                &Records::default()
+            )
+            .is_err()
+        );
+        assert!(fake.calls_to("new-window").is_empty());
+    }
+
+    #[test]
+    fn test_run_adds_window_and_attaches() {
+        let fake = FakeTmux::new().with_windows(&[("cove-test-api", "claude", "%t1")]);
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("stats.jsonl");
+        let manifest = dir.path().join("sessions.json");
+        let records = Records {
+            stats_log: Some(log.clone()),
+            manifest: Some(manifest.clone()),
+            home: false,
+        };
+        run_in(
+            &fake,
+            "cove-test-web",
+            "/tmp",
+            "claude",
+            None,
+            false,
+            &records,
+        )
+        .unwrap();
+
+        let new_window = fake.calls_to("new-window");
+        assert_eq!(new_window.len(), 1);
+        assert_eq!(new_window[0][..3], ["new-window", "-t", "cove:2"]);
+        assert_eq!(fake.calls_to("attach").len(), 1);
+        assert_eq!(stats::read_in(&log)[0].kind, stats::Kind::Created);
+        let saved = std::fs::read_to_string(&manifest).unwrap();
+        assert!(saved.contains("\"cove-test-web\""), "{saved}");
+    }
+
+    #[test]
+    fn test_duplicate_dir_needs_confirmation() {
+        let fake = FakeTmux::new()
+            .with_windows(&[
+                ("cove-test-api", "claude", "%t1"),
+                ("scratch", "zsh", "%t2"),
+            ])
+            .unmanaged("scratch");
+        assert!(check_duplicate_dir(&fake, "/code/cove-test-api", || false).is_err());
+        assert!(check_duplicate_dir(&fake, "/code/cove-test-api", || true).is_ok());
+        // Only cove sessions count
+        assert!(check_duplicate_dir(&fake, "/code/scratch", || false).is_ok());
+        assert!(check_duplicate_dir(&fake, "/code/web", || false).is_ok());
+    }
+
+    #[test]
+    fn test_run_inside_tmux_skips_attach() {
+        let fake = FakeTmux::new().with_windows(&[("cove-test-api", "claude", "%t1")]);
+        run_in(
+            &fake,
+            "cove-test-web",
+            "/tmp",
+            "claude",
+            None,
+            true,
+            &Records::default(),
+        )
+        .unwrap();
+        assert!(fake.calls_to("attach").is_empty());
+    }
+
+    #[test]
+    fn test_first_session_is_created_then_attached() {
+        let fake = FakeTmux::new()
+            .fail("has-session", "no server running")
+            .on("display-message", "%t9");
+        run_in(
+            &fake,
+            "cove-test-api",
+            "/tmp",
+            "claude",
+            None,
+            false,
+            &Records::default(),
+        )
+        .unwrap();
+        assert_eq!(fake.calls_to("new-session").len(), 1);
+        assert_eq!(fake.calls_to("attach").len(), 1);
+
+        // Inside tmux there's no terminal to size a new session against
+        let fake = FakeTmux::new().fail("has-session", "no server running");
+        assert!(
+            run_in(
+                &fake,
+                "cove-test-api",
+                "/tmp",
+                "claude",
+                None,
+                true,
+                &Records::default()
+            )
+            .is_err()
+        );
+        assert!(fake.calls_to("new-session").is_empty());
+    }
+}
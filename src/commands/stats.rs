@@ -0,0 +1,296 @@
+// ── cove stats ──
+//
+// How much supervising agents adds up to, from the lifetime counters in
+// stats.rs. `cove stats` compares this week with last week; `--all-time`
+// shows the totals since the first recorded event, the weekly average, the
+// most recent weeks and the milestones passed along the way (the 100th
+// prompt, the 10th hour of Working, ...) with the day each was reached.
+// Weeks start on Monday, local time. The attention column is the time you
+// were clocked in to sessions (attention.rs), next to the agents' working
+// time; each stretch counts towards the week it started in.
+
+use std::collections::BTreeMap;
+
+use crate::attention::{self, Interval};
+use crate::colors::*;
+use crate::commands::timesheet;
+use crate::stats::{self, Entry, Kind};
+
+// ── Types ──
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Totals {
+    created: u64,
+    prompts: u64,
+    working_secs: u64,
+    /// Time you were clocked in to sessions.
+    attention_secs: u64,
+    killed: u64,
+}
+
+// ── Constants ──
+
+const DAY: i64 = 86_400;
+
+/// Weeks listed under `--all-time`, newest first.
+const WEEKS_SHOWN: usize = 12;
+
+/// Counts at which a milestone is passed.
+const COUNT_MILESTONES: &[u64] = &[10, 100, 1000, 10_000];
+
+/// Hours of Working at which a milestone is passed.
+const HOUR_MILESTONES: &[u64] = &[10, 100, 1000];
+
+// ── Helpers ──
+
+impl Totals {
+    fn add(&mut self, entry: &Entry) {
+        match entry.kind {
+            Kind::Created => self.created += 1,
+            Kind::Prompt => self.prompts += 1,
+            Kind::Working => self.working_secs += entry.secs,
+            Kind::Killed => self.killed += 1,
+        }
+    }
+
+    /// (value, milestones, what a milestone is of) per counter.
+    fn counters(&self) -> [(u64, &'static [u64], &'static str); 4] {
+        [
+            (self.created, COUNT_MILESTONES, "sessions created"),
+            (self.prompts, COUNT_MILESTONES, "prompts sent"),
+            (
+                self.working_secs / 3600,
+                HOUR_MILESTONES,
+                "hours of Working",
+            ),
+            (self.killed, COUNT_MILESTONES, "sessions killed"),
+        ]
+    }
+}
+
+/// Local day of unix time `ts`, in days since 1970-01-01.
+fn local_day(ts: u64, offset: i64) -> i64 {
+    (ts as i64 + offset).div_euclid(DAY)
+}
+
+/// The Monday starting the week that contains `day`.
+fn week_of(day: i64) -> i64 {
+    // 1970-01-01 was a Thursday
+    day - (day + 3).rem_euclid(7)
+}
+
+/// Totals per week, keyed by the week's Monday.
+fn by_week(entries: &[Entry], attention: &[Interval], offset: i64) -> BTreeMap<i64, Totals> {
+    let mut weeks: BTreeMap<i64, Totals> = BTreeMap::new();
+    for entry in entries {
+        weeks
+            .entry(week_of(local_day(entry.ts, offset)))
+            .or_default()
+            .add(entry);
+    }
+    for interval in attention {
+        weeks
+            .entry(week_of(local_day(interval.start, offset)))
+            .or_default()
+            .attention_secs += interval.end - interval.start;
+    }
+    weeks
+}
+
+/// The milestones `entries` (oldest first) pass, with the local day of each.
+fn milestones(entries: &[Entry], offset: i64) -> Vec<(i64, String)> {
+    let mut totals = Totals::default();
+    let mut out = Vec::new();
+    for entry in entries {
+        let before = totals.counters();
+        totals.add(entry);
+        for ((old, marks, what), (new, _, _)) in before.into_iter().zip(totals.counters()) {
+            for mark in marks.iter().filter(|&&m| old < m && m <= new) {
+                out.push((local_day(entry.ts, offset), format!("{mark} {what}")));
+            }
+        }
+    }
+    out
+}
+
+fn hours(secs: u64) -> String {
+    format!("{:.1}h", secs as f64 / 3600.0)
+}
+
+fn header() -> String {
+    format!(
+        "{ANSI_OVERLAY}{:<12} {:>9} {:>8} {:>8} {:>9} {:>7}{ANSI_RESET}\n",
+        "", "sessions", "prompts", "working", "attention", "killed"
+    )
+}
+
+fn row(label: &str, totals: &Totals) -> String {
+    format!(
+        "{ANSI_WHITE}{label:<12}{ANSI_RESET} {:>9} {:>8} {:>8} {:>9} {:>7}\n",
+        totals.created,
+        totals.prompts,
+        hours(totals.working_secs),
+        hours(totals.attention_secs),
+        totals.killed
+    )
+}
+
+/// This week against last week, as of local day `today`.
+fn render_recent(weeks: &BTreeMap<i64, Totals>, today: i64) -> String {
+    let this_week = week_of(today);
+    let mut out = header();
+    for (label, week) in [("this week", this_week), ("last week", this_week - 7)] {
+        out.push_str(&row(label, &weeks.get(&week).copied().unwrap_or_default()));
+    }
+    out
+}
+
+/// Lifetime totals, the weekly average, recent weeks and milestones.
+fn render_all_time(entries: &[Entry], attention: &[Interval], offset: i64, today: i64) -> String {
+    let Some(first) = entries.first() else {
+        return format!(
+            "{ANSI_OVERLAY}Nothing recorded yet: counting starts with the next session.{ANSI_RESET}\n"
+        );
+    };
+    let first_week = week_of(local_day(first.ts, offset));
+    let span = ((week_of(today) - first_week) / 7 + 1) as u64;
+    let weeks = by_week(entries, attention, offset);
+    let mut total = Totals::default();
+    entries.iter().for_each(|e| total.add(e));
+    total.attention_secs = attention.iter().map(|i| i.end - i.start).sum();
+
+    let plural = if span == 1 { "" } else { "s" };
+    let mut out = format!(
+        "{ANSI_OVERLAY}Since {} ({span} week{plural}){ANSI_RESET}\n",
+        timesheet::format_day(local_day(first.ts, offset))
+    );
+    out.push_str(&header());
+    out.push_str(&row("all time", &total));
+    out.push_str(&format!(
+        "{ANSI_WHITE}{:<12}{ANSI_RESET} {:>9.1} {:>8.1} {:>8} {:>9} {:>7.1}\n",
+        "per week",
+        total.created as f64 / span as f64,
+        total.prompts as f64 / span as f64,
+        hours(total.working_secs / span),
+        hours(total.attention_secs / span),
+        total.killed as f64 / span as f64
+    ));
+    for (week, totals) in weeks.iter().rev().take(WEEKS_SHOWN) {
+        out.push_str(&row(&timesheet::format_day(*week), totals));
+    }
+
+    let passed = milestones(entries, offset);
+    if !passed.is_empty() {
+        out.push_str(&format!("\n{ANSI_OVERLAY}Milestones{ANSI_RESET}\n"));
+        for (day, what) in passed.iter().rev() {
+            out.push_str(&format!(
+                "  {ANSI_OVERLAY}{}{ANSI_RESET}  {ANSI_PEACH}{what}{ANSI_RESET}\n",
+                timesheet::format_day(*day)
+            ));
+        }
+    }
+    out
+}
+
+// ── Public API ──
+
+/// Print this week's and last week's counters, or with `all_time`, the
+/// lifetime view.
+pub fn run(all_time: bool) -> Result<(), String> {
+    let entries = stats::log()
+        .map(|path| stats::read_in(&path))
+        .unwrap_or_default();
+    let attention = attention::all();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let offset = timesheet::utc_offset();
+    let today = local_day(now, offset);
+    if all_time {
+        print!("{}", render_all_time(&entries, &attention, offset, today));
+    } else {
+        print!(
+            "{}",
+            render_recent(&by_week(&entries, &attention, offset), today)
+        );
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: Kind, day: i64, secs: u64) -> Entry {
+        Entry {
+            kind,
+            ts: (day * DAY) as u64 + 3600,
+            secs,
+        }
+    }
+
+    #[test]
+    fn test_weeks_start_on_monday() {
+        // 2026-10-12 is a Monday
+        let monday = 20_738;
+        assert_eq!(timesheet::format_day(monday), "2026-10-12");
+        assert_eq!(week_of(monday), monday);
+        assert_eq!(week_of(monday + 6), monday);
+        assert_eq!(week_of(monday - 1), monday - 7);
+
+        let entries = [
+            entry(Kind::Created, monday - 3, 0),
+            entry(Kind::Prompt, monday - 3, 0),
+            entry(Kind::Working, monday + 1, 5400),
+            entry(Kind::Killed, monday + 2, 0),
+        ];
+        let watched = Interval {
+            name: "api".to_string(),
+            dir: "/code/api".to_string(),
+            start: entries[2].ts,
+            end: entries[2].ts + 1800,
+        };
+        let weeks = by_week(&entries, &[watched], 0);
+        assert_eq!(weeks[&(monday - 7)].prompts, 1);
+        assert_eq!(
+            weeks[&monday],
+            Totals {
+                working_secs: 5400,
+                attention_secs: 1800,
+                killed: 1,
+                ..Totals::default()
+            }
+        );
+        let recent = render_recent(&weeks, monday + 2);
+        assert!(recent.contains("1.5h      0.5h"), "{recent}");
+    }
+
+    #[test]
+    fn test_milestones_dated_when_passed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats.jsonl");
+        stats::record_in(&path, Kind::Prompt, 0).unwrap();
+        stats::record_in(&path, Kind::Working, 90).unwrap();
+        let logged = stats::read_in(&path);
+        assert_eq!(logged.len(), 2);
+        assert_eq!((logged[1].kind, logged[1].secs), (Kind::Working, 90));
+
+        let mut entries: Vec<Entry> = (0..12).map(|i| entry(Kind::Prompt, i, 0)).collect();
+        entries.push(entry(Kind::Working, 12, 11 * 3600));
+        let passed = milestones(&entries, 0);
+        assert_eq!(
+            passed,
+            [
+                (9, "10 prompts sent".to_string()),
+                (12, "10 hours of Working".to_string())
+            ]
+        );
+
+        let out = render_all_time(&entries, &[], 0, 13);
+        assert!(out.contains("Since 1970-01-01 (3 weeks)"), "{out}");
+        assert!(out.contains("10 prompts sent"), "{out}");
+    }
+}
@@ -0,0 +1,610 @@
+// ── Time spent supervising sessions ──
+//
+// `cove timesheet` turns hook events into billable time. Each event's state
+// lasts until the session's next event: Working counts as working, Asking and
+// Idle ("your turn") as waiting on you; anything else isn't counted. A state
+// that outlasts MAX_GAP is cut there, since nobody was watching a session
+// that quiet. Events come from the live event files plus the history that
+// killed sessions leave behind (`state::history_dir`).
+//
+// Attention, the time you were clocked in to a session with `c` in the
+// sidebar (see attention.rs), is reported in a column of its own: Claude
+// working and you watching are different things, and can overlap.
+//
+// Time is split by local day, then summed per day, session and repository.
+// Each timestamp gets the UTC offset in force at that moment, so a week that
+// spans a DST change still splits at local midnight.
+// `cove annotate` milestones don't count as events here; the CSV lists each
+// day's in a column, followed by the session's tag (`work` for `work/api`),
+// which Toggl gets as the entry's tag.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::cli::SheetFormat;
+use crate::sidebar::state::{self, EventEntry, WindowState};
+use crate::tmux::{self, TmuxClient};
+use crate::{attention, git, names, paths};
+
+// ── Types ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Working,
+    Waiting,
+    /// You were clocked in to the session.
+    Attention,
+}
+
+/// One session's event file.
+pub struct Session {
+    /// Window name, for files archived when the session was killed.
+    pub name: Option<String>,
+    /// Oldest first.
+    pub events: Vec<EventEntry>,
+}
+
+/// Time in one session on one day.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct DayTotal {
+    /// Unix time the session was first active that day.
+    start: u64,
+    working: u64,
+    waiting: u64,
+    attention: u64,
+    /// Notes of the day's `cove annotate` milestones, oldest first.
+    milestones: Vec<String>,
+}
+
+/// (local day, session, repository).
+type Key = (i64, String, String);
+
+// ── Constants ──
+
+/// Longest a single state is counted for.
+const MAX_GAP: u64 = 30 * 60;
+
+const DAY: i64 = 86_400;
+
+// ── Helpers ──
+
+/// Seconds east of UTC of `date +%z` output ("+0200").
+fn parse_offset(text: &str) -> i64 {
+    let text = text.trim();
+    let (sign, digits) = match text.split_at_checked(1) {
+        Some(("-", d)) => (-1, d),
+        Some(("+", d)) => (1, d),
+        _ => return 0,
+    };
+    let hours: i64 = digits.get(..2).and_then(|h| h.parse().ok()).unwrap_or(0);
+    let mins: i64 = digits.get(2..4).and_then(|m| m.parse().ok()).unwrap_or(0);
+    sign * (hours * 3600 + mins * 60)
+}
+
+/// Seconds east of UTC of the local timezone, per `date +%z` ("+0200").
+pub fn utc_offset() -> i64 {
+    let Ok(output) = Command::new("date").arg("+%z").output() else {
+        return 0;
+    };
+    parse_offset(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Seconds east of UTC of the local timezone at Unix time `ts`: GNU date
+/// takes `-d @<ts>`, BSD date `-r <ts>`.
+pub fn utc_offset_at(ts: u64) -> i64 {
+    let at = format!("@{ts}");
+    let secs = ts.to_string();
+    [["-d", at.as_str()], ["-r", secs.as_str()]]
+        .into_iter()
+        .find_map(|args| {
+            let output = Command::new("date").args(args).arg("+%z").output().ok()?;
+            output
+                .status
+                .success()
+                .then(|| parse_offset(&String::from_utf8_lossy(&output.stdout)))
+        })
+        .unwrap_or_else(utc_offset)
+}
+
+/// `utc_offset_at`, asking `date` once per hour of time looked up (offsets
+/// change on the hour).
+fn local_offsets() -> impl Fn(u64) -> i64 {
+    let cache = RefCell::new(HashMap::new());
+    move |ts| {
+        *cache
+            .borrow_mut()
+            .entry(ts / 3600)
+            .or_insert_with_key(|hour| utc_offset_at(hour * 3600))
+    }
+}
+
+/// Local day of Unix time `ts`.
+fn local_day(ts: u64, offset: &dyn Fn(u64) -> i64) -> i64 {
+    (ts as i64 + offset(ts)).div_euclid(DAY)
+}
+
+/// Unix time of the local midnight starting `day`. The offset is looked up
+/// at a first guess of that time, then again at the result, in case the
+/// guess fell on the other side of a change.
+fn local_midnight(day: i64, offset: &dyn Fn(u64) -> i64) -> u64 {
+    let guess = (day * DAY).max(0) as u64;
+    let guess = (day * DAY - offset(guess)).max(0) as u64;
+    (day * DAY - offset(guess)).max(0) as u64
+}
+
+/// "YYYY-MM-DD" for a count of days since 1970-01-01.
+pub fn format_day(days: i64) -> String {
+    // Howard Hinnant's days-to-civil algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// "HH:MM:SS" for a time of day or a duration in seconds.
+fn format_clock(secs: i64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+fn hours(secs: u64) -> String {
+    format!("{:.2}", secs as f64 / 3600.0)
+}
+
+/// Quote a CSV field when it needs it.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn kind(state: &str) -> Option<Kind> {
+    match state::state_from_str(state) {
+        WindowState::Working => Some(Kind::Working),
+        WindowState::Asking | WindowState::Idle => Some(Kind::Waiting),
+        _ => None,
+    }
+}
+
+/// Counted stretches of one session, clipped to `[since, now]`.
+fn intervals(events: &[EventEntry], since: u64, now: u64) -> Vec<(Kind, u64, u64)> {
+    let events: Vec<&EventEntry> = events.iter().filter(|e| !e.is_milestone()).collect();
+    let mut out = Vec::new();
+    for (i, event) in events.iter().enumerate() {
+        let Some(kind) = kind(&event.state) else {
+            continue;
+        };
+        let next = events.get(i + 1).map_or(now, |e| e.ts);
+        let end = next.min(event.ts + MAX_GAP).min(now);
+        let start = event.ts.max(since);
+        if end > start {
+            out.push((kind, start, end));
+        }
+    }
+    out
+}
+
+/// Add stretches to per-day totals, splitting them at local midnight.
+fn add_to_days(
+    totals: &mut BTreeMap<Key, DayTotal>,
+    session: &str,
+    repo: &str,
+    intervals: &[(Kind, u64, u64)],
+    offset: &dyn Fn(u64) -> i64,
+) {
+    for &(kind, start, end) in intervals {
+        let mut t = start;
+        while t < end {
+            let day = local_day(t, offset);
+            let stop = end.min(local_midnight(day + 1, offset).max(t + 1));
+            let total = totals
+                .entry((day, session.to_string(), repo.to_string()))
+                .or_insert_with(|| DayTotal {
+                    start: t,
+                    ..DayTotal::default()
+                });
+            total.start = total.start.min(t);
+            match kind {
+                Kind::Working => total.working += stop - t,
+                Kind::Waiting => total.waiting += stop - t,
+                Kind::Attention => total.attention += stop - t,
+            }
+            t = stop;
+        }
+    }
+}
+
+/// Every event file in `dir`, by Claude session ID.
+pub fn read_sessions(dir: &Path) -> Vec<(String, Session)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(stem) = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .and_then(|f| f.strip_suffix(".jsonl"))
+        else {
+            continue;
+        };
+        // Archived files are "<name>.<session id>.jsonl"
+        let (name, id) = match stem.rsplit_once('.') {
+            Some((name, id)) => (Some(names::from_file_stem(name)), id.to_string()),
+            None => (None, stem.to_string()),
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let mut events: Vec<EventEntry> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        events.sort_by_key(|e| e.ts);
+        sessions.push((id, Session { name, events }));
+    }
+    sessions
+}
+
+/// Window name for each live Claude pane ID.
+fn live_names(client: &dyn TmuxClient) -> HashMap<String, String> {
+    if !client.has_session() {
+        return HashMap::new();
+    }
+    let windows = client.list_windows().unwrap_or_default();
+    client
+        .list_pane_commands()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|pane| {
+            let window = windows.iter().find(|w| w.index == pane.window_index)?;
+            Some((pane.pane_id, window.name.clone()))
+        })
+        .collect()
+}
+
+/// Repository a session ran in: the main checkout's directory name, so
+/// worktrees count towards their repository.
+fn repo_name(cwd: &str) -> String {
+    let dir = git::main_worktree(Path::new(cwd)).unwrap_or_else(|| cwd.to_string());
+    Path::new(&dir)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(dir)
+}
+
+fn totals(
+    sessions: Vec<(String, Session)>,
+    attention: &[attention::Interval],
+    names: &HashMap<String, String>,
+    since: u64,
+    now: u64,
+    offset: &dyn Fn(u64) -> i64,
+) -> BTreeMap<Key, DayTotal> {
+    let mut repos: HashMap<String, String> = HashMap::new();
+    let mut totals = BTreeMap::new();
+    for (id, session) in sessions {
+        let found = intervals(&session.events, since, now);
+        let milestones: Vec<&EventEntry> = session
+            .events
+            .iter()
+            .filter(|e| e.is_milestone() && (since..=now).contains(&e.ts))
+            .collect();
+        let Some(first) = session.events.first() else {
+            continue;
+        };
+        if found.is_empty() && milestones.is_empty() {
+            continue;
+        }
+        let repo = repos
+            .entry(first.cwd.clone())
+            .or_insert_with(|| repo_name(&first.cwd))
+            .clone();
+        let last_pane = session.events.last().map(|e| e.pane_id.as_str());
+        let name = session
+            .name
+            .or_else(|| names.get(last_pane?).cloned())
+            .unwrap_or_else(|| id.chars().take(8).collect());
+        add_to_days(&mut totals, &name, &repo, &found, offset);
+        for milestone in milestones {
+            let day = local_day(milestone.ts, offset);
+            totals
+                .entry((day, name.clone(), repo.clone()))
+                .or_insert_with(|| DayTotal {
+                    start: milestone.ts,
+                    ..DayTotal::default()
+                })
+                .milestones
+                .push(milestone.note.clone());
+        }
+    }
+    for interval in attention {
+        let (start, end) = (interval.start.max(since), interval.end.min(now));
+        if end <= start {
+            continue;
+        }
+        let repo = repos
+            .entry(interval.dir.clone())
+            .or_insert_with(|| repo_name(&interval.dir))
+            .clone();
+        let found = [(Kind::Attention, start, end)];
+        add_to_days(&mut totals, &interval.name, &repo, &found, offset);
+    }
+    totals
+}
+
+fn render_csv(totals: &BTreeMap<Key, DayTotal>) -> String {
+    let mut out = String::from(
+        "date,session,repo,working_hours,waiting_hours,total_hours,milestones,tag,attention_hours\n",
+    );
+    for ((day, session, repo), total) in totals {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            format_day(*day),
+            csv_field(session),
+            csv_field(repo),
+            hours(total.working),
+            hours(total.waiting),
+            hours(total.working + total.waiting),
+            csv_field(&total.milestones.join("; ")),
+            csv_field(names::tag(session).unwrap_or_default()),
+            hours(total.attention),
+        ));
+    }
+    out
+}
+
+/// Toggl Track's CSV import: one entry per session and day, starting when
+/// the session was first active.
+fn render_toggl(
+    totals: &BTreeMap<Key, DayTotal>,
+    email: &str,
+    offset: &dyn Fn(u64) -> i64,
+) -> String {
+    let mut out = String::from("Email,Project,Description,Start date,Start time,Duration,Tags\n");
+    for ((day, session, repo), total) in totals {
+        let start = (total.start as i64 + offset(total.start)).rem_euclid(DAY);
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            csv_field(email),
+            csv_field(repo),
+            csv_field(session),
+            format_day(*day),
+            format_clock(start),
+            format_clock((total.working + total.waiting) as i64),
+            csv_field(names::tag(session).unwrap_or_default()),
+        ));
+    }
+    out
+}
+
+fn git_email() -> Option<String> {
+    git::output(Path::new("."), &["config", "user.email"], &[0])
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty())
+}
+
+// ── Public API ──
+
+/// Print today's (or with `week`, this week's) time per session.
+pub fn run(week: bool, format: SheetFormat, email: Option<&str>) -> Result<(), String> {
+    let email = match format {
+        SheetFormat::Toggl => Some(
+            email
+                .map(String::from)
+                .or_else(git_email)
+                .ok_or("Toggl entries need an email: pass --email or set git config user.email")?,
+        ),
+        SheetFormat::Csv => None,
+    };
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let offset = local_offsets();
+    let today = local_day(now, &offset);
+    // 1970-01-01 was a Thursday; weeks start on Monday
+    let first_day = if week {
+        today - (today + 3).rem_euclid(7)
+    } else {
+        today
+    };
+    let since = local_midnight(first_day, &offset);
+
+    let mut sessions = Vec::new();
+    for dir in [state::history_dir(), paths::events_dir()]
+        .into_iter()
+        .flatten()
+    {
+        sessions.extend(read_sessions(&dir));
+    }
+    let totals = totals(
+        sessions,
+        &attention::all(),
+        &live_names(&tmux::Tmux),
+        since,
+        now,
+        &offset,
+    );
+
+    match email {
+        Some(email) => print!("{}", render_toggl(&totals, &email, &offset)),
+        None => print!("{}", render_csv(&totals)),
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(state: &str, ts: u64) -> EventEntry {
+        EventEntry {
+            state: state.to_string(),
+            cwd: "/code/api".to_string(),
+            pane_id: "%t1".to_string(),
+            ts,
+            hook: String::new(),
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(20_740), "2026-10-14");
+        assert_eq!(format_day(-1), "1969-12-31");
+    }
+
+    #[test]
+    fn test_intervals_count_working_and_waiting_up_to_gap() {
+        let events = [
+            event("working", 1000),
+            event("asking", 1600),
+            event("working", 1700),
+            event("idle", 2000),
+            // Quiet overnight: only MAX_GAP counts
+            event("working", 100_000),
+            event("done", 100_300),
+        ];
+        assert_eq!(
+            intervals(&events, 0, 200_000),
+            vec![
+                (Kind::Working, 1000, 1600),
+                (Kind::Waiting, 1600, 1700),
+                (Kind::Working, 1700, 2000),
+                (Kind::Waiting, 2000, 2000 + MAX_GAP),
+                (Kind::Working, 100_000, 100_300),
+            ]
+        );
+        // Clipped to the reporting window
+        assert_eq!(
+            intervals(&events, 1500, 1650),
+            vec![(Kind::Working, 1500, 1600), (Kind::Waiting, 1600, 1650)]
+        );
+    }
+
+    #[test]
+    fn test_days_split_at_local_midnight() {
+        let mut totals = BTreeMap::new();
+        // 23:30 to 00:30 in UTC+1
+        let midnight = (DAY * 10 - 3600) as u64;
+        let intervals = [(Kind::Working, midnight - 1800, midnight + 1800)];
+        add_to_days(&mut totals, "work/api", "api", &intervals, &|_| 3600);
+        let days: Vec<(i64, u64)> = totals.iter().map(|(k, t)| (k.0, t.working)).collect();
+        assert_eq!(days, [(9, 1800), (10, 1800)]);
+
+        let toggl = render_toggl(&totals, "me@example.com", &|_| 3600);
+        assert!(toggl.contains("me@example.com,api,work/api,1970-01-10,23:30:00,00:30:00,work\n"));
+        assert!(
+            render_csv(&totals).contains("1970-01-11,work/api,api,0.50,0.00,0.50,,work,0.00\n")
+        );
+    }
+
+    #[test]
+    fn test_days_split_with_the_offset_of_each_moment() {
+        let mut totals = BTreeMap::new();
+        // Clocks go forward an hour at 01:00 UTC on day 10: UTC+1, then UTC+2
+        let change = (DAY * 10 + 3600) as u64;
+        let offset = |ts: u64| if ts < change { 3600 } else { 7200 };
+        // 23:00 to 00:00 local on day 10 falls at 21:00 to 22:00 UTC
+        let midnight = (DAY * 11 - 7200) as u64;
+        let intervals = [(Kind::Working, midnight - 3600, midnight + 1800)];
+        add_to_days(&mut totals, "api", "api", &intervals, &offset);
+        let days: Vec<(i64, u64)> = totals.iter().map(|(k, t)| (k.0, t.working)).collect();
+        assert_eq!(days, [(10, 3600), (11, 1800)]);
+        assert_eq!(local_midnight(11, &offset), midnight);
+        assert_eq!(local_midnight(10, &offset), (DAY * 10 - 3600) as u64);
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("+0200\n"), 7200);
+        assert_eq!(parse_offset("-0330"), -(3 * 3600 + 30 * 60));
+        assert_eq!(parse_offset(""), 0);
+    }
+
+    #[test]
+    fn test_milestones_listed_per_day() {
+        let milestone = |note: &str, ts| EventEntry {
+            hook: state::MILESTONE_HOOK.to_string(),
+            note: note.to_string(),
+            ..event("working", ts)
+        };
+        let session = Session {
+            name: Some("api".to_string()),
+            events: vec![
+                event("working", 1000),
+                milestone("migrated db schema", 1300),
+                milestone("tests green, again", 1500),
+                event("idle", 1600),
+            ],
+        };
+        let totals = totals(
+            vec![("s1".to_string(), session)],
+            &[],
+            &HashMap::new(),
+            0,
+            2000,
+            &|_| 0,
+        );
+        let csv = render_csv(&totals);
+        // Milestones don't split the turn; it still ends at the idle event
+        assert!(
+            csv.contains(",0.17,0.11,0.28,\"migrated db schema; tests green, again\",,0.00\n"),
+            "{csv}"
+        );
+    }
+
+    #[test]
+    fn test_attention_reported_apart_from_working() {
+        let session = Session {
+            name: Some("api".to_string()),
+            events: vec![event("working", 1000), event("idle", 2800)],
+        };
+        let watched = |name: &str, start, end| attention::Interval {
+            name: name.to_string(),
+            dir: format!("/code/{name}"),
+            start,
+            end,
+        };
+        // Watching api for part of its turn, then web from before the window
+        let attention = [watched("api", 1900, 2800), watched("web", 0, 1000)];
+        let totals = totals(
+            vec![("s1".to_string(), session)],
+            &attention,
+            &HashMap::new(),
+            500,
+            2800,
+            &|_| 0,
+        );
+        let csv = render_csv(&totals);
+        assert!(
+            csv.contains("1970-01-01,api,api,0.50,0.00,0.50,,,0.25\n"),
+            "{csv}"
+        );
+        assert!(
+            csv.contains("1970-01-01,web,web,0.00,0.00,0.00,,,0.14\n"),
+            "{csv}"
+        );
+    }
+}
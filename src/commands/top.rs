@@ -0,0 +1,430 @@
+// ── cove top: resource view of every session ──
+//
+// A full-screen table of the cove session's windows with their state, the
+// CPU and memory of each Claude pane's process tree, tokens spent, uptime and
+// time since the last hook event. Keys are the sidebar's (j/k, Enter, q) plus
+// s to cycle the sort column, z to pause/resume a session's processes and x
+// to kill it (restorable with `cove undo`).
+
+use std::io::{self, stdout};
+
+use crossterm::cursor;
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Widget;
+
+use crate::colors::{self, ANSI_OVERLAY, ANSI_RESET};
+use crate::commands::kill;
+use crate::procs::{self, Usage};
+use crate::sidebar::event::{self, Action};
+use crate::sidebar::format::{format_age, format_tokens};
+use crate::sidebar::state::{self, StateDetector, WindowState};
+use crate::tmux::{self, Tmux, TmuxClient};
+use crate::tokens::TokenCounter;
+
+// ── Types ──
+
+struct Row {
+    index: u32,
+    name: String,
+    state: WindowState,
+    /// Claude pane's process tree, if it's still running.
+    usage: Option<Usage>,
+    tokens: Option<u64>,
+    /// Seconds since the last hook event.
+    idle: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortBy {
+    Cpu,
+    Memory,
+    Tokens,
+}
+
+impl SortBy {
+    fn next(self) -> Self {
+        match self {
+            SortBy::Cpu => SortBy::Memory,
+            SortBy::Memory => SortBy::Tokens,
+            SortBy::Tokens => SortBy::Cpu,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortBy::Cpu => "cpu",
+            SortBy::Memory => "memory",
+            SortBy::Tokens => "tokens",
+        }
+    }
+}
+
+struct TopWidget<'a> {
+    rows: &'a [Row],
+    selected: usize,
+    sort: SortBy,
+    /// Result of the last kill/pause, shown in the footer.
+    message: Option<&'a str>,
+}
+
+// ── Constants ──
+
+/// Ticks (~100ms each) between samples; `ps` CPU figures need time to settle.
+const SAMPLE_EVERY: u64 = 10;
+
+const COLUMNS: &[(&str, usize)] = &[
+    ("STATE", 11),
+    ("CPU%", 7),
+    ("MEM", 7),
+    ("TOKENS", 8),
+    ("UP", 5),
+    ("LAST", 5),
+];
+
+const KEYS: &str =
+    " j/k move \u{b7} enter switch \u{b7} s sort \u{b7} z pause \u{b7} x kill \u{b7} q quit";
+
+// ── Helpers ──
+
+/// `kb` kilobytes as "512K", "340M" or "1.2G" (also used by `cove du`).
+pub fn format_memory(kb: u64) -> String {
+    match kb {
+        k if k < 1024 => format!("{k}K"),
+        k if k < 1024 * 1024 => format!("{}M", k / 1024),
+        k => format!("{:.1}G", k as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+/// Heaviest first; sessions without a figure sink to the bottom.
+fn sort_rows(rows: &mut [Row], sort: SortBy) {
+    rows.sort_by(|a, b| {
+        let key = |r: &Row| match sort {
+            SortBy::Cpu => r.usage.map_or(-1.0, |u| f64::from(u.cpu)),
+            SortBy::Memory => r.usage.map_or(-1.0, |u| u.rss_kb as f64),
+            SortBy::Tokens => r.tokens.map_or(-1.0, |t| t as f64),
+        };
+        key(b)
+            .total_cmp(&key(a))
+            .then_with(|| a.index.cmp(&b.index))
+    });
+}
+
+fn state_text(row: &Row) -> (&'static str, Color) {
+    if row.usage.is_some_and(|u| u.stopped) {
+        return ("paused", colors::RED);
+    }
+    let color = match row.state {
+        WindowState::Working => colors::LAVENDER,
+        WindowState::Idle => colors::GREEN,
+        WindowState::Asking | WindowState::Queued => colors::BLUE,
+        WindowState::Fresh | WindowState::Done => colors::OVERLAY,
+    };
+    (row.state.label(), color)
+}
+
+fn sample(
+    detector: &mut StateDetector,
+    counter: &mut TokenCounter,
+    procs: &[procs::Proc],
+) -> Vec<Row> {
+    let windows: Vec<_> = tmux::list_windows()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|w| w.managed)
+        .collect();
+    let states = detector.detect(&Tmux, &windows);
+    let pids = Tmux.claude_pane_pids().unwrap_or_default();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    windows
+        .iter()
+        .map(|w| {
+            let tokens = detector
+                .pane_id(w.index)
+                .and_then(state::session_for_pane)
+                .and_then(|id| counter.count(&id));
+            Row {
+                index: w.index,
+                name: w.name.clone(),
+                state: states.get(&w.index).copied().unwrap_or(WindowState::Fresh),
+                usage: pids.get(&w.index).and_then(|pid| procs::usage(procs, *pid)),
+                tokens,
+                idle: detector
+                    .last_event_ts(w.index)
+                    .map(|ts| now.saturating_sub(ts)),
+            }
+        })
+        .collect()
+}
+
+/// Pause or resume a session; returns the footer message.
+fn toggle_pause(row: &Row) -> String {
+    let Some(pid) = Tmux
+        .claude_pane_pids()
+        .ok()
+        .and_then(|p| p.get(&row.index).copied())
+    else {
+        return format!("{} has no running process", row.name);
+    };
+    let pause = !row.usage.is_some_and(|u| u.stopped);
+    match procs::set_paused(&procs::snapshot(), pid, pause) {
+        Ok(()) if pause => format!("paused {} (z resumes)", row.name),
+        Ok(()) => format!("resumed {}", row.name),
+        Err(e) => e,
+    }
+}
+
+fn run_loop() -> Result<(), String> {
+    let backend = CrosstermBackend::new(io::stdout());
+    let mut terminal = Terminal::new(backend).map_err(|e| format!("terminal: {e}"))?;
+
+    let mut detector = StateDetector::new();
+    let mut counter = TokenCounter::new();
+    let mut sort = SortBy::Cpu;
+    let mut rows = Vec::new();
+    let mut selected = 0;
+    let mut message: Option<String> = None;
+    let mut tick: u64 = 0;
+
+    loop {
+        if tick % SAMPLE_EVERY == 0 {
+            let keep = rows.get(selected).map(|r: &Row| r.name.clone());
+            rows = sample(&mut detector, &mut counter, &procs::snapshot());
+            sort_rows(&mut rows, sort);
+            selected = keep
+                .and_then(|name| rows.iter().position(|r| r.name == name))
+                .unwrap_or(selected)
+                .min(rows.len().saturating_sub(1));
+        }
+
+        terminal
+            .draw(|frame| {
+                let widget = TopWidget {
+                    rows: &rows,
+                    selected,
+                    sort,
+                    message: message.as_deref(),
+                };
+                frame.render_widget(widget, frame.area());
+            })
+            .map_err(|e| format!("render: {e}"))?;
+
+        tick += 1;
+        for action in event::poll() {
+            match action {
+                Action::Up => selected = selected.saturating_sub(1),
+                Action::Down if selected + 1 < rows.len() => selected += 1,
+                Action::Select => {
+                    if let Some(row) = rows.get(selected) {
+                        let _ = tmux::select_window(row.index);
+                    }
+                }
+                Action::Sort => {
+                    sort = sort.next();
+                    sort_rows(&mut rows, sort);
+                }
+                Action::Pause => {
+                    if let Some(row) = rows.get(selected) {
+                        message = Some(toggle_pause(row));
+                        tick = 0;
+                    }
+                }
+                Action::Kill => {
+                    if let Some(row) = rows.get(selected) {
+                        message = Some(match kill::kill_quietly(&row.name) {
+                            Ok(()) => format!("killed {} (cove undo restores it)", row.name),
+                            Err(e) => e,
+                        });
+                        tick = 0;
+                    }
+                }
+                Action::Quit | Action::Back => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+// ── Rendering ──
+
+impl Widget for TopWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let dim = Style::default().fg(colors::OVERLAY);
+        let name_width = self
+            .rows
+            .iter()
+            .map(|r| r.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(8);
+
+        let cpu: f32 = self
+            .rows
+            .iter()
+            .filter_map(|r| r.usage)
+            .map(|u| u.cpu)
+            .sum();
+        let mem: u64 = self
+            .rows
+            .iter()
+            .filter_map(|r| r.usage)
+            .map(|u| u.rss_kb)
+            .sum();
+        let plural = if self.rows.len() == 1 { "" } else { "s" };
+        let header = Line::from(vec![
+            Span::styled(
+                " cove top",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(
+                    "  {} session{plural} \u{b7} {cpu:.0}% cpu \u{b7} {} \u{b7} sorted by {}",
+                    self.rows.len(),
+                    format_memory(mem),
+                    self.sort.label()
+                ),
+                dim,
+            ),
+        ]);
+        buf.set_line(area.x, area.y, &header, area.width);
+
+        let mut columns = format!("   {:<name_width$}", "NAME");
+        for (title, width) in COLUMNS {
+            columns.push_str(&format!(" {title:>width$}"));
+        }
+        let columns = Line::from(Span::styled(columns, Style::default().fg(colors::BLUE)));
+        buf.set_line(area.x, area.y + 2, &columns, area.width);
+
+        let body = area.height.saturating_sub(5) as usize;
+        let first = self.selected.saturating_sub(body.saturating_sub(1));
+        for (offset, row) in self.rows.iter().skip(first).take(body).enumerate() {
+            let y = area.y + 3 + offset as u16;
+            let is_selected = first + offset == self.selected;
+            let (state, state_color) = state_text(row);
+            let dash = || "-".to_string();
+            let cells = [
+                row.usage.map_or_else(dash, |u| format!("{:.1}", u.cpu)),
+                row.usage.map_or_else(dash, |u| format_memory(u.rss_kb)),
+                row.tokens.map_or_else(dash, format_tokens),
+                row.usage.map_or_else(dash, |u| format_age(u.elapsed)),
+                row.idle.map_or_else(dash, format_age),
+            ];
+
+            let (bullet, name_style) = if is_selected {
+                ("\u{276f}", Style::default().fg(Color::White))
+            } else {
+                (" ", dim)
+            };
+            let mut spans = vec![
+                Span::styled(format!(" {bullet} "), name_style),
+                Span::styled(format!("{:<name_width$}", row.name), name_style),
+                Span::styled(
+                    format!(" {state:>width$}", width = COLUMNS[0].1),
+                    Style::default().fg(state_color),
+                ),
+            ];
+            for (cell, (_, width)) in cells.iter().zip(&COLUMNS[1..]) {
+                spans.push(Span::styled(format!(" {cell:>width$}"), name_style));
+            }
+            buf.set_line(area.x, y, &Line::from(spans), area.width);
+        }
+
+        if self.rows.is_empty() {
+            let empty = Line::from(Span::styled("   no sessions", dim));
+            buf.set_line(area.x, area.y + 3, &empty, area.width);
+        }
+
+        let footer_y = area.y + area.height.saturating_sub(1);
+        if let Some(message) = self.message {
+            let line = Line::from(Span::styled(
+                format!(" {message}"),
+                Style::default().fg(colors::LAVENDER),
+            ));
+            buf.set_line(area.x, footer_y.saturating_sub(1), &line, area.width);
+        }
+        buf.set_line(
+            area.x,
+            footer_y,
+            &Line::from(Span::styled(KEYS, dim)),
+            area.width,
+        );
+    }
+}
+
+// ── Public API ──
+
+pub fn run() -> Result<(), String> {
+    if !Tmux.has_session() {
+        println!("{ANSI_OVERLAY}No active cove session.{ANSI_RESET}");
+        return Err(String::new());
+    }
+
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen, cursor::Hide).map_err(|e| format!("terminal: {e}"))?;
+    terminal::enable_raw_mode().map_err(|e| format!("terminal: {e}"))?;
+
+    let result = run_loop();
+
+    terminal::disable_raw_mode().ok();
+    execute!(stdout, cursor::Show, LeaveAlternateScreen).ok();
+
+    result
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(index: u32, cpu: Option<f32>, tokens: Option<u64>) -> Row {
+        Row {
+            index,
+            name: format!("s{index}"),
+            state: WindowState::Working,
+            usage: cpu.map(|cpu| Usage {
+                cpu,
+                rss_kb: (cpu * 1000.0) as u64,
+                ..Usage::default()
+            }),
+            tokens,
+            idle: None,
+        }
+    }
+
+    #[test]
+    fn test_sort_rows_heaviest_first() {
+        let mut rows = vec![
+            row(1, Some(2.0), Some(900)),
+            row(2, None, None),
+            row(3, Some(40.0), Some(10)),
+            row(4, Some(2.0), None),
+        ];
+        sort_rows(&mut rows, SortBy::Cpu);
+        let order: Vec<u32> = rows.iter().map(|r| r.index).collect();
+        assert_eq!(order, [3, 1, 4, 2]);
+
+        sort_rows(&mut rows, SortBy::Tokens);
+        let order: Vec<u32> = rows.iter().map(|r| r.index).collect();
+        assert_eq!(order, [1, 3, 2, 4]);
+    }
+
+    #[test]
+    fn test_format_memory() {
+        assert_eq!(format_memory(512), "512K");
+        assert_eq!(format_memory(300_000), "292M");
+        assert_eq!(format_memory(3 * 1024 * 1024), "3.0G");
+    }
+}
@@ -0,0 +1,105 @@
+use crate::colors::*;
+use crate::commands::start;
+use crate::sidebar::format::format_age;
+use crate::tmux::{self, Tmux, TmuxClient};
+use crate::trash::{self, TrashEntry};
+use crate::{clips, paths, profiles, say};
+
+// ── Helpers ──
+
+/// Command that reopens Claude, resuming the killed conversation if known.
+fn resume_command(claude_session: Option<&str>) -> String {
+    match claude_session {
+        Some(id) => format!("{} --resume {}", start::claude(), start::shell_quote(id)),
+        None => start::claude().to_string(),
+    }
+}
+
+fn grace_label() -> String {
+    format_age(trash::UNDO_GRACE_SECS)
+}
+
+fn list(entries: &[TrashEntry]) {
+    if entries.is_empty() {
+        println!(
+            "{ANSI_OVERLAY}No sessions killed in the last {}.{ANSI_RESET}",
+            grace_label()
+        );
+        return;
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    for entry in entries {
+        let age = format_age(now.saturating_sub(entry.killed_at));
+        println!(
+            "  {ANSI_PEACH}{}{ANSI_RESET}  {ANSI_SUBTEXT}{}{ANSI_RESET}  {ANSI_OVERLAY}{age} ago{ANSI_RESET}",
+            entry.name,
+            paths::tildify(&entry.dir)
+        );
+        if let Some(capture) = trash::capture_path(&entry.name) {
+            println!(
+                "      {ANSI_OVERLAY}last screen: {}{ANSI_RESET}",
+                paths::tildify(&capture.to_string_lossy())
+            );
+        }
+    }
+}
+
+// ── Public API ──
+
+/// Recreate the most recently killed session (or `name`), resuming its Claude
+/// conversation. With `list_only`, show what can be restored instead.
+pub fn run(name: Option<&str>, list_only: bool) -> Result<(), String> {
+    let entries = trash::entries();
+    if list_only {
+        list(&entries);
+        return Ok(());
+    }
+
+    let entry = match name {
+        Some(name) => entries.into_iter().find(|e| e.name == name).ok_or(format!(
+            "No session named '{ANSI_PEACH}{name}{ANSI_RESET}' was killed in the last {}.",
+            grace_label()
+        ))?,
+        None => entries.into_iter().next().ok_or(format!(
+            "Nothing to undo: no session was killed in the last {}.",
+            grace_label()
+        ))?,
+    };
+
+    if Tmux.has_session() && Tmux.list_window_names()?.contains(&entry.name) {
+        return Err(format!(
+            "Session '{ANSI_PEACH}{}{ANSI_RESET}' already exists.",
+            entry.name
+        ));
+    }
+
+    let agent_cmd =
+        profiles::command_for(&entry.dir, &resume_command(entry.claude_session.as_deref()));
+    start::create_window(&entry.name, &entry.dir, &agent_cmd)?;
+    for clip in &entry.clips {
+        clips::add(&entry.name, clip)?;
+    }
+    trash::remove(&entry.name);
+    say!("Restored: {ANSI_PEACH}{}{ANSI_RESET}", entry.name);
+
+    if !tmux::is_inside_tmux() {
+        Tmux.attach()?;
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_command() {
+        assert_eq!(resume_command(None), "claude");
+        assert_eq!(resume_command(Some("abc-123")), "claude --resume 'abc-123'");
+    }
+}
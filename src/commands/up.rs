@@ -0,0 +1,276 @@
+// ── Declarative session creation from a manifest ──
+//
+// `cove up -f sessions.yaml` creates every session listed in the manifest that
+// doesn't exist yet, and with `--prune` kills cove windows that aren't listed.
+//
+//   sessions:
+//     - name: api
+//       dir: ~/code/api
+//       prompt: Fix the failing integration tests
+//     - name: docs
+//       dir: ~/code/api
+//       worktree: docs-refresh
+//       template: review
+//       vars: {area: docs}
+//
+// `worktree` runs the session on that branch in a worktree of its own, as
+// `cove <name> --worktree` does. The first prompt is `prompt` or the named
+// template (see templates.rs) filled from `vars`. It's typed into Claude once
+// it's ready rather than put on its command line, so a respawned pane
+// doesn't send it again.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+use serde::Deserialize;
+
+use crate::colors::*;
+use crate::commands::start;
+use crate::{meta, names, paths, profiles, say, templates, tmux, worktrees};
+
+// ── Types ──
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    sessions: Vec<SessionSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SessionSpec {
+    name: String,
+    /// Working directory. `~` expands to $HOME; relative paths resolve
+    /// against the manifest's directory. Defaults to the manifest's directory.
+    #[serde(default)]
+    dir: Option<String>,
+    /// First prompt sent to Claude once it's ready.
+    #[serde(default)]
+    prompt: Option<String>,
+    /// Template the first prompt is rendered from, instead of `prompt`.
+    #[serde(default)]
+    template: Option<String>,
+    /// Values for the template's placeholders.
+    #[serde(default)]
+    vars: HashMap<String, String>,
+    /// Branch to run the session on, in a worktree of its own.
+    #[serde(default)]
+    worktree: Option<String>,
+}
+
+// ── Helpers ──
+
+fn parse_manifest(content: &str) -> Result<Manifest, String> {
+    let manifest: Manifest =
+        serde_yaml::from_str(content).map_err(|e| format!("parse manifest: {e}"))?;
+
+    let mut seen: Vec<&str> = Vec::new();
+    for spec in &manifest.sessions {
+        names::validate(&spec.name).map_err(|e| format!("manifest: {e}"))?;
+        if seen.contains(&spec.name.as_str()) {
+            return Err(format!("manifest: duplicate session '{}'", spec.name));
+        }
+        if spec.prompt.is_some() && spec.template.is_some() {
+            return Err(format!(
+                "manifest: session '{}' has both a prompt and a template",
+                spec.name
+            ));
+        }
+        seen.push(&spec.name);
+    }
+    Ok(manifest)
+}
+
+fn resolve_spec_dir(dir: Option<&str>, base: &Path) -> PathBuf {
+    let Some(dir) = dir else {
+        return base.to_path_buf();
+    };
+    let path = paths::expand_tilde(dir);
+    if path.is_absolute() {
+        path
+    } else {
+        base.join(path)
+    }
+}
+
+/// The first prompt of `spec`, if it has one.
+fn first_prompt(spec: &SessionSpec) -> Result<Option<String>, String> {
+    match &spec.template {
+        Some(template) => templates::render(&templates::load(template)?, &spec.vars)
+            .map(Some)
+            .map_err(|e| format!("{}: {e}", spec.name)),
+        None => Ok(spec.prompt.clone()),
+    }
+}
+
+/// Create the session `spec` describes in `dir`, returning the thread that
+/// sends its first prompt.
+fn create(spec: &SessionSpec, dir: &str) -> Result<Option<JoinHandle<()>>, String> {
+    let prompt = first_prompt(spec)?;
+    let Some(branch) = &spec.worktree else {
+        start::create_window(
+            &spec.name,
+            dir,
+            &profiles::command_for(dir, start::claude()),
+        )?;
+        return Ok(prompt.and_then(|p| start::send_first_prompt(&spec.name, &p)));
+    };
+    let checkout =
+        worktrees::checkout(Path::new(dir), branch).map_err(|e| format!("{}: {e}", spec.name))?;
+    let dir = &checkout.path;
+    if let Err(e) = start::create_window(
+        &spec.name,
+        dir,
+        &profiles::command_for(dir, start::claude()),
+    ) {
+        worktrees::discard(&checkout);
+        return Err(e);
+    }
+    // A worktree cove keeps under its root is the session's, to remove on kill
+    if worktrees::is_managed(dir) {
+        meta::update(&spec.name, |m| m.worktree = Some(dir.clone()))?;
+    }
+    Ok(prompt.and_then(|p| start::send_first_prompt(&spec.name, &p)))
+}
+
+// ── Public API ──
+
+pub fn run(file: &str, prune: bool) -> Result<(), String> {
+    let content = fs::read_to_string(file).map_err(|e| format!("read manifest '{file}': {e}"))?;
+    let manifest = parse_manifest(&content)?;
+
+    let base = fs::canonicalize(file)
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let existing = if tmux::has_session() {
+        tmux::list_window_names()?
+    } else {
+        Vec::new()
+    };
+
+    let mut created = 0;
+    let mut sending = Vec::new();
+    for spec in &manifest.sessions {
+        if existing.contains(&spec.name) {
+            say!(
+                "  {ANSI_OVERLAY}exists{ANSI_RESET}   {ANSI_WHITE}{}{ANSI_RESET}",
+                spec.name
+            );
+            continue;
+        }
+
+        let dir = resolve_spec_dir(spec.dir.as_deref(), &base);
+        let dir = fs::canonicalize(&dir)
+            .map_err(|e| format!("{}: invalid directory '{}': {e}", spec.name, dir.display()))?
+            .to_string_lossy()
+            .to_string();
+
+        sending.extend(create(spec, &dir)?);
+        created += 1;
+        say!(
+            "  {ANSI_PEACH}created{ANSI_RESET}  {ANSI_WHITE}{}{ANSI_RESET}  {ANSI_SUBTEXT}{dir}{ANSI_RESET}",
+            spec.name
+        );
+    }
+
+    // Prompts go out once each Claude is ready, all sessions at once
+    for handle in sending {
+        let _ = handle.join();
+    }
+
+    let mut pruned = 0;
+    if prune {
+        for name in &existing {
+            if manifest.sessions.iter().any(|s| &s.name == name) {
+                continue;
+            }
+            tmux::kill_window(name)?;
+            pruned += 1;
+            say!("  {ANSI_PEACH}pruned{ANSI_RESET}   {ANSI_WHITE}{name}{ANSI_RESET}");
+        }
+    }
+
+    say!(
+        "{created} created, {} unchanged, {pruned} pruned.",
+        manifest.sessions.len() - created
+    );
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = parse_manifest(
+            "sessions:\n  - name: api\n    dir: ~/code/api\n    prompt: fix tests\n  - name: web\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.sessions.len(), 2);
+        assert_eq!(manifest.sessions[0].dir.as_deref(), Some("~/code/api"));
+        assert_eq!(manifest.sessions[0].prompt.as_deref(), Some("fix tests"));
+        assert!(manifest.sessions[1].dir.is_none());
+    }
+
+    #[test]
+    fn test_parse_manifest_template_and_worktree() {
+        let manifest = parse_manifest(
+            "sessions:\n  - name: docs\n    worktree: docs-refresh\n    template: review\n    vars: {area: docs}\n",
+        )
+        .unwrap();
+        let spec = &manifest.sessions[0];
+        assert_eq!(spec.worktree.as_deref(), Some("docs-refresh"));
+        assert_eq!(spec.template.as_deref(), Some("review"));
+        assert_eq!(spec.vars["area"], "docs");
+        let manifest = parse_manifest("sessions:\n  - name: api\n    prompt: fix tests\n").unwrap();
+        assert_eq!(
+            first_prompt(&manifest.sessions[0]).unwrap().as_deref(),
+            Some("fix tests")
+        );
+
+        let err =
+            parse_manifest("sessions:\n  - name: api\n    prompt: hi\n    template: review\n")
+                .err()
+                .unwrap();
+        assert!(err.contains("both a prompt and a template"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_manifest_rejects_duplicates() {
+        let err = parse_manifest("sessions:\n  - name: api\n  - name: api\n")
+            .err()
+            .unwrap();
+        assert!(err.contains("duplicate session 'api'"));
+    }
+
+    #[test]
+    fn test_parse_manifest_rejects_untargetable_names() {
+        let err = parse_manifest("sessions:\n  - name: my.app\n")
+            .err()
+            .unwrap();
+        assert!(err.contains("Try 'my-app'"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_manifest_rejects_unknown_fields() {
+        assert!(parse_manifest("sessions:\n  - name: api\n    dri: /tmp\n").is_err());
+    }
+
+    #[test]
+    fn test_resolve_spec_dir() {
+        let base = Path::new("/manifests");
+        assert_eq!(resolve_spec_dir(None, base), PathBuf::from("/manifests"));
+        assert_eq!(resolve_spec_dir(Some("/abs"), base), PathBuf::from("/abs"));
+        assert_eq!(
+            resolve_spec_dir(Some("rel/dir"), base),
+            PathBuf::from("/manifests/rel/dir")
+        );
+    }
+}
@@ -0,0 +1,522 @@
+// ── cove verify-hooks ──
+//
+// Hook by hook, is cove hearing from Claude? Without flags it lists each hook
+// cove installs and the settings file it's installed in (the user settings,
+// then the project of the current directory).
+//
+// `--fire-test` checks that events actually arrive: every installed hook
+// command is run the way Claude runs it, with a payload on stdin, from a
+// dummy pane that isn't any session's, and $COVE_HOOK_PROBE set to a fresh
+// probe id. Hooks then only append their name to `<events>/<probe>.probe`
+// (see hook.rs), and a hook counts as working once its line is there. A hook
+// that ran fine but left no line is writing somewhere else (an older cove, or
+// an `--events-dir` the sidebar doesn't read). A cove from before the probe
+// would run its full handler instead, which can deliver queued prompts to
+// real panes, so the test refuses to fire hooks that run a cove reporting
+// another version than this one.
+//
+// With `--claude`, Claude fires them instead: `claude -p` is asked to run a
+// command and write a file in a scratch directory, which takes it through the
+// prompt, tool, command, file-edit, stop and session-end hooks. The ask hooks
+// need an interactive session, so they're left out. The probe file and the
+// scratch directory are removed afterwards.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+
+use crate::colors::*;
+use crate::commands::hook::{self, PROBE_ENV};
+use crate::commands::init::{self, HOOK_ENTRIES};
+use crate::paths;
+
+// ── Types ──
+
+/// How one of cove's hooks fared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    /// In no settings file Claude reads here.
+    Missing,
+    /// Installed in this settings file; not fired.
+    Installed(String),
+    /// Its event arrived.
+    Fired,
+    /// No event arrived, and why if known.
+    Silent(String),
+    /// `claude -p` can't fire it.
+    Skipped,
+}
+
+/// A line of the probe file: hook `hook` fired for `tool`.
+#[derive(Debug, Deserialize)]
+struct Probed {
+    hook: String,
+    tool: String,
+}
+
+// ── Constants ──
+
+/// How long one hook command, or `--claude`'s whole run, may take by default.
+const TIMEOUT_SECS: u64 = 10;
+const CLAUDE_TIMEOUT_SECS: u64 = 180;
+
+/// How long to keep looking for events after Claude exits (SessionEnd
+/// hooks can still be running).
+const SETTLE: Duration = Duration::from_secs(2);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Hooks `claude -p` never fires: they wait on a question or plan mode.
+const INTERACTIVE: &[&str] = &["hook ask", "hook ask-done"];
+
+// ── Helpers ──
+
+fn mark(glyph: &str, label: &str, detail: &str) {
+    println!("  {glyph}{ANSI_RESET} {label} {ANSI_OVERLAY}{detail}{ANSI_RESET}");
+}
+
+fn probe_id() -> String {
+    let ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    format!("verify-{}-{ms}", std::process::id())
+}
+
+/// A tool the hook with `matcher` fires for.
+fn sample_tool(matcher: &str) -> &str {
+    match matcher {
+        "*" => "Read",
+        m => m.split('|').next().unwrap_or(m),
+    }
+}
+
+/// What Claude writes to the stdin of a `hook_type` hook.
+fn payload(probe: &str, hook_type: &str, matcher: &str, cwd: &Path) -> String {
+    serde_json::json!({
+        "session_id": probe,
+        "cwd": cwd.to_string_lossy(),
+        "hook_event_name": hook_type,
+        "tool_name": sample_tool(matcher),
+        "tool_input": {},
+        "tool_response": {},
+        "prompt": "cove verify-hooks",
+    })
+    .to_string()
+}
+
+fn read_probe(path: &Path) -> Vec<Probed> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// True if hook `cmd` with `matcher` left a line in `probed`.
+fn reported(probed: &[Probed], matcher: &str, cmd: &str) -> bool {
+    let event = cmd.strip_prefix("hook ").unwrap_or(cmd);
+    probed
+        .iter()
+        .any(|p| p.hook == event && (matcher == "*" || matcher.split('|').any(|m| m == p.tool)))
+}
+
+/// Run `command`, feeding it `stdin`, killing it after `timeout`. Err says
+/// why it failed: its last line of stderr, if any.
+fn run_with_timeout(command: &mut Command, stdin: &str, timeout: Duration) -> Result<(), String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("couldn't run: {e}"))?;
+    if let Some(mut pipe) = child.stdin.take() {
+        // A hook that doesn't read its stdin is fine
+        let _ = pipe.write_all(stdin.as_bytes());
+    }
+    // Read on another thread so a chatty command can't fill the pipe and hang
+    let mut stderr = child.stderr.take().ok_or("no stderr")?;
+    let reader = thread::spawn(move || {
+        let mut out = String::new();
+        let _ = stderr.read_to_string(&mut out);
+        out
+    });
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("still running after {}s", timeout.as_secs()));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    let err = reader.join().unwrap_or_default();
+    if status.success() {
+        return Ok(());
+    }
+    Err(err
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| status.to_string()))
+}
+
+/// The outcome of a fired hook, given what its run came to.
+fn outcome(probed: &[Probed], matcher: &str, cmd: &str, ran: Result<(), String>) -> Outcome {
+    if reported(probed, matcher, cmd) {
+        return Outcome::Fired;
+    }
+    Outcome::Silent(ran.err().unwrap_or_else(|| {
+        let events = paths::events_dir().unwrap_or_default();
+        format!(
+            "ran, but no event reached {}",
+            paths::tildify(&events.to_string_lossy())
+        )
+    }))
+}
+
+/// The cove binary a hook command runs, as written in the command.
+fn command_bin(command: &str) -> &str {
+    command.split(" hook ").next().unwrap_or(command)
+}
+
+/// Binaries the `installed` hooks run that don't report `ours` for
+/// `--version`, with what they did report.
+fn stale_bins(installed: &[Option<String>], ours: &str) -> Vec<(String, String)> {
+    let mut bins: Vec<&str> = Vec::new();
+    for bin in installed.iter().flatten().map(|c| command_bin(c)) {
+        if !bins.contains(&bin) {
+            bins.push(bin);
+        }
+    }
+    bins.into_iter()
+        .filter_map(|bin| {
+            // Through the shell, so quoting and $PATH work as in the hook
+            let out = Command::new("sh")
+                .args(["-c", &format!("{bin} --version")])
+                .stdin(Stdio::null())
+                .output();
+            let version = match out {
+                Ok(out) if out.status.success() => {
+                    String::from_utf8_lossy(&out.stdout).trim().to_string()
+                }
+                _ => "no version".to_string(),
+            };
+            (version != ours).then(|| (bin.to_string(), version))
+        })
+        .collect()
+}
+
+/// Run every installed hook command as Claude would.
+fn fire_each(
+    installed: &[Option<String>],
+    probe: &str,
+    probe_path: &Path,
+    scratch: &Path,
+    timeout: Duration,
+) -> Vec<Outcome> {
+    HOOK_ENTRIES
+        .iter()
+        .zip(installed)
+        .map(|(&(hook_type, matcher, cmd), command)| {
+            let Some(command) = command else {
+                return Outcome::Missing;
+            };
+            let mut sh = Command::new("sh");
+            sh.args(["-c", command])
+                .current_dir(scratch)
+                .env(PROBE_ENV, probe)
+                .env_remove("TMUX_PANE")
+                .env("COVE_PANE", "verify-hooks");
+            let stdin = payload(probe, hook_type, matcher, scratch);
+            let ran = run_with_timeout(&mut sh, &stdin, timeout);
+            outcome(&read_probe(probe_path), matcher, cmd, ran)
+        })
+        .collect()
+}
+
+/// Have `claude -p` fire the hooks it can, from the current directory so
+/// project hooks run too, touching only `scratch`.
+fn fire_with_claude(
+    installed: &[Option<String>],
+    probe: &str,
+    probe_path: &Path,
+    scratch: &Path,
+    timeout: Duration,
+) -> Vec<Outcome> {
+    let scratch_dir = scratch.to_string_lossy();
+    let prompt = format!(
+        "This is a test of your hooks. Use the Bash tool to run `true`, then use the \
+         Write tool to create {scratch_dir}/notes.txt containing \"ok\", then reply \"done\"."
+    );
+    let mut claude = Command::new("claude");
+    claude
+        .args(["-p", &prompt, "--add-dir", &scratch_dir])
+        .args(["--allowedTools", "Bash(true)", "Write"])
+        .env(PROBE_ENV, probe);
+    let ran = run_with_timeout(&mut claude, "", timeout);
+
+    let expected: Vec<(&str, &str)> = HOOK_ENTRIES
+        .iter()
+        .zip(installed)
+        .filter(|(_, command)| command.is_some())
+        .map(|(&(_, matcher, cmd), _)| (matcher, cmd))
+        .filter(|(_, cmd)| !INTERACTIVE.contains(cmd))
+        .collect();
+    let settle = Instant::now() + SETTLE;
+    let mut probed = read_probe(probe_path);
+    while Instant::now() < settle && !expected.iter().all(|(m, c)| reported(&probed, m, c)) {
+        thread::sleep(POLL_INTERVAL);
+        probed = read_probe(probe_path);
+    }
+
+    HOOK_ENTRIES
+        .iter()
+        .zip(installed)
+        .map(|(&(_, matcher, cmd), command)| match command {
+            None => Outcome::Missing,
+            Some(_) if INTERACTIVE.contains(&cmd) => Outcome::Skipped,
+            Some(_) => match &ran {
+                Err(e) => outcome(&probed, matcher, cmd, Err(format!("claude: {e}"))),
+                Ok(()) if reported(&probed, matcher, cmd) => Outcome::Fired,
+                Ok(()) => Outcome::Silent("Claude didn't fire it".to_string()),
+            },
+        })
+        .collect()
+}
+
+/// Print a line per hook. Err counts the hooks that aren't working.
+fn report(outcomes: &[Outcome]) -> Result<(), String> {
+    for (&(hook_type, matcher, cmd), outcome) in HOOK_ENTRIES.iter().zip(outcomes) {
+        let label = format!("{hook_type}({matcher})");
+        match outcome {
+            Outcome::Fired => mark(&format!("{ANSI_GREEN}\u{2713}"), &label, cmd),
+            Outcome::Installed(file) => mark(
+                &format!("{ANSI_GREEN}\u{2713}"),
+                &label,
+                &format!("{cmd}, in {file}"),
+            ),
+            Outcome::Missing => mark(
+                &format!("{ANSI_RED}\u{2717}"),
+                &label,
+                &format!("{cmd}: not installed (cove init)"),
+            ),
+            Outcome::Silent(why) => mark(
+                &format!("{ANSI_RED}\u{2717}"),
+                &label,
+                &format!("{cmd}: {why}"),
+            ),
+            Outcome::Skipped => mark(
+                &format!("{ANSI_OVERLAY}-"),
+                &label,
+                &format!("{cmd}: only fires in an interactive session"),
+            ),
+        }
+    }
+    let failing = outcomes
+        .iter()
+        .filter(|o| matches!(o, Outcome::Missing | Outcome::Silent(_)))
+        .count();
+    if failing > 0 {
+        return Err(format!(
+            "{failing} of {} hooks aren't working",
+            outcomes.len()
+        ));
+    }
+    Ok(())
+}
+
+// ── Public API ──
+
+/// List cove's hooks and where they're installed; with `fire_test`, fire
+/// each (through Claude with `claude`) and report which reach the events dir.
+pub fn run(fire_test: bool, claude: bool, timeout_secs: Option<u64>) -> Result<(), String> {
+    let project = std::env::current_dir()
+        .ok()
+        .map(|dir| init::project_root(&dir));
+    let files = init::settings_files(project.as_deref());
+    let found: Vec<Option<(PathBuf, String)>> = HOOK_ENTRIES
+        .iter()
+        .map(|&(hook_type, matcher, cmd)| {
+            files.iter().find_map(|file| {
+                init::installed_command(file, hook_type, matcher, cmd)
+                    .map(|command| (file.clone(), command))
+            })
+        })
+        .collect();
+    if !fire_test {
+        let outcomes: Vec<Outcome> = found
+            .iter()
+            .map(|f| match f {
+                Some((file, _)) => Outcome::Installed(paths::tildify(&file.to_string_lossy())),
+                None => Outcome::Missing,
+            })
+            .collect();
+        return report(&outcomes);
+    }
+
+    let installed: Vec<Option<String>> = found.into_iter().map(|f| f.map(|(_, c)| c)).collect();
+    let ours = format!("cove {}", env!("CARGO_PKG_VERSION"));
+    let stale = stale_bins(&installed, &ours);
+    if !stale.is_empty() {
+        let found: Vec<String> = stale
+            .iter()
+            .map(|(bin, version)| format!("{bin} ({version})"))
+            .collect();
+        return Err(format!(
+            "The hooks run {}, not {ours}; an older cove would act on real sessions \
+             instead of reporting back. Run `cove init` to point them at this one, \
+             or run verify-hooks with that binary.",
+            found.join(", ")
+        ));
+    }
+    let events = paths::ensure_events_dir()?;
+    let probe = probe_id();
+    let probe_path = hook::probe_file(&events, &probe);
+    let scratch = std::env::temp_dir().join(&probe);
+    fs::create_dir_all(&scratch).map_err(|e| format!("create {}: {e}", scratch.display()))?;
+
+    let outcomes = if claude {
+        let timeout = Duration::from_secs(timeout_secs.unwrap_or(CLAUDE_TIMEOUT_SECS));
+        println!("{ANSI_OVERLAY}Asking Claude to fire the hooks…{ANSI_RESET}");
+        fire_with_claude(&installed, &probe, &probe_path, &scratch, timeout)
+    } else {
+        let timeout = Duration::from_secs(timeout_secs.unwrap_or(TIMEOUT_SECS));
+        fire_each(&installed, &probe, &probe_path, &scratch, timeout)
+    };
+    let _ = fs::remove_file(&probe_path);
+    let _ = fs::remove_dir_all(&scratch);
+    report(&outcomes)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probed(hook: &str, tool: &str) -> Probed {
+        Probed {
+            hook: hook.to_string(),
+            tool: tool.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reported_matches_hook_and_tool() {
+        let lines = [probed("ask", "ExitPlanMode"), probed("file-edit", "Write")];
+        assert!(reported(&lines, "ExitPlanMode", "hook ask"));
+        assert!(!reported(&lines, "AskUserQuestion", "hook ask"));
+        assert!(reported(
+            &lines,
+            "Edit|Write|MultiEdit|NotebookEdit",
+            "hook file-edit"
+        ));
+        assert!(!reported(&lines, "*", "hook stop"));
+        assert!(reported(&[probed("stop", "")], "*", "hook stop"));
+    }
+
+    #[test]
+    fn test_stale_bins_ask_each_binary_its_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin = |name: &str, version: &str| {
+            let path = dir.path().join(name);
+            fs::write(&path, format!("#!/bin/sh\necho '{version}'\n")).unwrap();
+            let mut perms = fs::metadata(&path).unwrap().permissions();
+            std::os::unix::fs::PermissionsExt::set_mode(&mut perms, 0o755);
+            fs::set_permissions(&path, perms).unwrap();
+            format!("'{}'", path.display())
+        };
+        let current = bin("current", "cove 1.2.0");
+        let old = bin("old", "cove 0.9.0");
+        let installed = [
+            Some(format!("{current} hook stop --events-dir '/tmp/e'")),
+            Some(format!("{current} hook ask")),
+            None,
+            Some(format!("{old} hook user-prompt")),
+        ];
+        assert_eq!(
+            stale_bins(&installed, "cove 1.2.0"),
+            [(old, "cove 0.9.0".to_string())]
+        );
+        let gone = [Some("/nonexistent/cove hook stop".to_string())];
+        assert_eq!(stale_bins(&gone, "cove 1.2.0")[0].1, "no version");
+    }
+
+    #[test]
+    fn test_run_with_timeout() {
+        let ok = run_with_timeout(
+            Command::new("sh").args(["-c", "cat >/dev/null"]),
+            "{}",
+            Duration::from_secs(5),
+        );
+        assert_eq!(ok, Ok(()));
+        let failed = run_with_timeout(
+            Command::new("sh").args(["-c", "echo 'parse hook input: eof' >&2; exit 1"]),
+            "",
+            Duration::from_secs(5),
+        );
+        assert_eq!(failed, Err("parse hook input: eof".to_string()));
+        let hung = run_with_timeout(
+            Command::new("sh").args(["-c", "sleep 5"]),
+            "",
+            Duration::from_millis(200),
+        );
+        assert!(hung.unwrap_err().contains("still running"));
+    }
+
+    #[test]
+    fn test_fire_each_reports_per_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        let probe_path = dir.path().join("p.probe");
+        // A stand-in for `cove hook`: only ask-done reports, and stop fails
+        let script = dir.path().join("hook.sh");
+        let body = format!(
+            "cat >/dev/null\ncase \"$1\" in\n\
+             ask-done) echo '{{\"hook\":\"ask-done\",\"tool\":\"AskUserQuestion\"}}' >> {};;\n\
+             stop) echo broken >&2; exit 1;;\nesac\n",
+            probe_path.display()
+        );
+        fs::write(&script, body).unwrap();
+        let installed: Vec<Option<String>> = HOOK_ENTRIES
+            .iter()
+            .map(|&(_, _, cmd)| match cmd {
+                "hook ask-done" | "hook stop" | "hook user-prompt" => {
+                    Some(format!("sh {} {}", script.display(), &cmd[5..]))
+                }
+                _ => None,
+            })
+            .collect();
+        let outcomes = fire_each(
+            &installed,
+            "verify-test",
+            &probe_path,
+            dir.path(),
+            Duration::from_secs(5),
+        );
+        let by_cmd = |hook_type: &str, matcher: &str| {
+            let i = HOOK_ENTRIES
+                .iter()
+                .position(|&(t, m, _)| t == hook_type && m == matcher)
+                .unwrap();
+            outcomes[i].clone()
+        };
+        assert_eq!(by_cmd("PostToolUse", "AskUserQuestion"), Outcome::Fired);
+        assert_eq!(by_cmd("Stop", "*"), Outcome::Silent("broken".to_string()));
+        assert!(matches!(
+            by_cmd("UserPromptSubmit", "*"),
+            Outcome::Silent(why) if why.starts_with("ran, but no event")
+        ));
+        assert_eq!(by_cmd("SessionEnd", "*"), Outcome::Missing);
+    }
+}
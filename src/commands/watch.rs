@@ -0,0 +1,162 @@
+// ── cove watch ──
+//
+// The smallest piece of automation glue: `cove watch <name> --until idle
+// --then 'notify-send done'` blocks until the session reaches a state, then
+// runs a shell command and exits with its status. The command gets the
+// session in `COVE_SESSION`, `COVE_DIR`, `COVE_PANE` (the Claude pane) and
+// `COVE_STATE`. A session already in the state counts at once; `--next`
+// waits for it to get there again, for use right after `cove send`, when the
+// prompt may not have reached Claude yet. States are read the way `cove
+// list` reads them, so a session whose Claude exited is `done`.
+
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+
+use crate::cli::StateFilter;
+use crate::colors::*;
+use crate::commands::kill;
+use crate::sidebar::state::{StateDetector, WindowState};
+use crate::tmux::{self, Tmux, TmuxClient, WindowInfo};
+
+// ── Constants ──
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// ── Helpers ──
+
+/// The state of window `name`, or None once it's gone.
+pub fn current_state(
+    client: &dyn TmuxClient,
+    detector: &mut StateDetector,
+    name: &str,
+) -> Result<Option<(WindowInfo, WindowState)>, String> {
+    let windows = client.list_windows()?;
+    let states = detector.detect(client, &windows);
+    Ok(windows.into_iter().find(|w| w.name == name).map(|w| {
+        let state = states.get(&w.index).copied().unwrap_or(WindowState::Fresh);
+        (w, state)
+    }))
+}
+
+/// Poll until window `name` is in `until` (newly, with `next`), giving up
+/// after `timeout`. Returns the window as last seen.
+fn wait_in(
+    client: &dyn TmuxClient,
+    detector: &mut StateDetector,
+    name: &str,
+    until: StateFilter,
+    next: bool,
+    timeout: Option<Duration>,
+    poll: Duration,
+) -> Result<WindowInfo, String> {
+    let started = Instant::now();
+    // With `next`, being in the state only counts after being out of it
+    let mut armed = !next;
+    loop {
+        let Some((window, state)) = current_state(client, detector, name)? else {
+            return Err(format!("Session '{name}' is gone."));
+        };
+        let reached = kill::matches_filter(state, until);
+        if reached && armed {
+            return Ok(window);
+        }
+        armed |= !reached;
+        if timeout.is_some_and(|t| started.elapsed() >= t) {
+            return Err(format!("Timed out: {name} is still {}.", state.label()));
+        }
+        thread::sleep(poll);
+    }
+}
+
+// ── Public API ──
+
+/// Wait for session `name` to reach `until`, then run `then` (if given) and
+/// exit with its status.
+pub fn run(
+    name: &str,
+    until: StateFilter,
+    then: Option<&str>,
+    next: bool,
+    timeout_secs: Option<u64>,
+) -> Result<(), String> {
+    if !tmux::has_session() {
+        println!("{ANSI_OVERLAY}No active cove session.{ANSI_RESET}");
+        return Err(String::new());
+    }
+    let name = &tmux::resolve_target(name)?;
+    let timeout = timeout_secs.map(Duration::from_secs);
+    let window = wait_in(
+        &Tmux,
+        &mut StateDetector::new(),
+        name,
+        until,
+        next,
+        timeout,
+        POLL_INTERVAL,
+    )?;
+    let Some(then) = then else {
+        return Ok(());
+    };
+
+    let state = until
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default();
+    let pane = Tmux.get_claude_pane_id(name).unwrap_or_default();
+    let status = Command::new("sh")
+        .args(["-c", then])
+        .env("COVE_SESSION", name)
+        .env("COVE_DIR", &window.pane_path)
+        .env("COVE_PANE", pane)
+        .env("COVE_STATE", state)
+        .status()
+        .map_err(|e| format!("run {then}: {e}"))?;
+    match status.code() {
+        Some(0) => Ok(()),
+        // Killed by a signal: no code to pass on
+        None => Err(String::new()),
+        Some(code) => std::process::exit(code),
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    #[test]
+    fn test_wait_for_done_and_gone() {
+        let client = FakeTmux::new().with_windows(&[("api", "zsh", "%t1")]);
+        let dir = tempfile::tempdir().unwrap();
+        let mut detector = StateDetector::with_events_dir(dir.path());
+        let wait = |detector: &mut StateDetector, name, until, next| {
+            wait_in(
+                &client,
+                detector,
+                name,
+                until,
+                next,
+                Some(Duration::ZERO),
+                Duration::ZERO,
+            )
+        };
+
+        // A shell in the Claude pane means Claude exited
+        let window = wait(&mut detector, "api", StateFilter::Done, false).unwrap();
+        assert_eq!(window.pane_path, "/code/api");
+        // Already done doesn't count with --next
+        let err = wait(&mut detector, "api", StateFilter::Done, true)
+            .map(|w| w.name)
+            .unwrap_err();
+        assert!(err.contains("still done"), "{err}");
+        let err = wait(&mut detector, "web", StateFilter::Idle, false)
+            .map(|w| w.name)
+            .unwrap_err();
+        assert!(err.contains("gone"), "{err}");
+    }
+}
@@ -0,0 +1,94 @@
+// ── Re-run a command after every Claude turn ──
+
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use crate::colors::*;
+use crate::commands::start;
+use crate::sidebar::state::{self, WindowState};
+use crate::tmux;
+use crate::watch::{self, WatchStatus};
+
+// ── Constants ──
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// ── Helpers ──
+
+/// Run `cmd` through the shell with the pane's stdio, returning its status.
+fn run_once(cmd: &str) -> WatchStatus {
+    match Command::new("sh").arg("-c").arg(cmd).status() {
+        Ok(s) if s.success() => WatchStatus::Passed,
+        Ok(s) => WatchStatus::Failed(s.code().unwrap_or(-1)),
+        Err(e) => {
+            eprintln!("{ANSI_PEACH}watch-run:{ANSI_RESET} {e}");
+            WatchStatus::Failed(-1)
+        }
+    }
+}
+
+/// Poll the session's state forever, running `cmd` on each Working → Idle edge.
+fn watch_loop(name: &str, claude_pane: &str, cmd: &str) -> Result<(), String> {
+    println!(
+        "{ANSI_OVERLAY}Watching {ANSI_PEACH}{name}{ANSI_OVERLAY}: `{cmd}` runs after each Claude turn (Ctrl-C to stop){ANSI_RESET}"
+    );
+    let current = || {
+        state::load_pane_states()
+            .get(claude_pane)
+            .copied()
+            .unwrap_or(WindowState::Fresh)
+    };
+
+    let mut prev = current();
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let now = current();
+        if watch::is_turn_end(prev, now) {
+            println!("\n{ANSI_OVERLAY}$ {cmd}{ANSI_RESET}");
+            watch::record(claude_pane, cmd, WatchStatus::Running)?;
+            let status = run_once(cmd);
+            match status {
+                WatchStatus::Failed(code) => println!("{ANSI_RED}✗ exit {code}{ANSI_RESET}"),
+                _ => println!("{ANSI_GREEN}✓ passed{ANSI_RESET}"),
+            }
+            watch::record(claude_pane, cmd, status)?;
+        }
+        prev = now;
+    }
+}
+
+// ── Public API ──
+
+/// Attach a re-runner to a session's terminal pane. Called from inside that pane
+/// it runs in the foreground; from anywhere else it's started there.
+pub fn run(name: &str, cmd: &[String]) -> Result<(), String> {
+    if !tmux::has_session() {
+        println!("{ANSI_OVERLAY}No active cove session.{ANSI_RESET}");
+        return Err(String::new());
+    }
+    let name = &tmux::resolve_target(name)?;
+
+    let cmd = cmd.join(" ");
+    let claude_pane = tmux::get_claude_pane_id(name)?;
+    let terminal_pane = tmux::get_terminal_pane_id(name)?;
+
+    if std::env::var("TMUX_PANE").is_ok_and(|p| p == terminal_pane) {
+        return watch_loop(name, &claude_pane, &cmd);
+    }
+
+    let bin = std::env::current_exe()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "cove".to_string());
+    let line = format!(
+        "{} watch-run {} -- {}",
+        start::shell_quote(&bin),
+        start::shell_quote(name),
+        start::shell_quote(&cmd)
+    );
+    tmux::send_command(&terminal_pane, &line)?;
+    println!(
+        "Watching {ANSI_PEACH}{name}{ANSI_RESET}: `{cmd}` runs in its terminal pane after each turn"
+    );
+    Ok(())
+}
@@ -0,0 +1,802 @@
+// ── User configuration ──
+//
+// Optional settings in ~/.cove/config.yaml (see `paths`). Every field has a
+// default, so a missing file — or a missing section — behaves like a fresh
+// install. Unknown keys are rejected so typos surface instead of being ignored.
+//
+// It's the one config file: layout, the agent command and extra shells are
+// sections here rather than a second config.toml. Colors and the tmux session
+// name aren't configurable — every hook, binding and format string finds
+// cove's windows by the `cove` session.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+
+use crate::paths;
+
+// ── Types ──
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub sidebar: SidebarConfig,
+    pub layout: LayoutConfig,
+    pub agent: AgentConfig,
+    /// Prompt templates for `cove send --template`, by name.
+    pub templates: HashMap<String, String>,
+    pub tmux: TmuxConfig,
+    pub window_status: WindowStatusConfig,
+    pub quota: QuotaConfig,
+    pub hooks: HooksConfig,
+    pub cleanup: CleanupConfig,
+    pub ci: CiConfig,
+    pub crash: CrashConfig,
+    pub notify: NotifyConfig,
+    pub send: SendConfig,
+    pub journal: JournalConfig,
+    pub snapshots: SnapshotConfig,
+    pub archive: ArchiveConfig,
+    pub worktrees: WorktreesConfig,
+    pub delegate: DelegateConfig,
+    /// Never type into sessions (same as `--no-send` on every command).
+    pub safe_mode: bool,
+    /// Print only errors and requested output, and never prompt (same as
+    /// `--quiet` on every command).
+    pub quiet: bool,
+    /// Print long output directly instead of through the pager (same as
+    /// `--no-pager` on every command).
+    pub no_pager: bool,
+    /// Tags for new sessions by directory, first match wins.
+    pub tags: Vec<TagRule>,
+    /// Claude flags for sessions by directory, first match wins.
+    pub profiles: Vec<ProfileRule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SidebarConfig {
+    /// Template for session rows, e.g. "{icon} {name} {branch} {age}".
+    /// Unset keeps the built-in layout.
+    pub row_format: Option<String>,
+    /// Show the rotating key tips footer.
+    pub show_tips: bool,
+    /// Linear text output for screen readers instead of the TUI.
+    pub plain: bool,
+    /// Line printed by the plain sidebar when a session changes state,
+    /// e.g. "{name}: {state}" (also `{index}`).
+    pub announce: String,
+    /// Ring the terminal bell when another session starts waiting for you.
+    pub bell: bool,
+    /// Milliseconds between window list refreshes.
+    pub refresh_ms: u64,
+    /// List sessions under a collapsible heading per repository.
+    pub group_by_repo: bool,
+    /// Minutes without progress before a Working session can be flagged as
+    /// possibly stuck; 0 turns the check off.
+    pub stuck_after_mins: u64,
+    /// Blocks under the session list filled by a command's output.
+    pub sections: Vec<SectionConfig>,
+    /// Shells besides the usual ones (bash, zsh, fish, …) whose prompt in
+    /// the Claude pane means Claude exited.
+    pub shells: Vec<String>,
+}
+
+impl Default for SidebarConfig {
+    fn default() -> Self {
+        Self {
+            row_format: None,
+            show_tips: true,
+            plain: false,
+            announce: "{name}: {state}".to_string(),
+            bell: false,
+            refresh_ms: 200,
+            group_by_repo: false,
+            stuck_after_mins: 30,
+            sections: Vec::new(),
+            shells: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// The Claude pane's share of a new window's width, in percent.
+    pub claude_width: u32,
+    /// The sidebar's share of the right column's height, in percent.
+    pub sidebar_height: u32,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            claude_width: 70,
+            sidebar_height: 50,
+        }
+    }
+}
+
+impl LayoutConfig {
+    /// `claude_width` kept to a usable 10–90%.
+    pub fn claude_pct(&self) -> u32 {
+        self.claude_width.clamp(10, 90)
+    }
+
+    /// `sidebar_height` kept to a usable 10–90%.
+    pub fn sidebar_pct(&self) -> u32 {
+        self.sidebar_height.clamp(10, 90)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AgentConfig {
+    /// Command line that starts Claude, before cove's arguments: a path, a
+    /// wrapper script, `npx @anthropic-ai/claude-code`.
+    pub command: String,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            command: "claude".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SectionConfig {
+    pub title: String,
+    /// Shell command whose output fills the section, run from the home directory.
+    pub command: String,
+    /// Seconds between runs.
+    pub interval_secs: u64,
+    /// Show at most this many of the output's last lines.
+    pub max_lines: usize,
+}
+
+impl Default for SectionConfig {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            command: String::new(),
+            interval_secs: 60,
+            max_lines: 5,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TmuxConfig {
+    /// Give up on a tmux command after this long; 0 waits forever.
+    /// `new-session` and `attach` always wait.
+    pub timeout_ms: u64,
+    /// Retries for "server not ready" errors just after the session is created.
+    pub retries: u32,
+    /// `prefix 1`–`9` in the cove session go to quick-switch slots
+    /// (`cove go`) instead of window indexes. Off by default: it rebinds
+    /// keys in the tmux server, for every session.
+    pub slot_keys: bool,
+}
+
+impl Default for TmuxConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 5000,
+            retries: 3,
+            slot_keys: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowStatusConfig {
+    /// Show each session's state after its name in the tmux window list.
+    pub enabled: bool,
+    pub working: String,
+    pub asking: String,
+    pub idle: String,
+    pub queued: String,
+    /// `window-status-format` applied to cove windows; `#{@cove-state}` is the
+    /// glyph. Empty leaves a custom status bar alone.
+    pub format: String,
+    /// Title each Claude pane with its glyph, state and since when, for
+    /// tmux's `pane-border-status` (as `#{@cove-title}` in `pane-border-format`).
+    pub pane_title: bool,
+}
+
+impl Default for WindowStatusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            working: "\u{25b6}".to_string(),
+            asking: "?".to_string(),
+            idle: "\u{2713}".to_string(),
+            queued: "\u{2026}".to_string(),
+            format: "#I:#W#{?@cove-state, #{@cove-state},}#{?window_flags,#{window_flags}, }"
+                .to_string(),
+            pane_title: false,
+        }
+    }
+}
+
+impl WindowStatusConfig {
+    /// Glyph for a hook state ("working", "asking", "idle", "queued").
+    pub fn glyph(&self, state: &str) -> Option<&str> {
+        match state {
+            "working" => Some(&self.working),
+            "asking" => Some(&self.asking),
+            "idle" => Some(&self.idle),
+            "queued" => Some(&self.queued),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Archive every hook's raw stdin here, for `cove hook replay`.
+    pub capture_dir: Option<String>,
+    /// What starting a session does when cove's hooks are missing or stale.
+    pub install: HookInstall,
+}
+
+/// Whether to install missing hooks when a session starts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookInstall {
+    /// Ask first (`never` in quiet mode).
+    #[default]
+    Ask,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuotaConfig {
+    /// Most sessions allowed to work at once; further prompts are queued.
+    /// 0 means no limit.
+    pub max_working: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CleanupConfig {
+    /// Periodically check whether each session's branch has been merged and
+    /// offer to tear it down from the sidebar.
+    pub check_merged: bool,
+    /// Seconds between checks.
+    pub interval_secs: u64,
+    /// Branch merges are checked against; unset uses origin's default branch.
+    pub base: Option<String>,
+    /// Also ask GitHub (`gh pr view`), which catches squash merges.
+    pub gh: bool,
+    /// Hours between the sidebar archiving event files of panes that are
+    /// gone (see `cove orphan-scan`). 0 turns it off.
+    pub orphan_scan_hours: u64,
+}
+
+impl Default for CleanupConfig {
+    fn default() -> Self {
+        Self {
+            check_merged: false,
+            interval_secs: 300,
+            base: None,
+            gh: false,
+            orphan_scan_hours: 24,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JournalConfig {
+    /// Daily note finished sessions are appended to; `{...}` parts are dates
+    /// (`{YYYY-MM-DD}`). Unset turns the journal off.
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SnapshotConfig {
+    /// Snapshot each session's working tree after every turn, for
+    /// `cove rollback`.
+    pub enabled: bool,
+    /// Snapshots kept per session; older ones are dropped.
+    pub keep: usize,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keep: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorktreesConfig {
+    /// Where `cove <name> --worktree <branch>` checks branches out, as
+    /// `<root>/<repo>/<branch>`. Unset means ~/.cove/worktrees.
+    pub root: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArchiveConfig {
+    /// Save a killed session's whole scrollback to ~/.cove/captures/.
+    pub enabled: bool,
+    /// Captures kept; the oldest go first. 0 keeps any number.
+    pub keep: usize,
+    /// Days a capture is kept. 0 keeps them for good.
+    pub max_days: u64,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keep: 200,
+            max_days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CiConfig {
+    /// Show the checks of each session's pull request (needs `gh`).
+    pub enabled: bool,
+    /// Seconds between asking GitHub.
+    pub interval_secs: u64,
+}
+
+impl Default for CiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 180,
+        }
+    }
+}
+
+/// What the sidebar does when Claude exits with an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrashPolicy {
+    /// Start Claude again on the same conversation.
+    RespawnResume,
+    /// Leave the pane stopped and send an alert.
+    Notify,
+    /// Leave the pane stopped.
+    Leave,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrashConfig {
+    pub on_crash: CrashPolicy,
+    /// Policies by session name, overriding `on_crash`.
+    pub sessions: HashMap<String, CrashPolicy>,
+    /// Respawns per session in an hour before cove stops and alerts instead.
+    pub max_per_hour: usize,
+}
+
+impl Default for CrashConfig {
+    fn default() -> Self {
+        Self {
+            on_crash: CrashPolicy::RespawnResume,
+            sessions: HashMap::new(),
+            max_per_hour: 3,
+        }
+    }
+}
+
+impl CrashConfig {
+    /// The policy for session `name`.
+    pub fn policy(&self, name: &str) -> CrashPolicy {
+        self.sessions.get(name).copied().unwrap_or(self.on_crash)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DelegateConfig {
+    /// Questions answered for you in sessions turned on with `cove delegate`,
+    /// first match wins.
+    pub rules: Vec<DelegateRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DelegateRule {
+    /// Named in the audit log.
+    pub name: String,
+    /// Text the question contains, ignoring case.
+    pub question: String,
+    /// Label of the option to pick, ignoring case.
+    pub answer: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TagRule {
+    /// Directory glob: `*` within one path component, `**` across any
+    /// number, `~` for the home directory (e.g. "~/work/**").
+    pub path: String,
+    pub tag: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileRule {
+    /// Directory glob, as for `tags`.
+    pub path: String,
+    /// `--model`, e.g. "opus".
+    pub model: Option<String>,
+    /// `--permission-mode`: default, acceptEdits, plan or bypassPermissions.
+    pub permission_mode: Option<String>,
+    /// `--allowed-tools`, e.g. ["Read", "Bash(git diff:*)"].
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SendConfig {
+    /// How to tell that Claude's input box is ready for typing.
+    pub ready: ReadyConfig,
+    /// Probes for other agents, by the pane's foreground command.
+    pub agents: HashMap<String, ReadyConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReadyConfig {
+    /// Text on the agent's screen once it accepts input (any one will do);
+    /// empty relies on `delay_ms` alone.
+    pub markers: Vec<String>,
+    /// Milliseconds to wait once ready, for the TUI to settle.
+    pub delay_ms: u64,
+    /// Send anyway when no marker shows up for this long.
+    pub timeout_ms: u64,
+}
+
+impl Default for ReadyConfig {
+    fn default() -> Self {
+        Self {
+            markers: vec![
+                // The idle prompt box; "esc to interrupt" would also match
+                // while Claude is still working
+                "? for shortcuts".to_string(),
+                "\u{2502} >".to_string(),
+            ],
+            delay_ms: 150,
+            timeout_ms: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    pub slack: Option<WebhookConfig>,
+    pub discord: Option<WebhookConfig>,
+    pub pushover: Option<PushoverConfig>,
+    pub desktop: DesktopConfig,
+    /// Alert a session entering the same state at most once in this many
+    /// seconds (0 alerts every time).
+    pub throttle_secs: u64,
+    /// When to send alerts, and through which backends.
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            slack: None,
+            discord: None,
+            pushover: None,
+            desktop: DesktopConfig::default(),
+            throttle_secs: 30,
+            rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub webhook: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PushoverConfig {
+    /// Application API token.
+    pub token: String,
+    /// User (or group) key to deliver to.
+    pub user: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DesktopConfig {
+    pub tool: DesktopTool,
+}
+
+/// Program that shows desktop notifications.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DesktopTool {
+    /// terminal-notifier if installed, else osascript, on macOS; notify-send elsewhere.
+    #[default]
+    Auto,
+    Osascript,
+    TerminalNotifier,
+    NotifySend,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyBackend {
+    Slack,
+    Discord,
+    Pushover,
+    Desktop,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertRule {
+    /// Hook states that fire the rule ("asking", "idle").
+    pub states: Vec<String>,
+    /// Only alert when the turn has been running at least this long.
+    pub min_turn_secs: u64,
+    pub via: Vec<NotifyBackend>,
+}
+
+impl Default for AlertRule {
+    fn default() -> Self {
+        Self {
+            states: vec!["asking".to_string(), "idle".to_string()],
+            min_turn_secs: 0,
+            via: Vec::new(),
+        }
+    }
+}
+
+// ── Helpers ──
+
+fn load_from(path: &Path) -> Result<Config, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(format!("read {}: {e}", path.display())),
+    };
+    if content.trim().is_empty() {
+        return Ok(Config::default());
+    }
+    serde_yaml::from_str(&content).map_err(|e| format!("parse {}: {e}", path.display()))
+}
+
+// ── Public API ──
+
+/// Load the user's config, or defaults if there is none.
+pub fn load() -> Result<Config, String> {
+    match paths::cove_path("config.yaml") {
+        Some(path) => load_from(&path),
+        None => Ok(Config::default()),
+    }
+}
+
+/// When the config file was last written, for noticing edits; `None` while
+/// there is no file.
+pub fn modified() -> Option<SystemTime> {
+    fs::metadata(paths::cove_path("config.yaml")?)
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_file_is_default() {
+        let config = load_from(Path::new("/nonexistent/config.yaml")).unwrap();
+        assert!(config.sidebar.row_format.is_none());
+        assert!(config.sidebar.show_tips);
+        assert_eq!(config.sidebar.refresh_ms, 200);
+    }
+
+    #[test]
+    fn test_parse_row_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "sidebar:\n  row_format: \"{icon} {name}\"\n").unwrap();
+
+        let config = load_from(&path).unwrap();
+        assert_eq!(config.sidebar.row_format.as_deref(), Some("{icon} {name}"));
+        assert!(config.sidebar.show_tips);
+    }
+
+    #[test]
+    fn test_parse_templates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "templates:\n  review: |\n    Review {{file}}\n").unwrap();
+
+        let config = load_from(&path).unwrap();
+        assert_eq!(config.templates["review"], "Review {{file}}\n");
+    }
+
+    #[test]
+    fn test_tmux_policy_defaults_and_overrides() {
+        assert_eq!(Config::default().tmux.timeout_ms, 5000);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "tmux:\n  timeout_ms: 0\n").unwrap();
+
+        let config = load_from(&path).unwrap();
+        assert_eq!(config.tmux.timeout_ms, 0);
+        assert_eq!(config.tmux.retries, 3);
+    }
+
+    #[test]
+    fn test_ci_defaults_and_overrides() {
+        assert!(!Config::default().ci.enabled);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "ci:\n  enabled: true\n").unwrap();
+
+        let config = load_from(&path).unwrap();
+        assert!(config.ci.enabled);
+        assert_eq!(config.ci.interval_secs, 180);
+    }
+
+    #[test]
+    fn test_crash_policy_per_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(
+            &path,
+            "crash:\n  on_crash: notify\n  sessions:\n    nightly: respawn-resume\n",
+        )
+        .unwrap();
+
+        let config = load_from(&path).unwrap();
+        assert_eq!(config.crash.policy("api"), CrashPolicy::Notify);
+        assert_eq!(config.crash.policy("nightly"), CrashPolicy::RespawnResume);
+        assert_eq!(config.crash.max_per_hour, 3);
+        assert_eq!(
+            Config::default().crash.policy("api"),
+            CrashPolicy::RespawnResume
+        );
+    }
+
+    #[test]
+    fn test_parse_quiet_and_hook_install() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "quiet: true\nhooks:\n  install: always\n").unwrap();
+
+        let config = load_from(&path).unwrap();
+        assert!(config.quiet);
+        assert_eq!(config.hooks.install, HookInstall::Always);
+        assert_eq!(Config::default().hooks.install, HookInstall::Ask);
+    }
+
+    #[test]
+    fn test_parse_layout_and_agent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(
+            &path,
+            "layout:\n  claude_width: 95\nagent:\n  command: ~/bin/claude-wrapper\nsidebar:\n  shells: [xonsh]\n",
+        )
+        .unwrap();
+
+        let config = load_from(&path).unwrap();
+        assert_eq!(config.layout.claude_pct(), 90);
+        assert_eq!(config.layout.sidebar_pct(), 50);
+        assert_eq!(config.agent.command, "~/bin/claude-wrapper");
+        assert_eq!(config.sidebar.shells, ["xonsh"]);
+        assert_eq!(Config::default().agent.command, "claude");
+    }
+
+    #[test]
+    fn test_parse_tag_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "tags:\n  - path: ~/work/**\n    tag: work\n").unwrap();
+
+        let config = load_from(&path).unwrap();
+        assert_eq!(
+            config.tags,
+            [TagRule {
+                path: "~/work/**".to_string(),
+                tag: "work".to_string()
+            }]
+        );
+        // A rule needs both halves
+        fs::write(&path, "tags:\n  - path: ~/oss/**\n").unwrap();
+        assert!(load_from(&path).is_err());
+    }
+
+    #[test]
+    fn test_parse_notify_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(
+            &path,
+            "notify:\n  pushover:\n    token: t\n    user: u\n  rules:\n    - min_turn_secs: 600\n      via: [pushover, slack]\n",
+        )
+        .unwrap();
+        let config = load_from(&path).unwrap();
+        let rule = &config.notify.rules[0];
+        assert_eq!(rule.states, ["asking", "idle"]);
+        assert_eq!(rule.min_turn_secs, 600);
+        assert_eq!(rule.via, [NotifyBackend::Pushover, NotifyBackend::Slack]);
+        assert_eq!(config.notify.pushover.unwrap().user, "u");
+        assert!(config.notify.slack.is_none());
+        assert_eq!(config.notify.throttle_secs, 30);
+        assert_eq!(config.notify.desktop.tool, DesktopTool::Auto);
+    }
+
+    #[test]
+    fn test_parse_desktop_notify() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(
+            &path,
+            "notify:\n  desktop:\n    tool: terminal-notifier\n  throttle_secs: 0\n  rules:\n    - states: [asking]\n      via: [desktop]\n",
+        )
+        .unwrap();
+        let config = load_from(&path).unwrap();
+        assert_eq!(config.notify.desktop.tool, DesktopTool::TerminalNotifier);
+        assert_eq!(config.notify.throttle_secs, 0);
+        assert_eq!(config.notify.rules[0].via, [NotifyBackend::Desktop]);
+    }
+
+    #[test]
+    fn test_parse_send_probes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(
+            &path,
+            "send:\n  ready:\n    delay_ms: 500\n  agents:\n    codex:\n      markers: []\n      delay_ms: 2000\n",
+        )
+        .unwrap();
+        let config = load_from(&path).unwrap();
+        assert_eq!(config.send.ready.delay_ms, 500);
+        assert_eq!(config.send.ready.markers, ReadyConfig::default().markers);
+        let codex = &config.send.agents["codex"];
+        assert!(codex.markers.is_empty());
+        assert_eq!(codex.timeout_ms, 10_000);
+    }
+
+    #[test]
+    fn test_unknown_key_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.yaml");
+        fs::write(&path, "sidebar:\n  row_fromat: \"{name}\"\n").unwrap();
+
+        assert!(load_from(&path).is_err());
+    }
+}
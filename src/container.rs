@@ -0,0 +1,344 @@
+// ── Containerized sessions ──
+//
+// `cove <name> --container <image>` runs the session's Claude in a container
+// instead of on the host. `docker run -d` starts one from the image with the
+// project directory mounted at the same path, and the Claude pane runs
+// `docker exec -it <id> claude` in it. `--container devcontainer` uses the
+// project's dev container instead: `devcontainer up` starts (or reuses) it,
+// and the pane runs `devcontainer exec`.
+//
+// The container is started, and ready, before the window opens. An image
+// pull or a dev container build can take minutes, and the Claude pane
+// shouldn't sit on docker's progress looking like a Claude that won't start.
+// Its id goes into the session's metadata: `cove kill` removes the container
+// if cove created it (a dev container that was already up stays) and no other
+// session shares it, and `cove restart` resumes Claude in it.
+//
+// Hooks still have to reach cove. The container gets cove's state directory,
+// Claude's config directory and the cove binary mounted at their host paths,
+// plus `TMUX_PANE` and the pane's `$TMUX` as `COVE_TMUX`, so events land on
+// the right pane. `TMUX` itself stays unset: its socket isn't in the
+// container, and tmux clients there would fail on it. The image
+// needs `claude` on its PATH and a libc the cove binary runs on. The pane's
+// foreground command is the container client (`docker`, or `node` for the
+// devcontainer CLI) rather than `claude`, which is why the pane command is
+// `exec`'d: left under a shell, the pane would look like Claude had exited.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::start;
+use crate::{meta, names, paths};
+
+// ── Types ──
+
+/// A session's container, as kept in its metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Container {
+    pub id: String,
+    /// Project directory of a dev container; None for a `docker run` one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// Cove created the container, so removes it with the session.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub created: bool,
+}
+
+/// Mounts as (source, target) pairs, or environment as (key, value) ones.
+type Pairs = Vec<(String, String)>;
+
+// ── Constants ──
+
+/// `--container` value that means the project's dev container.
+pub const DEVCONTAINER: &str = "devcontainer";
+
+/// Environment passed from the pane to Claude's hooks in the container, as
+/// (name inside, pane variable) pairs.
+const PANE_ENV: [(&str, &str); 2] = [("COVE_TMUX", "TMUX"), ("TMUX_PANE", "TMUX_PANE")];
+
+// ── Helpers ──
+
+fn run_stdout(program: &str, args: &[String]) -> Result<String, String> {
+    log::debug!("{program} {}", args.join(" "));
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{program}: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{program}: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Host paths hooks need inside the container, as (source, target) pairs,
+/// and the environment that points cove and Claude at them.
+fn shared_paths() -> Result<(Pairs, Pairs), String> {
+    let path = |p: PathBuf| p.to_string_lossy().to_string();
+    paths::ensure_cove_dir("")?;
+    let env = hook_env();
+    let [(_, cove_home), (_, claude_dir)] = &env[..] else {
+        return Err("cannot locate Claude's config directory".to_string());
+    };
+    let mut mounts = vec![
+        (cove_home.clone(), cove_home.clone()),
+        (claude_dir.clone(), claude_dir.clone()),
+    ];
+    // Without CLAUDE_CONFIG_DIR Claude keeps its account in ~/.claude.json;
+    // inside, CLAUDE_CONFIG_DIR is set and looks for it in the directory
+    if std::env::var_os("CLAUDE_CONFIG_DIR").is_none()
+        && let Some(account) = paths::home().map(|h| h.join(".claude.json"))
+        && account.exists()
+    {
+        mounts.push((path(account), format!("{claude_dir}/.claude.json")));
+    }
+    let bin = start::cove_bin();
+    mounts.push((bin.clone(), bin));
+    Ok((mounts, env))
+}
+
+/// Environment that points cove and Claude in the container at the shared
+/// directories: empty if either can't be located.
+fn hook_env() -> Pairs {
+    let path = |p: PathBuf| p.to_string_lossy().to_string();
+    match (paths::cove_home(), paths::claude_dir()) {
+        (Some(cove_home), Some(claude_dir)) => vec![
+            ("COVE_HOME".to_string(), path(cove_home)),
+            ("CLAUDE_CONFIG_DIR".to_string(), path(claude_dir)),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// `exec_command`, with `env` set for a dev container (a `docker run` one
+/// got it when it started).
+fn exec_command_with(
+    container: &Container,
+    dir: &str,
+    env: &[(String, String)],
+    agent_cmd: &str,
+) -> String {
+    let id = start::shell_quote(&container.id);
+    match &container.workspace {
+        Some(workspace) => {
+            let mut line = format!(
+                "exec devcontainer exec --workspace-folder {} --container-id {id}",
+                start::shell_quote(workspace)
+            );
+            for (key, value) in env {
+                line.push_str(&format!(
+                    " --remote-env {}",
+                    start::shell_quote(&format!("{key}={value}"))
+                ));
+            }
+            for (key, var) in PANE_ENV {
+                line.push_str(&format!(" --remote-env \"{key}=${var}\""));
+            }
+            format!("{line} {agent_cmd}")
+        }
+        None => format!(
+            "exec docker exec -it -w {} {} {id} {agent_cmd}",
+            start::shell_quote(dir),
+            PANE_ENV
+                .map(|(key, var)| format!("-e \"{key}=${var}\""))
+                .join(" ")
+        ),
+    }
+}
+
+/// `docker run` arguments that start a container for session `name`,
+/// idling until Claude is exec'd into it.
+fn docker_run_args(
+    image: &str,
+    name: &str,
+    dir: &str,
+    mounts: &[(String, String)],
+    env: &[(String, String)],
+) -> Vec<String> {
+    let mut args: Vec<String> = ["run", "-d", "--init", "--name"].map(String::from).to_vec();
+    args.push(container_name(name));
+    for (source, target) in std::iter::once(&(dir.to_string(), dir.to_string())).chain(mounts) {
+        args.extend(["-v".to_string(), format!("{source}:{target}")]);
+    }
+    for (key, value) in env {
+        args.extend(["-e".to_string(), format!("{key}={value}")]);
+    }
+    args.extend(["-w", dir, "--entrypoint", "sleep", image, "infinity"].map(String::from));
+    args
+}
+
+/// Container name for session `name`: "cove-" and the name, with the
+/// characters docker doesn't take (a tag's `/`) as `-`.
+fn container_name(name: &str) -> String {
+    let name: String = names::sanitize(name)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "_.-".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("cove-{name}")
+}
+
+/// Whether a container already runs the dev container for `dir`
+/// (`devcontainer up` labels its containers with the folder).
+fn devcontainer_exists(dir: &str) -> bool {
+    let args = ["ps", "-aq", "--filter"]
+        .map(String::from)
+        .into_iter()
+        .chain([format!("label=devcontainer.local_folder={dir}")])
+        .collect::<Vec<_>>();
+    run_stdout("docker", &args).is_ok_and(|out| !out.trim().is_empty())
+}
+
+/// The container id from `devcontainer up`'s JSON result, its last line.
+fn parse_up(output: &str) -> Result<String, String> {
+    let result: serde_json::Value = output
+        .lines()
+        .rev()
+        .find(|l| l.trim_start().starts_with('{'))
+        .and_then(|l| serde_json::from_str(l).ok())
+        .ok_or("devcontainer up: no result")?;
+    if result["outcome"] != "success" {
+        let message = result["message"].as_str().unwrap_or("failed");
+        return Err(format!("devcontainer up: {message}"));
+    }
+    result["containerId"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "devcontainer up: no container id".to_string())
+}
+
+// ── Public API ──
+
+/// Start the container `spec` (an image, or `devcontainer`) names for
+/// session `name` in `dir`, returning once it runs.
+pub fn start(spec: &str, name: &str, dir: &str) -> Result<Container, String> {
+    let (mounts, env) = shared_paths()?;
+    if spec == DEVCONTAINER {
+        let mut args = vec![
+            "up".to_string(),
+            "--workspace-folder".to_string(),
+            dir.to_string(),
+        ];
+        for (source, target) in &mounts {
+            args.extend([
+                "--mount".to_string(),
+                format!("type=bind,source={source},target={target}"),
+            ]);
+        }
+        let existed = devcontainer_exists(dir);
+        let id = parse_up(&run_stdout("devcontainer", &args)?)?;
+        // One cove brought up for another session is still cove's to remove
+        let created = !existed
+            || meta::containers()
+                .iter()
+                .any(|(_, c)| c.id == id && c.created);
+        return Ok(Container {
+            id,
+            workspace: Some(dir.to_string()),
+            created,
+        });
+    }
+    let args = docker_run_args(spec, name, dir, &mounts, &env);
+    let id = run_stdout("docker", &args)?.trim().to_string();
+    Ok(Container {
+        id,
+        workspace: None,
+        created: true,
+    })
+}
+
+/// Command line for the Claude pane that runs `agent_cmd` in `container`,
+/// in directory `dir`.
+pub fn exec_command(container: &Container, dir: &str, agent_cmd: &str) -> String {
+    exec_command_with(container, dir, &hook_env(), agent_cmd)
+}
+
+/// The container session `name` runs in, if any.
+pub fn for_session(name: &str) -> Option<Container> {
+    meta::containers()
+        .into_iter()
+        .find_map(|(n, c)| (n == name).then_some(c))
+}
+
+/// Forget session `name`'s container and remove it if cove created it,
+/// unless another session still runs in it (dev containers are shared per
+/// project).
+pub fn tear_down(name: &str) -> Result<(), String> {
+    let containers = meta::containers();
+    let Some(ours) = containers
+        .iter()
+        .find_map(|(n, c)| (n == name).then_some(c))
+    else {
+        return Ok(());
+    };
+    let shared = containers.iter().any(|(n, c)| n != name && c.id == ours.id);
+    // Another session on the container may be the one that created it, and
+    // `docker run` ones always are cove's (older metadata has no flag)
+    let created = containers
+        .iter()
+        .any(|(_, c)| c.id == ours.id && (c.created || c.workspace.is_none()));
+    if created && !shared {
+        run_stdout(
+            "docker",
+            &["rm".to_string(), "-f".to_string(), ours.id.clone()],
+        )?;
+    }
+    meta::update(name, |m| m.container = None)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docker_run_and_exec_commands() {
+        let mounts = [("/home/u/.cove".to_string(), "/home/u/.cove".to_string())];
+        let env = [("COVE_HOME".to_string(), "/home/u/.cove".to_string())];
+        let args = docker_run_args("node:22", "web/fix-auth", "/code/web", &mounts, &env);
+        assert_eq!(
+            args.join(" "),
+            "run -d --init --name cove-web-fix-auth -v /code/web:/code/web \
+             -v /home/u/.cove:/home/u/.cove -e COVE_HOME=/home/u/.cove \
+             -w /code/web --entrypoint sleep node:22 infinity"
+        );
+
+        let docker = Container {
+            id: "3f2a".to_string(),
+            workspace: None,
+            created: true,
+        };
+        assert_eq!(
+            exec_command_with(&docker, "/code/web", &env, "claude"),
+            "exec docker exec -it -w '/code/web' -e \"COVE_TMUX=$TMUX\" -e \"TMUX_PANE=$TMUX_PANE\" '3f2a' claude"
+        );
+        let dev = Container {
+            id: "9c1d".to_string(),
+            workspace: Some("/code/web".to_string()),
+            created: false,
+        };
+        assert_eq!(
+            exec_command_with(&dev, "/code/web", &env, "claude --continue"),
+            "exec devcontainer exec --workspace-folder '/code/web' --container-id '9c1d' \
+             --remote-env 'COVE_HOME=/home/u/.cove' --remote-env \"COVE_TMUX=$TMUX\" --remote-env \"TMUX_PANE=$TMUX_PANE\" claude --continue"
+        );
+    }
+
+    #[test]
+    fn test_parse_devcontainer_up_result() {
+        let out = "[2 ms] Start: Run: docker build\n\
+                   {\"outcome\":\"success\",\"containerId\":\"9c1d\",\"remoteUser\":\"node\"}\n";
+        assert_eq!(parse_up(out).unwrap(), "9c1d");
+        let out = "{\"outcome\":\"error\",\"message\":\"No devcontainer.json found.\"}";
+        assert!(parse_up(out).unwrap_err().contains("No devcontainer.json"));
+        assert!(parse_up("").is_err());
+    }
+}
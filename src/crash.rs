@@ -0,0 +1,248 @@
+// ── Crash recovery ──
+//
+// A Claude pane that exits with an error is left dead by the window's
+// pane-died hook instead of being respawned as a fresh `claude`. The sidebar
+// in that window spots it and applies `crash.on_crash` (or the session's
+// entry under `crash.sessions`):
+//
+//   respawn-resume  start Claude again with `--resume`, like `cove restart`
+//   notify          leave the pane stopped and alert through the notify
+//                   rules for the "crashed" state, plus a tmux message
+//   leave           leave the pane stopped
+//
+// Respawns are capped at `crash.max_per_hour` per session so a Claude that
+// dies on start doesn't loop; past the cap cove gives up, alerts and marks
+// the pane as failed to start. Every recovery is appended to
+// ~/.cove/crashes.jsonl, which also holds the count.
+//
+// A pane that exits cleanly is respawned by the hook itself, which stops
+// after 3 respawns in 30 seconds and marks it failed the same way. The
+// sidebar shows a failed window with the end of what the pane printed
+// (`error_lines`), usually why it couldn't start; `R` or `cove restart`
+// starts it again.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::restart;
+use crate::config::{Config, CrashConfig, CrashPolicy};
+use crate::tmux::{Tmux, TmuxClient};
+use crate::{notify, paths, tmux};
+
+// ── Types ──
+
+/// What was done about one crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Recovery {
+    Respawned,
+    Notified,
+    Left,
+    /// Respawned too often in the last hour: left stopped and marked failed
+    /// for good, with an alert. Nothing respawns it until it's restarted.
+    GaveUp,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LogEntry {
+    session: String,
+    pane_id: String,
+    action: Recovery,
+    ts: u64,
+}
+
+// ── Constants ──
+
+const LOG_FILE: &str = "crashes.jsonl";
+
+const HOUR_SECS: u64 = 3600;
+
+/// Lines of a failed pane's output shown in the sidebar.
+const ERROR_LINES: usize = 6;
+
+// ── Helpers ──
+
+fn read_log(path: &Path) -> Vec<LogEntry> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn append_log(path: &Path, entry: &LogEntry) -> Result<(), String> {
+    let line = serde_json::to_string(entry).map_err(|e| format!("encode crash log: {e}"))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("open {}: {e}", path.display()))?;
+    writeln!(file, "{line}").map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+/// Respawns of `session` logged in the hour before `now`.
+fn recent_respawns(log: &[LogEntry], session: &str, now: u64) -> usize {
+    log.iter()
+        .filter(|e| e.session == session && e.action == Recovery::Respawned)
+        .filter(|e| now.saturating_sub(e.ts) < HOUR_SECS)
+        .count()
+}
+
+/// What to do about a crash of `session`, given its earlier recoveries.
+fn decide(config: &CrashConfig, session: &str, log: &[LogEntry], now: u64) -> Recovery {
+    match config.policy(session) {
+        CrashPolicy::RespawnResume if recent_respawns(log, session, now) < config.max_per_hour => {
+            Recovery::Respawned
+        }
+        CrashPolicy::RespawnResume => Recovery::GaveUp,
+        CrashPolicy::Notify => Recovery::Notified,
+        CrashPolicy::Leave => Recovery::Left,
+    }
+}
+
+/// Tell the user `session` crashed, in tmux and through the notify rules for
+/// "crashed". Webhooks are slow, so they go out on their own thread.
+fn alert(config: &Config, session: &str, text: String) {
+    let _ = tmux::display_message(&format!("cove: {text}"));
+    let backends = notify::backends(&config.notify, "crashed", None);
+    if backends.is_empty() {
+        return;
+    }
+    let notify = config.notify.clone();
+    let session = session.to_string();
+    thread::spawn(move || {
+        for backend in backends {
+            if let Err(e) = notify::send(&notify, backend, &text, None) {
+                let _ = tmux::display_message(&format!("cove: {session}: {e}"));
+            }
+        }
+    });
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+// ── Public API ──
+
+/// The last lines a dead pane printed, from its `capture`, without the
+/// blank rows under them and tmux's "Pane is dead" line.
+pub fn error_lines(capture: &str) -> Vec<String> {
+    let lines: Vec<&str> = capture
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.starts_with("Pane is dead"))
+        .collect();
+    let end = lines
+        .iter()
+        .rposition(|l| !l.is_empty())
+        .map_or(0, |i| i + 1);
+    let start = end.saturating_sub(ERROR_LINES);
+    lines[start..end].iter().map(|l| l.to_string()).collect()
+}
+
+/// Index of the window the calling pane is in, from $TMUX_PANE.
+pub fn own_window(client: &dyn TmuxClient) -> Option<u32> {
+    let pane = std::env::var("TMUX_PANE").ok()?;
+    client
+        .output(&["display-message", "-t", &pane, "-p", "#{window_index}"])
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Apply the crash policy to session `name`, whose Claude pane `pane_id`
+/// exited with an error, and log what was done.
+pub fn recover(config: &Config, name: &str, pane_id: &str) -> Result<Recovery, String> {
+    let path = paths::ensure_cove_dir("")?.join(LOG_FILE);
+    let now = now();
+    let action = decide(&config.crash, name, &read_log(&path), now);
+    match action {
+        Recovery::Respawned => restart::restart(name)?,
+        Recovery::Notified => alert(config, name, format!("{name} crashed")),
+        Recovery::GaveUp => {
+            Tmux.set_failed(pane_id, true)?;
+            alert(
+                config,
+                name,
+                format!(
+                    "{name} crashed again after {} respawns within an hour; cove stopped \
+                     respawning it (R or `cove restart {name}` starts it)",
+                    config.crash.max_per_hour
+                ),
+            );
+        }
+        Recovery::Left => {}
+    }
+    append_log(
+        &path,
+        &LogEntry {
+            session: name.to_string(),
+            pane_id: pane_id.to_string(),
+            action,
+            ts: now,
+        },
+    )?;
+    Ok(action)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_respawns_are_capped_per_hour() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOG_FILE);
+        let config = CrashConfig {
+            max_per_hour: 2,
+            sessions: HashMap::from([("web".to_string(), CrashPolicy::Leave)]),
+            ..CrashConfig::default()
+        };
+        let entry = |session: &str, action, ts| LogEntry {
+            session: session.to_string(),
+            pane_id: "%1".to_string(),
+            action,
+            ts,
+        };
+
+        assert_eq!(decide(&config, "api", &[], 10_000), Recovery::Respawned);
+        append_log(&path, &entry("api", Recovery::Respawned, 7_000)).unwrap();
+        append_log(&path, &entry("api", Recovery::Respawned, 9_000)).unwrap();
+        append_log(&path, &entry("other", Recovery::Respawned, 9_500)).unwrap();
+        let log = read_log(&path);
+        assert_eq!(decide(&config, "api", &log, 9_900), Recovery::GaveUp);
+        // The first respawn falls out of the hour
+        assert_eq!(decide(&config, "api", &log, 10_700), Recovery::Respawned);
+        assert_eq!(decide(&config, "web", &log, 10_700), Recovery::Left);
+    }
+
+    #[test]
+    fn test_error_lines_keep_the_end_of_the_output() {
+        let capture = "one\ntwo\nthree\nfour\nfive\n\nInvalid API key \u{b7} Please run /login   \n\
+                       Pane is dead (status 1, Wed Oct 14 15:30:12 2026)\n\n\n";
+        assert_eq!(
+            error_lines(capture),
+            [
+                "two",
+                "three",
+                "four",
+                "five",
+                "",
+                "Invalid API key \u{b7} Please run /login"
+            ]
+        );
+        assert!(error_lines("\n\n").is_empty());
+    }
+}
@@ -0,0 +1,361 @@
+// ── Delegate mode ──
+//
+// Answers trivial AskUserQuestions for you, by rule: in a session turned on
+// with `cove delegate <name>`, a question matching one of `delegate.rules`
+// gets the rule's option picked, as if you'd pressed its number. The ask
+// hook (PreToolUse) fires before Claude draws the question, so it only plans
+// the answer; a background `cove hook answer`, started by tmux so the hook
+// returns at once, waits for the question to show on the pane and presses the
+// option's key. Showing means the question text and the numbered option
+// ("2. Yes, proceed") both in the bottom of the screen, where Claude draws
+// the prompt: the label alone could be anywhere in the scrollback. Only a single question with one answer and at most nine options is
+// handled — anything else waits for you as usual.
+//
+// Every answer, and every planned answer that couldn't be given, is logged
+// to ~/.cove/delegate.jsonl (`cove delegate <name> --log`). Safe mode turns
+// delegation off along with every other keypress cove would send.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::commands::start::{cove_bin, shell_quote};
+use crate::config::{Config, DelegateRule};
+use crate::tmux::TmuxClient;
+use crate::{meta, paths, send};
+
+// ── Types ──
+
+/// The option a rule picks for a question.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub rule: String,
+    pub question: String,
+    /// The option's label, as Claude shows it.
+    pub label: String,
+    /// Key that picks it: the option's number.
+    pub key: String,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub ts: u64,
+    pub session: String,
+    pub pane_id: String,
+    pub rule: String,
+    pub question: String,
+    pub answer: String,
+    pub key: String,
+    /// "answered", or why the key wasn't pressed.
+    pub outcome: String,
+}
+
+// ── Constants ──
+
+const LOG_FILE: &str = "delegate.jsonl";
+
+/// How long to wait for the question to be drawn.
+const SHOW_TIMEOUT: Duration = Duration::from_secs(10);
+
+const POLL: Duration = Duration::from_millis(200);
+
+/// Lines at the bottom of the pane the question and its options are drawn in.
+const PROMPT_LINES: usize = 30;
+
+/// Pause between the options showing and the keypress, so the prompt is
+/// taking input by then.
+const SETTLE: Duration = Duration::from_millis(300);
+
+// ── Helpers ──
+
+/// `text` with every run of whitespace as one space, so lines Claude wrapped
+/// compare equal to the unwrapped text.
+fn squash(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether `screen` shows `answer`'s question with its numbered option, both
+/// in the bottom `PROMPT_LINES`.
+fn question_shown(screen: &str, answer: &Answer) -> bool {
+    let lines: Vec<&str> = screen.trim_end().lines().collect();
+    let bottom = &lines[lines.len().saturating_sub(PROMPT_LINES)..];
+    let option = format!("{}. {}", answer.key, answer.label);
+    let has_option = bottom.iter().any(|line| {
+        let line = line.trim_start_matches(|c: char| !c.is_alphanumeric());
+        squash(line).starts_with(&option)
+    });
+    has_option && squash(&bottom.join("\n")).contains(&squash(&answer.question))
+}
+
+/// Wait up to `timeout` for `answer`'s question on `pane_id`'s screen, then
+/// press its key.
+fn press_in(
+    client: &dyn TmuxClient,
+    pane_id: &str,
+    answer: &Answer,
+    timeout: Duration,
+) -> Result<(), String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let screen = client.output(&["capture-pane", "-p", "-t", pane_id])?;
+        if question_shown(&screen, answer) {
+            break;
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "the question didn't show within {}s",
+                timeout.as_secs()
+            ));
+        }
+        std::thread::sleep(POLL);
+    }
+    std::thread::sleep(SETTLE);
+    client.send_key(pane_id, &answer.key)
+}
+
+fn append_in(path: &Path, entry: &LogEntry) -> Result<(), String> {
+    let line = serde_json::to_string(entry).map_err(|e| format!("serialize: {e}"))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("open {}: {e}", path.display()))?;
+    writeln!(file, "{line}").map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+fn entries_in(path: &Path, session: &str) -> Vec<LogEntry> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<LogEntry>(line).ok())
+        .filter(|e| e.session == session)
+        .collect()
+}
+
+/// The background command that answers on `pane_id`, as run by tmux.
+fn answer_command(session: &str, pane_id: &str, answer: &Answer) -> String {
+    let args = [
+        "hook",
+        "answer",
+        "--session",
+        session,
+        "--pane",
+        pane_id,
+        "--rule",
+        &answer.rule,
+        "--question",
+        &answer.question,
+        "--label",
+        &answer.label,
+        "--key",
+        &answer.key,
+    ];
+    let quoted: Vec<String> = args.iter().map(|a| shell_quote(a)).collect();
+    // Anything printed would pop up over the pane
+    format!(
+        "{} {} >/dev/null 2>&1",
+        shell_quote(&cove_bin()),
+        quoted.join(" ")
+    )
+}
+
+// ── Public API ──
+
+/// The answer `rules` give the AskUserQuestion in `tool_input`, if any.
+pub fn plan(rules: &[DelegateRule], tool_input: &Value) -> Option<Answer> {
+    let [question] = tool_input["questions"].as_array()?.as_slice() else {
+        return None;
+    };
+    if question["multiSelect"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    let text = question["question"].as_str()?;
+    let labels: Vec<&str> = question["options"]
+        .as_array()?
+        .iter()
+        .map(|o| o["label"].as_str().unwrap_or_default())
+        .collect();
+    let lower = text.to_lowercase();
+    rules.iter().find_map(|rule| {
+        if !lower.contains(&rule.question.to_lowercase()) {
+            return None;
+        }
+        let index = labels
+            .iter()
+            .position(|l| l.to_lowercase() == rule.answer.to_lowercase())?;
+        (index < 9).then(|| Answer {
+            rule: rule.name.clone(),
+            question: text.to_string(),
+            label: labels[index].to_string(),
+            key: (index + 1).to_string(),
+        })
+    })
+}
+
+/// Called by the ask hook: if session `name` delegates and a rule answers
+/// the question, have tmux press the answer once it shows. Returns the
+/// planned answer.
+pub fn on_ask(
+    client: &dyn TmuxClient,
+    config: &Config,
+    name: &str,
+    pane_id: &str,
+    tool_input: &Value,
+) -> Result<Option<Answer>, String> {
+    if config.delegate.rules.is_empty() || send::disabled_by(config) || !meta::delegated(name) {
+        return Ok(None);
+    }
+    let Some(answer) = plan(&config.delegate.rules, tool_input) else {
+        return Ok(None);
+    };
+    client.output(&["run-shell", "-b", &answer_command(name, pane_id, &answer)])?;
+    Ok(Some(answer))
+}
+
+/// Run by `cove hook answer`: press `answer` on `pane_id` once it shows, and
+/// log the outcome either way.
+pub fn press(client: &dyn TmuxClient, session: &str, pane_id: &str, answer: &Answer) {
+    let outcome = match press_in(client, pane_id, answer, SHOW_TIMEOUT) {
+        Ok(()) => "answered".to_string(),
+        Err(e) => {
+            log::warn!("delegate {session}: {e}");
+            e
+        }
+    };
+    let entry = LogEntry {
+        ts: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        session: session.to_string(),
+        pane_id: pane_id.to_string(),
+        rule: answer.rule.clone(),
+        question: answer.question.clone(),
+        answer: answer.label.clone(),
+        key: answer.key.clone(),
+        outcome,
+    };
+    let logged = paths::ensure_cove_dir("").and_then(|dir| append_in(&dir.join(LOG_FILE), &entry));
+    if let Err(e) = logged {
+        log::warn!("delegate log: {e}");
+    }
+}
+
+/// Audit log entries for session `name`, oldest first.
+pub fn entries(name: &str) -> Vec<LogEntry> {
+    paths::cove_path(LOG_FILE)
+        .map(|path| entries_in(&path, name))
+        .unwrap_or_default()
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+
+    fn rule(name: &str, question: &str, answer: &str) -> DelegateRule {
+        DelegateRule {
+            name: name.to_string(),
+            question: question.to_string(),
+            answer: answer.to_string(),
+        }
+    }
+
+    fn ask(question: &str, labels: &[&str], multi: bool) -> Value {
+        let options: Vec<Value> = labels
+            .iter()
+            .map(|l| serde_json::json!({"label": l, "description": ""}))
+            .collect();
+        serde_json::json!({"questions": [
+            {"question": question, "header": "Go?", "options": options, "multiSelect": multi}
+        ]})
+    }
+
+    #[test]
+    fn test_plan_picks_first_matching_rule() {
+        let rules = [
+            rule("deploys", "deploy to production", "No"),
+            rule("proceed", "shall i proceed", "yes, proceed"),
+        ];
+        let input = ask(
+            "Tests pass. Shall I proceed with the commit?",
+            &["Stop here", "Yes, proceed"],
+            false,
+        );
+        assert_eq!(
+            plan(&rules, &input),
+            Some(Answer {
+                rule: "proceed".to_string(),
+                question: "Tests pass. Shall I proceed with the commit?".to_string(),
+                label: "Yes, proceed".to_string(),
+                key: "2".to_string(),
+            })
+        );
+
+        // No such option, several answers, or an unrelated question: left alone
+        let missing = ask("Shall I proceed?", &["Yes", "No"], false);
+        assert_eq!(plan(&rules, &missing), None);
+        let multi = ask("Shall I proceed?", &["Yes, proceed"], true);
+        assert_eq!(plan(&rules, &multi), None);
+        let other = ask("Which database?", &["Yes, proceed"], false);
+        assert_eq!(plan(&rules, &other), None);
+    }
+
+    #[test]
+    fn test_press_waits_for_the_question_and_logs() {
+        let answer = Answer {
+            rule: "proceed".to_string(),
+            question: "Shall I proceed?".to_string(),
+            label: "Yes, proceed".to_string(),
+            key: "2".to_string(),
+        };
+        let fake = FakeTmux::new().on(
+            "capture-pane",
+            " Shall I\n proceed?\n❯ 1. Stop here\n  2. Yes, proceed\n",
+        );
+        press_in(&fake, "%t1", &answer, Duration::from_secs(1)).unwrap();
+        assert_eq!(
+            fake.calls_to("send-keys"),
+            [["send-keys", "-t", "%t1", "2"]]
+        );
+
+        let drawing = FakeTmux::new().on("capture-pane", "✻ Thinking…\n");
+        assert!(press_in(&drawing, "%t1", &answer, Duration::ZERO).is_err());
+        assert!(drawing.calls_to("send-keys").is_empty());
+
+        // The label in Claude's output, or an old question scrolled up, isn't it
+        let quoted = "Shall I proceed?\n> I'll say Yes, proceed\n";
+        assert!(!question_shown(quoted, &answer));
+        let old = format!(
+            "Shall I proceed?\n  2. Yes, proceed\n{}✻ Thinking…\n",
+            "output\n".repeat(PROMPT_LINES)
+        );
+        assert!(!question_shown(&old, &answer));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOG_FILE);
+        for session in ["api", "web"] {
+            let entry = LogEntry {
+                ts: 1,
+                session: session.to_string(),
+                pane_id: "%t1".to_string(),
+                rule: answer.rule.clone(),
+                question: answer.question.clone(),
+                answer: answer.label.clone(),
+                key: answer.key.clone(),
+                outcome: "answered".to_string(),
+            };
+            append_in(&path, &entry).unwrap();
+        }
+        let logged = entries_in(&path, "api");
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].answer, "Yes, proceed");
+    }
+}
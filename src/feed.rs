@@ -0,0 +1,170 @@
+// ── Session state feed ──
+//
+// ~/.cove/state.json holds every session and its state, for tools that want
+// cove's state without running the binary (Hammerspoon, AutoHotkey, eww
+// widgets, a status bar script): watch the file, or read it on a timer.
+// The sidebar's events worker rewrites it whenever a session comes, goes or
+// changes state, through a temp file and a rename, so readers never see
+// half of it. Several sidebars write the same content; the last one wins.
+// `cove all-kill`, which ends the cove session, removes the file.
+//
+//   {"updated": 1791997820, "sessions": [
+//     {"name": "api", "index": 1, "state": "working", "dir": "/code/api",
+//      "pane_id": "%3", "active": true, "since": 1791997790, "asks": 0}]}
+//
+// `state` is a `cove list` label: fresh, working, waiting, your turn, done
+// or queued. `since` is the time of the session's latest hook event.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::paths;
+use crate::sidebar::state::WindowState;
+use crate::tmux::WindowInfo;
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeedSession {
+    pub name: String,
+    pub index: u32,
+    pub state: &'static str,
+    pub dir: String,
+    /// Claude's tmux pane; empty when the window has none.
+    pub pane_id: String,
+    /// The window tmux shows.
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+    /// Questions waiting for an answer.
+    pub asks: u32,
+}
+
+#[derive(Serialize)]
+struct Feed<'a> {
+    updated: u64,
+    sessions: &'a [FeedSession],
+}
+
+// ── Constants ──
+
+const FEED_FILE: &str = "state.json";
+
+// ── Helpers ──
+
+fn write_in(path: &Path, sessions: &[FeedSession], updated: u64) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(&Feed { updated, sessions })
+        .map_err(|e| format!("serialize: {e}"))?;
+    let tmp = path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&tmp, content).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("replace {}: {e}", path.display())
+    })
+}
+
+// ── Public API ──
+
+/// The feed's sessions for managed `windows`, in window order.
+pub fn sessions(
+    windows: &[WindowInfo],
+    states: &HashMap<u32, WindowState>,
+    pane_ids: &HashMap<u32, String>,
+    event_ts: &HashMap<u32, u64>,
+    asks: &HashMap<u32, u32>,
+) -> Vec<FeedSession> {
+    let mut sessions: Vec<FeedSession> = windows
+        .iter()
+        .filter(|w| w.managed)
+        .map(|w| FeedSession {
+            name: w.name.clone(),
+            index: w.index,
+            state: states
+                .get(&w.index)
+                .copied()
+                .unwrap_or(WindowState::Fresh)
+                .label(),
+            dir: w.pane_path.clone(),
+            pane_id: pane_ids.get(&w.index).cloned().unwrap_or_default(),
+            active: w.is_active,
+            since: event_ts.get(&w.index).copied(),
+            asks: asks.get(&w.index).copied().unwrap_or(0),
+        })
+        .collect();
+    sessions.sort_by_key(|s| s.index);
+    sessions
+}
+
+/// Replace the feed with `sessions`.
+pub fn write(sessions: &[FeedSession]) -> Result<(), String> {
+    let path = paths::ensure_cove_dir("")?.join(FEED_FILE);
+    let updated = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    write_in(&path, sessions, updated)
+}
+
+/// Remove the feed: no sessions are left to report on.
+pub fn clear() {
+    if let Some(path) = paths::cove_path(FEED_FILE) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(index: u32, name: &str, managed: bool) -> WindowInfo {
+        WindowInfo {
+            id: format!("@{index}"),
+            index,
+            name: name.to_string(),
+            is_active: index == 2,
+            pane_path: format!("/code/{name}"),
+            managed,
+        }
+    }
+
+    #[test]
+    fn test_feed_lists_managed_sessions_and_replaces_file() {
+        let windows = [
+            window(2, "web", true),
+            window(1, "api", true),
+            window(3, "scratch", false),
+        ];
+        let states = HashMap::from([(1, WindowState::Asking)]);
+        let pane_ids = HashMap::from([(1, "%3".to_string()), (2, "%5".to_string())]);
+        let event_ts = HashMap::from([(1, 100)]);
+        let asks = HashMap::from([(1, 2)]);
+        let sessions = sessions(&windows, &states, &pane_ids, &event_ts, &asks);
+        assert_eq!(
+            sessions
+                .iter()
+                .map(|s| (s.name.as_str(), s.state))
+                .collect::<Vec<_>>(),
+            [("api", "waiting"), ("web", "fresh")]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FEED_FILE);
+        fs::write(&path, "old").unwrap();
+        write_in(&path, &sessions, 200).unwrap();
+        let feed: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(feed["updated"], 200);
+        assert_eq!(feed["sessions"][0]["pane_id"], "%3");
+        assert_eq!(feed["sessions"][0]["asks"], 2);
+        assert_eq!(feed["sessions"][1]["active"], true);
+        assert!(feed["sessions"][1].get("since").is_none());
+        // No temp file left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,268 @@
+// ── git helpers ──
+//
+// Thin wrappers around the `git` CLI. Failures (no git, not a repository)
+// come back as None/Err so callers can fall back to plain directories.
+
+use std::path::Path;
+use std::process::Command;
+
+// ── Public API ──
+
+/// Run git in `dir`, returning stdout if the exit code is one of `ok_codes`.
+pub fn output(dir: &Path, args: &[&str], ok_codes: &[i32]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if output.status.code().is_some_and(|c| ok_codes.contains(&c)) {
+        Some(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        None
+    }
+}
+
+/// Run git in `dir`, with stderr as the error on failure.
+pub fn run(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("git: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {}: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Check out `branch` in a new worktree at `path`, creating the branch from
+/// HEAD if it doesn't exist yet.
+pub fn worktree_add(repo: &Path, path: &Path, branch: &str) -> Result<(), String> {
+    let path = path.to_string_lossy();
+    let exists = output(
+        repo,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{branch}"),
+        ],
+        &[0],
+    )
+    .is_some();
+    if exists {
+        run(repo, &["worktree", "add", &path, branch])?;
+    } else {
+        run(repo, &["worktree", "add", "-b", branch, &path])?;
+    }
+    Ok(())
+}
+
+/// Current branch of the repository containing `dir`.
+pub fn branch(dir: &Path) -> Option<String> {
+    let out = output(dir, &["rev-parse", "--abbrev-ref", "HEAD"], &[0])?;
+    let branch = out.trim().to_string();
+    (!branch.is_empty()).then_some(branch)
+}
+
+/// Top-level directory of the repository containing `dir`.
+pub fn toplevel(dir: &Path) -> Result<String, String> {
+    output(dir, &["rev-parse", "--show-toplevel"], &[0])
+        .map(|out| out.trim().to_string())
+        .filter(|top| !top.is_empty())
+        .ok_or_else(|| format!("'{}' is not inside a git repository", dir.display()))
+}
+
+/// Branch merges land on: origin's default branch, else local main/master.
+pub fn default_base(dir: &Path) -> Option<String> {
+    let remote = output(
+        dir,
+        &[
+            "symbolic-ref",
+            "--quiet",
+            "--short",
+            "refs/remotes/origin/HEAD",
+        ],
+        &[0],
+    )
+    .map(|out| out.trim().to_string())
+    .filter(|b| !b.is_empty());
+    remote.or_else(|| {
+        ["main", "master"]
+            .into_iter()
+            .find(|b| {
+                let head = format!("refs/heads/{b}");
+                output(dir, &["rev-parse", "--verify", "--quiet", &head], &[0]).is_some()
+            })
+            .map(String::from)
+    })
+}
+
+/// True if `branch` has had commits of its own and all of them are in `base`.
+/// A branch that never moved since it was created (per its reflog) doesn't
+/// count, so a fresh session isn't flagged the moment its base moves on.
+pub fn is_merged(dir: &Path, branch: &str, base: &str) -> bool {
+    let base_name = base.strip_prefix("origin/").unwrap_or(base);
+    if branch == base || branch == base_name || branch == "HEAD" {
+        return false;
+    }
+    let head = format!("refs/heads/{branch}");
+    let moved = output(dir, &["reflog", "show", "--format=%H", &head], &[0])
+        .is_some_and(|out| out.lines().count() > 1);
+    // --is-ancestor answers with its exit code alone
+    moved && output(dir, &["merge-base", "--is-ancestor", &head, base], &[0]).is_some()
+}
+
+/// Every worktree of the repository containing `dir`, the main one first.
+pub fn worktrees(dir: &Path) -> Vec<String> {
+    output(dir, &["worktree", "list", "--porcelain"], &[0])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.strip_prefix("worktree "))
+        .map(String::from)
+        .collect()
+}
+
+/// The worktree `branch` is checked out in, if any, in the repository
+/// containing `dir`.
+pub fn worktree_for_branch(dir: &Path, branch: &str) -> Option<String> {
+    let list = output(dir, &["worktree", "list", "--porcelain"], &[0])?;
+    find_branch(&list, branch)
+}
+
+/// The `worktree` line of the `git worktree list --porcelain` entry whose
+/// `branch` line is `branch`.
+fn find_branch(list: &str, branch: &str) -> Option<String> {
+    let head = format!("refs/heads/{branch}");
+    list.split("\n\n").find_map(|entry| {
+        let mut path = None;
+        let mut on_branch = false;
+        for line in entry.lines() {
+            if let Some(p) = line.strip_prefix("worktree ") {
+                path = Some(p.to_string());
+            } else if line.strip_prefix("branch ") == Some(head.as_str()) {
+                on_branch = true;
+            }
+        }
+        path.filter(|_| on_branch)
+    })
+}
+
+/// The repository's main worktree, from any of its worktrees.
+pub fn main_worktree(dir: &Path) -> Option<String> {
+    worktrees(dir).into_iter().next()
+}
+
+/// Name of the repository containing `dir`, with linked worktrees named after
+/// their main repository. Reads `.git` directly instead of running git, so
+/// it's cheap enough to call on every window refresh.
+pub fn repo_name(dir: &Path) -> Option<String> {
+    let root = dir.ancestors().find(|d| d.join(".git").exists())?;
+    let dot_git = root.join(".git");
+    let mut main = root.to_path_buf();
+    if dot_git.is_file() {
+        // A worktree's .git file reads "gitdir: <main>/.git/worktrees/<name>"
+        let content = std::fs::read_to_string(&dot_git).ok()?;
+        let gitdir = root.join(content.trim().strip_prefix("gitdir:")?.trim());
+        if let Some(linked) = gitdir.parent()
+            && linked.file_name().is_some_and(|n| n == "worktrees")
+            && let Some(repo) = linked.parent().and_then(Path::parent)
+        {
+            main = repo.to_path_buf();
+        }
+    }
+    Some(main.file_name()?.to_string_lossy().into_owned())
+}
+
+/// True if the checkout has uncommitted or untracked changes.
+pub fn is_dirty(dir: &Path) -> bool {
+    output(dir, &["status", "--porcelain"], &[0]).is_none_or(|out| !out.trim().is_empty())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worktree_add_new_and_existing_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(&repo).unwrap();
+        run(&repo, &["init", "-q"]).unwrap();
+        run(
+            &repo,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        )
+        .unwrap();
+
+        let wt = dir.path().join("repo-1");
+        worktree_add(&repo, &wt, "1-fix").unwrap();
+        assert_eq!(branch(&wt).as_deref(), Some("1-fix"));
+        assert_eq!(repo_name(&wt.join("src")).as_deref(), Some("repo"));
+        assert_eq!(repo_name(&repo).as_deref(), Some("repo"));
+
+        // Reusing the branch after its worktree is gone checks it out again
+        run(&repo, &["worktree", "remove", &wt.to_string_lossy()]).unwrap();
+        worktree_add(&repo, &wt, "1-fix").unwrap();
+        assert_eq!(branch(&wt).as_deref(), Some("1-fix"));
+    }
+
+    #[test]
+    fn test_is_merged_needs_own_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        let commit = |msg: &str| {
+            let args = ["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-q"];
+            let mut args = args.to_vec();
+            args.extend(["--allow-empty", "-m", msg]);
+            run(repo, &args).unwrap();
+        };
+        run(repo, &["init", "-q", "-b", "main"]).unwrap();
+        commit("init");
+        run(repo, &["branch", "fresh"]).unwrap();
+        run(repo, &["checkout", "-q", "-b", "feature"]).unwrap();
+        commit("work");
+        run(repo, &["checkout", "-q", "main"]).unwrap();
+        assert!(!is_merged(repo, "feature", "main"));
+
+        run(repo, &["merge", "-q", "--ff-only", "feature"]).unwrap();
+        assert!(is_merged(repo, "feature", "main"));
+        assert!(!is_merged(repo, "fresh", "main"));
+        assert!(!is_merged(repo, "main", "main"));
+        assert_eq!(default_base(repo).as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_find_branch() {
+        let list = "worktree /code/api\nHEAD 1a2b\nbranch refs/heads/main\n\n\
+                    worktree /code/api-7\nHEAD 3c4d\nbranch refs/heads/feat/login\n\n\
+                    worktree /code/api-old\nHEAD 5e6f\ndetached\n";
+        assert_eq!(find_branch(list, "main").as_deref(), Some("/code/api"));
+        assert_eq!(
+            find_branch(list, "feat/login").as_deref(),
+            Some("/code/api-7")
+        );
+        assert_eq!(find_branch(list, "feat"), None);
+    }
+
+    #[test]
+    fn test_toplevel_outside_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(toplevel(dir.path()).is_err());
+    }
+}
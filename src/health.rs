@@ -0,0 +1,205 @@
+// ── Stuck detection ──
+//
+// A Working session can't tell us it's stuck, but a few signals together make
+// it likely: no hook or tool activity for a long time, one tool call running
+// for ages, the same Bash command run over and over, and a transcript that
+// stopped growing. Each signal scores points; at `STUCK_SCORE` the sidebar
+// marks the row "stuck?" and offers to interrupt Claude or ask it what's
+// wrong. Long thinking alone (quiet, but nothing else) stays below the bar.
+
+use crate::cmdlog::CommandEntry;
+use crate::sidebar::format::format_age;
+use crate::tools::RunningTool;
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Signal {
+    /// No hook event or tool call for this many seconds.
+    Quiet(u64),
+    /// One tool call has been running this long.
+    LongTool { label: String, secs: u64 },
+    /// The last Bash commands this turn were all this one.
+    Repeating { command: String, times: usize },
+    /// The transcript hasn't been written to for this many seconds.
+    Silent(u64),
+}
+
+/// What a Working session's activity looks like, at `now` (Unix seconds).
+pub struct Activity<'a> {
+    pub now: u64,
+    /// Latest hook event: while Working, the prompt (or answer) that started
+    /// the current stretch of work.
+    pub last_event: u64,
+    /// Latest tool start or end.
+    pub last_tool: Option<u64>,
+    pub running: Option<&'a RunningTool>,
+    /// Bash commands the session ran, oldest first.
+    pub commands: &'a [CommandEntry],
+    /// When the transcript was last written.
+    pub transcript_mtime: Option<u64>,
+}
+
+// ── Constants ──
+
+/// Points at which a session counts as possibly stuck.
+pub const STUCK_SCORE: u32 = 2;
+
+/// Identical commands in a row that count as repeating.
+const REPEAT_RUN: usize = 3;
+
+/// Message sent by the sidebar's nudge key.
+pub const NUDGE: &str = "Are you stuck? Say what you're trying to do and what's blocking you.";
+
+// ── Helpers ──
+
+impl Signal {
+    fn points(&self) -> u32 {
+        match self {
+            Signal::Repeating { .. } => 2,
+            Signal::Quiet(_) | Signal::LongTool { .. } | Signal::Silent(_) => 1,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Signal::Quiet(secs) => format!("no activity for {}", format_age(*secs)),
+            Signal::LongTool { label, secs } => {
+                format!("{label} running for {}", format_age(*secs))
+            }
+            Signal::Repeating { command, times } => format!("ran `{command}` {times}\u{00d7}"),
+            Signal::Silent(secs) => format!("no output for {}", format_age(*secs)),
+        }
+    }
+}
+
+/// The command repeated at the end of `commands` since `since`, if it was run
+/// at least `REPEAT_RUN` times in a row.
+fn repeated(commands: &[CommandEntry], since: u64) -> Option<(String, usize)> {
+    let turn: Vec<&CommandEntry> = commands.iter().filter(|c| c.ts >= since).collect();
+    let last = turn.last()?;
+    let times = turn
+        .iter()
+        .rev()
+        .take_while(|c| c.command == last.command)
+        .count();
+    (times >= REPEAT_RUN).then(|| (last.command.clone(), times))
+}
+
+// ── Public API ──
+
+/// Signals that a Working session may be stuck, given how long (`after`
+/// seconds) counts as too long.
+pub fn signals(activity: &Activity, after: u64) -> Vec<Signal> {
+    let now = activity.now;
+    let mut out = Vec::new();
+    let last_seen = activity
+        .last_tool
+        .map_or(activity.last_event, |t| t.max(activity.last_event));
+    match activity.running {
+        Some(tool) if now.saturating_sub(tool.since) >= after => out.push(Signal::LongTool {
+            label: tool.label(),
+            secs: now - tool.since,
+        }),
+        None if now.saturating_sub(last_seen) >= after => {
+            out.push(Signal::Quiet(now - last_seen));
+        }
+        _ => {}
+    }
+    // Commands from a previous turn don't count: repeats across turns are
+    // usually the user asking for the same check again
+    if let Some((command, times)) = repeated(activity.commands, activity.last_event) {
+        out.push(Signal::Repeating { command, times });
+    }
+    if let Some(mtime) = activity.transcript_mtime
+        && now.saturating_sub(mtime) >= after
+    {
+        out.push(Signal::Silent(now - mtime));
+    }
+    out
+}
+
+/// Sum of the signals' points.
+pub fn score(signals: &[Signal]) -> u32 {
+    signals.iter().map(Signal::points).sum()
+}
+
+/// One line explaining why a session looks stuck.
+pub fn summary(signals: &[Signal]) -> String {
+    let reasons: Vec<String> = signals.iter().map(Signal::describe).collect();
+    format!("possibly stuck: {}", reasons.join(", "))
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(command: &str, ts: u64) -> CommandEntry {
+        CommandEntry {
+            command: command.to_string(),
+            cwd: String::new(),
+            interrupted: false,
+            pane_id: "%1".to_string(),
+            ts,
+        }
+    }
+
+    #[test]
+    fn test_quiet_and_silent_add_up_to_stuck() {
+        let activity = Activity {
+            now: 10_000,
+            last_event: 7_000,
+            last_tool: Some(7_200),
+            running: None,
+            commands: &[],
+            transcript_mtime: Some(7_300),
+        };
+        let found = signals(&activity, 1800);
+        assert_eq!(found, [Signal::Quiet(2800), Signal::Silent(2700)]);
+        assert!(score(&found) >= STUCK_SCORE);
+        assert_eq!(
+            summary(&found),
+            "possibly stuck: no activity for 46m, no output for 45m"
+        );
+
+        // Recent tool activity means it's still getting somewhere
+        let busy = Activity {
+            last_tool: Some(9_900),
+            transcript_mtime: Some(9_950),
+            ..activity
+        };
+        assert!(signals(&busy, 1800).is_empty());
+    }
+
+    #[test]
+    fn test_repeated_commands_in_this_turn() {
+        let commands = [
+            command("cargo test", 100),
+            command("cargo test", 5_000),
+            command("cargo test", 5_100),
+            command("cargo test", 5_200),
+        ];
+        let activity = Activity {
+            now: 5_300,
+            last_event: 4_900,
+            last_tool: Some(5_250),
+            running: None,
+            commands: &commands,
+            transcript_mtime: Some(5_250),
+        };
+        let found = signals(&activity, 1800);
+        assert_eq!(
+            found,
+            [Signal::Repeating {
+                command: "cargo test".to_string(),
+                times: 3,
+            }]
+        );
+        assert_eq!(score(&found), STUCK_SCORE);
+
+        let mixed = [command("cargo test", 5_000), command("cargo build", 5_100)];
+        assert_eq!(repeated(&mixed, 0), None);
+    }
+}
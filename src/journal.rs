@@ -0,0 +1,296 @@
+// ── Daily journal ──
+//
+// With `journal.path` set, every session that finishes appends an entry to
+// that day's markdown note: its name, how long Claude worked in it (the sum
+// of its Working stretches, not the time the window stood open), the
+// repository and branch, and Claude's last reply as the summary. A session
+// finishes when its Claude exits (the SessionEnd hook, same moment the
+// sidebar marks it done, but not the one `/clear` fires mid-session) or when
+// `cove kill` archives it; killing a session usually ends its Claude
+// too, so each entry carries its Claude session id in an HTML comment and a
+// conversation is written once a day.
+//
+// The path is an Obsidian-style template: each `{...}` part is a date in the
+// format inside it (`YYYY`, `YY`, `MM`, `DD`), so
+// `~/vault/Daily/{YYYY}/{YYYY-MM-DD}.md` keeps a note per day in a folder
+// per year. Missing folders are created.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::commands::timesheet;
+use crate::config::JournalConfig;
+use crate::sidebar::state::EventEntry;
+use crate::transcript::{self, Role};
+use crate::{git, paths};
+
+// ── Types ──
+
+/// A finished session, as it goes in the note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Finished {
+    name: String,
+    session_id: String,
+    cwd: String,
+    /// Unix time of its first and last recorded event.
+    started: u64,
+    ended: u64,
+    /// Seconds spent Working, summed over its turns.
+    worked: u64,
+    /// Claude's last reply, if the transcript has one.
+    summary: Option<String>,
+}
+
+// ── Constants ──
+
+/// Longest summary kept, in characters.
+const MAX_SUMMARY: usize = 400;
+
+const DAY: i64 = 86_400;
+
+// ── Helpers ──
+
+/// `template` with each `{...}` part replaced by the local date `day`
+/// ("YYYY-MM-DD") in the format inside it.
+fn note_path(template: &str, day: &str) -> PathBuf {
+    let (year, rest) = day.split_once('-').unwrap_or((day, ""));
+    let (month, date) = rest.split_once('-').unwrap_or((rest, ""));
+    let mut path = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        path.push_str(&rest[..start]);
+        let format = &rest[start + 1..start + len];
+        path.push_str(
+            &format
+                .replace("YYYY", year)
+                .replace("YY", year.get(2..).unwrap_or(year))
+                .replace("MM", month)
+                .replace("DD", date),
+        );
+        rest = &rest[start + len + 1..];
+    }
+    path.push_str(rest);
+    paths::expand_tilde(&path)
+}
+
+/// "45m", "1h 12m": how long a session ran.
+fn format_duration(secs: u64) -> String {
+    let mins = secs / 60;
+    match mins {
+        0 => "<1m".to_string(),
+        m if m < 60 => format!("{m}m"),
+        m => format!("{}h {}m", m / 60, m % 60),
+    }
+}
+
+/// The first paragraph of Claude's last reply, on one line and shortened.
+fn summary_of(entries: &[transcript::Entry]) -> Option<String> {
+    let reply = entries.iter().rev().find(|e| e.role == Role::Assistant)?;
+    let paragraph = reply.text.trim().split("\n\n").next()?;
+    let line = paragraph.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.is_empty() {
+        return None;
+    }
+    Some(match line.char_indices().nth(MAX_SUMMARY) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line,
+    })
+}
+
+/// Marker that ends an entry and tells whether a note has the session.
+fn marker(session_id: &str) -> String {
+    format!("<!-- cove:{session_id} -->")
+}
+
+/// The note text for `session`, started at local time of day `clock`.
+fn entry_text(session: &Finished, clock: i64) -> String {
+    let dir = Path::new(&session.cwd);
+    let repo = git::repo_name(dir).unwrap_or_else(|| session.cwd.clone());
+    let place = match git::branch(dir) {
+        Some(branch) => format!("{repo} · {branch}"),
+        None => repo,
+    };
+    let mut text = format!(
+        "\n### {:02}:{:02} {}\n\n- Duration: {}\n- Repo: {place}\n",
+        clock / 3600,
+        clock / 60 % 60,
+        session.name,
+        format_duration(session.worked),
+    );
+    if let Some(summary) = &session.summary {
+        text.push_str(&format!("- Summary: {summary}\n"));
+    }
+    text.push_str(&marker(&session.session_id));
+    text.push('\n');
+    text
+}
+
+/// Append `session` to the note at `path` unless it's already there.
+/// Returns whether it was added.
+fn append_to(path: &Path, session: &Finished, clock: i64) -> Result<bool, String> {
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    if existing.contains(&marker(&session.session_id)) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create {}: {e}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("open {}: {e}", path.display()))?;
+    file.write_all(entry_text(session, clock).as_bytes())
+        .map_err(|e| format!("write {}: {e}", path.display()))?;
+    Ok(true)
+}
+
+/// Seconds of Working in `events`: each stretch runs from the change into
+/// Working to the next event in another state, or to `now` for a turn
+/// cut off by the end. Milestones don't end a turn.
+fn working_secs(events: &[EventEntry], now: u64) -> u64 {
+    let mut since = None;
+    let mut total = 0;
+    for event in events.iter().filter(|e| !e.is_milestone()) {
+        match (event.state == "working", since) {
+            (true, None) => since = Some(event.ts),
+            (false, Some(start)) => {
+                total += event.ts.saturating_sub(start);
+                since = None;
+            }
+            _ => {}
+        }
+    }
+    total + since.map_or(0, |start| now.saturating_sub(start))
+}
+
+/// Read what the journal needs about Claude session `session_id` from its
+/// events file in `dir`: None if it never got a prompt.
+fn finished_in(dir: &Path, name: &str, session_id: &str, now: u64) -> Option<Finished> {
+    let content = fs::read_to_string(dir.join(format!("{session_id}.jsonl"))).ok()?;
+    let events: Vec<EventEntry> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    if !events.iter().any(|e| e.state == "working") {
+        return None;
+    }
+    let first = events.first()?;
+    let last = events.last()?;
+    Some(Finished {
+        name: name.to_string(),
+        session_id: session_id.to_string(),
+        cwd: last.cwd.clone(),
+        started: first.ts,
+        ended: now.max(last.ts),
+        worked: working_secs(&events, now.max(last.ts)),
+        summary: None,
+    })
+}
+
+// ── Public API ──
+
+/// Note that session `name` (Claude session `session_id`) finished, if
+/// journaling is on. Errors are for the caller to log; none are fatal.
+pub fn record(config: &JournalConfig, name: &str, session_id: &str) -> Result<(), String> {
+    let Some(template) = config.path.as_deref().filter(|p| !p.trim().is_empty()) else {
+        return Ok(());
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let Some(mut session) =
+        paths::events_dir().and_then(|dir| finished_in(&dir, name, session_id, now))
+    else {
+        return Ok(());
+    };
+    session.summary = transcript::load(session_id)
+        .ok()
+        .and_then(|entries| summary_of(&entries));
+
+    let offset = timesheet::utc_offset();
+    let local = session.ended as i64 + offset;
+    let path = note_path(template, &timesheet::format_day(local.div_euclid(DAY)));
+    let started = (session.started as i64 + offset).rem_euclid(DAY);
+    if append_to(&path, &session, started)? {
+        log::info!("journaled {name} in {}", path.display());
+    }
+    Ok(())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_path_fills_date_formats() {
+        let home = paths::home().unwrap();
+        assert_eq!(
+            note_path("~/vault/Daily/{YYYY}/{YYYY-MM-DD}.md", "2026-03-07"),
+            home.join("vault/Daily/2026/2026-03-07.md")
+        );
+        assert_eq!(
+            note_path("/notes/{DD.MM.YY} agents.md", "2026-03-07"),
+            PathBuf::from("/notes/07.03.26 agents.md")
+        );
+    }
+
+    #[test]
+    fn test_append_writes_each_session_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Daily/2026-03-07.md");
+        let session = Finished {
+            name: "api/fix-auth".to_string(),
+            session_id: "abc".to_string(),
+            cwd: dir.path().to_string_lossy().to_string(),
+            started: 1_000,
+            ended: 1_000 + 90 * 60,
+            worked: 72 * 60,
+            summary: summary_of(&[transcript::Entry {
+                role: Role::Assistant,
+                text: "Fixed the token refresh\nrace.\n\nDetails follow.".to_string(),
+            }]),
+        };
+        assert!(append_to(&path, &session, 14 * 3600 + 32 * 60).unwrap());
+        assert!(!append_to(&path, &session, 15 * 3600).unwrap());
+
+        let note = fs::read_to_string(&path).unwrap();
+        assert!(note.starts_with("\n### 14:32 api/fix-auth\n"), "{note}");
+        assert!(note.contains("- Duration: 1h 12m\n"), "{note}");
+        assert!(note.contains("- Summary: Fixed the token refresh race.\n"));
+        assert_eq!(note.matches("<!-- cove:abc -->").count(), 1);
+    }
+
+    #[test]
+    fn test_duration_sums_working_stretches() {
+        let event = |state: &str, ts: u64, hook: &str| EventEntry {
+            state: state.to_string(),
+            cwd: "/code/api".to_string(),
+            pane_id: "%1".to_string(),
+            ts,
+            hook: hook.to_string(),
+            note: String::new(),
+        };
+        let events = [
+            event("fresh", 0, "session-start"),
+            event("working", 100, "user-prompt"),
+            event("working", 150, "post-tool"),
+            event("working", 200, "milestone"),
+            event("idle", 400, "stop"),
+            // An hour idle doesn't count
+            event("working", 4_000, "user-prompt"),
+            event("asking", 4_060, "ask"),
+        ];
+        assert_eq!(working_secs(&events, 9_000), 300 + 60);
+        // A turn the session ended in runs to the end
+        assert_eq!(working_secs(&events[..6], 4_500), 300 + 500);
+    }
+}
@@ -0,0 +1,105 @@
+// ── Lock files ──
+//
+// Sidebars, hooks and commands all read, change and rewrite the same small
+// JSON files under ~/.cove. `with_lock` serializes that: it holds
+// `<file>.lock`, created exclusively, while the change runs, so two writers
+// can't each drop the other's update. A lock older than `STALE_SECS` was left
+// by a process that died mid-change and is taken over.
+
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+// ── Types ──
+
+/// A held lock, released when dropped (also on a panic in the change).
+struct Held(PathBuf);
+
+impl Drop for Held {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// ── Constants ──
+
+/// How long to wait for another process's lock before giving up.
+const WAIT: Duration = Duration::from_secs(2);
+
+/// How often to retry a held lock.
+const POLL: Duration = Duration::from_millis(10);
+
+/// Age at which a lock counts as abandoned.
+const STALE_SECS: u64 = 10;
+
+// ── Helpers ──
+
+fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+fn is_stale(lock: &Path) -> bool {
+    fs::metadata(lock)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .is_some_and(|age| age.as_secs() >= STALE_SECS)
+}
+
+fn acquire(path: &Path) -> Result<Held, String> {
+    let lock = lock_path(path);
+    let deadline = Instant::now() + WAIT;
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&lock) {
+            Ok(_) => return Ok(Held(lock)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                if is_stale(&lock) {
+                    let _ = fs::remove_file(&lock);
+                } else if Instant::now() >= deadline {
+                    return Err(format!("{} is locked", path.display()));
+                } else {
+                    thread::sleep(POLL);
+                }
+            }
+            Err(e) => return Err(format!("lock {}: {e}", path.display())),
+        }
+    }
+}
+
+// ── Public API ──
+
+/// Run `change` holding the lock on `path`, for a read-modify-write of it.
+pub fn with_lock<T>(path: &Path, change: impl FnOnce() -> T) -> Result<T, String> {
+    let _held = acquire(path)?;
+    Ok(change())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_exclusive_and_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("meta.json");
+        let lock = dir.path().join("meta.json.lock");
+
+        let inner = with_lock(&path, || {
+            assert!(lock.exists());
+            let started = Instant::now();
+            let inner = with_lock(&path, || ());
+            (inner, started.elapsed())
+        })
+        .unwrap();
+        assert!(inner.0.unwrap_err().contains("is locked"));
+        assert!(inner.1 >= WAIT);
+        assert!(!lock.exists());
+        assert_eq!(with_lock(&path, || 7), Ok(7));
+    }
+}
@@ -0,0 +1,241 @@
+// ── Diagnostic logging ──
+//
+// Diagnostics go through the `log` facade (`log::debug!` and friends), not
+// `eprintln!`, which stays for messages meant for the user. `COVE_LOG`
+// picks what's recorded, written like env_logger's filters: a default level
+// and per-module ones, e.g. `COVE_LOG=debug` or
+// `COVE_LOG=tmux=trace,sidebar::state=debug`. Modules are named by their
+// path in the crate (`tmux`, `sidebar::state`, `commands::hook`); a bare
+// module name means every level. Without `COVE_LOG` only warnings and
+// errors are recorded.
+//
+// Lines go to stderr, or with `COVE_LOG_FILE` are appended to that file.
+// The file is what makes logs useful from hooks (Claude drops their stderr),
+// and since every cove process can append to it, each line carries its pid.
+// Full-screen views (the sidebar, `cove top`) never log to stderr: it's the
+// terminal they draw in, and a stray warning would sit over the frame. They
+// append to `COVE_LOG_FILE`, else to `LOG_FILE` under the state root, else
+// nowhere.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::paths;
+
+// ── Types ──
+
+/// Which levels are recorded, per module.
+#[derive(Debug, PartialEq, Eq)]
+struct Filter {
+    default: LevelFilter,
+    /// Module paths and their levels, longest path first.
+    modules: Vec<(String, LevelFilter)>,
+}
+
+/// Where log lines are written.
+#[derive(Debug, PartialEq, Eq)]
+enum Sink {
+    Stderr,
+    File(PathBuf),
+    Discard,
+}
+
+struct Logger {
+    filter: Filter,
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+// ── Constants ──
+
+/// Prefix of this crate's log targets (the binary is `cove`).
+const CRATE_PREFIX: &str = "cove::";
+
+/// Log of full-screen views without `COVE_LOG_FILE`, under the state root.
+const LOG_FILE: &str = "cove.log";
+
+// ── Helpers ──
+
+impl Filter {
+    /// Parse a `COVE_LOG` value. Parts that don't parse are ignored, so a
+    /// typo records less rather than failing the command.
+    fn parse(spec: &str) -> Self {
+        let mut filter = Self {
+            default: LevelFilter::Warn,
+            modules: Vec::new(),
+        };
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.split_once('=') {
+                Some((module, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        filter.modules.push((module.trim().to_string(), level));
+                    }
+                }
+                None => match part.parse() {
+                    Ok(level) => filter.default = level,
+                    Err(_) => filter.modules.push((part.to_string(), LevelFilter::Trace)),
+                },
+            }
+        }
+        filter
+            .modules
+            .sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        filter
+    }
+
+    /// Level recorded for log target `target` ("cove::sidebar::state").
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let module = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+        self.modules
+            .iter()
+            .find(|(m, _)| {
+                module == m
+                    || module
+                        .strip_prefix(m.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    /// The most verbose level any module records.
+    fn max(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+/// The sink for `COVE_LOG_FILE` (`file`), with `tui` set for full-screen
+/// views, which never get stderr.
+fn sink(file: Option<PathBuf>, tui: bool) -> Sink {
+    match file {
+        Some(path) => Sink::File(path),
+        None if tui => paths::cove_path(LOG_FILE).map_or(Sink::Discard, Sink::File),
+        None => Sink::Stderr,
+    }
+}
+
+/// `HH:MM:SS.mmm`, UTC.
+fn timestamp() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = now.as_secs() % 86_400;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        now.subsec_millis()
+    )
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let module = record
+            .target()
+            .strip_prefix(CRATE_PREFIX)
+            .unwrap_or(record.target());
+        let line = format!(
+            "{} [{}] {:<5} {module}: {}\n",
+            timestamp(),
+            std::process::id(),
+            record.level(),
+            record.args()
+        );
+        if let Ok(mut out) = self.out.lock() {
+            // One write per line keeps appends from several processes whole
+            let _ = out.write_all(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut out) = self.out.lock() {
+            let _ = out.flush();
+        }
+    }
+}
+
+// ── Public API ──
+
+/// Install the logger configured by `COVE_LOG` and `COVE_LOG_FILE`. `tui`
+/// is set for full-screen views, which must not log to their terminal.
+pub fn init(tui: bool) {
+    let filter = Filter::parse(&std::env::var("COVE_LOG").unwrap_or_default());
+    let file = std::env::var("COVE_LOG_FILE")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from);
+    let out: Box<dyn Write + Send> = match sink(file, tui) {
+        Sink::File(path) => match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => Box::new(file),
+            Err(_) if tui => Box::new(io::sink()),
+            Err(e) => {
+                eprintln!("cove: can't open log file {}: {e}", path.display());
+                Box::new(io::stderr())
+            }
+        },
+        Sink::Stderr => Box::new(io::stderr()),
+        Sink::Discard => Box::new(io::sink()),
+    };
+    log::set_max_level(filter.max());
+    let _ = log::set_boxed_logger(Box::new(Logger {
+        filter,
+        out: Mutex::new(out),
+    }));
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_per_module_levels() {
+        let filter =
+            Filter::parse("info, tmux=trace ,sidebar::state=debug,sidebar=warn,bogus=loud");
+        assert_eq!(filter.default, LevelFilter::Info);
+        assert_eq!(filter.level_for("cove::tmux"), LevelFilter::Trace);
+        assert_eq!(filter.level_for("cove::tmux::fake"), LevelFilter::Trace);
+        // The longest matching module wins
+        assert_eq!(filter.level_for("cove::sidebar::state"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("cove::sidebar::app"), LevelFilter::Warn);
+        // A shared prefix isn't a parent module
+        assert_eq!(filter.level_for("cove::tmuxinator"), LevelFilter::Info);
+        assert_eq!(filter.level_for("ratatui::buffer"), LevelFilter::Info);
+        assert_eq!(filter.max(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn test_full_screen_views_never_log_to_stderr() {
+        let file = PathBuf::from("/tmp/cove-test.log");
+        assert_eq!(sink(None, false), Sink::Stderr);
+        assert_eq!(sink(Some(file.clone()), false), Sink::File(file.clone()));
+        assert_eq!(sink(Some(file.clone()), true), Sink::File(file));
+        assert_ne!(sink(None, true), Sink::Stderr);
+    }
+
+    #[test]
+    fn test_filter_defaults() {
+        let filter = Filter::parse("");
+        assert_eq!(filter.default, LevelFilter::Warn);
+        assert!(filter.modules.is_empty());
+        // A bare module name records everything from it
+        let filter = Filter::parse("commands::hook");
+        assert_eq!(filter.level_for("cove::commands::hook"), LevelFilter::Trace);
+        assert_eq!(filter.level_for("cove::tmux"), LevelFilter::Warn);
+    }
+}
@@ -1,32 +1,243 @@
+mod archive;
+mod attention;
+mod autotag;
+mod changes;
+mod ci;
+mod cleanup;
 mod cli;
+mod clips;
+mod cmdlog;
 mod colors;
 mod commands;
+mod config;
+mod container;
+mod crash;
+mod delegate;
+mod feed;
+mod git;
+mod health;
+mod journal;
+mod lockfile;
+mod logging;
+mod meta;
+mod names;
+mod notify;
+mod orphans;
+mod pager;
+mod paths;
+mod procs;
+mod profiles;
+mod prompts;
+mod quiet;
+mod quota;
+mod seed;
+mod send;
+mod sessions;
 mod sidebar;
+mod snapshots;
+mod stats;
+mod tabs;
+mod templates;
 mod tmux;
+mod tokens;
+mod tools;
+mod transcript;
+mod trash;
+mod watch;
+mod width;
+mod worktrees;
 
-use clap::Parser;
-use cli::{Cli, Command};
+use cli::{Command, LayoutAction};
+use commands::start::StartOptions;
 
 fn main() {
-    let cli = Cli::parse();
+    let cli = cli::parse();
+    // The sidebar and top draw on stderr's terminal; their logs go to a file
+    logging::init(matches!(
+        cli.command,
+        Some(Command::Sidebar { .. } | Command::Top)
+    ));
+    if cli.no_send {
+        send::disable();
+    }
+    if cli.quiet {
+        quiet::enable();
+    }
+    if cli.no_pager {
+        pager::disable();
+    }
 
     let result = match cli.command {
         Some(Command::List) => commands::list::run(),
-        Some(Command::Kill { name }) => commands::kill::run(&name),
-        Some(Command::AllKill) => commands::kill::run_all(),
+        Some(Command::Kill { name, keep_pane }) => commands::kill::run(&name, keep_pane),
+        Some(Command::Restart { name }) => commands::restart::run(&name),
+        Some(Command::Interrupt { name, then }) => commands::interrupt::run(&name, then.as_deref()),
+        Some(Command::Pair { a, b, end }) => commands::pair::run(a.as_deref(), b.as_deref(), end),
+        Some(Command::Annotate {
+            name,
+            note,
+            git_commit,
+            install_git_hook,
+        }) => commands::annotate::run(
+            name.as_deref(),
+            note.as_deref(),
+            git_commit,
+            install_git_hook,
+        ),
+        Some(Command::Quota) => commands::quota::run(),
+        Some(Command::Top) => commands::top::run(),
+        Some(Command::Timesheet {
+            week,
+            format,
+            email,
+        }) => commands::timesheet::run(week, format, email.as_deref()),
+        Some(Command::Layout {
+            action: Some(LayoutAction::Apply { name }),
+            ..
+        }) => commands::layout::apply(name.as_deref()),
+        Some(Command::Layout {
+            name: Some(name),
+            preset: Some(preset),
+            ..
+        }) => commands::layout::preset(&name, preset),
+        Some(Command::Layout { .. }) => Err(
+            "pick a preset (cove layout <name> --preset even|main-vertical|focus) or run cove layout apply"
+                .to_string(),
+        ),
+        Some(Command::Clean { name, options }) => commands::clean::run(&name, options),
+        Some(Command::Delegate { name, off, log }) => commands::delegate::run(&name, off, log),
+        Some(Command::Share {
+            name,
+            write,
+            backend,
+        }) => commands::share::run(name.as_deref(), backend, write),
+        Some(Command::Unshare { name }) => commands::share::unshare(&name),
+        Some(Command::Stats { all_time }) => commands::stats::run(all_time),
+        Some(Command::Du { prune }) => commands::du::run(prune),
+        Some(Command::OrphanScan { archive, delete }) => {
+            commands::orphan_scan::run(archive, delete)
+        }
+        Some(Command::Console) => commands::console::run(),
+        Some(Command::PromptSegment { shell }) => commands::prompt_segment::run(shell),
+        Some(Command::Metrics { serve }) => commands::metrics::run(serve.as_deref()),
+        Some(Command::Rollback {
+            name,
+            to,
+            list,
+            diff,
+            yes,
+            tell,
+        }) => commands::rollback::run(&name, to, list, diff, yes, tell),
+        Some(Command::Undo { name, list }) => commands::undo::run(name.as_deref(), list),
+        Some(Command::Restore { list, forget }) => {
+            commands::restore::run(list, forget.as_deref())
+        }
+        Some(Command::AllKill {
+            except,
+            state,
+            dry_run,
+            yes,
+        }) => commands::kill::run_all(&except, state, dry_run, yes),
+        Some(Command::Copy { name, text }) => commands::copy::run(&name, text.as_deref()),
+        Some(Command::Changes { name, files }) => commands::changes::run(&name, files),
+        Some(Command::Cmds { name }) => commands::cmds::run(&name),
+        Some(Command::Prompts {
+            name,
+            all,
+            search,
+            jump,
+            send,
+            to,
+        }) => {
+            let (jump, send) = (jump.as_deref(), send.as_deref());
+            let pick = match (jump, send) {
+                (Some(id), _) => Some(commands::prompts::Pick::Jump(id)),
+                (None, Some(id)) => Some(commands::prompts::Pick::Send {
+                    id,
+                    to: to.as_deref(),
+                }),
+                (None, None) => None,
+            };
+            commands::prompts::run(name.as_deref(), all, search.as_deref(), pick)
+        }
+        Some(Command::Bind { remove }) => commands::bind::run(remove),
+        Some(Command::Focus { name }) => commands::focus::run(name.as_deref()),
+        Some(Command::Send {
+            name,
+            text,
+            template,
+            vars,
+        }) => commands::send::run(&name, text.as_deref(), template.as_deref(), &vars),
+        Some(Command::AdoptBranch {
+            issue,
+            dir,
+            template,
+        }) => commands::adopt::run(&issue, dir.as_deref(), template.as_deref()),
+        Some(Command::Pin { name }) => commands::pin::run(&name),
+        Some(Command::Go { slot }) => commands::go::run(slot),
+        Some(Command::Rename { name, new_name }) => commands::rename::run(&name, &new_name),
+        Some(Command::Merge { from, into }) => {
+            commands::merge::run(from.as_deref(), into.as_deref())
+        }
+        Some(Command::NameCheck { name }) => commands::name_check::run(&name),
         Some(Command::Resume) => commands::resume::run(),
-        Some(Command::Sidebar) => sidebar::app::run(),
-        Some(Command::Hook { event }) => commands::hook::run(event),
-        Some(Command::Init) => commands::init::run(),
+        Some(Command::Completions { shell, install }) => commands::completions::run(shell, install),
+        Some(Command::Sidebar { plain }) => sidebar::app::run(plain),
+        Some(Command::Hook {
+            event,
+            capture_dir,
+            events_dir,
+        }) => commands::hook::run(event, capture_dir.as_deref(), events_dir),
+        Some(Command::Init {
+            project,
+            local,
+            bin,
+        }) => commands::init::run(project, local, bin.as_deref()),
+        Some(Command::Doctor) => commands::doctor::run(),
+        Some(Command::VerifyHooks {
+            fire_test,
+            claude,
+            timeout,
+        }) => commands::verify_hooks::run(fire_test, claude, timeout),
+        Some(Command::Up { file, prune }) => commands::up::run(&file, prune),
+        Some(Command::Watch {
+            name,
+            until,
+            then,
+            next,
+            timeout,
+        }) => commands::watch::run(&name, until, then.as_deref(), next, timeout),
+        Some(Command::WatchRun { name, cmd }) => commands::watch_run::run(&name, &cmd),
         None => {
             // Default behavior: start a session or resume
             match cli.name {
-                Some(name) => commands::start::run(&name, cli.dir.as_deref()),
+                Some(name) => match cli.backend {
+                    Some(backend) => {
+                        commands::start::run_tab(&name, cli.dir.as_deref(), cli.root, backend)
+                    }
+                    None => commands::start::run(
+                        &name,
+                        cli.dir.as_deref(),
+                        cli.root,
+                        &StartOptions {
+                            allow_duplicate_dir: cli.allow_duplicate_dir,
+                            container: cli.container.as_deref(),
+                            seed: cli.seed.as_deref(),
+                            seed_prompt: cli.seed_prompt,
+                            worktree: cli.worktree.as_deref(),
+                        },
+                    ),
+                },
                 None => {
                     if tmux::has_session() {
                         commands::resume::run()
                     } else {
-                        commands::start::run("session", Some("."))
+                        commands::start::run(
+                            "session",
+                            Some("."),
+                            false,
+                            &StartOptions::default(),
+                        )
                     }
                 }
             }
@@ -0,0 +1,460 @@
+// ── Per-session metadata ──
+//
+// Small user-set facts about sessions, keyed by session name, in
+// ~/.cove/meta.json. Entries survive killing and re-creating a session with
+// the same name (e.g. via `cove up`), so a pin sticks to the project rather
+// than to one tmux window.
+//
+// Quick-switch slots (1–9, for `cove go` and `prefix 1`–`9`) are handed out
+// here too: each open session without one gets the lowest slot no open
+// session holds, taking it over from a killed session if need be. A slot
+// stays with its session however tmux renumbers the windows.
+//
+// Every sidebar assigns slots on its window list while hooks and commands
+// update other fields, so changes hold meta.json's lock (see lockfile.rs)
+// around the read and the write, and the write goes through a rename.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::Preset;
+use crate::container::Container;
+use crate::seed::Seed;
+use crate::{lockfile, paths};
+
+// ── Types ──
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionMeta {
+    /// Keep this session at the top of the sidebar and `cove list`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// Issue the session was started for (`cove adopt-branch`), as a URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+    /// Git worktree created for the session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<String>,
+    /// Container Claude runs in (`cove <name> --container`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<Container>,
+    /// Task description the session was started from (`cove <name> --seed`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<Seed>,
+    /// Pane arrangement picked with `cove layout --preset`; None is cove's default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<Preset>,
+    /// tmux options and hooks cove set on the session's window, for
+    /// `cove clean --options` (`hook:` and `pane:` mark hooks and pane options).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tmux_options: Vec<String>,
+    /// Answer questions matching `delegate.rules` for this session (`cove delegate`).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub delegate: bool,
+    /// Quick-switch slot, 1–9 (`cove go`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u8>,
+}
+
+impl SessionMeta {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// ── Constants ──
+
+const META_FILE: &str = "meta.json";
+
+/// Quick-switch slots, one per digit key.
+const SLOTS: std::ops::RangeInclusive<u8> = 1..=9;
+
+// ── Helpers ──
+
+fn load_from(path: &Path) -> BTreeMap<String, SessionMeta> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_to(path: &Path, meta: &BTreeMap<String, SessionMeta>) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(meta).map_err(|e| format!("serialize metadata: {e}"))?;
+    let tmp = path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&tmp, content).map_err(|e| format!("write metadata: {e}"))?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("replace metadata: {e}")
+    })
+}
+
+/// Apply `change` to one session's entry, dropping entries left empty.
+fn update_in<T>(
+    path: &Path,
+    name: &str,
+    change: impl FnOnce(&mut SessionMeta) -> T,
+) -> Result<T, String> {
+    lockfile::with_lock(path, || {
+        let mut meta = load_from(path);
+        let result = change(meta.entry(name.to_string()).or_default());
+        meta.retain(|_, m| !m.is_empty());
+        save_to(path, &meta)?;
+        Ok(result)
+    })?
+}
+
+/// Move `old`'s entry to `new`, replacing whatever `new` had.
+fn rename_in(path: &Path, old: &str, new: &str) -> Result<(), String> {
+    lockfile::with_lock(path, || {
+        let mut meta = load_from(path);
+        let Some(entry) = meta.remove(old) else {
+            return Ok(());
+        };
+        meta.insert(new.to_string(), entry);
+        save_to(path, &meta)
+    })?
+}
+
+/// Give `new` what `old`'s entry says about the session itself: its pin,
+/// issue, seed, layout and delegation. The worktree, container, slot and
+/// recorded tmux options belong to `old`'s window and stay with it.
+fn copy_in(path: &Path, old: &str, new: &str) -> Result<(), String> {
+    let Some(from) = load_from(path).remove(old) else {
+        return Ok(());
+    };
+    update_in(path, new, |m| {
+        *m = SessionMeta {
+            pinned: from.pinned,
+            issue: from.issue,
+            seed: from.seed,
+            layout: from.layout,
+            delegate: from.delegate,
+            ..SessionMeta::default()
+        };
+    })
+}
+
+/// Add `options` to `name`'s recorded tmux options, writing only if any
+/// are new: it runs on every hook.
+fn record_options_in(path: &Path, name: &str, options: &[&str]) -> Result<(), String> {
+    let known = load_from(path)
+        .remove(name)
+        .map(|m| m.tmux_options)
+        .unwrap_or_default();
+    if options.iter().all(|o| known.iter().any(|k| k == o)) {
+        return Ok(());
+    }
+    update_in(path, name, |m| {
+        for option in options {
+            if !m.tmux_options.iter().any(|k| k == option) {
+                m.tmux_options.push(option.to_string());
+            }
+        }
+    })
+}
+
+/// Give each of the `live` sessions without a slot one in `meta`. Returns
+/// the live sessions' slots, and whether `meta` changed.
+fn plan_slots(
+    meta: &mut BTreeMap<String, SessionMeta>,
+    live: &[String],
+) -> (HashMap<String, u8>, bool) {
+    let mut held: HashMap<u8, String> = HashMap::new();
+    for name in live {
+        if let Some(slot) = meta.get(name).and_then(|m| m.slot)
+            && !held.contains_key(&slot)
+        {
+            held.insert(slot, name.clone());
+        }
+    }
+    let mut changed = false;
+    for name in live {
+        if held.values().any(|n| n == name) {
+            continue;
+        }
+        // Rather a slot nobody had than one a killed session may come back for
+        let unused = SLOTS
+            .clone()
+            .find(|s| !held.contains_key(s) && !meta.values().any(|m| m.slot == Some(*s)));
+        let Some(slot) = unused.or_else(|| SLOTS.clone().find(|s| !held.contains_key(s))) else {
+            break;
+        };
+        for m in meta.values_mut().filter(|m| m.slot == Some(slot)) {
+            m.slot = None;
+        }
+        meta.entry(name.clone()).or_default().slot = Some(slot);
+        held.insert(slot, name.clone());
+        changed = true;
+    }
+    if changed {
+        meta.retain(|_, m| !m.is_empty());
+    }
+    let slots = held.into_iter().map(|(slot, name)| (name, slot)).collect();
+    (slots, changed)
+}
+
+/// Give each of the `live` sessions without a slot one, writing only if any
+/// changed: it runs on every window list. Returns the live sessions' slots.
+fn assign_slots_in(path: &Path, live: &[String]) -> Result<HashMap<String, u8>, String> {
+    let (slots, changed) = plan_slots(&mut load_from(path), live);
+    if !changed {
+        return Ok(slots);
+    }
+    // Plan again under the lock: another sidebar may have just handed them out
+    lockfile::with_lock(path, || {
+        let mut meta = load_from(path);
+        let (slots, changed) = plan_slots(&mut meta, live);
+        if changed {
+            save_to(path, &meta)?;
+        }
+        Ok(slots)
+    })?
+}
+
+fn toggle_pin_in(path: &Path, name: &str) -> Result<bool, String> {
+    update_in(path, name, |m| {
+        m.pinned = !m.pinned;
+        m.pinned
+    })
+}
+
+/// Session `name`'s metadata; the defaults if it has none.
+fn get(name: &str) -> SessionMeta {
+    paths::cove_path(META_FILE)
+        .map(|path| load_from(&path))
+        .unwrap_or_default()
+        .remove(name)
+        .unwrap_or_default()
+}
+
+// ── Public API ──
+
+/// Names of pinned sessions.
+pub fn pinned() -> HashSet<String> {
+    paths::cove_path(META_FILE)
+        .map(|path| load_from(&path))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, m)| m.pinned)
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Pin or unpin a session. Returns true if it is now pinned.
+pub fn toggle_pin(name: &str) -> Result<bool, String> {
+    let path = paths::ensure_cove_dir("")?.join(META_FILE);
+    toggle_pin_in(&path, name)
+}
+
+/// Change a session's metadata in place.
+pub fn update(name: &str, change: impl FnOnce(&mut SessionMeta)) -> Result<(), String> {
+    let path = paths::ensure_cove_dir("")?.join(META_FILE);
+    update_in(&path, name, change)
+}
+
+/// Sessions with a recorded worktree, as (name, worktree) pairs. Entries
+/// outlive their windows, so killed sessions are included.
+pub fn worktrees() -> Vec<(String, String)> {
+    paths::cove_path(META_FILE)
+        .map(|path| load_from(&path))
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(name, m)| Some((name, m.worktree?)))
+        .collect()
+}
+
+/// Sessions with a recorded container, as (name, container) pairs.
+pub fn containers() -> Vec<(String, Container)> {
+    paths::cove_path(META_FILE)
+        .map(|path| load_from(&path))
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(name, m)| Some((name, m.container?)))
+        .collect()
+}
+
+/// The seed session `name` was started from, if any.
+pub fn seed(name: &str) -> Option<Seed> {
+    get(name).seed
+}
+
+/// The layout preset last picked for session `name`, if any.
+pub fn layout(name: &str) -> Option<Preset> {
+    get(name).layout
+}
+
+/// Record that cove set tmux `options` on session `name`'s window.
+pub fn record_options(name: &str, options: &[&str]) -> Result<(), String> {
+    let path = paths::ensure_cove_dir("")?.join(META_FILE);
+    record_options_in(&path, name, options)
+}
+
+/// The tmux options cove recorded setting on session `name`'s window.
+pub fn tmux_options(name: &str) -> Vec<String> {
+    get(name).tmux_options
+}
+
+/// Whether session `name` has delegate mode on.
+pub fn delegated(name: &str) -> bool {
+    get(name).delegate
+}
+
+/// Turn delegate mode on or off for session `name`.
+pub fn set_delegate(name: &str, on: bool) -> Result<(), String> {
+    update(name, |m| m.delegate = on)
+}
+
+/// The quick-switch slot of each of the `live` sessions (open windows, in
+/// index order), handing out free slots to those without one.
+pub fn assign_slots(live: &[String]) -> HashMap<String, u8> {
+    let assigned =
+        paths::ensure_cove_dir("").and_then(|dir| assign_slots_in(&dir.join(META_FILE), live));
+    assigned.unwrap_or_else(|e| {
+        log::warn!("assigning slots: {e}");
+        HashMap::new()
+    })
+}
+
+/// Forget session `name`'s recorded tmux options (its window is gone, or
+/// they were removed).
+pub fn forget_options(name: &str) {
+    if get(name).tmux_options.is_empty() {
+        return;
+    }
+    if let Err(e) = update(name, |m| m.tmux_options.clear()) {
+        log::warn!("forgetting tmux options of {name}: {e}");
+    }
+}
+
+/// Carry a session's metadata over to its new name.
+pub fn rename(old: &str, new: &str) -> Result<(), String> {
+    match paths::cove_path(META_FILE) {
+        Some(path) if path.exists() => rename_in(&path, old, new),
+        _ => Ok(()),
+    }
+}
+
+/// Start session `new` with a copy of `old`'s metadata, for a window that
+/// had to be renamed because another took its name.
+pub fn copy(old: &str, new: &str) -> Result<(), String> {
+    match paths::cove_path(META_FILE) {
+        Some(path) if path.exists() => copy_in(&path, old, new),
+        _ => Ok(()),
+    }
+}
+
+/// Move pinned items to the front, keeping the existing order within each group.
+pub fn pinned_first<T>(items: &mut [T], pinned: &HashSet<String>, name: impl Fn(&T) -> &str) {
+    items.sort_by_key(|item| !pinned.contains(name(item)));
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_pin_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(META_FILE);
+
+        assert!(toggle_pin_in(&path, "api").unwrap());
+        assert!(load_from(&path)["api"].pinned);
+
+        assert!(!toggle_pin_in(&path, "api").unwrap());
+        // Unpinned entries with nothing else set are dropped
+        assert!(load_from(&path).is_empty());
+    }
+
+    #[test]
+    fn test_rename_moves_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(META_FILE);
+        toggle_pin_in(&path, "api").unwrap();
+
+        rename_in(&path, "api", "web/api").unwrap();
+        let meta = load_from(&path);
+        assert!(meta["web/api"].pinned);
+        assert!(!meta.contains_key("api"));
+
+        // A session without metadata has nothing to move
+        rename_in(&path, "docs", "notes").unwrap();
+        assert_eq!(load_from(&path).len(), 1);
+    }
+
+    #[test]
+    fn test_copy_leaves_window_resources_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(META_FILE);
+        update_in(&path, "api", |m| {
+            m.pinned = true;
+            m.issue = Some("https://example.com/1".to_string());
+            m.worktree = Some("/code/api-wt".to_string());
+            m.slot = Some(2);
+        })
+        .unwrap();
+
+        copy_in(&path, "api", "api-2").unwrap();
+        let meta = load_from(&path);
+        assert!(meta["api-2"].pinned);
+        assert_eq!(meta["api-2"].issue, meta["api"].issue);
+        assert_eq!(meta["api-2"].worktree, None);
+        assert_eq!(meta["api-2"].slot, None);
+        assert_eq!(meta["api"].slot, Some(2));
+    }
+
+    #[test]
+    fn test_record_options_adds_only_new() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(META_FILE);
+        record_options_in(&path, "api", &["remain-on-exit", "hook:pane-died"]).unwrap();
+        record_options_in(&path, "api", &["hook:pane-died", "@cove-focus"]).unwrap();
+        assert_eq!(
+            load_from(&path)["api"].tmux_options,
+            ["remain-on-exit", "hook:pane-died", "@cove-focus"]
+        );
+
+        // Nothing new: the file isn't rewritten
+        let before = fs::metadata(&path).unwrap().modified().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        record_options_in(&path, "api", &["@cove-focus"]).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), before);
+    }
+
+    #[test]
+    fn test_slots_stay_put_and_reuse_killed_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(META_FILE);
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let slots = assign_slots_in(&path, &names(&["api", "web"])).unwrap();
+        assert_eq!(slots, HashMap::from([("api".into(), 1), ("web".into(), 2)]));
+
+        // `api` killed: a new session leaves its slot alone while others are free
+        let slots = assign_slots_in(&path, &names(&["web", "docs"])).unwrap();
+        assert_eq!(slots["web"], 2);
+        assert_eq!(slots["docs"], 3);
+        let slots = assign_slots_in(&path, &names(&["api", "web", "docs"])).unwrap();
+        assert_eq!(slots["api"], 1);
+
+        // With every slot held, one of a killed session is taken over
+        let many: Vec<String> = (0..9).map(|i| format!("s{i}")).collect();
+        let slots = assign_slots_in(&path, &many).unwrap();
+        assert_eq!(slots.len(), 9);
+        assert!(load_from(&path).get("api").is_none_or(|m| m.slot.is_none()));
+    }
+
+    #[test]
+    fn test_pinned_first_is_stable() {
+        let mut names = vec!["a", "b", "c", "d"];
+        let pinned: HashSet<String> = ["c", "d"].iter().map(|s| s.to_string()).collect();
+        pinned_first(&mut names, &pinned, |n| n);
+        assert_eq!(names, ["c", "d", "a", "b"]);
+    }
+}
@@ -0,0 +1,303 @@
+// ── Session name rules ──
+//
+// A session's name is its tmux window name, and cove builds targets like
+// `cove:<name>.1` from it. tmux splits targets at `:` and `.`, reads an
+// all-digit name as a window index and a leading `@`, `%`, `=` or `~` as
+// special syntax, so such names would address the wrong window (or none).
+// New sessions must have a name that works as a target; windows that already
+// have an awkward name are addressed by their ID instead (see
+// `TmuxClient::window_target`).
+//
+// A name may carry a tag: `backend/fix-auth` and `frontend/fix-auth` are two
+// sessions that share the short name `fix-auth`. Commands accept the short
+// name as long as only one window has it (`qualify`), and the sidebar shows
+// short names, qualifying only the ones that clash (`label`).
+
+use clap::CommandFactory;
+
+use crate::cli::Cli;
+
+// ── Constants ──
+
+/// Characters tmux gives a meaning to at the start of a target.
+const SPECIAL_PREFIXES: &[char] = &['@', '%', '=', '~', '{', '!', '+', '-', '^', '$'];
+
+/// Separates a tag from the short name: `backend/fix-auth`.
+const TAG_SEP: char = '/';
+
+// ── Helpers ──
+
+fn is_bad_char(c: char) -> bool {
+    matches!(c, '.' | ':' | '#' | '\'' | '"' | '\\') || c.is_whitespace() || c.is_control()
+}
+
+/// What is wrong with `part` (a tag or short name) on its own, if anything.
+fn part_problem(part: &str) -> Option<String> {
+    if part.chars().all(|c| c.is_ascii_digit()) {
+        return Some("is all digits, which tmux reads as a window index".to_string());
+    }
+    if let Some(c) = part.chars().next().filter(|c| SPECIAL_PREFIXES.contains(c)) {
+        return Some(format!(
+            "starts with '{c}', which tmux reads as target syntax"
+        ));
+    }
+    None
+}
+
+/// Subcommand names and aliases: `cove <name>` would run the command instead
+/// of starting the session.
+fn is_reserved(name: &str) -> bool {
+    Cli::command()
+        .get_subcommands()
+        .any(|sub| sub.get_name() == name || sub.get_all_aliases().any(|alias| alias == name))
+}
+
+// ── Public API ──
+
+/// Whether `name` can be put in a tmux target as is.
+pub fn is_plain_target(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(is_bad_char)
+        && !name.chars().all(|c| c.is_ascii_digit())
+        && !name.starts_with(SPECIAL_PREFIXES)
+}
+
+/// What is wrong with `name` as a new session name, if anything.
+pub fn problem(name: &str) -> Option<String> {
+    if name.is_empty() {
+        return Some("name is empty".to_string());
+    }
+    if let Some(c) = name.chars().find(|&c| is_bad_char(c)) {
+        let c = match c {
+            ' ' => "a space".to_string(),
+            c if c.is_whitespace() || c.is_control() => format!("{c:?}"),
+            c => format!("'{c}'"),
+        };
+        return Some(format!("contains {c}, which tmux can't target"));
+    }
+    let parts: Vec<&str> = name.split(TAG_SEP).collect();
+    if parts.len() > 2 || parts.iter().any(|p| p.is_empty()) {
+        return Some("needs the form tag/name, with one '/'".to_string());
+    }
+    for (i, part) in parts.iter().enumerate() {
+        if let Some(problem) = part_problem(part) {
+            return Some(match (parts.len(), i) {
+                (1, _) => problem,
+                (_, 0) => format!("has a tag that {problem}"),
+                _ => format!("has a short name that {problem}"),
+            });
+        }
+    }
+    if is_reserved(name) {
+        return Some(format!("is a cove command (`cove {name}` would run it)"));
+    }
+    None
+}
+
+/// `part` of a name with awkward characters turned into dashes.
+fn sanitize_part(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if !is_bad_char(c) {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    let out = out
+        .trim_start_matches(SPECIAL_PREFIXES)
+        .trim_end_matches('-');
+    if out.is_empty() {
+        "session".to_string()
+    } else if out.chars().all(|c| c.is_ascii_digit()) {
+        format!("{out}-session")
+    } else {
+        out.to_string()
+    }
+}
+
+/// A usable name close to `name`: awkward characters become dashes, and so
+/// does every '/' unless it separates a tag from the name.
+pub fn sanitize(name: &str) -> String {
+    match name.split_once(TAG_SEP) {
+        Some((tag, short)) if !tag.is_empty() && !short.is_empty() && !short.contains(TAG_SEP) => {
+            format!("{}/{}", sanitize_part(tag), sanitize_part(short))
+        }
+        _ => {
+            let out = sanitize_part(&name.replace(TAG_SEP, "-"));
+            if is_reserved(&out) {
+                format!("{out}-session")
+            } else {
+                out
+            }
+        }
+    }
+}
+
+/// A name without its tag: `fix-auth` for `backend/fix-auth`.
+pub fn short_name(name: &str) -> &str {
+    name.split_once(TAG_SEP).map_or(name, |(_, short)| short)
+}
+
+/// A name's tag: `backend` for `backend/fix-auth`, None for an untagged name.
+pub fn tag(name: &str) -> Option<&str> {
+    name.split_once(TAG_SEP).map(|(tag, _)| tag)
+}
+
+/// `name` as a file name or path component: a tag's `/` would make a
+/// subdirectory, so it's written `%2F` (and `%` as `%25`, to read it back).
+pub fn file_stem(name: &str) -> String {
+    name.replace('%', "%25").replace(TAG_SEP, "%2F")
+}
+
+/// The name `file_stem` was made from.
+pub fn from_file_stem(stem: &str) -> String {
+    stem.replace("%2F", "/").replace("%25", "%")
+}
+
+/// `name` under `tag`, unless it already has a tag of its own.
+pub fn with_tag(name: &str, tag: &str) -> String {
+    if name.contains(TAG_SEP) {
+        name.to_string()
+    } else {
+        format!("{tag}{TAG_SEP}{name}")
+    }
+}
+
+/// The window `target` names among `windows`: one with exactly that name, or
+/// else the only tagged one whose short name it is. Unknown names are
+/// returned as is, for the caller to report.
+pub fn qualify(target: &str, windows: &[String]) -> Result<String, String> {
+    if target.contains(TAG_SEP) || windows.iter().any(|w| w == target) {
+        return Ok(target.to_string());
+    }
+    let tagged: Vec<&String> = windows.iter().filter(|w| short_name(w) == target).collect();
+    match tagged.as_slice() {
+        [] => Ok(target.to_string()),
+        [one] => Ok(one.to_string()),
+        many => Err(format!(
+            "'{target}' could be {}; name one with its tag.",
+            many.iter()
+                .map(|w| w.as_str())
+                .collect::<Vec<_>>()
+                .join(" or ")
+        )),
+    }
+}
+
+/// How to show `name` next to the other window names: its short name, or
+/// the full `tag/name` when another window has the same short name.
+pub fn label<'a>(name: &'a str, names: &[&str]) -> &'a str {
+    let short = short_name(name);
+    let clashes = names
+        .iter()
+        .filter(|other| **other != name && short_name(other) == short)
+        .count();
+    if clashes > 0 { name } else { short }
+}
+
+/// Check a new session name, explaining the problem and suggesting a fix.
+pub fn validate(name: &str) -> Result<(), String> {
+    match problem(name) {
+        None => Ok(()),
+        Some(problem) => Err(format!(
+            "Session name '{name}' {problem}. Try '{}'.",
+            sanitize(name)
+        )),
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem() {
+        assert_eq!(problem("api-v2"), None);
+        assert_eq!(problem("feat_login"), None);
+        assert!(problem("").is_some());
+        assert!(problem("my.app").unwrap().contains("'.'"));
+        assert!(problem("a:b").unwrap().contains("':'"));
+        assert!(problem("fix bug").unwrap().contains("a space"));
+        assert!(problem("42").unwrap().contains("window index"));
+        assert!(problem("@home").unwrap().contains("'@'"));
+        assert!(problem("list").unwrap().contains("cove command"));
+        assert!(problem("ls").is_some());
+
+        assert_eq!(problem("backend/fix-auth"), None);
+        assert_eq!(problem("list/top"), None);
+        assert!(problem("a/b/c").unwrap().contains("one '/'"));
+        assert!(problem("/fix").is_some());
+        assert!(problem("backend/").is_some());
+        assert!(problem("backend/42").unwrap().contains("short name"));
+        assert!(problem("@x/fix").unwrap().contains("tag"));
+    }
+
+    #[test]
+    fn test_sanitize_gives_valid_names() {
+        assert_eq!(sanitize("my.app"), "my-app");
+        assert_eq!(sanitize("fix  login: now"), "fix-login-now");
+        assert_eq!(sanitize("42"), "42-session");
+        assert_eq!(sanitize("@home"), "home");
+        assert_eq!(sanitize("..."), "session");
+        assert_eq!(sanitize("top"), "top-session");
+        assert_eq!(sanitize("api/fix.auth"), "api/fix-auth");
+        assert_eq!(sanitize("a/b/c"), "a-b-c");
+        assert_eq!(sanitize("/list"), "list-session");
+        for name in [
+            "my.app", "a b", "42", "=x", ":", "list", "-.-", "a//b", "/", "x/7", "@/-",
+        ] {
+            assert_eq!(problem(&sanitize(name)), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_qualify_short_names() {
+        let windows: Vec<String> = ["backend/fix-auth", "frontend/fix-auth", "api/docs", "web"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(qualify("docs", &windows).unwrap(), "api/docs");
+        assert_eq!(qualify("web", &windows).unwrap(), "web");
+        assert_eq!(
+            qualify("frontend/fix-auth", &windows).unwrap(),
+            "frontend/fix-auth"
+        );
+        assert_eq!(qualify("gone", &windows).unwrap(), "gone");
+        let err = qualify("fix-auth", &windows).unwrap_err();
+        assert!(
+            err.contains("backend/fix-auth or frontend/fix-auth"),
+            "{err}"
+        );
+
+        // A window with exactly the short name wins over tagged ones
+        let windows = ["fix-auth".to_string(), "backend/fix-auth".to_string()];
+        assert_eq!(qualify("fix-auth", &windows).unwrap(), "fix-auth");
+    }
+
+    #[test]
+    fn test_label_qualifies_only_clashes() {
+        let names = ["backend/fix-auth", "frontend/fix-auth", "api/docs", "web"];
+        assert_eq!(label("backend/fix-auth", &names), "backend/fix-auth");
+        assert_eq!(label("api/docs", &names), "docs");
+        assert_eq!(label("web", &names), "web");
+    }
+
+    #[test]
+    fn test_file_stem_round_trips() {
+        assert_eq!(file_stem("api"), "api");
+        assert_eq!(file_stem("work/api"), "work%2Fapi");
+        assert_eq!(file_stem("50%/api"), "50%25%2Fapi");
+        for name in ["api", "work/api", "50%/api", "a%2Fb"] {
+            assert_eq!(from_file_stem(&file_stem(name)), name);
+        }
+    }
+
+    #[test]
+    fn test_validate_suggests_name() {
+        assert!(validate("api").is_ok());
+        let err = validate("my app").unwrap_err();
+        assert!(err.contains("'my-app'"), "{err}");
+    }
+}
@@ -0,0 +1,425 @@
+// ── Push notifications ──
+//
+// Alerts for sessions that need you, sent by the hook handler when a session
+// starts waiting on a question (asking) or finishes its turn (idle). Each rule
+// in `notify.rules` picks the states it fires on, how long the turn must have
+// been running and which backends to use, so short turns can stay quiet while
+// a long one pings your phone. The web backends are a single HTTPS request
+// made with `curl`: a Slack or Discord incoming webhook, or the Pushover API,
+// whose credentials reach curl on stdin rather than its command line.
+// The desktop backend shows a notification on this machine, through
+// terminal-notifier or osascript on macOS and notify-send elsewhere, with the
+// session's directory under the message.
+//
+// `notify.throttle_secs` keeps rapid hooks from repeating an alert: a session
+// entering the same state again that soon sends nothing. When each
+// (session, state) last alerted is kept in ~/.cove/notified.json, since every
+// hook is its own process.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde_json::json;
+
+use crate::config::{DesktopTool, NotifyBackend, NotifyConfig};
+use crate::paths;
+use crate::sidebar::format::format_age;
+
+// ── Constants ──
+
+const PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+
+const NOTIFIED_FILE: &str = "notified.json";
+
+const TITLE: &str = "cove";
+
+/// Give up on a request after this many seconds; hooks shouldn't linger.
+const TIMEOUT_SECS: &str = "10";
+
+// ── Helpers ──
+
+fn backend_name(backend: NotifyBackend) -> &'static str {
+    match backend {
+        NotifyBackend::Slack => "slack",
+        NotifyBackend::Discord => "discord",
+        NotifyBackend::Pushover => "pushover",
+        NotifyBackend::Desktop => "desktop",
+    }
+}
+
+fn on_path(program: &str) -> bool {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path).any(|dir| dir.join(program).is_file())
+}
+
+/// `text` as an AppleScript string literal.
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Program and arguments showing `body` under `title` with `tool`; `Auto`
+/// resolves per `macos` and what `installed` finds.
+fn desktop_command(
+    tool: DesktopTool,
+    title: &str,
+    body: &str,
+    macos: bool,
+    installed: impl Fn(&str) -> bool,
+) -> (&'static str, Vec<String>) {
+    let tool = match tool {
+        DesktopTool::Auto if !macos => DesktopTool::NotifySend,
+        DesktopTool::Auto if installed("terminal-notifier") => DesktopTool::TerminalNotifier,
+        DesktopTool::Auto => DesktopTool::Osascript,
+        tool => tool,
+    };
+    match tool {
+        DesktopTool::TerminalNotifier => (
+            "terminal-notifier",
+            vec![
+                "-title".to_string(),
+                title.to_string(),
+                "-message".to_string(),
+                body.to_string(),
+            ],
+        ),
+        DesktopTool::NotifySend => (
+            "notify-send",
+            vec![
+                format!("--app-name={TITLE}"),
+                "--".to_string(),
+                title.to_string(),
+                body.to_string(),
+            ],
+        ),
+        _ => (
+            "osascript",
+            vec![
+                "-e".to_string(),
+                format!(
+                    "display notification {} with title {}",
+                    applescript_string(body),
+                    applescript_string(title)
+                ),
+            ],
+        ),
+    }
+}
+
+/// True if `key` last alerted at least `secs` before `now`, per the times
+/// in `path`; if so, `now` is recorded for it.
+fn due_in(path: &Path, key: &str, now: u64, secs: u64) -> bool {
+    let mut sent: HashMap<String, u64> = fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    if sent
+        .get(key)
+        .is_some_and(|&at| now.saturating_sub(at) < secs)
+    {
+        return false;
+    }
+    // Entries past the window can't throttle anything
+    sent.retain(|_, at| now.saturating_sub(*at) < secs);
+    sent.insert(key.to_string(), now);
+    // Through a temp file and a rename: hooks firing together mustn't read
+    // a half-written file
+    if let Ok(content) = serde_json::to_string(&sent) {
+        let tmp = path.with_extension(format!("json.{}", std::process::id()));
+        if fs::write(&tmp, content).is_err() || fs::rename(&tmp, path).is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+    true
+}
+
+/// `s` as a quoted string in a curl config file.
+fn curl_quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("\"{escaped}\"")
+}
+
+/// `curl` arguments delivering `text` through `backend`, and the curl config
+/// to feed it on stdin (`-K -`): credentials go there, out of sight of `ps`.
+fn curl_args(
+    config: &NotifyConfig,
+    backend: NotifyBackend,
+    text: &str,
+) -> Result<(Vec<String>, String), String> {
+    let missing = || format!("notify.{} is not configured", backend_name(backend));
+    let mut args: Vec<String> = ["-sS", "--fail", "--max-time", TIMEOUT_SECS, "-K", "-"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let mut secrets = String::new();
+    let webhook = |url: &str, body: serde_json::Value| {
+        [
+            "-H".to_string(),
+            "Content-Type: application/json".to_string(),
+            "-d".to_string(),
+            body.to_string(),
+            url.to_string(),
+        ]
+    };
+    match backend {
+        NotifyBackend::Desktop => return Err("desktop notifications don't use curl".to_string()),
+        NotifyBackend::Slack => {
+            let slack = config.slack.as_ref().ok_or_else(missing)?;
+            args.extend(webhook(&slack.webhook, json!({ "text": text })));
+        }
+        NotifyBackend::Discord => {
+            let discord = config.discord.as_ref().ok_or_else(missing)?;
+            args.extend(webhook(&discord.webhook, json!({ "content": text })));
+        }
+        NotifyBackend::Pushover => {
+            let pushover = config.pushover.as_ref().ok_or_else(missing)?;
+            // --form-string sends values verbatim; plain -F reads "@file" and "<file"
+            for field in [
+                format!("token={}", pushover.token),
+                format!("user={}", pushover.user),
+            ] {
+                secrets.push_str(&format!("form-string = {}\n", curl_quote(&field)));
+            }
+            for field in ["title=cove".to_string(), format!("message={text}")] {
+                args.push("--form-string".to_string());
+                args.push(field);
+            }
+            args.push(PUSHOVER_URL.to_string());
+        }
+    }
+    Ok((args, secrets))
+}
+
+// ── Public API ──
+
+/// Backends the rules pick for a session entering `state`, after a turn of
+/// `turn_secs` (unknown counts as zero). Each backend appears once.
+pub fn backends(config: &NotifyConfig, state: &str, turn_secs: Option<u64>) -> Vec<NotifyBackend> {
+    let turn = turn_secs.unwrap_or(0);
+    let mut picked = Vec::new();
+    let matching = config
+        .rules
+        .iter()
+        .filter(|rule| rule.states.iter().any(|s| s == state) && turn >= rule.min_turn_secs);
+    for backend in matching.flat_map(|rule| rule.via.iter().copied()) {
+        if !picked.contains(&backend) {
+            picked.push(backend);
+        }
+    }
+    picked
+}
+
+/// The alert text for session `name` entering `state`.
+pub fn message(name: &str, state: &str, turn_secs: Option<u64>) -> String {
+    let what = match state {
+        "asking" => "is waiting for your answer",
+        "idle" => "finished its turn",
+        _ => state,
+    };
+    match turn_secs {
+        Some(secs) if secs > 0 => format!("{name} {what} after {}", format_age(secs)),
+        _ => format!("{name} {what}"),
+    }
+}
+
+/// True if session `name` entering `state` should alert now, given
+/// `throttle_secs`; records the alert if so.
+pub fn due(name: &str, state: &str, throttle_secs: u64) -> bool {
+    if throttle_secs == 0 {
+        return true;
+    }
+    let Ok(dir) = paths::ensure_cove_dir("") else {
+        return true;
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    due_in(
+        &dir.join(NOTIFIED_FILE),
+        &format!("{name}:{state}"),
+        now,
+        throttle_secs,
+    )
+}
+
+/// Deliver `text` through one backend. The desktop one also shows `dir`,
+/// the session's directory, when known.
+pub fn send(
+    config: &NotifyConfig,
+    backend: NotifyBackend,
+    text: &str,
+    dir: Option<&str>,
+) -> Result<(), String> {
+    let (program, args, input) = match backend {
+        NotifyBackend::Desktop => {
+            let body = match dir {
+                Some(dir) => format!("{text}\n{}", paths::tildify(dir)),
+                None => text.to_string(),
+            };
+            let (program, args) = desktop_command(
+                config.desktop.tool,
+                TITLE,
+                &body,
+                cfg!(target_os = "macos"),
+                on_path,
+            );
+            (program, args, None)
+        }
+        _ => {
+            let (args, secrets) = curl_args(config, backend, text)?;
+            ("curl", args, Some(secrets))
+        }
+    };
+    let mut child = Command::new(program)
+        .args(&args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("run {program}: {e}"))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| format!("run {program}: {e}"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("run {program}: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} notification failed: {}",
+            backend_name(backend),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AlertRule, PushoverConfig, WebhookConfig};
+
+    fn config() -> NotifyConfig {
+        NotifyConfig {
+            slack: Some(WebhookConfig {
+                webhook: "https://hooks.slack.test/x".to_string(),
+            }),
+            discord: None,
+            pushover: Some(PushoverConfig {
+                token: "tok".to_string(),
+                user: "usr".to_string(),
+            }),
+            rules: vec![
+                AlertRule {
+                    states: vec!["asking".to_string()],
+                    min_turn_secs: 0,
+                    via: vec![NotifyBackend::Slack],
+                },
+                AlertRule {
+                    min_turn_secs: 600,
+                    via: vec![NotifyBackend::Pushover, NotifyBackend::Slack],
+                    ..AlertRule::default()
+                },
+            ],
+            ..NotifyConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_backends_follow_rules() {
+        let config = config();
+        assert_eq!(backends(&config, "asking", None), [NotifyBackend::Slack]);
+        assert!(backends(&config, "idle", Some(60)).is_empty());
+        assert_eq!(
+            backends(&config, "asking", Some(900)),
+            [NotifyBackend::Slack, NotifyBackend::Pushover]
+        );
+        assert!(backends(&config, "working", Some(900)).is_empty());
+    }
+
+    #[test]
+    fn test_curl_args_per_backend() {
+        let config = config();
+        let (slack, _) = curl_args(&config, NotifyBackend::Slack, "api finished").unwrap();
+        assert_eq!(slack.last().unwrap(), "https://hooks.slack.test/x");
+        assert!(slack.contains(&r#"{"text":"api finished"}"#.to_string()));
+
+        let (pushover, secrets) = curl_args(&config, NotifyBackend::Pushover, "@home").unwrap();
+        assert!(pushover.contains(&"message=@home".to_string()));
+        assert_eq!(pushover.last().unwrap(), PUSHOVER_URL);
+        // Credentials go on stdin, not the command line
+        assert!(
+            !pushover
+                .iter()
+                .any(|a| a.contains("tok") || a.contains("usr"))
+        );
+        assert_eq!(
+            secrets,
+            "form-string = \"token=tok\"\nform-string = \"user=usr\"\n"
+        );
+        assert_eq!(curl_quote(r#"a"b\c"#), r#""a\"b\\c""#);
+
+        let err = curl_args(&config, NotifyBackend::Discord, "x").unwrap_err();
+        assert!(err.contains("notify.discord"), "{err}");
+    }
+
+    #[test]
+    fn test_message() {
+        assert_eq!(
+            message("api", "asking", Some(720)),
+            "api is waiting for your answer after 12m"
+        );
+        assert_eq!(message("web", "idle", None), "web finished its turn");
+    }
+
+    #[test]
+    fn test_desktop_command_per_platform() {
+        let none = |_: &str| false;
+        let (program, args) =
+            desktop_command(DesktopTool::Auto, "cove", "api finished", false, none);
+        assert_eq!(program, "notify-send");
+        assert_eq!(args[2..], ["cove", "api finished"]);
+
+        let (program, args) =
+            desktop_command(DesktopTool::Auto, "cove", r#"say "hi" \ bye"#, true, none);
+        assert_eq!(program, "osascript");
+        assert_eq!(
+            args[1],
+            r#"display notification "say \"hi\" \\ bye" with title "cove""#
+        );
+
+        let installed = |p: &str| p == "terminal-notifier";
+        let (program, _) = desktop_command(DesktopTool::Auto, "cove", "x", true, installed);
+        assert_eq!(program, "terminal-notifier");
+        // An explicit tool is used as is
+        let (program, _) = desktop_command(DesktopTool::Osascript, "cove", "x", false, installed);
+        assert_eq!(program, "osascript");
+    }
+
+    #[test]
+    fn test_due_throttles_per_session_and_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(NOTIFIED_FILE);
+        assert!(due_in(&path, "api:idle", 1000, 30));
+        assert!(!due_in(&path, "api:idle", 1010, 30));
+        // Another state or session isn't held back
+        assert!(due_in(&path, "api:asking", 1010, 30));
+        assert!(due_in(&path, "web:idle", 1010, 30));
+        assert!(due_in(&path, "api:idle", 1030, 30));
+    }
+}
@@ -0,0 +1,259 @@
+// ── Orphaned event files ──
+//
+// An event file is kept in ~/.cove/events/ while its session's pane lives;
+// `cove kill` moves it to the history when the window goes. Panes that die
+// any other way — tmux killed, the machine rebooted, a window closed by hand,
+// Claude run outside cove — leave their files behind, and every state
+// detection keeps reading them. An orphan is a file whose latest event names
+// a pane tmux (any session on the server) and the terminal tabs no longer
+// have, and that hasn't been written to for `MIN_AGE_SECS`.
+//
+// tmux numbers panes from %0 again when its server restarts, so a live pane
+// id doesn't prove a file's pane lives. A tmux pane's file also counts as
+// orphaned when its latest event predates the server, or when a newer file
+// names the same pane (the session `state::session_for_pane` would pick).
+//
+// `cove orphan-scan` reports them; `--archive` moves them to the history
+// (where `cove timesheet` still counts them), `--delete` removes them. With
+// `cleanup.orphan_scan_hours`, the sidebar archives them on its own that
+// often, sidebars sharing one timestamp in ~/.cove/orphan-scan.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::sidebar::state::{self, EventEntry};
+use crate::tmux::TmuxClient;
+use crate::{paths, tabs};
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Orphan {
+    pub path: PathBuf,
+    /// Claude session id: the file's name.
+    pub session_id: String,
+    /// Pane of the latest event; empty for Claude run outside tmux.
+    pub pane_id: String,
+    /// State the latest event recorded ("idle", "working", …).
+    pub state: String,
+    pub ts: u64,
+}
+
+// ── Constants ──
+
+/// A file written to this recently isn't an orphan yet, whatever its pane.
+const MIN_AGE_SECS: u64 = 600;
+
+const STAMP_FILE: &str = "orphan-scan";
+
+// ── Helpers ──
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Orphaned event files in `dir`, given the `live` panes and when the tmux
+/// server started (0 if unknown), oldest first.
+fn find_in(dir: &Path, live: &HashSet<String>, server_start: u64, now: u64) -> Vec<Orphan> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let files: Vec<(PathBuf, EventEntry)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .filter_map(|path| {
+            let event = state::read_last_event(&path)?;
+            Some((path, event))
+        })
+        .collect();
+    let mut newest: HashMap<String, u64> = HashMap::new();
+    for (_, event) in &files {
+        let ts = newest.entry(event.pane_id.clone()).or_default();
+        *ts = (*ts).max(event.ts);
+    }
+    let is_live = |event: &EventEntry| {
+        let tmux = event.pane_id.starts_with('%');
+        live.contains(&event.pane_id)
+            && !(tmux && event.ts < server_start)
+            && !(tmux && newest[&event.pane_id] > event.ts)
+    };
+    let mut orphans: Vec<Orphan> = files
+        .into_iter()
+        .filter(|(_, event)| !is_live(event) && now.saturating_sub(event.ts) >= MIN_AGE_SECS)
+        .filter_map(|(path, event)| {
+            Some(Orphan {
+                session_id: path.file_stem()?.to_string_lossy().to_string(),
+                path,
+                pane_id: event.pane_id,
+                state: event.state,
+                ts: event.ts,
+            })
+        })
+        .collect();
+    orphans.sort_by_key(|o| o.ts);
+    orphans
+}
+
+/// Move `orphans` into `history`, or remove them without one. Returns how
+/// many went.
+fn archive_in(orphans: &[Orphan], history: Option<&Path>) -> usize {
+    orphans
+        .iter()
+        .filter(|o| {
+            let moved = history
+                .zip(o.path.file_name())
+                .is_some_and(|(history, file)| fs::rename(&o.path, history.join(file)).is_ok());
+            moved || fs::remove_file(&o.path).is_ok()
+        })
+        .count()
+}
+
+/// Whether the stamp at `path` is at least `hours` old (or missing).
+fn is_due(path: &Path, hours: u64, now: SystemTime) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_or(true, |t| {
+            now.duration_since(t)
+                .is_ok_and(|age| age >= Duration::from_secs(hours * 3600))
+        })
+}
+
+// ── Public API ──
+
+/// Every pane a session could still be writing events from: all tmux panes
+/// on the server and the terminal tabs' tokens. Fails if tmux is running
+/// but can't be asked, rather than taking every pane for dead.
+pub fn live_panes(client: &dyn TmuxClient) -> Result<HashSet<String>, String> {
+    let mut live: HashSet<String> = match client.output(&["list-panes", "-a", "-F", "#{pane_id}"]) {
+        Ok(out) => out.lines().map(str::to_string).collect(),
+        Err(_) if !client.has_session() => HashSet::new(),
+        Err(e) => return Err(e),
+    };
+    live.extend(tabs::sessions().into_iter().map(|t| t.token));
+    Ok(live)
+}
+
+/// When the tmux server started, in Unix seconds; 0 if it can't be asked.
+fn server_start(client: &dyn TmuxClient) -> u64 {
+    client
+        .output(&["display-message", "-p", "#{start_time}"])
+        .ok()
+        .and_then(|out| out.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Orphaned event files, oldest first.
+pub fn find(client: &dyn TmuxClient) -> Result<Vec<Orphan>, String> {
+    let live = live_panes(client)?;
+    let start = server_start(client);
+    Ok(paths::events_dir()
+        .map(|dir| find_in(&dir, &live, start, now()))
+        .unwrap_or_default())
+}
+
+/// Move `orphans` into the history `cove timesheet` reads.
+pub fn archive(orphans: &[Orphan]) -> usize {
+    let history = state::history_dir().filter(|dir| fs::create_dir_all(dir).is_ok());
+    let archived = archive_in(orphans, history.as_deref());
+    state::prune_history();
+    archived
+}
+
+/// Remove `orphans` for good.
+pub fn delete(orphans: &[Orphan]) -> usize {
+    archive_in(orphans, None)
+}
+
+/// Archive orphans if no scan ran in the last `hours` (0 never scans).
+pub fn scan_if_due(client: &dyn TmuxClient, hours: u64) {
+    let Some(stamp) = paths::cove_path(STAMP_FILE) else {
+        return;
+    };
+    if hours == 0 || !is_due(&stamp, hours, SystemTime::now()) {
+        return;
+    }
+    // Stamped first, so other sidebars skip this round
+    if fs::write(&stamp, "").is_err() {
+        return;
+    }
+    match find(client) {
+        Ok(orphans) if !orphans.is_empty() => {
+            log::info!("archived {} orphaned event files", archive(&orphans));
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("orphan scan: {e}"),
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_event(dir: &Path, session: &str, pane_id: &str, ts: u64) {
+        fs::write(
+            dir.join(format!("{session}.jsonl")),
+            format!(r#"{{"state":"idle","cwd":"/tmp","pane_id":"{pane_id}","ts":{ts}}}"#) + "\n",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_orphans_are_old_files_of_dead_panes() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events");
+        fs::create_dir_all(&events).unwrap();
+        write_event(&events, "live", "%1", 1_000);
+        write_event(&events, "dead", "%2", 2_000);
+        write_event(&events, "outside", "", 1_500);
+        write_event(&events, "recent", "%3", 9_900);
+        fs::write(events.join("notes.txt"), "").unwrap();
+
+        let live = HashSet::from(["%1".to_string()]);
+        let orphans = find_in(&events, &live, 0, 10_000);
+        let ids: Vec<&str> = orphans.iter().map(|o| o.session_id.as_str()).collect();
+        assert_eq!(ids, ["outside", "dead"]);
+        assert_eq!(orphans[1].pane_id, "%2");
+        assert_eq!(orphans[1].state, "idle");
+
+        let history = dir.path().join("history");
+        fs::create_dir_all(&history).unwrap();
+        assert_eq!(archive_in(&orphans, Some(&history)), 2);
+        assert!(history.join("dead.jsonl").exists());
+        assert!(!events.join("dead.jsonl").exists());
+        assert!(events.join("live.jsonl").exists());
+    }
+
+    #[test]
+    fn test_reused_pane_ids_dont_keep_files_alive() {
+        let dir = tempfile::tempdir().unwrap();
+        write_event(dir.path(), "before-restart", "%1", 1_000);
+        write_event(dir.path(), "cleared", "%2", 4_000);
+        write_event(dir.path(), "current", "%2", 6_000);
+
+        // The server started at 3000: %1 is another pane now, and %2's
+        // latest session is `current`
+        let live = HashSet::from(["%1".to_string(), "%2".to_string()]);
+        let orphans = find_in(dir.path(), &live, 3_000, 10_000);
+        let ids: Vec<&str> = orphans.iter().map(|o| o.session_id.as_str()).collect();
+        assert_eq!(ids, ["before-restart", "cleared"]);
+    }
+
+    #[test]
+    fn test_scan_is_due_after_interval() {
+        let dir = tempfile::tempdir().unwrap();
+        let stamp = dir.path().join(STAMP_FILE);
+        let now = SystemTime::now();
+        assert!(is_due(&stamp, 24, now));
+        fs::write(&stamp, "").unwrap();
+        assert!(!is_due(&stamp, 24, now));
+        assert!(is_due(&stamp, 24, now + Duration::from_secs(25 * 3600)));
+    }
+}
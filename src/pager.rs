@@ -0,0 +1,131 @@
+// ── Pager ──
+//
+// Commands whose output can run long (`cove changes`, `cmds`, `prompts`)
+// build it as one string and hand it to `show`. On a terminal, output taller
+// than the screen goes through `$COVE_PAGER`, `$PAGER` or `less`, with
+// `LESS=FRX` unless `LESS` is set, so colors come through and a pager that
+// isn't needed gets out of the way. `--no-pager` (or `no_pager: true` in the
+// config) always prints directly. Colors are ANSI codes written by the
+// commands; they're stripped when stdout isn't a terminal or `NO_COLOR` is
+// set, so piping to a file or `grep` gets plain text.
+
+use std::borrow::Cow;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config;
+
+// ── Constants ──
+
+/// Set by `--no-pager` for the rest of this process.
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// `no_pager` from the config file, read once.
+static FROM_CONFIG: OnceLock<bool> = OnceLock::new();
+
+const DEFAULT_PAGER: &str = "less";
+
+// ── Helpers ──
+
+/// `text` without ANSI escape sequences (CSI `ESC [ ... <final>`).
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+        if chars.next() == Some('[') {
+            // Parameters and intermediates, up to the final byte
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    plain
+}
+
+/// The pager command: `$COVE_PAGER`, `$PAGER`, or less. Empty means none.
+fn command() -> String {
+    ["COVE_PAGER", "PAGER"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string())
+}
+
+/// Whether output of `lines` lines should be paged on a screen `rows` tall.
+fn needs_paging(lines: usize, rows: u16) -> bool {
+    lines >= rows as usize
+}
+
+/// Run `pager` with `text` on its stdin. Err if it can't be started.
+fn run_pager(pager: &str, text: &str) -> io::Result<()> {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", pager]).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        cmd.env("LESS", "FRX");
+    }
+    let mut child = cmd.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Quitting the pager early closes the pipe; nothing more to show then
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+fn disabled() -> bool {
+    DISABLED.load(Ordering::Relaxed)
+        || *FROM_CONFIG.get_or_init(|| config::load().is_ok_and(|c| c.no_pager))
+}
+
+// ── Public API ──
+
+/// Turn off paging for this process (`--no-pager`).
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Print `text`, through the pager if it's longer than the terminal.
+pub fn show(text: &str) {
+    let tty = io::stdout().is_terminal();
+    let text = if tty && std::env::var_os("NO_COLOR").is_none() {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(strip_ansi(text))
+    };
+    let pager = command();
+    if tty && !disabled() && !pager.trim().is_empty() {
+        let rows = crossterm::terminal::size().map_or(u16::MAX, |(_, rows)| rows);
+        if needs_paging(text.lines().count(), rows) {
+            match run_pager(&pager, &text) {
+                Ok(()) => return,
+                Err(e) => log::warn!("pager {pager}: {e}"),
+            }
+        }
+    }
+    print!("{text}");
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi_and_paging_threshold() {
+        let colored = "\x1b[1m\x1b[38;2;205;214;244m~/code/api\x1b[0m\n  \x1b[38;2;166;227;161m\u{2713}\x1b[0m";
+        assert_eq!(strip_ansi(colored), "~/code/api\n  \u{2713}");
+        assert_eq!(strip_ansi("plain"), "plain");
+
+        // The prompt line takes the last row
+        assert!(!needs_paging(23, 24));
+        assert!(needs_paging(24, 24));
+    }
+}
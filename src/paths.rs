@@ -0,0 +1,277 @@
+// ── Filesystem locations ──
+//
+// Every path cove reads or writes is resolved here. The state root is:
+//   1. $COVE_HOME, if set
+//   2. ~/.cove, if it exists or $XDG_STATE_HOME is unset
+//   3. $XDG_STATE_HOME/cove
+// When none of these can be determined (e.g. HOME unset in a container),
+// lookups return None: readers behave as if nothing was recorded and hook
+// writers become no-ops, so cove keeps working without persistence.
+//
+// Hook events are kept per tmux server: panes of the default server write to
+// events/, panes of `tmux -L work` to events/work/, so pane IDs reused across
+// servers never mix. The server is read from `$TMUX`, which hooks inherit
+// from the pane Claude runs in.
+//
+// Claude may run hooks in a sandbox whose $HOME isn't the sidebar's, so
+// `cove init` writes the events root into the hook commands (`--events-dir`)
+// and the hook handler sets it with `set_events_root`; the per-server
+// subdirectory still comes from `$TMUX`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// ── Constants ──
+
+/// Events root given to this process with `--events-dir`.
+static EVENTS_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+// ── Helpers ──
+
+fn non_empty_env(var: &str) -> Option<String> {
+    std::env::var(var).ok().filter(|v| !v.is_empty())
+}
+
+fn resolve_cove_home(
+    cove_home: Option<String>,
+    home: Option<String>,
+    xdg_state: Option<String>,
+) -> Option<PathBuf> {
+    if let Some(dir) = cove_home {
+        return Some(PathBuf::from(dir));
+    }
+    let legacy = home.map(|h| PathBuf::from(h).join(".cove"));
+    match (legacy, xdg_state) {
+        (Some(legacy), Some(xdg)) if !legacy.exists() => Some(PathBuf::from(xdg).join("cove")),
+        (Some(legacy), _) => Some(legacy),
+        (None, Some(xdg)) => Some(PathBuf::from(xdg).join("cove")),
+        (None, None) => None,
+    }
+}
+
+/// The tmux server this process runs under: `$TMUX`, or `$COVE_TMUX` in a
+/// container, which gets the pane's value without its socket.
+fn tmux_env() -> Option<String> {
+    non_empty_env("TMUX").or_else(|| non_empty_env("COVE_TMUX"))
+}
+
+/// Socket name from a `$TMUX` value ("/tmp/tmux-1000/work,1234,0" -> "work").
+/// None for the default server and outside tmux.
+fn socket_name(tmux: Option<String>) -> Option<String> {
+    let tmux = tmux?;
+    let socket = tmux.split(',').next()?;
+    let name = Path::new(socket).file_name()?.to_string_lossy().to_string();
+    (name != "default").then_some(name)
+}
+
+fn events_rel(socket: Option<String>) -> String {
+    match socket {
+        Some(name) => format!("events/{name}"),
+        None => "events".to_string(),
+    }
+}
+
+/// Events directory for tmux server `socket` under an explicit events root.
+fn events_under(root: &Path, socket: Option<String>) -> PathBuf {
+    match socket {
+        Some(name) => root.join(name),
+        None => root.to_path_buf(),
+    }
+}
+
+// ── Public API ──
+
+/// The user's home directory, if `$HOME` is set and non-empty.
+pub fn home() -> Option<PathBuf> {
+    non_empty_env("HOME").map(PathBuf::from)
+}
+
+/// Root directory for all cove state (events, registers, logs).
+pub fn cove_home() -> Option<PathBuf> {
+    resolve_cove_home(
+        non_empty_env("COVE_HOME"),
+        non_empty_env("HOME"),
+        non_empty_env("XDG_STATE_HOME"),
+    )
+}
+
+/// A file or directory under the cove state root, e.g. `cove_path("events")`.
+pub fn cove_path(rel: &str) -> Option<PathBuf> {
+    cove_home().map(|root| root.join(rel))
+}
+
+/// Event files for the tmux server this process runs under.
+pub fn events_dir() -> Option<PathBuf> {
+    let socket = socket_name(tmux_env());
+    match EVENTS_ROOT.get() {
+        Some(root) => Some(events_under(root, socket)),
+        None => cove_path(&events_rel(socket)),
+    }
+}
+
+/// Like `events_dir`, but creates the directory.
+pub fn ensure_events_dir() -> Result<PathBuf, String> {
+    let Some(root) = EVENTS_ROOT.get() else {
+        return ensure_cove_dir(&events_rel(socket_name(tmux_env())));
+    };
+    let dir = events_under(root, socket_name(tmux_env()));
+    fs::create_dir_all(&dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    Ok(dir)
+}
+
+/// Where every tmux server's event files go: `events/` under the state root
+/// unless `set_events_root` moved it.
+pub fn events_root() -> Option<PathBuf> {
+    EVENTS_ROOT.get().cloned().or_else(|| cove_path("events"))
+}
+
+/// Keep this process's events under `root` (from `--events-dir`) whatever
+/// the state root resolves to. Only the first call takes effect.
+pub fn set_events_root(root: PathBuf) {
+    let _ = EVENTS_ROOT.set(root);
+}
+
+/// Like `cove_path`, but creates the directory, explaining why if it can't.
+pub fn ensure_cove_dir(rel: &str) -> Result<PathBuf, String> {
+    let dir = cove_path(rel).ok_or(
+        "cannot locate cove state directory: HOME is not set (set COVE_HOME to choose one)",
+    )?;
+    fs::create_dir_all(&dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    Ok(dir)
+}
+
+/// The state root, created if needed, or None if it can't be resolved or
+/// written to (unset HOME, read-only filesystem).
+pub fn writable_cove_home() -> Option<PathBuf> {
+    let root = cove_home()?;
+    fs::create_dir_all(&root).ok()?;
+    is_writable(&root).then_some(root)
+}
+
+/// Claude Code's config directory (honors `$CLAUDE_CONFIG_DIR`).
+pub fn claude_dir() -> Option<PathBuf> {
+    non_empty_env("CLAUDE_CONFIG_DIR")
+        .map(PathBuf::from)
+        .or_else(|| home().map(|h| h.join(".claude")))
+}
+
+/// Claude Code's user settings file.
+pub fn claude_settings() -> Option<PathBuf> {
+    Some(claude_dir()?.join("settings.json"))
+}
+
+/// A project's shared and personal Claude settings files, in the order
+/// Claude applies them: `.claude/settings.json`, `.claude/settings.local.json`.
+pub fn project_settings(root: &Path) -> [PathBuf; 2] {
+    let dir = root.join(".claude");
+    [dir.join("settings.json"), dir.join("settings.local.json")]
+}
+
+/// Replace a leading `$HOME` with `~` for display.
+pub fn tildify(path: &str) -> String {
+    match home() {
+        Some(h) => {
+            let h = h.to_string_lossy();
+            match path.strip_prefix(h.as_ref()) {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("~{rest}"),
+                _ => path.to_string(),
+            }
+        }
+        None => path.to_string(),
+    }
+}
+
+/// Expand a leading `~/` (or bare `~`) to `$HOME`.
+pub fn expand_tilde(path: &str) -> PathBuf {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest.trim_start_matches('/'),
+        _ => return PathBuf::from(path),
+    };
+    match home() {
+        Some(h) => h.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// True if `path` can be written to (directory exists and accepts new files).
+pub fn is_writable(path: &Path) -> bool {
+    let probe = path.join(format!(".cove-write-test-{}", std::process::id()));
+    let ok = fs::write(&probe, b"").is_ok();
+    let _ = fs::remove_file(&probe);
+    ok
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cove_home_override_wins() {
+        let root = resolve_cove_home(
+            Some("/custom".into()),
+            Some("/home/u".into()),
+            Some("/xdg".into()),
+        );
+        assert_eq!(root, Some(PathBuf::from("/custom")));
+    }
+
+    #[test]
+    fn test_cove_home_legacy_when_no_xdg() {
+        let root = resolve_cove_home(None, Some("/home/u".into()), None);
+        assert_eq!(root, Some(PathBuf::from("/home/u/.cove")));
+    }
+
+    #[test]
+    fn test_cove_home_prefers_existing_legacy_dir() {
+        let home = tempfile::tempdir().unwrap();
+        fs::create_dir(home.path().join(".cove")).unwrap();
+        let home_str = home.path().to_string_lossy().to_string();
+
+        let root = resolve_cove_home(None, Some(home_str), Some("/xdg".into()));
+        assert_eq!(root, Some(home.path().join(".cove")));
+    }
+
+    #[test]
+    fn test_cove_home_xdg_for_new_installs() {
+        let home = tempfile::tempdir().unwrap();
+        let home_str = home.path().to_string_lossy().to_string();
+
+        let root = resolve_cove_home(None, Some(home_str), Some("/xdg".into()));
+        assert_eq!(root, Some(PathBuf::from("/xdg/cove")));
+    }
+
+    #[test]
+    fn test_cove_home_unresolvable() {
+        assert_eq!(resolve_cove_home(None, None, None), None);
+    }
+
+    #[test]
+    fn test_events_scoped_by_tmux_socket() {
+        let socket = |v: &str| socket_name(Some(v.to_string()));
+        assert_eq!(socket("/tmp/tmux-1000/work,4242,0"), Some("work".into()));
+        assert_eq!(socket("/tmp/tmux-1000/default,4242,0"), None);
+        assert_eq!(socket_name(None), None);
+        assert_eq!(
+            events_rel(socket("/tmp/tmux-1000/work,4242,0")),
+            "events/work"
+        );
+        assert_eq!(events_rel(None), "events");
+        // An explicit root keeps the per-server split
+        let root = Path::new("/sandbox/events");
+        assert_eq!(
+            events_under(root, socket("/tmp/tmux-1000/work,4242,0")),
+            PathBuf::from("/sandbox/events/work")
+        );
+        assert_eq!(events_under(root, None), PathBuf::from("/sandbox/events"));
+    }
+
+    #[test]
+    fn test_is_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(is_writable(dir.path()));
+        assert!(!is_writable(Path::new("/nonexistent/dir")));
+    }
+}
@@ -0,0 +1,168 @@
+// ── Process usage for session panes ──
+//
+// One `ps` call lists every process; a pane's usage is the sum over the
+// process tree rooted at its `#{pane_pid}` (the shell or Claude itself plus
+// anything it spawned). Pausing sends SIGSTOP to the whole tree, so a paused
+// session resumes exactly where it stopped with SIGCONT.
+
+use std::process::Command;
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proc {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Percent of one CPU, as reported by `ps`.
+    pub cpu: f32,
+    /// Resident memory in KiB.
+    pub rss_kb: u64,
+    /// Seconds since the process started.
+    pub elapsed: u64,
+    /// Stopped with SIGSTOP (`ps` state "T").
+    pub stopped: bool,
+}
+
+/// Usage summed over a pane's process tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    pub cpu: f32,
+    pub rss_kb: u64,
+    /// Uptime of the tree's root process.
+    pub elapsed: u64,
+    pub stopped: bool,
+}
+
+// ── Helpers ──
+
+/// Parse `ps` elapsed time: `[[dd-]hh:]mm:ss`.
+fn parse_elapsed(s: &str) -> Option<u64> {
+    let (days, clock) = match s.split_once('-') {
+        Some((d, rest)) => (d.parse::<u64>().ok()?, rest),
+        None => (0, s),
+    };
+    let mut secs = 0;
+    for part in clock.split(':') {
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(days * 86_400 + secs)
+}
+
+fn parse_ps(out: &str) -> Vec<Proc> {
+    out.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [pid, ppid, cpu, rss, elapsed, stat, ..] = fields.as_slice() else {
+                return None;
+            };
+            Some(Proc {
+                pid: pid.parse().ok()?,
+                ppid: ppid.parse().ok()?,
+                cpu: cpu.parse().unwrap_or(0.0),
+                rss_kb: rss.parse().unwrap_or(0),
+                elapsed: parse_elapsed(elapsed).unwrap_or(0),
+                stopped: stat.starts_with('T'),
+            })
+        })
+        .collect()
+}
+
+/// PIDs of `root` and all its descendants.
+fn tree(procs: &[Proc], root: u32) -> Vec<u32> {
+    let mut pids = vec![root];
+    let mut i = 0;
+    while i < pids.len() {
+        let parent = pids[i];
+        pids.extend(procs.iter().filter(|p| p.ppid == parent).map(|p| p.pid));
+        i += 1;
+    }
+    pids
+}
+
+fn signal(procs: &[Proc], root: u32, sig: &str) -> Result<(), String> {
+    let pids: Vec<String> = tree(procs, root).iter().map(u32::to_string).collect();
+    let status = Command::new("kill")
+        .arg(sig)
+        .args(&pids)
+        .status()
+        .map_err(|e| format!("kill: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("kill {sig} {root} failed"))
+    }
+}
+
+// ── Public API ──
+
+/// Every process on the machine, or an empty list if `ps` is unavailable.
+pub fn snapshot() -> Vec<Proc> {
+    Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,pcpu=,rss=,etime=,stat="])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| parse_ps(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default()
+}
+
+/// Usage of the process tree rooted at `root`, or None if it has exited.
+pub fn usage(procs: &[Proc], root: u32) -> Option<Usage> {
+    let top = procs.iter().find(|p| p.pid == root)?;
+    let pids = tree(procs, root);
+    let members = procs.iter().filter(|p| pids.contains(&p.pid));
+    let mut usage = Usage {
+        elapsed: top.elapsed,
+        ..Usage::default()
+    };
+    for p in members {
+        usage.cpu += p.cpu;
+        usage.rss_kb += p.rss_kb;
+        // A tree counts as paused once any member is stopped (the shell of a
+        // stopped Claude stays in "S")
+        usage.stopped |= p.stopped;
+    }
+    Some(usage)
+}
+
+/// Stop (`pause: true`) or continue the process tree rooted at `root`.
+pub fn set_paused(procs: &[Proc], root: u32, pause: bool) -> Result<(), String> {
+    signal(procs, root, if pause { "-STOP" } else { "-CONT" })
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_elapsed() {
+        assert_eq!(parse_elapsed("00:07"), Some(7));
+        assert_eq!(parse_elapsed("12:30"), Some(750));
+        assert_eq!(parse_elapsed("01:00:00"), Some(3600));
+        assert_eq!(parse_elapsed("2-00:00:01"), Some(172_801));
+        assert_eq!(parse_elapsed("soon"), None);
+    }
+
+    #[test]
+    fn test_usage_sums_process_tree() {
+        let procs = parse_ps(
+            "  100     1  0.0  2000    10:00 Ss\n\
+             \x20 200   100 45.5 300000   09:59 T\n\
+             \x20 300   200  4.5 50000    00:30 S\n\
+             \x20 400     1 99.0 999999   00:01 R\n",
+        );
+        let usage = usage(&procs, 100).unwrap();
+        assert_eq!(
+            usage,
+            Usage {
+                cpu: 50.0,
+                rss_kb: 352_000,
+                elapsed: 600,
+                stopped: true,
+            }
+        );
+        assert!(super::usage(&procs, 999).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+// ── Claude profiles by directory ──
+//
+// `profiles` in the config maps directory globs (matched like `tags`, see
+// autotag.rs) to flags for the Claude CLI: a model, a permission mode and
+// the tools allowed without asking. Every Claude cove launches in a
+// matching directory — `cove <name>`, tabs, `cove up`, `cove adopt`,
+// `cove restart` (and so crash recovery), `cove undo` and the sidebar's
+// repair — gets the first matching rule's flags, so a sensitive repo always
+// starts in, say, plan mode. The pane-died hook's respawns rerun the pane's
+// first command, flags included. The sidebar shows the selected session's
+// profile under the list.
+//
+// Flags are appended after Claude's own arguments, `--allowed-tools` last
+// since it takes every argument after it.
+
+use crate::autotag;
+use crate::commands::start::shell_quote;
+use crate::config::{self, ProfileRule};
+
+// ── Public API ──
+
+/// The first rule whose pattern matches directory `dir`.
+pub fn rule_for<'a>(rules: &'a [ProfileRule], dir: &str) -> Option<&'a ProfileRule> {
+    rules.iter().find(|rule| autotag::matches(&rule.path, dir))
+}
+
+/// The Claude CLI flags `rule` sets, quoted for a shell command line.
+pub fn flags(rule: &ProfileRule) -> String {
+    let mut flags = Vec::new();
+    if let Some(model) = &rule.model {
+        flags.push(format!("--model {}", shell_quote(model)));
+    }
+    if let Some(mode) = &rule.permission_mode {
+        flags.push(format!("--permission-mode {}", shell_quote(mode)));
+    }
+    if !rule.allowed_tools.is_empty() {
+        let tools: Vec<String> = rule.allowed_tools.iter().map(|t| shell_quote(t)).collect();
+        flags.push(format!("--allowed-tools {}", tools.join(" ")));
+    }
+    flags.join(" ")
+}
+
+/// One line for the sidebar, e.g. "opus · plan · Read, Grep".
+pub fn summary(rule: &ProfileRule) -> String {
+    let tools = rule.allowed_tools.join(", ");
+    [
+        rule.model.as_deref(),
+        rule.permission_mode.as_deref(),
+        Some(&tools),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<_>>()
+    .join(" \u{b7} ")
+}
+
+/// Claude `command` with the flags the rule matching `dir` sets, if any.
+pub fn apply(rules: &[ProfileRule], dir: &str, command: &str) -> String {
+    match rule_for(rules, dir).map(flags) {
+        Some(flags) if !flags.is_empty() => format!("{command} {flags}"),
+        _ => command.to_string(),
+    }
+}
+
+/// `apply` with the rules from the config.
+pub fn command_for(dir: &str, command: &str) -> String {
+    apply(&config::load().unwrap_or_default().profiles, dir, command)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(path: &str, model: Option<&str>, mode: Option<&str>, tools: &[&str]) -> ProfileRule {
+        ProfileRule {
+            path: path.to_string(),
+            model: model.map(str::to_string),
+            permission_mode: mode.map(str::to_string),
+            allowed_tools: tools.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_first_matching_profile_adds_flags() {
+        let rules = [
+            rule(
+                "/code/payments/**",
+                None,
+                Some("plan"),
+                &["Read", "Bash(git diff:*)"],
+            ),
+            rule("/code/**", Some("sonnet"), None, &[]),
+        ];
+        assert_eq!(
+            apply(&rules, "/code/payments/api", "claude 'fix tests'"),
+            "claude 'fix tests' --permission-mode 'plan' --allowed-tools 'Read' 'Bash(git diff:*)'"
+        );
+        assert_eq!(
+            apply(&rules, "/code/web", "claude --continue"),
+            "claude --continue --model 'sonnet'"
+        );
+        assert_eq!(apply(&rules, "/tmp", "claude"), "claude");
+        // A rule with no flags leaves the command alone
+        assert_eq!(
+            apply(&[rule("/tmp", None, None, &[])], "/tmp", "claude"),
+            "claude"
+        );
+
+        assert_eq!(summary(&rules[0]), "plan \u{b7} Read, Bash(git diff:*)");
+        assert_eq!(summary(&rules[1]), "sonnet");
+    }
+}
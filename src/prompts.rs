@@ -0,0 +1,180 @@
+// ── Prompt history ──
+//
+// The `cove hook user-prompt` handler appends every prompt Claude accepted,
+// with the session's window name and directory, to
+// ~/.cove/prompts/{session_id}.jsonl. Unlike the command log it isn't purged
+// when a pane goes away: `cove prompts --all` searches every session's past
+// instructions so they can be sent again. Each prompt is picked by a short id
+// hashed from its time, pane and text, which unlike a position in the listing
+// doesn't move when a new prompt comes in.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+use crate::sidebar::state;
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptEntry {
+    pub prompt: String,
+    /// Window name of the session when the prompt was sent ("" outside tmux).
+    #[serde(default)]
+    pub window: String,
+    #[serde(default)]
+    pub cwd: String,
+    #[serde(default)]
+    pub pane_id: String,
+    pub ts: u64,
+}
+
+impl PromptEntry {
+    /// Short id for picking this prompt, the same in every listing.
+    pub fn id(&self) -> String {
+        // FNV-1a, written out: std's hasher may change between releases
+        let mut hash: u32 = 0x811c_9dc5;
+        let key = format!("{}|{}|{}", self.ts, self.pane_id, self.prompt);
+        for byte in key.bytes() {
+            hash = (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193);
+        }
+        format!("{:06x}", hash & 0xff_ffff)
+    }
+}
+
+// ── Helpers ──
+
+fn prompts_dir() -> Option<PathBuf> {
+    paths::cove_path("prompts")
+}
+
+/// Every logged prompt, newest first.
+fn read_all(dir: &Path) -> Vec<PromptEntry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut out: Vec<PromptEntry> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<PromptEntry>(line).ok())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    out.sort_by_key(|e| std::cmp::Reverse(e.ts));
+    out
+}
+
+/// Prompts sent in window `window` (any window if None) containing `search`
+/// (case-insensitive), newest first.
+fn search_in(dir: &Path, window: Option<&str>, search: Option<&str>) -> Vec<PromptEntry> {
+    let needle = search.map(str::to_lowercase);
+    read_all(dir)
+        .into_iter()
+        .filter(|e| window.is_none_or(|w| e.window == w))
+        .filter(|e| {
+            needle
+                .as_deref()
+                .is_none_or(|n| e.prompt.to_lowercase().contains(n))
+        })
+        .collect()
+}
+
+// ── Public API ──
+
+/// Record a prompt the user submitted to Claude.
+pub fn record(
+    session_id: &str,
+    pane_id: &str,
+    window: &str,
+    cwd: &str,
+    prompt: &str,
+) -> Result<(), String> {
+    if prompt.trim().is_empty() {
+        return Ok(());
+    }
+    let dir = paths::ensure_cove_dir("prompts")?;
+
+    let path = dir.join(format!("{session_id}.jsonl"));
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("open prompt log: {e}"))?;
+
+    let entry = PromptEntry {
+        prompt: prompt.to_string(),
+        window: window.to_string(),
+        cwd: cwd.to_string(),
+        pane_id: pane_id.to_string(),
+        ts: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let line = serde_json::to_string(&entry).map_err(|e| format!("serialize prompt: {e}"))?;
+    writeln!(f, "{line}").map_err(|e| format!("write prompt: {e}"))
+}
+
+/// Logged prompts of session `window` (all sessions if None) matching
+/// `search`, newest first.
+pub fn search(window: Option<&str>, search: Option<&str>) -> Vec<PromptEntry> {
+    prompts_dir()
+        .map(|dir| search_in(&dir, window, search))
+        .unwrap_or_default()
+}
+
+/// The last prompt logged for session `session_id`.
+pub fn latest(session_id: &str) -> Option<PromptEntry> {
+    let path = prompts_dir()?.join(format!("{session_id}.jsonl"));
+    let line = state::read_last_line(&path)?;
+    serde_json::from_str(&line).ok()
+}
+
+/// How many prompts have been logged, across every session.
+pub fn count() -> usize {
+    prompts_dir().map(|dir| read_all(&dir).len()).unwrap_or(0)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_in_filters_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("s1.jsonl"),
+            r#"{"prompt":"Write the users migration","window":"api","cwd":"/a","pane_id":"%1","ts":100}
+not json
+{"prompt":"run the tests","window":"api","cwd":"/a","pane_id":"%1","ts":300}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("s2.jsonl"),
+            r#"{"prompt":"Roll back the MIGRATION","window":"web","ts":200}
+"#,
+        )
+        .unwrap();
+
+        let found = search_in(dir.path(), None, Some("migration"));
+        let prompts: Vec<&str> = found.iter().map(|e| e.prompt.as_str()).collect();
+        assert_eq!(
+            prompts,
+            ["Roll back the MIGRATION", "Write the users migration"]
+        );
+        assert_eq!(search_in(dir.path(), Some("api"), None).len(), 2);
+        assert_eq!(search_in(dir.path(), None, None)[0].ts, 300);
+        assert!(search_in(dir.path(), Some("web"), Some("tests")).is_empty());
+    }
+}
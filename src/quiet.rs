@@ -0,0 +1,48 @@
+// ── Quiet mode ──
+//
+// `-q/--quiet` (or `quiet: true` in the config) is for cove run from
+// scripts, keybindings and other tools: confirmations ("Killed: api"),
+// warnings that only lead up to a question, and install banners aren't
+// printed, and nothing waits on stdin. The hook install prompt follows
+// `hooks.install` instead, with `ask` taken as `never`; starting a second
+// session in a directory is refused unless `--allow-duplicate-dir` says
+// otherwise. Errors and output a command exists to print (`cove list`, the
+// join command from `cove share`) are unaffected.
+//
+// Informational lines go through `say!`, which is `println!` unless quiet.
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config;
+
+// ── Constants ──
+
+/// Set by `-q/--quiet` for the rest of this process.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// `quiet` from the config file, read once.
+static FROM_CONFIG: OnceLock<bool> = OnceLock::new();
+
+// ── Public API ──
+
+/// Turn on quiet mode for this process (`-q/--quiet`).
+pub fn enable() {
+    QUIET.store(true, Ordering::Relaxed);
+}
+
+/// Quiet mode is on, by `-q/--quiet` or `quiet` in the config file.
+pub fn on() -> bool {
+    QUIET.load(Ordering::Relaxed)
+        || *FROM_CONFIG.get_or_init(|| config::load().is_ok_and(|c| c.quiet))
+}
+
+/// `println!`, unless quiet mode is on.
+#[macro_export]
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::quiet::on() {
+            println!($($arg)*);
+        }
+    };
+}
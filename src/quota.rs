@@ -0,0 +1,174 @@
+// ── Cross-session concurrency limit ──
+//
+// With `quota.max_working` set, `cove hook user-prompt` (installed as a
+// synchronous hook, so it can block) counts the other live panes whose latest
+// event is "working". At the limit the prompt is appended to
+// ~/.cove/queue.jsonl, Claude is told to drop it, and the pane is marked
+// "queued". Each `cove hook stop` frees a slot and pastes the oldest queued
+// prompt back into its pane, which submits it through the hook again.
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+use crate::sidebar::state::{self, WindowState};
+use crate::tmux::TmuxClient;
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedPrompt {
+    pub pane_id: String,
+    pub session_id: String,
+    pub prompt: String,
+    pub ts: u64,
+}
+
+// ── Constants ──
+
+const QUEUE_FILE: &str = "queue.jsonl";
+
+// ── Helpers ──
+
+fn queue_path() -> Option<PathBuf> {
+    paths::cove_path(QUEUE_FILE)
+}
+
+fn load_in(path: &Path) -> Vec<QueuedPrompt> {
+    fs::read_to_string(path)
+        .map(|content| {
+            content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn enqueue_in(path: &Path, entry: &QueuedPrompt) -> Result<(), String> {
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("open queue: {e}"))?;
+    let line = serde_json::to_string(entry).map_err(|e| format!("serialize prompt: {e}"))?;
+    writeln!(f, "{line}").map_err(|e| format!("write queue: {e}"))
+}
+
+/// Remove and return the oldest prompt whose pane is still `live`. Prompts for
+/// panes that have gone away are dropped on the way.
+fn pop_in(path: &Path, live: &HashSet<String>) -> Option<QueuedPrompt> {
+    let mut queue = load_in(path);
+    queue.retain(|p| live.contains(&p.pane_id));
+    let next = (!queue.is_empty()).then(|| queue.remove(0));
+
+    let mut content = String::new();
+    for entry in &queue {
+        if let Ok(line) = serde_json::to_string(entry) {
+            content.push_str(&line);
+            content.push('\n');
+        }
+    }
+    let _ = fs::write(path, content);
+    next
+}
+
+fn count_working(states: &[(String, WindowState)], live: &HashSet<String>, except: &str) -> usize {
+    states
+        .iter()
+        .filter(|(pane, state)| {
+            *state == WindowState::Working && pane != except && live.contains(pane)
+        })
+        .count()
+}
+
+// ── Public API ──
+
+/// Pane IDs of every Claude pane in the cove session.
+pub fn live_panes(client: &dyn TmuxClient) -> HashSet<String> {
+    client
+        .list_pane_commands()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| p.pane_id)
+        .collect()
+}
+
+/// Live sessions other than `except` whose latest event is "working".
+pub fn working_elsewhere(live: &HashSet<String>, except: &str) -> usize {
+    let states: Vec<(String, WindowState)> = state::load_pane_states().into_iter().collect();
+    count_working(&states, live, except)
+}
+
+/// Hold a prompt until a working slot frees up.
+pub fn enqueue(pane_id: &str, session_id: &str, prompt: &str) -> Result<(), String> {
+    let path = paths::ensure_cove_dir("")?.join(QUEUE_FILE);
+    let entry = QueuedPrompt {
+        pane_id: pane_id.to_string(),
+        session_id: session_id.to_string(),
+        prompt: prompt.to_string(),
+        ts: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    enqueue_in(&path, &entry)
+}
+
+/// Take the oldest queued prompt for a live pane.
+pub fn pop(live: &HashSet<String>) -> Option<QueuedPrompt> {
+    pop_in(&queue_path()?, live)
+}
+
+/// Every queued prompt, oldest first.
+pub fn queued() -> Vec<QueuedPrompt> {
+    queue_path().map(|p| load_in(&p)).unwrap_or_default()
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(pane_id: &str, text: &str) -> QueuedPrompt {
+        QueuedPrompt {
+            pane_id: pane_id.to_string(),
+            session_id: "s".to_string(),
+            prompt: text.to_string(),
+            ts: 0,
+        }
+    }
+
+    #[test]
+    fn test_pop_oldest_live_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(QUEUE_FILE);
+        enqueue_in(&path, &prompt("%9", "gone")).unwrap();
+        enqueue_in(&path, &prompt("%1", "first")).unwrap();
+        enqueue_in(&path, &prompt("%2", "second")).unwrap();
+
+        let live = HashSet::from(["%1".to_string(), "%2".to_string()]);
+        assert_eq!(pop_in(&path, &live).unwrap().prompt, "first");
+        assert_eq!(load_in(&path), vec![prompt("%2", "second")]);
+        assert_eq!(pop_in(&path, &live).unwrap().prompt, "second");
+        assert!(pop_in(&path, &live).is_none());
+    }
+
+    #[test]
+    fn test_count_working_skips_self_and_dead_panes() {
+        let states = vec![
+            ("%1".to_string(), WindowState::Working),
+            ("%2".to_string(), WindowState::Working),
+            ("%3".to_string(), WindowState::Idle),
+            ("%9".to_string(), WindowState::Working),
+        ];
+        let live = HashSet::from(["%1", "%2", "%3"].map(String::from));
+        assert_eq!(count_working(&states, &live, ""), 2);
+        assert_eq!(count_working(&states, &live, "%1"), 1);
+    }
+}
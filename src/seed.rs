@@ -0,0 +1,125 @@
+// ── Session seeds ──
+//
+// `cove <name> --seed <url>` starts a session from a prepared task: a
+// markdown description fetched from a URL (with curl) or read from a local
+// file. GitHub gist and blob pages are fetched as their raw text. The seed is
+// kept in the session's metadata, where the sidebar's `S` view shows it, and
+// with `--seed-prompt` it's also Claude's first prompt, typed once Claude is
+// ready.
+
+use std::path::Path;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+// ── Types ──
+
+/// A session's task description and where it came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Seed {
+    pub url: String,
+    pub text: String,
+}
+
+// ── Constants ──
+
+/// Largest seed kept: sent as a prompt, it's pasted into Claude in one go.
+const MAX_BYTES: usize = 8 * 1024;
+
+// ── Helpers ──
+
+/// The raw-text URL for a GitHub gist or blob page; other URLs as given.
+fn raw_url(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    if let Some(gist) = url.strip_prefix("https://gist.github.com/") {
+        // "<user>/<id>", or just "<id>", which GitHub redirects
+        return format!("https://gist.githubusercontent.com/{gist}/raw");
+    }
+    if let Some(path) = url.strip_prefix("https://github.com/")
+        && let Some((repo, file)) = path.split_once("/blob/")
+    {
+        return format!("https://raw.githubusercontent.com/{repo}/{file}");
+    }
+    url.to_string()
+}
+
+fn download(url: &str) -> Result<String, String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", "30", url])
+        .output()
+        .map_err(|e| format!("curl: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("fetch {url}: {}", stderr.trim()));
+    }
+    String::from_utf8(output.stdout).map_err(|_| format!("{url} isn't text"))
+}
+
+/// `text` as a seed, if it has something in it and isn't too long.
+fn checked(url: &str, text: String) -> Result<Seed, String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(format!("{url} is empty"));
+    }
+    if text.len() > MAX_BYTES {
+        return Err(format!(
+            "{url} is {} KiB; seeds can be up to {} KiB",
+            text.len().div_ceil(1024),
+            MAX_BYTES / 1024
+        ));
+    }
+    Ok(Seed {
+        url: url.to_string(),
+        text,
+    })
+}
+
+// ── Public API ──
+
+/// Fetch the seed at `url`, or read it if `url` is a local file.
+pub fn fetch(url: &str) -> Result<Seed, String> {
+    let text = if Path::new(url).is_file() {
+        std::fs::read_to_string(url).map_err(|e| format!("read {url}: {e}"))?
+    } else if url.starts_with("https://") || url.starts_with("http://") {
+        download(&raw_url(url))?
+    } else {
+        return Err(format!("{url} is neither a URL nor a file"));
+    };
+    checked(url, text)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raw_url_for_github_pages() {
+        assert_eq!(
+            raw_url("https://gist.github.com/alice/4f1d2e"),
+            "https://gist.githubusercontent.com/alice/4f1d2e/raw"
+        );
+        assert_eq!(
+            raw_url("https://github.com/acme/api/blob/main/tasks/fix-auth.md"),
+            "https://raw.githubusercontent.com/acme/api/main/tasks/fix-auth.md"
+        );
+        assert_eq!(
+            raw_url("https://example.com/task.md"),
+            "https://example.com/task.md"
+        );
+    }
+
+    #[test]
+    fn test_fetch_reads_files_and_checks_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("task.md");
+        std::fs::write(&path, "# Fix auth\n\nThe login test fails.\n\n").unwrap();
+        let seed = fetch(path.to_str().unwrap()).unwrap();
+        assert_eq!(seed.text, "# Fix auth\n\nThe login test fails.");
+
+        std::fs::write(&path, "x".repeat(MAX_BYTES + 1)).unwrap();
+        assert!(fetch(path.to_str().unwrap()).unwrap_err().contains("9 KiB"));
+        assert!(fetch("not-a-file.md").is_err());
+    }
+}
@@ -0,0 +1,193 @@
+// ── Sending input to an agent ──
+//
+// Everything that types into a Claude pane (cove send, queued prompts, prompt
+// history, interrupt follow-ups, the sidebar's nudge) goes through
+// `deliver`, which first waits for the agent's input box. A pane counts as
+// ready once its screen shows one of the probe's markers, plus `delay_ms` for
+// the TUI to settle; with no markers only the delay applies. Input typed
+// before Claude has drawn its prompt is otherwise swallowed or half-applied.
+//
+// The probe is `send.ready` in the config, overridden under `send.agents` by
+// the pane's foreground command, so other agents can bring their own markers.
+// A pane that never looks ready gets the message anyway after `timeout_ms`,
+// the same as before probing existed.
+//
+// Safe mode (`--no-send`, or `safe_mode: true` in the config) is for watching
+// sessions only: `deliver` refuses, commands that would type (`send`,
+// `interrupt`, `prompts --send`) warn and do nothing, the sidebar's nudge,
+// interrupt and clip paste show the notice instead, and quota stops queueing
+// prompts since nothing would release them.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::colors::*;
+use crate::config::{self, Config, ReadyConfig, SendConfig};
+use crate::tmux::TmuxClient;
+
+// ── Constants ──
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub const SAFE_MODE: &str = "safe mode: cove doesn't type into sessions";
+
+/// Set by `--no-send` for the rest of this process.
+static NO_SEND: AtomicBool = AtomicBool::new(false);
+
+// ── Helpers ──
+
+/// The probe for the agent running in `pane_id`.
+fn probe_for<'a>(
+    client: &dyn TmuxClient,
+    config: &'a SendConfig,
+    pane_id: &str,
+) -> &'a ReadyConfig {
+    client
+        .output(&[
+            "display-message",
+            "-t",
+            pane_id,
+            "-p",
+            "#{pane_current_command}",
+        ])
+        .ok()
+        .and_then(|command| config.agents.get(command.trim()))
+        .unwrap_or(&config.ready)
+}
+
+/// The visible screen shows one of the probe's markers.
+fn looks_ready(client: &dyn TmuxClient, probe: &ReadyConfig, pane_id: &str) -> bool {
+    client
+        .output(&["capture-pane", "-p", "-t", pane_id])
+        .is_ok_and(|screen| probe.markers.iter().any(|m| screen.contains(m.as_str())))
+}
+
+// ── Public API ──
+
+/// Turn on safe mode for this process (`--no-send`).
+pub fn disable() {
+    NO_SEND.store(true, Ordering::Relaxed);
+}
+
+/// Safe mode is on, by `--no-send` or `safe_mode` in `config`.
+pub fn disabled_by(config: &Config) -> bool {
+    NO_SEND.load(Ordering::Relaxed) || config.safe_mode
+}
+
+/// Safe mode is on, by `--no-send` or the config file.
+pub fn disabled() -> bool {
+    disabled_by(&config::load().unwrap_or_default())
+}
+
+/// In safe mode, warn that `what` was skipped and return true.
+pub fn skip(what: &str) -> bool {
+    let off = disabled();
+    if off {
+        eprintln!("{ANSI_PEACH}Warning:{ANSI_RESET} safe mode is on, not {what}");
+    }
+    off
+}
+
+/// Wait until `pane_id` is ready for input, or the probe's timeout passes.
+/// Returns false on timeout.
+pub fn wait_ready(client: &dyn TmuxClient, probe: &ReadyConfig, pane_id: &str) -> bool {
+    let ready = probe.markers.is_empty() || {
+        let deadline = Instant::now() + Duration::from_millis(probe.timeout_ms);
+        loop {
+            if looks_ready(client, probe, pane_id) {
+                break true;
+            }
+            if Instant::now() >= deadline {
+                break false;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    };
+    thread::sleep(Duration::from_millis(probe.delay_ms));
+    ready
+}
+
+/// Submit `message` to the agent in `pane_id` once it's ready, using `config`.
+pub fn deliver_with(
+    client: &dyn TmuxClient,
+    config: &SendConfig,
+    pane_id: &str,
+    message: &str,
+) -> Result<(), String> {
+    wait_ready(client, probe_for(client, config, pane_id), pane_id);
+    client.paste_message(pane_id, message)
+}
+
+/// Submit `message` to the agent in `pane_id` once it's ready.
+pub fn deliver(client: &dyn TmuxClient, pane_id: &str, message: &str) -> Result<(), String> {
+    let config = config::load().unwrap_or_default();
+    if disabled_by(&config) {
+        return Err(SAFE_MODE.to_string());
+    }
+    deliver_with(client, &config.send, pane_id, message)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux::fake::FakeTmux;
+    use std::collections::HashMap;
+
+    fn probe(markers: &[&str], timeout_ms: u64) -> ReadyConfig {
+        ReadyConfig {
+            markers: markers.iter().map(|m| m.to_string()).collect(),
+            delay_ms: 0,
+            timeout_ms,
+        }
+    }
+
+    #[test]
+    fn test_wait_ready_matches_markers_or_times_out() {
+        let fake = FakeTmux::new().on("capture-pane", "\u{2502} > \n  ? for shortcuts\n");
+        assert!(wait_ready(&fake, &probe(&["? for shortcuts"], 1000), "%t1"));
+        assert_eq!(fake.calls_to("capture-pane").len(), 1);
+
+        let starting = FakeTmux::new().on("capture-pane", "Loading...\n");
+        assert!(!wait_ready(
+            &starting,
+            &probe(&["? for shortcuts"], 150),
+            "%t1"
+        ));
+        assert!(starting.calls_to("capture-pane").len() >= 2);
+
+        // Without markers only the delay applies
+        assert!(wait_ready(&starting, &probe(&[], 0), "%t2"));
+    }
+
+    #[test]
+    fn test_safe_mode_from_config() {
+        assert!(!disabled_by(&Config::default()));
+        let config = Config {
+            safe_mode: true,
+            ..Config::default()
+        };
+        assert!(disabled_by(&config));
+    }
+
+    #[test]
+    fn test_probe_per_agent_command() {
+        let fake =
+            FakeTmux::new().with_windows(&[("api", "codex", "%t1"), ("web", "claude", "%t2")]);
+        let config = SendConfig {
+            ready: probe(&["? for shortcuts"], 0),
+            agents: HashMap::from([("codex".to_string(), probe(&["\u{203a}"], 0))]),
+        };
+        assert_eq!(probe_for(&fake, &config, "%t1").markers, ["\u{203a}"]);
+        assert_eq!(
+            probe_for(&fake, &config, "%t2").markers,
+            ["? for shortcuts"]
+        );
+        assert_eq!(
+            probe_for(&fake, &config, "%t9").markers,
+            ["? for shortcuts"]
+        );
+    }
+}
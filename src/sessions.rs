@@ -0,0 +1,258 @@
+// ── Saved sessions, for cove restore ──
+//
+// ~/.cove/sessions.json lists every session cove has a window for, in the
+// order they were created, so `cove restore` can bring them back once tmux
+// or the machine went down:
+//
+//   [{"name": "api", "dir": "/code/api", "command": "claude",
+//     "pane_id": "%3", "claude_session": "4f1d2e…"}]
+//
+// Every window cove creates is recorded (see `start::create_window_in`),
+// `cove kill` forgets it and `cove rename` moves it; a window closed with
+// tmux directly stays until `cove restore --forget <name>`. Sidebars, hooks
+// and commands all rewrite the file, so each change runs under its lock
+// (`lockfile::with_lock`). `command` is what the
+// Claude pane was started with. The hook fills in `claude_session` on each
+// prompt, matching the entry by pane, so a restored session can resume its
+// conversation. Re-creating a session in the same directory (undo, restore)
+// keeps the conversation it had.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{lockfile, paths};
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub name: String,
+    pub dir: String,
+    /// Command the Claude pane was started with.
+    pub command: String,
+    /// Claude's pane when the window was created.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pane_id: String,
+    /// Claude's session id, for `claude --resume`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_session: Option<String>,
+}
+
+// ── Constants ──
+
+const SESSIONS_FILE: &str = "sessions.json";
+
+// ── Helpers ──
+
+fn load_from(path: &Path) -> Vec<SavedSession> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Write through a temp file and a rename, so a crash mid-write can't lose
+/// the list.
+fn save_to(path: &Path, sessions: &[SavedSession]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create {}: {e}", parent.display()))?;
+    }
+    let content = serde_json::to_string_pretty(sessions).map_err(|e| format!("serialize: {e}"))?;
+    let tmp = path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&tmp, content).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("replace {}: {e}", path.display())
+    })
+}
+
+fn update_in(path: &Path, change: impl FnOnce(&mut Vec<SavedSession>)) -> Result<(), String> {
+    lockfile::with_lock(path, || {
+        let mut sessions = load_from(path);
+        let before = sessions.clone();
+        change(&mut sessions);
+        if sessions == before {
+            return Ok(());
+        }
+        save_to(path, &sessions)
+    })?
+}
+
+fn forget_in(path: &Path, name: &str) -> Result<(), String> {
+    update_in(path, |sessions| sessions.retain(|s| s.name != name))
+}
+
+fn rename_in(path: &Path, old: &str, new: &str) -> Result<(), String> {
+    update_in(path, |sessions| {
+        for s in sessions.iter_mut().filter(|s| s.name == old) {
+            s.name = new.to_string();
+        }
+    })
+}
+
+fn note_claude_session_in(path: &Path, pane_id: &str, session_id: &str) -> Result<(), String> {
+    update_in(path, |sessions| {
+        for s in sessions.iter_mut().filter(|s| s.pane_id == pane_id) {
+            s.claude_session = Some(session_id.to_string());
+        }
+    })
+}
+
+// ── Public API ──
+
+/// The saved-sessions file.
+pub fn manifest() -> Option<PathBuf> {
+    paths::cove_path(SESSIONS_FILE)
+}
+
+/// Saved sessions, oldest first.
+pub fn saved() -> Vec<SavedSession> {
+    manifest().map(|path| load_from(&path)).unwrap_or_default()
+}
+
+/// Record a window created for session `name`, in place of any earlier
+/// entry of that name. An entry in the same directory keeps its Claude
+/// session, since the window was brought back to resume it.
+pub fn record_in(
+    path: &Path,
+    name: &str,
+    dir: &str,
+    command: &str,
+    pane_id: &str,
+) -> Result<(), String> {
+    update_in(path, |sessions| {
+        let claude_session = sessions
+            .iter()
+            .find(|s| s.name == name && s.dir == dir)
+            .and_then(|s| s.claude_session.clone());
+        let entry = SavedSession {
+            name: name.to_string(),
+            dir: dir.to_string(),
+            command: command.to_string(),
+            pane_id: pane_id.to_string(),
+            claude_session,
+        };
+        match sessions.iter_mut().find(|s| s.name == name) {
+            Some(existing) => *existing = entry,
+            None => sessions.push(entry),
+        }
+        // Another session can't be in a pane this one now has
+        for s in sessions.iter_mut().filter(|s| s.name != name) {
+            if s.pane_id == pane_id {
+                s.pane_id.clear();
+            }
+        }
+    })
+}
+
+/// Drop killed session `name`.
+pub fn forget(name: &str) {
+    if let Some(path) = manifest()
+        && let Err(e) = forget_in(&path, name)
+    {
+        log::warn!("sessions: {e}");
+    }
+}
+
+/// Drop saved session `name` so `cove restore` leaves it closed, for a
+/// window that was closed without `cove kill`.
+pub fn forget_saved(name: &str) -> Result<(), String> {
+    let Some(path) = manifest() else {
+        return Err(format!("No saved session '{name}'."));
+    };
+    if !load_from(&path).iter().any(|s| s.name == name) {
+        return Err(format!("No saved session '{name}'."));
+    }
+    forget_in(&path, name)
+}
+
+/// Follow session `old` being renamed to `new`.
+pub fn rename(old: &str, new: &str) -> Result<(), String> {
+    match manifest() {
+        Some(path) => rename_in(&path, old, new),
+        None => Ok(()),
+    }
+}
+
+/// Remember that the session in `pane_id` is Claude session `session_id`.
+pub fn note_claude_session(pane_id: &str, session_id: &str) -> Result<(), String> {
+    let Some(path) = manifest().filter(|_| !pane_id.is_empty()) else {
+        return Ok(());
+    };
+    // Reading first keeps the usual case, nothing changed, to one read
+    let known = load_from(&path)
+        .iter()
+        .filter(|s| s.pane_id == pane_id)
+        .all(|s| s.claude_session.as_deref() == Some(session_id));
+    if known {
+        return Ok(());
+    }
+    note_claude_session_in(&path, pane_id, session_id)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_forget_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SESSIONS_FILE);
+        record_in(&path, "api", "/code/api", "claude", "%t1").unwrap();
+        record_in(&path, "web", "/code/web", "claude 'fix it'", "%t2").unwrap();
+        rename_in(&path, "web", "frontend").unwrap();
+
+        let saved = load_from(&path);
+        let names: Vec<&str> = saved.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["api", "frontend"]);
+        assert_eq!(saved[1].command, "claude 'fix it'");
+
+        forget_in(&path, "api").unwrap();
+        assert_eq!(load_from(&path).len(), 1);
+        // Unknown names are fine
+        forget_in(&path, "docs").unwrap();
+    }
+
+    #[test]
+    fn test_claude_session_follows_pane_and_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SESSIONS_FILE);
+        record_in(&path, "api", "/code/api", "claude", "%t1").unwrap();
+        note_claude_session_in(&path, "%t1", "abc").unwrap();
+        assert_eq!(load_from(&path)[0].claude_session.as_deref(), Some("abc"));
+
+        // Brought back in the same directory: still the same conversation
+        record_in(&path, "api", "/code/api", "claude --resume 'abc'", "%t5").unwrap();
+        assert_eq!(load_from(&path)[0].claude_session.as_deref(), Some("abc"));
+        assert_eq!(load_from(&path)[0].pane_id, "%t5");
+
+        // A new session of that name elsewhere starts over
+        record_in(&path, "api", "/code/api-v2", "claude", "%t6").unwrap();
+        assert_eq!(load_from(&path)[0].claude_session, None);
+    }
+
+    #[test]
+    fn test_recycled_pane_leaves_old_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SESSIONS_FILE);
+        record_in(&path, "api", "/code/api", "claude", "%t1").unwrap();
+        // tmux restarted and handed the pane ID to another window
+        record_in(&path, "web", "/code/web", "claude", "%t1").unwrap();
+        note_claude_session_in(&path, "%t1", "def").unwrap();
+
+        let saved = load_from(&path);
+        assert_eq!(saved[0].pane_id, "");
+        assert_eq!(saved[0].claude_session, None);
+        assert_eq!(saved[1].claude_session.as_deref(), Some("def"));
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_from(&dir.path().join(SESSIONS_FILE)).is_empty());
+    }
+}
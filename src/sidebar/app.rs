@@ -1,36 +1,242 @@
 // ── Sidebar application ──
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{self, stdout};
+use std::time::{Duration, Instant, SystemTime};
 
 use crossterm::cursor;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use crossterm::execute;
 use crossterm::terminal::{self, DisableLineWrap, EnableLineWrap};
 use ratatui::Terminal;
-use ratatui::backend::CrosstermBackend;
 
+use crate::ci::CiStatus;
+use crate::clips::{self, Clip};
+use crate::commands::{init, interrupt, layout, rename, restart, start};
+use crate::config::{CiConfig, CleanupConfig, Config, ProfileRule};
+use crate::crash::{self, Recovery};
 use crate::sidebar::event::{self, Action};
-use crate::sidebar::state::{StateDetector, WindowState};
-use crate::sidebar::ui::SidebarWidget;
-use crate::tmux::{self, WindowInfo};
+use crate::sidebar::format::{self, Segment, Var};
+use crate::sidebar::groups::{self, Row};
+use crate::sidebar::screen::FrameBackend;
+use crate::sidebar::sections::Section;
+use crate::sidebar::state::{self, WindowState};
+use crate::sidebar::ui::{self, ChangesWidget, ClipsWidget, SidebarWidget, TranscriptWidget};
+use crate::sidebar::workers::{EnrichRequest, Update, WindowList, Workers};
+use crate::sidebar::{plain, tips};
+use crate::tmux::{self, Tmux, WindowInfo};
+use crate::transcript::{self, Entry};
+use crate::watch::WatchStatus;
+use crate::{attention, changes, cleanup};
+use crate::{config, meta, names, profiles, send};
 
 // ── Types ──
 
 struct SidebarApp {
     windows: Vec<WindowInfo>,
     states: HashMap<u32, WindowState>,
+    /// Label of the tool each Working window is running, by window index.
+    running_tools: HashMap<u32, String>,
+    /// Latest `cove watch-run` result per window index.
+    watch: HashMap<u32, WatchStatus>,
+    /// Long command running in a helper pane, by window index.
+    helpers: HashMap<u32, String>,
+    /// Unanswered questions per Asking window index.
+    asks: HashMap<u32, u32>,
+    /// Parsed `sidebar.row_format` from the config, if set.
+    row_format: Option<Vec<Segment>>,
+    /// Pinned session names; pinned windows are listed first.
+    pinned: HashSet<String>,
+    /// Session you're clocked in to supervise (`c`).
+    clocked: Option<String>,
+    /// Quick-switch slot of each session, by name (`cove go`).
+    slots: HashMap<String, u8>,
+    /// Git branch per window index, refreshed with the window list.
+    branches: HashMap<u32, String>,
+    /// Seconds since each window's last hook event.
+    ages: HashMap<u32, u64>,
+    /// Tokens spent per window index (only looked up when shown).
+    tokens: HashMap<u32, u64>,
+    /// Claude pane ID per window index, from the events worker.
+    pane_ids: HashMap<u32, String>,
+    /// Last Claude pane ID seen in each window, kept after the pane closes so
+    /// `R` can resume its conversation.
+    last_pane_ids: HashMap<u32, String>,
+    /// Unix time of each window's latest hook event.
+    event_ts: HashMap<u32, u64>,
+    /// Footer tips; empty when disabled in the config.
+    tips: Vec<String>,
+    /// Tips hidden with `?` (shared by all sidebars).
+    tips_hidden: bool,
+    /// Installed hooks don't match what this Claude version fires.
+    hooks_broken: bool,
+    /// Tick until which each recently changed window's row flashes.
+    flash_until: HashMap<u32, u64>,
+    /// Ring the bell along with the flash (`sidebar.bell`).
+    bell: bool,
+    /// Sessions whose branch has been merged, from the last check.
+    merged: HashSet<String>,
+    /// Windows whose Claude pane was closed, by index.
+    broken: HashSet<u32>,
+    /// Claude panes left dead by a crash, by window index.
+    crashed: HashMap<u32, String>,
+    /// Windows with a pane that failed to start, and the end of its output.
+    failed: HashMap<u32, Vec<String>>,
+    /// Windows whose Claude was stopped with `cove kill --keep-pane`.
+    parked: HashSet<u32>,
+    /// Custom sections under the session list (`sidebar.sections`).
+    sections: Vec<Section>,
+    /// Crashed panes already seen, so each crash is handled once.
+    crashes_seen: HashSet<String>,
+    /// Why each possibly stuck Working window looks stuck, by index.
+    stuck: HashMap<u32, String>,
+    /// Minutes without progress before the stuck check flags a session
+    /// (`sidebar.stuck_after_mins`, 0 = off).
+    stuck_after_mins: u64,
+    stuck_checked_at: Option<Instant>,
+    /// CI status of each session's pull request, by name.
+    ci: HashMap<String, CiStatus>,
+    ci_config: CiConfig,
+    ci_checked_at: Option<Instant>,
+    orphans_checked_at: Option<Instant>,
+    /// Claude flags by directory, for the selected session's profile line.
+    profiles: Vec<ProfileRule>,
+    /// List sessions under repository headings (`sidebar.group_by_repo`).
+    group_by_repo: bool,
+    /// Collapsed repository groups, shared by all sidebars.
+    collapsed: HashSet<String>,
+    /// Repository of each managed session, from the tmux worker.
+    repos: HashMap<u32, String>,
+    /// Only sessions with this tag are listed (`T`).
+    tag_filter: Option<String>,
+    /// The session list as displayed, rebuilt with the window list.
+    rows: Vec<Row>,
+    /// When the last merged-branch check and branch/token lookups were queued.
+    merge_checked_at: Option<Instant>,
+    enriched_at: Option<Instant>,
+    cleanup: CleanupConfig,
+    /// Modification time of the config file the settings above came from.
+    config_stamp: Option<SystemTime>,
+    config_checked_at: Instant,
+    /// When the selection was last moved locally; window lists polled before
+    /// tmux caught up must not snap it back.
+    moved_at: Option<Instant>,
+    /// Outcome of the last action that can fail, shown in the footer.
+    message: Option<String>,
+    /// Session name being edited in place (`r`).
+    renaming: Option<Rename>,
+    /// Window whose interrupt waits for a second `i`.
+    interrupting: Option<u32>,
     selected: usize,
     tick: u64,
-    detector: StateDetector,
+    view: View,
+}
+
+/// What the sidebar pane is currently showing.
+enum View {
+    Sessions,
+    /// Files edited by Claude in one session, optionally with one file's diff open.
+    Changes(ChangesView),
+    /// A session's scratch register; Enter pastes into its Claude pane.
+    Clips(ClipsView),
+    /// A session's Claude conversation, read-only.
+    Transcript(TranscriptView),
+}
+
+struct ClipsView {
+    name: String,
+    window_index: u32,
+    pane_id: String,
+    clips: Vec<Clip>,
+    selected: usize,
+}
+
+struct ChangesView {
+    name: String,
+    files: Vec<String>,
+    selected: usize,
+    diff: Option<DiffView>,
+}
+
+struct DiffView {
+    lines: Vec<String>,
+    scroll: usize,
+}
+
+struct Rename {
+    /// Window index of the session being renamed.
+    index: u32,
+    text: String,
+}
+
+/// Where typing into the rename box has got to.
+#[derive(Debug, PartialEq, Eq)]
+enum Edit {
+    Typing,
+    Done,
+    Cancelled,
+}
+
+/// A session's transcript, or its seed: the same scrolling, searchable text.
+struct TranscriptView {
+    name: String,
+    /// What the view shows, for its header: "transcript" or "seed".
+    title: &'static str,
+    entries: Vec<Entry>,
+    /// `entries` laid out for the pane's current size.
+    lines: Vec<transcript::Line>,
+    width: u16,
+    /// Text rows visible at once.
+    rows: usize,
+    /// First visible row; starts at the end of the conversation.
+    scroll: usize,
+    query: String,
+    /// Row of the match `n`/`N` continue from; the view may not be able to
+    /// scroll it to the top.
+    hit: Option<usize>,
+    /// Search being typed after `/`.
+    typing: Option<String>,
+    error: Option<String>,
 }
 
 // ── Constants ──
 
-const REFRESH_EVERY: u64 = 2;
+/// How long a local selection move wins over tmux's active window.
+const SELECT_SETTLE: Duration = Duration::from_millis(400);
+
+/// Minimum gap between branch/token lookups for an unchanged window list.
+const ENRICH_EVERY: Duration = Duration::from_secs(2);
+
+/// Minimum gap between stuck checks; they read the command logs.
+const STUCK_EVERY: Duration = Duration::from_secs(15);
+
+/// Minimum gap between reads of the shared CI cache; GitHub itself is only
+/// asked every `ci.interval_secs`.
+const CI_EVERY: Duration = Duration::from_secs(30);
+
+/// Minimum gap between looks at the shared orphan-scan stamp; the scan
+/// itself runs every `cleanup.orphan_scan_hours`.
+const ORPHANS_EVERY: Duration = Duration::from_secs(600);
+
+/// Ticks a row keeps flashing after its session starts waiting (~100ms each).
+const FLASH_TICKS: u64 = 12;
+
+/// How often to look for edits to the config file.
+const CONFIG_POLL: Duration = Duration::from_secs(1);
+
+/// Shortest window list interval `sidebar.refresh_ms` may ask for.
+const MIN_REFRESH: Duration = Duration::from_millis(50);
+
+const HOOKS_NOTICE: &str = "hooks out of date \u{00b7} H reinstalls";
 
 // ── Public API ──
 
-pub fn run() -> Result<(), String> {
+/// Run the TUI, or the plain-text sidebar with `plain` or `sidebar.plain`.
+pub fn run(plain: bool) -> Result<(), String> {
+    if plain || config::load().is_ok_and(|c| c.sidebar.plain) {
+        return plain::run();
+    }
+
     // No alternate screen — render in-place in tmux pane (matches bash behavior)
     let mut stdout = stdout();
     execute!(stdout, cursor::Hide, DisableLineWrap).map_err(|e| format!("terminal: {e}"))?;
@@ -48,95 +254,1164 @@ pub fn run() -> Result<(), String> {
 // ── Helpers ──
 
 fn run_loop() -> Result<(), String> {
-    let backend = CrosstermBackend::new(io::stdout());
+    let backend = FrameBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend).map_err(|e| format!("terminal: {e}"))?;
 
+    // A broken config shouldn't take the sidebar down; fall back to defaults
+    let config_stamp = config::modified();
+    let config = config::load().unwrap_or_else(|e| {
+        log::warn!("{e}");
+        Config::default()
+    });
+
     let mut app = SidebarApp {
         windows: Vec::new(),
         states: HashMap::new(),
+        running_tools: HashMap::new(),
+        watch: HashMap::new(),
+        helpers: HashMap::new(),
+        asks: HashMap::new(),
+        row_format: None,
+        pinned: HashSet::new(),
+        clocked: None,
+        slots: HashMap::new(),
+        branches: HashMap::new(),
+        ages: HashMap::new(),
+        tokens: HashMap::new(),
+        pane_ids: HashMap::new(),
+        last_pane_ids: HashMap::new(),
+        event_ts: HashMap::new(),
+        tips: Vec::new(),
+        tips_hidden: tips::dismissed(),
+        hooks_broken: hooks_broken(),
+        flash_until: HashMap::new(),
+        bell: false,
+        merged: HashSet::new(),
+        broken: HashSet::new(),
+        crashed: HashMap::new(),
+        failed: HashMap::new(),
+        parked: HashSet::new(),
+        sections: Vec::new(),
+        crashes_seen: HashSet::new(),
+        stuck: HashMap::new(),
+        stuck_after_mins: 0,
+        stuck_checked_at: None,
+        ci: HashMap::new(),
+        ci_config: CiConfig::default(),
+        ci_checked_at: None,
+        orphans_checked_at: None,
+        profiles: Vec::new(),
+        group_by_repo: false,
+        collapsed: groups::collapsed(),
+        repos: HashMap::new(),
+        rows: Vec::new(),
+        merge_checked_at: None,
+        enriched_at: None,
+        cleanup: CleanupConfig::default(),
+        config_stamp,
+        config_checked_at: Instant::now(),
+        moved_at: None,
+        message: None,
+        interrupting: None,
+        renaming: None,
+        tag_filter: None,
         selected: 0,
         tick: 0,
-        detector: StateDetector::new(),
+        view: View::Sessions,
     };
+    let workers = Workers::spawn(refresh_interval(&config), config.sidebar.sections.clone());
+    apply_config(&mut app, config);
 
     loop {
-        // Refresh window list periodically
-        if app.tick % REFRESH_EVERY == 0 {
-            refresh_windows(&mut app);
+        // Take whatever the workers have finished since the last frame
+        for update in workers.updates.try_iter() {
+            apply_update(&mut app, update);
         }
+        reload_config(&mut app, &workers);
+        request_enrichment(&mut app, &workers);
+        recover_crashes(&mut app, &workers);
 
-        // Detect states every tick
-        app.states = app.detector.detect(&app.windows);
+        if let View::Transcript(tv) = &mut app.view
+            && let Ok(size) = terminal.size()
+        {
+            resize_transcript(tv, size.width, size.height);
+        }
+
+        let profile = app
+            .windows
+            .get(app.selected)
+            .filter(|w| w.managed)
+            .and_then(|w| profiles::rule_for(&app.profiles, &w.pane_path))
+            .map(profiles::summary)
+            .filter(|s| !s.is_empty());
 
         // Render
         terminal
             .draw(|frame| {
                 let area = frame.area();
-                let widget = SidebarWidget {
-                    windows: &app.windows,
-                    states: &app.states,
-                    selected: app.selected,
-                    tick: app.tick,
-                };
-                frame.render_widget(widget, area);
+                match &app.view {
+                    View::Sessions => {
+                        let widget = SidebarWidget {
+                            windows: &app.windows,
+                            rows: &app.rows,
+                            states: &app.states,
+                            running_tools: &app.running_tools,
+                            watch: &app.watch,
+                            helpers: &app.helpers,
+                            asks: &app.asks,
+                            flashing: &app.flash_until,
+                            row_format: app.row_format.as_deref(),
+                            pinned: &app.pinned,
+                            clocked: app.clocked.as_deref(),
+                            slots: &app.slots,
+                            branches: &app.branches,
+                            ages: &app.ages,
+                            tokens: &app.tokens,
+                            tip: if app.tips_hidden {
+                                None
+                            } else {
+                                tips::current(&app.tips, app.tick)
+                            },
+                            merged: &app.merged,
+                            broken: &app.broken,
+                            crashed: &app.crashed,
+                            failed: &app.failed,
+                            profile: profile.as_deref(),
+                            parked: &app.parked,
+                            sections: &app.sections,
+                            stuck: &app.stuck,
+                            ci: &app.ci,
+                            renaming: app.renaming.as_ref().map(|r| (r.index, r.text.as_str())),
+                            notice: app
+                                .message
+                                .as_deref()
+                                .or(app.hooks_broken.then_some(HOOKS_NOTICE))
+                                .or_else(|| {
+                                    let win = app.windows.get(app.selected)?;
+                                    app.stuck.get(&win.index).map(String::as_str)
+                                }),
+                            selected: app.selected,
+                            tick: app.tick,
+                        };
+                        frame.render_widget(widget, area);
+                    }
+                    View::Changes(cv) => {
+                        let widget = ChangesWidget {
+                            name: &cv.name,
+                            files: &cv.files,
+                            selected: cv.selected,
+                            diff: cv.diff.as_ref().map(|d| (d.lines.as_slice(), d.scroll)),
+                        };
+                        frame.render_widget(widget, area);
+                    }
+                    View::Clips(cv) => {
+                        let widget = ClipsWidget {
+                            name: &cv.name,
+                            clips: &cv.clips,
+                            selected: cv.selected,
+                        };
+                        frame.render_widget(widget, area);
+                    }
+                    View::Transcript(tv) => {
+                        let widget = TranscriptWidget {
+                            name: &tv.name,
+                            title: tv.title,
+                            lines: &tv.lines,
+                            scroll: tv.scroll,
+                            query: &tv.query,
+                            typing: tv.typing.as_deref(),
+                            error: tv.error.as_deref(),
+                        };
+                        frame.render_widget(widget, area);
+                    }
+                }
             })
             .map_err(|e| format!("render: {e}"))?;
 
+        // The transcript view takes typed text (its search), so it reads keys
+        // rather than actions
+        if let View::Transcript(tv) = &mut app.view {
+            if !handle_transcript_keys(tv, &event::poll_keys()) {
+                app.view = View::Sessions;
+            }
+            app.tick += 1;
+            continue;
+        }
+
+        // So does the rename box
+        if let Some(edit) = &mut app.renaming {
+            match handle_rename_keys(&mut edit.text, &event::poll_keys()) {
+                Edit::Typing => {}
+                Edit::Cancelled => app.renaming = None,
+                Edit::Done => finish_rename(&mut app, &workers),
+            }
+            app.tick += 1;
+            continue;
+        }
+
         // Handle events
         let actions = event::poll();
+
+        let keep_view = match &mut app.view {
+            View::Sessions | View::Transcript(_) => None,
+            View::Changes(cv) => Some(handle_changes_actions(cv, &actions)),
+            View::Clips(cv) => Some(handle_clips_actions(cv, &actions)),
+        };
+        if let Some(keep) = keep_view {
+            if !keep {
+                app.view = View::Sessions;
+            }
+            app.tick += 1;
+            continue;
+        }
+
         let mut moved = false;
 
+        if actions.iter().any(|a| !matches!(a, Action::Tick)) {
+            app.message = None;
+        }
+        // Any other key takes back a pending interrupt
+        if actions
+            .iter()
+            .any(|a| !matches!(a, Action::Tick | Action::Interrupt))
+        {
+            app.interrupting = None;
+        }
         for action in actions {
             match action {
-                Action::Up => {
-                    if app.selected > 0 {
-                        app.selected -= 1;
+                Action::Up | Action::Down => {
+                    let down = matches!(action, Action::Down);
+                    if let Some(from) = groups::cursor_row(&app.rows, app.selected)
+                        && let Some(next) = groups::step(&app.rows, from, down)
+                    {
+                        app.selected = next;
                         moved = true;
                     }
                 }
-                Action::Down => {
-                    if app.selected + 1 < app.windows.len() {
-                        app.selected += 1;
-                        moved = true;
+                Action::Collapse => {
+                    if let Some((repo, false)) = groups::group_of(&app.rows, app.selected) {
+                        let repo = repo.to_string();
+                        set_group_collapsed(&mut app, repo, true);
+                    }
+                }
+                Action::Expand => {
+                    if let Some((repo, true)) = groups::group_of(&app.rows, app.selected) {
+                        let repo = repo.to_string();
+                        set_group_collapsed(&mut app, repo, false);
                     }
                 }
                 Action::Select => {
-                    if let Some(win) = app.windows.get(app.selected) {
+                    if let Some((repo, true)) = groups::group_of(&app.rows, app.selected) {
+                        let repo = repo.to_string();
+                        set_group_collapsed(&mut app, repo, false);
+                    } else if let Some(win) = app.windows.get(app.selected) {
                         let _ = tmux::select_window(win.index);
-                        refresh_windows(&mut app);
-                        app.tick = 0;
-                        continue;
+                        app.moved_at = None;
+                        workers.refresh();
+                    }
+                }
+                Action::Changes => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i)) {
+                        app.view = View::Changes(open_changes(&win.name));
+                    }
+                }
+                Action::Clips if send::disabled() => {
+                    app.message = Some(send::SAFE_MODE.to_string());
+                }
+                Action::Clips => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i))
+                        && let Some(pane_id) = app.pane_ids.get(&win.index)
+                    {
+                        app.view = View::Clips(open_clips(win, pane_id));
+                    }
+                }
+                Action::Transcript => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i))
+                        && let Some(pane_id) = app.pane_ids.get(&win.index)
+                    {
+                        app.view = View::Transcript(open_transcript(&win.name, pane_id));
+                    }
+                }
+                Action::Seed => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i)) {
+                        app.view = View::Transcript(open_seed(&win.name));
+                    }
+                }
+                Action::Pin => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i)) {
+                        let _ = meta::toggle_pin(&win.name);
+                        workers.refresh();
+                    }
+                }
+                Action::Clock => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i)) {
+                        match attention::toggle(&win.name, &win.pane_path) {
+                            Ok(true) => {
+                                app.message = Some(format!(
+                                    "clocked in to {} \u{00b7} c clocks out",
+                                    win.name
+                                ));
+                                app.clocked = Some(win.name.clone());
+                            }
+                            Ok(false) => {
+                                app.message = Some(format!("clocked out of {}", win.name));
+                                app.clocked = None;
+                            }
+                            Err(e) => app.message = Some(e),
+                        }
+                    }
+                }
+                Action::Rename => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i)) {
+                        app.renaming = Some(Rename {
+                            index: win.index,
+                            text: win.name.clone(),
+                        });
+                    }
+                }
+                Action::Focus => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i)) {
+                        let _ = tmux::toggle_focus(&win.name);
                     }
                 }
+                Action::Adopt => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i))
+                        && !win.managed
+                    {
+                        let sidebar = start::sidebar_command();
+                        if tmux::adopt_window(&win.name, &win.pane_path, &sidebar).is_ok()
+                            && let Ok(pane_id) = tmux::get_claude_pane_id(&win.name)
+                        {
+                            start::purge_pane_state(&pane_id, None);
+                        }
+                        workers.refresh();
+                    }
+                }
+                Action::Cleanup => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i))
+                        && app.merged.contains(&win.name)
+                    {
+                        match cleanup::teardown(&win.name, &win.pane_path) {
+                            Ok(()) => {
+                                app.merged.remove(&win.name);
+                            }
+                            Err(e) => app.message = Some(e),
+                        }
+                        workers.refresh();
+                    }
+                }
+                Action::Repair => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i))
+                        && (app.crashed.contains_key(&win.index)
+                            || app.failed.contains_key(&win.index)
+                            || app.parked.contains(&win.index))
+                    {
+                        if let Err(e) = restart::restart(&win.name) {
+                            app.message = Some(e);
+                        }
+                        workers.refresh();
+                    } else if let Some(win) = focused(&app).and_then(|i| app.windows.get(i))
+                        && app.broken.contains(&win.index)
+                    {
+                        // Resume the closed pane's own conversation; `--continue`
+                        // would pick the directory's most recent one
+                        let session = app
+                            .last_pane_ids
+                            .get(&win.index)
+                            .and_then(|pane_id| state::session_for_pane(pane_id));
+                        let agent_cmd = profiles::command_for(
+                            &win.pane_path,
+                            &restart::restart_command(session.as_deref()),
+                        );
+                        match tmux::repair_claude_pane(win.index, &win.pane_path, &agent_cmd) {
+                            Ok(()) => {
+                                app.broken.remove(&win.index);
+                                layout::reapply_in(&Tmux, &win.name);
+                            }
+                            Err(e) => app.message = Some(e),
+                        }
+                        workers.refresh();
+                    }
+                }
+                Action::Interrupt => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i))
+                        && matches!(
+                            app.states.get(&win.index),
+                            Some(WindowState::Working | WindowState::Asking)
+                        )
+                    {
+                        if app.interrupting != Some(win.index) {
+                            app.interrupting = Some(win.index);
+                            app.message = Some(format!("i again to interrupt {}", win.name));
+                            continue;
+                        }
+                        app.interrupting = None;
+                        match interrupt::interrupt(&win.name, None) {
+                            Ok(()) => {
+                                app.stuck.remove(&win.index);
+                            }
+                            Err(e) => app.message = Some(e),
+                        }
+                    }
+                }
+                Action::Nudge => {
+                    if let Some(win) = focused(&app).and_then(|i| app.windows.get(i))
+                        && app.stuck.contains_key(&win.index)
+                        && let Some(pane_id) = app.pane_ids.get(&win.index)
+                    {
+                        workers.nudge(win.index, pane_id.clone());
+                    }
+                }
+                Action::FixHooks if app.hooks_broken => {
+                    for path in init::broken_settings(project().as_deref(), init::claude_version())
+                    {
+                        let _ = init::install_hooks(&path);
+                    }
+                    app.hooks_broken = hooks_broken();
+                }
                 Action::Quit => return Ok(()),
-                Action::Tick => {}
+                Action::FilterTag => {
+                    app.tag_filter = next_tag(&app.windows, app.tag_filter.as_deref());
+                    app.message = Some(match &app.tag_filter {
+                        Some(tag) => format!("showing {tag} \u{00b7} T next tag"),
+                        None if tags(&app.windows).is_empty() => "no tagged sessions".to_string(),
+                        None => "showing all sessions".to_string(),
+                    });
+                    relayout(&mut app);
+                }
+                Action::ToggleTips => {
+                    app.tips_hidden = !app.tips_hidden;
+                    tips::set_dismissed(app.tips_hidden);
+                }
+                Action::FixHooks
+                | Action::Back
+                | Action::Kill
+                | Action::Pause
+                | Action::Sort
+                | Action::Tick => {}
             }
         }
 
         // Single tmux call after all queued keys are processed
-        if moved {
-            if let Some(win) = app.windows.get(app.selected) {
-                let _ = tmux::select_window_sidebar(win.index);
+        if moved && let Some(win) = app.windows.get(app.selected) {
+            let _ = tmux::select_window_sidebar(win.index);
+            app.moved_at = Some(Instant::now());
+        }
+        app.tick += 1;
+    }
+}
+
+fn refresh_interval(config: &Config) -> Duration {
+    Duration::from_millis(config.sidebar.refresh_ms).max(MIN_REFRESH)
+}
+
+/// Take the sidebar's settings from the config.
+fn apply_config(app: &mut SidebarApp, config: Config) {
+    app.tips = if config.sidebar.show_tips {
+        tips::all(&tmux::prefix_key().unwrap_or_else(|| "C-b".to_string()))
+    } else {
+        Vec::new()
+    };
+    app.row_format = config.sidebar.row_format.map(|f| format::parse(&f));
+    app.bell = config.sidebar.bell;
+    app.cleanup = config.cleanup;
+    app.group_by_repo = config.sidebar.group_by_repo;
+    app.stuck_after_mins = config.sidebar.stuck_after_mins;
+    if app.stuck_after_mins == 0 {
+        app.stuck.clear();
+    }
+    app.ci_config = config.ci;
+    if !app.ci_config.enabled {
+        app.ci.clear();
+    }
+    app.profiles = config.profiles;
+    relayout(app);
+}
+
+/// Rebuild the displayed rows from the window list.
+fn relayout(app: &mut SidebarApp) {
+    let repos = app.group_by_repo.then_some(&app.repos);
+    let rows = groups::layout(&app.windows, repos, &app.collapsed);
+    // A filter whose last session is gone would show an empty list
+    if let Some(tag) = &app.tag_filter
+        && !tags(&app.windows).contains(&tag.as_str())
+    {
+        app.tag_filter = None;
+    }
+    app.rows = match &app.tag_filter {
+        Some(tag) => {
+            let windows = &app.windows;
+            groups::filter(rows, |i| names::tag(&windows[i].name) == Some(tag.as_str()))
+        }
+        None => rows,
+    };
+    // The active window may be filtered out; select one that's listed
+    if groups::cursor_row(&app.rows, app.selected).is_none()
+        && let Some(first) = groups::first(&app.rows)
+    {
+        app.selected = first;
+    }
+}
+
+/// Tags of the managed sessions, sorted.
+fn tags(windows: &[WindowInfo]) -> Vec<&str> {
+    let mut tags: Vec<&str> = windows
+        .iter()
+        .filter(|w| w.managed)
+        .filter_map(|w| names::tag(&w.name))
+        .collect();
+    tags.sort_unstable();
+    tags.dedup();
+    tags
+}
+
+/// The tag `T` switches to after `current`: the next one in order, then
+/// none (every session) after the last.
+fn next_tag(windows: &[WindowInfo], current: Option<&str>) -> Option<String> {
+    let tags = tags(windows);
+    let next = match current {
+        None => tags.first(),
+        Some(current) => tags.iter().find(|t| **t > current),
+    };
+    next.map(|t| t.to_string())
+}
+
+/// Collapse or open `repo`'s group in every sidebar. The selection stays on
+/// the same session, which shows as the group's heading while it's collapsed.
+fn set_group_collapsed(app: &mut SidebarApp, repo: String, collapsed: bool) {
+    if collapsed {
+        app.collapsed.insert(repo);
+    } else {
+        app.collapsed.remove(&repo);
+    }
+    groups::set_collapsed(&app.collapsed);
+    relayout(app);
+}
+
+/// The selected session, unless it's hidden in a collapsed group.
+fn focused(app: &SidebarApp) -> Option<usize> {
+    app.rows
+        .contains(&Row::Window(app.selected))
+        .then_some(app.selected)
+}
+
+/// Apply the config again when its file changes, so edits reach running
+/// sidebars. A config that no longer parses keeps the current settings and
+/// says why in the footer.
+fn reload_config(app: &mut SidebarApp, workers: &Workers) {
+    if app.config_checked_at.elapsed() < CONFIG_POLL {
+        return;
+    }
+    app.config_checked_at = Instant::now();
+    let stamp = config::modified();
+    if stamp == app.config_stamp {
+        return;
+    }
+    app.config_stamp = stamp;
+    match config::load() {
+        Ok(config) => {
+            workers.set_interval(refresh_interval(&config));
+            workers.set_sections(config.sidebar.sections.clone());
+            apply_config(app, config);
+            // The new row format or cleanup settings may need fresh lookups
+            app.enriched_at = None;
+            app.merge_checked_at = None;
+            app.stuck_checked_at = None;
+            app.ci_checked_at = None;
+            app.message = Some("config reloaded".to_string());
+            let _ = tmux::display_message("cove: config reloaded");
+        }
+        Err(e) => app.message = Some(e),
+    }
+}
+
+/// The project whose settings Claude reads in this window (the sidebar
+/// starts in the session's directory).
+fn project() -> Option<std::path::PathBuf> {
+    std::env::current_dir()
+        .ok()
+        .map(|dir| init::project_root(&dir))
+}
+
+/// Check cove's hooks once at startup, in every scope; a Claude update can
+/// leave them silently dead. Settings without any cove hooks are left to
+/// `cove init`.
+fn hooks_broken() -> bool {
+    !init::broken_settings(project().as_deref(), init::claude_version()).is_empty()
+}
+
+fn open_changes(name: &str) -> ChangesView {
+    let files = tmux::get_claude_pane_id(name)
+        .map(|pane_id| changes::files_for_pane(&pane_id))
+        .unwrap_or_default();
+    ChangesView {
+        name: name.to_string(),
+        files,
+        selected: 0,
+        diff: None,
+    }
+}
+
+/// Apply key actions to the changes view. Returns false when the view should close.
+fn handle_changes_actions(cv: &mut ChangesView, actions: &[Action]) -> bool {
+    for action in actions {
+        match (&mut cv.diff, action) {
+            (Some(d), Action::Up) => d.scroll = d.scroll.saturating_sub(1),
+            (Some(d), Action::Down) if d.scroll + 1 < d.lines.len() => d.scroll += 1,
+            (Some(_), Action::Back | Action::Quit) => cv.diff = None,
+            (None, Action::Up) => cv.selected = cv.selected.saturating_sub(1),
+            (None, Action::Down) if cv.selected + 1 < cv.files.len() => cv.selected += 1,
+            (None, Action::Select) => {
+                if let Some(file) = cv.files.get(cv.selected) {
+                    let lines = changes::diff(file).unwrap_or_else(|e| vec![e]);
+                    cv.diff = Some(DiffView { lines, scroll: 0 });
+                }
             }
-            // Skip next refresh so select-window has time to take effect
-            app.tick = 1;
-        } else {
-            app.tick += 1;
+            (None, Action::Back | Action::Quit) => return false,
+            _ => {}
         }
     }
+    true
 }
 
-fn refresh_windows(app: &mut SidebarApp) {
-    if let Ok(windows) = tmux::list_windows() {
-        // Sync selected to the tmux-active window
-        let active_pos = windows.iter().position(|w| w.is_active).unwrap_or(0);
+fn open_clips(win: &WindowInfo, pane_id: &str) -> ClipsView {
+    ClipsView {
+        name: win.name.clone(),
+        window_index: win.index,
+        pane_id: pane_id.to_string(),
+        clips: clips::entries(&win.name, changes::files_for_pane(pane_id)),
+        selected: 0,
+    }
+}
+
+/// Apply key actions to the clip view. Returns false when the view should close.
+fn handle_clips_actions(cv: &mut ClipsView, actions: &[Action]) -> bool {
+    for action in actions {
+        match action {
+            Action::Up => cv.selected = cv.selected.saturating_sub(1),
+            Action::Down if cv.selected + 1 < cv.clips.len() => cv.selected += 1,
+            Action::Select => {
+                if let Some(clip) = cv.clips.get(cv.selected) {
+                    // Paste without Enter, then focus the Claude pane so the user can finish the message
+                    let _ = tmux::send_text(&cv.pane_id, &clip.text);
+                    let _ = tmux::select_window(cv.window_index);
+                }
+                return false;
+            }
+            Action::Back | Action::Quit => return false,
+            _ => {}
+        }
+    }
+    true
+}
 
-        app.selected = active_pos;
-        app.windows = windows;
+fn open_transcript(name: &str, pane_id: &str) -> TranscriptView {
+    let loaded = state::session_for_pane(pane_id)
+        .ok_or_else(|| "no Claude session recorded yet".to_string())
+        .and_then(|session| transcript::load(&session));
+    let (entries, error) = match loaded {
+        Ok(entries) => (entries, None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+    TranscriptView {
+        name: name.to_string(),
+        title: "transcript",
+        entries,
+        lines: Vec::new(),
+        width: 0,
+        rows: 0,
+        scroll: usize::MAX,
+        query: String::new(),
+        hit: None,
+        typing: None,
+        error,
+    }
+}
 
-        // Clamp
-        if app.selected >= app.windows.len() && !app.windows.is_empty() {
-            app.selected = app.windows.len() - 1;
+/// The task description session `name` was started from, from the top.
+fn open_seed(name: &str) -> TranscriptView {
+    let (entries, error) = match meta::seed(name) {
+        Some(seed) => (
+            vec![Entry {
+                role: transcript::Role::User,
+                text: format!("{}\n\n{}", seed.url, seed.text),
+            }],
+            None,
+        ),
+        None => (
+            Vec::new(),
+            Some(format!("no seed; start with cove {name} --seed <url>")),
+        ),
+    };
+    TranscriptView {
+        name: name.to_string(),
+        title: "seed",
+        entries,
+        lines: Vec::new(),
+        width: 0,
+        rows: 0,
+        scroll: 0,
+        query: String::new(),
+        hit: None,
+        typing: None,
+        error,
+    }
+}
+
+/// Lay the transcript out again when the pane size changes, keeping the
+/// scroll position within it.
+fn resize_transcript(tv: &mut TranscriptView, width: u16, height: u16) {
+    let rows = ui::transcript_rows(height);
+    if tv.width != width {
+        // One column of margin on the left
+        tv.lines = transcript::layout(&tv.entries, width.saturating_sub(1), ui::role_label);
+        tv.width = width;
+    }
+    tv.rows = rows;
+    tv.scroll = tv.scroll.min(max_scroll(tv));
+}
+
+fn max_scroll(tv: &TranscriptView) -> usize {
+    tv.lines.len().saturating_sub(tv.rows)
+}
+
+/// Scroll to the next row matching the query after row `from` (or before it,
+/// when not `forward`), wrapping around the ends.
+fn find_match(tv: &mut TranscriptView, from: usize, forward: bool) {
+    let n = tv.lines.len();
+    let found = (1..=n)
+        .map(|step| {
+            if forward {
+                (from + step) % n
+            } else {
+                (from + n - step) % n
+            }
+        })
+        .find(|&i| ui::contains_ignore_case(&tv.lines[i].text, &tv.query));
+    if let Some(i) = found {
+        tv.scroll = i.min(max_scroll(tv));
+    }
+    tv.hit = found;
+}
+
+/// Apply key presses to the transcript view. Returns false when the view should close.
+fn handle_transcript_keys(tv: &mut TranscriptView, keys: &[KeyEvent]) -> bool {
+    for key in keys {
+        if let Some(typing) = &mut tv.typing {
+            match key.code {
+                KeyCode::Char(c) => typing.push(c),
+                KeyCode::Backspace => {
+                    typing.pop();
+                }
+                KeyCode::Enter => {
+                    tv.query = tv.typing.take().unwrap_or_default();
+                    // Search from the top row itself, so a match on it counts
+                    let from = tv
+                        .scroll
+                        .checked_sub(1)
+                        .unwrap_or(tv.lines.len().saturating_sub(1));
+                    find_match(tv, from, true);
+                }
+                KeyCode::Esc => tv.typing = None,
+                _ => {}
+            }
+            continue;
         }
+        let page = tv.rows.max(1);
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => tv.scroll = tv.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => tv.scroll += 1,
+            KeyCode::PageUp | KeyCode::Char('b') => tv.scroll = tv.scroll.saturating_sub(page),
+            KeyCode::PageDown | KeyCode::Char(' ') => tv.scroll += page,
+            KeyCode::Home | KeyCode::Char('g') => tv.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => tv.scroll = usize::MAX,
+            KeyCode::Char('/') => tv.typing = Some(String::new()),
+            KeyCode::Char('n') => find_match(tv, tv.hit.unwrap_or(tv.scroll), true),
+            KeyCode::Char('N') => find_match(tv, tv.hit.unwrap_or(tv.scroll), false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Esc | KeyCode::Char('q') => return false,
+            _ => {}
+        }
+        tv.scroll = tv.scroll.min(max_scroll(tv));
+    }
+    true
+}
+
+/// Apply key presses to the rename box's `text`.
+fn handle_rename_keys(text: &mut String, keys: &[KeyEvent]) -> Edit {
+    for key in keys {
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Edit::Cancelled;
+            }
+            // Ctrl+U clears the box, as in a shell
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => text.clear(),
+            KeyCode::Char(c) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Enter => return Edit::Done,
+            KeyCode::Esc => return Edit::Cancelled,
+            _ => {}
+        }
+    }
+    Edit::Typing
+}
+
+/// Rename the session in the rename box to what was typed. An invalid or
+/// taken name leaves the box open with the reason in the footer.
+fn finish_rename(app: &mut SidebarApp, workers: &Workers) {
+    let Some(edit) = &app.renaming else {
+        return;
+    };
+    let Some(win) = app.windows.iter().find(|w| w.index == edit.index) else {
+        // The window closed while its name was being edited
+        app.renaming = None;
+        return;
+    };
+    let new = edit.text.trim();
+    match rename::rename(&win.name, new) {
+        Ok(()) => {
+            app.renaming = None;
+            app.message = None;
+            workers.refresh();
+        }
+        Err(e) => app.message = Some(e),
+    }
+}
+
+fn apply_update(app: &mut SidebarApp, update: Update) {
+    match update {
+        Update::Windows(list) => {
+            let WindowList {
+                windows,
+                pinned,
+                clocked,
+                slots,
+                tips_hidden,
+                broken,
+                crashed,
+                failed,
+                parked,
+                collapsed,
+                repos,
+            } = *list;
+            let changed = windows.len() != app.windows.len()
+                || windows
+                    .iter()
+                    .zip(&app.windows)
+                    .any(|(a, b)| a.index != b.index || a.name != b.name);
+            if changed {
+                app.enriched_at = None;
+            }
+
+            // Follow tmux's active window, unless the user just moved the
+            // selection and tmux may not have caught up
+            let settling = app.moved_at.is_some_and(|t| t.elapsed() < SELECT_SETTLE);
+            let keep = app.windows.get(app.selected).map(|w| w.index);
+            app.selected = match keep {
+                Some(index) if settling => windows.iter().position(|w| w.index == index),
+                _ => windows.iter().position(|w| w.is_active),
+            }
+            .unwrap_or(0)
+            .min(windows.len().saturating_sub(1));
+            app.last_pane_ids
+                .retain(|index, _| windows.iter().any(|w| w.index == *index));
+            app.windows = windows;
+            app.pinned = pinned;
+            app.clocked = clocked;
+            app.slots = slots;
+            // Pick up `?` presses from other sidebars
+            app.tips_hidden = tips_hidden;
+            app.broken = broken;
+            app.crashed = crashed;
+            app.failed = failed;
+            app.parked = parked;
+            app.collapsed = collapsed;
+            app.repos = repos;
+            relayout(app);
+        }
+        Update::States(detection) => {
+            let selected = app.windows.get(app.selected).map(|w| w.index);
+            let changed = newly_waiting(&app.states, &detection.states, selected);
+            if !changed.is_empty() && app.bell {
+                print!("\x07");
+            }
+            for index in changed {
+                app.flash_until.insert(index, app.tick + FLASH_TICKS);
+            }
+            app.flash_until.retain(|_, until| *until > app.tick);
+
+            app.states = detection.states;
+            // Only Working sessions can be stuck
+            app.stuck
+                .retain(|index, _| app.states.get(index) == Some(&WindowState::Working));
+            app.last_pane_ids.extend(detection.pane_ids.clone());
+            app.pane_ids = detection.pane_ids;
+            app.event_ts = detection.event_ts;
+            app.asks = detection.asks;
+            app.running_tools = detection.running_tools;
+            app.watch = detection.watch;
+            app.helpers = detection.helpers;
+            app.ages = event_ages(&app.event_ts);
+        }
+        Update::Branches(branches) => app.branches = branches,
+        Update::Tokens(tokens) => app.tokens = tokens,
+        Update::Merged(merged) => app.merged = merged,
+        Update::Stuck(stuck) => app.stuck = stuck,
+        Update::Ci(ci) => {
+            if app.ci_config.enabled {
+                app.ci = ci;
+            }
+        }
+        Update::Sections(sections) => app.sections = sections,
+        Update::Nudged(index, Ok(())) => {
+            app.stuck.remove(&index);
+        }
+        Update::Nudged(_, Err(e)) => app.message = Some(e),
+    }
+}
+
+/// Queue git branch, token, merged-branch, stuck and CI lookups, and the
+/// orphan scan, when they're due.
+/// Each is only paid for when the row format or config asks for it.
+fn request_enrichment(app: &mut SidebarApp, workers: &Workers) {
+    let uses = |var| {
+        app.row_format
+            .as_deref()
+            .is_some_and(|f| format::uses(f, var))
+    };
+    let (branches, tokens) = (uses(Var::Branch), uses(Var::Tokens));
+    let enrich_due =
+        (branches || tokens) && app.enriched_at.is_none_or(|t| t.elapsed() >= ENRICH_EVERY);
+    let interval = Duration::from_secs(app.cleanup.interval_secs.max(1));
+    let merge_due =
+        app.cleanup.check_merged && app.merge_checked_at.is_none_or(|t| t.elapsed() >= interval);
+    let working: HashMap<u32, u64> = app
+        .states
+        .iter()
+        .filter(|(_, state)| **state == WindowState::Working)
+        .filter_map(|(index, _)| Some((*index, *app.event_ts.get(index)?)))
+        .collect();
+    let stuck_due = app.stuck_after_mins > 0
+        && !working.is_empty()
+        && app
+            .stuck_checked_at
+            .is_none_or(|t| t.elapsed() >= STUCK_EVERY);
+    let ci_due = app.ci_config.enabled && app.ci_checked_at.is_none_or(|t| t.elapsed() >= CI_EVERY);
+    let orphans_due = app.cleanup.orphan_scan_hours > 0
+        && app
+            .orphans_checked_at
+            .is_none_or(|t| t.elapsed() >= ORPHANS_EVERY);
+    if app.windows.is_empty() || !(enrich_due || merge_due || stuck_due || ci_due || orphans_due) {
+        return;
+    }
+
+    workers.enrich(EnrichRequest {
+        windows: app.windows.clone(),
+        pane_ids: app.pane_ids.clone(),
+        branches: enrich_due && branches,
+        tokens: enrich_due && tokens,
+        merged: merge_due.then(|| app.cleanup.clone()),
+        stuck: stuck_due.then(|| (working, app.stuck_after_mins * 60)),
+        ci: ci_due.then_some(app.ci_config.interval_secs),
+        orphans: orphans_due.then_some(app.cleanup.orphan_scan_hours),
+    });
+    if enrich_due {
+        app.enriched_at = Some(Instant::now());
+    }
+    if merge_due {
+        app.merge_checked_at = Some(Instant::now());
+    }
+    if stuck_due {
+        app.stuck_checked_at = Some(Instant::now());
+    }
+    if ci_due {
+        app.ci_checked_at = Some(Instant::now());
+    }
+    if orphans_due {
+        app.orphans_checked_at = Some(Instant::now());
+    }
+}
+
+/// Apply the crash policy to this window's Claude pane once it has crashed.
+/// Every sidebar sees every crash, but only the one in the crashed window
+/// acts, so each is handled once.
+fn recover_crashes(app: &mut SidebarApp, workers: &Workers) {
+    // A pane that came back can crash again later
+    app.crashes_seen
+        .retain(|pane| app.crashed.values().any(|p| p == pane));
+    let new: Vec<(u32, String)> = app
+        .crashed
+        .iter()
+        .filter(|(_, pane)| !app.crashes_seen.contains(*pane))
+        .map(|(index, pane)| (*index, pane.clone()))
+        .collect();
+    if new.is_empty() {
+        return;
+    }
+    let own = crash::own_window(&Tmux);
+    for (index, pane) in new {
+        app.crashes_seen.insert(pane.clone());
+        let Some(win) = app.windows.iter().find(|w| w.index == index) else {
+            continue;
+        };
+        if own != Some(index) {
+            continue;
+        }
+        let config = config::load().unwrap_or_default();
+        match crash::recover(&config, &win.name, &pane) {
+            Ok(Recovery::Respawned) => workers.refresh(),
+            Ok(_) => {}
+            Err(e) => app.message = Some(format!("{}: {e}", win.name)),
+        }
+    }
+}
+
+/// Windows other than the selected one that just started waiting for the
+/// user (Asking or Idle). Nothing flashes before the first detection.
+fn newly_waiting(
+    before: &HashMap<u32, WindowState>,
+    after: &HashMap<u32, WindowState>,
+    selected: Option<u32>,
+) -> Vec<u32> {
+    if before.is_empty() {
+        return Vec::new();
+    }
+    let mut changed: Vec<u32> = after
+        .iter()
+        .filter(|&(index, state)| {
+            matches!(state, WindowState::Asking | WindowState::Idle)
+                && Some(*index) != selected
+                && before.get(index) != Some(state)
+        })
+        .map(|(index, _)| *index)
+        .collect();
+    changed.sort_unstable();
+    changed
+}
+
+fn event_ages(event_ts: &HashMap<u32, u64>) -> HashMap<u32, u64> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    event_ts
+        .iter()
+        .map(|(index, ts)| (*index, now.saturating_sub(*ts)))
+        .collect()
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newly_waiting() {
+        let before = HashMap::from([
+            (1, WindowState::Working),
+            (2, WindowState::Working),
+            (3, WindowState::Idle),
+        ]);
+        let after = HashMap::from([
+            (1, WindowState::Asking),
+            (2, WindowState::Idle),
+            (3, WindowState::Idle),
+            (4, WindowState::Idle),
+        ]);
+        assert_eq!(newly_waiting(&before, &after, Some(2)), vec![1, 4]);
+        assert!(newly_waiting(&HashMap::new(), &after, None).is_empty());
+    }
+
+    #[test]
+    fn test_rename_box_editing() {
+        let key = |c| KeyEvent::from(KeyCode::Char(c));
+        let mut text = "api".to_string();
+        let keys = [KeyEvent::from(KeyCode::Backspace), key('p'), key('2')];
+        assert_eq!(handle_rename_keys(&mut text, &keys), Edit::Typing);
+        assert_eq!(text, "app2");
+
+        let clear = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL);
+        let keys = [clear, key('w'), KeyEvent::from(KeyCode::Enter), key('x')];
+        assert_eq!(handle_rename_keys(&mut text, &keys), Edit::Done);
+        // Nothing after Enter reaches the box
+        assert_eq!(text, "w");
+
+        let keys = [key('x'), KeyEvent::from(KeyCode::Esc)];
+        assert_eq!(handle_rename_keys(&mut text, &keys), Edit::Cancelled);
+    }
+
+    #[test]
+    fn test_tag_filter_cycles_through_tags() {
+        let windows: Vec<WindowInfo> = ["work/api", "oss/web", "cli", "work/infra"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| WindowInfo {
+                id: format!("@{i}"),
+                index: i as u32,
+                name: name.to_string(),
+                is_active: false,
+                pane_path: String::new(),
+                managed: true,
+            })
+            .collect();
+        assert_eq!(tags(&windows), ["oss", "work"]);
+        assert_eq!(next_tag(&windows, None).as_deref(), Some("oss"));
+        assert_eq!(next_tag(&windows, Some("oss")).as_deref(), Some("work"));
+        assert_eq!(next_tag(&windows, Some("work")), None);
+        assert_eq!(next_tag(&windows[2..3], None), None);
+    }
+
+    #[test]
+    fn test_transcript_search_wraps_around() {
+        let entries = (0..10)
+            .map(|i| Entry {
+                role: transcript::Role::User,
+                text: if i % 4 == 1 {
+                    format!("Fix bug {i}")
+                } else {
+                    format!("line {i}")
+                },
+            })
+            .collect();
+        let mut tv = TranscriptView {
+            name: "api".to_string(),
+            title: "transcript",
+            entries,
+            lines: Vec::new(),
+            width: 0,
+            rows: 0,
+            scroll: usize::MAX,
+            query: String::new(),
+            hit: None,
+            typing: None,
+            error: None,
+        };
+        // 10 entries with a blank row between each: 19 rows, 5 visible
+        resize_transcript(&mut tv, 40, 8);
+        assert_eq!((tv.lines.len(), tv.scroll), (19, 14));
+
+        let key = |c| KeyEvent::from(KeyCode::Char(c));
+        let mut keys = vec![key('/')];
+        keys.extend("BUG".chars().map(key));
+        keys.push(KeyEvent::from(KeyCode::Enter));
+        assert!(handle_transcript_keys(&mut tv, &keys));
+        assert_eq!(tv.query, "BUG");
+        // "Fix bug 9" is row 18, past the last full page, so the view stops at the end
+        assert_eq!(tv.scroll, 14);
+        // Wraps to "Fix bug 1" at row 2, then "Fix bug 5" at row 10
+        handle_transcript_keys(&mut tv, &[key('n')]);
+        assert_eq!(tv.scroll, 2);
+        handle_transcript_keys(&mut tv, &[key('n')]);
+        assert_eq!(tv.scroll, 10);
+        handle_transcript_keys(&mut tv, &[key('N')]);
+        assert_eq!(tv.scroll, 2);
+
+        assert!(!handle_transcript_keys(
+            &mut tv,
+            &[KeyEvent::from(KeyCode::Esc)]
+        ));
     }
 }
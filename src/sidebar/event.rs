@@ -2,14 +2,58 @@
 
 use std::time::Duration;
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
 // ── Types ──
 
 pub enum Action {
     Up,
     Down,
+    /// Collapse the selected session's repository group.
+    Collapse,
+    /// Open the selected collapsed group.
+    Expand,
     Select,
+    /// Open the changes view for the selected session.
+    Changes,
+    /// Open the scratch register (clips) for the selected session.
+    Clips,
+    /// Open the selected session's Claude transcript.
+    Transcript,
+    /// Show the task description the selected session was seeded with.
+    Seed,
+    /// Leave the current sub-view.
+    Back,
+    /// Pin or unpin the selected session.
+    Pin,
+    /// Clock in to or out of supervising the selected session.
+    Clock,
+    /// Rename the selected session in place.
+    Rename,
+    /// Zoom the selected session's Claude pane (focus mode).
+    Focus,
+    /// Give the selected window opened outside cove the cove layout.
+    Adopt,
+    /// Kill the selected session (`cove top`).
+    Kill,
+    /// Pause or resume the selected session's processes (`cove top`).
+    Pause,
+    /// Cycle the sort column (`cove top`).
+    Sort,
+    /// Kill a merged session and remove its worktree.
+    Cleanup,
+    /// Give a window whose Claude pane was closed a new one.
+    Repair,
+    /// Interrupt the selected session's Claude (Escape).
+    Interrupt,
+    /// Ask a possibly stuck session's Claude what's blocking it.
+    Nudge,
+    /// Reinstall cove's Claude hooks after a compatibility warning.
+    FixHooks,
+    /// List only the sessions with the next tag, or all of them again.
+    FilterTag,
+    /// Hide or show the tips footer.
+    ToggleTips,
     Quit,
     Tick,
 }
@@ -19,47 +63,81 @@ pub enum Action {
 /// Poll for input events with a 100ms timeout. Returns accumulated actions.
 /// Batches rapid arrow presses into single moves (key draining).
 pub fn poll() -> Vec<Action> {
-    let mut actions = Vec::new();
+    let mut actions = poll_with(key_to_action);
+
+    if actions.is_empty() {
+        actions.push(Action::Tick);
+    }
+
+    actions
+}
+
+/// Like `poll`, but returns the key presses themselves, for views that take
+/// typed text (an empty list means the timeout passed).
+pub fn poll_keys() -> Vec<KeyEvent> {
+    poll_with(|key| (key.kind == KeyEventKind::Press).then_some(key))
+}
+
+// ── Helpers ──
+
+fn poll_with<T>(map: impl Fn(KeyEvent) -> Option<T>) -> Vec<T> {
+    let mut out = Vec::new();
 
     if event::poll(Duration::from_millis(100)).unwrap_or(false) {
         // Process first event
         if let Ok(Event::Key(key)) = event::read()
-            && let Some(action) = key_to_action(key)
+            && let Some(item) = map(key)
         {
-            actions.push(action);
+            out.push(item);
         }
 
         // Drain queued keys (batch rapid arrow presses)
         while event::poll(Duration::from_millis(0)).unwrap_or(false) {
             if let Ok(Event::Key(key)) = event::read()
-                && let Some(action) = key_to_action(key)
+                && let Some(item) = map(key)
             {
-                actions.push(action);
+                out.push(item);
             }
         }
     }
 
-    if actions.is_empty() {
-        actions.push(Action::Tick);
-    }
-
-    actions
+    out
 }
 
-// ── Helpers ──
-
 fn key_to_action(key: KeyEvent) -> Option<Action> {
     // Only handle key press events (ignore release/repeat)
-    if key.kind != crossterm::event::KeyEventKind::Press {
+    if key.kind != KeyEventKind::Press {
         return None;
     }
 
     match key.code {
         KeyCode::Up | KeyCode::Char('k') => Some(Action::Up),
         KeyCode::Down | KeyCode::Char('j') => Some(Action::Down),
+        KeyCode::Left | KeyCode::Char('h') => Some(Action::Collapse),
+        KeyCode::Right | KeyCode::Char('l') => Some(Action::Expand),
         KeyCode::Enter => Some(Action::Select),
-        KeyCode::Char('q') => Some(Action::Quit),
+        KeyCode::Char('d') => Some(Action::Changes),
+        KeyCode::Char('y') => Some(Action::Clips),
+        KeyCode::Char('t') => Some(Action::Transcript),
+        KeyCode::Char('S') => Some(Action::Seed),
+        KeyCode::Char('f') => Some(Action::Focus),
+        KeyCode::Char('p') => Some(Action::Pin),
         KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
+        KeyCode::Char('c') => Some(Action::Clock),
+        KeyCode::Char('r') => Some(Action::Rename),
+        KeyCode::Char('a') => Some(Action::Adopt),
+        KeyCode::Char('x') => Some(Action::Kill),
+        KeyCode::Char('z') => Some(Action::Pause),
+        KeyCode::Char('X') => Some(Action::Cleanup),
+        KeyCode::Char('s') => Some(Action::Sort),
+        KeyCode::Char('T') => Some(Action::FilterTag),
+        KeyCode::Esc => Some(Action::Back),
+        KeyCode::Char('?') => Some(Action::ToggleTips),
+        KeyCode::Char('H') => Some(Action::FixHooks),
+        KeyCode::Char('R') => Some(Action::Repair),
+        KeyCode::Char('i') => Some(Action::Interrupt),
+        KeyCode::Char('n') => Some(Action::Nudge),
+        KeyCode::Char('q') => Some(Action::Quit),
         _ => None,
     }
 }
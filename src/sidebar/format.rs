@@ -0,0 +1,185 @@
+// ── Sidebar row templates ──
+//
+// `sidebar.row_format` in the config is a template like "{icon} {name} {age}".
+// Text outside braces is copied as-is; `{var}` is replaced per row:
+//
+//   {icon}    state glyph (spinner while working)
+//   {name}    window name
+//   {status}  "waiting…" / "your turn" / empty
+//   {tool}    tool Claude is running, e.g. "Bash (cargo test)"
+//   {watch}   last `cove watch-run` result (✓ / ✗ / …)
+//   {ci}      checks of the session's pull request (✓ / ✗ / ●)
+//   {helper}  long command in another pane of the window, e.g. "⚙ cargo 2m"
+//   {branch}  git branch of the window's directory
+//   {dir}     window directory, with $HOME shortened to ~
+//   {age}     time since the last hook event, e.g. "4m"
+//   {index}   tmux window index
+//   {slot}    quick-switch slot (`cove go`), 1–9
+//   {pin}     ▴ if the session is pinned
+//   {asks}    "?×3" when several questions are waiting for an answer
+//   {tokens}  tokens the Claude session has spent, e.g. "12.3k"
+//
+// Unknown variables are kept literally so mistakes are visible in the sidebar.
+
+// ── Types ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Var {
+    Icon,
+    Name,
+    Status,
+    Tool,
+    Watch,
+    Ci,
+    Helper,
+    Branch,
+    Dir,
+    Age,
+    Index,
+    Slot,
+    Pin,
+    Asks,
+    Tokens,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Literal(String),
+    Var(Var),
+}
+
+// ── Helpers ──
+
+fn var_from_name(name: &str) -> Option<Var> {
+    Some(match name {
+        "icon" => Var::Icon,
+        "name" => Var::Name,
+        "status" => Var::Status,
+        "tool" => Var::Tool,
+        "watch" => Var::Watch,
+        "ci" => Var::Ci,
+        "helper" => Var::Helper,
+        "branch" => Var::Branch,
+        "dir" => Var::Dir,
+        "age" => Var::Age,
+        "index" => Var::Index,
+        "slot" => Var::Slot,
+        "pin" => Var::Pin,
+        "asks" => Var::Asks,
+        "tokens" => Var::Tokens,
+        _ => return None,
+    })
+}
+
+fn push_literal(segments: &mut Vec<Segment>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(Segment::Literal(prev)) = segments.last_mut() {
+        prev.push_str(text);
+    } else {
+        segments.push(Segment::Literal(text.to_string()));
+    }
+}
+
+// ── Public API ──
+
+/// Split a row template into literal text and variables.
+pub fn parse(template: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        push_literal(&mut segments, &rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find('}') {
+            Some(close) => {
+                let name = &after[..close];
+                match var_from_name(name) {
+                    Some(var) => segments.push(Segment::Var(var)),
+                    None => push_literal(&mut segments, &rest[open..open + close + 2]),
+                }
+                rest = &after[close + 1..];
+            }
+            None => {
+                push_literal(&mut segments, &rest[open..]);
+                rest = "";
+            }
+        }
+    }
+    push_literal(&mut segments, rest);
+    segments
+}
+
+/// True if the template references `var` (lets the sidebar skip costly lookups).
+pub fn uses(segments: &[Segment], var: Var) -> bool {
+    segments.contains(&Segment::Var(var))
+}
+
+/// Compact duration, e.g. "45s", "12m", "3h", "2d".
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{s}s"),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86400),
+    }
+}
+
+/// Compact token count, e.g. "950", "12.3k", "2.5M".
+pub fn format_tokens(n: u64) -> String {
+    match n {
+        n if n < 1000 => n.to_string(),
+        n if n < 1_000_000 => format!("{:.1}k", n as f64 / 1000.0),
+        n => format!("{:.1}M", n as f64 / 1_000_000.0),
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vars_and_literals() {
+        let segments = parse("{icon} {name} [{branch}]");
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Var(Var::Icon),
+                Segment::Literal(" ".into()),
+                Segment::Var(Var::Name),
+                Segment::Literal(" [".into()),
+                Segment::Var(Var::Branch),
+                Segment::Literal("]".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_keeps_unknown_and_unclosed() {
+        assert_eq!(
+            parse("{nope} {name} {oops"),
+            vec![
+                Segment::Literal("{nope} ".into()),
+                Segment::Var(Var::Name),
+                Segment::Literal(" {oops".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(5), "5s");
+        assert_eq!(format_age(125), "2m");
+        assert_eq!(format_age(7200), "2h");
+        assert_eq!(format_age(200_000), "2d");
+    }
+
+    #[test]
+    fn test_format_tokens() {
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(12_345), "12.3k");
+        assert_eq!(format_tokens(2_500_000), "2.5M");
+    }
+}
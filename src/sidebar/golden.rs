@@ -0,0 +1,228 @@
+// ── Golden-file tests for the sidebar's widgets ──
+//
+// Widgets draw into a ratatui `Buffer`; `snapshot` flattens one into text a
+// reviewer can read: the characters, then a second grid with one letter per
+// cell for its foreground color (upper case when bold) and, if any cell has
+// a background, a third marking those cells. `assert_golden` compares the
+// snapshot with src/sidebar/golden/<name>.txt; run the tests with
+// `COVE_BLESS=1` to write the files after a deliberate change and review
+// the diff. `Fixture` owns everything a `SidebarWidget` borrows, so a test
+// only fills in what it's about.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier};
+use ratatui::widgets::Widget;
+
+use crate::ci::CiStatus;
+use crate::colors;
+use crate::sidebar::format::Segment;
+use crate::sidebar::groups::{self, Row};
+use crate::sidebar::sections::Section;
+use crate::sidebar::state::WindowState;
+use crate::sidebar::ui::SidebarWidget;
+use crate::tmux::WindowInfo;
+use crate::watch::WatchStatus;
+
+// ── Types ──
+
+/// Owned data for one `SidebarWidget`.
+#[derive(Default)]
+pub struct Fixture {
+    pub windows: Vec<WindowInfo>,
+    pub rows: Vec<Row>,
+    pub states: HashMap<u32, WindowState>,
+    pub running_tools: HashMap<u32, String>,
+    pub watch: HashMap<u32, WatchStatus>,
+    pub helpers: HashMap<u32, String>,
+    pub asks: HashMap<u32, u32>,
+    pub flashing: HashMap<u32, u64>,
+    pub row_format: Option<Vec<Segment>>,
+    pub pinned: HashSet<String>,
+    pub clocked: Option<String>,
+    pub slots: HashMap<String, u8>,
+    pub branches: HashMap<u32, String>,
+    pub ages: HashMap<u32, u64>,
+    pub tokens: HashMap<u32, u64>,
+    pub tip: Option<String>,
+    pub merged: HashSet<String>,
+    pub broken: HashSet<u32>,
+    pub crashed: HashMap<u32, String>,
+    pub failed: HashMap<u32, Vec<String>>,
+    pub profile: Option<String>,
+    pub parked: HashSet<u32>,
+    pub sections: Vec<Section>,
+    pub stuck: HashMap<u32, String>,
+    pub ci: HashMap<String, CiStatus>,
+    pub renaming: Option<(u32, String)>,
+    pub notice: Option<String>,
+    pub selected: usize,
+    pub tick: u64,
+}
+
+// ── Constants ──
+
+/// Letters for the palette in the color grid; anything else is '?'.
+const COLORS: &[(Color, char)] = &[
+    (colors::LAVENDER, 'l'),
+    (colors::BLUE, 'b'),
+    (colors::OVERLAY, 'o'),
+    (colors::GREEN, 'g'),
+    (colors::SURFACE, 's'),
+    (colors::RED, 'r'),
+    (colors::PEACH, 'p'),
+    (Color::White, 'w'),
+    (Color::Reset, '.'),
+];
+
+// ── Helpers ──
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/sidebar/golden")
+        .join(format!("{name}.txt"))
+}
+
+fn color_letter(color: Color, bold: bool) -> char {
+    let letter = COLORS
+        .iter()
+        .find(|(c, _)| *c == color)
+        .map_or('?', |(_, l)| *l);
+    if bold {
+        letter.to_ascii_uppercase()
+    } else {
+        letter
+    }
+}
+
+/// Rows of `buf`, one string per row with `cell` turning each cell into text.
+fn grid(buf: &Buffer, cell: impl Fn(&ratatui::buffer::Cell) -> String) -> Vec<String> {
+    let area = buf.area;
+    (area.y..area.y + area.height)
+        .map(|y| {
+            let row: String = (area.x..area.x + area.width)
+                .map(|x| cell(&buf[(x, y)]))
+                .collect();
+            row.trim_end().to_string()
+        })
+        .collect()
+}
+
+// ── Public API ──
+
+impl Fixture {
+    /// Managed sessions named `names` in `/code/<name>`, indexed from 1, the
+    /// first one active and selected.
+    pub fn new(names: &[&str]) -> Self {
+        let windows: Vec<WindowInfo> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| WindowInfo {
+                id: format!("@{}", i + 1),
+                index: i as u32 + 1,
+                name: name.to_string(),
+                is_active: i == 0,
+                pane_path: format!("/code/{name}"),
+                managed: true,
+            })
+            .collect();
+        let rows = groups::layout(&windows, None, &HashSet::new());
+        Self {
+            windows,
+            rows,
+            ..Self::default()
+        }
+    }
+
+    pub fn widget(&self) -> SidebarWidget<'_> {
+        SidebarWidget {
+            windows: &self.windows,
+            rows: &self.rows,
+            states: &self.states,
+            running_tools: &self.running_tools,
+            watch: &self.watch,
+            helpers: &self.helpers,
+            asks: &self.asks,
+            flashing: &self.flashing,
+            row_format: self.row_format.as_deref(),
+            pinned: &self.pinned,
+            clocked: self.clocked.as_deref(),
+            slots: &self.slots,
+            branches: &self.branches,
+            ages: &self.ages,
+            tokens: &self.tokens,
+            tip: self.tip.as_deref(),
+            merged: &self.merged,
+            broken: &self.broken,
+            crashed: &self.crashed,
+            failed: &self.failed,
+            profile: self.profile.as_deref(),
+            parked: &self.parked,
+            sections: &self.sections,
+            stuck: &self.stuck,
+            ci: &self.ci,
+            renaming: self.renaming.as_ref().map(|(i, t)| (*i, t.as_str())),
+            notice: self.notice.as_deref(),
+            selected: self.selected,
+            tick: self.tick,
+        }
+    }
+}
+
+/// Draw `widget` on a `width` × `height` buffer and flatten it for a golden
+/// file.
+pub fn snapshot(widget: impl Widget, width: u16, height: u16) -> String {
+    let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+    widget.render(buf.area, &mut buf);
+
+    // Wide characters leave an empty cell behind them; keep the grids aligned
+    let text = grid(&buf, |c| match c.symbol() {
+        "" => String::new(),
+        s => s.to_string(),
+    });
+    let fg = grid(&buf, |c| {
+        let letter = color_letter(c.fg, c.modifier.contains(Modifier::BOLD));
+        match c.symbol() {
+            " " if c.bg == Color::Reset => " ".to_string(),
+            _ => letter.to_string(),
+        }
+    });
+    let mut out = format!(
+        "── text ──\n{}\n── fg ──\n{}\n",
+        text.join("\n"),
+        fg.join("\n")
+    );
+    let bg = grid(&buf, |c| {
+        if c.bg == Color::Reset { " " } else { "#" }.to_string()
+    });
+    if bg.iter().any(|row| !row.is_empty()) {
+        out.push_str(&format!("── bg ──\n{}\n", bg.join("\n")));
+    }
+    out
+}
+
+/// Compare `actual` with golden file `name`, or write it with `COVE_BLESS=1`.
+pub fn assert_golden(name: &str, actual: &str) {
+    let path = golden_path(name);
+    if std::env::var_os("COVE_BLESS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "no golden file {}; run with COVE_BLESS=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "{name} differs from {}; rerun with COVE_BLESS=1 if the change is intended\n\
+         ── expected ──\n{expected}\n── actual ──\n{actual}",
+        path.display()
+    );
+}
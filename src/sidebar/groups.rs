@@ -0,0 +1,309 @@
+// ── Repository groups ──
+//
+// With `sidebar.group_by_repo`, the session list is split under one heading
+// per repository (worktrees count as their main repository). ← collapses the
+// group of the selected session and → (or enter) opens it again; a collapsed
+// group is a single row showing how many of its sessions are asking, waiting
+// for you or working. Collapsed groups are kept in a file under the state dir,
+// shared by every sidebar and kept across restarts.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use crate::paths;
+use crate::tmux::WindowInfo;
+
+// ── Types ──
+
+/// One line of the session list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Row {
+    /// Heading of a repository's sessions, listing their positions in the
+    /// window list.
+    Group {
+        repo: String,
+        collapsed: bool,
+        windows: Vec<usize>,
+    },
+    /// A session, by position in the window list.
+    Window(usize),
+    /// Heading above the windows opened outside cove.
+    Other,
+}
+
+// ── Constants ──
+
+const COLLAPSED_FILE: &str = "collapsed-groups";
+
+// ── Helpers ──
+
+/// The window selecting `row` selects: its own, or a collapsed group's first.
+fn row_window(row: &Row) -> Option<usize> {
+    match row {
+        Row::Window(i) => Some(*i),
+        Row::Group {
+            collapsed: true,
+            windows,
+            ..
+        } => windows.first().copied(),
+        _ => None,
+    }
+}
+
+// ── Public API ──
+
+/// Lay out `windows` (managed first) as rows. With `repos`, managed sessions
+/// are grouped by repository in order of first appearance, so a group holding
+/// a pinned session comes first; sessions of collapsed groups are left out.
+pub fn layout(
+    windows: &[WindowInfo],
+    repos: Option<&HashMap<u32, String>>,
+    collapsed: &HashSet<String>,
+) -> Vec<Row> {
+    let managed = windows.iter().take_while(|w| w.managed).count();
+    let mut rows = Vec::new();
+    match repos {
+        None => rows.extend((0..managed).map(Row::Window)),
+        Some(repos) => {
+            let mut order: Vec<&str> = Vec::new();
+            let mut members: HashMap<&str, Vec<usize>> = HashMap::new();
+            for (i, win) in windows[..managed].iter().enumerate() {
+                let repo = repos
+                    .get(&win.index)
+                    .map_or(win.name.as_str(), String::as_str);
+                if !members.contains_key(repo) {
+                    order.push(repo);
+                }
+                members.entry(repo).or_default().push(i);
+            }
+            for repo in order {
+                let windows = members.remove(repo).unwrap_or_default();
+                let collapsed = collapsed.contains(repo);
+                let shown = if collapsed {
+                    Vec::new()
+                } else {
+                    windows.clone()
+                };
+                rows.push(Row::Group {
+                    repo: repo.to_string(),
+                    collapsed,
+                    windows,
+                });
+                rows.extend(shown.into_iter().map(Row::Window));
+            }
+        }
+    }
+    if managed < windows.len() {
+        rows.push(Row::Other);
+        rows.extend((managed..windows.len()).map(Row::Window));
+    }
+    rows
+}
+
+/// `rows` with only the windows `keep` accepts, dropping headings left
+/// without any.
+pub fn filter(rows: Vec<Row>, keep: impl Fn(usize) -> bool) -> Vec<Row> {
+    let mut out: Vec<Row> = Vec::new();
+    for row in rows {
+        match row {
+            Row::Window(i) if !keep(i) => continue,
+            Row::Group {
+                repo,
+                collapsed,
+                windows,
+            } => {
+                let windows: Vec<usize> = windows.into_iter().filter(|i| keep(*i)).collect();
+                if !windows.is_empty() {
+                    out.push(Row::Group {
+                        repo,
+                        collapsed,
+                        windows,
+                    });
+                }
+            }
+            row => out.push(row),
+        }
+    }
+    if matches!(out.last(), Some(Row::Other)) {
+        out.pop();
+    }
+    out
+}
+
+/// Position of the row window `selected` shows on: its own row, or its
+/// group's heading when the group is collapsed.
+pub fn cursor_row(rows: &[Row], selected: usize) -> Option<usize> {
+    rows.iter().position(|row| match row {
+        Row::Window(i) => *i == selected,
+        Row::Group {
+            collapsed: true,
+            windows,
+            ..
+        } => windows.contains(&selected),
+        _ => false,
+    })
+}
+
+/// The window to select when moving the cursor down or up from row `from`;
+/// landing on a collapsed group selects its first session.
+pub fn step(rows: &[Row], from: usize, down: bool) -> Option<usize> {
+    if down {
+        rows.iter().skip(from + 1).find_map(row_window)
+    } else {
+        rows[..from.min(rows.len())]
+            .iter()
+            .rev()
+            .find_map(row_window)
+    }
+}
+
+/// The window on the first row that can be selected.
+pub fn first(rows: &[Row]) -> Option<usize> {
+    rows.iter().find_map(row_window)
+}
+
+/// The group window `selected` belongs to, and whether it's collapsed.
+pub fn group_of(rows: &[Row], selected: usize) -> Option<(&str, bool)> {
+    rows.iter().find_map(|row| match row {
+        Row::Group {
+            repo,
+            collapsed,
+            windows,
+        } if windows.contains(&selected) => Some((repo.as_str(), *collapsed)),
+        _ => None,
+    })
+}
+
+/// Repositories collapsed in any sidebar.
+pub fn collapsed() -> HashSet<String> {
+    paths::cove_path(COLLAPSED_FILE)
+        .and_then(|p| fs::read_to_string(p).ok())
+        .map(|s| s.lines().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Save the collapsed repositories for all sidebars.
+pub fn set_collapsed(repos: &HashSet<String>) {
+    if let Ok(dir) = paths::ensure_cove_dir("") {
+        let mut names: Vec<&str> = repos.iter().map(String::as_str).collect();
+        names.sort_unstable();
+        let _ = fs::write(dir.join(COLLAPSED_FILE), names.join("\n"));
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(index: u32, name: &str, managed: bool) -> WindowInfo {
+        WindowInfo {
+            id: format!("@{index}"),
+            index,
+            name: name.to_string(),
+            is_active: false,
+            pane_path: format!("/code/{name}"),
+            managed,
+        }
+    }
+
+    #[test]
+    fn test_layout_groups_by_repo() {
+        let windows = [
+            window(1, "api", true),
+            window(2, "web", true),
+            window(3, "api-fix", true),
+            window(4, "htop", false),
+        ];
+        let repos = HashMap::from([
+            (1, "backend".to_string()),
+            (2, "frontend".to_string()),
+            (3, "backend".to_string()),
+        ]);
+        let group = |repo: &str, collapsed, windows: &[usize]| Row::Group {
+            repo: repo.to_string(),
+            collapsed,
+            windows: windows.to_vec(),
+        };
+
+        let open = layout(&windows, Some(&repos), &HashSet::new());
+        assert_eq!(
+            open,
+            [
+                group("backend", false, &[0, 2]),
+                Row::Window(0),
+                Row::Window(2),
+                group("frontend", false, &[1]),
+                Row::Window(1),
+                Row::Other,
+                Row::Window(3),
+            ]
+        );
+
+        let folded = layout(
+            &windows,
+            Some(&repos),
+            &HashSet::from(["backend".to_string()]),
+        );
+        assert_eq!(folded[0], group("backend", true, &[0, 2]));
+        assert_eq!(folded[1], group("frontend", false, &[1]));
+        assert_eq!(group_of(&folded, 2), Some(("backend", true)));
+
+        let flat = layout(&windows, None, &HashSet::new());
+        assert_eq!(flat[3], Row::Other);
+        assert_eq!(flat.len(), 5);
+    }
+
+    #[test]
+    fn test_step_skips_open_headings() {
+        let windows = [
+            window(1, "api", true),
+            window(2, "web", true),
+            window(3, "cli", true),
+        ];
+        let repos = HashMap::from([
+            (1, "backend".to_string()),
+            (2, "frontend".to_string()),
+            (3, "tools".to_string()),
+        ]);
+        let collapsed = HashSet::from(["frontend".to_string()]);
+        let rows = layout(&windows, Some(&repos), &collapsed);
+
+        // backend, api, frontend (collapsed), tools, cli
+        assert_eq!(step(&rows, 1, true), Some(1));
+        assert_eq!(cursor_row(&rows, 1), Some(2));
+        assert_eq!(step(&rows, 2, true), Some(2));
+        assert_eq!(step(&rows, 4, false), Some(1));
+        assert_eq!(step(&rows, 1, false), None);
+    }
+
+    #[test]
+    fn test_filter_drops_emptied_headings() {
+        let windows = [
+            window(1, "work/api", true),
+            window(2, "oss/web", true),
+            window(3, "work/cli", true),
+            window(4, "htop", false),
+        ];
+        let repos = HashMap::from([
+            (1, "backend".to_string()),
+            (2, "frontend".to_string()),
+            (3, "backend".to_string()),
+        ]);
+        let rows = layout(&windows, Some(&repos), &HashSet::new());
+        let work = filter(rows, |i| windows[i].name.starts_with("work/"));
+        assert_eq!(
+            work,
+            [
+                Row::Group {
+                    repo: "backend".to_string(),
+                    collapsed: false,
+                    windows: vec![0, 2],
+                },
+                Row::Window(0),
+                Row::Window(2),
+            ]
+        );
+    }
+}
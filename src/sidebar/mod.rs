@@ -1,4 +1,14 @@
 pub mod app;
 pub mod event;
+pub mod format;
+#[cfg(test)]
+pub mod golden;
+pub mod groups;
+pub mod panes;
+pub mod plain;
+pub mod screen;
+pub mod sections;
 pub mod state;
+pub mod tips;
 pub mod ui;
+pub mod workers;
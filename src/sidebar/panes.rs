@@ -0,0 +1,150 @@
+// ── Helper pane activity ──
+//
+// Claude's pane isn't the only thing working in a window: the terminal pane
+// (and any pane the user split off) often runs the tests or a build. tmux
+// only reports each pane's foreground command, so the events worker notes
+// when a helper pane first showed a command other than its shell and, once
+// that has run for `LONG_SECS`, the row gets a dim "⚙ cargo 2m" badge.
+// Interactive programs (editors, pagers, monitors) sit in the foreground for
+// hours and are left out.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::config;
+use crate::sidebar::format::format_age;
+use crate::tmux::PaneInfo;
+
+// ── Types ──
+
+/// When each helper pane's current command was first seen, by pane ID.
+#[derive(Default)]
+pub struct HelperTracker {
+    seen: HashMap<String, (String, u64)>,
+}
+
+// ── Constants ──
+
+/// Seconds a command runs before it counts as long.
+const LONG_SECS: u64 = 30;
+
+const SHELLS: &[&str] = &["bash", "zsh", "fish", "sh", "dash", "ksh", "tcsh", "nu"];
+
+const INTERACTIVE: &[&str] = &[
+    "vim", "nvim", "vi", "nano", "emacs", "hx", "less", "more", "man", "htop", "top", "btop",
+    "tig", "lazygit", "ssh", "tmux",
+];
+
+// ── Helpers ──
+
+/// A command worth reporting: not a shell waiting for input, nor a program
+/// the user is sitting in.
+fn is_job(command: &str) -> bool {
+    let name = command.trim_start_matches('-');
+    !name.is_empty() && !is_shell(name) && !INTERACTIVE.contains(&name)
+}
+
+// ── Public API ──
+
+/// Shells named in `sidebar.shells`, read once.
+fn extra_shells() -> &'static [String] {
+    static SHELLS: OnceLock<Vec<String>> = OnceLock::new();
+    SHELLS.get_or_init(|| config::load().unwrap_or_default().sidebar.shells)
+}
+
+/// Whether a pane's foreground command is a shell.
+pub fn is_shell(command: &str) -> bool {
+    // Login shells show up as "-zsh"
+    let name = command.trim_start_matches('-');
+    SHELLS.contains(&name) || extra_shells().iter().any(|s| s == name)
+}
+
+impl HelperTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note what `panes` are running at `now` (Unix seconds). Returns, per
+    /// window index, the longest-running helper command that ran at least
+    /// `LONG_SECS`, as "command age".
+    pub fn update(&mut self, panes: &[PaneInfo], now: u64) -> HashMap<u32, String> {
+        let mut jobs: HashMap<String, (String, u64)> = HashMap::new();
+        let mut longest: HashMap<u32, (&str, u64)> = HashMap::new();
+        for pane in panes.iter().filter(|p| p.is_helper() && is_job(&p.command)) {
+            let since = match self.seen.get(&pane.pane_id) {
+                Some((command, since)) if *command == pane.command => *since,
+                _ => now,
+            };
+            jobs.insert(pane.pane_id.clone(), (pane.command.clone(), since));
+            let secs = now.saturating_sub(since);
+            let best = longest
+                .entry(pane.window_index)
+                .or_insert((&pane.command, secs));
+            if secs > best.1 {
+                *best = (&pane.command, secs);
+            }
+        }
+        let out = longest
+            .into_iter()
+            .filter(|(_, (_, secs))| *secs >= LONG_SECS)
+            .map(|(index, (command, secs))| (index, format!("{command} {}", format_age(secs))))
+            .collect();
+        // Panes back at their shell (or closed) start over next time
+        self.seen = jobs;
+        out
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pane(window_index: u32, pane_index: u32, command: &str, role: &str) -> PaneInfo {
+        PaneInfo {
+            window_index,
+            pane_index,
+            command: command.to_string(),
+            pane_id: format!("%t{window_index}{pane_index}"),
+            role: role.to_string(),
+            window_id: format!("@{window_index}"),
+            dead: false,
+            failed: false,
+            parked: false,
+            paired: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_long_helper_commands_are_reported() {
+        let mut tracker = HelperTracker::new();
+        let panes = [
+            pane(1, 1, "claude", "claude"),
+            pane(1, 2, "cove", "sidebar"),
+            pane(1, 3, "cargo", "terminal"),
+            pane(2, 3, "zsh", "terminal"),
+            pane(2, 4, "nvim", ""),
+        ];
+        assert!(tracker.update(&panes, 1_000).is_empty());
+        assert!(tracker.update(&panes, 1_020).is_empty());
+        assert_eq!(
+            tracker.update(&panes, 1_090),
+            HashMap::from([(1, "cargo 1m".to_string())])
+        );
+
+        // A new command in the same pane starts its own clock
+        let rerun = [pane(1, 3, "make", "terminal")];
+        assert!(tracker.update(&rerun, 1_100).is_empty());
+    }
+
+    #[test]
+    fn test_is_job() {
+        assert!(is_job("cargo"));
+        assert!(is_job("node"));
+        assert!(!is_job("-zsh"));
+        assert!(!is_job("fish"));
+        assert!(!is_job("less"));
+        assert!(!is_job(""));
+    }
+}
@@ -0,0 +1,171 @@
+// ── Plain-text sidebar ──
+//
+// `cove sidebar --plain` (or `sidebar.plain: true`) replaces the full-screen
+// TUI with linear, unstyled output for terminal screen readers. It prints the
+// session list once, then one line per change, rendered from
+// `sidebar.announce` ("{name}: {state}" by default; also `{index}`). State
+// words are the explicit labels ("working", "waiting", "your turn", …), plus
+// "opened" and "closed" as sessions come and go.
+//
+// Input is line-based: a window number and Enter switches to it, Enter alone
+// reads the list again, `q` quits.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::config;
+use crate::sidebar::state::{StateDetector, WindowState};
+use crate::tmux::{self, Tmux};
+
+// ── Constants ──
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// ── Helpers ──
+
+/// Session name and state word per window index.
+type Snapshot = BTreeMap<u32, (String, String)>;
+
+fn state_word(state: WindowState, asks: Option<u32>) -> String {
+    match asks {
+        Some(n) if n > 1 => format!("{} ({n} questions)", state.label()),
+        _ => state.label().to_string(),
+    }
+}
+
+fn render(template: &str, index: u32, name: &str, state: &str) -> String {
+    template
+        .replace("{index}", &index.to_string())
+        .replace("{name}", name)
+        .replace("{state}", state)
+}
+
+/// Announcements for everything that changed between two snapshots.
+fn changes(template: &str, before: &Snapshot, after: &Snapshot) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (index, (name, state)) in after {
+        match before.get(index) {
+            Some(prev) if prev.0 == *name && prev.1 == *state => {}
+            Some(prev) if prev.0 == *name => lines.push(render(template, *index, name, state)),
+            _ => lines.push(render(template, *index, name, "opened")),
+        }
+    }
+    for (index, (name, _)) in before {
+        if after.get(index).is_none_or(|(n, _)| n != name) {
+            lines.push(render(template, *index, name, "closed"));
+        }
+    }
+    lines
+}
+
+fn snapshot(detector: &mut StateDetector) -> Snapshot {
+    let windows = tmux::list_windows().unwrap_or_default();
+    let states = detector.detect(&Tmux, &windows);
+    windows
+        .iter()
+        .map(|w| {
+            let state = states.get(&w.index).copied().unwrap_or(WindowState::Fresh);
+            let word = if w.managed {
+                state_word(state, detector.pending_asks(w.index))
+            } else {
+                "not a cove session".to_string()
+            };
+            (w.index, (w.name.clone(), word))
+        })
+        .collect()
+}
+
+fn print_list(template: &str, current: &Snapshot) {
+    let plural = if current.len() == 1 { "" } else { "s" };
+    println!("{} session{plural}", current.len());
+    for (index, (name, state)) in current {
+        println!("{}", render(template, *index, name, state));
+    }
+}
+
+/// Forward stdin lines to the main loop, so polling never blocks on input.
+fn spawn_reader() -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+// ── Public API ──
+
+pub fn run() -> Result<(), String> {
+    let config = config::load().unwrap_or_default();
+    let template = config.sidebar.announce;
+    let mut detector = StateDetector::new();
+    let input = spawn_reader();
+
+    let mut current = snapshot(&mut detector);
+    print_list(&template, &current);
+    println!("Type a window number and Enter to switch, Enter to repeat, q to quit.");
+
+    loop {
+        match input.recv_timeout(POLL_INTERVAL) {
+            Ok(line) => match line.trim() {
+                "q" => return Ok(()),
+                "" => print_list(&template, &current),
+                answer => match answer.parse::<u32>() {
+                    Ok(index) if current.contains_key(&index) => {
+                        let _ = tmux::select_window(index);
+                    }
+                    _ => println!("No window {answer}"),
+                },
+            },
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            // stdin closed: keep announcing
+            Err(mpsc::RecvTimeoutError::Disconnected) => thread::sleep(POLL_INTERVAL),
+        }
+
+        let next = snapshot(&mut detector);
+        for line in changes(&template, &current, &next) {
+            println!("{line}");
+        }
+        current = next;
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(rows: &[(u32, &str, &str)]) -> Snapshot {
+        rows.iter()
+            .map(|(i, n, s)| (*i, (n.to_string(), s.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn test_changes_announces_state_open_and_close() {
+        let before = snap(&[(1, "api", "working"), (2, "web", "your turn")]);
+        let after = snap(&[(1, "api", "waiting"), (3, "docs", "fresh")]);
+        assert_eq!(
+            changes("{index} {name}: {state}", &before, &after),
+            vec!["1 api: waiting", "3 docs: opened", "2 web: closed"]
+        );
+        assert!(changes("{name}", &after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_state_word_counts_questions() {
+        assert_eq!(
+            state_word(WindowState::Asking, Some(3)),
+            "waiting (3 questions)"
+        );
+        assert_eq!(state_word(WindowState::Asking, Some(1)), "waiting");
+        assert_eq!(state_word(WindowState::Idle, None), "your turn");
+    }
+}
@@ -0,0 +1,196 @@
+// ── Flicker-free frame output ──
+//
+// ratatui already keeps the previous frame and hands the backend only the
+// cells that changed. What still flickers is how those cells reach the
+// terminal: stdout is line-buffered, so a big redraw (a resize, a view
+// switch) goes out in pieces the terminal paints one at a time, and every
+// frame re-sends "hide cursor" even when nothing moved. Over SSH that is a
+// steady trickle of bytes ten times a second.
+//
+// `FrameBackend` collects a whole frame in memory, drops cursor commands that
+// don't change anything, and writes the frame in one go between synchronized
+// update markers (terminals without support ignore them). A frame with no
+// changed cells writes nothing at all.
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use crossterm::QueueableCommand;
+use crossterm::terminal::{BeginSynchronizedUpdate, EndSynchronizedUpdate};
+use ratatui::backend::{Backend, ClearType, CrosstermBackend, WindowSize};
+use ratatui::buffer::Cell;
+use ratatui::layout::{Position, Size};
+
+// ── Types ──
+
+/// A ratatui backend that buffers each frame and writes it to `out` at once.
+pub struct FrameBackend<W: Write> {
+    /// Renders crossterm commands into `pending`.
+    frame: CrosstermBackend<Pending>,
+    pending: Rc<RefCell<Vec<u8>>>,
+    out: W,
+    /// Whether the cursor is hidden on screen, once known.
+    cursor_hidden: Option<bool>,
+}
+
+/// The frame being built, shared with the inner backend's writer.
+struct Pending(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Pending {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// ── Public API ──
+
+impl<W: Write> FrameBackend<W> {
+    pub fn new(out: W) -> Self {
+        let pending = Rc::new(RefCell::new(Vec::new()));
+        Self {
+            frame: CrosstermBackend::new(Pending(Rc::clone(&pending))),
+            pending,
+            out,
+            cursor_hidden: None,
+        }
+    }
+
+    #[cfg(test)]
+    fn out(&self) -> &W {
+        &self.out
+    }
+}
+
+impl<W: Write> Backend for FrameBackend<W> {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        // crossterm's backend resets colors after every draw, even an empty one
+        let mut content = content.peekable();
+        if content.peek().is_none() {
+            return Ok(());
+        }
+        self.frame.draw(content)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        if self.cursor_hidden == Some(true) {
+            return Ok(());
+        }
+        self.cursor_hidden = Some(true);
+        self.frame.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        if self.cursor_hidden == Some(false) {
+            return Ok(());
+        }
+        self.cursor_hidden = Some(false);
+        self.frame.show_cursor()
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        self.flush()?;
+        self.frame.get_cursor_position()
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.frame.set_cursor_position(position)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.frame.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        self.frame.clear_region(clear_type)
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        self.frame.size()
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        self.frame.window_size()
+    }
+
+    /// Write the pending frame, if it has anything in it.
+    fn flush(&mut self) -> io::Result<()> {
+        // The inner backend's flush only moves crossterm's queue into `pending`
+        Backend::flush(&mut self.frame)?;
+        let mut pending = self.pending.borrow_mut();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let mut bytes = Vec::with_capacity(pending.len() + 16);
+        bytes.queue(BeginSynchronizedUpdate)?;
+        bytes.append(&mut pending);
+        bytes.queue(EndSynchronizedUpdate)?;
+        self.out.write_all(&bytes)?;
+        self.out.flush()
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ratatui::layout::Rect;
+    use ratatui::widgets::Paragraph;
+    use ratatui::{Terminal, TerminalOptions, Viewport};
+
+    const BEGIN: &str = "\x1b[?2026h";
+    const END: &str = "\x1b[?2026l";
+
+    fn terminal() -> Terminal<FrameBackend<Vec<u8>>> {
+        let options = TerminalOptions {
+            viewport: Viewport::Fixed(Rect::new(0, 0, 10, 2)),
+        };
+        Terminal::with_options(FrameBackend::new(Vec::new()), options).unwrap()
+    }
+
+    fn draw(terminal: &mut Terminal<FrameBackend<Vec<u8>>>, text: &str) -> String {
+        let before = terminal.backend().out().len();
+        terminal
+            .draw(|f| f.render_widget(Paragraph::new(text), f.area()))
+            .unwrap();
+        String::from_utf8_lossy(&terminal.backend().out()[before..]).into_owned()
+    }
+
+    #[test]
+    fn test_frame_is_wrapped_in_one_update() {
+        let mut terminal = terminal();
+        let out = draw(&mut terminal, "cove");
+        assert!(out.starts_with(BEGIN), "{out:?}");
+        assert!(out.ends_with(END), "{out:?}");
+        assert!(out.contains("cove"));
+        assert_eq!(out.matches(BEGIN).count(), 1);
+    }
+
+    #[test]
+    fn test_unchanged_frame_writes_nothing() {
+        let mut terminal = terminal();
+        draw(&mut terminal, "cove");
+        assert_eq!(draw(&mut terminal, "cove"), "");
+    }
+
+    #[test]
+    fn test_only_changed_cells_are_sent() {
+        let mut terminal = terminal();
+        draw(&mut terminal, "cove-a");
+        let out = draw(&mut terminal, "cove-b");
+        assert!(out.contains('b'), "{out:?}");
+        assert!(!out.contains("cove"), "{out:?}");
+        // The cursor was already hidden by the first frame
+        assert!(!out.contains("\x1b[?25l"), "{out:?}");
+    }
+}
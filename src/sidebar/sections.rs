@@ -0,0 +1,344 @@
+// ── Custom sidebar sections ──
+//
+// `sidebar.sections` adds blocks under the session list filled by a shell
+// command of the user's: a summary of `kubectl get pods`, the CI queue,
+// anything that prints a few lines. Each command runs every `interval_secs`
+// (via `sh -c`, from the home directory) and the last `max_lines` lines of
+// its output are shown under the section's title. A command that fails or
+// runs longer than `TIMEOUT` keeps its previous output, marked stale, so a
+// flaky cluster doesn't blank the section.
+//
+// Every window has a sidebar, and they all show the same sections, so the
+// results are shared through ~/.cove/sections.json, keyed by command: the
+// first sidebar to find a command due claims it (under the file's lock) and
+// runs it, and the others show what it wrote until it's due again.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SectionConfig;
+use crate::{lockfile, paths};
+
+// ── Types ──
+
+/// One section as displayed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub title: String,
+    pub lines: Vec<String>,
+    /// The last run failed; `lines` are from an earlier one.
+    pub stale: bool,
+}
+
+/// The configured sections with their latest output and when each ran
+/// (unix time).
+pub struct SectionRunner {
+    configs: Vec<SectionConfig>,
+    sections: Vec<Section>,
+    ran_at: Vec<Option<u64>>,
+}
+
+/// A command's latest run as every sidebar sees it in `SHARED_FILE`. `ran`
+/// is set when a sidebar claims the run, so the others wait for it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Shared {
+    ran: u64,
+    lines: Vec<String>,
+    stale: bool,
+}
+
+// ── Constants ──
+
+/// Longest a section command may run before it's killed.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Results of every section command, shared by the sidebars.
+const SHARED_FILE: &str = "sections.json";
+
+// ── Helpers ──
+
+/// Stdout of `sh -c command`, or None if it failed or ran past `timeout`.
+fn run_command(command: &str, timeout: Duration) -> Option<String> {
+    let mut sh = Command::new("sh");
+    sh.args(["-c", command]);
+    if let Some(home) = paths::home() {
+        sh.current_dir(home);
+    }
+    let mut child = sh
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    // Read on another thread so a chatty command can't fill the pipe and hang
+    let mut stdout = child.stdout.take()?;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut out = String::new();
+        let _ = stdout.read_to_string(&mut out);
+        let _ = tx.send(out);
+    });
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().ok()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    // Something it started in the background can hold the pipe open
+    // past its exit, so the output gets only what's left of the timeout
+    let out = rx
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .ok()?;
+    status.success().then_some(out)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn load_shared(path: &Path) -> HashMap<String, Shared> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Write through a temp file and a rename, as sessions.rs does.
+fn save_shared(path: &Path, shared: &HashMap<String, Shared>) -> Result<(), String> {
+    let content = serde_json::to_string(shared).map_err(|e| format!("serialize: {e}"))?;
+    let tmp = path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&tmp, content).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("replace {}: {e}", path.display())
+    })
+}
+
+/// `command`'s shared result if another sidebar ran (or is running) it
+/// within `interval` of `now`; otherwise claim the run, returning None.
+fn claim(path: &Path, command: &str, interval: u64, now: u64) -> Result<Option<Shared>, String> {
+    lockfile::with_lock(path, || {
+        let mut shared = load_shared(path);
+        let entry = shared.entry(command.to_string()).or_default();
+        if now.saturating_sub(entry.ran) < interval {
+            return Ok(Some(entry.clone()));
+        }
+        entry.ran = now;
+        save_shared(path, &shared).map(|()| None)
+    })?
+}
+
+/// Record the result of a run of `command` this sidebar claimed.
+fn publish(path: &Path, command: &str, result: Shared) -> Result<(), String> {
+    lockfile::with_lock(path, || {
+        let mut shared = load_shared(path);
+        shared.insert(command.to_string(), result);
+        save_shared(path, &shared)
+    })?
+}
+
+/// The last `max` non-blank lines of `output`, trailing space trimmed.
+fn last_lines(output: &str, max: usize) -> Vec<String> {
+    let lines: Vec<String> = output
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| !l.trim().is_empty())
+        .map(String::from)
+        .collect();
+    lines[lines.len().saturating_sub(max)..].to_vec()
+}
+
+// ── Public API ──
+
+impl SectionRunner {
+    pub fn new(configs: Vec<SectionConfig>) -> Self {
+        let sections = configs
+            .iter()
+            .map(|c| Section {
+                title: c.title.clone(),
+                lines: Vec::new(),
+                stale: false,
+            })
+            .collect();
+        let ran_at = vec![None; configs.len()];
+        Self {
+            configs,
+            sections,
+            ran_at,
+        }
+    }
+
+    /// Start over with new configs, keeping what sections that didn't change
+    /// last showed.
+    pub fn configure(&mut self, configs: Vec<SectionConfig>) {
+        let mut next = Self::new(configs);
+        for (i, config) in next.configs.iter().enumerate() {
+            if let Some(old) = self.configs.iter().position(|c| c == config) {
+                next.sections[i] = self.sections[old].clone();
+                next.ran_at[i] = self.ran_at[old];
+            }
+        }
+        *self = next;
+    }
+
+    /// Bring the sections that are due up to date at `now`: from `shared`
+    /// when another sidebar ran the command lately, else by running it with
+    /// `run` standing in for the shell. Returns whether any section changed.
+    fn run_due_with(
+        &mut self,
+        shared: Option<&Path>,
+        now: u64,
+        run: impl Fn(&str) -> Option<String>,
+    ) -> bool {
+        let mut changed = false;
+        for (i, config) in self.configs.iter().enumerate() {
+            let interval = config.interval_secs.max(1);
+            if config.command.is_empty()
+                || self.ran_at[i].is_some_and(|t| now.saturating_sub(t) < interval)
+            {
+                continue;
+            }
+            let section = &mut self.sections[i];
+            let before = section.clone();
+            let claimed = match shared.map(|path| claim(path, &config.command, interval, now)) {
+                Some(Ok(claimed)) => claimed,
+                Some(Err(e)) => {
+                    log::warn!("section {}: {e}", config.title);
+                    continue;
+                }
+                None => None,
+            };
+            if let Some(result) = claimed {
+                self.ran_at[i] = Some(result.ran);
+                section.lines = last_lines(&result.lines.join("\n"), config.max_lines);
+                section.stale = result.stale;
+            } else {
+                self.ran_at[i] = Some(now);
+                match run(&config.command) {
+                    Some(out) => {
+                        section.lines = last_lines(&out, config.max_lines);
+                        section.stale = false;
+                    }
+                    None => section.stale = true,
+                }
+                let result = Shared {
+                    ran: now,
+                    lines: section.lines.clone(),
+                    stale: section.stale,
+                };
+                if let Some(Err(e)) = shared.map(|path| publish(path, &config.command, result)) {
+                    log::warn!("section {}: {e}", config.title);
+                }
+            }
+            changed |= *section != before;
+        }
+        changed
+    }
+
+    /// Run the commands that are due, or take another sidebar's results.
+    /// Returns whether any section changed.
+    pub fn run_due(&mut self) -> bool {
+        let shared: Option<PathBuf> = paths::ensure_cove_dir("")
+            .ok()
+            .map(|dir| dir.join(SHARED_FILE));
+        self.run_due_with(shared.as_deref(), now(), |command| {
+            run_command(command, TIMEOUT)
+        })
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(title: &str, command: &str, max_lines: usize) -> SectionConfig {
+        SectionConfig {
+            title: title.to_string(),
+            command: command.to_string(),
+            interval_secs: 60,
+            max_lines,
+        }
+    }
+
+    #[test]
+    fn test_sections_keep_last_lines_and_go_stale() {
+        let mut runner = SectionRunner::new(vec![
+            section("pods", "kubectl get pods", 2),
+            section("queue", "broken", 5),
+        ]);
+        let changed = runner.run_due_with(None, 1_000, |command| match command {
+            "kubectl get pods" => Some("NAME  STATUS\napi   Running\n\nweb   Pending  \n".into()),
+            _ => None,
+        });
+        assert!(changed);
+        let sections = runner.sections();
+        assert_eq!(sections[0].lines, ["api   Running", "web   Pending"]);
+        assert!(!sections[0].stale);
+        assert!(sections[1].lines.is_empty() && sections[1].stale);
+
+        // Nothing is due again within the interval
+        assert!(!runner.run_due_with(None, 1_059, |_| panic!("ran too early")));
+
+        // An unchanged section keeps its output across a reload
+        runner.configure(vec![section("pods", "kubectl get pods", 2)]);
+        assert_eq!(runner.sections()[0].lines.len(), 2);
+    }
+
+    #[test]
+    fn test_sidebars_share_one_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SHARED_FILE);
+        let configs = vec![section("pods", "kubectl get pods", 5)];
+        let mut first = SectionRunner::new(configs.clone());
+        let mut second = SectionRunner::new(configs);
+
+        assert!(first.run_due_with(Some(&path), 1_000, |_| Some("api Running\n".into())));
+        // Another sidebar shows the same output without running the command
+        assert!(second.run_due_with(Some(&path), 1_010, |_| panic!("ran it twice")));
+        assert_eq!(second.sections()[0].lines, ["api Running"]);
+        // ...until it's due again
+        assert!(second.run_due_with(Some(&path), 1_060, |_| Some("api Pending\n".into())));
+        assert!(first.run_due_with(Some(&path), 1_061, |_| panic!("ran it twice")));
+        assert_eq!(first.sections()[0].lines, ["api Pending"]);
+    }
+
+    #[test]
+    fn test_run_command() {
+        assert_eq!(run_command("echo hi", TIMEOUT).as_deref(), Some("hi\n"));
+        assert_eq!(run_command("exit 1", TIMEOUT), None);
+        assert_eq!(run_command("sleep 5", Duration::from_millis(100)), None);
+        // A background job holding the pipe doesn't hold up the read forever
+        let started = Instant::now();
+        assert_eq!(
+            run_command("sleep 5 & echo hi", Duration::from_millis(300)),
+            None
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
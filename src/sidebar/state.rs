@@ -6,14 +6,17 @@
 // (from $TMUX_PANE) to each window's tmux pane ID. This correctly handles
 // multiple sessions in the same working directory.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{BufRead, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 use serde::Deserialize;
 
-use crate::tmux;
+use crate::sidebar::panes;
+use crate::tmux::{self, TmuxClient};
+use crate::{names, paths};
 
 // ── Types ──
 
@@ -29,39 +32,109 @@ pub enum WindowState {
     Idle,
     /// Claude process exited — shell prompt visible.
     Done,
+    /// The prompt is held back by `quota.max_working` until a slot frees up.
+    Queued,
 }
 
-#[derive(Deserialize)]
-struct EventEntry {
-    state: String,
-    #[allow(dead_code)]
-    cwd: String,
+impl WindowState {
+    /// Lowercase name, as shown by `cove list` and accepted by `--state`.
+    pub fn label(self) -> &'static str {
+        match self {
+            WindowState::Fresh => "fresh",
+            WindowState::Working => "working",
+            WindowState::Asking => "waiting",
+            WindowState::Idle => "your turn",
+            WindowState::Done => "done",
+            WindowState::Queued => "queued",
+        }
+    }
+}
+
+/// One line of a hook event file.
+#[derive(Clone, Deserialize)]
+pub struct EventEntry {
+    pub state: String,
+    pub cwd: String,
     /// Tmux pane ID (e.g. "%0") — used to match events to windows.
     #[serde(default)]
-    pane_id: String,
-    ts: u64,
+    pub pane_id: String,
+    pub ts: u64,
+    /// Hook that wrote the line ("ask", "ask-done", …); absent in old files.
+    #[serde(default)]
+    pub hook: String,
+    /// What a `cove annotate` milestone marks.
+    #[serde(default)]
+    pub note: String,
 }
 
-// ── Helpers ──
+impl EventEntry {
+    /// A milestone from `cove annotate`; it repeats the session's latest state,
+    /// and `read_last_event` passes over it.
+    pub fn is_milestone(&self) -> bool {
+        self.hook == MILESTONE_HOOK
+    }
+}
 
-fn events_dir() -> PathBuf {
-    let home = std::env::var("HOME").unwrap_or_default();
-    PathBuf::from(home).join(".cove").join("events")
+/// Parsed tail of every event file, so each sidebar tick costs a `stat` per
+/// file instead of a read. There is one cache per sidebar process.
+#[derive(Default)]
+struct EventCache {
+    files: HashMap<PathBuf, CachedFile>,
 }
 
-/// Read the last line of a file efficiently.
-/// Returns None if the file is empty or unreadable.
-fn read_last_line(path: &Path) -> Option<String> {
-    let file = fs::File::open(path).ok()?;
-    let len = file.metadata().ok()?.len();
-    if len == 0 {
-        return None;
-    }
+struct CachedFile {
+    /// (mtime, size) when the file was last read.
+    stamp: (Option<SystemTime>, u64),
+    last: Option<EventEntry>,
+    /// Computed on first use for Asking/Working windows.
+    pending_asks: Option<u32>,
+}
+
+// ── Constants ──
+
+/// `hook` of events written by `cove annotate`.
+pub const MILESTONE_HOOK: &str = "milestone";
+
+/// How much of an event file to scan when pairing ask/ask-done events.
+const ASK_SCAN_BYTES: u64 = 8 * 1024;
+
+const HISTORY_DIR: &str = "history";
+
+/// Days an archived event file is kept after its last event.
+const HISTORY_MAX_DAYS: u64 = 90;
+
+/// State `cove restart` records while it swaps Claude out.
+pub const RESTARTING: &str = "restarting";
+
+/// Start of the event file name used for a pane whose Claude session isn't
+/// known (see `placeholder_session`).
+const PLACEHOLDER_PREFIX: &str = "pane-";
+
+/// State `cove kill --keep-pane` records when it stops Claude; shown as
+/// fresh, with a parked badge.
+pub const PARKED: &str = "parked";
+
+/// How long after a restart a shell in pane .1 is taken for the restart
+/// still starting up rather than Claude having exited.
+const RESTART_GRACE_SECS: u64 = 10;
+
+// ── Helpers ──
 
-    // Read last 1KB — event lines are ~80 bytes, so this is more than enough
-    let tail_start = len.saturating_sub(1024);
+fn events_dir() -> Option<PathBuf> {
+    paths::events_dir()
+}
+
+/// Read the complete, non-empty lines in the last `bytes` of a file.
+fn read_tail_lines(path: &Path, bytes: u64) -> Vec<String> {
+    let Ok(file) = fs::File::open(path) else {
+        return Vec::new();
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let tail_start = len.saturating_sub(bytes);
     let mut reader = std::io::BufReader::new(file);
-    reader.seek(SeekFrom::Start(tail_start)).ok()?;
+    if reader.seek(SeekFrom::Start(tail_start)).is_err() {
+        return Vec::new();
+    }
 
     // If we seeked mid-line, skip the partial first line
     if tail_start > 0 {
@@ -69,7 +142,7 @@ fn read_last_line(path: &Path) -> Option<String> {
         let _ = reader.read_line(&mut discard);
     }
 
-    let mut last = None;
+    let mut lines = Vec::new();
     let mut line = String::new();
     loop {
         line.clear();
@@ -78,14 +151,50 @@ fn read_last_line(path: &Path) -> Option<String> {
             Ok(_) => {
                 let trimmed = line.trim();
                 if !trimmed.is_empty() {
-                    last = Some(trimmed.to_string());
+                    lines.push(trimmed.to_string());
                 }
             }
             Err(_) => break,
         }
     }
+    lines
+}
+
+/// Read the last line of a file efficiently.
+/// Returns None if the file is empty or unreadable.
+pub fn read_last_line(path: &Path) -> Option<String> {
+    // Read last 1KB — event lines are ~100 bytes, so this is more than enough
+    read_tail_lines(path, 1024).pop()
+}
 
-    last
+/// The latest event in an event file, passing over `cove annotate`
+/// milestones: they repeat the state, but their timestamp would read as new
+/// activity and reset the session's age and the stuck check.
+pub fn read_last_event(path: &Path) -> Option<EventEntry> {
+    read_tail_lines(path, ASK_SCAN_BYTES)
+        .iter()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<EventEntry>(line).ok())
+        .find(|event| !event.is_milestone())
+}
+
+/// Questions Claude has asked in the current turn that haven't been answered:
+/// ask events minus ask-done events since the last prompt or stop.
+fn pending_asks(path: &Path) -> u32 {
+    let (mut asked, mut answered) = (0u32, 0u32);
+    for line in read_tail_lines(path, ASK_SCAN_BYTES).iter().rev() {
+        let Ok(event) = serde_json::from_str::<EventEntry>(line) else {
+            continue;
+        };
+        match event.hook.as_str() {
+            MILESTONE_HOOK => {}
+            "ask" => asked += 1,
+            "ask-done" => answered += 1,
+            // A turn boundary, or an old-format line that can't be paired
+            _ => break,
+        }
+    }
+    asked.saturating_sub(answered)
 }
 
 /// Load the latest event from each event file in the events directory.
@@ -93,115 +202,380 @@ fn read_last_line(path: &Path) -> Option<String> {
 /// per pane_id. This deduplicates across multiple files that share a recycled
 /// pane ID, ensuring the current session's events always win.
 fn load_latest_events(dir: &Path) -> HashMap<String, String> {
-    let entries = match fs::read_dir(dir) {
-        Ok(e) => e,
-        Err(_) => return HashMap::new(),
-    };
+    load_latest_events_with_ts(dir)
+        .into_iter()
+        .map(|(k, (state, _, _))| (k, state))
+        .collect()
+}
 
-    // Track (state, timestamp) per pane_id — keep highest timestamp
-    let mut best: HashMap<String, (String, u64)> = HashMap::new();
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
-            continue;
+/// Like `load_latest_events`, keeping each event's timestamp and file.
+fn load_latest_events_with_ts(dir: &Path) -> HashMap<String, (String, u64, PathBuf)> {
+    EventCache::default().latest(dir)
+}
+
+impl EventCache {
+    /// Latest event per pane_id across the files in `dir`, re-reading only
+    /// files whose size or mtime changed since the previous call.
+    fn latest(&mut self, dir: &Path) -> HashMap<String, (String, u64, PathBuf)> {
+        let entries = match fs::read_dir(dir) {
+            Ok(e) => e,
+            Err(_) => {
+                self.files.clear();
+                return HashMap::new();
+            }
+        };
+
+        let mut seen = HashSet::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            let stamp = (meta.modified().ok(), meta.len());
+            seen.insert(path.clone());
+
+            let fresh = self.files.get(&path).is_some_and(|f| f.stamp == stamp);
+            if !fresh {
+                let last = read_last_event(&path);
+                self.files.insert(
+                    path,
+                    CachedFile {
+                        stamp,
+                        last,
+                        pending_asks: None,
+                    },
+                );
+            }
         }
-        if let Some(line) = read_last_line(&path) {
-            if let Ok(event) = serde_json::from_str::<EventEntry>(&line) {
-                if !event.pane_id.is_empty() {
-                    let replace = best
-                        .get(&event.pane_id)
-                        .is_none_or(|(_, prev_ts)| event.ts > *prev_ts);
-                    if replace {
-                        best.insert(event.pane_id, (event.state, event.ts));
-                    }
-                }
+        // Files purged on window create/kill drop out here
+        self.files.retain(|path, _| seen.contains(path));
+
+        // Track (state, timestamp, file) per pane_id — keep highest timestamp
+        let mut best: HashMap<String, (String, u64, PathBuf)> = HashMap::new();
+        for (path, file) in &self.files {
+            let Some(event) = &file.last else {
+                continue;
+            };
+            if event.pane_id.is_empty() {
+                continue;
+            }
+            let replace = best
+                .get(&event.pane_id)
+                .is_none_or(|(_, prev_ts, _)| event.ts > *prev_ts);
+            if replace {
+                best.insert(
+                    event.pane_id.clone(),
+                    (event.state.clone(), event.ts, path.clone()),
+                );
             }
         }
+        best
     }
 
-    best.into_iter().map(|(k, (state, _))| (k, state)).collect()
+    /// `pending_asks` for a file seen by the last `latest` call, cached until
+    /// the file changes.
+    fn pending_asks(&mut self, path: &Path) -> u32 {
+        match self.files.get_mut(path) {
+            Some(file) => *file.pending_asks.get_or_insert_with(|| pending_asks(path)),
+            None => pending_asks(path),
+        }
+    }
 }
 
-fn state_from_str(s: &str) -> WindowState {
+pub fn state_from_str(s: &str) -> WindowState {
     match s {
         "working" => WindowState::Working,
         "asking" => WindowState::Asking,
         "idle" => WindowState::Idle,
+        "queued" => WindowState::Queued,
         _ => WindowState::Fresh,
     }
 }
 
+/// Without a history dir the files are simply removed.
+fn purge_events_for_pane_in(dir: &Path, history: Option<&Path>, pane_id: &str, name: Option<&str>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Some(event) = read_last_event(&path) else {
+            continue;
+        };
+        if event.pane_id != pane_id {
+            continue;
+        }
+        let file = path.file_name().map(|f| f.to_string_lossy().to_string());
+        let moved = history.zip(file).is_some_and(|(history, file)| {
+            let file = match name {
+                Some(name) => format!("{}.{file}", names::file_stem(name)),
+                None => file,
+            };
+            fs::rename(&path, history.join(file)).is_ok()
+        });
+        if !moved {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+/// Remove history files last written more than `HISTORY_MAX_DAYS` before
+/// `now`.
+fn prune_history_in(history: &Path, now: SystemTime) {
+    let Ok(entries) = fs::read_dir(history) else {
+        return;
+    };
+    let max_age = Duration::from_secs(HISTORY_MAX_DAYS * 86_400);
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > max_age);
+        if expired {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
 // ── Public API ──
 
-/// Remove event files whose last event matches the given pane_id.
-/// Called when a new window is created to prevent stale events (from a previous
-/// session that used the same recycled tmux pane_id) from contaminating state.
-pub fn purge_events_for_pane(pane_id: &str) {
-    let dir = events_dir();
-    let entries = match fs::read_dir(&dir) {
-        Ok(e) => e,
-        Err(_) => return,
+/// Move event files whose last event matches the given pane_id out of the
+/// events dir, into the history `cove timesheet` reads (tagged with the
+/// session `name` when known). Called when a window is killed, and when one
+/// is created so stale events from a previous session that used the same
+/// recycled tmux pane_id don't contaminate its state.
+pub fn purge_events_for_pane(pane_id: &str, name: Option<&str>) {
+    let Some(dir) = events_dir() else {
+        return;
     };
+    let history = paths::ensure_cove_dir(HISTORY_DIR).ok();
+    purge_events_for_pane_in(&dir, history.as_deref(), pane_id, name);
+    prune_history();
+}
 
-    for entry in entries.flatten() {
+/// Drop history files whose last event is over `HISTORY_MAX_DAYS` old.
+/// Called whenever files are moved in, so the history can't grow forever.
+pub fn prune_history() {
+    if let Some(dir) = history_dir() {
+        prune_history_in(&dir, SystemTime::now());
+    }
+}
+
+/// Archived event files, named `<session id>.jsonl` or
+/// `<name>.<session id>.jsonl` (session names can't contain dots).
+pub fn history_dir() -> Option<PathBuf> {
+    paths::cove_path(HISTORY_DIR)
+}
+
+/// Event file name (without `.jsonl`) for events about `pane_id` when its
+/// Claude session id is unknown, e.g. a restart before any hook fired.
+/// `session_for_pane` passes over these files, so they're never resumed.
+pub fn placeholder_session(pane_id: &str) -> String {
+    format!("{PLACEHOLDER_PREFIX}{}", pane_id.trim_start_matches('%'))
+}
+
+/// Claude session id (the event file's name) of the newest session whose
+/// latest event came from `pane_id`.
+pub fn session_for_pane(pane_id: &str) -> Option<String> {
+    session_for_pane_in(&events_dir()?, pane_id)
+}
+
+fn session_for_pane_in(dir: &Path, pane_id: &str) -> Option<String> {
+    let mut best: Option<(u64, String)> = None;
+    for entry in fs::read_dir(dir).ok()?.flatten() {
         let path = entry.path();
         if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
             continue;
         }
-        if let Some(line) = read_last_line(&path) {
-            if let Ok(event) = serde_json::from_str::<EventEntry>(&line) {
-                if event.pane_id == pane_id {
-                    let _ = fs::remove_file(&path);
-                }
-            }
+        let Some(event) = read_last_event(&path) else {
+            continue;
+        };
+        if event.pane_id != pane_id || best.as_ref().is_some_and(|(ts, _)| *ts > event.ts) {
+            continue;
+        }
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_string());
+        if stem
+            .as_ref()
+            .is_some_and(|id| id.starts_with(PLACEHOLDER_PREFIX))
+        {
+            continue;
         }
+        best = stem.map(|id| (event.ts, id));
     }
+    best.map(|(_, id)| id)
 }
 
-pub struct StateDetector;
+/// Latest hook-reported state per pane_id, for panes not tracked by tmux
+/// (kitty/wezterm tabs, keyed by their `$COVE_PANE` token).
+pub fn load_pane_states() -> HashMap<String, WindowState> {
+    events_dir()
+        .map(|dir| load_latest_events(&dir))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(pane_id, s)| (pane_id, state_from_str(&s)))
+        .collect()
+}
+
+pub struct StateDetector {
+    /// Claude pane ID per window index, from the most recent `detect` call.
+    pane_ids: HashMap<u32, String>,
+    /// Timestamp of each window's latest hook event, from the most recent `detect` call.
+    event_ts: HashMap<u32, u64>,
+    /// Unanswered questions per Asking window, from the most recent `detect` call.
+    asks: HashMap<u32, u32>,
+    /// State of each window as of the last `detect` call, so changes can be logged.
+    last: HashMap<u32, WindowState>,
+    events_dir: Option<PathBuf>,
+    cache: EventCache,
+}
 
 impl StateDetector {
     pub fn new() -> Self {
-        Self
+        Self::with(events_dir())
+    }
+
+    /// A detector reading events from `dir` instead of the state directory.
+    #[cfg(test)]
+    pub fn with_events_dir(dir: &Path) -> Self {
+        Self::with(Some(dir.to_path_buf()))
+    }
+
+    fn with(events_dir: Option<PathBuf>) -> Self {
+        Self {
+            pane_ids: HashMap::new(),
+            event_ts: HashMap::new(),
+            asks: HashMap::new(),
+            last: HashMap::new(),
+            events_dir,
+            cache: EventCache::default(),
+        }
+    }
+
+    /// Claude pane ID of a window as of the last `detect` call.
+    pub fn pane_id(&self, window_index: u32) -> Option<&str> {
+        self.pane_ids.get(&window_index).map(String::as_str)
+    }
+
+    /// Unix time of a window's latest hook event as of the last `detect` call.
+    pub fn last_event_ts(&self, window_index: u32) -> Option<u64> {
+        self.event_ts.get(&window_index).copied()
+    }
+
+    /// Unanswered AskUserQuestion/plan prompts of a window as of the last
+    /// `detect` call (only set while it is Asking).
+    pub fn pending_asks(&self, window_index: u32) -> Option<u32> {
+        self.asks.get(&window_index).copied()
     }
 
     /// Detect the state of each window. Returns a map from window_index to state.
-    pub fn detect(&mut self, windows: &[tmux::WindowInfo]) -> HashMap<u32, WindowState> {
+    pub fn detect(
+        &mut self,
+        client: &dyn TmuxClient,
+        windows: &[tmux::WindowInfo],
+    ) -> HashMap<u32, WindowState> {
+        // Get foreground commands + pane IDs for all panes in one tmux call
+        let pane_infos: Vec<tmux::PaneInfo> = client.list_pane_commands().unwrap_or_default();
+        self.detect_with(&pane_infos, windows)
+    }
+
+    /// Like `detect`, with the Claude panes already listed (so tmux can be
+    /// polled on another thread).
+    pub fn detect_with(
+        &mut self,
+        pane_infos: &[tmux::PaneInfo],
+        windows: &[tmux::WindowInfo],
+    ) -> HashMap<u32, WindowState> {
         let mut states = HashMap::new();
 
-        // Get foreground commands + pane IDs for all panes in one tmux call
-        let pane_infos: Vec<tmux::PaneInfo> = tmux::list_pane_commands().unwrap_or_default();
         let pane_cmds: HashMap<u32, &str> = pane_infos
             .iter()
             .map(|p| (p.window_index, p.command.as_str()))
             .collect();
-        let pane_ids: HashMap<u32, &str> = pane_infos
+        self.pane_ids = pane_infos
             .iter()
-            .map(|p| (p.window_index, p.pane_id.as_str()))
+            .map(|p| (p.window_index, p.pane_id.clone()))
             .collect();
 
         // Load all latest events once per detect cycle
-        let events = load_latest_events(&events_dir());
+        let events = match self.events_dir.as_deref() {
+            Some(dir) => self.cache.latest(dir),
+            None => HashMap::new(),
+        };
+        self.event_ts.clear();
+        self.asks.clear();
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
 
         for win in windows {
+            // Windows opened outside cove don't run Claude in pane .1
+            if !win.managed {
+                continue;
+            }
             let cmd = pane_cmds.get(&win.index).copied().unwrap_or("zsh");
 
-            // Shell prompt means Claude exited
-            if cmd == "zsh" || cmd == "bash" || cmd == "fish" {
+            // Match event by pane_id — each tmux pane has a unique ID like "%0"
+            let win_pane_id = self.pane_id(win.index).unwrap_or("");
+
+            // Shell prompt means Claude exited, unless a restart is on its way.
+            // A container session's pane shows its client (`docker`) until
+            // Claude exits, since the pane command execs it
+            let restarting = events.get(win_pane_id).is_some_and(|(state, ts, _)| {
+                state == RESTARTING && now.saturating_sub(*ts) < RESTART_GRACE_SECS
+            });
+            if panes::is_shell(cmd) && !restarting {
                 states.insert(win.index, WindowState::Done);
                 continue;
             }
 
-            // Match event by pane_id — each tmux pane has a unique ID like "%0"
-            let win_pane_id = pane_ids.get(&win.index).copied().unwrap_or("");
             let state = match events.get(win_pane_id) {
-                Some(state_str) => state_from_str(state_str),
+                Some((state_str, ts, path)) => {
+                    self.event_ts.insert(win.index, *ts);
+                    let state = state_from_str(state_str);
+                    // One answer among several parallel questions logs "working";
+                    // the window is still waiting until every ask is answered
+                    let pending = match state {
+                        WindowState::Asking | WindowState::Working => self.cache.pending_asks(path),
+                        _ => 0,
+                    };
+                    if pending > 0 {
+                        self.asks.insert(win.index, pending);
+                        WindowState::Asking
+                    } else {
+                        state
+                    }
+                }
                 None => WindowState::Fresh,
             };
 
             states.insert(win.index, state);
         }
 
+        for win in windows {
+            let Some(&state) = states.get(&win.index) else {
+                continue;
+            };
+            if self.last.get(&win.index) != Some(&state) {
+                let pane_id = self.pane_id(win.index).unwrap_or("");
+                log::debug!(
+                    "{} ({pane_id}): {:?} -> {state:?} (foreground {}, latest event {:?})",
+                    win.name,
+                    self.last.get(&win.index),
+                    pane_cmds.get(&win.index).copied().unwrap_or("none"),
+                    events.get(pane_id).map(|(state, ts, _)| (state, ts))
+                );
+            }
+        }
+        self.last = states.clone();
         states
     }
 }
@@ -211,6 +585,7 @@ impl StateDetector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tmux::fake::FakeTmux;
     use std::io::Write;
 
     #[test]
@@ -245,6 +620,59 @@ mod tests {
         assert!(read_last_line(&path).is_none());
     }
 
+    #[test]
+    fn test_pending_asks_pairs_within_turn() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.jsonl");
+        let line = |state: &str, hook: &str, ts: u64| {
+            format!(r#"{{"state":"{state}","cwd":"/p","pane_id":"%t1","ts":{ts},"hook":"{hook}"}}"#)
+        };
+        let lines = [
+            line("working", "user-prompt", 1),
+            line("asking", "ask", 2),
+            line("working", "ask-done", 3),
+            line("idle", "stop", 4),
+            line("working", "user-prompt", 5),
+            line("asking", "ask", 6),
+            line("asking", "ask", 7),
+            line("asking", "ask", 8),
+            line("working", "ask-done", 9),
+        ];
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+        assert_eq!(pending_asks(&path), 2);
+
+        // Old-format lines without a hook name can't be paired
+        fs::write(
+            &path,
+            r#"{"state":"asking","cwd":"/p","pane_id":"%t1","ts":1}"#,
+        )
+        .unwrap();
+        assert_eq!(pending_asks(&path), 0);
+    }
+
+    #[test]
+    fn test_event_cache_tracks_changes_and_purges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s.jsonl");
+        fs::write(
+            &path,
+            "{\"state\":\"working\",\"cwd\":\"/p\",\"pane_id\":\"%t1\",\"ts\":1}\n",
+        )
+        .unwrap();
+
+        let mut cache = EventCache::default();
+        assert_eq!(cache.latest(dir.path())["%t1"].0, "working");
+
+        // Appending changes the size, so the file is re-read
+        let mut f = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(f, r#"{{"state":"idle","cwd":"/p","pane_id":"%t1","ts":2}}"#).unwrap();
+        assert_eq!(cache.latest(dir.path())["%t1"].0, "idle");
+
+        fs::remove_file(&path).unwrap();
+        assert!(cache.latest(dir.path()).is_empty());
+        assert!(cache.files.is_empty());
+    }
+
     #[test]
     fn test_read_last_line_missing() {
         let path = Path::new("/nonexistent/test.jsonl");
@@ -366,6 +794,23 @@ mod tests {
         assert_eq!(state_from_str("unknown"), WindowState::Fresh);
     }
 
+    #[test]
+    fn test_prune_history_drops_old_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let old = dir.path().join("api.old.jsonl");
+        let recent = dir.path().join("api.recent.jsonl");
+        for (path, days) in [(&old, HISTORY_MAX_DAYS + 1), (&recent, 1)] {
+            let file = fs::File::create(path).unwrap();
+            file.set_modified(now - Duration::from_secs(days * 86_400))
+                .unwrap();
+        }
+
+        prune_history_in(dir.path(), now);
+        assert!(!old.exists());
+        assert!(recent.exists());
+    }
+
     #[test]
     fn test_purge_events_for_pane() {
         let dir = tempfile::tempdir().unwrap();
@@ -394,22 +839,12 @@ mod tests {
         )
         .unwrap();
 
-        // Call purge with a custom dir (can't use purge_events_for_pane directly
-        // since it uses events_dir(), so test the logic inline)
-        let entries = fs::read_dir(dir.path()).unwrap();
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
-                continue;
-            }
-            if let Some(line) = read_last_line(&path) {
-                if let Ok(event) = serde_json::from_str::<EventEntry>(&line) {
-                    if event.pane_id == "%3" {
-                        fs::remove_file(&path).unwrap();
-                    }
-                }
-            }
-        }
+        let history = dir.path().join("history");
+        fs::create_dir(&history).unwrap();
+        purge_events_for_pane_in(dir.path(), Some(&history), "%3", Some("work/api"));
+        assert!(history.join("work%2Fapi.old-session.jsonl").exists());
+        let archived = crate::commands::timesheet::read_sessions(&history);
+        assert_eq!(archived[0].1.name.as_deref(), Some("work/api"));
 
         // Only the %0 file should remain
         let remaining: Vec<_> = fs::read_dir(dir.path())
@@ -428,4 +863,82 @@ mod tests {
                 .contains("active-session")
         );
     }
+
+    #[test]
+    fn test_session_for_pane_picks_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("old-session.jsonl"),
+            r#"{"state":"idle","cwd":"/p","pane_id":"%3","ts":1000}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("new-session.jsonl"),
+            r#"{"state":"working","cwd":"/p","pane_id":"%3","ts":2000}"#,
+        )
+        .unwrap();
+        // A restart's placeholder is newer, but isn't a session to resume
+        fs::write(
+            dir.path()
+                .join(format!("{}.jsonl", placeholder_session("%3"))),
+            r#"{"state":"restarting","cwd":"/p","pane_id":"%3","ts":3000}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            session_for_pane_in(dir.path(), "%3").as_deref(),
+            Some("new-session")
+        );
+        assert!(session_for_pane_in(dir.path(), "%4").is_none());
+    }
+
+    #[test]
+    fn test_detect_with_fake_tmux() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("a.jsonl"),
+            r#"{"state":"working","cwd":"/code/api","pane_id":"%t1","ts":1000}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("b.jsonl"),
+            r#"{"state":"idle","cwd":"/code/web","pane_id":"%t2","ts":1001}"#,
+        )
+        .unwrap();
+
+        let fake = FakeTmux::new().with_windows(&[
+            ("api", "claude", "%t1"),
+            ("web", "claude", "%t2"),
+            ("docs", "zsh", "%t3"),
+            ("new", "claude", "%t4"),
+            ("restarted", "zsh", "%t5"),
+            ("boxed", "docker", "%t6"),
+            ("login", "-bash", "%t7"),
+        ]);
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        fs::write(
+            dir.path().join("c.jsonl"),
+            format!(
+                r#"{{"state":"restarting","cwd":"/code/restarted","pane_id":"%t5","ts":{now}}}"#
+            ),
+        )
+        .unwrap();
+        let windows = fake.list_windows().unwrap();
+        let mut detector = StateDetector::with_events_dir(dir.path());
+        let states = detector.detect(&fake, &windows);
+
+        assert_eq!(states[&1], WindowState::Working);
+        assert_eq!(states[&2], WindowState::Idle);
+        assert_eq!(states[&3], WindowState::Done);
+        assert_eq!(states[&4], WindowState::Fresh);
+        assert_eq!(states[&5], WindowState::Fresh);
+        // A container session's client isn't a shell; a login shell is
+        assert_eq!(states[&6], WindowState::Fresh);
+        assert_eq!(states[&7], WindowState::Done);
+        assert_eq!(detector.pane_id(2), Some("%t2"));
+        assert_eq!(detector.last_event_ts(1), Some(1000));
+    }
 }
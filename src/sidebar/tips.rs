@@ -0,0 +1,83 @@
+// ── First-time tips footer ──
+//
+// A one-line footer under the session list cycles through the keys new users
+// need. `?` hides it in every sidebar (a marker file under the state dir), `?`
+// again brings it back, and `sidebar.show_tips: false` in the config turns it
+// off for good.
+
+use std::fs;
+
+use crate::paths;
+
+// ── Constants ──
+
+/// Ticks each tip stays on screen (~100ms per tick).
+const TIP_TICKS: u64 = 80;
+
+const DISMISSED_FILE: &str = "tips-dismissed";
+
+// ── Public API ──
+
+/// The tips in display order; `prefix` is the tmux prefix key (e.g. "C-b").
+pub fn all(prefix: &str) -> Vec<String> {
+    vec![
+        "\u{2191}\u{2193} move \u{00b7} enter switch".to_string(),
+        "d changes \u{00b7} y clips \u{00b7} t transcript".to_string(),
+        format!("{prefix} d detaches from tmux"),
+        format!("f focuses Claude \u{00b7} {prefix} F returns"),
+        "cove <name> <dir> adds a session".to_string(),
+        "cove kill <name> ends one".to_string(),
+        "a adopts a window opened outside cove".to_string(),
+        "c clocks you in to supervise a session".to_string(),
+        "? hides these tips".to_string(),
+    ]
+}
+
+/// Tip to show at `tick`, rotating through `tips`.
+pub fn current(tips: &[String], tick: u64) -> Option<&str> {
+    if tips.is_empty() {
+        return None;
+    }
+    let i = (tick / TIP_TICKS) as usize % tips.len();
+    Some(tips[i].as_str())
+}
+
+/// True once the user has hidden the tips from any sidebar.
+pub fn dismissed() -> bool {
+    paths::cove_path(DISMISSED_FILE).is_some_and(|p| p.exists())
+}
+
+/// Hide or restore the tips for all sidebars.
+pub fn set_dismissed(dismissed: bool) {
+    if dismissed {
+        if let Ok(dir) = paths::ensure_cove_dir("") {
+            let _ = fs::write(dir.join(DISMISSED_FILE), "");
+        }
+    } else if let Some(path) = paths::cove_path(DISMISSED_FILE) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_rotates() {
+        let tips = all("C-b");
+        assert_eq!(current(&tips, 0), Some(tips[0].as_str()));
+        assert_eq!(current(&tips, TIP_TICKS), Some(tips[1].as_str()));
+        assert_eq!(
+            current(&tips, TIP_TICKS * tips.len() as u64),
+            Some(tips[0].as_str())
+        );
+        assert!(tips[2].starts_with("C-b d"));
+    }
+
+    #[test]
+    fn test_current_empty() {
+        assert_eq!(current(&[], 5), None);
+    }
+}
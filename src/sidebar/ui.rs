@@ -1,6 +1,6 @@
 // ── ratatui rendering for sidebar ──
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -8,9 +8,17 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Widget;
 
-use crate::colors;
+use crate::changes::{self, DiffLineKind};
+use crate::ci::CiStatus;
+use crate::clips::{Clip, ClipSource};
+use crate::sidebar::format::{self, Segment, Var};
+use crate::sidebar::groups::Row;
+use crate::sidebar::sections::Section;
 use crate::sidebar::state::WindowState;
 use crate::tmux::WindowInfo;
+use crate::transcript::{self, Role};
+use crate::watch::WatchStatus;
+use crate::{colors, names, paths, width};
 
 // ── Types ──
 
@@ -39,9 +47,93 @@ const LEGEND: &[LegendEntry] = &[
     },
 ];
 
+/// Files Claude edited in one session, or one file's diff when `diff` is set.
+pub struct ChangesWidget<'a> {
+    pub name: &'a str,
+    pub files: &'a [String],
+    pub selected: usize,
+    /// Diff lines of the selected file plus the scroll offset.
+    pub diff: Option<(&'a [String], usize)>,
+}
+
+/// A session's scratch register: saved snippets plus recently edited files.
+pub struct ClipsWidget<'a> {
+    pub name: &'a str,
+    pub clips: &'a [Clip],
+    pub selected: usize,
+}
+
+/// A session's Claude conversation (or its seed, under that `title`),
+/// scrolled to `scroll`, with search matches highlighted.
+pub struct TranscriptWidget<'a> {
+    pub name: &'a str,
+    pub title: &'a str,
+    pub lines: &'a [transcript::Line],
+    pub scroll: usize,
+    /// Last submitted search.
+    pub query: &'a str,
+    /// Search being typed after `/`.
+    pub typing: Option<&'a str>,
+    /// Why the transcript couldn't be loaded.
+    pub error: Option<&'a str>,
+}
+
 pub struct SidebarWidget<'a> {
     pub windows: &'a [WindowInfo],
+    /// How `windows` are laid out: sessions plus group and "other" headings.
+    pub rows: &'a [Row],
     pub states: &'a HashMap<u32, WindowState>,
+    /// Tool currently running per window index (Working windows only).
+    pub running_tools: &'a HashMap<u32, String>,
+    /// Latest `cove watch-run` result per window index.
+    pub watch: &'a HashMap<u32, WatchStatus>,
+    /// Long command running in a helper pane per window index, e.g. "cargo 2m".
+    pub helpers: &'a HashMap<u32, String>,
+    /// Unanswered questions per Asking window index.
+    pub asks: &'a HashMap<u32, u32>,
+    /// Windows whose row flashes because they just started waiting.
+    pub flashing: &'a HashMap<u32, u64>,
+    /// Parsed `sidebar.row_format`; None keeps the built-in row layout.
+    pub row_format: Option<&'a [Segment]>,
+    /// Names of pinned sessions (listed first, marked with ▴).
+    pub pinned: &'a HashSet<String>,
+    /// Session you're clocked in to supervise (marked with ◷).
+    pub clocked: Option<&'a str>,
+    /// Quick-switch slot of each session, by name, shown before the name.
+    pub slots: &'a HashMap<String, u8>,
+    /// Git branch per window index (only filled when the row format uses it).
+    pub branches: &'a HashMap<u32, String>,
+    /// Seconds since each window's last hook event.
+    pub ages: &'a HashMap<u32, u64>,
+    /// Tokens each window's Claude session has spent (only filled when the
+    /// row format uses them).
+    pub tokens: &'a HashMap<u32, u64>,
+    /// Footer tip, if tips are enabled and not dismissed.
+    pub tip: Option<&'a str>,
+    /// Sessions whose branch has been merged (`cleanup.check_merged`).
+    pub merged: &'a HashSet<String>,
+    /// Windows whose Claude pane was closed, by index.
+    pub broken: &'a HashSet<u32>,
+    /// Windows whose Claude pane crashed and was left stopped, by index.
+    pub crashed: &'a HashMap<u32, String>,
+    /// Windows with a pane that failed to start, with the end of its
+    /// output, shown under the list while the window is selected.
+    pub failed: &'a HashMap<u32, Vec<String>>,
+    /// Claude flags of the selected session's `profiles` rule, e.g.
+    /// "opus · plan", shown under the list.
+    pub profile: Option<&'a str>,
+    /// Windows whose Claude was stopped on purpose, by index.
+    pub parked: &'a HashSet<u32>,
+    /// Custom sections shown under the session list.
+    pub sections: &'a [Section],
+    /// Working windows that look stuck, by index.
+    pub stuck: &'a HashMap<u32, String>,
+    /// CI status of each session's pull request, by name (`ci.enabled`).
+    pub ci: &'a HashMap<String, CiStatus>,
+    /// Window index and typed text of a session being renamed in place.
+    pub renaming: Option<(u32, &'a str)>,
+    /// Warning shown in the footer instead of the tip (e.g. broken hooks).
+    pub notice: Option<&'a str>,
     pub selected: usize,
     pub tick: u64,
 }
@@ -51,38 +143,49 @@ pub struct SidebarWidget<'a> {
 impl Widget for SidebarWidget<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let window_count = self.windows.len();
+        let session_count = self.windows.iter().filter(|w| w.managed).count();
+        let grouped = self.rows.iter().any(|r| matches!(r, Row::Group { .. }));
 
         // ── Header ──
-        let plural = if window_count == 1 { "" } else { "s" };
-        let header = Line::from(vec![
+        let plural = if session_count == 1 { "" } else { "s" };
+        let mut header = Line::from(vec![
             Span::raw(" "),
             Span::styled(
-                format!("{window_count} session{plural}"),
+                format!("{session_count} session{plural}"),
                 Style::default().fg(colors::OVERLAY),
             ),
             Span::styled(" \u{00b7} ", Style::default().fg(colors::SURFACE)),
             Span::styled("\u{2191}\u{2193}", Style::default().fg(colors::BLUE)),
             Span::styled(" navigate", Style::default().fg(colors::OVERLAY)),
         ]);
+        if grouped {
+            header.push_span(Span::styled(
+                " \u{00b7} ",
+                Style::default().fg(colors::SURFACE),
+            ));
+            header.push_span(Span::styled(
+                "\u{2190}\u{2192}",
+                Style::default().fg(colors::BLUE),
+            ));
+            header.push_span(Span::styled(" fold", Style::default().fg(colors::OVERLAY)));
+        }
         if area.height > 0 {
             buf.set_line(area.x, area.y, &header, area.width);
         }
 
         // ── Separator ──
-        if area.height > 1 {
-            let sep_row = area.y + 1;
-            for x in area.x..area.x + area.width {
-                buf.cell_mut((x, sep_row))
-                    .map(|cell| cell.set_char('\u{2500}').set_fg(colors::SURFACE));
-            }
-        }
+        render_separator(area, buf);
 
         // ── Body: sessions (left) + legend (right) ──
         let body_start = area.y + 2;
-        let max_rows = window_count.max(LEGEND.len());
+        let max_rows = self.rows.len().max(LEGEND.len());
 
         // Calculate right column start (for legend)
         let right_col = area.width.saturating_sub(15);
+        // Session rows stop one column short of the legend so they never touch it
+        let left_col = right_col.saturating_sub(1);
+        // Names are cut to the left column, after the " ❯ " prefix
+        let name_max = (left_col as usize).saturating_sub(3);
 
         #[allow(clippy::needless_range_loop)] // indexes two parallel arrays of different lengths
         for row in 0..max_rows {
@@ -92,14 +195,44 @@ impl Widget for SidebarWidget<'_> {
             }
 
             // Left column: session list
-            if row < window_count {
-                let win = &self.windows[row];
+            let win_row = match self.rows.get(row) {
+                Some(Row::Window(i)) => Some(*i),
+                Some(Row::Other) => {
+                    let heading =
+                        Line::from(Span::styled(" other", Style::default().fg(colors::SURFACE)));
+                    buf.set_line(area.x, y, &heading, left_col);
+                    None
+                }
+                Some(Row::Group {
+                    repo,
+                    collapsed,
+                    windows,
+                }) => {
+                    let heading = self.group_heading(repo, *collapsed, windows);
+                    buf.set_line(area.x, y, &heading, left_col);
+                    let flashing = windows
+                        .iter()
+                        .any(|&i| self.flashing.contains_key(&self.windows[i].index));
+                    if *collapsed && flashing && (self.tick / 3) % 2 == 0 {
+                        buf.set_style(
+                            Rect::new(area.x, y, right_col, 1),
+                            Style::default().bg(colors::SURFACE),
+                        );
+                    }
+                    None
+                }
+                None => None,
+            };
+            if let Some(i) = win_row
+                && i < window_count
+            {
+                let win = &self.windows[i];
                 let state = self
                     .states
                     .get(&win.index)
                     .copied()
                     .unwrap_or(WindowState::Fresh);
-                let is_selected = row == self.selected;
+                let is_selected = i == self.selected;
 
                 let (bullet, name_style) = if is_selected {
                     (
@@ -110,28 +243,132 @@ impl Widget for SidebarWidget<'_> {
                     (Span::raw(" "), Style::default().fg(colors::OVERLAY))
                 };
 
-                let mut spans = vec![
-                    Span::raw(" "),
-                    bullet,
-                    Span::raw(" "),
-                    Span::styled(&win.name, name_style),
-                ];
-
-                let status = status_text(state);
-                if matches!(state, WindowState::Working) {
-                    // Spinner renders inline right after the name
-                    spans.push(status_span(state, self.tick));
-                } else if !status.is_empty() {
-                    // Right-align status text against the legend column
-                    let name_width = 3 + win.name.len(); // " · " or " ❯ " prefix + name
-                    let status_width = status.chars().count() + 2; // 2 spaces before status
-                    let pad = (right_col as usize).saturating_sub(name_width + status_width);
-                    spans.push(Span::raw(" ".repeat(pad)));
-                    spans.push(status_span(state, self.tick));
+                if let Some((index, text)) = self.renaming
+                    && index == win.index
+                {
+                    let dim = Style::default().fg(colors::OVERLAY);
+                    let spans = vec![
+                        Span::raw(" "),
+                        bullet,
+                        Span::raw(" "),
+                        Span::styled(text, Style::default().fg(Color::White).bg(colors::SURFACE)),
+                        // Cursor
+                        Span::styled(" ", Style::default().bg(Color::White)),
+                        Span::styled("  \u{23ce}", Style::default().fg(colors::BLUE)),
+                        Span::styled(" rename", dim),
+                        Span::styled(" \u{00b7} ", Style::default().fg(colors::SURFACE)),
+                        Span::styled("esc", Style::default().fg(colors::BLUE)),
+                        Span::styled(" cancel", dim),
+                    ];
+                    buf.set_line(area.x, y, &Line::from(spans), left_col);
+                } else if !win.managed {
+                    let mut spans = vec![
+                        Span::raw(" "),
+                        bullet,
+                        Span::raw(" "),
+                        Span::styled(width::truncate(&win.name, name_max), name_style),
+                    ];
+                    if is_selected {
+                        spans.push(Span::styled("  a", Style::default().fg(colors::BLUE)));
+                        spans.push(Span::styled(" adopt", Style::default().fg(colors::OVERLAY)));
+                    }
+                    buf.set_line(area.x, y, &Line::from(spans), left_col);
+                } else if let Some(segments) = self.row_format {
+                    let mut spans = vec![Span::raw(" "), bullet, Span::raw(" ")];
+                    spans.extend(self.format_row(segments, win, state, name_style));
+                    spans.extend(self.pane_badge(win.index, is_selected));
+                    if self.stuck.contains_key(&win.index) {
+                        spans.push(stuck_badge(is_selected));
+                    }
+                    buf.set_line(area.x, y, &Line::from(spans), left_col);
+                } else {
+                    let slot = self.slot_mark(&win.name);
+                    let slot_width = slot.as_ref().map_or(0, |s| s.width());
+                    let pin = self.pinned.contains(&win.name).then(pin_mark);
+                    let clock = (self.clocked == Some(win.name.as_str())).then(clock_mark);
+                    let badge = self.watch.get(&win.index).map(|s| watch_badge(*s));
+                    let ci = self.ci.get(&win.name).map(|s| ci_badge(*s));
+                    let helper = self.helpers.get(&win.index).map(|h| helper_badge(h));
+                    let asks = self.asks.get(&win.index).and_then(|n| asks_badge(*n));
+                    let hinted = |selected: bool| {
+                        let merged = self
+                            .merged
+                            .contains(&win.name)
+                            .then(|| merged_badge(selected));
+                        let broken = self.pane_badge(win.index, selected);
+                        let stuck = self
+                            .stuck
+                            .contains_key(&win.index)
+                            .then(|| stuck_badge(selected));
+                        [merged, broken, stuck]
+                    };
+                    let cols = |b: &Option<Span>| b.as_ref().map_or(0, |b| b.width());
+                    let widths = |hints: &[Option<Span>; 3]| hints.iter().map(cols).sum::<usize>();
+                    let plain = [&pin, &clock, &badge, &ci, &helper, &asks]
+                        .into_iter()
+                        .map(cols)
+                        .sum::<usize>();
+                    let mut hints = hinted(is_selected);
+                    // The name keeps at least half the column; the selected row's
+                    // key hints go first when the badges need more
+                    let name_floor = width::of(self.label(win)).min(name_max / 2);
+                    if slot_width + name_floor + plain + widths(&hints) > name_max {
+                        hints = hinted(false);
+                    }
+                    let badge_width = plain + widths(&hints);
+                    let [merged, broken, stuck] = hints;
+                    let label_max = name_max
+                        .saturating_sub(slot_width + badge_width)
+                        .max(name_floor);
+                    let label = width::truncate(self.label(win), label_max);
+                    let label_width = slot_width + width::of(&label);
+                    let mut spans = vec![Span::raw(" "), bullet, Span::raw(" ")];
+                    spans.extend(slot);
+                    spans.push(Span::styled(label, name_style));
+                    spans.extend(pin);
+                    spans.extend(clock);
+                    spans.extend(badge);
+                    spans.extend(ci);
+                    spans.extend(helper);
+                    spans.extend(asks);
+                    spans.extend(merged);
+                    spans.extend(broken);
+                    spans.extend(stuck);
+
+                    let status = status_text(state);
+                    if matches!(state, WindowState::Working) {
+                        // Spinner renders inline right after the name, followed by the running tool
+                        spans.push(status_span(state, self.tick));
+                        if let Some(tool) = self.running_tools.get(&win.index) {
+                            spans.push(Span::styled(
+                                format!(" {tool}"),
+                                Style::default().fg(colors::OVERLAY),
+                            ));
+                        }
+                    } else if !status.is_empty() {
+                        // Right-align status text against the legend column
+                        let name_width = 3 + label_width + badge_width; // " · " or " ❯ " prefix + name + badge
+                        let status_width = width::of(status) + 2; // 2 spaces before status
+                        // Left out rather than cut when the name fills the row
+                        if let Some(pad) =
+                            (right_col as usize).checked_sub(name_width + status_width)
+                        {
+                            spans.push(Span::raw(" ".repeat(pad)));
+                            spans.push(status_span(state, self.tick));
+                        }
+                    }
+
+                    let line = Line::from(spans);
+                    buf.set_line(area.x, y, &line, left_col);
                 }
 
-                let line = Line::from(spans);
-                buf.set_line(area.x, y, &line, right_col);
+                // Blink the row of a session that just started waiting
+                if self.flashing.contains_key(&win.index) && (self.tick / 3) % 2 == 0 {
+                    buf.set_style(
+                        Rect::new(area.x, y, right_col, 1),
+                        Style::default().bg(colors::SURFACE),
+                    );
+                }
             }
 
             // Right column: legend
@@ -145,11 +382,662 @@ impl Widget for SidebarWidget<'_> {
                 buf.set_line(area.x + right_col, y, &legend_line, area.width - right_col);
             }
         }
+
+        // ── Why the selected session failed to start, under the body ──
+        let footer_y = area.y + area.height.saturating_sub(1);
+        let mut y = body_start + max_rows as u16;
+        if let Some(win) = self.windows.get(self.selected)
+            && let Some(lines) = self.failed.get(&win.index)
+            && y + 2 < footer_y
+        {
+            let heading = Line::from(vec![
+                Span::styled(" \u{2717} ", Style::default().fg(colors::RED)),
+                Span::styled(
+                    format!("{} failed to start", self.label(win)),
+                    Style::default()
+                        .fg(colors::RED)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                // The row drops its key hint when the name needs the room
+                Span::styled(" \u{00b7} ", Style::default().fg(colors::SURFACE)),
+                Span::styled("R", Style::default().fg(colors::BLUE)),
+                Span::styled(" restart", Style::default().fg(colors::OVERLAY)),
+            ]);
+            buf.set_line(area.x, y + 1, &heading, area.width);
+            y += 2;
+            for line in lines.iter().take((footer_y - y) as usize) {
+                let line = Line::from(vec![
+                    Span::raw("   "),
+                    Span::styled(line.as_str(), Style::default().fg(colors::OVERLAY)),
+                ]);
+                buf.set_line(area.x, y, &line, area.width);
+                y += 1;
+            }
+        }
+
+        // ── Claude profile of the selected session, under the body ──
+        if let Some(profile) = self.profile
+            && y + 2 < footer_y
+        {
+            let line = Line::from(vec![
+                Span::styled(" \u{2699} ", Style::default().fg(colors::LAVENDER)),
+                Span::styled(profile, Style::default().fg(colors::OVERLAY)),
+            ]);
+            buf.set_line(area.x, y + 1, &line, area.width);
+            y += 2;
+        }
+
+        // ── Custom sections, under the body while rows are left ──
+        for section in self.sections {
+            // A blank row, the heading and at least one line
+            if y + 2 >= footer_y {
+                break;
+            }
+            buf.set_line(area.x, y + 1, &section_heading(section), area.width);
+            y += 2;
+            for line in section.lines.iter().take((footer_y - y) as usize) {
+                let line = Line::from(vec![
+                    Span::raw("   "),
+                    Span::styled(line.as_str(), Style::default().fg(colors::OVERLAY)),
+                ]);
+                buf.set_line(area.x, y, &line, area.width);
+                y += 1;
+            }
+        }
+
+        // ── Footer: a notice or tip, only when there's a spare row below the body ──
+        if area.height >= 2 && footer_y > y {
+            let line = if let Some(notice) = self.notice {
+                Some(Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled("! ", Style::default().fg(colors::RED)),
+                    Span::styled(notice, Style::default().fg(colors::RED)),
+                ]))
+            } else {
+                self.tip.map(|tip| {
+                    Line::from(vec![
+                        Span::raw(" "),
+                        Span::styled(
+                            "tip ",
+                            Style::default()
+                                .fg(colors::SURFACE)
+                                .add_modifier(Modifier::ITALIC),
+                        ),
+                        Span::styled(tip, Style::default().fg(colors::OVERLAY)),
+                    ])
+                })
+            };
+            if let Some(line) = line {
+                buf.set_line(area.x, footer_y, &line, area.width);
+            }
+        }
+    }
+}
+
+impl<'a> SidebarWidget<'a> {
+    /// "▾ repo" over an open group; a collapsed one shows "▸ repo" with how
+    /// many of its sessions are asking, waiting for you and working.
+    fn group_heading(&self, repo: &'a str, collapsed: bool, windows: &[usize]) -> Line<'a> {
+        if !collapsed {
+            return Line::from(Span::styled(
+                format!(" \u{25be} {repo}"),
+                Style::default().fg(colors::SURFACE),
+            ));
+        }
+        let is_selected = windows.contains(&self.selected);
+        let style = Style::default().fg(if is_selected {
+            Color::White
+        } else {
+            colors::OVERLAY
+        });
+        let mut spans = vec![
+            Span::raw(" "),
+            Span::styled("\u{25b8} ", style),
+            Span::styled(repo, style),
+        ];
+
+        let count = |state| {
+            windows
+                .iter()
+                .filter(|&&i| self.states.get(&self.windows[i].index) == Some(&state))
+                .count()
+        };
+        let counts: Vec<Span> = [
+            (WindowState::Asking, "?", colors::LAVENDER),
+            (WindowState::Idle, "\u{25cf}", colors::GREEN),
+            (WindowState::Working, "\u{25b6}", colors::LAVENDER),
+        ]
+        .into_iter()
+        .filter_map(|(state, mark, color)| {
+            let n = count(state);
+            (n > 0).then(|| Span::styled(format!("{n}{mark}"), Style::default().fg(color)))
+        })
+        .collect();
+        if !counts.is_empty() {
+            let dim = Style::default().fg(colors::OVERLAY);
+            spans.push(Span::styled(" (", dim));
+            for (i, span) in counts.into_iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::raw(" "));
+                }
+                spans.push(span);
+            }
+            spans.push(Span::styled(")", dim));
+        }
+        if is_selected {
+            spans.push(Span::styled(
+                "  \u{2192}",
+                Style::default().fg(colors::BLUE),
+            ));
+            spans.push(Span::styled(" open", Style::default().fg(colors::OVERLAY)));
+        }
+        Line::from(spans)
+    }
+
+    /// A session's slot before its name, or padding for one without while
+    /// any session has a slot.
+    fn slot_mark(&self, name: &str) -> Option<Span<'static>> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        Some(match self.slots.get(name) {
+            Some(slot) => Span::styled(format!("{slot} "), Style::default().fg(colors::OVERLAY)),
+            None => Span::raw("  "),
+        })
+    }
+
+    /// Badge for a window whose Claude pane failed to start, crashed, was
+    /// parked or was closed.
+    fn pane_badge(&self, index: u32, selected: bool) -> Option<Span<'static>> {
+        if self.failed.contains_key(&index) {
+            Some(failed_badge(selected))
+        } else if self.crashed.contains_key(&index) {
+            Some(crashed_badge(selected))
+        } else if self.parked.contains(&index) {
+            Some(parked_badge(selected))
+        } else {
+            self.broken.contains(&index).then(|| broken_badge(selected))
+        }
+    }
+
+    /// `win`'s name as shown: without its tag unless another window shares
+    /// the short name.
+    fn label(&self, win: &'a WindowInfo) -> &'a str {
+        let names: Vec<&str> = self.windows.iter().map(|w| w.name.as_str()).collect();
+        names::label(&win.name, &names)
+    }
+
+    /// Spans for one session row rendered from a user's row template.
+    fn format_row(
+        &self,
+        segments: &'a [Segment],
+        win: &'a WindowInfo,
+        state: WindowState,
+        name_style: Style,
+    ) -> Vec<Span<'a>> {
+        let dim = Style::default().fg(colors::OVERLAY);
+        segments
+            .iter()
+            .filter_map(|seg| {
+                let var = match seg {
+                    Segment::Literal(text) => return Some(Span::styled(text.as_str(), dim)),
+                    Segment::Var(var) => *var,
+                };
+                let span = match var {
+                    Var::Icon => state_icon(state, self.tick),
+                    Var::Name => Span::styled(self.label(win), name_style),
+                    Var::Status => status_span(state, self.tick),
+                    Var::Tool => Span::styled(self.running_tools.get(&win.index)?.as_str(), dim),
+                    Var::Watch => {
+                        let badge = watch_badge(*self.watch.get(&win.index)?);
+                        // Badges carry a leading space for the built-in layout
+                        Span::styled(badge.content.trim_start().to_string(), badge.style)
+                    }
+                    Var::Ci => {
+                        let badge = ci_badge(*self.ci.get(&win.name)?);
+                        Span::styled(badge.content.trim_start().to_string(), badge.style)
+                    }
+                    Var::Helper => {
+                        let badge = helper_badge(self.helpers.get(&win.index)?);
+                        Span::styled(badge.content.trim_start().to_string(), badge.style)
+                    }
+                    Var::Branch => Span::styled(
+                        self.branches.get(&win.index)?.as_str(),
+                        Style::default().fg(colors::BLUE),
+                    ),
+                    Var::Dir => Span::styled(paths::tildify(&win.pane_path), dim),
+                    Var::Age => Span::styled(format::format_age(*self.ages.get(&win.index)?), dim),
+                    Var::Index => Span::styled(win.index.to_string(), dim),
+                    Var::Slot => Span::styled(self.slots.get(&win.name)?.to_string(), dim),
+                    Var::Tokens => {
+                        Span::styled(format::format_tokens(*self.tokens.get(&win.index)?), dim)
+                    }
+                    Var::Asks => {
+                        let badge = asks_badge(*self.asks.get(&win.index)?)?;
+                        Span::styled(badge.content.trim_start().to_string(), badge.style)
+                    }
+                    Var::Pin => {
+                        self.pinned.get(&win.name)?;
+                        Span::styled("\u{25b4}", pin_mark().style)
+                    }
+                };
+                Some(span)
+            })
+            .collect()
+    }
+}
+
+impl Widget for ChangesWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some((lines, scroll)) = self.diff else {
+            let n = self.files.len();
+            let plural = if n == 1 { "" } else { "s" };
+            render_view_header(
+                area,
+                buf,
+                format!("{} \u{00b7} {n} file{plural}", self.name),
+                " enter diff \u{00b7} esc back",
+            );
+            let items: Vec<Vec<Span>> = self
+                .files
+                .iter()
+                .map(|f| vec![Span::raw(paths::tildify(f))])
+                .collect();
+            render_pick_list(area, buf, &items, self.selected, "no files changed yet");
+            return;
+        };
+
+        let title = self
+            .files
+            .get(self.selected)
+            .map(|f| paths::tildify(f))
+            .unwrap_or_default();
+        render_view_header(
+            area,
+            buf,
+            title,
+            " \u{2191}\u{2193} scroll \u{00b7} esc back",
+        );
+
+        let body_start = area.y + 2;
+        let rows = area.height.saturating_sub(2) as usize;
+        for (row, line) in lines.iter().skip(scroll).take(rows).enumerate() {
+            let color = match changes::classify(line) {
+                DiffLineKind::Header => colors::OVERLAY,
+                DiffLineKind::Hunk => colors::BLUE,
+                DiffLineKind::Added => colors::GREEN,
+                DiffLineKind::Removed => colors::RED,
+                DiffLineKind::Context => Color::White,
+            };
+            let span = Span::styled(line.as_str(), Style::default().fg(color));
+            buf.set_line(
+                area.x,
+                body_start + row as u16,
+                &Line::from(span),
+                area.width,
+            );
+        }
+    }
+}
+
+impl Widget for ClipsWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        render_view_header(
+            area,
+            buf,
+            format!("{} \u{00b7} clips", self.name),
+            " enter paste \u{00b7} esc back",
+        );
+        let items: Vec<Vec<Span>> = self
+            .clips
+            .iter()
+            .map(|c| {
+                // Only the first line of multi-line snippets fits on a row
+                let text = c.text.lines().next().unwrap_or("").to_string();
+                let mut spans = vec![Span::raw(paths::tildify(&text))];
+                if c.source == ClipSource::Edited {
+                    spans.push(Span::styled(
+                        "  edited",
+                        Style::default()
+                            .fg(colors::SURFACE)
+                            .add_modifier(Modifier::ITALIC),
+                    ));
+                }
+                spans
+            })
+            .collect();
+        render_pick_list(
+            area,
+            buf,
+            &items,
+            self.selected,
+            "empty \u{2014} cove copy <name> <text>",
+        );
+    }
+}
+
+impl Widget for TranscriptWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        render_view_header(
+            area,
+            buf,
+            format!("{} \u{00b7} {}", self.name, self.title),
+            " / search \u{00b7} esc back",
+        );
+        let body_start = area.y + 2;
+        let rows = transcript_rows(area.height);
+        if rows == 0 {
+            return;
+        }
+        let dim = Style::default()
+            .fg(colors::OVERLAY)
+            .add_modifier(Modifier::ITALIC);
+        if self.lines.is_empty() {
+            let text = self.error.unwrap_or("no messages yet");
+            let line = Line::from(Span::styled(format!(" {text}"), dim));
+            buf.set_line(area.x, body_start, &line, area.width);
+            return;
+        }
+
+        for (row, line) in self.lines.iter().skip(self.scroll).take(rows).enumerate() {
+            let style = Style::default().fg(match line.role {
+                Role::User => colors::BLUE,
+                Role::Assistant => Color::White,
+                Role::Tool => colors::OVERLAY,
+            });
+            let mut spans = vec![Span::raw(" ")];
+            let mut text = line.text.as_str();
+            if line.first {
+                let label = role_label(line.role);
+                spans.push(Span::styled(label, style.add_modifier(Modifier::BOLD)));
+                text = &text[label.len()..];
+            }
+            spans.extend(highlight(text, self.query, style));
+            let y = body_start + row as u16;
+            buf.set_line(area.x, y, &Line::from(spans), area.width);
+        }
+
+        // ── Footer: search prompt or match count ──
+        let footer = match self.typing {
+            Some(typing) => Line::from(vec![
+                Span::styled(" /", Style::default().fg(colors::BLUE)),
+                Span::styled(
+                    format!("{typing}\u{258f}"),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
+            None if !self.query.is_empty() => {
+                let hits = self
+                    .lines
+                    .iter()
+                    .filter(|l| contains_ignore_case(&l.text, self.query))
+                    .count();
+                let text = match hits {
+                    0 => format!(" no match for \u{201c}{}\u{201d}", self.query),
+                    n => format!(" {n} rows match \u{00b7} n next \u{00b7} N previous"),
+                };
+                Line::from(Span::styled(text, dim))
+            }
+            None => Line::default(),
+        };
+        buf.set_line(area.x, area.y + area.height - 1, &footer, area.width);
     }
 }
 
+/// Label starting each transcript entry: Claude's own prompt and reply marks.
+pub fn role_label(role: Role) -> &'static str {
+    match role {
+        Role::User => "\u{276f}",
+        Role::Assistant => "\u{25cf}",
+        Role::Tool => "\u{00b7}",
+    }
+}
+
+/// Rows left for transcript text under the header and above the footer.
+pub fn transcript_rows(height: u16) -> usize {
+    height.saturating_sub(3) as usize
+}
+
+/// Whether `text` contains `query`, ignoring ASCII case.
+pub fn contains_ignore_case(text: &str, query: &str) -> bool {
+    !query.is_empty()
+        && text
+            .to_ascii_lowercase()
+            .contains(&query.to_ascii_lowercase())
+}
+
 // ── Helpers ──
 
+/// `text` as spans in `style`, with every (ASCII case-insensitive) occurrence
+/// of `query` picked out.
+fn highlight<'a>(text: &'a str, query: &str, style: Style) -> Vec<Span<'a>> {
+    if query.is_empty() {
+        return vec![Span::styled(text, style)];
+    }
+    // ASCII lowercasing keeps byte offsets, so they index `text` too
+    let haystack = text.to_ascii_lowercase();
+    let needle = query.to_ascii_lowercase();
+    let mut spans = Vec::new();
+    let mut start = 0;
+    while let Some(found) = haystack[start..].find(&needle) {
+        let at = start + found;
+        let end = at + needle.len();
+        spans.push(Span::styled(&text[start..at], style));
+        spans.push(Span::styled(
+            &text[at..end],
+            style.bg(colors::SURFACE).add_modifier(Modifier::BOLD),
+        ));
+        start = end;
+    }
+    spans.push(Span::styled(&text[start..], style));
+    spans
+}
+
+fn render_separator(area: Rect, buf: &mut Buffer) {
+    if area.height > 1 {
+        let sep_row = area.y + 1;
+        for x in area.x..area.x + area.width {
+            buf.cell_mut((x, sep_row))
+                .map(|cell| cell.set_char('\u{2500}').set_fg(colors::SURFACE));
+        }
+    }
+}
+
+/// Title line + separator shared by the sidebar's sub-views.
+fn render_view_header(area: Rect, buf: &mut Buffer, title: String, hint: &str) {
+    let header = Line::from(vec![
+        Span::raw(" "),
+        Span::styled(title, Style::default().fg(Color::White)),
+        Span::styled(hint.to_string(), Style::default().fg(colors::OVERLAY)),
+    ]);
+    if area.height > 0 {
+        buf.set_line(area.x, area.y, &header, area.width);
+    }
+    render_separator(area, buf);
+}
+
+/// Selectable rows below a view header, scrolled to keep `selected` visible.
+fn render_pick_list(
+    area: Rect,
+    buf: &mut Buffer,
+    items: &[Vec<Span>],
+    selected: usize,
+    empty: &str,
+) {
+    let body_start = area.y + 2;
+    let rows = area.height.saturating_sub(2) as usize;
+    if rows == 0 {
+        return;
+    }
+
+    if items.is_empty() {
+        let span = Span::styled(
+            format!(" {empty}"),
+            Style::default()
+                .fg(colors::OVERLAY)
+                .add_modifier(Modifier::ITALIC),
+        );
+        buf.set_line(area.x, body_start, &Line::from(span), area.width);
+        return;
+    }
+
+    let offset = (selected + 1).saturating_sub(rows);
+    for (row, item) in items.iter().enumerate().skip(offset).take(rows) {
+        let is_selected = row == selected;
+        let (bullet, style) = if is_selected {
+            ("\u{276f}", Style::default().fg(Color::White))
+        } else {
+            (" ", Style::default().fg(colors::OVERLAY))
+        };
+        let mut spans = vec![
+            Span::raw(" "),
+            Span::styled(bullet, Style::default().fg(Color::White)),
+            Span::raw(" "),
+        ];
+        for (i, span) in item.iter().enumerate() {
+            // The first span is the item label; later spans keep their own style
+            if i == 0 {
+                spans.push(Span::styled(span.content.clone(), style));
+            } else {
+                spans.push(span.clone());
+            }
+        }
+        let y = body_start + (row - offset) as u16;
+        buf.set_line(area.x, y, &Line::from(spans), area.width);
+    }
+}
+
+/// Single-cell state indicator for `{icon}` in row templates.
+fn state_icon(state: WindowState, tick: u64) -> Span<'static> {
+    match state {
+        WindowState::Working => {
+            let frame = SPINNER[tick as usize % SPINNER.len()];
+            Span::styled(frame.to_string(), Style::default().fg(colors::LAVENDER))
+        }
+        WindowState::Asking => Span::styled("?", Style::default().fg(colors::LAVENDER)),
+        WindowState::Idle => Span::styled("\u{25cf}", Style::default().fg(colors::GREEN)),
+        WindowState::Done => Span::styled("\u{25cb}", Style::default().fg(colors::SURFACE)),
+        WindowState::Fresh => Span::styled("\u{00b7}", Style::default().fg(colors::SURFACE)),
+        WindowState::Queued => Span::styled("\u{2026}", Style::default().fg(colors::BLUE)),
+    }
+}
+
+/// Marker shown after a pinned session's name.
+fn pin_mark() -> Span<'static> {
+    Span::styled(" \u{25b4}", Style::default().fg(colors::LAVENDER))
+}
+
+/// Marker shown after the name of the session you're clocked in to.
+fn clock_mark() -> Span<'static> {
+    Span::styled(" \u{25f7}", Style::default().fg(colors::PEACH))
+}
+
+/// "merged" after a session whose branch has landed, plus the teardown key
+/// on the selected row.
+fn merged_badge(selected: bool) -> Span<'static> {
+    let text = if selected {
+        " merged \u{b7} X clean up"
+    } else {
+        " merged"
+    };
+    Span::styled(text, Style::default().fg(colors::GREEN))
+}
+
+/// "▪ pods", with a note when the lines are from an earlier run.
+fn section_heading(section: &Section) -> Line<'_> {
+    let mut line = Line::from(Span::styled(
+        format!(" \u{25aa} {}", section.title),
+        Style::default().fg(colors::SURFACE),
+    ));
+    if section.stale {
+        line.push_span(Span::styled(" stale", Style::default().fg(colors::PEACH)));
+    }
+    line
+}
+
+fn failed_badge(selected: bool) -> Span<'static> {
+    let text = if selected {
+        " failed to start \u{b7} R restart"
+    } else {
+        " failed to start"
+    };
+    Span::styled(text, Style::default().fg(colors::RED))
+}
+
+fn crashed_badge(selected: bool) -> Span<'static> {
+    let text = if selected {
+        " crashed \u{b7} R restart"
+    } else {
+        " crashed"
+    };
+    Span::styled(text, Style::default().fg(colors::RED))
+}
+
+fn parked_badge(selected: bool) -> Span<'static> {
+    let text = if selected {
+        " parked \u{b7} R relaunch"
+    } else {
+        " parked"
+    };
+    Span::styled(text, Style::default().fg(colors::OVERLAY))
+}
+
+fn broken_badge(selected: bool) -> Span<'static> {
+    let text = if selected {
+        " broken \u{b7} R repair"
+    } else {
+        " broken"
+    };
+    Span::styled(text, Style::default().fg(colors::RED))
+}
+
+/// "stuck?" after a session that looks stuck, plus its keys on the selected row.
+fn stuck_badge(selected: bool) -> Span<'static> {
+    let text = if selected {
+        " stuck? \u{b7} i interrupt \u{b7} n ask"
+    } else {
+        " stuck?"
+    };
+    Span::styled(text, Style::default().fg(colors::RED))
+}
+
+/// "? ×3" after a session with several unanswered questions; a single question
+/// is already covered by the "waiting…" status.
+fn asks_badge(pending: u32) -> Option<Span<'static>> {
+    (pending > 1).then(|| {
+        Span::styled(
+            format!(" ?\u{00d7}{pending}"),
+            Style::default().fg(colors::BLUE),
+        )
+    })
+}
+
+/// Compact watch-run result shown after a session name.
+fn watch_badge(status: WatchStatus) -> Span<'static> {
+    match status {
+        WatchStatus::Running => Span::styled(" \u{2026}", Style::default().fg(colors::OVERLAY)),
+        WatchStatus::Passed => Span::styled(" \u{2713}", Style::default().fg(colors::GREEN)),
+        WatchStatus::Failed(_) => Span::styled(" \u{2717}", Style::default().fg(colors::RED)),
+    }
+}
+
+/// Checks of the session's pull request: passed, failed or still running.
+fn ci_badge(status: CiStatus) -> Span<'static> {
+    match status {
+        CiStatus::Passed => Span::styled(" \u{2713}", Style::default().fg(colors::GREEN)),
+        CiStatus::Failed => Span::styled(" \u{2717}", Style::default().fg(colors::RED)),
+        CiStatus::Pending => Span::styled(" \u{25cf}", Style::default().fg(colors::PEACH)),
+    }
+}
+
+/// Dim note of a long command in one of the window's other panes.
+fn helper_badge(label: &str) -> Span<'static> {
+    Span::styled(
+        format!(" \u{2699} {label}"),
+        Style::default().fg(colors::SURFACE),
+    )
+}
+
 const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
 fn status_text(state: WindowState) -> &'static str {
@@ -159,6 +1047,7 @@ fn status_text(state: WindowState) -> &'static str {
         WindowState::Idle => "your turn",
         WindowState::Done => "",
         WindowState::Fresh => "",
+        WindowState::Queued => "queued",
     }
 }
 
@@ -177,3 +1066,134 @@ fn status_span(state: WindowState, tick: u64) -> Span<'static> {
         ),
     }
 }
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sidebar::golden::{Fixture, assert_golden, snapshot};
+    use crate::sidebar::groups;
+
+    #[test]
+    fn test_golden_session_states() {
+        let mut f = Fixture::new(&["api", "web", "docs", "infra", "notes"]);
+        f.states = HashMap::from([
+            (1, WindowState::Working),
+            (2, WindowState::Asking),
+            (3, WindowState::Idle),
+            (4, WindowState::Done),
+            (5, WindowState::Queued),
+        ]);
+        f.running_tools = HashMap::from([(1, "Bash".to_string())]);
+        f.asks = HashMap::from([(2, 2)]);
+        f.pinned = HashSet::from(["api".to_string()]);
+        f.ci = HashMap::from([
+            ("api".to_string(), CiStatus::Passed),
+            ("web".to_string(), CiStatus::Failed),
+        ]);
+        f.helpers = HashMap::from([(3, "cargo 2m".to_string())]);
+        f.clocked = Some("docs".to_string());
+        f.watch = HashMap::from([(4, WatchStatus::Failed(1))]);
+        f.tip = Some("p pins a session to the top".to_string());
+        assert_golden("session_states", &snapshot(f.widget(), 56, 12));
+    }
+
+    #[test]
+    fn test_golden_narrow_with_long_names() {
+        let mut f = Fixture::new(&["payments/refund-webhook-retries", "api", "web"]);
+        f.states = HashMap::from([(1, WindowState::Idle), (2, WindowState::Working)]);
+        f.crashed = HashMap::from([(1, "%1".to_string())]);
+        f.broken = HashSet::from([2]);
+        f.stuck = HashMap::from([(3, "no tool calls".to_string())]);
+        f.merged = HashSet::from(["web".to_string()]);
+        f.flashing = HashMap::from([(3, 10)]);
+        f.selected = 0;
+        assert_golden("narrow_long_names", &snapshot(f.widget(), 30, 8));
+    }
+
+    #[test]
+    fn test_golden_groups_sections_and_row_format() {
+        let mut f = Fixture::new(&["api", "web", "worker", "scratch"]);
+        f.windows[3].managed = false;
+        let repos = HashMap::from([
+            (1, "backend".to_string()),
+            (2, "frontend".to_string()),
+            (3, "backend".to_string()),
+        ]);
+        f.rows = groups::layout(
+            &f.windows,
+            Some(&repos),
+            &HashSet::from(["frontend".to_string()]),
+        );
+        f.row_format = Some(format::parse("{icon} {name} {branch} {age}"));
+        f.states = HashMap::from([(1, WindowState::Idle), (3, WindowState::Fresh)]);
+        f.branches = HashMap::from([(1, "main".to_string())]);
+        f.ages = HashMap::from([(1, 90), (3, 4000)]);
+        f.sections = vec![Section {
+            title: "pods".to_string(),
+            lines: vec!["api   Running".to_string(), "web   Pending".to_string()],
+            stale: true,
+        }];
+        f.notice = Some("hooks need updating: press H".to_string());
+        f.selected = 1;
+        assert_golden("groups_sections", &snapshot(f.widget(), 48, 16));
+    }
+
+    #[test]
+    fn test_golden_wide_character_names() {
+        let mut f = Fixture::new(&[
+            "修正-ログイン",
+            "🚀launch",
+            "api",
+            "とても長いセッションの名前です",
+        ]);
+        f.states = HashMap::from([
+            (1, WindowState::Idle),
+            (2, WindowState::Idle),
+            (3, WindowState::Idle),
+            (4, WindowState::Idle),
+        ]);
+        f.selected = 1;
+        assert_golden("wide_characters", &snapshot(f.widget(), 44, 8));
+    }
+
+    #[test]
+    fn test_golden_failed_to_start() {
+        let mut f = Fixture::new(&["api", "web"]);
+        f.failed = HashMap::from([(
+            1,
+            vec![
+                "Invalid API key \u{b7} Please run /login".to_string(),
+                "".to_string(),
+                "Exited with status 0".to_string(),
+            ],
+        )]);
+        f.selected = 0;
+        assert_golden("failed_to_start", &snapshot(f.widget(), 48, 12));
+    }
+
+    #[test]
+    fn test_golden_slot_numbers() {
+        let mut f = Fixture::new(&["api", "web", "docs"]);
+        f.slots = HashMap::from([("api".to_string(), 2), ("docs".to_string(), 1)]);
+        assert_golden("slot_numbers", &snapshot(f.widget(), 44, 7));
+    }
+
+    #[test]
+    fn test_golden_profile_line() {
+        let mut f = Fixture::new(&["api", "payments"]);
+        f.profile = Some("opus \u{b7} plan \u{b7} Read, Grep".to_string());
+        f.selected = 1;
+        assert_golden("profile_line", &snapshot(f.widget(), 48, 10));
+    }
+
+    #[test]
+    fn test_golden_rename_box() {
+        let mut f = Fixture::new(&["api", "web"]);
+        f.selected = 1;
+        f.renaming = Some((2, "web/login".to_string()));
+        f.notice = Some("Session 'web/login' already exists.".to_string());
+        assert_golden("rename_box", &snapshot(f.widget(), 56, 8));
+    }
+}
@@ -0,0 +1,513 @@
+// ── Sidebar background workers ──
+//
+// The sidebar's main thread only reads keys, applies updates and draws.
+// Anything that can stall runs on one of four worker threads, each owning
+// its own state and talking to the main loop over channels:
+//
+//   tmux    lists windows (in pin order) and Claude panes every
+//           `sidebar.refresh_ms`, or right away when nudged after an action
+//           that changed them
+//   events  turns each tmux snapshot into session states from the hook event
+//           files, plus running tools, `cove watch-run` results and long
+//           commands in helper panes, and keeps ~/.cove/state.json (see
+//           feed.rs) up to date
+//   enrich  git branches, token counts, merged-branch checks, stuck
+//           detection, PR checks and the orphan scan, on request
+//   sections  the commands behind `sidebar.sections`, each on its own
+//           interval
+//
+// A nudge to a stuck session waits for Claude's prompt before typing, which
+// can take seconds, so each runs on a short-lived thread of its own.
+//
+// All results arrive as `Update`s on one receiver. A worker exits as soon as
+// the sidebar drops its end of the channels.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::ci::{self, CiStatus};
+use crate::config::{CleanupConfig, SectionConfig};
+use crate::feed::{self, FeedSession};
+use crate::health::{self, Activity};
+use crate::sidebar::panes::HelperTracker;
+use crate::sidebar::sections::{Section, SectionRunner};
+use crate::sidebar::state::{self, StateDetector, WindowState};
+use crate::sidebar::{groups, tips};
+use crate::tmux::{self, PaneInfo, Tmux, TmuxClient, WindowInfo};
+use crate::tokens::TokenCounter;
+use crate::watch::{self, WatchStatus};
+use crate::{attention, cleanup, cmdlog, crash, git, meta, orphans, send, tools, transcript};
+
+// ── Types ──
+
+pub enum Update {
+    Windows(Box<WindowList>),
+    States(Box<Detection>),
+    Branches(HashMap<u32, String>),
+    Tokens(HashMap<u32, u64>),
+    /// Names of sessions whose branch has been merged.
+    Merged(HashSet<String>),
+    /// Why each possibly stuck session looks stuck, by window index.
+    Stuck(HashMap<u32, String>),
+    /// CI status of each session's pull request, by session name.
+    Ci(HashMap<String, CiStatus>),
+    /// Custom sections, in config order.
+    Sections(Vec<Section>),
+    /// Whether the nudge to the window at this index was delivered.
+    Nudged(u32, Result<(), String>),
+}
+
+/// The window list, pinned sessions first and unmanaged windows last, with
+/// what the tmux worker read alongside it.
+pub struct WindowList {
+    pub windows: Vec<WindowInfo>,
+    pub pinned: HashSet<String>,
+    /// Session you're clocked in to supervise.
+    pub clocked: Option<String>,
+    /// Quick-switch slot of each session, by name.
+    pub slots: HashMap<String, u8>,
+    /// Tips hidden with `?` in any sidebar.
+    pub tips_hidden: bool,
+    /// Indexes of windows whose Claude pane was closed.
+    pub broken: HashSet<u32>,
+    /// Claude panes left dead by a crash, by window index.
+    pub crashed: HashMap<u32, String>,
+    /// Windows with a pane that failed to start, and the end of its output.
+    pub failed: HashMap<u32, Vec<String>>,
+    /// Indexes of windows whose Claude was parked.
+    pub parked: HashSet<u32>,
+    /// Repository groups collapsed in any sidebar.
+    pub collapsed: HashSet<String>,
+    /// Repository each managed session is grouped under, by window index.
+    pub repos: HashMap<u32, String>,
+}
+
+/// Everything the events worker learned from one tmux snapshot, by window index.
+pub struct Detection {
+    pub states: HashMap<u32, WindowState>,
+    pub pane_ids: HashMap<u32, String>,
+    /// Unix time of each window's latest hook event.
+    pub event_ts: HashMap<u32, u64>,
+    pub asks: HashMap<u32, u32>,
+    pub running_tools: HashMap<u32, String>,
+    pub watch: HashMap<u32, WatchStatus>,
+    /// Long command running in a helper pane, e.g. "cargo 2m".
+    pub helpers: HashMap<u32, String>,
+}
+
+/// What the enrich worker should look up for the current windows.
+pub struct EnrichRequest {
+    pub windows: Vec<WindowInfo>,
+    pub pane_ids: HashMap<u32, String>,
+    pub branches: bool,
+    pub tokens: bool,
+    /// Run the merged-branch check with this config.
+    pub merged: Option<CleanupConfig>,
+    /// Check Working windows (index → latest event time) for signs of being
+    /// stuck, with this many seconds counting as too long.
+    pub stuck: Option<(HashMap<u32, u64>, u64)>,
+    /// Look up PR checks, asking GitHub if the shared cache is older than
+    /// this many seconds.
+    pub ci: Option<u64>,
+    /// Archive orphaned event files if no sidebar did in this many hours.
+    pub orphans: Option<u64>,
+}
+
+/// One tmux poll (windows and all their panes), handed from the tmux worker
+/// to the events worker.
+type Snapshot = (Vec<WindowInfo>, Vec<PaneInfo>);
+
+/// Handles to the running workers.
+pub struct Workers {
+    refresh: Sender<()>,
+    /// Milliseconds between window lists, read by the tmux worker each round.
+    interval_ms: Arc<AtomicU64>,
+    enrich: Sender<EnrichRequest>,
+    sections: Sender<Vec<SectionConfig>>,
+    /// For threads started on request, like nudges.
+    updates_tx: Sender<Update>,
+    pub updates: Receiver<Update>,
+}
+
+// ── Constants ──
+
+const EVENT_POLL: Duration = Duration::from_millis(100);
+
+/// How often the sections worker looks for due commands.
+const SECTIONS_POLL: Duration = Duration::from_secs(1);
+
+// ── Helpers ──
+
+/// Windows with a pane that kept dying on start, with what it printed last.
+/// A dead pane's output doesn't change, so each is captured once, when it is
+/// first seen failed, and kept in `captured` until it starts again.
+fn failed_windows(
+    panes: &[PaneInfo],
+    captured: &mut HashMap<String, Vec<String>>,
+) -> HashMap<u32, Vec<String>> {
+    let failed = tmux::failed_windows(panes);
+    captured.retain(|pane, _| failed.values().any(|p| p == pane));
+    failed
+        .into_iter()
+        .map(|(index, pane)| {
+            let lines = captured.entry(pane).or_insert_with_key(|pane| {
+                crash::error_lines(&Tmux.capture_pane(pane).unwrap_or_default())
+            });
+            (index, lines.clone())
+        })
+        .collect()
+}
+
+/// Repository a session is grouped under; sessions outside git are grouped
+/// by their directory.
+fn repo_for(win: &WindowInfo) -> String {
+    let dir = Path::new(&win.pane_path);
+    git::repo_name(dir)
+        .or_else(|| Some(dir.file_name()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| win.name.clone())
+}
+
+fn list_windows(panes: &[PaneInfo], captured: &mut HashMap<String, Vec<String>>) -> Option<Update> {
+    let mut windows = tmux::list_windows().ok()?;
+    let mut managed: Vec<&WindowInfo> = windows.iter().filter(|w| w.managed).collect();
+    managed.sort_by_key(|w| w.index);
+    let names: Vec<String> = managed.iter().map(|w| w.name.clone()).collect();
+    let slots = meta::assign_slots(&names);
+    let pinned = meta::pinned();
+    meta::pinned_first(&mut windows, &pinned, |w| &w.name);
+    // Windows opened outside cove go last, in their own section
+    windows.sort_by_key(|w| !w.managed);
+    let repos = windows
+        .iter()
+        .filter(|w| w.managed)
+        .map(|w| (w.index, repo_for(w)))
+        .collect();
+    Some(Update::Windows(Box::new(WindowList {
+        windows,
+        pinned,
+        clocked: attention::clocked_in(),
+        slots,
+        tips_hidden: tips::dismissed(),
+        broken: tmux::broken_windows(panes),
+        crashed: tmux::crashed_windows(panes),
+        failed: failed_windows(panes, captured),
+        parked: tmux::parked_windows(panes),
+        collapsed: groups::collapsed(),
+        repos,
+    })))
+}
+
+fn tmux_worker(
+    refresh: Receiver<()>,
+    interval_ms: Arc<AtomicU64>,
+    updates: Sender<Update>,
+    events: Sender<Snapshot>,
+) {
+    let mut captured = HashMap::new();
+    loop {
+        let panes = Tmux.list_panes().unwrap_or_default();
+        if let Some(update) = list_windows(&panes, &mut captured) {
+            if let Update::Windows(list) = &update {
+                if events.send((list.windows.clone(), panes)).is_err() {
+                    return;
+                }
+            }
+            if updates.send(update).is_err() {
+                return;
+            }
+        }
+        let interval = Duration::from_millis(interval_ms.load(Ordering::Relaxed));
+        match refresh.recv_timeout(interval) {
+            Ok(()) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+fn detect(
+    detector: &mut StateDetector,
+    helpers: &mut HelperTracker,
+    windows: &[WindowInfo],
+    all_panes: &[PaneInfo],
+) -> Detection {
+    let panes: Vec<PaneInfo> = all_panes
+        .iter()
+        .filter(|p| p.is_claude())
+        .cloned()
+        .collect();
+    let states = detector.detect_with(&panes, windows);
+    let pane_ids: HashMap<u32, String> = panes
+        .iter()
+        .map(|p| (p.window_index, p.pane_id.clone()))
+        .collect();
+    let by_window = |index: u32| pane_ids.get(&index).map(String::as_str);
+
+    let running = tools::running();
+    let running_tools = windows
+        .iter()
+        .filter(|w| states.get(&w.index) == Some(&WindowState::Working))
+        .filter_map(|w| Some((w.index, running.get(by_window(w.index)?)?.label())))
+        .collect();
+    let results = watch::results();
+    let watch = windows
+        .iter()
+        .filter_map(|w| Some((w.index, *results.get(by_window(w.index)?)?)))
+        .collect();
+    let event_ts = windows
+        .iter()
+        .filter_map(|w| Some((w.index, detector.last_event_ts(w.index)?)))
+        .collect();
+    let asks = windows
+        .iter()
+        .filter_map(|w| Some((w.index, detector.pending_asks(w.index)?)))
+        .collect();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    Detection {
+        states,
+        pane_ids,
+        event_ts,
+        asks,
+        running_tools,
+        watch,
+        helpers: helpers.update(all_panes, now),
+    }
+}
+
+fn events_worker(snapshots: Receiver<Snapshot>, updates: Sender<Update>) {
+    let mut detector = StateDetector::new();
+    let mut helpers = HelperTracker::new();
+    let mut latest: Option<Snapshot> = None;
+    let mut fed: Option<Vec<FeedSession>> = None;
+    loop {
+        match snapshots.recv_timeout(EVENT_POLL) {
+            Ok(snapshot) => latest = Some(snapshot),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        // Only the newest snapshot matters
+        if let Some(snapshot) = snapshots.try_iter().last() {
+            latest = Some(snapshot);
+        }
+        // Event files change between tmux snapshots, so re-read them every poll
+        if let Some((windows, panes)) = &latest {
+            let detection = detect(&mut detector, &mut helpers, windows, panes);
+            let sessions = feed::sessions(
+                windows,
+                &detection.states,
+                &detection.pane_ids,
+                &detection.event_ts,
+                &detection.asks,
+            );
+            if fed.as_ref() != Some(&sessions) {
+                if let Err(e) = feed::write(&sessions) {
+                    log::warn!("state feed: {e}");
+                }
+                fed = Some(sessions);
+            }
+            if updates.send(Update::States(Box::new(detection))).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Working windows that look stuck, with the reasons.
+fn stuck_sessions(
+    working: &HashMap<u32, u64>,
+    pane_ids: &HashMap<u32, String>,
+    after: u64,
+) -> HashMap<u32, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let running = tools::running();
+    let last_tool = tools::last_activity();
+    let mtime = |session: &str| {
+        let modified = fs::metadata(transcript::path(session)?)
+            .ok()?
+            .modified()
+            .ok()?;
+        Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+    };
+    working
+        .iter()
+        .filter_map(|(index, last_event)| {
+            let pane = pane_ids.get(index)?;
+            let commands = cmdlog::for_pane(pane);
+            let activity = Activity {
+                now,
+                last_event: *last_event,
+                last_tool: last_tool.get(pane).copied(),
+                running: running.get(pane),
+                commands: &commands,
+                transcript_mtime: state::session_for_pane(pane).and_then(|s| mtime(&s)),
+            };
+            let signals = health::signals(&activity, after);
+            (health::score(&signals) >= health::STUCK_SCORE)
+                .then(|| (*index, health::summary(&signals)))
+        })
+        .collect()
+}
+
+fn enrich_worker(requests: Receiver<EnrichRequest>, updates: Sender<Update>) {
+    let mut counter = TokenCounter::new();
+    while let Ok(mut request) = requests.recv() {
+        // Fold queued requests into the newest, keeping every lookup asked for
+        for next in requests.try_iter() {
+            request = EnrichRequest {
+                branches: request.branches || next.branches,
+                tokens: request.tokens || next.tokens,
+                merged: next.merged.or(request.merged),
+                stuck: next.stuck.or(request.stuck),
+                ci: next.ci.or(request.ci),
+                orphans: next.orphans.or(request.orphans),
+                ..next
+            };
+        }
+        let managed = || request.windows.iter().filter(|w| w.managed);
+
+        let mut results = Vec::new();
+        if request.branches {
+            let branches = managed()
+                .filter_map(|w| Some((w.index, git::branch(Path::new(&w.pane_path))?)))
+                .collect();
+            results.push(Update::Branches(branches));
+        }
+        if request.tokens {
+            let tokens = managed()
+                .filter_map(|w| {
+                    let session = state::session_for_pane(request.pane_ids.get(&w.index)?)?;
+                    Some((w.index, counter.count(&session)?))
+                })
+                .collect();
+            results.push(Update::Tokens(tokens));
+        }
+        let sessions = || -> Vec<(String, String)> {
+            managed()
+                .map(|w| (w.name.clone(), w.pane_path.clone()))
+                .collect()
+        };
+        if let Some(config) = &request.merged {
+            results.push(Update::Merged(cleanup::merged_sessions(
+                &sessions(),
+                config,
+            )));
+        }
+        if let Some(interval) = request.ci {
+            results.push(Update::Ci(ci::statuses(&sessions(), interval)));
+        }
+        if let Some((working, after)) = &request.stuck {
+            results.push(Update::Stuck(stuck_sessions(
+                working,
+                &request.pane_ids,
+                *after,
+            )));
+        }
+        if let Some(hours) = request.orphans {
+            orphans::scan_if_due(&Tmux, hours);
+        }
+        for update in results {
+            if updates.send(update).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn sections_worker(configs: Receiver<Vec<SectionConfig>>, updates: Sender<Update>) {
+    let mut runner = SectionRunner::new(Vec::new());
+    loop {
+        // A changed config is shown even before its commands run
+        let reconfigured = match configs.recv_timeout(SECTIONS_POLL) {
+            Ok(next) => {
+                runner.configure(next);
+                true
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        if (runner.run_due() || reconfigured)
+            && updates
+                .send(Update::Sections(runner.sections().to_vec()))
+                .is_err()
+        {
+            return;
+        }
+    }
+}
+
+// ── Public API ──
+
+impl Workers {
+    pub fn spawn(interval: Duration, sections: Vec<SectionConfig>) -> Self {
+        let (updates_tx, updates) = mpsc::channel();
+        let (refresh, refresh_rx) = mpsc::channel();
+        let (snapshots_tx, snapshots_rx) = mpsc::channel();
+        let (enrich, enrich_rx) = mpsc::channel();
+        let (sections_tx, sections_rx) = mpsc::channel();
+        let _ = sections_tx.send(sections);
+
+        let interval_ms = Arc::new(AtomicU64::new(interval.as_millis() as u64));
+        let tx = updates_tx.clone();
+        let ms = Arc::clone(&interval_ms);
+        thread::spawn(move || tmux_worker(refresh_rx, ms, tx, snapshots_tx));
+        let tx = updates_tx.clone();
+        thread::spawn(move || events_worker(snapshots_rx, tx));
+        let tx = updates_tx.clone();
+        thread::spawn(move || enrich_worker(enrich_rx, tx));
+        let tx = updates_tx.clone();
+        thread::spawn(move || sections_worker(sections_rx, tx));
+
+        Self {
+            refresh,
+            interval_ms,
+            enrich,
+            sections: sections_tx,
+            updates_tx,
+            updates,
+        }
+    }
+
+    /// List windows again now, e.g. after pinning or adopting one.
+    pub fn refresh(&self) {
+        let _ = self.refresh.send(());
+    }
+
+    /// Change how often windows are listed, from the next round on.
+    pub fn set_interval(&self, interval: Duration) {
+        self.interval_ms
+            .store(interval.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Replace the custom sections, e.g. after the config changed.
+    pub fn set_sections(&self, sections: Vec<SectionConfig>) {
+        let _ = self.sections.send(sections);
+    }
+
+    /// Queue lookups; requests not started yet are merged into the newest.
+    pub fn enrich(&self, request: EnrichRequest) {
+        let _ = self.enrich.send(request);
+    }
+
+    /// Nudge the stuck session in window `index` once its prompt shows,
+    /// reporting back with `Update::Nudged`.
+    pub fn nudge(&self, index: u32, pane_id: String) {
+        let tx = self.updates_tx.clone();
+        thread::spawn(move || {
+            let sent = send::deliver(&Tmux, &pane_id, health::NUDGE);
+            let _ = tx.send(Update::Nudged(index, sent));
+        });
+    }
+}
@@ -0,0 +1,363 @@
+// ── Working-tree snapshots ──
+//
+// A snapshot is a commit of a session's whole working tree: tracked files as
+// they are on disk, plus untracked ones that aren't ignored. One is taken
+// when a session starts and after each of Claude's turns (the Stop hook),
+// unless nothing changed since the last. The tree is built through a copy of
+// the index, so the repository's own index, HEAD and branches never move,
+// and each commit is kept on its own ref, `refs/cove/snapshots/<name>/<ms>`,
+// out of the way of branches and tags. Only the newest `snapshots.keep`
+// refs stay; `git gc` drops the rest in time. `cove rename` moves the refs
+// to the new name.
+//
+// `cove rollback` (commands/rollback.rs) puts one back. It applies the diff
+// from the current tree to the snapshot's to the working tree, leaving the
+// index alone, so `git status` and `git diff` afterwards show what the
+// rollback changed.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::SnapshotConfig;
+use crate::{git, names};
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub commit: String,
+    pub tree: String,
+    /// Unix time it was taken, in milliseconds (its ref's name).
+    pub ms: u64,
+    /// What it follows: "session start", or the prompt of the turn before it.
+    pub label: String,
+}
+
+// ── Constants ──
+
+const REF_PREFIX: &str = "refs/cove/snapshots";
+
+/// Snapshot commits carry this identity, so no git user config is needed.
+const IDENTITY: &[(&str, &str)] = &[
+    ("GIT_AUTHOR_NAME", "cove"),
+    ("GIT_AUTHOR_EMAIL", "cove@localhost"),
+    ("GIT_COMMITTER_NAME", "cove"),
+    ("GIT_COMMITTER_EMAIL", "cove@localhost"),
+];
+
+/// Characters of a prompt kept in a label.
+const MAX_LABEL: usize = 72;
+
+// ── Helpers ──
+
+/// Ref directory of session `name`: a `/` in the name would nest it under
+/// another session's.
+fn refs_of(name: &str) -> String {
+    format!("{REF_PREFIX}/{}", names::file_stem(name))
+}
+
+/// Run git in `dir` with `env` set, feeding it `input`.
+fn git_with(
+    dir: &Path,
+    env: &[(&str, &str)],
+    args: &[&str],
+    input: Option<&[u8]>,
+) -> Result<String, String> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .envs(env.iter().copied())
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("git: {e}"))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin
+            .write_all(input)
+            .map_err(|e| format!("git {}: {e}", args.join(" ")))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("git: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {}: {}", args.join(" "), stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// A scratch copy of the repository's index, removed on drop. Starting from
+/// the real index lets `git add` skip files whose stat info hasn't changed;
+/// the copy keeps the index's mtime, which git needs to tell a file changed
+/// in the same second the index was written from one that wasn't.
+struct ScratchIndex(PathBuf);
+
+impl ScratchIndex {
+    fn new(dir: &Path) -> Result<ScratchIndex, String> {
+        let index = git::run(dir, &["rev-parse", "--git-path", "index"])?;
+        let index = dir.join(index.trim());
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos();
+        let scratch =
+            std::env::temp_dir().join(format!("cove-index-{}-{nanos}", std::process::id()));
+        if let Ok(modified) = std::fs::metadata(&index).and_then(|m| m.modified()) {
+            std::fs::copy(&index, &scratch)
+                .and_then(|_| std::fs::File::options().write(true).open(&scratch))
+                .and_then(|f| f.set_modified(modified))
+                .map_err(|e| format!("copy index: {e}"))?;
+        }
+        Ok(ScratchIndex(scratch))
+    }
+}
+
+impl Drop for ScratchIndex {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// The first line of `prompt`, shortened for a label.
+fn label_for(prompt: &str) -> String {
+    let line = prompt
+        .lines()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or_default()
+        .trim();
+    match line.char_indices().nth(MAX_LABEL) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line.to_string(),
+    }
+}
+
+/// Snapshots of `name` in the repository at `dir`, newest first.
+fn list_in(dir: &Path, name: &str) -> Vec<Snapshot> {
+    let format = "%(objectname) %(tree) %(refname:lstrip=-1) %(contents:subject)";
+    let out = git::output(
+        dir,
+        &[
+            "for-each-ref",
+            &format!("--format={format}"),
+            &format!("{}/", refs_of(name)),
+        ],
+        &[0],
+    )
+    .unwrap_or_default();
+    let mut snapshots: Vec<Snapshot> = out
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, ' ');
+            Some(Snapshot {
+                commit: parts.next()?.to_string(),
+                tree: parts.next()?.to_string(),
+                ms: parts.next()?.parse().ok()?,
+                label: parts.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.ms));
+    snapshots
+}
+
+// ── Public API ──
+
+/// The tree object of the working tree at `dir` as it is now.
+pub fn worktree_tree(dir: &Path) -> Result<String, String> {
+    let index = ScratchIndex::new(dir)?;
+    let index_path = index.0.to_string_lossy().to_string();
+    let env = [("GIT_INDEX_FILE", index_path.as_str())];
+    git_with(dir, &env, &["add", "-A"], None)?;
+    Ok(git_with(dir, &env, &["write-tree"], None)?
+        .trim()
+        .to_string())
+}
+
+/// Snapshot the working tree at `dir` for session `name`, unless it isn't a
+/// repository or matches the last snapshot. `label` says what it follows.
+pub fn take(
+    config: &SnapshotConfig,
+    dir: &Path,
+    name: &str,
+    label: &str,
+) -> Result<Option<Snapshot>, String> {
+    if !config.enabled || git::toplevel(dir).is_err() {
+        return Ok(None);
+    }
+    let tree = worktree_tree(dir)?;
+    let existing = list_in(dir, name);
+    if existing.first().is_some_and(|s| s.tree == tree) {
+        return Ok(None);
+    }
+
+    let label = label_for(label);
+    let commit = git_with(dir, IDENTITY, &["commit-tree", &tree, "-m", &label], None)?
+        .trim()
+        .to_string();
+    let ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let refs = refs_of(name);
+    git::run(dir, &["update-ref", &format!("{refs}/{ms}"), &commit])?;
+    for old in existing.iter().skip(config.keep.saturating_sub(1)) {
+        let _ = git::run(dir, &["update-ref", "-d", &format!("{refs}/{}", old.ms)]);
+    }
+    Ok(Some(Snapshot {
+        commit,
+        tree,
+        ms,
+        label,
+    }))
+}
+
+/// Snapshot the session after a turn, labeled with the turn's prompt.
+pub fn after_turn(config: &SnapshotConfig, dir: &Path, name: &str, prompt: Option<&str>) {
+    let label = match prompt {
+        Some(prompt) => format!("after: {prompt}"),
+        None => "after a turn".to_string(),
+    };
+    if let Err(e) = take(config, dir, name, &label) {
+        log::warn!("snapshot of {name}: {e}");
+    }
+}
+
+/// Snapshots of session `name` in the repository at `dir`, newest first.
+pub fn list(dir: &Path, name: &str) -> Vec<Snapshot> {
+    list_in(dir, name)
+}
+
+/// Move session `old`'s snapshots in the repository at `dir` to `new`.
+pub fn rename(dir: &Path, old: &str, new: &str) -> Result<(), String> {
+    let (from, to) = (refs_of(old), refs_of(new));
+    for snapshot in list_in(dir, old) {
+        git::run(
+            dir,
+            &[
+                "update-ref",
+                &format!("{to}/{}", snapshot.ms),
+                &snapshot.commit,
+            ],
+        )?;
+        git::run(
+            dir,
+            &["update-ref", "-d", &format!("{from}/{}", snapshot.ms)],
+        )?;
+    }
+    Ok(())
+}
+
+/// `git diff` from tree `from` to tree `to`, with `args` (e.g. "--stat").
+pub fn diff(dir: &Path, from: &str, to: &str, args: &[&str]) -> Result<String, String> {
+    let mut all = vec!["diff"];
+    all.extend_from_slice(args);
+    all.extend_from_slice(&[from, to]);
+    git::run(dir, &all)
+}
+
+/// Make the working tree at `dir`, now tree `from`, match tree `to`.
+pub fn restore(dir: &Path, from: &str, to: &str) -> Result<(), String> {
+    let patch = diff(dir, from, to, &["--binary", "--no-color"])?;
+    if patch.is_empty() {
+        return Ok(());
+    }
+    // Patch paths are relative to the top of the repository
+    let top = git::toplevel(dir)?;
+    git_with(
+        Path::new(&top),
+        &[],
+        &["apply", "-"],
+        Some(patch.as_bytes()),
+    )
+    .map(|_| ())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        git::run(dir.path(), &["init", "-q"]).unwrap();
+        fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        git_with(dir.path(), IDENTITY, &["add", "a.txt"], None).unwrap();
+        git_with(dir.path(), IDENTITY, &["commit", "-qm", "init"], None).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_take_skips_unchanged_trees_and_prunes() {
+        let dir = repo();
+        let config = SnapshotConfig {
+            enabled: true,
+            keep: 2,
+        };
+        let first = take(&config, dir.path(), "api/fix", "session start").unwrap();
+        assert!(first.is_some());
+        assert_eq!(take(&config, dir.path(), "api/fix", "again").unwrap(), None);
+
+        fs::write(dir.path().join("new.txt"), "untracked\n").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = take(&config, dir.path(), "api/fix", "after: add a file").unwrap();
+        assert!(second.is_some());
+        let snapshots = list(dir.path(), "api/fix");
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].label, "after: add a file");
+        assert!(list(dir.path(), "api").is_empty());
+
+        // The repository's own index didn't pick up the untracked file
+        let status = git::run(dir.path(), &["status", "--porcelain"]).unwrap();
+        assert_eq!(status, "?? new.txt\n");
+    }
+
+    #[test]
+    fn test_rename_moves_refs() {
+        let dir = repo();
+        let config = SnapshotConfig {
+            enabled: true,
+            keep: 5,
+        };
+        let taken = take(&config, dir.path(), "api", "session start")
+            .unwrap()
+            .unwrap();
+
+        rename(dir.path(), "api", "api/v2").unwrap();
+        assert!(list(dir.path(), "api").is_empty());
+        assert_eq!(list(dir.path(), "api/v2"), [taken]);
+        // Not a repository: nothing to move
+        let plain = tempfile::tempdir().unwrap();
+        assert_eq!(rename(plain.path(), "api", "web"), Ok(()));
+    }
+
+    #[test]
+    fn test_restore_brings_back_the_tree() {
+        let dir = repo();
+        let before = worktree_tree(dir.path()).unwrap();
+        // Same size, and likely the same second the index was written
+        fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        fs::write(dir.path().join("b.txt"), "added\n").unwrap();
+        let after = worktree_tree(dir.path()).unwrap();
+
+        let stat = diff(dir.path(), &after, &before, &["--stat"]).unwrap();
+        assert!(
+            stat.contains("a.txt") && stat.contains("2 files changed"),
+            "{stat}"
+        );
+        restore(dir.path(), &after, &before).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n"
+        );
+        assert!(!dir.path().join("b.txt").exists());
+        assert_eq!(worktree_tree(dir.path()).unwrap(), before);
+    }
+}
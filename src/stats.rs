@@ -0,0 +1,99 @@
+// ── Lifetime counters ──
+//
+// Event files come and go with their sessions, so `cove stats` keeps its own
+// record: ~/.cove/stats.jsonl gets a line for every session created, prompt
+// sent, finished Working stretch (with its length) and session killed.
+// Appending a line is safe from concurrent hooks where rewriting a shared
+// counter file wouldn't be, and the timestamps let the totals be split by
+// week and milestones dated. The file grows by a few dozen bytes a prompt.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+// ── Types ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Created,
+    Prompt,
+    /// A finished Working stretch; `secs` is its length.
+    Working,
+    Killed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub kind: Kind,
+    pub ts: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub secs: u64,
+}
+
+// ── Constants ──
+
+const LOG_FILE: &str = "stats.jsonl";
+
+// ── Helpers ──
+
+fn is_zero(secs: &u64) -> bool {
+    *secs == 0
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+// ── Public API ──
+
+/// Where the counters are kept.
+pub fn log() -> Option<PathBuf> {
+    paths::cove_path(LOG_FILE)
+}
+
+/// Append one `kind` event (lasting `secs`, for Working) to the log at `path`.
+pub fn record_in(path: &Path, kind: Kind, secs: u64) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("create {}: {e}", dir.display()))?;
+    }
+    let entry = Entry {
+        kind,
+        ts: now(),
+        secs,
+    };
+    let line = serde_json::to_string(&entry).map_err(|e| format!("encode stats: {e}"))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("open {}: {e}", path.display()))?;
+    writeln!(file, "{line}").map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+/// Count one `kind` event. Best-effort: a lost line beats failing the
+/// command that did the work.
+pub fn record(kind: Kind, secs: u64) {
+    if let Some(path) = log() {
+        let _ = record_in(&path, kind, secs);
+    }
+}
+
+/// Every entry in the log at `path`, oldest first.
+pub fn read_in(path: &Path) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    // Concurrent appends can land slightly out of order
+    entries.sort_by_key(|e| e.ts);
+    entries
+}
@@ -0,0 +1,312 @@
+// ── kitty / wezterm tab backends ──
+//
+// Lightweight alternative to tmux: each session is a terminal tab running
+// Claude directly (no sidebar or helper pane). Tabs are driven through kitty
+// remote control (`kitty @`) or the wezterm CLI (`wezterm cli`).
+//
+// State detection stays hook-based. Instead of `$TMUX_PANE`, the tab is
+// launched with `$COVE_PANE` set to a cove-generated token, which the hook
+// handler records as the event's `pane_id`. A small registry in
+// ~/.cove/tabs.json maps tokens to session names and backend pane ids.
+//
+// The agent command is a shell command line (profile flags, a multi-word
+// `agent.command`), while kitty and wezterm exec their last arguments
+// directly, so it's launched through `sh -c`.
+
+use std::fs;
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cli::Backend;
+use crate::paths;
+
+// ── Types ──
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TabSession {
+    pub backend: Backend,
+    pub name: String,
+    pub dir: String,
+    /// Value of `$COVE_PANE` inside the tab — matches event `pane_id`.
+    pub token: String,
+    /// Backend-native id: kitty window id or wezterm pane id.
+    pub pane: u64,
+}
+
+// ── Helpers ──
+
+fn load_registry() -> Vec<TabSession> {
+    paths::cove_path("tabs.json")
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_registry(tabs: &[TabSession]) -> Result<(), String> {
+    let path = paths::ensure_cove_dir("")?.join("tabs.json");
+    let content =
+        serde_json::to_string_pretty(tabs).map_err(|e| format!("serialize registry: {e}"))?;
+    fs::write(&path, content).map_err(|e| format!("write registry: {e}"))
+}
+
+fn run_stdout(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{program}: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{program}: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn new_token() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("cove-{:x}-{:x}", std::process::id(), nanos)
+}
+
+/// Program and arguments that open a tab in `dir` titled `name`, running
+/// `agent_cmd` through `sh -c` with `$COVE_PANE` set to `token`.
+fn launch_args(
+    backend: Backend,
+    name: &str,
+    dir: &str,
+    token: &str,
+    agent_cmd: &str,
+) -> (&'static str, Vec<String>) {
+    let env_arg = format!("COVE_PANE={token}");
+    let args: Vec<&str> = match backend {
+        Backend::Kitty => vec![
+            "@",
+            "launch",
+            "--type=tab",
+            "--tab-title",
+            name,
+            "--cwd",
+            dir,
+            "--env",
+            &env_arg,
+            "sh",
+            "-c",
+            agent_cmd,
+        ],
+        Backend::Wezterm => vec![
+            "cli", "spawn", "--cwd", dir, "--", "env", &env_arg, "sh", "-c", agent_cmd,
+        ],
+    };
+    let program = match backend {
+        Backend::Kitty => "kitty",
+        Backend::Wezterm => "wezterm",
+    };
+    (program, args.into_iter().map(String::from).collect())
+}
+
+/// Collect the ids of all live kitty windows from `kitty @ ls` output.
+fn kitty_window_ids(ls_json: &str) -> Vec<u64> {
+    let parsed: Value = serde_json::from_str(ls_json).unwrap_or(Value::Null);
+    let mut ids = Vec::new();
+    for os_window in parsed.as_array().into_iter().flatten() {
+        for tab in os_window["tabs"].as_array().into_iter().flatten() {
+            for window in tab["windows"].as_array().into_iter().flatten() {
+                if let Some(id) = window["id"].as_u64() {
+                    ids.push(id);
+                }
+            }
+        }
+    }
+    ids
+}
+
+/// Collect the ids of all live wezterm panes from `wezterm cli list --format json` output.
+fn wezterm_pane_ids(list_json: &str) -> Vec<u64> {
+    let parsed: Value = serde_json::from_str(list_json).unwrap_or(Value::Null);
+    parsed
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p["pane_id"].as_u64())
+        .collect()
+}
+
+fn live_panes(backend: Backend) -> Option<Vec<u64>> {
+    match backend {
+        Backend::Kitty => run_stdout("kitty", &["@", "ls"])
+            .ok()
+            .map(|out| kitty_window_ids(&out)),
+        Backend::Wezterm => run_stdout("wezterm", &["cli", "list", "--format", "json"])
+            .ok()
+            .map(|out| wezterm_pane_ids(&out)),
+    }
+}
+
+// ── Public API ──
+
+/// Open a new tab running `agent_cmd` in `dir`, registered under `name`.
+pub fn spawn(backend: Backend, name: &str, dir: &str, agent_cmd: &str) -> Result<(), String> {
+    let mut tabs = sessions();
+    if tabs.iter().any(|t| t.name == name) {
+        return Err(format!("tab session '{name}' already exists"));
+    }
+
+    let token = new_token();
+    let (program, args) = launch_args(backend, name, dir, &token, agent_cmd);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let out = run_stdout(program, &args)?;
+
+    let pane: u64 = out
+        .trim()
+        .parse()
+        .map_err(|_| format!("unexpected output from {backend}: {}", out.trim()))?;
+
+    if backend == Backend::Wezterm {
+        let pane_arg = pane.to_string();
+        run_stdout(
+            "wezterm",
+            &["cli", "set-tab-title", "--pane-id", &pane_arg, name],
+        )?;
+    }
+
+    tabs.push(TabSession {
+        backend,
+        name: name.to_string(),
+        dir: dir.to_string(),
+        token,
+        pane,
+    });
+    save_registry(&tabs)
+}
+
+/// All registered tab sessions whose backend pane is still alive.
+/// Dead entries are dropped from the registry as a side effect.
+pub fn sessions() -> Vec<TabSession> {
+    let tabs = load_registry();
+    if tabs.is_empty() {
+        return tabs;
+    }
+
+    let mut kitty = None;
+    let mut wezterm = None;
+    let live: Vec<TabSession> = tabs
+        .iter()
+        .filter(|t| {
+            let panes = match t.backend {
+                Backend::Kitty => kitty.get_or_insert_with(|| live_panes(Backend::Kitty)),
+                Backend::Wezterm => wezterm.get_or_insert_with(|| live_panes(Backend::Wezterm)),
+            };
+            // Backend unreachable (e.g. not running in that terminal) — keep the entry
+            panes.as_ref().is_none_or(|ids| ids.contains(&t.pane))
+        })
+        .cloned()
+        .collect();
+
+    if live.len() != tabs.len() {
+        let _ = save_registry(&live);
+    }
+    live
+}
+
+/// Close a tab session by name. Returns Ok(false) if no such tab is registered.
+pub fn close(name: &str) -> Result<bool, String> {
+    let mut tabs = load_registry();
+    let Some(pos) = tabs.iter().position(|t| t.name == name) else {
+        return Ok(false);
+    };
+    let tab = tabs.remove(pos);
+    let pane_arg = tab.pane.to_string();
+
+    match tab.backend {
+        Backend::Kitty => {
+            let matcher = format!("id:{pane_arg}");
+            run_stdout("kitty", &["@", "close-window", "--match", &matcher])?;
+        }
+        Backend::Wezterm => {
+            run_stdout("wezterm", &["cli", "kill-pane", "--pane-id", &pane_arg])?;
+        }
+    }
+
+    save_registry(&tabs)?;
+    Ok(true)
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kitty_window_ids() {
+        let json = r#"[{"id":1,"tabs":[{"id":1,"windows":[{"id":3},{"id":7}]},{"id":2,"windows":[{"id":9}]}]}]"#;
+        assert_eq!(kitty_window_ids(json), vec![3, 7, 9]);
+    }
+
+    #[test]
+    fn test_wezterm_pane_ids() {
+        let json =
+            r#"[{"window_id":0,"tab_id":0,"pane_id":0},{"window_id":0,"tab_id":1,"pane_id":4}]"#;
+        assert_eq!(wezterm_pane_ids(json), vec![0, 4]);
+    }
+
+    #[test]
+    fn test_launch_runs_profile_flags_through_a_shell() {
+        let cmd = "claude --model 'opus' --allowed-tools 'Read'";
+        let (program, args) = launch_args(Backend::Kitty, "api", "/code/api", "t1", cmd);
+        assert_eq!(program, "kitty");
+        assert_eq!(
+            args,
+            [
+                "@",
+                "launch",
+                "--type=tab",
+                "--tab-title",
+                "api",
+                "--cwd",
+                "/code/api",
+                "--env",
+                "COVE_PANE=t1",
+                "sh",
+                "-c",
+                cmd,
+            ]
+        );
+
+        let (program, args) = launch_args(Backend::Wezterm, "api", "/code/api", "t1", cmd);
+        assert_eq!(program, "wezterm");
+        assert_eq!(
+            args,
+            [
+                "cli",
+                "spawn",
+                "--cwd",
+                "/code/api",
+                "--",
+                "env",
+                "COVE_PANE=t1",
+                "sh",
+                "-c",
+                cmd,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_launch_keeps_a_multi_word_agent_command_whole() {
+        let cmd = "npx claude --dangerously-skip-permissions";
+        for backend in [Backend::Kitty, Backend::Wezterm] {
+            let (_, args) = launch_args(backend, "api", "/code/api", "t1", cmd);
+            assert_eq!(args[args.len() - 3..], ["sh", "-c", cmd]);
+        }
+    }
+
+    #[test]
+    fn test_pane_ids_invalid_json() {
+        assert!(kitty_window_ids("not json").is_empty());
+        assert!(wezterm_pane_ids("").is_empty());
+    }
+}
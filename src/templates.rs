@@ -0,0 +1,147 @@
+// ── Prompt templates ──
+//
+// Reusable instructions for `cove send --template <name>`. A template is
+// ~/.cove/templates/<name>.md, or an entry under `templates:` in the config
+// (the file wins if both exist). `{{var}}` placeholders are filled from
+// `--var key=value`; a placeholder without a value is an error rather than
+// being sent to Claude half-filled.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::{config, paths};
+
+// ── Helpers ──
+
+fn load_from(
+    dir: Option<&Path>,
+    configured: &HashMap<String, String>,
+    name: &str,
+) -> Result<String, String> {
+    if let Some(dir) = dir {
+        let path = dir.join(format!("{name}.md"));
+        match fs::read_to_string(&path) {
+            Ok(content) => return Ok(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("read {}: {e}", path.display())),
+        }
+    }
+    configured
+        .get(name)
+        .cloned()
+        .ok_or_else(|| format!("no template named '{name}'"))
+}
+
+// ── Public API ──
+
+/// Look up a template by name in ~/.cove/templates, then the config.
+pub fn load(name: &str) -> Result<String, String> {
+    let config = config::load()?;
+    load_from(
+        paths::cove_path("templates").as_deref(),
+        &config.templates,
+        name,
+    )
+}
+
+/// Split a `--var key=value` argument.
+pub fn parse_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("invalid --var '{arg}': expected key=value")),
+    }
+}
+
+/// Replace every `{{var}}` (whitespace inside the braces is ignored).
+/// Errors list all placeholders that have no value.
+pub fn render(template: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut missing: Vec<String> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let key = rest[start + 2..start + 2 + len].trim();
+        match vars.get(key) {
+            Some(value) => out.push_str(value),
+            None => {
+                if !missing.iter().any(|m| m == key) {
+                    missing.push(key.to_string());
+                }
+            }
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+
+    if !missing.is_empty() {
+        return Err(format!(
+            "missing template variables: {} (pass --var key=value)",
+            missing.join(", ")
+        ));
+    }
+    Ok(out.trim_end().to_string())
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_substitutes() {
+        let out = render(
+            "Fix the tests in {{file}}, then {{ step }}.\n",
+            &vars(&[("file", "src/lib.rs"), ("step", "run clippy")]),
+        )
+        .unwrap();
+        assert_eq!(out, "Fix the tests in src/lib.rs, then run clippy.");
+    }
+
+    #[test]
+    fn test_render_reports_missing() {
+        let err = render("{{a}} {{b}} {{a}}", &vars(&[])).unwrap_err();
+        assert!(err.contains("a, b"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_var() {
+        assert_eq!(
+            parse_var("file=a=b.rs").unwrap(),
+            ("file".to_string(), "a=b.rs".to_string())
+        );
+        assert!(parse_var("novalue").is_err());
+        assert!(parse_var("=x").is_err());
+    }
+
+    #[test]
+    fn test_file_template_wins_over_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("fix.md"), "from file").unwrap();
+        let configured = vars(&[("fix", "from config"), ("review", "from config")]);
+
+        assert_eq!(
+            load_from(Some(dir.path()), &configured, "fix").unwrap(),
+            "from file"
+        );
+        assert_eq!(
+            load_from(Some(dir.path()), &configured, "review").unwrap(),
+            "from config"
+        );
+        assert!(load_from(None, &configured, "other").is_err());
+    }
+}
@@ -1,81 +1,210 @@
 // ── tmux Command wrappers ──
+//
+// All tmux access goes through `TmuxClient`: `Tmux` runs the real binary, and
+// tests script a `fake::FakeTmux` instead so they run without a tmux server.
+// The free functions at the bottom are shorthands for the real client.
 
-use std::process::Command;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{self, LayoutConfig, TmuxConfig};
+use crate::{meta, names};
+
+pub mod control;
+#[cfg(test)]
+pub mod fake;
 
 // ── Types ──
 
+#[derive(Clone)]
 pub struct WindowInfo {
+    /// Stable tmux window ID (e.g. "@3"); unlike the index it never shifts.
+    pub id: String,
     pub index: u32,
     pub name: String,
     pub is_active: bool,
     pub pane_path: String,
+    /// Has cove's layout (Claude, sidebar, terminal); false for windows
+    /// someone opened in the session by hand.
+    pub managed: bool,
 }
 
-// ── Helpers ──
-
-fn tmux(args: &[&str]) -> std::io::Result<std::process::Output> {
-    Command::new("tmux").args(args).output()
+/// Info about pane .1 in each window (for state detection).
+#[derive(Clone)]
+pub struct PaneInfo {
+    pub window_index: u32,
+    pub pane_index: u32,
+    pub command: String,
+    /// Unique tmux pane identifier (e.g. "%0", "%3").
+    pub pane_id: String,
+    /// `@cove-role` of the pane ("claude", "sidebar", "terminal"); empty for
+    /// panes cove didn't create.
+    pub role: String,
+    /// Stable tmux window ID (e.g. "@3").
+    pub window_id: String,
+    /// Left dead by `PANE_DIED_HOOK` instead of closing.
+    pub dead: bool,
+    /// Marked with `FAILED_OPTION` for dying on start.
+    pub failed: bool,
+    /// Marked with `PARKED_OPTION` by `cove kill --keep-pane`.
+    pub parked: bool,
+    /// `PAIR_OPTION`: the "pane:window" entries a pair view borrowed.
+    pub paired: String,
 }
 
-fn tmux_ok(args: &[&str]) -> bool {
-    tmux(args).is_ok_and(|o| o.status.success())
-}
+impl PaneInfo {
+    /// The pane Claude runs in (always .1).
+    pub fn is_claude(&self) -> bool {
+        self.pane_index == 1
+    }
 
-fn tmux_stdout(args: &[&str]) -> Result<String, String> {
-    let output = tmux(args).map_err(|e| format!("tmux: {e}"))?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("tmux: {}", stderr.trim()));
+    /// A pane other than Claude's and the sidebar: the terminal pane, or
+    /// any the user split off.
+    pub fn is_helper(&self) -> bool {
+        let sidebar = self.role == "sidebar" || (self.role.is_empty() && self.pane_index == 2);
+        !self.is_claude() && !sidebar
     }
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-// ── Public API ──
+/// The real tmux binary.
+pub struct Tmux;
 
-pub const SESSION: &str = "cove";
+/// tmux operations cove needs. Implementors only provide the two primitives;
+/// every higher-level call is built on them. The exception is
+/// `record_options`, which writes cove's metadata and which the fake
+/// overrides so tests leave ~/.cove alone.
+pub trait TmuxClient {
+    /// Run tmux and capture stdout. A non-zero exit becomes `Err(stderr)`.
+    fn output(&self, args: &[&str]) -> Result<String, String>;
 
-pub fn has_session() -> bool {
-    tmux_ok(&["has-session", "-t", SESSION])
-}
+    /// Run tmux on the user's terminal (attach, layout chains), reporting success.
+    fn status(&self, args: &[&str]) -> Result<bool, String>;
 
-pub fn list_windows() -> Result<Vec<WindowInfo>, String> {
-    let out = tmux_stdout(&[
-        "list-windows",
-        "-t",
-        SESSION,
-        "-F",
-        "#{window_index}|#{window_name}|#{window_active}|#{pane_current_path}",
-    ])?;
-
-    let mut windows = Vec::new();
-    for line in out.lines() {
-        let parts: Vec<&str> = line.splitn(4, '|').collect();
-        if parts.len() < 4 {
-            continue;
-        }
-        windows.push(WindowInfo {
-            index: parts[0].parse().unwrap_or(0),
-            name: parts[1].to_string(),
-            is_active: parts[2] == "1",
-            pane_path: parts[3].to_string(),
+    fn has_session(&self) -> bool {
+        self.output(&["has-session", "-t", SESSION]).is_ok()
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, String> {
+        self.session_windows(SESSION)
+    }
+
+    /// Windows of tmux session `session`, which needn't be cove's.
+    fn session_windows(&self, session: &str) -> Result<Vec<WindowInfo>, String> {
+        let out = self.output(&[
+            "list-windows",
+            "-t",
+            session,
+            "-F",
+            "#{window_id}|#{window_index}|#{window_name}|#{window_active}|#{window_panes}|#{@cove-layout}|#{pane_current_path}",
+        ])?;
+
+        let mut windows = Vec::new();
+        for line in out.lines() {
+            let parts: Vec<&str> = line.splitn(7, '|').collect();
+            if parts.len() < 7 {
+                continue;
+            }
+            // Windows from before the layout option was set still have three panes
+            let panes: u32 = parts[4].parse().unwrap_or(0);
+            windows.push(WindowInfo {
+                id: parts[0].to_string(),
+                index: parts[1].parse().unwrap_or(0),
+                name: parts[2].to_string(),
+                is_active: parts[3] == "1",
+                managed: parts[5] == "1" || panes >= 3,
+                pane_path: parts[6].to_string(),
+            });
+        }
+        Ok(windows)
+    }
+
+    /// Target for window `name`: `cove:<name>`, or `cove:<window id>` when the
+    /// name can't be written in a target (e.g. it contains a `.`).
+    fn window_target(&self, name: &str) -> Result<String, String> {
+        if names::is_plain_target(name) {
+            return Ok(format!("{SESSION}:{name}"));
+        }
+        let window = self.list_windows()?.into_iter().find(|w| w.name == name);
+        match window {
+            Some(w) => Ok(format!("{SESSION}:{}", w.id)),
+            None => Err(format!("no session named '{name}'")),
+        }
+    }
+
+    /// Name of the window a command-line target means: a window index ("3"),
+    /// the ID of any pane in the window ("%7"), or else a window name, which
+    /// may leave out a tag only one window has (see `names::qualify`).
+    /// Session names can be neither of the first two, so only windows named
+    /// before that rule can be shadowed by an index.
+    fn resolve_target(&self, target: &str) -> Result<String, String> {
+        let by_pane = target.len() > 1 && target.starts_with('%');
+        let by_index = !target.is_empty() && target.chars().all(|c| c.is_ascii_digit());
+        if !by_pane && !by_index {
+            return match self.list_window_names() {
+                Ok(windows) => names::qualify(target, &windows),
+                Err(_) => Ok(target.to_string()),
+            };
+        }
+        let out = self.output(&[
+            "list-panes",
+            "-s",
+            "-t",
+            SESSION,
+            "-F",
+            "#{pane_id}|#{window_index}|#{window_name}",
+        ])?;
+        let found = out.lines().find_map(|line| {
+            let mut parts = line.splitn(3, '|');
+            let (pane, index, name) = (parts.next()?, parts.next()?, parts.next()?);
+            let hit = if by_pane {
+                pane == target
+            } else {
+                index == target
+            };
+            hit.then(|| name.to_string())
         });
+        found.ok_or_else(|| {
+            if by_pane {
+                format!("No cove window has pane {target}.")
+            } else {
+                format!("No cove window at index {target}.")
+            }
+        })
     }
-    Ok(windows)
-}
 
-/// List window names only (for duplicate checking).
-pub fn list_window_names() -> Result<Vec<String>, String> {
-    let out = tmux_stdout(&["list-windows", "-t", SESSION, "-F", "#{window_name}"])?;
-    Ok(out.lines().map(|s| s.to_string()).collect())
-}
+    /// Names of every tmux session on the server.
+    fn list_sessions(&self) -> Result<Vec<String>, String> {
+        let out = self.output(&["list-sessions", "-F", "#{session_name}"])?;
+        Ok(out.lines().map(|s| s.to_string()).collect())
+    }
 
-pub fn is_inside_tmux() -> bool {
-    std::env::var("TMUX").is_ok_and(|v| !v.is_empty())
-}
+    /// Move window `id` ("@3", unique across sessions) to the first free
+    /// index of tmux session `session`, keeping the current window selected.
+    fn move_window(&self, id: &str, session: &str) -> Result<(), String> {
+        self.output(&["move-window", "-d", "-s", id, "-t", &format!("{session}:")])?;
+        Ok(())
+    }
 
-pub fn new_session(name: &str, dir: &str, sidebar_bin: &str) -> Result<(), String> {
-    let status = Command::new("tmux")
-        .args([
+    /// List window names only (for duplicate checking).
+    fn list_window_names(&self) -> Result<Vec<String>, String> {
+        let out = self.output(&["list-windows", "-t", SESSION, "-F", "#{window_name}"])?;
+        Ok(out.lines().map(|s| s.to_string()).collect())
+    }
+
+    fn new_session(
+        &self,
+        name: &str,
+        dir: &str,
+        sidebar_bin: &str,
+        agent_cmd: &str,
+    ) -> Result<(), String> {
+        let side = (100 - layout().claude_pct()).to_string();
+        let sidebar = layout().sidebar_pct().to_string();
+        let ok = self.status(&[
             "new-session",
             "-d",
             "-s",
@@ -87,17 +216,29 @@ pub fn new_session(name: &str, dir: &str, sidebar_bin: &str) -> Result<(), Strin
             ";",
             "set-option",
             "-w",
+            "-t",
+            SESSION,
             "remain-on-exit",
             "on",
             ";",
+            "set-option",
+            "-w",
+            "-t",
+            SESSION,
+            LAYOUT_OPTION,
+            "1",
+            ";",
             "set-hook",
+            "-w",
+            "-t",
+            SESSION,
             "pane-died",
-            "respawn-pane",
+            PANE_DIED_HOOK,
             ";",
             "split-window",
             "-h",
             "-p",
-            "30",
+            &side,
             "-c",
             dir,
             ";",
@@ -107,7 +248,7 @@ pub fn new_session(name: &str, dir: &str, sidebar_bin: &str) -> Result<(), Strin
             "-v",
             "-b",
             "-p",
-            "50",
+            &sidebar,
             sidebar_bin,
             ";",
             "select-pane",
@@ -118,44 +259,78 @@ pub fn new_session(name: &str, dir: &str, sidebar_bin: &str) -> Result<(), Strin
             "-t",
             ".1",
             "-k",
+            agent_cmd,
+            ";",
+            "set-option",
+            "-p",
+            "-t",
+            ".1",
+            ROLE_OPTION,
             "claude",
             ";",
+            "set-option",
+            "-p",
+            "-t",
+            ".2",
+            ROLE_OPTION,
+            "sidebar",
+            ";",
+            "set-option",
+            "-p",
+            "-t",
+            ".3",
+            ROLE_OPTION,
+            "terminal",
+            ";",
             "set-hook",
             "-w",
+            "-t",
+            SESSION,
             "window-layout-changed",
-            "run-shell 'tmux resize-pane -t #{session_name}:#{window_index}.1 -x $((#{window_width} * 70 / 100))'",
-        ])
-        .status()
-        .map_err(|e| format!("tmux: {e}"))?;
+            LAYOUT_HOOK,
+        ])?;
 
-    if !status.success() {
-        return Err("tmux new-session failed".to_string());
+        if !ok {
+            return Err("tmux new-session failed".to_string());
+        }
+        self.record_options(name, LAYOUT_OPTIONS);
+        self.keep_claude_width(name)
     }
-    Ok(())
-}
 
-pub fn new_window(name: &str, dir: &str) -> Result<(), String> {
-    // Explicitly pick the next unused index to avoid "index N in use" errors
-    // caused by zombie windows kept alive by remain-on-exit.
-    let max_idx = list_windows()?.iter().map(|w| w.index).max().unwrap_or(0);
-    let next_idx = (max_idx + 1).to_string();
+    fn new_window(&self, name: &str, dir: &str, agent_cmd: &str) -> Result<(), String> {
+        // Explicitly pick the next unused index to avoid "index N in use" errors
+        // caused by zombie windows kept alive by remain-on-exit.
+        let max_idx = self
+            .list_windows()?
+            .iter()
+            .map(|w| w.index)
+            .max()
+            .unwrap_or(0);
+        let next_idx = (max_idx + 1).to_string();
 
-    let target = format!("{SESSION}:{next_idx}");
-    let status = Command::new("tmux")
-        .args(["new-window", "-t", &target, "-n", name, "-c", dir, "claude"])
-        .status()
-        .map_err(|e| format!("tmux: {e}"))?;
+        let target = format!("{SESSION}:{next_idx}");
+        let ok = self.status(&[
+            "new-window",
+            "-t",
+            &target,
+            "-n",
+            name,
+            "-c",
+            dir,
+            agent_cmd,
+        ])?;
 
-    if !status.success() {
-        return Err("tmux new-window failed".to_string());
+        if !ok {
+            return Err("tmux new-window failed".to_string());
+        }
+        Ok(())
     }
-    Ok(())
-}
 
-pub fn setup_layout(name: &str, dir: &str, sidebar_bin: &str) -> Result<(), String> {
-    let win = format!("{SESSION}:{name}");
-    let status = Command::new("tmux")
-        .args([
+    fn setup_layout(&self, name: &str, dir: &str, sidebar_bin: &str) -> Result<(), String> {
+        let win = self.window_target(name)?;
+        let side = (100 - layout().claude_pct()).to_string();
+        let sidebar = layout().sidebar_pct().to_string();
+        let ok = self.status(&[
             "set-option",
             "-w",
             "-t",
@@ -163,19 +338,26 @@ pub fn setup_layout(name: &str, dir: &str, sidebar_bin: &str) -> Result<(), Stri
             "remain-on-exit",
             "on",
             ";",
+            "set-option",
+            "-w",
+            "-t",
+            &win,
+            LAYOUT_OPTION,
+            "1",
+            ";",
             "set-hook",
             "-w",
             "-t",
             &win,
             "pane-died",
-            "respawn-pane",
+            PANE_DIED_HOOK,
             ";",
             "split-window",
             "-t",
             &win,
             "-h",
             "-p",
-            "30",
+            &side,
             "-c",
             dir,
             ";",
@@ -185,70 +367,285 @@ pub fn setup_layout(name: &str, dir: &str, sidebar_bin: &str) -> Result<(), Stri
             "-v",
             "-b",
             "-p",
-            "50",
+            &sidebar,
             sidebar_bin,
             ";",
             "select-pane",
             "-t",
             &format!("{win}.2"),
             ";",
+            "set-option",
+            "-p",
+            "-t",
+            &format!("{win}.1"),
+            ROLE_OPTION,
+            "claude",
+            ";",
+            "set-option",
+            "-p",
+            "-t",
+            &format!("{win}.2"),
+            ROLE_OPTION,
+            "sidebar",
+            ";",
+            "set-option",
+            "-p",
+            "-t",
+            &format!("{win}.3"),
+            ROLE_OPTION,
+            "terminal",
+            ";",
             "set-hook",
             "-w",
             "-t",
             &win,
             "window-layout-changed",
-            &format!(
-                "run-shell 'tmux resize-pane -t {win}.1 -x $(( #{{window_width}} * 70 / 100 ))'"
-            ),
-        ])
-        .status()
-        .map_err(|e| format!("tmux: {e}"))?;
+            LAYOUT_HOOK,
+        ])?;
 
-    if !status.success() {
-        return Err("tmux setup-layout failed".to_string());
+        if !ok {
+            return Err("tmux setup-layout failed".to_string());
+        }
+        self.record_options(name, LAYOUT_OPTIONS);
+        self.keep_claude_width(name)
     }
-    Ok(())
-}
 
-pub fn attach() -> Result<(), String> {
-    let status = Command::new("tmux")
-        .args(["attach", "-t", SESSION])
-        .status()
-        .map_err(|e| format!("tmux: {e}"))?;
+    /// Give a window opened outside cove the cove layout, its pane becoming
+    /// the Claude pane. Only single-pane windows can be adopted.
+    fn adopt_window(&self, name: &str, dir: &str, sidebar_bin: &str) -> Result<(), String> {
+        let target = format!("{}.1", self.window_target(name)?);
+        let panes = self.output(&["display-message", "-t", &target, "-p", "#{window_panes}"])?;
+        let panes = panes.trim();
+        if panes != "1" {
+            return Err(format!(
+                "{name} has {panes} panes; only single-pane windows can be adopted"
+            ));
+        }
+        self.setup_layout(name, dir, sidebar_bin)
+    }
 
-    if !status.success() {
-        return Err("tmux attach failed".to_string());
+    /// Mark `pane_id` as failing to start, or clear that and its respawn
+    /// count once it is started again.
+    fn set_failed(&self, pane_id: &str, failed: bool) -> Result<(), String> {
+        if failed {
+            self.output(&["set-option", "-p", "-t", pane_id, FAILED_OPTION, "1"])?;
+            if let Some(name) = self.window_of_pane(pane_id) {
+                self.record_options(&name, &["pane:@cove-failed"]);
+            }
+        } else {
+            self.output(&[
+                "set-option",
+                "-p",
+                "-u",
+                "-t",
+                pane_id,
+                FAILED_OPTION,
+                ";",
+                "set-option",
+                "-p",
+                "-u",
+                "-t",
+                pane_id,
+                RESPAWNS_OPTION,
+            ])?;
+        }
+        Ok(())
     }
-    Ok(())
-}
 
-pub fn switch_client() -> Result<(), String> {
-    let status = Command::new("tmux")
-        .args(["switch-client", "-t", SESSION])
-        .status()
-        .map_err(|e| format!("tmux: {e}"))?;
+    /// Rename window `name` to `new_name`.
+    fn rename_window(&self, name: &str, new_name: &str) -> Result<(), String> {
+        let target = self.window_target(name)?;
+        self.output(&["rename-window", "-t", &target, new_name])?;
+        Ok(())
+    }
 
-    if !status.success() {
-        return Err("tmux switch-client failed".to_string());
+    /// Mark Claude pane `pane_id` as parked, or clear the mark.
+    fn set_parked(&self, pane_id: &str, parked: bool) -> Result<(), String> {
+        let args = if parked {
+            vec!["set-option", "-p", "-t", pane_id, PARKED_OPTION, "1"]
+        } else {
+            vec!["set-option", "-p", "-u", "-t", pane_id, PARKED_OPTION]
+        };
+        self.output(&args)?;
+        if parked && let Some(name) = self.window_of_pane(pane_id) {
+            self.record_options(&name, &[PARKED_PANE_OPTION]);
+        }
+        Ok(())
     }
-    Ok(())
-}
 
-pub fn kill_window(name: &str) -> Result<(), String> {
-    let target = format!("{SESSION}:{name}");
-    tmux_stdout(&["kill-window", "-t", &target])?;
-    Ok(())
-}
+    /// Name of the window holding `pane_id`.
+    fn window_of_pane(&self, pane_id: &str) -> Option<String> {
+        self.output(&["display-message", "-t", pane_id, "-p", "#{window_name}"])
+            .map(|out| out.trim().to_string())
+            .ok()
+            .filter(|name| !name.is_empty())
+    }
 
-pub fn kill_session() -> Result<(), String> {
-    tmux_stdout(&["kill-session", "-t", SESSION])?;
-    Ok(())
-}
+    /// Have the layout hook hold new window `name`'s Claude pane at
+    /// `layout.claude_width`, when that isn't the hook's own 70%.
+    fn keep_claude_width(&self, name: &str) -> Result<(), String> {
+        let width = layout().claude_pct();
+        if width == DEFAULT_CLAUDE_WIDTH {
+            return Ok(());
+        }
+        let win = self.window_target(name)?;
+        self.output(&[
+            "set-option",
+            "-w",
+            "-t",
+            &win,
+            WIDTH_OPTION,
+            &width.to_string(),
+        ])?;
+        self.record_options(name, &[WIDTH_OPTION]);
+        Ok(())
+    }
 
-pub fn select_window(index: u32) -> Result<(), String> {
-    let target = format!("{SESSION}:{index}");
-    let status = Command::new("tmux")
-        .args([
+    /// Note in window `name`'s metadata that cove set `options` on it (in
+    /// the form of `LAYOUT_OPTIONS`), for `cove clean --options`.
+    fn record_options(&self, name: &str, options: &[&str]) {
+        if let Err(e) = meta::record_options(name, options) {
+            log::warn!("recording tmux options of {name}: {e}");
+        }
+    }
+
+    /// Unset every option and hook cove set on window `name` — the
+    /// `recorded` ones and any of `COVE_OPTIONS` — and a pane-died hook of
+    /// cove's on the cove session itself. Returns what was removed.
+    fn clean_options(&self, name: &str, recorded: &[String]) -> Result<Vec<String>, String> {
+        let win = self.window_target(name)?;
+        let window_options = self.output(&["show-options", "-w", "-t", &win])?;
+        let hooks = self.output(&["show-hooks", "-w", "-t", &win])?;
+        let session_hooks = self.output(&["show-hooks", "-t", SESSION])?;
+        let panes = self.output(&["list-panes", "-t", &win, "-F", "#{pane_id}"])?;
+        let mut pane_options = Vec::new();
+        for pane in panes.lines().filter(|p| !p.is_empty()) {
+            pane_options.push((
+                pane.to_string(),
+                self.output(&["show-options", "-p", "-t", pane])?,
+            ));
+        }
+        let set = SetOptions {
+            window: &window_options,
+            hooks: &hooks,
+            session_hooks: &session_hooks,
+            panes: &pane_options,
+        };
+        let (removed, commands) = plan_clean(&win, recorded, &set);
+        if !commands.is_empty() {
+            let args: Vec<&str> = commands.iter().map(String::as_str).collect();
+            self.output(&args)?;
+        }
+        Ok(removed)
+    }
+
+    /// Put back what `setup_layout` set on window `name` without touching
+    /// what runs in its panes: remain-on-exit, the pane-died and layout
+    /// hooks, pane roles and split sizes. Returns what had to be fixed.
+    fn apply_layout(&self, name: &str) -> Result<Vec<String>, String> {
+        let win = self.window_target(name)?;
+        let format = format!(
+            "#{{pane_id}}|#{{{ROLE_OPTION}}}|#{{pane_width}}|#{{pane_height}}|#{{window_width}}|#{{window_zoomed_flag}}|#{{remain-on-exit}}|#{{{WIDTH_OPTION}}}"
+        );
+        let out = self.output(&["list-panes", "-t", &win, "-F", &format])?;
+        let hooks = self.output(&["show-hooks", "-w", "-t", &win])?;
+        let (fixes, commands) = plan_layout(name, &win, &out, &hooks, layout().sidebar_pct())?;
+        if !commands.is_empty() {
+            let args: Vec<&str> = commands.iter().map(String::as_str).collect();
+            self.output(&args)?;
+        }
+        self.record_options(name, LAYOUT_OPTIONS);
+        Ok(fixes)
+    }
+
+    /// Arrange window `name` with tmux layout `layout`, and keep its Claude
+    /// pane at `claude_width` percent of the window from then on.
+    fn select_preset(&self, name: &str, layout: &str, claude_width: u32) -> Result<(), String> {
+        let win = self.window_target(name)?;
+        let width = claude_width.to_string();
+        // On its own: the layout hook fired by a chain's select-layout would
+        // still see the old width
+        self.output(&["set-option", "-w", "-t", &win, WIDTH_OPTION, &width])?;
+        self.record_options(name, &[WIDTH_OPTION]);
+        self.output(&[
+            "select-layout",
+            "-t",
+            &win,
+            layout,
+            ";",
+            "resize-pane",
+            "-t",
+            &format!("{win}.1"),
+            "-x",
+            &format!("{width}%"),
+        ])?;
+        Ok(())
+    }
+
+    /// Give a window that lost its Claude pane a new one running `agent_cmd`,
+    /// as the full-height left pane (.1) the layout hook sizes again.
+    fn repair_claude_pane(&self, index: u32, dir: &str, agent_cmd: &str) -> Result<(), String> {
+        let window = format!("{SESSION}:{index}");
+        let pane = self.output(&[
+            "split-window",
+            "-f",
+            "-h",
+            "-b",
+            "-d",
+            "-t",
+            &window,
+            "-c",
+            dir,
+            "-P",
+            "-F",
+            "#{pane_id}",
+            agent_cmd,
+        ])?;
+        self.output(&["set-option", "-p", "-t", pane.trim(), ROLE_OPTION, "claude"])?;
+        Ok(())
+    }
+
+    fn attach(&self) -> Result<(), String> {
+        let ok = self.status(&["attach", "-t", SESSION])?;
+
+        if !ok {
+            return Err("tmux attach failed".to_string());
+        }
+        Ok(())
+    }
+
+    fn switch_client(&self) -> Result<(), String> {
+        let ok = self.status(&["switch-client", "-t", SESSION])?;
+
+        if !ok {
+            return Err("tmux switch-client failed".to_string());
+        }
+        Ok(())
+    }
+
+    fn kill_window(&self, name: &str) -> Result<(), String> {
+        let target = self.window_target(name)?;
+        self.output(&["kill-window", "-t", &target])?;
+        Ok(())
+    }
+
+    /// Kill a window by its stable ID. Returns false if it no longer exists.
+    fn kill_window_id(&self, id: &str) -> Result<bool, String> {
+        match self.output(&["kill-window", "-t", id]) {
+            Ok(_) => Ok(true),
+            Err(e) if e.contains("can't find window") => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn kill_session(&self) -> Result<(), String> {
+        self.output(&["kill-session", "-t", SESSION])?;
+        Ok(())
+    }
+
+    fn select_window(&self, index: u32) -> Result<(), String> {
+        let target = format!("{SESSION}:{index}");
+        let ok = self.status(&[
             "select-window",
             "-t",
             &target,
@@ -256,65 +653,229 @@ pub fn select_window(index: u32) -> Result<(), String> {
             "select-pane",
             "-t",
             ":.1",
-        ])
-        .status()
-        .map_err(|e| format!("tmux: {e}"))?;
+        ])?;
 
-    if !status.success() {
-        return Err("tmux select-window failed".to_string());
+        if !ok {
+            return Err("tmux select-window failed".to_string());
+        }
+        // Coming back to a window left in focus mode zooms it again
+        let _ = self.restore_focus(&target);
+        Ok(())
     }
-    Ok(())
-}
 
-/// Info about pane .1 in each window (for state detection).
-pub struct PaneInfo {
-    pub window_index: u32,
-    pub command: String,
-    /// Unique tmux pane identifier (e.g. "%0", "%3").
-    pub pane_id: String,
-}
+    /// Zoom a window's Claude pane, or unzoom it if the window is zoomed.
+    /// The choice is remembered in the window's `@cove-focus` option.
+    /// Returns true if the window is now focused.
+    fn toggle_focus(&self, window: &str) -> Result<bool, String> {
+        let target = self.window_target(window)?;
+        let flags = self.output(&[
+            "display-message",
+            "-t",
+            &target,
+            "-p",
+            "#{window_zoomed_flag}",
+        ])?;
+        let claude = format!("{target}.1");
+        if flags.trim() == "1" {
+            self.output(&["resize-pane", "-Z", "-t", &claude])?;
+            self.output(&["set-option", "-w", "-t", &target, "-u", FOCUS_OPTION])?;
+            Ok(false)
+        } else {
+            self.output(&["select-pane", "-t", &claude])?;
+            self.output(&["resize-pane", "-Z", "-t", &claude])?;
+            self.output(&["set-option", "-w", "-t", &target, FOCUS_OPTION, "1"])?;
+            self.record_options(window, &[FOCUS_OPTION]);
+            Ok(true)
+        }
+    }
 
-/// Get the foreground command and pane ID of pane .1 in every window.
-pub fn list_pane_commands() -> Result<Vec<PaneInfo>, String> {
-    let out = tmux_stdout(&[
-        "list-panes",
-        "-s",
-        "-t",
-        SESSION,
-        "-F",
-        "#{window_index}|#{pane_index}|#{pane_current_command}|#{pane_id}",
-    ])?;
-
-    let mut panes = Vec::new();
-    for line in out.lines() {
-        let parts: Vec<&str> = line.splitn(4, '|').collect();
-        if parts.len() < 4 {
-            continue;
-        }
-        // Only pane index 1 (the Claude pane)
-        if parts[1] != "1" {
-            continue;
-        }
-        panes.push(PaneInfo {
-            window_index: parts[0].parse().unwrap_or(0),
-            command: parts[2].to_string(),
-            pane_id: parts[3].to_string(),
-        });
+    /// Re-zoom the Claude pane of a window marked focused that has since been
+    /// unzoomed (e.g. by browsing to it from the sidebar).
+    fn restore_focus(&self, target: &str) -> Result<(), String> {
+        let format = format!("#{{{FOCUS_OPTION}}}|#{{window_zoomed_flag}}");
+        let flags = self.output(&["display-message", "-t", target, "-p", &format])?;
+        if flags.trim() == "1|0" {
+            self.output(&["resize-pane", "-Z", "-t", &format!("{target}.1")])?;
+        }
+        Ok(())
     }
-    Ok(panes)
-}
 
-/// Get the pane_id (e.g. "%5") of pane .1 (the Claude pane) in a specific window.
-pub fn get_claude_pane_id(window_name: &str) -> Result<String, String> {
-    let target = format!("{SESSION}:{window_name}.1");
-    let out = tmux_stdout(&["display-message", "-t", &target, "-p", "#{pane_id}"])?;
-    Ok(out.trim().to_string())
-}
+    /// Name of the window the user is in.
+    fn current_window_name(&self) -> Result<String, String> {
+        let out = self.output(&["display-message", "-p", "#{window_name}"])?;
+        Ok(out.trim().to_string())
+    }
 
-pub fn select_window_sidebar(index: u32) -> Result<(), String> {
-    let target = format!("{SESSION}:{index}");
-    let status = Command::new("tmux")
-        .args([
+    /// Record a state glyph in the `@cove-state` option of the window holding
+    /// `pane_id`, and (unless `format` is empty) show it in the window list.
+    /// Window names stay untouched — cove addresses windows by name.
+    fn set_window_state(&self, pane_id: &str, glyph: &str, format: &str) -> Result<(), String> {
+        self.output(&["set-option", "-w", "-t", pane_id, STATE_OPTION, glyph])?;
+        if !format.is_empty() {
+            for option in STATUS_OPTIONS {
+                self.output(&["set-option", "-w", "-t", pane_id, option, format])?;
+            }
+        }
+        if let Some(name) = self.window_of_pane(pane_id) {
+            self.record_options(&name, &[STATE_OPTION]);
+            if !format.is_empty() {
+                self.record_options(&name, STATUS_OPTIONS);
+            }
+        }
+        Ok(())
+    }
+
+    /// Set pane `pane_id`'s cove title, its `@cove-title` option (unset when
+    /// `title` is empty). Not `#{pane_title}`: Claude sets that itself.
+    fn set_pane_title(&self, pane_id: &str, title: &str) -> Result<(), String> {
+        let args = if title.is_empty() {
+            vec!["set-option", "-p", "-u", "-t", pane_id, TITLE_OPTION]
+        } else {
+            vec!["set-option", "-p", "-t", pane_id, TITLE_OPTION, title]
+        };
+        self.output(&args)?;
+        if !title.is_empty()
+            && let Some(name) = self.window_of_pane(pane_id)
+        {
+            self.record_options(&name, &[TITLE_PANE_OPTION]);
+        }
+        Ok(())
+    }
+
+    /// Get the foreground command, pane ID, role and cove marks of every
+    /// pane in every window. The sidebar derives broken, crashed, failed and
+    /// parked windows from this one listing.
+    fn list_panes(&self) -> Result<Vec<PaneInfo>, String> {
+        let format = format!(
+            "#{{window_index}}|#{{pane_index}}|#{{pane_id}}|#{{window_id}}|#{{pane_dead}}|\
+             #{{{FAILED_OPTION}}}|#{{{PARKED_OPTION}}}|#{{{ROLE_OPTION}}}|#{{{PAIR_OPTION}}}|\
+             #{{pane_current_command}}"
+        );
+        let out = self.output(&["list-panes", "-s", "-t", SESSION, "-F", &format])?;
+
+        let mut panes = Vec::new();
+        for line in out.lines() {
+            let parts: Vec<&str> = line.splitn(10, '|').collect();
+            if parts.len() < 10 {
+                continue;
+            }
+            panes.push(PaneInfo {
+                window_index: parts[0].parse().unwrap_or(0),
+                pane_index: parts[1].parse().unwrap_or(0),
+                pane_id: parts[2].to_string(),
+                window_id: parts[3].to_string(),
+                dead: parts[4] == "1",
+                failed: parts[5] == "1",
+                parked: parts[6] == "1",
+                role: parts[7].to_string(),
+                paired: parts[8].to_string(),
+                command: parts[9].to_string(),
+            });
+        }
+        Ok(panes)
+    }
+
+    /// Get the foreground command and pane ID of pane .1 in every window.
+    fn list_pane_commands(&self) -> Result<Vec<PaneInfo>, String> {
+        let mut panes = self.list_panes()?;
+        panes.retain(PaneInfo::is_claude);
+        Ok(panes)
+    }
+
+    /// PID of the process running in pane .1 of every window, by window index.
+    fn claude_pane_pids(&self) -> Result<HashMap<u32, u32>, String> {
+        let out = self.output(&[
+            "list-panes",
+            "-s",
+            "-t",
+            SESSION,
+            "-F",
+            "#{window_index}|#{pane_index}|#{pane_pid}",
+        ])?;
+        Ok(out
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '|');
+                let index = parts.next()?.parse().ok()?;
+                (parts.next()? == "1").then_some(())?;
+                Some((index, parts.next()?.parse().ok()?))
+            })
+            .collect())
+    }
+
+    /// The pane's scrollback and visible screen as plain text.
+    fn capture_pane(&self, pane_id: &str) -> Result<String, String> {
+        self.output(&["capture-pane", "-p", "-J", "-S", "-", "-t", pane_id])
+    }
+
+    /// Type `text` into a pane literally, without pressing Enter.
+    fn send_text(&self, pane_id: &str, text: &str) -> Result<(), String> {
+        self.output(&["send-keys", "-t", pane_id, "-l", "--", text])?;
+        Ok(())
+    }
+
+    /// Run a command line in a pane, as if typed at its prompt.
+    fn send_command(&self, pane_id: &str, command: &str) -> Result<(), String> {
+        self.output(&["send-keys", "-t", pane_id, "-l", "--", command])?;
+        self.output(&["send-keys", "-t", pane_id, "Enter"])?;
+        Ok(())
+    }
+
+    /// Press a named key (e.g. "Escape") in a pane.
+    fn send_key(&self, pane_id: &str, key: &str) -> Result<(), String> {
+        self.output(&["send-keys", "-t", pane_id, key])?;
+        Ok(())
+    }
+
+    /// Submit a (possibly multi-line) message to a pane: bracketed paste keeps
+    /// newlines inside one message instead of sending each line on its own.
+    fn paste_message(&self, pane_id: &str, message: &str) -> Result<(), String> {
+        self.output(&["set-buffer", "-b", "cove-send", "--", message])?;
+        self.output(&["paste-buffer", "-p", "-d", "-b", "cove-send", "-t", pane_id])?;
+        self.output(&["send-keys", "-t", pane_id, "Enter"])?;
+        Ok(())
+    }
+
+    /// Kill whatever runs in a pane and start `command` there instead, in
+    /// the pane's original directory.
+    fn respawn_pane(&self, pane_id: &str, command: &str) -> Result<(), String> {
+        if !self.status(&["respawn-pane", "-k", "-t", pane_id, command])? {
+            return Err(format!("tmux respawn-pane failed for {pane_id}"));
+        }
+        Ok(())
+    }
+
+    /// Flash a message in the status line of attached clients.
+    fn display_message(&self, message: &str) -> Result<(), String> {
+        // `-l` keeps tmux from expanding formats in the text
+        self.output(&["display-message", "-l", message])?;
+        Ok(())
+    }
+
+    /// The tmux prefix key in tmux notation (e.g. "C-b").
+    fn prefix_key(&self) -> Option<String> {
+        let out = self.output(&["show-options", "-gv", "prefix"]).ok()?;
+        let key = out.trim();
+        (!key.is_empty() && key != "None").then(|| key.to_string())
+    }
+
+    /// Get the pane_id (e.g. "%5") of pane .1 (the Claude pane) in a specific window.
+    fn get_claude_pane_id(&self, window_name: &str) -> Result<String, String> {
+        let target = format!("{}.1", self.window_target(window_name)?);
+        let out = self.output(&["display-message", "-t", &target, "-p", "#{pane_id}"])?;
+        Ok(out.trim().to_string())
+    }
+
+    /// Get the pane_id of pane .3 (the terminal pane) in a specific window.
+    fn get_terminal_pane_id(&self, window_name: &str) -> Result<String, String> {
+        let target = format!("{}.3", self.window_target(window_name)?);
+        let out = self.output(&["display-message", "-t", &target, "-p", "#{pane_id}"])?;
+        Ok(out.trim().to_string())
+    }
+
+    fn select_window_sidebar(&self, index: u32) -> Result<(), String> {
+        let target = format!("{SESSION}:{index}");
+        let ok = self.status(&[
             "select-window",
             "-t",
             &target,
@@ -322,12 +883,783 @@ pub fn select_window_sidebar(index: u32) -> Result<(), String> {
             "select-pane",
             "-t",
             ":.2",
-        ])
-        .status()
-        .map_err(|e| format!("tmux: {e}"))?;
+        ])?;
+
+        if !ok {
+            return Err("tmux select-window failed".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ── Helpers ──
+
+/// When this process last created the cove session.
+static BOOTSTRAPPED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Timeout and retry settings from the `tmux` config section, read once.
+fn policy() -> &'static TmuxConfig {
+    static POLICY: OnceLock<TmuxConfig> = OnceLock::new();
+    POLICY.get_or_init(|| config::load().map(|c| c.tmux).unwrap_or_default())
+}
+
+/// Pane sizes from the `layout` config section, read once.
+fn layout() -> &'static LayoutConfig {
+    static LAYOUT: OnceLock<LayoutConfig> = OnceLock::new();
+    LAYOUT.get_or_init(|| config::load().map(|c| c.layout).unwrap_or_default())
+}
+
+/// What is set on a window, as `show-options`/`show-hooks` print it.
+struct SetOptions<'a> {
+    window: &'a str,
+    hooks: &'a str,
+    session_hooks: &'a str,
+    /// Each pane's id and options.
+    panes: &'a [(String, String)],
+}
+
+/// Whether `show-options` output `shown` has option `name` set.
+fn has_option(shown: &str, name: &str) -> bool {
+    shown
+        .lines()
+        .any(|l| l.split_whitespace().next() == Some(name))
+}
+
+/// Whether `show-hooks` output `shown` has hook `name` set.
+fn has_hook(shown: &str, name: &str) -> bool {
+    shown.lines().any(|l| l.starts_with(&format!("{name}[")))
+}
+
+/// What `clean_options` removes from window `win`: a description of each
+/// option and the tmux command chain that unsets them.
+fn plan_clean(win: &str, recorded: &[String], set: &SetOptions) -> (Vec<String>, Vec<String>) {
+    let mut candidates: Vec<&str> = recorded.iter().map(String::as_str).collect();
+    for option in COVE_OPTIONS.iter().flat_map(|group| group.iter()) {
+        if !candidates.contains(option) {
+            candidates.push(option);
+        }
+    }
+
+    let mut removed = Vec::new();
+    let mut commands: Vec<String> = Vec::new();
+    let mut run = |args: &[&str]| {
+        if !commands.is_empty() {
+            commands.push(";".to_string());
+        }
+        commands.extend(args.iter().map(|a| a.to_string()));
+    };
+    for option in candidates {
+        if let Some(hook) = option.strip_prefix("hook:") {
+            if has_hook(set.hooks, hook) {
+                removed.push(format!("{hook} hook"));
+                run(&["set-hook", "-w", "-u", "-t", win, hook]);
+            }
+        } else if let Some(option) = option.strip_prefix("pane:") {
+            let panes: Vec<&str> = set
+                .panes
+                .iter()
+                .filter(|(_, shown)| has_option(shown, option))
+                .map(|(pane, _)| pane.as_str())
+                .collect();
+            for pane in &panes {
+                run(&["set-option", "-p", "-u", "-t", pane, option]);
+            }
+            match panes.len() {
+                0 => {}
+                1 => removed.push(format!("{option} (1 pane)")),
+                n => removed.push(format!("{option} ({n} panes)")),
+            }
+        } else if has_option(set.window, option) {
+            removed.push(option.to_string());
+            run(&["set-option", "-w", "-u", "-t", win, option]);
+        }
+    }
+    // On a tmux with only session hooks, it respawned every pane of the session
+    let leaked = set
+        .session_hooks
+        .lines()
+        .any(|l| l.starts_with("pane-died[") && l.contains(ROLE_OPTION));
+    if leaked {
+        removed.push(format!("pane-died hook of the {SESSION} session"));
+        run(&["set-hook", "-u", "-t", SESSION, "pane-died"]);
+    }
+    (removed, commands)
+}
+
+/// What `apply_layout` must change on window `name` (targeted as `win`),
+/// from its `list-panes` output and window hooks, with the sidebar at
+/// `sidebar_pct` of the right column: a description of each fix and the
+/// tmux command chain that makes them.
+fn plan_layout(
+    name: &str,
+    win: &str,
+    panes: &str,
+    hooks: &str,
+    sidebar_pct: u32,
+) -> Result<(Vec<String>, Vec<String>), String> {
+    let panes: Vec<Vec<&str>> = panes
+        .lines()
+        .map(|line| line.splitn(8, '|').collect::<Vec<&str>>())
+        .filter(|parts| parts.len() == 8)
+        .collect();
+    let Some(first) = panes.first() else {
+        return Err(format!("{name} has no panes"));
+    };
+    let untagged = panes.iter().all(|p| p[1].is_empty());
+    if untagged && panes.len() != ROLES.len() {
+        return Err(format!(
+            "{name} doesn't have cove's layout; adopt it from the sidebar (a) instead"
+        ));
+    }
+
+    let mut fixes = Vec::new();
+    let mut commands: Vec<String> = Vec::new();
+    let mut run = |args: &[&str]| {
+        if !commands.is_empty() {
+            commands.push(";".to_string());
+        }
+        commands.extend(args.iter().map(|a| a.to_string()));
+    };
+    if first[6] != "on" {
+        fixes.push("remain-on-exit".to_string());
+        run(&["set-option", "-w", "-t", win, "remain-on-exit", "on"]);
+    }
+    let hook = |name: &str| hooks.lines().find(|l| l.starts_with(&format!("{name}[")));
+    // Hooks from before crash recovery respawn a crashed Claude right away,
+    // and ones from before `pane_dead_signal` take an instant exit for a crash
+    if !hook("pane-died").is_some_and(|h| h.contains("pane_dead_signal")) {
+        fixes.push("pane-died hook".to_string());
+        run(&["set-hook", "-w", "-t", win, "pane-died", PANE_DIED_HOOK]);
+    }
+    // ...and ones from before presets hold Claude at 70%
+    if !hook("window-layout-changed").is_some_and(|h| h.contains(WIDTH_OPTION)) {
+        fixes.push("layout hook".to_string());
+        run(&[
+            "set-hook",
+            "-w",
+            "-t",
+            win,
+            "window-layout-changed",
+            LAYOUT_HOOK,
+        ]);
+    }
+    run(&["set-option", "-w", "-t", win, LAYOUT_OPTION, "1"]);
+
+    // Windows from before roles were set get them by position
+    let role_of = |i: usize, pane: &[&str]| {
+        if untagged {
+            ROLES[i].to_string()
+        } else {
+            pane[1].to_string()
+        }
+    };
+    if untagged {
+        for (i, pane) in panes.iter().enumerate() {
+            fixes.push(format!("{} pane role", ROLES[i]));
+            run(&["set-option", "-p", "-t", pane[0], ROLE_OPTION, ROLES[i]]);
+        }
+    }
+
+    // Split sizes; a zoomed window is left alone
+    if first[5] != "1" {
+        let find = |role: &str| {
+            panes
+                .iter()
+                .enumerate()
+                .find(|(i, p)| role_of(*i, p) == role)
+                .map(|(_, p)| p)
+        };
+        let size = |s: &str| s.parse::<i64>().unwrap_or(0);
+        if let Some(claude) = find("claude") {
+            let share = first[7]
+                .parse::<i64>()
+                .unwrap_or(i64::from(DEFAULT_CLAUDE_WIDTH));
+            let target = size(first[4]) * share / 100;
+            if (size(claude[2]) - target).abs() > 1 {
+                fixes.push("Claude pane width".to_string());
+                run(&["resize-pane", "-t", claude[0], "-x", &target.to_string()]);
+            }
+        }
+        if let (Some(sidebar), Some(terminal)) = (find("sidebar"), find("terminal")) {
+            let target = (size(sidebar[3]) + size(terminal[3])) * i64::from(sidebar_pct) / 100;
+            if (size(sidebar[3]) - target).abs() > 1 {
+                fixes.push("sidebar height".to_string());
+                run(&["resize-pane", "-t", sidebar[0], "-y", &target.to_string()]);
+            }
+        }
+    }
+    Ok((fixes, commands))
+}
+
+/// The one place tmux failures become error strings: "tmux <subcommand>: <detail>".
+pub fn error(args: &[&str], detail: impl std::fmt::Display) -> String {
+    let subcommand = args.first().copied().unwrap_or_default();
+    format!("tmux {subcommand}: {detail}")
+}
+
+fn in_bootstrap_grace() -> bool {
+    BOOTSTRAPPED_AT
+        .lock()
+        .ok()
+        .and_then(|at| *at)
+        .is_some_and(|at| at.elapsed() < BOOTSTRAP_GRACE)
+}
+
+/// Wait for `child`, killing it once `timeout` passes.
+fn wait_with_timeout(
+    child: &mut Child,
+    args: &[&str],
+    timeout: Option<Duration>,
+) -> Result<ExitStatus, String> {
+    let deadline = timeout.map(|t| Instant::now() + t);
+    loop {
+        if let Some(status) = child.try_wait().map_err(|e| error(args, e))? {
+            return Ok(status);
+        }
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            let _ = child.kill();
+            let _ = child.wait();
+            let ms = timeout.unwrap_or_default().as_millis();
+            return Err(error(args, format!("timed out after {ms}ms")));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Drain a pipe on its own thread so a chatty command can't block on a full pipe.
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        String::from_utf8_lossy(&buf).to_string()
+    })
+}
+
+impl Tmux {
+    fn timeout(args: &[&str]) -> Option<Duration> {
+        // attach holds the terminal for the whole session, and a first
+        // new-session can take a while to start the server and its panes
+        if matches!(
+            args.first(),
+            Some(&"attach" | &"attach-session" | &"new-session")
+        ) {
+            return None;
+        }
+        let ms = policy().timeout_ms;
+        (ms > 0).then(|| Duration::from_millis(ms))
+    }
+
+    fn output_once(args: &[&str]) -> Result<String, String> {
+        let mut child = Command::new("tmux")
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| error(args, e))?;
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+        let status = wait_with_timeout(&mut child, args, Self::timeout(args))?;
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if !status.success() {
+            return Err(error(args, stderr.trim()));
+        }
+        Ok(stdout)
+    }
+}
+
+impl TmuxClient for Tmux {
+    fn output(&self, args: &[&str]) -> Result<String, String> {
+        log::trace!("tmux {}", args.join(" "));
+        let mut attempt = 0;
+        loop {
+            match Self::output_once(args) {
+                Err(e)
+                    if attempt < policy().retries
+                        && TRANSIENT_ERRORS.iter().any(|t| e.contains(t))
+                        && in_bootstrap_grace() =>
+                {
+                    attempt += 1;
+                    log::debug!("retry {attempt} after: {e}");
+                    thread::sleep(RETRY_BACKOFF * attempt);
+                }
+                Err(e) => {
+                    log::debug!("{e}");
+                    return Err(e);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn status(&self, args: &[&str]) -> Result<bool, String> {
+        // stdio stays on the user's terminal, so failures can't be classified
+        // and these calls (new-session among them) are never retried
+        log::trace!("tmux {}", args.join(" "));
+        let mut child = Command::new("tmux")
+            .args(args)
+            .spawn()
+            .map_err(|e| error(args, e))?;
+        let ok = wait_with_timeout(&mut child, args, Self::timeout(args))?.success();
+        if ok
+            && args.first() == Some(&"new-session")
+            && let Ok(mut at) = BOOTSTRAPPED_AT.lock()
+        {
+            *at = Some(Instant::now());
+        }
+        Ok(ok)
+    }
+}
+
+// ── Public API ──
+
+pub const SESSION: &str = "cove";
+
+/// How often a running tmux command is checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// Delay before retry n is n times this.
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Transient stderr seen while a freshly started server is still coming up.
+const TRANSIENT_ERRORS: &[&str] = &[
+    "no server running",
+    "server exited unexpectedly",
+    "lost server",
+    "error connecting to",
+];
+
+/// Transient errors are only retried this long after `new-session`; at any
+/// other time "no server running" is a real answer (e.g. for `has_session`).
+const BOOTSTRAP_GRACE: Duration = Duration::from_secs(10);
+
+/// Window option marking windows that have cove's layout.
+const LAYOUT_OPTION: &str = "@cove-layout";
+
+/// Window option set on windows in focus mode.
+const FOCUS_OPTION: &str = "@cove-focus";
+
+/// Pane option naming each pane's part in the layout: claude, sidebar, terminal.
+const ROLE_OPTION: &str = "@cove-role";
+
+/// Pane option set on a Claude pane whose agent was stopped on purpose.
+const PARKED_OPTION: &str = "@cove-parked";
+
+/// Window option: the Claude pane's share of the window width in percent,
+/// set by `cove layout --preset` or from `layout.claude_width`. Unset means
+/// `DEFAULT_CLAUDE_WIDTH`.
+const WIDTH_OPTION: &str = "@cove-claude-width";
+
+/// The Claude pane's share of the width `LAYOUT_HOOK` falls back to.
+const DEFAULT_CLAUDE_WIDTH: u32 = 70;
+
+/// Keep the Claude pane at its share of the window width (`WIDTH_OPTION`).
+/// Resolved when the hook fires, so it follows the window even if its index
+/// changes; skipped while zoomed, where resize-pane would undo `cove focus`,
+/// and when the width is already right: even a resize that changes nothing
+/// fires the hook again.
+const LAYOUT_HOOK: &str = "if-shell -F '#{?window_zoomed_flag,0,1}' { run-shell 'w=$(( #{window_width} * #{?@cove-claude-width,#{@cove-claude-width},70} / 100 )); [ \"$(tmux display -p -t #{window_id}.1 \"##{pane_width}\")\" = \"$w\" ] || tmux resize-pane -t #{window_id}.1 -x $w' }";
+
+/// Roles of a cove window's panes, left to right and top to bottom.
+const ROLES: [&str; 3] = ["claude", "sidebar", "terminal"];
+
+/// Respawn panes that exit, except a Claude pane that exited with an error or
+/// a signal: that one stays dead for the sidebar's crash policy to handle. A
+/// pane that exits before tmux reads its status has neither and counts as a
+/// clean exit, so one dying instantly is caught as a start loop. A pane that
+/// was already respawned 3 times in the last 30 seconds (kept in
+/// `RESPAWNS_OPTION`) is dying on start, e.g. Claude failing to log in: it is
+/// left dead and marked with `FAILED_OPTION` instead of looping.
+const PANE_DIED_HOOK: &str = "if-shell -F '#{||:#{!=:#{@cove-role},claude},#{m/r:^0?$,#{pane_dead_status}#{pane_dead_signal}}}' { run-shell 'now=$(date +%s); n=0; keep=; for t in #{@cove-respawns}; do [ $((now - t)) -lt 30 ] && n=$((n + 1)) && keep=\"$keep $t\"; done; if [ $n -ge 3 ]; then tmux set-option -p -t #{pane_id} @cove-failed 1; else tmux set-option -p -t #{pane_id} @cove-respawns \"$keep $now\" \\; respawn-pane -t #{pane_id}; fi' }";
+
+/// Pane option: unix times of the pane's recent respawns by `PANE_DIED_HOOK`.
+const RESPAWNS_OPTION: &str = "@cove-respawns";
+
+/// Pane option set on a pane left dead because it kept dying on start.
+const FAILED_OPTION: &str = "@cove-failed";
+
+/// Window option of a `cove pair` view: the "pane:window" each pane came from.
+pub const PAIR_OPTION: &str = "@cove-pair";
+
+/// Window option holding the session's state glyph (see `config::WindowStatusConfig`).
+const STATE_OPTION: &str = "@cove-state";
+
+/// Window options showing `STATE_OPTION` in the window list.
+const STATUS_OPTIONS: &[&str] = &["window-status-format", "window-status-current-format"];
+
+/// `PARKED_OPTION` as `record_options` writes a pane option.
+const PARKED_PANE_OPTION: &str = "pane:@cove-parked";
+
+/// Pane option holding the state title hooks give a Claude pane, for
+/// `pane-border-format`.
+const TITLE_OPTION: &str = "@cove-title";
+
+/// `TITLE_OPTION` as `record_options` writes a pane option.
+const TITLE_PANE_OPTION: &str = "pane:@cove-title";
+
+/// What `setup_layout` sets on a window, in the form its metadata records
+/// options: a window option by name, `hook:<name>` for a window hook and
+/// `pane:<name>` for an option of its panes.
+const LAYOUT_OPTIONS: &[&str] = &[
+    "remain-on-exit",
+    LAYOUT_OPTION,
+    "hook:pane-died",
+    "hook:window-layout-changed",
+    "pane:@cove-role",
+    // Set by the pane-died hook
+    "pane:@cove-respawns",
+    "pane:@cove-failed",
+];
+
+/// Everything cove may set on a window. Windows from before options were
+/// recorded have no record, so `clean_options` checks for all of them.
+const COVE_OPTIONS: &[&[&str]] = &[
+    LAYOUT_OPTIONS,
+    &[
+        FOCUS_OPTION,
+        WIDTH_OPTION,
+        STATE_OPTION,
+        PARKED_PANE_OPTION,
+        TITLE_PANE_OPTION,
+    ],
+    STATUS_OPTIONS,
+];
+
+pub fn is_inside_tmux() -> bool {
+    std::env::var("TMUX").is_ok_and(|v| !v.is_empty())
+}
+
+pub fn has_session() -> bool {
+    Tmux.has_session()
+}
+
+pub fn list_windows() -> Result<Vec<WindowInfo>, String> {
+    Tmux.list_windows()
+}
+
+pub fn list_window_names() -> Result<Vec<String>, String> {
+    Tmux.list_window_names()
+}
+
+pub fn resolve_target(target: &str) -> Result<String, String> {
+    Tmux.resolve_target(target)
+}
+
+pub fn kill_window(name: &str) -> Result<(), String> {
+    Tmux.kill_window(name)
+}
+
+pub fn select_window(index: u32) -> Result<(), String> {
+    Tmux.select_window(index)
+}
+
+pub fn send_text(pane_id: &str, text: &str) -> Result<(), String> {
+    Tmux.send_text(pane_id, text)
+}
+
+pub fn send_command(pane_id: &str, command: &str) -> Result<(), String> {
+    Tmux.send_command(pane_id, command)
+}
+
+pub fn prefix_key() -> Option<String> {
+    Tmux.prefix_key()
+}
+
+pub fn display_message(message: &str) -> Result<(), String> {
+    Tmux.display_message(message)
+}
+
+pub fn get_claude_pane_id(window_name: &str) -> Result<String, String> {
+    Tmux.get_claude_pane_id(window_name)
+}
+
+pub fn get_terminal_pane_id(window_name: &str) -> Result<String, String> {
+    Tmux.get_terminal_pane_id(window_name)
+}
+
+pub fn select_window_sidebar(index: u32) -> Result<(), String> {
+    Tmux.select_window_sidebar(index)
+}
+
+pub fn toggle_focus(window: &str) -> Result<bool, String> {
+    Tmux.toggle_focus(window)
+}
+
+pub fn adopt_window(name: &str, dir: &str, sidebar_bin: &str) -> Result<(), String> {
+    Tmux.adopt_window(name, dir, sidebar_bin)
+}
+
+pub fn repair_claude_pane(index: u32, dir: &str, agent_cmd: &str) -> Result<(), String> {
+    Tmux.repair_claude_pane(index, dir, agent_cmd)
+}
+
+/// Indexes of cove windows whose Claude pane was closed: their panes carry
+/// roles, but none is "claude". Windows from before roles were set have none
+/// and are never flagged, nor are windows whose Claude pane is only visiting
+/// a `cove pair` view.
+pub fn broken_windows(panes: &[PaneInfo]) -> HashSet<u32> {
+    // Pair views list "pane:window" for each pane they borrowed
+    let paired: HashSet<&str> = panes
+        .iter()
+        .flat_map(|p| p.paired.split_whitespace())
+        .filter_map(|entry| Some(entry.split_once(':')?.1))
+        .collect();
+    let mut roles: HashMap<(u32, &str), bool> = HashMap::new();
+    for pane in panes.iter().filter(|p| !p.role.is_empty()) {
+        let has_claude = roles
+            .entry((pane.window_index, pane.window_id.as_str()))
+            .or_default();
+        *has_claude |= pane.role == "claude";
+    }
+    roles
+        .into_iter()
+        .filter(|((_, id), has_claude)| !has_claude && !paired.contains(id))
+        .map(|((index, _), _)| index)
+        .collect()
+}
+
+/// Claude panes that exited with an error and were left dead by
+/// `PANE_DIED_HOOK`, as window index → pane id. Panes that failed to start
+/// aren't crashes: `failed_windows` has those.
+pub fn crashed_windows(panes: &[PaneInfo]) -> HashMap<u32, String> {
+    panes
+        .iter()
+        .filter(|p| p.dead && !p.failed && p.role == "claude")
+        .map(|p| (p.window_index, p.pane_id.clone()))
+        .collect()
+}
+
+/// Panes left dead because they kept dying on start (`FAILED_OPTION`), as
+/// window index → pane id; a window's Claude pane wins over the others.
+pub fn failed_windows(panes: &[PaneInfo]) -> HashMap<u32, String> {
+    let mut failed = HashMap::new();
+    for pane in panes.iter().filter(|p| p.dead && p.failed) {
+        if pane.role == "claude" || !failed.contains_key(&pane.window_index) {
+            failed.insert(pane.window_index, pane.pane_id.clone());
+        }
+    }
+    failed
+}
+
+/// Indexes of windows whose Claude was stopped by `cove kill --keep-pane`.
+pub fn parked_windows(panes: &[PaneInfo]) -> HashSet<u32> {
+    panes
+        .iter()
+        .filter(|p| p.parked && p.role == "claude")
+        .map(|p| p.window_index)
+        .collect()
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_with_timeout_kills_slow_command() {
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+        let started = Instant::now();
+        let err = wait_with_timeout(
+            &mut child,
+            &["list-windows"],
+            Some(Duration::from_millis(50)),
+        )
+        .unwrap_err();
+        assert_eq!(err, "tmux list-windows: timed out after 50ms");
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_session_start_and_attach_have_no_timeout() {
+        assert_eq!(Tmux::timeout(&["new-session", "-d", "-s", SESSION]), None);
+        assert_eq!(Tmux::timeout(&["attach", "-t", SESSION]), None);
+    }
+
+    /// Panes as `list_panes` reads them, from "index|pane|window id|dead|
+    /// failed|parked|role|pair" lines.
+    fn panes(lines: &str) -> Vec<PaneInfo> {
+        let out: String = lines
+            .lines()
+            .map(|line| {
+                let (index, rest) = line.split_once('|').unwrap();
+                format!("{index}|1|{rest}|claude\n")
+            })
+            .collect();
+        fake::FakeTmux::new()
+            .on("list-panes", &out)
+            .list_panes()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_broken_windows_lack_a_claude_role() {
+        let panes = panes(
+            "1|%t1|@1|0|||claude|\n1|%t2|@1|0|||sidebar|\n2|%t3|@2|0|||sidebar|\n\
+             2|%t4|@2|0|||terminal|\n3|%t5|@3|0||||\n4|%t6|@4|0|||sidebar|\n\
+             5|%t9|@5|0|||claude|%t9:@4",
+        );
+        assert_eq!(broken_windows(&panes), HashSet::from([2]));
+    }
+
+    #[test]
+    fn test_crashed_windows_are_dead_claude_panes() {
+        let panes = panes(
+            "1|%t1|@1|1|||claude|\n1|%t2|@1|0|||sidebar|\n2|%t3|@2|0|||claude|\n\
+             3|%t4|@3|1|||terminal|\n4|%t5|@4|1|1||claude|\n5|%t6|@5|1|1||terminal|\n\
+             5|%t7|@5|0|||claude|",
+        );
+        let crashed = crashed_windows(&panes);
+        assert_eq!(crashed, HashMap::from([(1, "%t1".to_string())]));
+        // Dead from failing to start: not a crash
+        let failed = failed_windows(&panes);
+        assert_eq!(
+            failed,
+            HashMap::from([(4, "%t5".to_string()), (5, "%t6".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_parked_windows_are_marked_claude_panes() {
+        let panes = panes(
+            "1|%t1|@1|0||1|claude|\n1|%t2|@1|0|||sidebar|\n2|%t3|@2|0|||claude|\n\
+             3|%t4|@3|0||1|terminal|",
+        );
+        assert_eq!(parked_windows(&panes), HashSet::from([1]));
+    }
+
+    #[test]
+    fn test_plan_layout_fixes_only_what_drifted() {
+        let hooks =
+            format!("pane-died[0] {PANE_DIED_HOOK}\nwindow-layout-changed[0] {LAYOUT_HOOK}\n");
+        let tidy =
+            "%1|claude|139|50|200|0|on|\n%2|sidebar|60|25|200|0|on|\n%3|terminal|60|24|200|0|on|\n";
+        let (fixes, _) = plan_layout("api", "cove:api", tidy, &hooks, 50).unwrap();
+        assert!(fixes.is_empty(), "{fixes:?}");
+        // A hook that takes an instant exit for a crash is upgraded
+        let stale = hooks.replace("#{pane_dead_signal}", "");
+        let (fixes, _) = plan_layout("api", "cove:api", tidy, &stale, 50).unwrap();
+        assert_eq!(fixes, ["pane-died hook"]);
+
+        // An old window: no roles, no hooks, squashed panes
+        let old = "%1||100|50|200|0|off|\n%2||99|10|200|0|off|\n%3||99|39|200|0|off|\n";
+        let (fixes, commands) = plan_layout("api", "cove:api", old, "", 50).unwrap();
+        assert_eq!(
+            fixes,
+            [
+                "remain-on-exit",
+                "pane-died hook",
+                "layout hook",
+                "claude pane role",
+                "sidebar pane role",
+                "terminal pane role",
+                "Claude pane width",
+                "sidebar height",
+            ]
+        );
+        let chain = commands.join(" ");
+        assert!(chain.contains("resize-pane -t %1 -x 140"), "{chain}");
+        assert!(chain.contains("resize-pane -t %2 -y 24"), "{chain}");
+        // ...to the configured sidebar height
+        let (_, commands) = plan_layout("api", "cove:api", old, "", 25).unwrap();
+        assert!(commands.join(" ").contains("resize-pane -t %2 -y 12"));
+
+        assert!(plan_layout("scratch", "cove:scratch", "%9||200|50|200|0|off|\n", "", 50).is_err());
+
+        // A preset's share of the width is kept
+        let even = "%1|claude|66|50|200|0|on|33\n%2|sidebar|66|50|200|0|on|33\n%3|terminal|66|50|200|0|on|33\n";
+        let (fixes, _) = plan_layout("api", "cove:api", even, &hooks, 50).unwrap();
+        assert!(fixes.is_empty(), "{fixes:?}");
+    }
+
+    #[test]
+    fn test_list_windows_flags_unmanaged() {
+        let fake = fake::FakeTmux::new()
+            .with_windows(&[("api", "claude", "%t1"), ("scratch", "zsh", "%t2")])
+            .unmanaged("scratch");
+        let managed: Vec<bool> = fake
+            .list_windows()
+            .unwrap()
+            .iter()
+            .map(|w| w.managed)
+            .collect();
+        assert_eq!(managed, [true, false]);
+    }
+
+    #[test]
+    fn test_adopt_window_needs_single_pane() {
+        let fake = fake::FakeTmux::new()
+            .with_windows(&[("api", "claude", "%t1"), ("scratch", "zsh", "%t2")])
+            .unmanaged("scratch");
+        assert!(fake.adopt_window("api", "/tmp", "cove sidebar").is_err());
+        assert!(fake.calls_to("split-window").is_empty());
+
+        fake.adopt_window("scratch", "/tmp", "cove sidebar")
+            .unwrap();
+        let layout = fake.calls_to("set-option");
+        assert!(layout[0].iter().any(|a| a == "@cove-layout"));
+    }
+
+    #[test]
+    fn test_send_text_starting_with_a_dash() {
+        let fake = fake::FakeTmux::new();
+        fake.send_text("%t1", "--help").unwrap();
+        assert_eq!(
+            fake.calls_to("send-keys"),
+            [["send-keys", "-t", "%t1", "-l", "--", "--help"]]
+        );
+    }
+
+    #[test]
+    fn test_send_command_starting_with_a_dash() {
+        let fake = fake::FakeTmux::new();
+        fake.send_command("%t2", "-x make").unwrap();
+        assert_eq!(
+            fake.calls_to("send-keys"),
+            [
+                vec!["send-keys", "-t", "%t2", "-l", "--", "-x make"],
+                vec!["send-keys", "-t", "%t2", "Enter"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_target_by_index_or_pane() {
+        let fake = fake::FakeTmux::new()
+            .with_windows(&[("api", "claude", "%t1"), ("web", "claude", "%t2")]);
+        assert_eq!(fake.resolve_target("2").unwrap(), "web");
+        assert_eq!(fake.resolve_target("%t1").unwrap(), "api");
+        assert_eq!(fake.resolve_target("docs").unwrap(), "docs");
+        assert!(fake.resolve_target("9").is_err());
+        assert!(fake.resolve_target("%t9").is_err());
+    }
+
+    #[test]
+    fn test_resolve_target_short_name() {
+        let fake = fake::FakeTmux::new().with_windows(&[
+            ("backend/fix-auth", "claude", "%t1"),
+            ("frontend/fix-auth", "claude", "%t2"),
+            ("api/docs", "claude", "%t3"),
+        ]);
+        assert_eq!(fake.resolve_target("docs").unwrap(), "api/docs");
+        assert_eq!(
+            fake.resolve_target("frontend/fix-auth").unwrap(),
+            "frontend/fix-auth"
+        );
+        assert!(fake.resolve_target("fix-auth").is_err());
+        assert_eq!(fake.resolve_target("%t2").unwrap(), "frontend/fix-auth");
+    }
 
-    if !status.success() {
-        return Err("tmux select-window failed".to_string());
+    #[test]
+    fn test_awkward_names_are_targeted_by_id() {
+        let fake = fake::FakeTmux::new()
+            .with_windows(&[("api", "claude", "%t1"), ("my.app", "claude", "%t2")]);
+        assert_eq!(fake.window_target("api").unwrap(), "cove:api");
+        assert_eq!(fake.window_target("my.app").unwrap(), "cove:@2");
+        assert_eq!(fake.get_claude_pane_id("my.app").unwrap(), "%t2");
+        assert!(fake.window_target("no.such").is_err());
     }
-    Ok(())
 }
@@ -0,0 +1,272 @@
+// ── tmux control mode ──
+//
+// `ControlTmux` keeps one `tmux -C` client attached to the cove session and
+// sends every command down it, so a long-running caller (`cove console`)
+// doesn't start a tmux process per call. The client attaches with
+// `no-output,ignore-size`: pane output isn't streamed to it, and it never
+// resizes the session's windows. Commands are written one per line in
+// tmux's own syntax; each reply is the `%begin … %end` (or `%error`) block
+// flagged as coming from this client, and notifications between blocks are
+// skipped.
+//
+// Without a cove session to attach to — or once the connection drops, say
+// because its last window was killed — calls go through the `tmux` CLI like
+// `Tmux`'s, and the next call tries to attach again. A command that reached
+// the client but got no reply isn't run again through the CLI: tmux may have
+// run it already, and typing a message twice is worse than an error.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use super::{SESSION, Tmux, TmuxClient, error, policy};
+
+// ── Types ──
+
+/// tmux commands over one control-mode connection.
+#[derive(Default)]
+pub struct ControlTmux {
+    conn: Mutex<Option<Conn>>,
+}
+
+struct Conn {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines from the client's stdout, read on their own thread.
+    lines: Receiver<String>,
+}
+
+/// Why a command on the connection got no reply.
+enum Lost {
+    /// It never reached tmux, so it can be run another way.
+    Unsent(String),
+    /// tmux may have run it; running it again could repeat it.
+    Sent(String),
+}
+
+// ── Helpers ──
+
+/// `arg` as one word for tmux's command parser: bare if it's plain, else
+/// single-quoted, else double-quoted with escapes (for `'` and control
+/// characters, which must not end the command line).
+pub(super) fn quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:@%=,+".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        return arg.to_string();
+    }
+    if !arg.contains('\'') && !arg.chars().any(char::is_control) {
+        return format!("'{arg}'");
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '\\' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            // `~` would expand to a home directory
+            c if c.is_control() || c == '~' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Read up to the reply to this client's command: Ok(output) for `%end`,
+/// Err(message) for `%error`. None if the connection ended first.
+fn read_reply(next: &mut dyn FnMut() -> Option<String>) -> Option<Result<String, String>> {
+    loop {
+        let line = next()?;
+        let Some(header) = line.strip_prefix("%begin ") else {
+            continue;
+        };
+        // "<time> <number> <flags>"; flags 1 marks this client's commands
+        let mut fields = header.split(' ');
+        let id = format!(
+            "{} {}",
+            fields.next().unwrap_or_default(),
+            fields.next().unwrap_or_default()
+        );
+        let ours = fields.next() == Some("1");
+        let mut body = String::new();
+        let done = loop {
+            let line = next()?;
+            if line
+                .strip_prefix("%end ")
+                .is_some_and(|rest| rest.starts_with(&id))
+            {
+                break true;
+            }
+            if line
+                .strip_prefix("%error ")
+                .is_some_and(|rest| rest.starts_with(&id))
+            {
+                break false;
+            }
+            body.push_str(&line);
+            body.push('\n');
+        };
+        if ours {
+            return Some(if done { Ok(body) } else { Err(body) });
+        }
+    }
+}
+
+impl Conn {
+    fn open() -> Result<Conn, String> {
+        let args = [
+            "-C",
+            "attach-session",
+            "-t",
+            SESSION,
+            "-f",
+            "no-output,ignore-size",
+        ];
+        let mut child = Command::new("tmux")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| error(&args[1..], e))?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(error(&args[1..], "no pipes to the control client"));
+        };
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        log::debug!("tmux control client attached");
+        Ok(Conn {
+            child,
+            stdin,
+            lines,
+        })
+    }
+
+    /// Run one command. The outer Err means the connection is gone.
+    fn run(&mut self, args: &[&str]) -> Result<Result<String, String>, Lost> {
+        let line: Vec<String> = args.iter().map(|a| quote(a)).collect();
+        writeln!(self.stdin, "{}", line.join(" "))
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| Lost::Unsent(error(args, e)))?;
+
+        let ms = policy().timeout_ms;
+        let mut timed_out = false;
+        let mut next = || {
+            let line = if ms > 0 {
+                self.lines.recv_timeout(Duration::from_millis(ms))
+            } else {
+                self.lines
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected)
+            };
+            timed_out = matches!(line, Err(RecvTimeoutError::Timeout));
+            line.ok()
+                .filter(|l| l != "%exit" && !l.starts_with("%exit "))
+        };
+        match read_reply(&mut next) {
+            Some(reply) => Ok(reply.map_err(|e| error(args, e.trim()))),
+            None if timed_out => Err(Lost::Sent(error(args, format!("no reply after {ms}ms")))),
+            None => Err(Lost::Sent(error(args, "control client exited"))),
+        }
+    }
+}
+
+impl Drop for Conn {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// ── Public API ──
+
+impl TmuxClient for ControlTmux {
+    fn output(&self, args: &[&str]) -> Result<String, String> {
+        log::trace!("tmux -C {}", args.join(" "));
+        let Ok(mut conn) = self.conn.lock() else {
+            return Tmux.output(args);
+        };
+        if conn.is_none() {
+            *conn = Conn::open().ok();
+        }
+        let Some(open) = conn.as_mut() else {
+            return Tmux.output(args);
+        };
+        match open.run(args) {
+            Ok(reply) => reply,
+            Err(Lost::Unsent(e)) => {
+                log::debug!("{e}; running commands through the CLI");
+                *conn = None;
+                Tmux.output(args)
+            }
+            Err(Lost::Sent(e)) => {
+                log::debug!("{e}; not running it again through the CLI");
+                *conn = None;
+                Err(e)
+            }
+        }
+    }
+
+    /// There's no terminal to hand over, so this reports whether the
+    /// command succeeded.
+    fn status(&self, args: &[&str]) -> Result<bool, String> {
+        Ok(self.output(args).is_ok())
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_for_command_line() {
+        assert_eq!(quote("send-keys"), "send-keys");
+        assert_eq!(quote("cove:api"), "cove:api");
+        assert_eq!(
+            quote("#{pane_id}|#{window_name}"),
+            "'#{pane_id}|#{window_name}'"
+        );
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("it's\n$HOME ~/x"), "\"it's\\n\\$HOME \\u007e/x\"");
+    }
+
+    #[test]
+    fn test_read_reply_skips_other_blocks_and_notifications() {
+        let lines = [
+            "%begin 1791984914 268 0",
+            "%end 1791984914 268 0",
+            "%session-changed $0 cove",
+            "%begin 1791984914 273 1",
+            "bash",
+            "api",
+            "%end 1791984914 273 1",
+            "%begin 1791984914 274 1",
+            "parse error: unknown command: bogus",
+            "%error 1791984914 274 1",
+        ];
+        let mut lines = lines.iter().map(|l| l.to_string());
+        let mut next = || lines.next();
+        assert_eq!(read_reply(&mut next), Some(Ok("bash\napi\n".to_string())));
+        assert_eq!(
+            read_reply(&mut next),
+            Some(Err("parse error: unknown command: bogus\n".to_string()))
+        );
+        assert_eq!(read_reply(&mut next), None);
+    }
+}
@@ -0,0 +1,181 @@
+// ── Scriptable tmux for tests ──
+//
+// `FakeTmux` answers each call by its tmux subcommand (the first argument) from
+// a table of canned responses and records every invocation. Unscripted
+// subcommands succeed with empty output, so tests only script what they read.
+// Windows added with `with_windows` are rendered through the caller's `-F`
+// format for `list-windows` / `list-panes`, like the real server.
+//
+// Test pane IDs use a `%t` prefix: code under test may purge state recorded
+// for a pane, and real tmux IDs are always `%<digits>`, so nothing real matches.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::TmuxClient;
+
+// ── Types ──
+
+#[derive(Default)]
+pub struct FakeTmux {
+    responses: HashMap<String, Result<String, String>>,
+    windows: Vec<FakeWindow>,
+    calls: RefCell<Vec<Vec<String>>>,
+}
+
+struct FakeWindow {
+    index: u32,
+    name: String,
+    active: bool,
+    /// Foreground command of the Claude pane (.1).
+    command: String,
+    /// pane_id of the Claude pane.
+    pane_id: String,
+    /// Has the cove layout (three panes) rather than a single pane.
+    managed: bool,
+}
+
+// ── Helpers ──
+
+impl FakeWindow {
+    fn render(&self, format: &str) -> String {
+        format
+            .replace("#{window_id}", &format!("@{}", self.index))
+            .replace("#{window_index}", &self.index.to_string())
+            .replace("#{window_name}", &self.name)
+            .replace("#{window_active}", if self.active { "1" } else { "0" })
+            .replace("#{pane_current_path}", &format!("/code/{}", self.name))
+            .replace("#{pane_index}", "1")
+            .replace("#{@cove-role}", if self.managed { "claude" } else { "" })
+            .replace("#{pane_current_command}", &self.command)
+            .replace("#{pane_id}", &self.pane_id)
+            .replace("#{pane_pid}", &(1000 + self.index).to_string())
+            .replace("#{window_panes}", if self.managed { "3" } else { "1" })
+            .replace("#{@cove-layout}", if self.managed { "1" } else { "" })
+            .replace("#{pane_dead}", "0")
+            .replace("#{@cove-failed}", "")
+            .replace("#{@cove-parked}", "")
+            .replace("#{@cove-pair}", "")
+    }
+}
+
+// ── Public API ──
+
+impl FakeTmux {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer `subcommand` with `stdout`.
+    pub fn on(mut self, subcommand: &str, stdout: &str) -> Self {
+        self.responses
+            .insert(subcommand.to_string(), Ok(stdout.to_string()));
+        self
+    }
+
+    /// Make `subcommand` fail with `stderr`.
+    pub fn fail(mut self, subcommand: &str, stderr: &str) -> Self {
+        self.responses.insert(
+            subcommand.to_string(),
+            Err(super::error(&[subcommand], stderr)),
+        );
+        self
+    }
+
+    /// A session with the given `(name, claude pane command, pane_id)` windows,
+    /// indexed from 1; the first window is active.
+    pub fn with_windows(mut self, windows: &[(&str, &str, &str)]) -> Self {
+        self.windows = windows
+            .iter()
+            .enumerate()
+            .map(|(i, (name, command, pane_id))| FakeWindow {
+                index: i as u32 + 1,
+                name: name.to_string(),
+                active: i == 0,
+                command: command.to_string(),
+                pane_id: pane_id.to_string(),
+                managed: true,
+            })
+            .collect();
+        self
+    }
+
+    /// Mark window `name` as opened by hand (one pane, no cove layout).
+    pub fn unmanaged(mut self, name: &str) -> Self {
+        for w in self.windows.iter_mut().filter(|w| w.name == name) {
+            w.managed = false;
+        }
+        self
+    }
+
+    /// Every call so far, as argument lists.
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.borrow().clone()
+    }
+
+    /// Calls whose subcommand is `subcommand`.
+    pub fn calls_to(&self, subcommand: &str) -> Vec<Vec<String>> {
+        self.calls()
+            .into_iter()
+            .filter(|c| c.first().is_some_and(|s| s == subcommand))
+            .collect()
+    }
+
+    fn respond(&self, args: &[&str]) -> Result<String, String> {
+        self.calls
+            .borrow_mut()
+            .push(args.iter().map(|a| a.to_string()).collect());
+        let subcommand = args.first().copied().unwrap_or("");
+        if let Some(response) = self.responses.get(subcommand) {
+            return response.clone();
+        }
+        match (subcommand, args.iter().position(|a| *a == "-F")) {
+            ("list-windows" | "list-panes", Some(i)) => {
+                let format = args.get(i + 1).copied().unwrap_or("");
+                Ok(self
+                    .windows
+                    .iter()
+                    .map(|w| w.render(format) + "\n")
+                    .collect())
+            }
+            // `display-message -t cove:<name or id>.1 -p <format>` resolves the Claude
+            // pane; `-t <pane_id>` targets it directly
+            ("display-message", _) => {
+                let arg_after = |flag: &str| {
+                    let i = args.iter().position(|a| *a == flag)?;
+                    args.get(i + 1).copied()
+                };
+                let target = arg_after("-t").unwrap_or("");
+                let name = target
+                    .strip_prefix(&format!("{}:", super::SESSION))
+                    .and_then(|t| t.strip_suffix(".1"));
+                let window = self.windows.iter().find(|w| {
+                    target == w.pane_id
+                        || name.is_some_and(|n| n == w.name || n == format!("@{}", w.index))
+                });
+                match window {
+                    Some(w) => Ok(w.render(arg_after("-p").unwrap_or(""))),
+                    None => Err(super::error(args, format!("can't find pane: {target}"))),
+                }
+            }
+            _ => Ok(String::new()),
+        }
+    }
+}
+
+impl TmuxClient for FakeTmux {
+    fn output(&self, args: &[&str]) -> Result<String, String> {
+        self.respond(args)
+    }
+
+    fn status(&self, args: &[&str]) -> Result<bool, String> {
+        Ok(self.respond(args).is_ok())
+    }
+
+    /// Logged as a `record-options <name> <options...>` call.
+    fn record_options(&self, name: &str, options: &[&str]) {
+        let mut call = vec!["record-options".to_string(), name.to_string()];
+        call.extend(options.iter().map(|o| o.to_string()));
+        self.calls.borrow_mut().push(call);
+    }
+}
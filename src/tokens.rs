@@ -0,0 +1,274 @@
+// ── Token spend per Claude session ──
+//
+// Claude Code keeps each conversation at
+// ~/.claude/projects/<project>/<session_id>.jsonl; assistant lines carry the
+// API `usage` for that turn. A session's spend is input + output + cache
+// writes (cache reads are left out: they're cheap and would dwarf the rest).
+// Streaming repeats a message's usage on every content block, so each message
+// id counts once.
+//
+// Transcripts grow to many megabytes, so they're never read twice: each
+// session's byte offset and running totals are kept under usage/ in the state
+// dir, and every count only parses the lines appended since. The store is
+// shared, so a new `cove top` or sidebar picks up where the last one stopped.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{paths, transcript};
+
+// ── Types ──
+
+/// Token totals by kind.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Usage {
+    pub input: u64,
+    pub output: u64,
+    pub cache_creation: u64,
+    pub cache_read: u64,
+}
+
+/// What has been counted of one transcript, as saved in the store.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Tally {
+    path: PathBuf,
+    /// Bytes of the transcript already counted (always at a line boundary).
+    offset: u64,
+    usage: Usage,
+    /// Ids of the latest messages; repeats of a message's usage come one
+    /// right after another, so a short history is enough to skip them.
+    recent: VecDeque<String>,
+}
+
+/// Running token totals, keyed by Claude session id.
+pub struct TokenCounter {
+    /// Where tallies are saved; None keeps them in memory only.
+    store: Option<PathBuf>,
+    tallies: HashMap<String, Tally>,
+}
+
+// ── Constants ──
+
+const STORE_DIR: &str = "usage";
+
+/// Message ids remembered per transcript for skipping repeated usage.
+const RECENT_IDS: usize = 16;
+
+// ── Helpers ──
+
+impl Usage {
+    /// Tokens one transcript line adds.
+    fn from_line(value: &serde_json::Value) -> Self {
+        let usage = &value["message"]["usage"];
+        let get = |key: &str| usage[key].as_u64().unwrap_or(0);
+        Self {
+            input: get("input_tokens"),
+            output: get("output_tokens"),
+            cache_creation: get("cache_creation_input_tokens"),
+            cache_read: get("cache_read_input_tokens"),
+        }
+    }
+
+    fn add(&mut self, other: Usage) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_creation += other.cache_creation;
+        self.cache_read += other.cache_read;
+    }
+}
+
+impl Tally {
+    /// Count one transcript line, unless its message was already counted.
+    fn add_line(&mut self, line: &str) {
+        if !line.contains("\"usage\"") {
+            return;
+        }
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            return;
+        };
+        if let Some(id) = value["message"]["id"].as_str() {
+            if self.recent.iter().any(|seen| seen == id) {
+                return;
+            }
+            if self.recent.len() == RECENT_IDS {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(id.to_string());
+        }
+        self.usage.add(Usage::from_line(&value));
+    }
+
+    /// Count the lines appended since the last update. Returns true if
+    /// anything new was read.
+    fn update(&mut self) -> bool {
+        let before = self.offset;
+        // A shorter file was rewritten: start over
+        if fs::metadata(&self.path).is_ok_and(|m| m.len() < self.offset) {
+            *self = Tally {
+                path: std::mem::take(&mut self.path),
+                ..Tally::default()
+            };
+        }
+        let (path, mut offset) = (self.path.clone(), self.offset);
+        transcript::read_appended(&path, &mut offset, |line| self.add_line(line));
+        self.offset = offset;
+        self.offset != before
+    }
+}
+
+fn store_file(store: &Path, session_id: &str) -> PathBuf {
+    store.join(format!("{session_id}.json"))
+}
+
+fn load_tally(store: &Path, session_id: &str) -> Option<Tally> {
+    let content = fs::read_to_string(store_file(store, session_id)).ok()?;
+    let tally: Tally = serde_json::from_str(&content).ok()?;
+    tally.path.is_file().then_some(tally)
+}
+
+/// Write through a temp file so a reader never sees half a tally.
+fn save_tally(store: &Path, session_id: &str, tally: &Tally) {
+    let Ok(content) = serde_json::to_string(tally) else {
+        return;
+    };
+    if fs::create_dir_all(store).is_err() {
+        return;
+    }
+    let path = store_file(store, session_id);
+    let tmp = path.with_extension(format!("json.{}", std::process::id()));
+    if fs::write(&tmp, content).is_ok() && fs::rename(&tmp, &path).is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+}
+
+// ── Public API ──
+
+impl TokenCounter {
+    /// A counter backed by the shared store in the state dir.
+    pub fn new() -> Self {
+        Self::in_store(paths::cove_path(STORE_DIR))
+    }
+
+    fn in_store(store: Option<PathBuf>) -> Self {
+        Self {
+            store,
+            tallies: HashMap::new(),
+        }
+    }
+
+    /// Token totals of a Claude session so far, or None if its transcript
+    /// can't be found.
+    pub fn usage(&mut self, session_id: &str) -> Option<Usage> {
+        if !self.tallies.contains_key(session_id) {
+            let stored = self
+                .store
+                .as_deref()
+                .and_then(|store| load_tally(store, session_id));
+            let tally = match stored {
+                Some(tally) => tally,
+                None => Tally {
+                    path: transcript::path(session_id)?,
+                    ..Tally::default()
+                },
+            };
+            self.tallies.insert(session_id.to_string(), tally);
+        }
+        let tally = self.tallies.get_mut(session_id)?;
+        if tally.update()
+            && let Some(store) = &self.store
+        {
+            save_tally(store, session_id, tally);
+        }
+        Some(tally.usage)
+    }
+
+    /// Tokens spent so far in a Claude session: input, output and cache
+    /// writes.
+    pub fn count(&mut self, session_id: &str) -> Option<u64> {
+        self.usage(session_id)
+            .map(|u| u.input + u.output + u.cache_creation)
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+
+    fn turn(id: &str, out: u64) -> String {
+        format!(
+            r#"{{"type":"assistant","message":{{"id":"{id}","usage":{{"input_tokens":10,"output_tokens":{out},"cache_creation_input_tokens":100,"cache_read_input_tokens":5000}}}}}}"#
+        )
+    }
+
+    #[test]
+    fn test_counts_each_message_once_incrementally() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("-code-api");
+        fs::create_dir(&project).unwrap();
+        let path = project.join("abc.jsonl");
+        let mut f = File::create(&path).unwrap();
+        writeln!(f, r#"{{"type":"user","message":{{"content":"hi"}}}}"#).unwrap();
+        writeln!(f, "{}", turn("m1", 5)).unwrap();
+        writeln!(f, "{}", turn("m1", 5)).unwrap();
+
+        assert_eq!(transcript::find(dir.path(), "abc"), Some(path.clone()));
+        let mut tally = Tally {
+            path: path.clone(),
+            ..Tally::default()
+        };
+        assert!(tally.update());
+        assert_eq!(tally.usage.output, 5);
+        assert_eq!(tally.usage.cache_read, 5000);
+
+        // An unfinished line waits for the next update
+        write!(f, "{}", turn("m2", 20)).unwrap();
+        assert!(!tally.update());
+        writeln!(f).unwrap();
+        assert!(tally.update());
+        assert_eq!(
+            tally.usage,
+            Usage {
+                input: 20,
+                output: 25,
+                cache_creation: 200,
+                cache_read: 10000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_store_resumes_from_saved_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abc.jsonl");
+        let store = dir.path().join("usage");
+        fs::write(&path, format!("{}\n", turn("m1", 5))).unwrap();
+        let tally = Tally {
+            path: path.clone(),
+            ..Tally::default()
+        };
+        let mut counter = TokenCounter::in_store(Some(store.clone()));
+        counter.tallies.insert("abc".to_string(), tally);
+        assert_eq!(counter.count("abc"), Some(115));
+
+        // A new counter reads only what was appended since the saved offset
+        let mut f = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(f, "{}", turn("m2", 20)).unwrap();
+        let saved = load_tally(&store, "abc").unwrap();
+        assert!(saved.offset > 0);
+        let mut fresh = TokenCounter::in_store(Some(store.clone()));
+        assert_eq!(fresh.count("abc"), Some(245));
+
+        // A rewritten transcript is counted from the start again
+        fs::write(&path, format!("{}\n", turn("m3", 1))).unwrap();
+        assert_eq!(fresh.count("abc"), Some(111));
+    }
+}
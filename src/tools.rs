@@ -0,0 +1,237 @@
+// ── Tool usage tracking ──
+//
+// `cove hook pre-tool` / `cove hook post-tool` (PreToolUse/PostToolUse, matcher `*`)
+// append start/end records to ~/.cove/tools/{session_id}.jsonl. The sidebar reads
+// the last record per pane: a "start" without a matching "end" means the tool
+// is still running, shown as e.g. "Bash (cargo test)" next to the spinner.
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::sidebar::state::read_last_line;
+use crate::{paths, width};
+
+// ── Types ──
+
+#[derive(Serialize, Deserialize)]
+struct ToolEntry {
+    tool: String,
+    #[serde(default)]
+    detail: String,
+    /// "start" (PreToolUse) or "end" (PostToolUse).
+    phase: String,
+    #[serde(default)]
+    pane_id: String,
+    ts: u64,
+}
+
+/// A tool call that has started but not finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningTool {
+    pub tool: String,
+    pub detail: String,
+    pub since: u64,
+}
+
+impl RunningTool {
+    /// Compact label, e.g. "Bash (cargo test)".
+    pub fn label(&self) -> String {
+        if self.detail.is_empty() {
+            self.tool.clone()
+        } else {
+            format!("{} ({})", self.tool, self.detail)
+        }
+    }
+}
+
+// ── Constants ──
+
+const DETAIL_MAX: usize = 40;
+
+// ── Helpers ──
+
+fn tools_dir() -> Option<PathBuf> {
+    paths::cove_path("tools")
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    let first_line = s.lines().next().unwrap_or("").trim();
+    width::truncate(first_line, max)
+}
+
+/// Pick the most telling input field for a tool call.
+pub fn summarize(tool: &str, input: &Value) -> String {
+    let field = |k: &str| input[k].as_str().unwrap_or("");
+    let detail = match tool {
+        "Bash" => field("command").to_string(),
+        "Read" | "Edit" | "Write" | "MultiEdit" => Path::new(field("file_path"))
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        "NotebookEdit" => Path::new(field("notebook_path"))
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        "Grep" | "Glob" => field("pattern").to_string(),
+        "WebFetch" => field("url").to_string(),
+        "WebSearch" => field("query").to_string(),
+        "Task" => field("description").to_string(),
+        _ => String::new(),
+    };
+    truncate(&detail, DETAIL_MAX)
+}
+
+/// The newest record per pane.
+fn latest_in(dir: &Path) -> HashMap<String, ToolEntry> {
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return HashMap::new(),
+    };
+
+    let mut best: HashMap<String, ToolEntry> = HashMap::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Some(line) = read_last_line(&path) else {
+            continue;
+        };
+        let Ok(rec) = serde_json::from_str::<ToolEntry>(&line) else {
+            continue;
+        };
+        if rec.pane_id.is_empty() {
+            continue;
+        }
+        let replace = best.get(&rec.pane_id).is_none_or(|prev| rec.ts >= prev.ts);
+        if replace {
+            best.insert(rec.pane_id.clone(), rec);
+        }
+    }
+    best
+}
+
+/// Report each pane's newest record only if it's a start.
+fn load_running_in(dir: &Path) -> HashMap<String, RunningTool> {
+    latest_in(dir)
+        .into_iter()
+        .filter(|(_, rec)| rec.phase == "start")
+        .map(|(pane, rec)| {
+            (
+                pane,
+                RunningTool {
+                    tool: rec.tool,
+                    detail: rec.detail,
+                    since: rec.ts,
+                },
+            )
+        })
+        .collect()
+}
+
+// ── Public API ──
+
+/// Append a tool start/end record for a Claude session.
+pub fn record(
+    session_id: &str,
+    pane_id: &str,
+    phase: &str,
+    tool: &str,
+    input: &Value,
+) -> Result<(), String> {
+    let dir = paths::ensure_cove_dir("tools")?;
+
+    let path = dir.join(format!("{session_id}.jsonl"));
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("open tools file: {e}"))?;
+
+    let entry = ToolEntry {
+        tool: tool.to_string(),
+        detail: summarize(tool, input),
+        phase: phase.to_string(),
+        pane_id: pane_id.to_string(),
+        ts: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let line = serde_json::to_string(&entry).map_err(|e| format!("serialize tool: {e}"))?;
+    writeln!(f, "{line}").map_err(|e| format!("write tool: {e}"))
+}
+
+/// When each pane last started or finished a tool call, keyed by pane_id.
+pub fn last_activity() -> HashMap<String, u64> {
+    tools_dir()
+        .map(|dir| latest_in(&dir))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(pane, rec)| (pane, rec.ts))
+        .collect()
+}
+
+/// Tools currently running, keyed by pane_id.
+pub fn running() -> HashMap<String, RunningTool> {
+    tools_dir()
+        .map(|dir| load_running_in(&dir))
+        .unwrap_or_default()
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let input = serde_json::json!({"command": "cargo test\n--release"});
+        assert_eq!(summarize("Bash", &input), "cargo test");
+
+        let input = serde_json::json!({"file_path": "/repo/src/main.rs"});
+        assert_eq!(summarize("Edit", &input), "main.rs");
+
+        assert_eq!(summarize("TodoWrite", &serde_json::json!({})), "");
+    }
+
+    #[test]
+    fn test_truncate_long_detail() {
+        let long = "x".repeat(60);
+        let out = truncate(&long, 10);
+        assert_eq!(out.chars().count(), 10);
+        assert!(out.ends_with('\u{2026}'));
+    }
+
+    #[test]
+    fn test_load_running() {
+        let dir = tempfile::tempdir().unwrap();
+        // Pane %1: Bash started, not finished → running
+        fs::write(
+            dir.path().join("a.jsonl"),
+            r#"{"tool":"Read","detail":"a.rs","phase":"start","pane_id":"%1","ts":10}
+{"tool":"Read","detail":"a.rs","phase":"end","pane_id":"%1","ts":11}
+{"tool":"Bash","detail":"cargo test","phase":"start","pane_id":"%1","ts":12}
+"#,
+        )
+        .unwrap();
+        // Pane %2: last tool finished → nothing running
+        fs::write(
+            dir.path().join("b.jsonl"),
+            r#"{"tool":"Grep","detail":"foo","phase":"start","pane_id":"%2","ts":20}
+{"tool":"Grep","detail":"foo","phase":"end","pane_id":"%2","ts":21}
+"#,
+        )
+        .unwrap();
+
+        let running = load_running_in(dir.path());
+        assert_eq!(running.len(), 1);
+        assert_eq!(running["%1"].label(), "Bash (cargo test)");
+    }
+}
@@ -0,0 +1,280 @@
+// ── Claude transcripts ──
+//
+// Claude Code keeps each conversation at
+// ~/.claude/projects/<project>/<session_id>.jsonl, one JSON object per line.
+// User and assistant lines carry a `message` whose `content` is a string or a
+// list of blocks (text, thinking, tool_use, tool_result). The sidebar's
+// transcript view shows the text blocks plus a one-line summary per tool call;
+// thinking and tool output are left out.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::{paths, tools, width};
+
+// ── Types ──
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    User,
+    Assistant,
+    /// A tool call Claude made.
+    Tool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub role: Role,
+    pub text: String,
+}
+
+/// One display row of a transcript laid out for a given width.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub role: Role,
+    pub text: String,
+    /// First row of an entry, which starts with the role label.
+    pub first: bool,
+}
+
+// ── Helpers ──
+
+fn text_entry(role: Role, text: &str) -> Option<Entry> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| Entry {
+        role,
+        text: text.to_string(),
+    })
+}
+
+fn parse_line(line: &str) -> Vec<Entry> {
+    let Ok(value) = serde_json::from_str::<Value>(line) else {
+        return Vec::new();
+    };
+    let role = match value["type"].as_str() {
+        Some("user") => Role::User,
+        Some("assistant") => Role::Assistant,
+        _ => return Vec::new(),
+    };
+    // Injected context (slash command expansions, reminders), not typed by anyone
+    if value["isMeta"].as_bool() == Some(true) {
+        return Vec::new();
+    }
+    let content = &value["message"]["content"];
+    if let Some(text) = content.as_str() {
+        return text_entry(role, text).into_iter().collect();
+    }
+    content
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|block| match block["type"].as_str()? {
+            "text" => text_entry(role, block["text"].as_str()?),
+            "tool_use" => {
+                let tool = block["name"].as_str()?;
+                let detail = tools::summarize(tool, &block["input"]);
+                let text = if detail.is_empty() {
+                    tool.to_string()
+                } else {
+                    format!("{tool} ({detail})")
+                };
+                Some(Entry {
+                    role: Role::Tool,
+                    text,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Split `text` into rows of at most `width` columns, breaking at spaces
+/// where possible.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    for para in text.lines() {
+        let mut row = String::new();
+        let mut len = 0;
+        for word in para.split(' ') {
+            let word_len = width::of(word);
+            if len > 0 && len + 1 + word_len > width {
+                rows.push(std::mem::take(&mut row));
+                len = 0;
+            }
+            if len > 0 {
+                row.push(' ');
+                len += 1;
+            }
+            // Words longer than a row are cut wherever the row ends, never
+            // inside a wide character
+            for c in word.chars() {
+                let w = width::of_char(c);
+                if len > 0 && len + w > width {
+                    rows.push(std::mem::take(&mut row));
+                    len = 0;
+                }
+                row.push(c);
+                len += w;
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+// ── Public API ──
+
+pub fn find(projects: &Path, session_id: &str) -> Option<PathBuf> {
+    let file = format!("{session_id}.jsonl");
+    fs::read_dir(projects)
+        .ok()?
+        .flatten()
+        .map(|project| project.path().join(&file))
+        .find(|path| path.is_file())
+}
+
+/// Where Claude keeps the transcript of `session_id`, if it exists.
+pub fn path(session_id: &str) -> Option<PathBuf> {
+    find(&paths::claude_dir()?.join("projects"), session_id)
+}
+
+pub fn parse(content: &str) -> Vec<Entry> {
+    content.lines().flat_map(parse_line).collect()
+}
+
+/// The conversation in Claude session `session_id`.
+pub fn load(session_id: &str) -> Result<Vec<Entry>, String> {
+    let path = path(session_id).ok_or_else(|| format!("no transcript for {session_id}"))?;
+    let content = fs::read_to_string(&path).map_err(|e| format!("read {}: {e}", path.display()))?;
+    Ok(parse(&content))
+}
+
+/// Hand each complete line written to `path` after byte `offset` to
+/// `on_line`, moving `offset` past it. A line Claude is still writing is left
+/// for the next call, so `offset` always sits at a line boundary and
+/// transcripts of any size cost only what was appended. Callers keep the
+/// offset (with whatever they derived from the lines) and start over from 0
+/// when the file is shorter than it, meaning it was rewritten.
+pub fn read_appended(path: &Path, offset: &mut u64, mut on_line: impl FnMut(&str)) {
+    let Ok(mut file) = File::open(path) else {
+        return;
+    };
+    if file.seek(SeekFrom::Start(*offset)).is_err() {
+        return;
+    }
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    while let Ok(n) = reader.read_line(&mut line) {
+        if n == 0 || !line.ends_with('\n') {
+            break;
+        }
+        *offset += n as u64;
+        on_line(&line);
+        line.clear();
+    }
+}
+
+/// Lay entries out as rows of `width` cells, a blank row between entries.
+/// Rows after an entry's first are indented under its label.
+pub fn layout(entries: &[Entry], width: u16, label: impl Fn(Role) -> &'static str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if i > 0 && entry.role != Role::Tool {
+            lines.push(Line {
+                role: entry.role,
+                text: String::new(),
+                first: false,
+            });
+        }
+        let label = label(entry.role);
+        let indent = width::of(label) + 1;
+        let rows = wrap(&entry.text, (width as usize).saturating_sub(indent));
+        for (j, row) in rows.into_iter().enumerate() {
+            let text = if j == 0 {
+                format!("{label} {row}")
+            } else {
+                format!("{}{row}", " ".repeat(indent))
+            };
+            lines.push(Line {
+                role: entry.role,
+                text,
+                first: j == 0,
+            });
+        }
+    }
+    lines
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keeps_text_and_tool_calls() {
+        let content = [
+            r#"{"type":"summary","summary":"Fix tests"}"#,
+            r#"{"type":"user","message":{"role":"user","content":"Fix the failing test"}}"#,
+            r#"{"type":"user","isMeta":true,"message":{"content":"<command-name>/clear</command-name>"}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"thinking","thinking":"hmm"}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Running them first."}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Bash","input":{"command":"cargo test"}}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"ok"}]}}"#,
+            "not json",
+        ]
+        .join("\n");
+        assert_eq!(
+            parse(&content),
+            vec![
+                Entry {
+                    role: Role::User,
+                    text: "Fix the failing test".to_string()
+                },
+                Entry {
+                    role: Role::Assistant,
+                    text: "Running them first.".to_string()
+                },
+                Entry {
+                    role: Role::Tool,
+                    text: "Bash (cargo test)".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("one two three", 7), ["one two", "three"]);
+        assert_eq!(wrap("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(wrap("a\n\nb", 5), ["a", "", "b"]);
+        // Two columns each: a row of 5 holds two
+        assert_eq!(wrap("修正したバグ", 5), ["修正", "した", "バグ"]);
+        assert_eq!(wrap("直す bug", 6), ["直す", "bug"]);
+    }
+
+    #[test]
+    fn test_layout_indents_under_label() {
+        let entries = [
+            Entry {
+                role: Role::User,
+                text: "hello there".to_string(),
+            },
+            Entry {
+                role: Role::Tool,
+                text: "Read".to_string(),
+            },
+        ];
+        let lines = layout(&entries, 10, |role| match role {
+            Role::Tool => "*",
+            _ => ">",
+        });
+        let text: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(text, ["> hello", "  there", "* Read"]);
+        assert!(lines[0].first && !lines[1].first && lines[2].first);
+    }
+}
@@ -0,0 +1,190 @@
+// ── Killed sessions, kept for undo ──
+//
+// `cove kill` moves what it needs to bring a session back into
+// ~/.cove/trash/: `{name}.json` (directory, Claude session id, saved clips)
+// and `{name}.txt` (the Claude pane's last screen). `cove undo` recreates the
+// window from the newest entry still inside the grace period; older entries
+// are dropped whenever the trash is touched.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{names, paths};
+
+// ── Types ──
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub name: String,
+    pub dir: String,
+    /// Claude's session id, for `claude --resume`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude_session: Option<String>,
+    /// The session's saved clips, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clips: Vec<String>,
+    pub killed_at: u64,
+}
+
+impl TrashEntry {
+    pub fn new(name: &str, dir: &str, claude_session: Option<String>, clips: Vec<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            dir: dir.to_string(),
+            claude_session,
+            clips,
+            killed_at: now(),
+        }
+    }
+}
+
+// ── Constants ──
+
+/// How long a killed session can be brought back.
+pub const UNDO_GRACE_SECS: u64 = 15 * 60;
+
+// ── Helpers ──
+
+fn trash_dir() -> Option<PathBuf> {
+    paths::cove_path("trash")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Trash file of session `name` in `dir`, with extension `ext`.
+fn file_in(dir: &Path, name: &str, ext: &str) -> PathBuf {
+    dir.join(format!("{}.{ext}", names::file_stem(name)))
+}
+
+fn remove_in(dir: &Path, name: &str) {
+    let _ = fs::remove_file(file_in(dir, name, "json"));
+    let _ = fs::remove_file(file_in(dir, name, "txt"));
+}
+
+/// Entries still inside the grace period at `now`, newest first. Expired
+/// entries are deleted along with their captures.
+fn entries_in(dir: &Path, now: u64) -> Vec<TrashEntry> {
+    let Ok(files) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    for file in files.flatten() {
+        let path = file.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(entry) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<TrashEntry>(&c).ok())
+        else {
+            continue;
+        };
+        if now.saturating_sub(entry.killed_at) > UNDO_GRACE_SECS {
+            remove_in(dir, &entry.name);
+            continue;
+        }
+        entries.push(entry);
+    }
+    entries.sort_by_key(|e| std::cmp::Reverse(e.killed_at));
+    entries
+}
+
+// ── Public API ──
+
+/// The trash directory, created if needed.
+pub fn dir() -> Result<PathBuf, String> {
+    paths::ensure_cove_dir("trash")
+}
+
+/// Keep a killed session and its last screen for `cove undo`, replacing any
+/// earlier entry of the same name.
+pub fn save_in(dir: &Path, entry: &TrashEntry, capture: &str) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("create trash dir: {e}"))?;
+    let content =
+        serde_json::to_string_pretty(entry).map_err(|e| format!("serialize trash: {e}"))?;
+    fs::write(file_in(dir, &entry.name, "txt"), capture)
+        .map_err(|e| format!("write capture: {e}"))?;
+    fs::write(file_in(dir, &entry.name, "json"), content).map_err(|e| format!("write trash: {e}"))
+}
+
+/// Killed sessions that can still be restored, newest first.
+pub fn entries() -> Vec<TrashEntry> {
+    trash_dir()
+        .map(|dir| entries_in(&dir, now()))
+        .unwrap_or_default()
+}
+
+/// Where a trashed session's last screen is kept.
+pub fn capture_path(name: &str) -> Option<PathBuf> {
+    trash_dir().map(|dir| file_in(&dir, name, "txt"))
+}
+
+/// Forget a trashed session (after it's been restored).
+pub fn remove(name: &str) {
+    if let Some(dir) = trash_dir() {
+        remove_in(&dir, name);
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, killed_at: u64) -> TrashEntry {
+        TrashEntry {
+            name: name.to_string(),
+            dir: "/code/api".to_string(),
+            claude_session: Some("abc-123".to_string()),
+            clips: vec!["src/lib.rs".to_string()],
+            killed_at,
+        }
+    }
+
+    #[test]
+    fn test_entries_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        save_in(dir.path(), &entry("api", 1000), "screen").unwrap();
+        save_in(dir.path(), &entry("web", 1100), "").unwrap();
+
+        let entries = entries_in(dir.path(), 1200);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["web", "api"]);
+        assert_eq!(entries[1], entry("api", 1000));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("api.txt")).unwrap(),
+            "screen"
+        );
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        save_in(dir.path(), &entry("api", 1000), "screen").unwrap();
+
+        assert!(entries_in(dir.path(), 1000 + UNDO_GRACE_SECS + 1).is_empty());
+        assert!(!dir.path().join("api.json").exists());
+        assert!(!dir.path().join("api.txt").exists());
+    }
+
+    #[test]
+    fn test_tagged_names_stay_in_the_trash_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        save_in(dir.path(), &entry("work/api", 1000), "screen").unwrap();
+        assert!(dir.path().join("work%2Fapi.txt").exists());
+
+        let entries = entries_in(dir.path(), 1100);
+        assert_eq!(entries[0].name, "work/api");
+        remove_in(dir.path(), "work/api");
+        assert!(entries_in(dir.path(), 1100).is_empty());
+    }
+}
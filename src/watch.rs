@@ -0,0 +1,135 @@
+// ── Re-runners attached to sessions ──
+//
+// `cove watch-run <name> -- <cmd>` runs in a session's terminal pane and re-runs
+// `cmd` each time Claude finishes a turn (Working → Idle). The latest result is
+// kept in ~/.cove/watch/{pane_id}.json, keyed by the Claude pane, and shown as a
+// badge next to the session in the sidebar.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+use crate::sidebar::state::WindowState;
+
+// ── Types ──
+
+/// Outcome of the most recent run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchStatus {
+    Running,
+    Passed,
+    /// Non-zero exit code (-1 when killed by a signal).
+    Failed(i32),
+}
+
+#[derive(Serialize, Deserialize)]
+struct WatchEntry {
+    cmd: String,
+    status: WatchStatus,
+    ts: u64,
+}
+
+// ── Helpers ──
+
+fn watch_dir() -> Option<PathBuf> {
+    paths::cove_path("watch")
+}
+
+fn load_results_in(dir: &Path) -> HashMap<String, WatchStatus> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    let mut out = HashMap::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(pane_id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(rec) = serde_json::from_str::<WatchEntry>(&content) {
+            out.insert(pane_id, rec.status);
+        }
+    }
+    out
+}
+
+// ── Public API ──
+
+/// True when a session just finished a turn, i.e. the watched command is due.
+pub fn is_turn_end(prev: WindowState, now: WindowState) -> bool {
+    prev == WindowState::Working && now == WindowState::Idle
+}
+
+/// Store the latest result for the session whose Claude pane is `pane_id`.
+pub fn record(pane_id: &str, cmd: &str, status: WatchStatus) -> Result<(), String> {
+    let dir = paths::ensure_cove_dir("watch")?;
+    let entry = WatchEntry {
+        cmd: cmd.to_string(),
+        status,
+        ts: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let content = serde_json::to_string(&entry).map_err(|e| format!("serialize watch: {e}"))?;
+    fs::write(dir.join(format!("{pane_id}.json")), content).map_err(|e| format!("write watch: {e}"))
+}
+
+/// Latest watch result per Claude pane_id.
+pub fn results() -> HashMap<String, WatchStatus> {
+    watch_dir()
+        .map(|dir| load_results_in(&dir))
+        .unwrap_or_default()
+}
+
+/// Forget the result for `pane_id` (called when a pane ID is recycled).
+pub fn purge_for_pane(pane_id: &str) {
+    if let Some(dir) = watch_dir() {
+        let _ = fs::remove_file(dir.join(format!("{pane_id}.json")));
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_turn_end() {
+        assert!(is_turn_end(WindowState::Working, WindowState::Idle));
+        assert!(!is_turn_end(WindowState::Idle, WindowState::Idle));
+        assert!(!is_turn_end(WindowState::Working, WindowState::Asking));
+        assert!(!is_turn_end(WindowState::Fresh, WindowState::Idle));
+    }
+
+    #[test]
+    fn test_load_results() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("%1.json"),
+            r#"{"cmd":"cargo check","status":"passed","ts":10}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("%2.json"),
+            r#"{"cmd":"cargo test","status":{"failed":101},"ts":11}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("%3.json"), "not json").unwrap();
+
+        let results = load_results_in(dir.path());
+        assert_eq!(results.len(), 2);
+        assert_eq!(results["%1"], WatchStatus::Passed);
+        assert_eq!(results["%2"], WatchStatus::Failed(101));
+    }
+}
@@ -0,0 +1,103 @@
+// ── Display width ──
+//
+// Terminals draw most CJK characters and emoji two cells wide, and combining
+// marks in none, so neither `len()` (bytes) nor `chars().count()` says how
+// many columns a session name or path takes. Everything that lines text up
+// in columns — the sidebar's rows, `cove list`, the transcript view — measures,
+// cuts and pads through these instead. A cut never splits a wide character:
+// one that doesn't fit is dropped, leaving the text a column short.
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// ── Constants ──
+
+const ELLIPSIS: char = '\u{2026}';
+
+// ── Public API ──
+
+/// Columns `s` takes in a terminal.
+pub fn of(s: &str) -> usize {
+    s.width()
+}
+
+/// Columns `c` takes in a terminal.
+pub fn of_char(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+/// `s` cut to at most `max` columns, ending in "…" if anything was cut.
+pub fn truncate(s: &str, max: usize) -> String {
+    if of(s) <= max {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = of_char(c);
+        if used + w + 1 > max {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    if max > 0 {
+        out.push(ELLIPSIS);
+    }
+    out
+}
+
+/// The end of `s` in at most `max` columns, starting with "…" if anything
+/// was cut.
+pub fn truncate_start(s: &str, max: usize) -> String {
+    if of(s) <= max {
+        return s.to_string();
+    }
+    let mut tail = Vec::new();
+    let mut used = 0;
+    for c in s.chars().rev() {
+        let w = of_char(c);
+        if used + w + 1 > max {
+            break;
+        }
+        tail.push(c);
+        used += w;
+    }
+    let mut out = String::new();
+    if max > 0 {
+        out.push(ELLIPSIS);
+    }
+    out.extend(tail.iter().rev());
+    out
+}
+
+/// `s` followed by spaces up to `width` columns.
+pub fn pad(s: &str, width: usize) -> String {
+    format!("{s}{}", " ".repeat(width.saturating_sub(of(s))))
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wide_characters_count_two_columns() {
+        assert_eq!(of("api"), 3);
+        assert_eq!(of("修正-auth"), 9);
+        assert_eq!(of("🚀ship"), 6);
+        assert_eq!(pad("修正", 6), "修正  ");
+        assert_eq!(pad("too-long", 3), "too-long");
+    }
+
+    #[test]
+    fn test_truncate_never_splits_a_wide_character() {
+        assert_eq!(truncate("api", 3), "api");
+        assert_eq!(truncate("修正したバグ", 7), "修正し…");
+        // "修正し" would leave no room for the ellipsis in 6 columns
+        assert_eq!(truncate("修正したバグ", 6), "修正…");
+        assert_eq!(of(&truncate("🚀🚀🚀", 4)), 3);
+        assert_eq!(truncate_start("~/code/修正", 6), "…/修正");
+        assert_eq!(truncate_start("abcdef", 4), "…def");
+    }
+}
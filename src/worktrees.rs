@@ -0,0 +1,255 @@
+// ── Session worktrees ──
+//
+// `cove <name> --worktree <branch>` runs a session in a git worktree of its
+// own, so parallel sessions on one repository each get a branch and a
+// checkout. The worktree goes under `worktrees.root` (default
+// ~/.cove/worktrees) as `<repo>/<branch>`, slashes in the branch turned into
+// dashes, and the branch is created from HEAD if it doesn't exist. git allows
+// a branch in one worktree only, so if the branch is already checked out
+// somewhere — a killed session's kept worktree, or the main checkout — the
+// session starts there instead.
+//
+// The session's metadata records the worktree when it's under the root: that
+// session owns it. `cove kill` removes an owned worktree with the window,
+// unless it has uncommitted changes (it's kept for `cove du --prune`) or
+// another session recorded it too. The branch always stays. Worktrees
+// elsewhere, like `cove adopt-branch`'s siblings, are left alone.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{config, git, meta, paths};
+
+// ── Types ──
+
+/// Where a session's branch is checked out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkout {
+    pub path: String,
+    /// The worktree was added for this session rather than reused.
+    pub created: bool,
+}
+
+// ── Constants ──
+
+const WORKTREES_DIR: &str = "worktrees";
+
+// ── Helpers ──
+
+/// `<root>/<repo>/<branch>`, with the branch kept to one directory level.
+fn path_in(root: &Path, repo: &str, branch: &str) -> PathBuf {
+    root.join(repo).join(branch.replace('/', "-"))
+}
+
+fn check_branch_name(dir: &Path, branch: &str) -> Result<(), String> {
+    git::output(dir, &["check-ref-format", "--branch", branch], &[0])
+        .map(|_| ())
+        .ok_or_else(|| format!("'{branch}' is not a valid branch name"))
+}
+
+fn checkout_in(root: &Path, dir: &Path, branch: &str) -> Result<Checkout, String> {
+    let main = git::main_worktree(dir)
+        .ok_or_else(|| format!("'{}' is not inside a git repository", dir.display()))?;
+    let main = Path::new(&main);
+    check_branch_name(main, branch)?;
+    if let Some(path) = git::worktree_for_branch(main, branch) {
+        return Ok(Checkout {
+            path,
+            created: false,
+        });
+    }
+
+    let repo = git::repo_name(main).unwrap_or_else(|| "repo".to_string());
+    let path = path_in(root, &repo, branch);
+    if path.exists() {
+        return Err(format!(
+            "{} already exists and doesn't have {branch} checked out",
+            paths::tildify(&path.to_string_lossy())
+        ));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create {}: {e}", parent.display()))?;
+    }
+    git::worktree_add(main, &path, branch)?;
+    // As git lists it, so a later checkout of the branch finds the same path
+    let path = fs::canonicalize(&path).unwrap_or(path);
+    Ok(Checkout {
+        path: path.to_string_lossy().to_string(),
+        created: true,
+    })
+}
+
+/// The worktree session `name` owns under `root`, given every session's
+/// recorded (name, worktree) pair: its own, and no other session's.
+fn owned_in(root: &Path, name: &str, recorded: &[(String, String)]) -> Option<String> {
+    let path = recorded
+        .iter()
+        .find_map(|(n, path)| (n == name).then_some(path))?;
+    let shared = recorded.iter().any(|(n, p)| n != name && p == path);
+    (Path::new(path).starts_with(root) && !shared).then(|| path.clone())
+}
+
+/// Remove worktree `path` unless it has uncommitted changes.
+fn remove(path: &str) -> Result<(), String> {
+    let dir = Path::new(path);
+    if !dir.exists() {
+        return Ok(());
+    }
+    if git::is_dirty(dir) {
+        return Err(format!(
+            "{} has uncommitted changes, so it was kept (cove du --prune removes it once clean)",
+            paths::tildify(path)
+        ));
+    }
+    let main =
+        git::main_worktree(dir).ok_or_else(|| format!("{path}: not a git worktree anymore"))?;
+    git::run(Path::new(&main), &["worktree", "remove", path]).map(|_| ())
+}
+
+// ── Public API ──
+
+/// Where session worktrees go: `worktrees.root`, else ~/.cove/worktrees.
+pub fn root() -> Option<PathBuf> {
+    match config::load().unwrap_or_default().worktrees.root {
+        Some(root) => Some(paths::expand_tilde(&root)),
+        None => paths::cove_path(WORKTREES_DIR),
+    }
+}
+
+/// Check `branch` of the repository containing `dir` out for a session,
+/// reusing the worktree it's already checked out in.
+pub fn checkout(dir: &Path, branch: &str) -> Result<Checkout, String> {
+    let root = root().ok_or("no home directory to keep worktrees in")?;
+    checkout_in(&root, dir, branch)
+}
+
+/// True if `path` is under the worktree root, so a session started in it owns it.
+pub fn is_managed(path: &str) -> bool {
+    root().is_some_and(|root| Path::new(path).starts_with(root))
+}
+
+/// True if killing session `name` will remove its worktree.
+pub fn will_remove(name: &str) -> bool {
+    let Some(root) = root() else {
+        return false;
+    };
+    owned_in(&root, name, &meta::worktrees())
+        .is_some_and(|path| Path::new(&path).exists() && !git::is_dirty(Path::new(&path)))
+}
+
+/// Remove the worktree session `name` owns, if any, and forget it. Err (and
+/// the worktree kept) when it has uncommitted changes.
+pub fn tear_down(name: &str) -> Result<(), String> {
+    let Some(root) = root() else {
+        return Ok(());
+    };
+    let Some(path) = owned_in(&root, name, &meta::worktrees()) else {
+        return Ok(());
+    };
+    remove(&path)?;
+    meta::update(name, |m| m.worktree = None)
+}
+
+/// Undo `checkout` after the session it was for failed to start.
+pub fn discard(checkout: &Checkout) {
+    if checkout.created
+        && let Err(e) = remove(&checkout.path)
+    {
+        log::warn!("worktree: {e}");
+    }
+}
+
+// ── Tests ──
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(dir: &Path) -> PathBuf {
+        let repo = dir.join("api");
+        fs::create_dir_all(&repo).unwrap();
+        git::run(&repo, &["init", "-q", "-b", "main"]).unwrap();
+        let args = ["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-q"];
+        let mut args = args.to_vec();
+        args.extend(["--allow-empty", "-m", "init"]);
+        git::run(&repo, &args).unwrap();
+        fs::canonicalize(repo).unwrap()
+    }
+
+    #[test]
+    fn test_path_in() {
+        assert_eq!(
+            path_in(Path::new("/h/.cove/worktrees"), "api", "feat/login"),
+            PathBuf::from("/h/.cove/worktrees/api/feat-login")
+        );
+    }
+
+    #[test]
+    fn test_checkout_creates_then_reuses() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo(dir.path());
+        let root = dir.path().join("worktrees");
+
+        let first = checkout_in(&root, &repo, "feat/login").unwrap();
+        assert!(first.created);
+        assert_eq!(
+            first.path,
+            root.join("api/feat-login").to_string_lossy().to_string()
+        );
+        assert_eq!(
+            git::branch(Path::new(&first.path)).as_deref(),
+            Some("feat/login")
+        );
+
+        // Asked again, from the worktree or the main checkout, it's reused
+        let again = checkout_in(&root, Path::new(&first.path), "feat/login").unwrap();
+        assert_eq!(again.path, first.path);
+        assert!(!again.created);
+
+        // A branch checked out in the main checkout starts there
+        let main = checkout_in(&root, &repo, "main").unwrap();
+        assert_eq!(main.path, repo.to_string_lossy());
+        assert!(!main.created);
+
+        assert!(checkout_in(&root, &repo, "bad..name").is_err());
+    }
+
+    #[test]
+    fn test_remove_keeps_dirty_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo(dir.path());
+        let root = dir.path().join("worktrees");
+        let wt = checkout_in(&root, &repo, "fix").unwrap();
+
+        fs::write(Path::new(&wt.path).join("notes.txt"), "wip").unwrap();
+        assert!(remove(&wt.path).is_err());
+        assert!(Path::new(&wt.path).exists());
+
+        fs::remove_file(Path::new(&wt.path).join("notes.txt")).unwrap();
+        remove(&wt.path).unwrap();
+        assert!(!Path::new(&wt.path).exists());
+        // The branch outlives its worktree
+        assert!(git::output(&repo, &["rev-parse", "--verify", "fix"], &[0]).is_some());
+        // Already gone is fine
+        remove(&wt.path).unwrap();
+    }
+
+    #[test]
+    fn test_owned_in() {
+        let root = Path::new("/h/.cove/worktrees");
+        let recorded = [
+            ("api".to_string(), "/h/.cove/worktrees/api/fix".to_string()),
+            ("web".to_string(), "/code/web-12".to_string()),
+            ("a".to_string(), "/h/.cove/worktrees/api/shared".to_string()),
+            ("b".to_string(), "/h/.cove/worktrees/api/shared".to_string()),
+        ];
+        assert_eq!(
+            owned_in(root, "api", &recorded).as_deref(),
+            Some("/h/.cove/worktrees/api/fix")
+        );
+        // Outside the root (adopt-branch), shared, or not recorded
+        assert_eq!(owned_in(root, "web", &recorded), None);
+        assert_eq!(owned_in(root, "a", &recorded), None);
+        assert_eq!(owned_in(root, "docs", &recorded), None);
+    }
+}